@@ -1,7 +1,3 @@
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::configure()
-        .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
-        .compile(&["proto/video_analyzer.proto"], &["proto"])?;
+fn main() {
     tauri_build::build();
-    Ok(())
 }
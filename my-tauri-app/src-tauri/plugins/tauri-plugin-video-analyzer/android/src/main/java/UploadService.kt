@@ -0,0 +1,82 @@
+package com.plugin.videoanalyzer
+
+import android.app.NotificationChannel
+import android.app.NotificationManager
+import android.app.Service
+import android.content.Context
+import android.content.Intent
+import android.content.pm.ServiceInfo
+import android.os.Build
+import android.os.IBinder
+import androidx.core.app.NotificationCompat
+import androidx.core.content.ContextCompat
+import java.util.concurrent.ConcurrentHashMap
+import java.util.concurrent.atomic.AtomicInteger
+
+// Foreground service that keeps the process (and the Rust upload tasks in
+// it) alive while the app is in the background. It runs as long as at least
+// one transfer is active and shows an ongoing notification meanwhile.
+class UploadService : Service() {
+    companion object {
+        private const val CHANNEL_ID = "video_analyzer_uploads"
+        private const val NOTIFICATION_ID = 50051
+
+        private val nextId = AtomicInteger(1)
+        private val active = ConcurrentHashMap<Int, String>()
+
+        fun begin(context: Context, name: String): Int {
+            val id = nextId.getAndIncrement()
+            active[id] = name
+            ContextCompat.startForegroundService(context, Intent(context, UploadService::class.java))
+            return id
+        }
+
+        fun end(context: Context, id: Int) {
+            active.remove(id)
+            val intent = Intent(context, UploadService::class.java)
+            if (active.isEmpty()) {
+                context.stopService(intent)
+            } else {
+                // Refresh the notification text
+                ContextCompat.startForegroundService(context, intent)
+            }
+        }
+    }
+
+    override fun onBind(intent: Intent?): IBinder? = null
+
+    override fun onStartCommand(intent: Intent?, flags: Int, startId: Int): Int {
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.O) {
+            val channel = NotificationChannel(
+                CHANNEL_ID,
+                "Video uploads",
+                NotificationManager.IMPORTANCE_LOW
+            )
+            getSystemService(NotificationManager::class.java).createNotificationChannel(channel)
+        }
+
+        val names = active.values.toList()
+        val text = when (names.size) {
+            0 -> "Finishing up"
+            1 -> names[0]
+            else -> "${names.size} videos"
+        }
+        val notification = NotificationCompat.Builder(this, CHANNEL_ID)
+            .setContentTitle("Uploading video")
+            .setContentText(text)
+            .setSmallIcon(android.R.drawable.stat_sys_upload)
+            .setOngoing(true)
+            .build()
+
+        if (Build.VERSION.SDK_INT >= Build.VERSION_CODES.Q) {
+            startForeground(NOTIFICATION_ID, notification, ServiceInfo.FOREGROUND_SERVICE_TYPE_DATA_SYNC)
+        } else {
+            startForeground(NOTIFICATION_ID, notification)
+        }
+        if (names.isEmpty()) {
+            stopSelf()
+        }
+        // Uploads can't resume after the process dies, so don't restart
+        return START_NOT_STICKY
+    }
+}
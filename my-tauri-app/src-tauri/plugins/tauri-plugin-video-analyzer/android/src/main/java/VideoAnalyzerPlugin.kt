@@ -0,0 +1,87 @@
+package com.plugin.videoanalyzer
+
+import android.app.Activity
+import android.net.Uri
+import android.provider.OpenableColumns
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.JSObject
+import app.tauri.plugin.Plugin
+
+@InvokeArg
+class OpenUriArgs {
+    lateinit var uri: String
+}
+
+@InvokeArg
+class BeginTransferArgs {
+    lateinit var name: String
+}
+
+@InvokeArg
+class EndTransferArgs {
+    var id: Int = 0
+}
+
+@TauriPlugin
+class VideoAnalyzerPlugin(private val activity: Activity) : Plugin(activity) {
+    // Scoped storage only lets us read picker results through the
+    // ContentResolver, so open the URI here and hand the descriptor to Rust
+    @Command
+    fun openUri(invoke: Invoke) {
+        val args = invoke.parseArgs(OpenUriArgs::class.java)
+        try {
+            val uri = Uri.parse(args.uri)
+            val resolver = activity.contentResolver
+
+            var name = uri.lastPathSegment ?: "video"
+            var size = -1L
+            resolver.query(
+                uri,
+                arrayOf(OpenableColumns.DISPLAY_NAME, OpenableColumns.SIZE),
+                null,
+                null,
+                null
+            )?.use { cursor ->
+                if (cursor.moveToFirst()) {
+                    if (!cursor.isNull(0)) name = cursor.getString(0)
+                    if (!cursor.isNull(1)) size = cursor.getLong(1)
+                }
+            }
+
+            val pfd = resolver.openFileDescriptor(uri, "r")
+                ?: throw IllegalStateException("No provider for ${args.uri}")
+
+            val ret = JSObject()
+            // Rust owns (and closes) the descriptor from here on
+            ret.put("fd", pfd.detachFd())
+            ret.put("name", name)
+            ret.put("size", size)
+            invoke.resolve(ret)
+        } catch (ex: Exception) {
+            invoke.reject(ex.message ?: "Failed to open ${args.uri}")
+        }
+    }
+
+    @Command
+    fun beginTransfer(invoke: Invoke) {
+        val args = invoke.parseArgs(BeginTransferArgs::class.java)
+        try {
+            val ret = JSObject()
+            ret.put("id", UploadService.begin(activity, args.name))
+            invoke.resolve(ret)
+        } catch (ex: Exception) {
+            // e.g. starting a foreground service from the background on 12+
+            invoke.reject(ex.message ?: "Failed to start upload service")
+        }
+    }
+
+    @Command
+    fun endTransfer(invoke: Invoke) {
+        val args = invoke.parseArgs(EndTransferArgs::class.java)
+        UploadService.end(activity, args.id)
+        invoke.resolve()
+    }
+}
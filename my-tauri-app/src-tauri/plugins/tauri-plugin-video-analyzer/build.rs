@@ -0,0 +1,114 @@
+const COMMANDS: &[&str] = &[
+    "upload_video",
+    "upload_video_from_path",
+    "upload_videos",
+    "cancel_upload",
+    "resume_upload",
+    "get_resumable_uploads",
+    "register_local_video",
+    "import_directory",
+    "open_video_picker",
+    "probe_video",
+    "get_thumbnail",
+    "extract_frame",
+    "upload_audio_only",
+    "extract_subtitles",
+    "verify_video",
+    "reregister_video",
+    "relink_video",
+    "update_analysis_options",
+    "inspect_attachment",
+    "detect_language",
+    "export_local_history",
+    "pin_message",
+    "list_pinned",
+    "backup_workspace",
+    "restore_workspace",
+    "rate_response",
+    "submit_bug_report",
+    "get_changelog",
+    "get_feature_flags",
+    "set_feature_flag",
+    "get_transcript",
+    "get_detections",
+    "get_timeline",
+    "create_share_link",
+    "get_backend_queue",
+    "cancel_processing",
+    "delete_video",
+    "rename_video",
+    "export_frames",
+    "get_media_jobs",
+    "get_operations",
+    "cancel_media_job",
+    "add_bookmark",
+    "remove_bookmark",
+    "search_transcript",
+    "export_subtitles",
+    "pin_for_offline",
+    "unpin_offline",
+    "get_offline_videos",
+    "sync_now",
+    "get_sync_status",
+    "get_sync_conflicts",
+    "resolve_sync_conflict",
+    "get_power_state",
+    "set_power_saving",
+    "get_upload_speed_limit",
+    "set_upload_speed_limit",
+    "process_query",
+    "process_query_range",
+    "process_query_streaming",
+    "set_session_params",
+    "get_session_params",
+    "set_active_session",
+    "get_active_session",
+    "get_incomplete_responses",
+    "retry_incomplete_response",
+    "get_outbox",
+    "discard_outbox_message",
+    "flush_outbox",
+    "get_last_session",
+    "get_chat_history",
+    "resume_session",
+    "clear_chat_history",
+    "get_processing_status",
+    "check_backend_ready",
+    "get_backend_health",
+    "get_backend_info",
+    "get_capabilities",
+    "get_network_simulation",
+    "set_network_simulation",
+    "set_auth_token",
+    "clear_auth_token",
+    "detect_local_backend",
+    "test_connection",
+    "save_initial_profile",
+    "list_backends",
+    "switch_backend",
+    "list_accounts",
+    "create_account",
+    "switch_account",
+    "reload_config",
+    "get_integrity_report",
+    "run_self_test",
+    "get_storage_usage",
+    "cleanup_videos",
+    "get_quota",
+];
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    tonic_build::configure()
+        .type_attribute(".", "#[derive(serde::Serialize, serde::Deserialize)]")
+        // Options come from the UI; let it send only the fields it cares about
+        .type_attribute("video_analyzer.AnalysisOptions", "#[serde(default)]")
+        .type_attribute("video_analyzer.SessionParams", "#[serde(default)]")
+        // Absent from backends that don't set any flags
+        .field_attribute("video_analyzer.ServerInfo.features", "#[serde(default)]")
+        .compile(&["proto/video_analyzer.proto"], &["proto"])?;
+    tauri_plugin::Builder::new(COMMANDS)
+        .android_path("android")
+        .ios_path("ios")
+        .build();
+    Ok(())
+}
@@ -0,0 +1,85 @@
+import Foundation
+import Tauri
+import UIKit
+
+class OpenUriArgs: Decodable {
+  let uri: String
+}
+
+class BeginTransferArgs: Decodable {
+  let name: String
+}
+
+class EndTransferArgs: Decodable {
+  let id: Int
+}
+
+class VideoAnalyzerPlugin: Plugin {
+  private var nextTransferId = 1
+  private var transfers: [Int: UIBackgroundTaskIdentifier] = [:]
+
+  // Files picked outside the sandbox are security scoped; open them while we
+  // have access and hand the descriptor to Rust, which keeps it readable
+  @objc public func openUri(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(OpenUriArgs.self)
+    guard let url = URL(string: args.uri), url.isFileURL else {
+      invoke.reject("Not a file URL: \(args.uri)")
+      return
+    }
+
+    let scoped = url.startAccessingSecurityScopedResource()
+    defer {
+      if scoped {
+        url.stopAccessingSecurityScopedResource()
+      }
+    }
+
+    let fd = open(url.path, O_RDONLY)
+    if fd < 0 {
+      invoke.reject("Failed to open \(url.path): \(String(cString: strerror(errno)))")
+      return
+    }
+
+    let attributes = try? FileManager.default.attributesOfItem(atPath: url.path)
+    let size = (attributes?[.size] as? NSNumber)?.int64Value ?? -1
+    invoke.resolve(["fd": fd, "name": url.lastPathComponent, "size": size])
+  }
+
+  // iOS only grants a few minutes of background time; if it runs out the
+  // task is ended and the upload is suspended with the app
+  @objc public func beginTransfer(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(BeginTransferArgs.self)
+    DispatchQueue.main.async {
+      let id = self.nextTransferId
+      self.nextTransferId += 1
+      let task = UIApplication.shared.beginBackgroundTask(withName: args.name) {
+        self.endTask(id)
+      }
+      if task == .invalid {
+        invoke.reject("Background time is not available")
+        return
+      }
+      self.transfers[id] = task
+      invoke.resolve(["id": id])
+    }
+  }
+
+  @objc public func endTransfer(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(EndTransferArgs.self)
+    DispatchQueue.main.async {
+      self.endTask(args.id)
+      invoke.resolve()
+    }
+  }
+
+  private func endTask(_ id: Int) {
+    if let task = transfers.removeValue(forKey: id) {
+      UIApplication.shared.endBackgroundTask(task)
+    }
+  }
+}
+
+@_cdecl("init_plugin_video_analyzer")
+func initPlugin() -> Plugin {
+  return VideoAnalyzerPlugin()
+}
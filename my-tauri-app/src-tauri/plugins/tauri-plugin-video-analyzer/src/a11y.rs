@@ -0,0 +1,204 @@
+//! Accessibility fields on chat chunks
+//!
+//! Every chunk of an answer, streamed or returned whole, is given the same
+//! fields after post-processing so the frontend can drive screen-reader
+//! announcements without second-guessing the backend's chunk types:
+//!
+//! - `role`: `assistant` for answer text and results, `status` for progress
+//!   (a polite live region), `alert` for errors (an assertive one)
+//! - `agent`: who produced the chunk, `assistant` when the backend doesn't say
+//! - `is_partial`: whether the chunk is a step on the way to the answer
+//!   (messages and progress) rather than the answer itself (a result or an
+//!   error)
+//! - `plain_text`: the chunk as text to read out, without markdown, with a
+//!   structured result described in words when there is no text
+
+use crate::video_analyzer::chat_response::ResponseType;
+use serde_json::Value;
+
+/// Items listed by name in a description before it says "and N more"
+const MAX_LISTED: usize = 5;
+
+/// Add the accessibility fields to each of `chunks`
+pub fn annotate(chunks: &mut [Value]) {
+    for chunk in chunks {
+        annotate_chunk(chunk);
+    }
+}
+
+fn annotate_chunk(chunk: &mut Value) {
+    let kind = chunk["type"]
+        .as_i64()
+        .and_then(|t| ResponseType::try_from(t as i32).ok())
+        .unwrap_or(ResponseType::Message);
+    let role = match kind {
+        ResponseType::Message | ResponseType::Result => "assistant",
+        ResponseType::Progress => "status",
+        ResponseType::Error => "alert",
+    };
+    let agent = chunk["agent_name"]
+        .as_str()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .unwrap_or("assistant")
+        .to_string();
+    let text = strip_markdown(chunk["content"].as_str().unwrap_or_default());
+    let plain_text = if text.is_empty() {
+        describe_result(&chunk["result"]).unwrap_or_default()
+    } else {
+        text
+    };
+
+    chunk["role"] = role.into();
+    chunk["agent"] = agent.into();
+    chunk["is_partial"] = matches!(kind, ResponseType::Message | ResponseType::Progress).into();
+    chunk["plain_text"] = plain_text.into();
+}
+
+/// `text` without markdown emphasis, headings, quotes, bullets, code
+/// fences or link targets, one line per line of text
+fn strip_markdown(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with("```") {
+            continue;
+        }
+        let line = line.trim_start_matches('#').trim_start();
+        let line = line.strip_prefix("> ").unwrap_or(line);
+        let line = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| line.strip_prefix(bullet))
+            .unwrap_or(line);
+        let line = strip_links(line).replace(['*', '`'], "").replace("__", "");
+        let line = line.replace("~~", "");
+        if !line.trim().is_empty() {
+            lines.push(line.trim().to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// `[text](url)` and `![alt](url)` as `text` and `alt`
+fn strip_links(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(open) = rest.find('[') {
+        let Some(close) = rest[open..].find("](").map(|i| open + i) else {
+            break;
+        };
+        let Some(end) = rest[close..].find(')').map(|i| close + i) else {
+            break;
+        };
+        out.push_str(rest[..open].strip_suffix('!').unwrap_or(&rest[..open]));
+        out.push_str(&rest[open + 1..close]);
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A structured `result`, as annotated on RESULT chunks, in words
+fn describe_result(result: &Value) -> Option<String> {
+    let names = |items: &[Value], key: &str| {
+        let mut names: Vec<&str> = Vec::new();
+        for name in items.iter().filter_map(|item| item[key].as_str()) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        let more = names.len().saturating_sub(MAX_LISTED);
+        let mut listed = names[..names.len().min(MAX_LISTED)].join(", ");
+        if more > 0 {
+            listed.push_str(&format!(" and {} more", more));
+        }
+        listed
+    };
+    let count =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+
+    match result["kind"].as_str()? {
+        "detections" => {
+            let items = result["detections"].as_array()?;
+            Some(format!(
+                "{} detected: {}",
+                count(items.len(), "object", "objects"),
+                names(items, "class")
+            ))
+        }
+        "timeline" => {
+            let items = result["timeline"].as_array()?;
+            Some(format!(
+                "Timeline of {}: {}",
+                count(items.len(), "event", "events"),
+                names(items, "label")
+            ))
+        }
+        "transcript" => {
+            let text: Vec<&str> = result["transcript"]
+                .as_array()?
+                .iter()
+                .filter_map(|segment| segment["text"].as_str())
+                .map(str::trim)
+                .collect();
+            Some(text.join(" "))
+        }
+        "agent_run" => {
+            let agents: Vec<&str> = result["selected_agents"]
+                .as_array()?
+                .iter()
+                .filter_map(Value::as_str)
+                .collect();
+            Some(format!("Answered by {}", agents.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_get_accessibility_fields() {
+        let mut chunks = vec![
+            serde_json::json!({
+                "type": ResponseType::Progress as i32,
+                "content": "Analysing **frames**…",
+                "agent_name": "vision",
+            }),
+            serde_json::json!({
+                "type": ResponseType::Result as i32,
+                "content": "",
+                "agent_name": "",
+                "result": {
+                    "kind": "detections",
+                    "detections": [
+                        { "class": "person", "confidence": 0.9, "timestamp": 1.0 },
+                        { "class": "person", "confidence": 0.8, "timestamp": 2.0 },
+                        { "class": "car", "confidence": 0.7, "timestamp": 2.0 },
+                    ],
+                },
+            }),
+            serde_json::json!({
+                "type": ResponseType::Error as i32,
+                "content": "## Failed\n- see [the log](file:///tmp/log) [1]",
+                "agent_name": "system",
+            }),
+        ];
+        annotate(&mut chunks);
+
+        assert_eq!(chunks[0]["role"], "status");
+        assert_eq!(chunks[0]["agent"], "vision");
+        assert_eq!(chunks[0]["is_partial"], true);
+        assert_eq!(chunks[0]["plain_text"], "Analysing frames…");
+
+        assert_eq!(chunks[1]["role"], "assistant");
+        assert_eq!(chunks[1]["agent"], "assistant");
+        assert_eq!(chunks[1]["is_partial"], false);
+        assert_eq!(chunks[1]["plain_text"], "3 objects detected: person, car");
+
+        assert_eq!(chunks[2]["role"], "alert");
+        assert_eq!(chunks[2]["plain_text"], "Failed\nsee the log [1]");
+    }
+}
@@ -0,0 +1,248 @@
+//! Accounts, to work against several backends as different identities
+//!
+//! An account is a backend profile, an auth token and a workspace of its
+//! own: the library, chat history, bookmarks, caches and everything else
+//! [`crate::backup::WORKSPACE_ENTRIES`] lists. They are listed in
+//! `accounts.json` in the app data directory:
+//!
+//! ```json
+//! { "active": "acme",
+//!   "accounts": [{ "id": "acme", "name": "ACME", "created_at": 1700000000.0 }] }
+//! ```
+//!
+//! The default account is always there and keeps its workspace in the app
+//! data directory itself, where it was before there were accounts; any
+//! other account's lives in `accounts/<id>/`. Its token is stored in the
+//! credential store under its id.
+//!
+//! Stores are opened from the active account's workspace once, at start,
+//! so `switch_account` records the choice and restarts the app instead of
+//! swapping them under work in flight. A run only ever sees one account's
+//! files, which keeps history, caches and exports from mixing.
+
+use crate::files;
+use crate::store::{load_json, save_json};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where the accounts are listed, in the app data directory
+pub const ACCOUNTS_FILE: &str = "accounts.json";
+
+/// Directory of the workspaces of all but the default account
+pub const ACCOUNTS_DIR: &str = "accounts";
+
+/// The account every install starts with
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub created_at: f64,
+}
+
+impl Account {
+    fn default_account() -> Self {
+        Self {
+            id: DEFAULT_ACCOUNT.to_string(),
+            name: "Default".to_string(),
+            created_at: 0.0,
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_ACCOUNT
+    }
+
+    /// The credential store key for its token, `None` for the default
+    /// account's
+    pub fn token_key(&self) -> Option<&str> {
+        (!self.is_default()).then_some(self.id.as_str())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AccountsFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    active: Option<String>,
+    #[serde(default)]
+    accounts: Vec<Account>,
+}
+
+/// Managed state: the account this run belongs to, and its workspace
+#[derive(Debug)]
+pub struct Accounts {
+    root: PathBuf,
+    active: Account,
+    workspace: PathBuf,
+}
+
+impl Accounts {
+    /// The active account in `root`, the app data directory; the default
+    /// one if none was chosen or the chosen one is gone
+    pub fn load(root: &Path) -> Self {
+        let file: AccountsFile = load_json(&root.join(ACCOUNTS_FILE));
+        let active = file
+            .active
+            .as_deref()
+            .filter(|id| *id != DEFAULT_ACCOUNT)
+            .and_then(|id| {
+                let found = file.accounts.iter().find(|account| account.id == id);
+                if found.is_none() {
+                    log::warn!("The active account '{}' is gone; using the default one", id);
+                }
+                found.cloned()
+            })
+            .unwrap_or_else(Account::default_account);
+        Self {
+            root: root.to_path_buf(),
+            workspace: workspace_dir(root, &active.id),
+            active,
+        }
+    }
+
+    pub fn active(&self) -> &Account {
+        &self.active
+    }
+
+    /// The active account's workspace, in place of the app data directory
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    /// Every account, the default one first
+    pub fn list(&self) -> Vec<Account> {
+        let mut accounts = vec![Account::default_account()];
+        accounts.extend(self.load_file().accounts);
+        accounts
+    }
+
+    /// Add an account called `name`, with an empty workspace
+    pub fn create(&self, name: &str) -> Result<Account, String> {
+        let name = name.trim();
+        let base = slug(name);
+        if base.is_empty() {
+            return Err(format!(
+                "'{}' can't name an account; use letters or digits",
+                name
+            ));
+        }
+        let mut file = self.load_file();
+        let taken = |id: &str| id == DEFAULT_ACCOUNT || file.accounts.iter().any(|a| a.id == id);
+        let id = (1..)
+            .map(|n| {
+                if n == 1 {
+                    base.clone()
+                } else {
+                    format!("{}-{}", base, n)
+                }
+            })
+            .find(|id| !taken(id))
+            .expect("an unused id");
+        let workspace = workspace_dir(&self.root, &id);
+        std::fs::create_dir_all(&workspace)
+            .map_err(|e| format!("Failed to create {}: {}", workspace.display(), e))?;
+        let account = Account {
+            id,
+            name: name.to_string(),
+            created_at: files::unix_seconds(std::time::SystemTime::now()),
+        };
+        file.accounts.push(account.clone());
+        self.save_file(&file)?;
+        Ok(account)
+    }
+
+    /// The workspace of account `id`, which must exist
+    pub fn workspace_of(&self, id: &str) -> Result<PathBuf, String> {
+        self.find(id)?;
+        Ok(workspace_dir(&self.root, id))
+    }
+
+    /// Make account `id` the one the next start opens
+    pub fn set_active(&self, id: &str) -> Result<Account, String> {
+        let account = self.find(id)?;
+        let mut file = self.load_file();
+        file.active = (!account.is_default()).then(|| account.id.clone());
+        self.save_file(&file)?;
+        Ok(account)
+    }
+
+    fn find(&self, id: &str) -> Result<Account, String> {
+        let id = id.trim();
+        let accounts = self.list();
+        accounts
+            .iter()
+            .find(|account| account.id == id)
+            .cloned()
+            .ok_or_else(|| {
+                let known: Vec<&str> = accounts.iter().map(|a| a.id.as_str()).collect();
+                format!("No account '{}'; known: {}", id, known.join(", "))
+            })
+    }
+
+    fn load_file(&self) -> AccountsFile {
+        load_json(&self.root.join(ACCOUNTS_FILE))
+    }
+
+    fn save_file(&self, file: &AccountsFile) -> Result<(), String> {
+        save_json(&self.root.join(ACCOUNTS_FILE), file)
+    }
+}
+
+/// The workspace of account `id` under `root`, the app data directory
+pub fn workspace_dir(root: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_ACCOUNT {
+        root.to_path_buf()
+    } else {
+        root.join(ACCOUNTS_DIR).join(id)
+    }
+}
+
+/// `name` as an id safe in a path: lowercase ASCII letters and digits,
+/// other runs of characters as one `-`
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_get_workspaces_of_their_own() {
+        let root = std::env::temp_dir().join(format!("accounts-test-{}", std::process::id()));
+        let accounts = Accounts::load(&root);
+        assert!(accounts.active().is_default());
+        assert_eq!(accounts.workspace(), root.as_path());
+
+        let acme = accounts.create(" ACME Corp. ").unwrap();
+        assert_eq!(acme.id, "acme-corp");
+        assert_eq!(accounts.create("acme corp").unwrap().id, "acme-corp-2");
+        assert!(accounts.create("!!!").is_err());
+        assert!(accounts.set_active("globex").is_err());
+
+        accounts.set_active("acme-corp").unwrap();
+        let reopened = Accounts::load(&root);
+        assert_eq!(reopened.active().name, "ACME Corp.");
+        assert_eq!(reopened.active().token_key(), Some("acme-corp"));
+        assert_eq!(
+            reopened.workspace(),
+            root.join("accounts").join("acme-corp")
+        );
+        assert!(reopened.workspace().is_dir());
+        assert_eq!(reopened.list().len(), 3);
+
+        reopened.set_active(DEFAULT_ACCOUNT).unwrap();
+        assert!(Accounts::load(&root).active().is_default());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
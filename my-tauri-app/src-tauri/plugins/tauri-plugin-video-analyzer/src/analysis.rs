@@ -0,0 +1,80 @@
+//! Per-video analysis options
+//!
+//! The options themselves are the proto `AnalysisOptions` message; this
+//! module holds the client-side validation so a typo in the UI is caught
+//! before a long upload rather than when the backend starts analysing.
+
+use crate::video_analyzer::AnalysisOptions;
+
+/// Model tiers the backend understands; empty means "backend default"
+pub const MODEL_TIERS: &[&str] = &["fast", "balanced", "accurate"];
+
+/// Upper bound on frames sampled per second of video
+pub const MAX_FRAME_SAMPLE_RATE: f64 = 30.0;
+
+/// Check options before they are sent to the backend
+pub fn validate(options: &AnalysisOptions) -> Result<(), String> {
+    if !options.model_tier.is_empty() && !MODEL_TIERS.contains(&options.model_tier.as_str()) {
+        return Err(format!(
+            "Unknown model tier '{}': expected one of {}",
+            options.model_tier,
+            MODEL_TIERS.join(", ")
+        ));
+    }
+    if !is_language_tag(&options.language) {
+        return Err(format!(
+            "Invalid language '{}': use 'auto' or a code like 'en' or 'pt-BR'",
+            options.language
+        ));
+    }
+    let rate = options.frame_sample_rate;
+    if !(0.0..=MAX_FRAME_SAMPLE_RATE).contains(&rate) {
+        return Err(format!(
+            "Frame sampling rate must be between 0 and {} frames/s (0 = backend default), got {}",
+            MAX_FRAME_SAMPLE_RATE, rate
+        ));
+    }
+    Ok(())
+}
+
+/// Empty, "auto", or a BCP 47-ish `ll` / `lll` code with an optional region
+fn is_language_tag(tag: &str) -> bool {
+    if tag.is_empty() || tag == "auto" {
+        return true;
+    }
+    let mut parts = tag.split('-');
+    let lang_ok = parts
+        .next()
+        .map(|l| (2..=3).contains(&l.len()) && l.chars().all(|c| c.is_ascii_lowercase()))
+        .unwrap_or(false);
+    let region_ok = match parts.next() {
+        None => true,
+        Some(r) => (2..=4).contains(&r.len()) && r.chars().all(|c| c.is_ascii_alphanumeric()),
+    };
+    lang_ok && region_ok && parts.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert!(validate(&AnalysisOptions::default()).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_values() {
+        let opts = |tier: &str, lang: &str, rate: f64| AnalysisOptions {
+            model_tier: tier.to_string(),
+            language: lang.to_string(),
+            frame_sample_rate: rate,
+            enable_transcription: Some(true),
+        };
+        assert!(validate(&opts("accurate", "pt-BR", 2.0)).is_ok());
+        assert!(validate(&opts("huge", "en", 1.0)).is_err());
+        assert!(validate(&opts("fast", "English", 1.0)).is_err());
+        assert!(validate(&opts("fast", "en", 120.0)).is_err());
+        assert!(validate(&opts("fast", "en", f64::NAN)).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Files attached to chat questions
+//!
+//! A question can carry a few small files (a reference document, a
+//! screenshot, a floor plan). They are read and checked here, then sent
+//! inline in `ChatRequest.attachments`; only their metadata is kept in
+//! the local history.
+
+use crate::config;
+use crate::files;
+use crate::history::AttachmentInfo;
+use crate::video_analyzer::Attachment;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// More than this per question is almost certainly a mistake
+pub const MAX_ATTACHMENTS: usize = 5;
+
+/// Extensions accepted as plain text; `infer` cannot sniff text formats
+const TEXT_EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("json", "application/json"),
+    ("log", "text/plain"),
+];
+
+/// Read, size-check and type-check a file to attach
+pub async fn load_attachment(file_path: &str) -> Result<Attachment, String> {
+    let path = files::normalize_path(file_path)?;
+    let io_path = files::paths::fs_path(&path);
+    let metadata = tokio::fs::metadata(&io_path)
+        .await
+        .map_err(|e| format!("Cannot attach {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!(
+            "Cannot attach {}: not a regular file",
+            path.display()
+        ));
+    }
+
+    let max = config::get().app.max_attachment_bytes;
+    if metadata.len() > max {
+        return Err(format!(
+            "Attachment {} is too large ({} bytes, limit {} bytes)",
+            path.display(),
+            metadata.len(),
+            max
+        ));
+    }
+
+    let data = tokio::fs::read(&io_path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mime_type = detect_mime(&path, &data).ok_or_else(|| {
+        format!(
+            "Unsupported attachment type: {} (images, PDFs, office documents and text files are allowed)",
+            path.display()
+        )
+    })?;
+
+    Ok(Attachment {
+        filename: files::FileName::of(&path).display,
+        mime_type: mime_type.to_string(),
+        size_bytes: data.len() as i64,
+        sha256: hex::encode(Sha256::digest(&data)),
+        data,
+    })
+}
+
+/// Sniff the content first, then fall back to the extension for text files
+pub fn detect_mime(path: &Path, data: &[u8]) -> Option<&'static str> {
+    if let Some(kind) = infer::get(data) {
+        let allowed = matches!(
+            kind.matcher_type(),
+            infer::MatcherType::Image | infer::MatcherType::Doc | infer::MatcherType::Text
+        ) || kind.mime_type() == "application/pdf";
+        return allowed.then(|| kind.mime_type());
+    }
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let (_, mime) = TEXT_EXTENSIONS.iter().find(|(e, _)| *e == ext)?;
+    std::str::from_utf8(data).is_ok().then_some(*mime)
+}
+
+pub fn info(attachment: &Attachment) -> AttachmentInfo {
+    AttachmentInfo {
+        filename: attachment.filename.clone(),
+        mime_type: attachment.mime_type.clone(),
+        size_bytes: attachment.size_bytes.max(0) as u64,
+        sha256: attachment.sha256.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_mime() {
+        let png = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0];
+        assert_eq!(detect_mime(Path::new("shot.bin"), &png), Some("image/png"));
+        assert_eq!(
+            detect_mime(Path::new("plan.pdf"), b"%PDF-1.7\n..."),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            detect_mime(Path::new("notes.MD"), b"# Notes"),
+            Some("text/markdown")
+        );
+        assert_eq!(detect_mime(Path::new("notes.txt"), b"\xff\xfe\x00"), None);
+        assert_eq!(detect_mime(Path::new("tool.exe"), b"MZ\x90\x00"), None);
+    }
+}
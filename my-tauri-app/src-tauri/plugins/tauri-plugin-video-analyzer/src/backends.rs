@@ -0,0 +1,154 @@
+//! Named backends to switch between at runtime
+//!
+//! `backends.json` in the app data directory, or the file BACKENDS_FILE
+//! names, lists them:
+//!
+//! ```json
+//! { "backends": [
+//!     { "name": "local", "server_url": "http://127.0.0.1:50051" },
+//!     { "name": "staging", "server_url": "https://staging.example.com" },
+//!     { "name": "gpu-box", "server_url": "http://10.0.0.5:50051", "description": "A100" }
+//! ] }
+//! ```
+//!
+//! `switch_backend` saves the chosen entry as the [`BackendProfile`] and
+//! drops the gRPC channel, so the next call connects to it without the app
+//! restarting. The file is read on every call, so edits apply straight
+//! away.
+
+use crate::config::{self, BackendProfile};
+use crate::store::load_json;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Where the list lives, in the app data directory
+pub const BACKENDS_FILE: &str = "backends.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedBackend {
+    pub name: String,
+    pub server_url: String,
+    /// Defaults to on when the server URL is `https://`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Shared with the backend to encrypt uploads to it, see
+    /// [`config::GrpcConfig::upload_encryption_key`]; never listed
+    #[serde(default, skip_serializing)]
+    pub upload_encryption_key: Option<String>,
+}
+
+impl NamedBackend {
+    /// The profile to save when switching to this backend
+    pub fn profile(&self) -> BackendProfile {
+        BackendProfile {
+            name: Some(self.name.clone()),
+            server_url: self.server_url.trim().to_string(),
+            tls: self
+                .tls
+                .unwrap_or_else(|| self.server_url.trim().starts_with("https://")),
+            upload_encryption_key: self.upload_encryption_key.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BackendsFile {
+    #[serde(default)]
+    backends: Vec<NamedBackend>,
+}
+
+/// The list to read: BACKENDS_FILE, or `backends.json` in `data_dir`
+pub fn path(data_dir: &Path) -> PathBuf {
+    config::get()
+        .grpc
+        .backends_file
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| data_dir.join(BACKENDS_FILE))
+}
+
+/// The backends listed in `path`, empty if there is no list; entries
+/// without a name, or with one already taken, are skipped
+pub fn load(path: &Path) -> Vec<NamedBackend> {
+    let mut seen = HashSet::new();
+    load_json::<BackendsFile>(path)
+        .backends
+        .into_iter()
+        .filter_map(|mut backend| {
+            backend.name = backend.name.trim().to_string();
+            if backend.name.is_empty() {
+                log::warn!("Skipping a backend without a name in {}", path.display());
+                return None;
+            }
+            if !seen.insert(backend.name.clone()) {
+                log::warn!(
+                    "Skipping the second backend named '{}' in {}",
+                    backend.name,
+                    path.display()
+                );
+                return None;
+            }
+            Some(backend)
+        })
+        .collect()
+}
+
+/// The backend called `name`
+pub fn find<'a>(backends: &'a [NamedBackend], name: &str) -> Result<&'a NamedBackend, String> {
+    let name = name.trim();
+    backends
+        .iter()
+        .find(|backend| backend.name == name)
+        .ok_or_else(|| {
+            let known: Vec<&str> = backends.iter().map(|b| b.name.as_str()).collect();
+            if known.is_empty() {
+                format!("No backend named '{}': no backends are configured", name)
+            } else {
+                format!("No backend named '{}'; known: {}", name, known.join(", "))
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backends_are_loaded_by_unique_name() {
+        let path = std::env::temp_dir().join(format!("backends-test-{}.json", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"{ "backends": [
+                { "name": "local", "server_url": "http://127.0.0.1:50051" },
+                { "name": " gpu-box ", "server_url": "https://gpu.lan:50051" },
+                { "name": "", "server_url": "http://nameless:50051" },
+                { "name": "local", "server_url": "http://other:50051" }
+            ] }"#,
+        )
+        .unwrap();
+        let backends = load(&path);
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(backends.len(), 2);
+        let gpu = find(&backends, "gpu-box").unwrap();
+        assert_eq!(
+            gpu.profile(),
+            BackendProfile {
+                name: Some("gpu-box".to_string()),
+                server_url: "https://gpu.lan:50051".to_string(),
+                tls: true,
+                upload_encryption_key: None,
+            }
+        );
+        assert_eq!(
+            find(&backends, "local").unwrap().server_url,
+            "http://127.0.0.1:50051"
+        );
+        assert!(find(&backends, "staging")
+            .unwrap_err()
+            .contains("local, gpu-box"));
+    }
+}
@@ -0,0 +1,277 @@
+//! Backing up and restoring the whole local workspace
+//!
+//! A backup is a `.tar.gz` of the [`WORKSPACE_ENTRIES`] in the app data
+//! directory, led by a `manifest.json` recording the [`SCHEMA_VERSION`]
+//! it was written with. Restoring checks that version, then unpacks into
+//! `restore-pending/` rather than over the live files, which the managed
+//! stores would write straight back; [`apply_pending_restore`] swaps it in
+//! on the next start, before anything is loaded. SQLite databases are
+//! archived from a snapshot, since the app keeps writing to them.
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Component, Path};
+
+/// Layout of the workspace entries; bump when a store changes format in a
+/// way older builds can't read
+///
+/// 2: the session cache moved to `session_cache.db`, and `outbox.json`
+/// was added
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Files and directories under the app data directory that make up the
+/// workspace: settings, library, history, annotations and caches
+pub const WORKSPACE_ENTRIES: &[&str] = &[
+    "library.json",
+    "uploads.json",
+    "history.json",
+    "feature_flags.json",
+    "bookmarks.json",
+    "power.json",
+    "profile.json",
+    "backends.json",
+    "offline",
+    "detections",
+    "timeline",
+    "transcripts",
+    crate::storage::DB_FILE,
+    "outbox.json",
+];
+
+/// Workspace entries that are SQLite databases
+const SQLITE_ENTRIES: &[&str] = &[crate::storage::DB_FILE];
+
+const MANIFEST: &str = "manifest.json";
+const PENDING_DIR: &str = "restore-pending";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub created_at: f64,
+    /// Workspace entries in the archive
+    pub entries: Vec<String>,
+}
+
+/// Archive the workspace in `data_dir` to `dest`
+pub fn backup(data_dir: &Path, dest: &Path) -> Result<Manifest, String> {
+    let manifest = Manifest {
+        schema_version: SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: crate::files::unix_seconds(std::time::SystemTime::now()),
+        entries: WORKSPACE_ENTRIES
+            .iter()
+            .filter(|entry| data_dir.join(entry).exists())
+            .map(|entry| entry.to_string())
+            .collect(),
+    };
+    let file =
+        File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", dest.display(), e);
+
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize backup manifest: {}", e))?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive
+        .append_data(&mut header, MANIFEST, json.as_slice())
+        .map_err(write_error)?;
+
+    for entry in &manifest.entries {
+        let path = data_dir.join(entry);
+        if path.is_dir() {
+            archive.append_dir_all(entry, &path).map_err(write_error)?;
+        } else if SQLITE_ENTRIES.contains(&entry.as_str()) {
+            let snapshot = std::env::temp_dir().join(format!(
+                "video-analyzer-backup-{}-{}",
+                std::process::id(),
+                entry
+            ));
+            let appended = snapshot_sqlite(&path, &snapshot).and_then(|()| {
+                archive
+                    .append_path_with_name(&snapshot, entry)
+                    .map_err(write_error)
+            });
+            std::fs::remove_file(&snapshot).ok();
+            appended?;
+        } else {
+            archive
+                .append_path_with_name(&path, entry)
+                .map_err(write_error)?;
+        }
+    }
+    archive
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(write_error)?;
+    Ok(manifest)
+}
+
+/// Write a consistent copy of the SQLite database at `path` to `snapshot`
+/// with `VACUUM INTO`, even while another connection is writing to it
+fn snapshot_sqlite(path: &Path, snapshot: &Path) -> Result<(), String> {
+    std::fs::remove_file(snapshot).ok();
+    let db = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    db.execute("VACUUM INTO ?1", [snapshot.to_string_lossy()])
+        .map_err(|e| format!("Failed to snapshot {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+/// Check the backup at `src` and unpack it into `data_dir`'s pending
+/// restore, to be applied on the next start
+pub fn stage_restore(src: &Path, data_dir: &Path) -> Result<Manifest, String> {
+    let file = File::open(src).map_err(|e| format!("Failed to open {}: {}", src.display(), e))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let read_error = |e: std::io::Error| format!("Failed to read backup {}: {}", src.display(), e);
+    let mut entries = archive.entries().map_err(read_error)?;
+
+    let manifest: Manifest = match entries.next() {
+        Some(Ok(entry)) if entry.path().is_ok_and(|p| p == Path::new(MANIFEST)) => {
+            serde_json::from_reader(entry)
+                .map_err(|e| format!("{} has an unreadable manifest: {}", src.display(), e))?
+        }
+        Some(Err(e)) => return Err(read_error(e)),
+        _ => return Err(format!("{} is not a workspace backup", src.display())),
+    };
+    if manifest.schema_version > SCHEMA_VERSION {
+        return Err(format!(
+            "The backup is from a newer version of the app (schema {}, this build reads up to {}); update the app to restore it",
+            manifest.schema_version, SCHEMA_VERSION
+        ));
+    }
+
+    let pending = data_dir.join(PENDING_DIR);
+    if pending.exists() {
+        std::fs::remove_dir_all(&pending)
+            .map_err(|e| format!("Failed to clear {}: {}", pending.display(), e))?;
+    }
+    std::fs::create_dir_all(&pending)
+        .map_err(|e| format!("Failed to create {}: {}", pending.display(), e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(read_error)?;
+        let path = entry.path().map_err(read_error)?.into_owned();
+        let known = match path.components().next() {
+            Some(Component::Normal(top)) => WORKSPACE_ENTRIES.iter().any(|e| top == *e),
+            _ => false,
+        };
+        if !known {
+            log::warn!(
+                "Skipping {} in backup: not a workspace entry",
+                path.display()
+            );
+            continue;
+        }
+        entry.unpack_in(&pending).map_err(read_error)?;
+    }
+    crate::store::save_json(&pending.join(MANIFEST), &manifest)?;
+    Ok(manifest)
+}
+
+/// Swap in a restore staged by [`stage_restore`], if there is one
+///
+/// Must run before the stores are loaded. Entries missing from the backup
+/// are cleared, so the workspace matches it exactly. Safe to run again
+/// after being cut off part way.
+pub fn apply_pending_restore(data_dir: &Path) -> Result<bool, String> {
+    let pending = data_dir.join(PENDING_DIR);
+    if !pending.join(MANIFEST).exists() {
+        return Ok(false);
+    }
+    let manifest: Manifest = std::fs::read_to_string(pending.join(MANIFEST))
+        .map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+        .map_err(|e| format!("Pending restore has an unreadable manifest: {}", e))?;
+    for entry in WORKSPACE_ENTRIES {
+        let staged = pending.join(entry);
+        if !staged.exists() && manifest.entries.iter().any(|e| e == entry) {
+            // Moved in by an earlier, interrupted run
+            continue;
+        }
+        let live = data_dir.join(entry);
+        if live.is_dir() {
+            std::fs::remove_dir_all(&live)
+        } else if live.exists() {
+            std::fs::remove_file(&live)
+        } else {
+            Ok(())
+        }
+        .map_err(|e| format!("Failed to replace {}: {}", live.display(), e))?;
+
+        if staged.exists() {
+            std::fs::rename(&staged, &live)
+                .map_err(|e| format!("Failed to restore {}: {}", live.display(), e))?;
+        }
+    }
+    std::fs::remove_dir_all(&pending)
+        .map_err(|e| format!("Failed to clear {}: {}", pending.display(), e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_replaces_the_workspace_on_next_start() {
+        let root = std::env::temp_dir().join(format!("backup-test-{}", std::process::id()));
+        let source = root.join("source");
+        let target = root.join("target");
+        std::fs::create_dir_all(source.join("transcripts")).unwrap();
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(source.join("library.json"), "{\"a\":1}").unwrap();
+        std::fs::write(source.join("transcripts/v1.json"), "{}").unwrap();
+        std::fs::write(target.join("history.json"), "{}").unwrap();
+
+        let archive = root.join("workspace.tar.gz");
+        let manifest = backup(&source, &archive).unwrap();
+        assert_eq!(manifest.entries, ["library.json", "transcripts"]);
+
+        stage_restore(&archive, &target).unwrap();
+        assert!(!target.join("library.json").exists());
+        assert!(apply_pending_restore(&target).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(target.join("library.json")).unwrap(),
+            "{\"a\":1}"
+        );
+        assert!(target.join("transcripts/v1.json").exists());
+        assert!(!target.join("history.json").exists());
+        assert!(!apply_pending_restore(&target).unwrap());
+        std::fs::remove_dir_all(root).ok();
+    }
+
+    #[test]
+    fn the_session_cache_is_backed_up_while_open() {
+        let root = std::env::temp_dir().join(format!("backup-sqlite-{}", std::process::id()));
+        let source = root.join("source");
+        let target = root.join("target");
+        std::fs::create_dir_all(&target).unwrap();
+        let cache = crate::storage::SessionCache::load(&source);
+        cache
+            .put_session(&crate::responses::SessionInfo {
+                has_session: true,
+                video_id: "v1".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let archive = root.join("workspace.tar.gz");
+        let manifest = backup(&source, &archive).unwrap();
+        assert_eq!(manifest.schema_version, SCHEMA_VERSION);
+        assert_eq!(manifest.entries, [crate::storage::DB_FILE]);
+
+        stage_restore(&archive, &target).unwrap();
+        assert!(apply_pending_restore(&target).unwrap());
+        let restored = crate::storage::SessionCache::load(&target);
+        assert_eq!(restored.last_session().unwrap().0.video_id, "v1");
+        drop(cache);
+        std::fs::remove_dir_all(root).ok();
+    }
+}
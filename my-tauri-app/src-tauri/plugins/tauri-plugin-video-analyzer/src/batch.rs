@@ -0,0 +1,174 @@
+//! Uploading several files as one batch
+//!
+//! `upload_videos` puts the files in a queue that
+//! [`GrpcConfig::upload_concurrency`](crate::config::GrpcConfig::upload_concurrency)
+//! workers (one while [saving power](crate::power)) take from in order,
+//! each file going through the same steps as `upload_video_from_path`
+//! under an upload id of its own, so `upload://progress` and
+//! `cancel_upload` work per file. On top of that:
+//!
+//! - `video-analyzer://batch-file` with a [`BatchFile`] when a file starts, and
+//!   again when it is done or failed
+//! - `video-analyzer://batch-progress` with a [`BatchProgress`] across the batch
+//!   after every chunk of any file
+//!
+//! One file failing doesn't stop the others; the [`BatchSummary`] lists
+//! how each went.
+
+use crate::error::AppError;
+use crate::responses::UploadResult;
+use serde::Serialize;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileState {
+    Queued,
+    Uploading,
+    Done,
+    Failed,
+}
+
+/// One file of a batch and how it went
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchFile {
+    pub batch_id: String,
+    /// Position in the paths given
+    pub index: usize,
+    pub path: String,
+    pub upload_id: String,
+    pub state: FileState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<UploadResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+}
+
+/// Progress across a whole batch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BatchProgress {
+    pub batch_id: String,
+    pub files_total: usize,
+    pub files_done: usize,
+    pub files_failed: usize,
+    pub bytes_sent: u64,
+    /// Sizes of the files as found when the batch started
+    pub total_bytes: u64,
+    pub percent: Option<f64>,
+}
+
+/// What `upload_videos` answers with
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary {
+    pub batch_id: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub bytes_sent: u64,
+    /// In the order the paths were given
+    pub files: Vec<BatchFile>,
+}
+
+/// Bytes and outcomes of a running batch, shared by its workers
+pub struct BatchTracker {
+    batch_id: String,
+    sizes: Vec<u64>,
+    state: Mutex<TrackerState>,
+}
+
+struct TrackerState {
+    sent: Vec<u64>,
+    done: usize,
+    failed: usize,
+}
+
+impl BatchTracker {
+    /// A batch of files of `sizes` bytes, 0 where unknown
+    pub fn new(batch_id: String, sizes: Vec<u64>) -> Self {
+        Self {
+            batch_id,
+            state: Mutex::new(TrackerState {
+                sent: vec![0; sizes.len()],
+                done: 0,
+                failed: 0,
+            }),
+            sizes,
+        }
+    }
+
+    /// File `index` has sent `bytes` so far
+    pub fn sent(&self, index: usize, bytes: u64) -> BatchProgress {
+        let mut state = self.state.lock().unwrap();
+        state.sent[index] = bytes;
+        self.progress(&state)
+    }
+
+    /// File `index` is finished; a failed one no longer counts its bytes
+    pub fn finish(&self, index: usize, ok: bool) -> BatchProgress {
+        let mut state = self.state.lock().unwrap();
+        if ok {
+            state.done += 1;
+            state.sent[index] = state.sent[index].max(self.sizes[index]);
+        } else {
+            state.failed += 1;
+            state.sent[index] = 0;
+        }
+        self.progress(&state)
+    }
+
+    /// The outcome of the batch once every file in `files` is finished
+    pub fn summary(&self, files: Vec<BatchFile>) -> BatchSummary {
+        let progress = self.progress(&self.state.lock().unwrap());
+        BatchSummary {
+            batch_id: self.batch_id.clone(),
+            total: files.len(),
+            succeeded: progress.files_done,
+            failed: progress.files_failed,
+            bytes_sent: progress.bytes_sent,
+            files,
+        }
+    }
+
+    fn progress(&self, state: &TrackerState) -> BatchProgress {
+        let total_bytes: u64 = self.sizes.iter().sum();
+        let bytes_sent: u64 = state.sent.iter().sum();
+        let files_total = self.sizes.len();
+        let percent = if total_bytes > 0 {
+            Some((bytes_sent as f64 / total_bytes as f64 * 100.0).min(100.0))
+        } else if files_total > 0 {
+            Some((state.done + state.failed) as f64 / files_total as f64 * 100.0)
+        } else {
+            None
+        };
+        BatchProgress {
+            batch_id: self.batch_id.clone(),
+            files_total,
+            files_done: state.done,
+            files_failed: state.failed,
+            bytes_sent,
+            total_bytes,
+            percent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_adds_up_across_files() {
+        let tracker = BatchTracker::new("b".to_string(), vec![100, 300]);
+        assert_eq!(tracker.sent(0, 50).bytes_sent, 50);
+        let progress = tracker.sent(1, 150);
+        assert_eq!(progress.bytes_sent, 200);
+        assert_eq!(progress.percent, Some(50.0));
+
+        // Done counts the whole file, failed none of it
+        let progress = tracker.finish(0, true);
+        assert_eq!((progress.files_done, progress.bytes_sent), (1, 250));
+        let progress = tracker.finish(1, false);
+        assert_eq!((progress.files_failed, progress.bytes_sent), (1, 100));
+        assert_eq!(progress.percent, Some(25.0));
+    }
+}
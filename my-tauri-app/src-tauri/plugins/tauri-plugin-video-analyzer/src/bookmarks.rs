@@ -0,0 +1,219 @@
+//! User bookmarks and annotations on a video's timeline
+//!
+//! Purely client-side, persisted in `bookmarks.json` in the app data
+//! directory; `get_timeline` merges them with the backend's scenes and
+//! events. A video can't have two bookmarks at the same moment or span.
+
+use crate::query;
+use crate::store::{load_json, save_json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// A marked moment (or span, with `end_ms`) and the user's note on it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: String,
+    pub start_ms: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_ms: Option<i64>,
+    pub label: String,
+    #[serde(default)]
+    pub note: String,
+    pub created_at: f64,
+}
+
+/// Managed state: every video's bookmarks
+pub struct Bookmarks {
+    path: PathBuf,
+    videos: Mutex<HashMap<String, Vec<Bookmark>>>,
+}
+
+impl Bookmarks {
+    pub fn load(path: PathBuf) -> Self {
+        let videos = load_json(&path);
+        Self {
+            path,
+            videos: Mutex::new(videos),
+        }
+    }
+
+    /// `video_id`'s bookmarks in time order
+    pub fn list(&self, video_id: &str) -> Vec<Bookmark> {
+        let mut bookmarks = self
+            .videos
+            .lock()
+            .unwrap()
+            .get(video_id)
+            .cloned()
+            .unwrap_or_default();
+        bookmarks.sort_by_key(|b| b.start_ms);
+        bookmarks
+    }
+
+    /// Why `bookmark` can't be added to `video_id`, if it can't: a
+    /// negative position, a span that ends before it starts, or one already
+    /// bookmarked
+    pub fn check(&self, video_id: &str, bookmark: &Bookmark) -> Result<(), String> {
+        match bookmark.end_ms {
+            Some(end_ms) => {
+                query::parse_range(bookmark.start_ms, end_ms)?;
+            }
+            None if bookmark.start_ms < 0 => {
+                return Err(format!(
+                    "Bookmark position must not be negative, got {} ms",
+                    bookmark.start_ms
+                ))
+            }
+            None => {}
+        }
+        let videos = self.videos.lock().unwrap();
+        let taken = videos.get(video_id).is_some_and(|bookmarks| {
+            bookmarks
+                .iter()
+                .any(|b| b.start_ms == bookmark.start_ms && b.end_ms == bookmark.end_ms)
+        });
+        if taken {
+            return Err(format!(
+                "There already is a bookmark at {} ms on {}",
+                bookmark.start_ms, video_id
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn add(&self, video_id: &str, bookmark: Bookmark) -> Result<(), String> {
+        let mut videos = self.videos.lock().unwrap();
+        videos
+            .entry(video_id.to_string())
+            .or_default()
+            .push(bookmark);
+        save_json(&self.path, &*videos)
+    }
+
+    /// Returns whether there was such a bookmark
+    pub fn remove(&self, video_id: &str, id: &str) -> Result<bool, String> {
+        let mut videos = self.videos.lock().unwrap();
+        let Some(bookmarks) = videos.get_mut(video_id) else {
+            return Ok(false);
+        };
+        let before = bookmarks.len();
+        bookmarks.retain(|b| b.id != id);
+        if bookmarks.len() == before {
+            return Ok(false);
+        }
+        if bookmarks.is_empty() {
+            videos.remove(video_id);
+        }
+        save_json(&self.path, &*videos)?;
+        Ok(true)
+    }
+
+    /// Drop every bookmark of `video_id`; returns how many there were
+    pub fn remove_video(&self, video_id: &str) -> Result<usize, String> {
+        let mut videos = self.videos.lock().unwrap();
+        let Some(removed) = videos.remove(video_id) else {
+            return Ok(0);
+        };
+        save_json(&self.path, &*videos)?;
+        Ok(removed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(id: &str, start_ms: i64, end_ms: Option<i64>) -> Bookmark {
+        Bookmark {
+            id: id.to_string(),
+            start_ms,
+            end_ms,
+            label: format!("Bookmark {}", id),
+            note: String::new(),
+            created_at: 0.0,
+        }
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bookmarks-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("bookmarks.json")
+    }
+
+    #[test]
+    fn test_add_list_remove() {
+        let path = test_path("crud");
+        let bookmarks = Bookmarks::load(path.clone());
+        bookmarks.add("v1", bookmark("b", 5_000, None)).unwrap();
+        bookmarks
+            .add("v1", bookmark("a", 1_000, Some(2_000)))
+            .unwrap();
+        bookmarks.add("v2", bookmark("c", 0, None)).unwrap();
+
+        let ids = |video_id| -> Vec<String> {
+            bookmarks.list(video_id).into_iter().map(|b| b.id).collect()
+        };
+        assert_eq!(ids("v1"), ["a", "b"]);
+        assert_eq!(ids("v2"), ["c"]);
+
+        assert!(bookmarks.remove("v1", "a").unwrap());
+        assert!(!bookmarks.remove("v1", "a").unwrap());
+        assert!(!bookmarks.remove("v3", "a").unwrap());
+        assert_eq!(ids("v1"), ["b"]);
+        assert_eq!(bookmarks.remove_video("v2").unwrap(), 1);
+        assert!(ids("v2").is_empty());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_bookmarks_survive_a_reload() {
+        let path = test_path("reload");
+        let bookmarks = Bookmarks::load(path.clone());
+        bookmarks.add("v1", bookmark("a", 1_000, None)).unwrap();
+        bookmarks
+            .add("v1", bookmark("b", 3_000, Some(4_000)))
+            .unwrap();
+        bookmarks.remove("v1", "a").unwrap();
+
+        let reloaded = Bookmarks::load(path.clone());
+        let listed = reloaded.list("v1");
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "b");
+        assert_eq!(listed[0].end_ms, Some(4_000));
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_duplicate_and_out_of_range_are_refused() {
+        let path = test_path("check");
+        let bookmarks = Bookmarks::load(path.clone());
+        bookmarks.add("v1", bookmark("a", 1_000, None)).unwrap();
+        bookmarks
+            .add("v1", bookmark("b", 1_000, Some(2_000)))
+            .unwrap();
+
+        assert!(bookmarks
+            .check("v1", &bookmark("c", 1_000, None))
+            .unwrap_err()
+            .contains("already"));
+        assert!(bookmarks
+            .check("v1", &bookmark("c", 1_000, Some(2_000)))
+            .is_err());
+        // Same moment on another video, or another span from the same start
+        assert!(bookmarks.check("v2", &bookmark("c", 1_000, None)).is_ok());
+        assert!(bookmarks
+            .check("v1", &bookmark("c", 1_000, Some(3_000)))
+            .is_ok());
+
+        assert!(bookmarks.check("v1", &bookmark("c", -1, None)).is_err());
+        assert!(bookmarks
+            .check("v1", &bookmark("c", 5_000, Some(4_000)))
+            .is_err());
+        assert!(bookmarks
+            .check("v1", &bookmark("c", 5_000, Some(5_000)))
+            .is_err());
+        std::fs::remove_dir_all(path.parent().unwrap()).ok();
+    }
+}
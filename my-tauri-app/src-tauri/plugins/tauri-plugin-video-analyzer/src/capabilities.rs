@@ -0,0 +1,160 @@
+//! Which optional subsystems this run can use
+//!
+//! Some features lean on things outside the app that may not be there:
+//! ffmpeg for frame exports, whisper for language detection, the OS
+//! credential store for remembering the auth token, a writable workspace
+//! for the local stores. Without one, its features fail with an
+//! explanation when used, and the rest of the app carries on.
+//!
+//! Each is probed once in the background at startup ([`spawn_probe`]) so
+//! `get_capabilities` can tell the UI up front which commands to disable,
+//! and why, instead of the user finding out by clicking.
+
+use crate::media;
+use crate::transport::auth;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::sync::Mutex;
+
+/// One optional subsystem and whether it can be used
+#[derive(Debug, Clone, Serialize)]
+pub struct Capability {
+    pub name: &'static str,
+    pub available: bool,
+    /// What was found, e.g. the ffmpeg version, when available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Why not, for the UI to show, when unavailable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Commands that fail, or do less, without it
+    pub commands: &'static [&'static str],
+}
+
+impl Capability {
+    fn new(
+        name: &'static str,
+        commands: &'static [&'static str],
+        probed: Result<Option<String>, String>,
+    ) -> Self {
+        let (detail, reason) = match probed {
+            Ok(detail) => (detail, None),
+            Err(reason) => (None, Some(reason)),
+        };
+        Self {
+            name,
+            available: reason.is_none(),
+            detail,
+            reason,
+            commands,
+        }
+    }
+}
+
+/// Managed state: the last probe of every subsystem
+pub struct Capabilities {
+    workspace: PathBuf,
+    probed: Mutex<Option<Vec<Capability>>>,
+}
+
+impl Capabilities {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self {
+            workspace,
+            probed: Mutex::new(None),
+        }
+    }
+
+    /// Every subsystem, probing them if that hasn't happened yet or
+    /// `refresh` asks to; ffmpeg is only ever searched for once a run
+    pub async fn get(&self, refresh: bool) -> Vec<Capability> {
+        let mut probed = self.probed.lock().await;
+        if refresh || probed.is_none() {
+            *probed = Some(probe(&self.workspace).await);
+        }
+        probed.clone().unwrap_or_default()
+    }
+}
+
+async fn probe(workspace: &Path) -> Vec<Capability> {
+    let ffmpeg = media::locator::ffmpeg()
+        .await
+        .map(|found| Some(format!("{} ({:?})", found.version, found.source)));
+    let whisper = if media::language::available() {
+        Ok(None)
+    } else {
+        Err("whisper-cli or its model is not installed; set WHISPER_PATH and WHISPER_MODEL".into())
+    };
+    let credential_store = tokio::task::spawn_blocking(auth::credential_store_status)
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()))
+        .map(|()| None);
+    vec![
+        Capability::new("ffmpeg", &["export_frames"], ffmpeg),
+        Capability::new("language_detection", &["detect_language"], whisper),
+        Capability::new(
+            "credential_store",
+            &["set_auth_token", "save_initial_profile", "create_account"],
+            credential_store.map_err(|e| format!("{}; tokens only apply until the app quits", e)),
+        ),
+        Capability::new(
+            "local_storage",
+            &[
+                "register_local_video",
+                "pin_message",
+                "add_bookmark",
+                "pin_for_offline",
+                "backup_workspace",
+                "get_last_session",
+                "get_chat_history",
+            ],
+            writable(workspace).map(|()| Some(workspace.display().to_string())),
+        ),
+    ]
+}
+
+/// Whether files can be created in `dir`
+fn writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(".write-probe");
+    std::fs::create_dir_all(dir)
+        .and_then(|()| std::fs::write(&probe, b""))
+        .and_then(|()| std::fs::remove_file(&probe))
+        .map_err(|e| format!("{} is not writable: {}", dir.display(), e))
+}
+
+/// Probe everything in the background at startup, logging what's missing
+pub fn spawn_probe<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        for capability in app.state::<Capabilities>().get(false).await {
+            if let Some(reason) = &capability.reason {
+                log::warn!(
+                    "{} unavailable, degrading {}: {}",
+                    capability.name,
+                    capability.commands.join(", "),
+                    reason
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwritable_workspace_is_reported() {
+        let dir = std::env::temp_dir().join(format!("capabilities-test-{}", std::process::id()));
+        assert!(writable(&dir).is_ok());
+        std::fs::remove_dir_all(&dir).ok();
+
+        let file = std::env::temp_dir().join(format!("capabilities-file-{}", std::process::id()));
+        std::fs::write(&file, b"").unwrap();
+        let probed = writable(&file.join("sub")).map(|()| None);
+        let capability = Capability::new("local_storage", &[], probed);
+        std::fs::remove_file(&file).ok();
+        assert!(!capability.available);
+        assert!(capability.reason.unwrap().contains("is not writable"));
+    }
+}
@@ -0,0 +1,145 @@
+//! "What's new" data
+//!
+//! The host app hands its changelog (`CHANGELOG.md`, Keep a Changelog
+//! format) to the plugin at init, embedded in the binary, and it is parsed
+//! into structured entries. Release notes published
+//! next to the backend can optionally be merged in, so notes can be
+//! corrected after a release without rebuilding the app.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version label used by Keep a Changelog for not-yet-released changes
+pub const UNRELEASED: &str = "Unreleased";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangelogEntry {
+    pub version: String,
+    #[serde(default)]
+    pub date: Option<String>,
+    /// Section name ("added", "fixed", ...) to bullet points
+    #[serde(default)]
+    pub sections: BTreeMap<String, Vec<String>>,
+}
+
+impl ChangelogEntry {
+    pub fn is_unreleased(&self) -> bool {
+        self.version.eq_ignore_ascii_case(UNRELEASED)
+    }
+}
+
+/// Parse Keep a Changelog markdown, newest entry first as written
+pub fn parse(markdown: &str) -> Vec<ChangelogEntry> {
+    let mut entries: Vec<ChangelogEntry> = Vec::new();
+    let mut section: Option<String> = None;
+
+    for line in markdown.lines().map(str::trim_end) {
+        if let Some(heading) = line.strip_prefix("## ") {
+            // "[1.2.0] - 2025-10-01" or "[Unreleased]"
+            let (version, date) = match heading.split_once(" - ") {
+                Some((v, d)) => (v, Some(d.trim().to_string())),
+                None => (heading, None),
+            };
+            entries.push(ChangelogEntry {
+                version: version
+                    .trim()
+                    .trim_matches(|c| c == '[' || c == ']')
+                    .to_string(),
+                date,
+                sections: BTreeMap::new(),
+            });
+            section = None;
+        } else if let Some(name) = line.strip_prefix("### ") {
+            section = Some(name.trim().to_ascii_lowercase());
+        } else if let Some(item) = line.strip_prefix("- ") {
+            if let (Some(entry), Some(name)) = (entries.last_mut(), &section) {
+                entry
+                    .sections
+                    .entry(name.clone())
+                    .or_default()
+                    .push(item.trim().to_string());
+            }
+        }
+    }
+    entries
+}
+
+/// Numeric components of a version like "1.2.3"; `None` for labels such as "Unreleased"
+pub fn version_key(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim_start_matches('v')
+        .split(['.', '-', '+'])
+        .take(3)
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Released entries newer than `since` and no newer than `current`
+pub fn newer_than<'a>(
+    entries: &'a [ChangelogEntry],
+    since: Option<&str>,
+    current: &str,
+) -> Vec<&'a ChangelogEntry> {
+    let since = since.and_then(version_key);
+    let current = version_key(current);
+    entries
+        .iter()
+        .filter(|e| match version_key(&e.version) {
+            None => false,
+            Some(v) => {
+                since.as_ref().is_none_or(|s| &v > s) && current.as_ref().is_none_or(|c| &v <= c)
+            }
+        })
+        .collect()
+}
+
+/// Overlay remote entries on local ones; remote wins for the same version
+pub fn merge(local: Vec<ChangelogEntry>, remote: Vec<ChangelogEntry>) -> Vec<ChangelogEntry> {
+    let mut merged: Vec<ChangelogEntry> = local
+        .into_iter()
+        .filter(|l| !remote.iter().any(|r| r.version == l.version))
+        .collect();
+    merged.extend(remote);
+    // Unreleased first, then newest version first
+    merged.sort_by_key(|e| std::cmp::Reverse(version_key(&e.version)));
+    merged.sort_by_key(|e| !e.is_unreleased());
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "# Changelog\n\n## [Unreleased]\n### Added\n- Thing\n\n\
+        ## [1.1.0] - 2025-11-01\n### Fixed\n- Bug\n- Other bug\n\n\
+        ## [1.0.0] - 2025-10-01\n### Added\n- First\n";
+
+    #[test]
+    fn test_parse_sections() {
+        let entries = parse(SAMPLE);
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_unreleased());
+        assert_eq!(entries[1].version, "1.1.0");
+        assert_eq!(entries[1].date.as_deref(), Some("2025-11-01"));
+        assert_eq!(entries[1].sections["fixed"], vec!["Bug", "Other bug"]);
+    }
+
+    #[test]
+    fn test_newer_than() {
+        let entries = parse(SAMPLE);
+        let versions =
+            |v: Vec<&ChangelogEntry>| v.iter().map(|e| e.version.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            versions(newer_than(&entries, Some("1.0.0"), "1.1.0")),
+            ["1.1.0"]
+        );
+        assert_eq!(versions(newer_than(&entries, None, "1.0.0")), ["1.0.0"]);
+        assert!(newer_than(&entries, Some("1.1.0"), "1.1.0").is_empty());
+    }
+
+    #[test]
+    fn test_app_changelog_parses() {
+        let entries = parse(include_str!("../../../../CHANGELOG.md"));
+        assert!(entries.iter().any(|e| version_key(&e.version).is_some()));
+    }
+}
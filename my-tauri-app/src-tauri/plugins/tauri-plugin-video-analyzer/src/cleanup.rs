@@ -0,0 +1,67 @@
+//! Criteria for bulk remote cleanup
+//!
+//! `cleanup_videos` takes ages like "30d" from the UI; the backend wants
+//! absolute cutoffs, so they are converted here against the local clock.
+
+use std::time::{Duration, SystemTime};
+
+/// Parse an age such as "90s", "45m", "12h", "30d" or "2w"; bare numbers are seconds
+pub fn parse_age(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (digits, unit) = input.split_at(split);
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("Invalid age '{}': expected e.g. 30d, 12h, 45m", input))?;
+    let unit_secs = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        other => {
+            return Err(format!(
+                "Invalid age unit '{}' in '{}': use s, m, h, d or w",
+                other, input
+            ))
+        }
+    };
+    Ok(Duration::from_secs(value.saturating_mul(unit_secs)))
+}
+
+/// Unix-seconds cutoff for an optional age; 0 means "no constraint" on the wire
+pub fn cutoff(age: Option<&str>, now: SystemTime) -> Result<f64, String> {
+    match age.filter(|a| !a.trim().is_empty()) {
+        None => Ok(0.0),
+        Some(a) => {
+            let at = now
+                .checked_sub(parse_age(a)?)
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            Ok(crate::files::unix_seconds(at))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_age("45m").unwrap(), Duration::from_secs(45 * 60));
+        assert_eq!(parse_age(" 30d ").unwrap(), Duration::from_secs(30 * 86400));
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("3y").is_err());
+    }
+
+    #[test]
+    fn test_cutoff() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(10_000);
+        assert_eq!(cutoff(None, now).unwrap(), 0.0);
+        assert_eq!(cutoff(Some(""), now).unwrap(), 0.0);
+        assert_eq!(cutoff(Some("1h"), now).unwrap(), 10_000.0 - 3600.0);
+    }
+}
@@ -0,0 +1,5521 @@
+//! Tauri commands exposed by the plugin
+//!
+//! Each command is a thin gRPC call to the Python backend, plus whatever
+//! client-side checks and bookkeeping it needs. Commands return
+//! `Result<Value, AppError>` so the frontend gets plain JSON or a
+//! structured error, see [`crate::error`].
+
+use crate::accounts::Accounts;
+use crate::capabilities::Capabilities;
+use crate::config;
+use crate::error::AppError;
+use crate::history::{
+    AttachmentInfo, Feedback, IncompleteResponse, LocalHistory, LocalMessage, Rating,
+};
+use crate::library::{self, Library, LibraryEntry};
+use crate::offline::{self, OfflineCache};
+use crate::outbox::{self, Outbox, OutboxMessage};
+use crate::query_cache::{self, QueryCache};
+use crate::quota::QuotaTracker;
+use crate::responses::{self, ChatChunk, ChatHistory, SessionInfo, UploadResult};
+use crate::session::ActiveSession;
+use crate::shutdown::Shutdown;
+use crate::storage::SessionCache;
+use crate::transport::tls::CertificateError;
+use crate::transport::{ChatStream, Transport};
+use crate::uploads::{self, ManifestEntry, UploadGuard, UploadManifest, UploadRegistry};
+use crate::video_analyzer::offline_asset;
+use crate::video_analyzer::{
+    self, AddCaptionsRequest, AnalysisOptions, BackendQueueRequest, CancelProcessingRequest,
+    Caption, ChatRequest, ChatResponse, CleanupRequest, ClearHistoryRequest, DeleteVideoRequest,
+    DetectionsRequest, FeedbackRequest, GetHistoryRequest, OfflineAssetsRequest,
+    RegisterVideoRequest, ResumeRequest, SessionParams, ShareLinkRequest, TimeRange,
+    TimelineRequest, TranscriptRequest, UpdateAnalysisOptionsRequest, UpdateVideoMetadataRequest,
+    VideoChunk,
+};
+use crate::{
+    a11y, analysis, attachments, backends, backup, batch, bookmarks, changelog, cleanup, compat,
+    deletion, detections, diagnostics, files, flags, health, http, import, integrity, media,
+    onboarding, operations, params, picker, postprocess, power, query, results, selftest, sequence,
+    staleness, store, sync, throttle, thumbnails, timeline, transcript, PluginOptions,
+    VideoAnalyzerExt,
+};
+use log::{info, warn};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tokio_util::sync::CancellationToken;
+
+/// How long `check_backend_ready` waits unless told otherwise
+pub(crate) const READY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A deadline picked by the frontend for one call, in seconds; unset or 0
+/// keeps the configured one
+fn timeout_override(timeout_secs: Option<u64>) -> Option<Duration> {
+    timeout_secs
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Read a chat stream to the end, continuing after `received` when the
+/// request is a retry
+///
+/// `on_chunk` sees everything received so far each time new chunks are
+/// ready. Returns the chunks and, if the stream broke off, why.
+async fn collect_chat_stream(
+    mut stream: ChatStream,
+    received: Vec<ChatResponse>,
+    cancel: &CancellationToken,
+    mut on_chunk: impl FnMut(&[ChatResponse]),
+) -> (Vec<ChatResponse>, Option<String>) {
+    // Resumed or retried streams can repeat or reorder chunks
+    let (mut order, mut responses) = match sequence::Reorder::resume(&received) {
+        Some(order) => (order, received),
+        None => (sequence::Reorder::default(), Vec::new()),
+    };
+
+    let interrupted = loop {
+        let next = tokio::select! {
+            next = stream.next() => next,
+            _ = cancel.cancelled() => Some(Err(tonic::Status::cancelled("The app is shutting down"))),
+        };
+        match next {
+            Some(Ok(message)) => {
+                let ready = order.push(message);
+                if !ready.is_empty() {
+                    responses.extend(ready);
+                    on_chunk(&responses);
+                }
+            }
+            None => {
+                // Normal end of stream
+                responses.extend(order.finish());
+                break None;
+            }
+            Some(Err(e)) => {
+                let err_msg = format!(
+                    "Stream interrupted: {}. Some partial results may be missing.",
+                    e
+                );
+                warn!("gRPC chat stream error: {}", err_msg);
+                responses.extend(order.finish());
+                break Some(err_msg);
+            }
+        }
+    };
+    if order.duplicates() > 0 {
+        info!("Dropped {} duplicate chat chunk(s)", order.duplicates());
+    }
+    (responses, interrupted)
+}
+
+/// The JSON array the frontend gets for a chat stream
+///
+/// An interrupted stream ends in an ERROR chunk, so the frontend still
+/// receives an array.
+fn chat_value(
+    mut responses: Vec<ChatResponse>,
+    interrupted: Option<String>,
+) -> Result<Value, String> {
+    use video_analyzer::chat_response::ResponseType;
+
+    if let Some(err_msg) = interrupted {
+        responses.push(ChatResponse {
+            r#type: ResponseType::Error as i32,
+            content: err_msg,
+            agent_name: "system".to_string(),
+            result_json: String::new(),
+            sequence: 0,
+        });
+    }
+    serde_json::to_value(responses).map_err(|e| format!("Failed to serialize chat stream: {}", e))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn upload_video<R: Runtime>(
+    app: AppHandle<R>,
+    transport: tauri::State<'_, Transport>,
+    quota: tauri::State<'_, QuotaTracker>,
+    filename: String,
+    video_data: Vec<u8>,
+    analysis_options: Option<AnalysisOptions>,
+) -> Result<UploadResult, AppError> {
+    println!("🦀 Rust: upload_video called with {}", filename);
+    println!("🦀 Rust: video_data size: {}", video_data.len());
+    quota
+        .check(video_data.len() as u64)
+        .map_err(AppError::validation)?;
+    if let Some(options) = &analysis_options {
+        analysis::validate(options).map_err(AppError::validation)?;
+    }
+
+    let _transfer = app.video_analyzer().begin_transfer(&filename);
+
+    // Stream chunks via channel to avoid allocating all chunks upfront
+    let video_len = video_data.len();
+    let chunk_size = config::get().grpc.video_chunk_size;
+    let (tx, rx) = tokio::sync::mpsc::channel::<VideoChunk>(8);
+
+    let fname = filename.clone();
+    let file_sha256 = uploads::sha256_hex(&video_data);
+    let sent_sha256 = file_sha256.clone();
+    tokio::spawn(async move {
+        let mut analysis_options = analysis_options;
+        let mut idx: i32 = 0;
+        let mut offset: usize = 0;
+        while offset < video_data.len() {
+            let end = (offset + chunk_size).min(video_data.len());
+            let slice = &video_data[offset..end];
+            throttle::acquire(slice.len()).await;
+            let chunk = VideoChunk {
+                data: slice.to_vec(),
+                filename: fname.clone(),
+                chunk_index: idx,
+                analysis_options: analysis_options.take(),
+                idempotency_key: String::new(),
+                offset: offset as i64,
+                language_hint: String::new(),
+                encryption: None,
+                sha256: uploads::sha256_hex(slice),
+                file_sha256: if end == video_data.len() {
+                    file_sha256.clone()
+                } else {
+                    String::new()
+                },
+                stream_index: 0,
+                stream_count: 0,
+                file_size: 0,
+                source_sha256: String::new(),
+            };
+            offset = end;
+            idx += 1;
+            if tx.send(chunk).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let request_stream = ReceiverStream::new(rx);
+
+    let inner = transport
+        .upload_video(request_stream)
+        .await
+        .map_err(AppError::from)?;
+    info!(
+        "upload_video response: success={}, file_id={}",
+        inner.success, inner.file_id
+    );
+    uploads::verify_upload_digest(&filename, &inner, &sent_sha256)?;
+    if inner.success {
+        quota.adjust(video_len as i64);
+    }
+    Ok(inner.into())
+}
+
+/// Upload a file from disk, streaming it in chunks
+///
+/// Progress is emitted as `upload://progress` events carrying the
+/// `upload_id` (returned in the response); pass `upload_id` to know it
+/// before the first event, or to `cancel_upload` it. If the upload is cut
+/// off, even by a restart, `resume_upload` continues it.
+///
+/// A file the backend already has, by content, isn't sent again: that
+/// video's session is resumed instead and `already_uploaded` is set.
+///
+/// A file still being written is refused unless `force` is set; a file
+/// that changes while it is sent comes back with `changed_during_upload`
+/// and isn't added to the library.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn upload_video_from_path<R: Runtime>(
+    app: AppHandle<R>,
+    file_path: String,
+    force: Option<bool>,
+    analysis_options: Option<AnalysisOptions>,
+    upload_id: Option<String>,
+) -> Result<UploadResult, AppError> {
+    println!("🦀 Rust: upload_video_from_path called with {}", file_path);
+    let upload_id = upload_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    upload_path(
+        &app,
+        file_path,
+        force,
+        analysis_options,
+        false,
+        upload_id,
+        |_| {},
+    )
+    .await
+}
+
+/// Upload only the audio of a file from disk, extracted locally as AAC,
+/// for analysis that works from the transcript; far smaller than the
+/// whole video
+///
+/// Otherwise the same as `upload_video_from_path`: the same progress
+/// events, and `cancel_upload` and `resume_upload` work alike. The audio
+/// is always sent, and never matched against the whole video.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn upload_audio_only<R: Runtime>(
+    app: AppHandle<R>,
+    path: String,
+    analysis_options: Option<AnalysisOptions>,
+    upload_id: Option<String>,
+) -> Result<UploadResult, AppError> {
+    println!("🦀 Rust: upload_audio_only called with {}", path);
+    let upload_id = upload_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    upload_path(&app, path, None, analysis_options, true, upload_id, |_| {}).await
+}
+
+/// Upload several files from disk through one queue, see [`batch`]
+///
+/// Up to `concurrency` files (default UPLOAD_CONCURRENCY) are sent at a
+/// time, one while saving power, started in the order given; `force` and
+/// `analysis_options` apply to each as in `upload_video_from_path`. Pass
+/// `batch_id` to subscribe to the batch's events before the first one.
+/// Returns a [`batch::BatchSummary`] once every file is done or has failed.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn upload_videos<R: Runtime>(
+    app: AppHandle<R>,
+    paths: Vec<String>,
+    force: Option<bool>,
+    analysis_options: Option<AnalysisOptions>,
+    concurrency: Option<usize>,
+    batch_id: Option<String>,
+) -> Result<batch::BatchSummary, AppError> {
+    println!("🦀 Rust: upload_videos called with {} files", paths.len());
+    if paths.is_empty() {
+        return Err(AppError::validation("No paths to upload".to_string()));
+    }
+    if let Some(options) = &analysis_options {
+        analysis::validate(options).map_err(AppError::validation)?;
+    }
+    let batch_id = batch_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let workers = concurrency
+        .unwrap_or(config::get().grpc.upload_concurrency)
+        .clamp(1, paths.len());
+    let total = paths.len();
+
+    let sizes = paths
+        .iter()
+        .map(|path| {
+            files::normalize_path(path)
+                .ok()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map_or(0, |metadata| metadata.len())
+        })
+        .collect();
+    let tracker = Arc::new(batch::BatchTracker::new(batch_id.clone(), sizes));
+    let queue: Vec<batch::BatchFile> = paths
+        .into_iter()
+        .enumerate()
+        .map(|(index, path)| batch::BatchFile {
+            batch_id: batch_id.clone(),
+            index,
+            path,
+            upload_id: uuid::Uuid::new_v4().to_string(),
+            state: batch::FileState::Queued,
+            result: None,
+            error: None,
+        })
+        .collect();
+    let queue = Arc::new(std::sync::Mutex::new(queue));
+    let next = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    info!("upload_videos: batch {} with {} workers", batch_id, workers);
+
+    let handles: Vec<_> = (0..workers)
+        .map(|worker| {
+            let app = app.clone();
+            let tracker = tracker.clone();
+            let queue = queue.clone();
+            let next = next.clone();
+            let analysis_options = analysis_options.clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    // While saving power only the first worker takes files;
+                    // the others wait for it to end or the queue to run out
+                    while worker > 0 && app.state::<power::PowerState>().is_saving() {
+                        if next.load(std::sync::atomic::Ordering::SeqCst) >= total {
+                            return;
+                        }
+                        tokio::time::sleep(power::RESUME_CHECK).await;
+                    }
+                    let index = next.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    let Some(mut file) = queue.lock().unwrap().get(index).cloned() else {
+                        break;
+                    };
+                    file.state = batch::FileState::Uploading;
+                    app.emit(&crate::event_name("batch-file"), &file).ok();
+                    let on_sent = {
+                        let app = app.clone();
+                        let tracker = tracker.clone();
+                        move |bytes_sent| {
+                            let progress = tracker.sent(index, bytes_sent);
+                            app.emit(&crate::event_name("batch-progress"), progress)
+                                .ok();
+                        }
+                    };
+                    let uploaded = upload_path(
+                        &app,
+                        file.path.clone(),
+                        force,
+                        analysis_options.clone(),
+                        false,
+                        file.upload_id.clone(),
+                        on_sent,
+                    )
+                    .await;
+                    match uploaded {
+                        Ok(result) => {
+                            file.state = if result.success {
+                                batch::FileState::Done
+                            } else {
+                                batch::FileState::Failed
+                            };
+                            file.result = Some(result);
+                        }
+                        Err(e) => {
+                            warn!("upload_videos: {} failed: {}", file.path, e);
+                            file.state = batch::FileState::Failed;
+                            file.error = Some(e);
+                        }
+                    }
+                    let progress = tracker.finish(index, file.state == batch::FileState::Done);
+                    app.emit(&crate::event_name("batch-file"), &file).ok();
+                    app.emit(&crate::event_name("batch-progress"), progress)
+                        .ok();
+                    queue.lock().unwrap()[index] = file;
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        if let Err(e) = handle.await {
+            warn!("upload_videos: a worker stopped: {}", e);
+        }
+    }
+
+    let files = std::mem::take(&mut *queue.lock().unwrap());
+    let summary = tracker.summary(files);
+    info!(
+        "upload_videos: batch {} done, {} succeeded, {} failed",
+        summary.batch_id, summary.succeeded, summary.failed
+    );
+    Ok(summary)
+}
+
+/// The body of `upload_video_from_path`, shared with `upload_videos`;
+/// `on_sent` gets the bytes sent so far after each chunk
+async fn upload_path<R: Runtime>(
+    app: &AppHandle<R>,
+    file_path: String,
+    force: Option<bool>,
+    analysis_options: Option<AnalysisOptions>,
+    audio_only: bool,
+    upload_id: String,
+    on_sent: impl Fn(u64) + Send + Sync + 'static,
+) -> Result<UploadResult, AppError> {
+    let transport = app.state::<Transport>();
+    let library = app.state::<Library>();
+    let quota = app.state::<QuotaTracker>();
+    let manifest = app.state::<UploadManifest>();
+    let uploads = app.state::<UploadRegistry>();
+    let upload = uploads.register(&upload_id).map_err(AppError::validation)?;
+
+    if let Some(options) = &analysis_options {
+        analysis::validate(options).map_err(AppError::validation)?;
+    }
+
+    if files::uri::is_platform_uri(&file_path) {
+        return upload_platform_uri(
+            app,
+            &transport,
+            &quota,
+            &file_path,
+            force,
+            analysis_options,
+            &upload_id,
+            upload.token(),
+        )
+        .await;
+    }
+
+    let original_path = file_path;
+    let file_path = files::normalize_path(&original_path).map_err(AppError::validation)?;
+    let resolved = files::resolve_path(&file_path).map_err(AppError::validation)?;
+    let name = files::FileName::of(&resolved.canonical);
+    if name.is_lossy {
+        warn!(
+            "upload_video_from_path: file name is not valid UTF-8, uploading as {:?}",
+            name.display
+        );
+    }
+    let mut filename = name.display.clone();
+
+    // Don't upload a file that is still being written (e.g. an active OBS
+    // recording) unless the caller explicitly forces it, in which case
+    // there is no point waiting for it to settle
+    let mut warnings: Vec<String> = Vec::new();
+    let in_use = if force.unwrap_or(false) {
+        files::in_use::check_in_use(&resolved.canonical).await
+    } else {
+        files::in_use::wait_until_idle(&resolved.canonical).await
+    };
+    let (in_use, mut before) = in_use.map_err(AppError::storage)?;
+    if in_use.is_busy() {
+        let msg = format!(
+            "{} appears to still be in use: {}",
+            file_path.display(),
+            in_use.describe()
+        );
+        if !force.unwrap_or(false) {
+            return Err(AppError::validation(format!(
+                "{}. Retry when it is finished, or pass force=true",
+                msg
+            )));
+        }
+        warn!("upload_video_from_path: {} (forced)", msg);
+        warnings.push(msg);
+    }
+
+    // Refuse before streaming anything if the last known quota can't fit it
+    if let Err(msg) = quota.check(before.size_bytes) {
+        if !force.unwrap_or(false) {
+            return Err(AppError::validation(format!(
+                "{}. Free up space with cleanup_videos, or pass force=true",
+                msg
+            )));
+        }
+        warn!("upload_video_from_path: {} (forced)", msg);
+        warnings.push(msg);
+    }
+
+    // Don't spend an upload on a file the backend can't analyse, unless
+    // re-encoding it fixes that; without ffprobe this is left to the backend
+    let config = config::get();
+    let media_config = &config.media;
+    let target = media::transcode::Target {
+        video_kbps: media_config.transcode_video_kbps,
+        audio_kbps: media_config.transcode_audio_kbps,
+    };
+    let mut conversion = audio_only.then_some(media::transcode::Conversion::AudioOnly);
+    let mut duration_secs = None;
+    match media::probe::probe(&resolved.canonical).await {
+        Ok(probe) if audio_only => {
+            if probe.audio.is_empty() {
+                return Err(AppError::validation(format!(
+                    "{} has no audio track to upload",
+                    file_path.display()
+                )));
+            }
+            duration_secs = probe.duration_secs;
+        }
+        Ok(probe) => {
+            let mode = if app
+                .state::<flags::FeatureFlags>()
+                .enabled("local_transcode")
+            {
+                media::transcode::Mode::parse(&media_config.transcode)
+            } else {
+                media::transcode::Mode::Off
+            };
+            conversion = media::transcode::reason(mode, &probe, target)
+                .map(media::transcode::Conversion::Video);
+            duration_secs = probe.duration_secs;
+            if !probe.supported && conversion.is_none() {
+                let msg = format!("{}: {}", file_path.display(), probe.problems.join("; "));
+                if !force.unwrap_or(false) {
+                    return Err(AppError::validation(format!(
+                        "{}. Convert it first, or pass force=true",
+                        msg
+                    )));
+                }
+                warn!("upload_video_from_path: {} (forced)", msg);
+                warnings.push(msg);
+            }
+        }
+        Err(e) => info!("upload_video_from_path: not probed: {}", e),
+    }
+
+    let mut stages = vec!["hash"];
+    if let Some(conversion) = &conversion {
+        stages.push(conversion.stage());
+    }
+    stages.push("upload");
+    let operation = operations::begin(app, &upload_id, "upload", &stages);
+    operation.start("hash");
+    // The backend deduplicates on the file as picked, so ask before
+    // spending a conversion on it
+    let picked_sha256 = operation
+        .check(files::validation::hash_file(&resolved.canonical).await)
+        .map_err(AppError::storage)?;
+    let dedup_sha256 = dedup_sha256(conversion.as_ref(), &picked_sha256).map(str::to_string);
+    let existing = match &dedup_sha256 {
+        Some(sha256) => existing_upload(&transport, sha256, before.size_bytes).await,
+        None => None,
+    };
+    if let Some(existing) = existing {
+        info!(
+            "upload_video_from_path: {} is already on the backend as {}; not sending it",
+            file_path.display(),
+            existing.file_id
+        );
+        let resumed = transport
+            .resume_session(ResumeRequest {
+                video_id: existing.file_id.clone(),
+            })
+            .await;
+        match resumed.map(SessionInfo::from) {
+            Ok(session) if session.has_session => {
+                if let Err(e) = app.state::<SessionCache>().put_session(&session) {
+                    warn!(
+                        "upload_video_from_path: failed to mirror the session: {}",
+                        e
+                    );
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!(
+                "upload_video_from_path: couldn't resume the session of {}: {}",
+                existing.file_id, e
+            ),
+        }
+        let mut entry = uploaded_entry(
+            &existing.file_id,
+            original_path,
+            resolved,
+            before.size_bytes,
+            before.modified,
+            analysis_options,
+        );
+        if !existing.display_name.is_empty() {
+            entry.display_name = existing.display_name.clone();
+        }
+        library.upsert(entry).map_err(AppError::storage)?;
+        operation.finish();
+        return Ok(UploadResult {
+            file_id: existing.file_id,
+            success: true,
+            message: "Already uploaded; resumed its session".to_string(),
+            upload_id: Some(upload_id),
+            warnings,
+            tracked: true,
+            already_uploaded: true,
+            ..Default::default()
+        });
+    }
+    // What is streamed: the file itself, or its converted copy
+    let mut source = resolved.canonical.clone();
+    let mut transcoded_path = None;
+    // Fingerprint of what is streamed, so a resumed upload can't splice in
+    // a changed one
+    let mut sha256 = picked_sha256.clone();
+    let mut source_sha256 = String::new();
+    if let Some(conversion) = conversion {
+        info!(
+            "upload_video_from_path: converting {} ({:?})",
+            file_path.display(),
+            conversion
+        );
+        operation.start(conversion.stage());
+        let dest = media::transcode::scratch_path(&upload_id, &conversion);
+        let progress = operation.progress_fn(conversion.stage());
+        let pipeline = app.state::<media::pipeline::Pipeline>();
+        let power_state = app.state::<power::PowerState>();
+        if power_state.is_saving() {
+            info!(
+                "upload_video_from_path: holding the conversion of {} until power saving ends",
+                file_path.display()
+            );
+        }
+        let encode = async {
+            // The heaviest work done locally; it waits out power saving
+            while power_state.is_saving() {
+                tokio::time::sleep(power::RESUME_CHECK).await;
+            }
+            media::transcode::convert(
+                &pipeline,
+                &conversion,
+                &source,
+                &dest,
+                target,
+                move |processed_ms| {
+                    if let Some(secs) = duration_secs.filter(|secs| *secs > 0.0) {
+                        progress((processed_ms as f64 / (secs * 1000.0)).min(1.0));
+                    }
+                },
+            )
+            .await
+        };
+        let token = upload.token();
+        // Dropping the encode kills its ffmpeg, or stops it waiting
+        let encoded = tokio::select! {
+            encoded = encode => encoded.map_err(AppError::internal),
+            _ = token.cancelled() => Err(AppError::cancelled("Upload cancelled")),
+        };
+        if let Err(e) = operation.check(encoded) {
+            std::fs::remove_file(&dest).ok();
+            return Err(e);
+        }
+        before = files::in_use::FileSnapshot::take(&dest).map_err(AppError::storage)?;
+        if let media::transcode::Conversion::Video(reason) = &conversion {
+            warnings.push(format!(
+                "Re-encoded to H.264/AAC before uploading: {}",
+                reason
+            ));
+        }
+        filename = format!(
+            "{}.{}",
+            std::path::Path::new(&filename)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            conversion.extension()
+        );
+        sha256 = match operation.check(files::validation::hash_file(&dest).await) {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                std::fs::remove_file(&dest).ok();
+                return Err(AppError::storage(e));
+            }
+        };
+        source_sha256 = dedup_sha256.unwrap_or_default();
+        transcoded_path = Some(dest.to_string_lossy().into_owned());
+        source = dest;
+    }
+
+    let detected_language = media::language::hint_for(
+        &app.state::<media::pipeline::Pipeline>(),
+        &resolved.canonical,
+        analysis_options.as_ref(),
+    )
+    .await;
+    let mut chunks = UploadChunks::new(filename.clone(), analysis_options.clone());
+    if let Some(detected) = &detected_language {
+        chunks.language_hint = detected.code.clone();
+    }
+    chunks.source_sha256 = source_sha256;
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    manifest
+        .insert(ManifestEntry {
+            upload_id: upload_id.clone(),
+            path: original_path.clone(),
+            filename: filename.clone(),
+            size_bytes: before.size_bytes,
+            sha256: sha256.clone(),
+            chunk_size: chunks.chunk_size,
+            idempotency_key: chunks.idempotency_key.clone(),
+            analysis_options: analysis_options.clone(),
+            transcoded_path: transcoded_path.clone(),
+            chunks_sent: 0,
+            started_at: now,
+            updated_at: now,
+        })
+        .map_err(AppError::storage)?;
+
+    let _transfer = app.video_analyzer().begin_transfer(&filename);
+    operation.start("upload");
+    let emit = emit_progress(app, &upload_id, Some(before.size_bytes));
+    let progress = track_stage(
+        &operation,
+        "upload",
+        before.size_bytes,
+        move |chunk_index, bytes_sent| {
+            emit(chunk_index, bytes_sent);
+            on_sent(bytes_sent);
+        },
+    );
+    let grpc = &config.grpc;
+    let mut parallel = grpc.upload_streams > 1
+        && before.size_bytes >= grpc.parallel_upload_min_bytes
+        && app
+            .state::<flags::FeatureFlags>()
+            .enabled("parallel_uploads");
+    // Without the part fields the backend would store each part as a video
+    if parallel
+        && !app
+            .state::<compat::BackendInfo>()
+            .reports_proto(compat::PARALLEL_UPLOAD_PROTO_VERSION)
+    {
+        info!(
+            "upload_video_from_path: the backend doesn't report protocol {}; sending {} over one stream",
+            compat::PARALLEL_UPLOAD_PROTO_VERSION,
+            filename
+        );
+        parallel = false;
+    }
+    let streamed = if parallel {
+        stream_upload_parallel(
+            &transport,
+            &source,
+            before.size_bytes,
+            &sha256,
+            chunks,
+            grpc.upload_streams,
+            progress,
+            upload.token(),
+        )
+        .await
+    } else {
+        match tokio::fs::File::open(files::paths::fs_path(&source)).await {
+            Ok(file) => stream_upload(&transport, file, chunks, progress, upload.token()).await,
+            Err(e) => Err(AppError::io(
+                format!("Failed to open file {}", source.display()),
+                e,
+            )),
+        }
+    };
+    // The re-encoded copy is only kept while the upload can be resumed
+    if streamed.is_ok() || upload.is_cancelled() {
+        if let Some(transcoded) = &transcoded_path {
+            std::fs::remove_file(transcoded).ok();
+        }
+    }
+    let (inner, bytes_sent) =
+        operation.check(streamed.map_err(|e| upload_failed(&manifest, &upload, e)))?;
+    manifest.remove(&upload_id).map_err(AppError::storage)?;
+    info!(
+        "upload_video_from_path response: success={}, file_id={}",
+        inner.success, inner.file_id
+    );
+    if inner.success {
+        operation.finish();
+    } else {
+        operation.fail(&inner.message);
+    }
+
+    // Re-verify: if the file grew or was touched while streaming, the
+    // backend copy may be truncated or inconsistent
+    let after = match &transcoded_path {
+        // Nothing else writes the re-encoded copy, and it is gone by now
+        Some(_) => Some(before),
+        None => files::in_use::FileSnapshot::take(&resolved.canonical).ok(),
+    };
+    let changed_during_upload = after != Some(before) || bytes_sent != before.size_bytes;
+    if changed_during_upload {
+        let msg = format!(
+            "{} changed during upload (sent {} of {} bytes); the uploaded copy may be incomplete, \
+             so it isn't added to the library",
+            file_path.display(),
+            bytes_sent,
+            after.map(|a| a.size_bytes).unwrap_or(before.size_bytes)
+        );
+        warn!("upload_video_from_path: {}", msg);
+        warnings.push(msg);
+    }
+
+    if inner.success {
+        quota.adjust(bytes_sent as i64);
+    }
+    // A possibly incomplete copy isn't worth tracking against the file
+    let tracked = inner.success && !inner.file_id.is_empty() && !changed_during_upload;
+    if tracked {
+        let mut entry = uploaded_entry(
+            &inner.file_id,
+            original_path,
+            resolved,
+            bytes_sent,
+            before.modified,
+            analysis_options,
+        );
+        entry.detected_language = detected_language.clone();
+        library.upsert(entry).map_err(AppError::storage)?;
+    }
+
+    Ok(UploadResult {
+        upload_id: Some(upload_id),
+        warnings,
+        detected_language,
+        changed_during_upload,
+        tracked,
+        ..inner.into()
+    })
+}
+
+/// The digest of the picked file the backend matches this upload by, both
+/// when asking [`existing_upload`] and as the sent copy's `source_sha256`;
+/// `None` when what is sent isn't that video, e.g. only its audio
+fn dedup_sha256<'a>(
+    conversion: Option<&media::transcode::Conversion>,
+    picked_sha256: &'a str,
+) -> Option<&'a str> {
+    conversion
+        .is_none_or(media::transcode::Conversion::keeps_the_video)
+        .then_some(picked_sha256)
+}
+
+/// The video the backend already has with this content, if any
+///
+/// Only saves time, so a backend without CheckFileExists, or one that
+/// can't answer, just gets the file uploaded.
+async fn existing_upload(
+    transport: &Transport,
+    sha256: &str,
+    size_bytes: u64,
+) -> Option<video_analyzer::CheckFileExistsResponse> {
+    let request = video_analyzer::CheckFileExistsRequest {
+        sha256: sha256.to_string(),
+        size_bytes: size_bytes as i64,
+    };
+    match transport.check_file_exists(request).await {
+        Ok(found) if found.exists && !found.file_id.is_empty() => Some(found),
+        Ok(_) => None,
+        Err(e) if e.code() == tonic::Code::Unimplemented => None,
+        Err(e) => {
+            warn!("CheckFileExists failed, uploading anyway: {}", e);
+            None
+        }
+    }
+}
+
+/// Library entry for a file just uploaded as a copy
+fn uploaded_entry(
+    file_id: &str,
+    original_path: String,
+    resolved: files::volume::ResolvedPath,
+    size_bytes: u64,
+    modified: Option<std::time::SystemTime>,
+    analysis_options: Option<AnalysisOptions>,
+) -> LibraryEntry {
+    let name = files::FileName::of(&resolved.canonical);
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    LibraryEntry {
+        file_id: file_id.to_string(),
+        display_name: name.display,
+        original_path,
+        canonical_path: resolved.canonical.to_string_lossy().to_string(),
+        canonical_path_raw: files::names::raw_path_if_lossy(&resolved.canonical),
+        name_raw: name.raw_hex,
+        volume_id: resolved.volume.id,
+        volume_kind: resolved.volume.kind,
+        reference_only: false,
+        size_bytes,
+        sha256: String::new(),
+        modified_at: modified.map(files::unix_seconds).unwrap_or(0.0),
+        container: String::new(),
+        registered_at: now,
+        status: library::FileStatus::Ok,
+        last_verified_at: now,
+        analysis_options,
+        detected_language: None,
+    }
+}
+
+/// The error for an upload from a path that didn't finish
+///
+/// A cancelled upload is forgotten; any other, including one paused for
+/// a shutdown, can still be resumed, and the error says how.
+fn upload_failed(manifest: &UploadManifest, upload: &UploadGuard<'_>, error: AppError) -> AppError {
+    if upload.is_cancelled() {
+        manifest.remove(upload.upload_id()).ok();
+        return error;
+    }
+    if upload.is_paused() {
+        return AppError::cancelled(format!(
+            "Upload paused as the app shuts down. Resume it with resume_upload(\"{}\")",
+            upload.upload_id()
+        ));
+    }
+    error.map_message(|message| {
+        format!(
+            "{}. Resume it with resume_upload(\"{}\")",
+            message,
+            upload.upload_id()
+        )
+    })
+}
+
+/// Continue an `upload_video_from_path` that was cut off, even by a restart
+///
+/// The backend says which chunks it already has and only the rest are
+/// sent, after checking the file hasn't changed since. Progress events and
+/// `cancel_upload` work as for the original upload.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn resume_upload<R: Runtime>(
+    app: AppHandle<R>,
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    quota: tauri::State<'_, QuotaTracker>,
+    uploads: tauri::State<'_, UploadRegistry>,
+    manifest: tauri::State<'_, UploadManifest>,
+    upload_id: String,
+) -> Result<UploadResult, AppError> {
+    println!("🦀 Rust: resume_upload called for {}", upload_id);
+    let entry = manifest
+        .get(&upload_id)
+        .ok_or_else(|| format!("No unfinished upload {}", upload_id))
+        .map_err(AppError::validation)?;
+    let upload = uploads.register(&upload_id).map_err(AppError::validation)?;
+    let operation = operations::begin(&app, &upload_id, "upload", &["verify", "upload"]);
+    operation.start("verify");
+
+    let file_path = files::normalize_path(&entry.path).map_err(AppError::validation)?;
+    let resolved = files::resolve_path(&file_path).map_err(AppError::validation)?;
+    // A transcoded upload carries on with its re-encoded copy
+    let source = match &entry.transcoded_path {
+        Some(transcoded) => std::path::PathBuf::from(transcoded),
+        None => resolved.canonical.clone(),
+    };
+    let snapshot = files::in_use::FileSnapshot::take(&source).map_err(AppError::storage)?;
+    if snapshot.size_bytes != entry.size_bytes
+        || operation
+            .check(files::validation::hash_file(&source).await)
+            .map_err(AppError::storage)?
+            != entry.sha256
+    {
+        manifest.remove(&upload_id).map_err(AppError::storage)?;
+        return operation
+            .check(Err(format!(
+                "{} changed since the upload started; upload it again",
+                file_path.display()
+            )))
+            .map_err(AppError::validation);
+    }
+
+    let offer = transport
+        .negotiate_upload_offset(video_analyzer::UploadOffsetRequest {
+            idempotency_key: entry.idempotency_key.clone(),
+            sha256: entry.sha256.clone(),
+            size_bytes: entry.size_bytes as i64,
+            chunk_size: entry.chunk_size as i64,
+        })
+        .await
+        .map_err(|e| AppError::rpc("NegotiateUploadOffset", e));
+    let offer = operation.check(offer)?;
+    let (inner, resumed_from_chunk) = if offer.completed {
+        let inner = offer.response.unwrap_or_default();
+        uploads::verify_upload_digest(&entry.filename, &inner, &entry.sha256)?;
+        (inner, None)
+    } else {
+        let chunks = UploadChunks {
+            filename: entry.filename.clone(),
+            analysis_options: entry.analysis_options.clone(),
+            // The backend kept these from the first attempt
+            language_hint: String::new(),
+            source_sha256: String::new(),
+            idempotency_key: entry.idempotency_key.clone(),
+            chunk_size: entry.chunk_size,
+            first_chunk: i32::try_from(offer.next_chunk_index.max(0))
+                .map_err(|_| format!("Backend asked for chunk {}", offer.next_chunk_index))
+                .map_err(AppError::internal)?,
+            part: None,
+        };
+        info!(
+            "Resuming upload {} at chunk {} of {}",
+            upload_id, chunks.first_chunk, entry.filename
+        );
+        let mut file = tokio::fs::File::open(files::paths::fs_path(&source))
+            .await
+            .map_err(|e| AppError::io(format!("Failed to open file {}", source.display()), e))?;
+        file.seek(std::io::SeekFrom::Start(chunks.offset()))
+            .await
+            .map_err(|e| AppError::io(format!("Failed to read {}", file_path.display()), e))?;
+        let _transfer = app.video_analyzer().begin_transfer(&entry.filename);
+        operation.start("upload");
+        let progress = track_stage(
+            &operation,
+            "upload",
+            entry.size_bytes,
+            emit_progress(&app, &upload_id, Some(entry.size_bytes)),
+        );
+        let first_chunk = chunks.first_chunk;
+        let (inner, _) = operation.check(
+            stream_upload(&transport, file, chunks, progress, upload.token())
+                .await
+                .map_err(|e| upload_failed(&manifest, &upload, e)),
+        )?;
+        (inner, Some(first_chunk))
+    };
+    manifest.remove(&upload_id).map_err(AppError::storage)?;
+    if let Some(transcoded) = &entry.transcoded_path {
+        std::fs::remove_file(transcoded).ok();
+    }
+    info!(
+        "resume_upload response: success={}, file_id={}",
+        inner.success, inner.file_id
+    );
+    if inner.success {
+        operation.finish();
+    } else {
+        operation.fail(&inner.message);
+    }
+
+    if inner.success {
+        quota.adjust(entry.size_bytes as i64);
+    }
+    let tracked = inner.success && !inner.file_id.is_empty();
+    if tracked {
+        library
+            .upsert(uploaded_entry(
+                &inner.file_id,
+                entry.path,
+                resolved,
+                entry.size_bytes,
+                snapshot.modified,
+                entry.analysis_options,
+            ))
+            .map_err(AppError::storage)?;
+    }
+
+    Ok(UploadResult {
+        upload_id: Some(upload_id),
+        tracked,
+        resumed_from_chunk,
+        ..inner.into()
+    })
+}
+
+/// Uploads from a path that were cut off and can be resumed, oldest first
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_resumable_uploads(
+    manifest: tauri::State<'_, UploadManifest>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_resumable_uploads called");
+    serde_json::to_value(manifest.list())
+        .map_err(|e| AppError::internal(format!("Failed to serialize uploads: {}", e)))
+}
+
+/// Stop an `upload_video_from_path` that is still streaming
+///
+/// The upload then fails with "Upload cancelled"; `cancelled` is false if
+/// no upload with that id is running.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn cancel_upload(
+    uploads: tauri::State<'_, UploadRegistry>,
+    upload_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: cancel_upload called for {}", upload_id);
+    let cancelled = uploads.cancel(&upload_id);
+    if cancelled {
+        info!("Cancelling upload {}", upload_id);
+    }
+    Ok(serde_json::json!({ "upload_id": upload_id, "cancelled": cancelled }))
+}
+
+/// Upload a `content://` (Android) or security-scoped `file://` (iOS) URI
+///
+/// The OS only lends us a file descriptor, so there is no path to
+/// fingerprint or watch: the video is always uploaded as a copy and is not
+/// added to the local library (`tracked: false` in the response).
+#[allow(clippy::too_many_arguments)]
+async fn upload_platform_uri<R: Runtime>(
+    app: &AppHandle<R>,
+    transport: &Transport,
+    quota: &QuotaTracker,
+    uri: &str,
+    force: Option<bool>,
+    analysis_options: Option<AnalysisOptions>,
+    upload_id: &str,
+    cancel: CancellationToken,
+) -> Result<UploadResult, AppError> {
+    let opened = app
+        .video_analyzer()
+        .open_uri(uri)
+        .map_err(AppError::storage)?;
+    let mut warnings: Vec<String> = Vec::new();
+    if let Some(size) = opened.size {
+        if let Err(msg) = quota.check(size) {
+            if !force.unwrap_or(false) {
+                return Err(AppError::validation(format!(
+                    "{}. Free up space with cleanup_videos, or pass force=true",
+                    msg
+                )));
+            }
+            warn!("upload_video_from_path: {} (forced)", msg);
+            warnings.push(msg);
+        }
+    }
+
+    let file = tokio::fs::File::from_std(opened.file);
+    let _transfer = app.video_analyzer().begin_transfer(&opened.name);
+    let progress = emit_progress(app, upload_id, opened.size);
+    let chunks = UploadChunks::new(opened.name, analysis_options);
+    let (inner, bytes_sent) = stream_upload(transport, file, chunks, progress, cancel).await?;
+    info!(
+        "upload_video_from_path response: success={}, file_id={}, uri={}",
+        inner.success, inner.file_id, uri
+    );
+    if inner.success {
+        quota.adjust(bytes_sent as i64);
+    }
+
+    Ok(UploadResult {
+        upload_id: Some(upload_id.to_string()),
+        warnings,
+        ..inner.into()
+    })
+}
+
+/// Payload of an `upload://progress` event
+#[derive(Debug, Clone, serde::Serialize)]
+struct UploadProgress {
+    upload_id: String,
+    bytes_sent: u64,
+    /// None when the OS didn't tell us the size
+    total_bytes: Option<u64>,
+    chunk_index: i32,
+    percent: Option<f64>,
+}
+
+/// Progress callback for [`stream_upload`] that emits `upload://progress`
+/// and notes the chunk in the upload manifest
+fn emit_progress<R: Runtime>(
+    app: &AppHandle<R>,
+    upload_id: &str,
+    total_bytes: Option<u64>,
+) -> impl Fn(i32, u64) + Send + Sync + 'static {
+    let app = app.clone();
+    let upload_id = upload_id.to_string();
+    move |chunk_index, bytes_sent| {
+        let percent = total_bytes
+            .filter(|total| *total > 0)
+            .map(|total| (bytes_sent as f64 / total as f64 * 100.0).min(100.0));
+        let progress = UploadProgress {
+            upload_id: upload_id.clone(),
+            bytes_sent,
+            total_bytes,
+            chunk_index,
+            percent,
+        };
+        app.emit("upload://progress", progress).ok();
+        app.state::<UploadManifest>()
+            .record_sent(&upload_id, chunk_index as u64 + 1);
+    }
+}
+
+/// `on_chunk` that also reports the bytes sent as `stage` progress
+fn track_stage<R: Runtime>(
+    operation: &operations::OperationHandle<R>,
+    stage: &str,
+    total_bytes: u64,
+    on_chunk: impl Fn(i32, u64) + Send + Sync + 'static,
+) -> impl Fn(i32, u64) + Send + Sync + 'static {
+    let report = operation.progress_fn(stage);
+    move |chunk_index, bytes_sent| {
+        on_chunk(chunk_index, bytes_sent);
+        if total_bytes > 0 {
+            report(bytes_sent as f64 / total_bytes as f64);
+        }
+    }
+}
+
+/// Whether a failed upload may have been cut off by the network, rather
+/// than refused by the backend, and so is worth retrying
+fn is_retryable_upload_error(status: &tonic::Status) -> bool {
+    matches!(
+        status.code(),
+        tonic::Code::Unavailable
+            | tonic::Code::DeadlineExceeded
+            | tonic::Code::Aborted
+            | tonic::Code::Unknown
+    )
+}
+
+/// Stream `file`, positioned at `chunks.offset()`, to UploadVideo
+///
+/// Returns the backend's response and the offset reading stopped at,
+/// which is less than the file size if a read failed part way.
+/// `on_chunk(chunk_index, bytes_sent)` runs after each chunk is handed to
+/// the transport. Cancelling `cancel` abandons the upload with an error.
+///
+/// Every attempt carries the same idempotency key. When one is cut off,
+/// the backend is asked whether it got the whole video anyway before the
+/// file is sent again from the offset, up to `upload_max_retries` times,
+/// so a retry never stores the video twice.
+async fn stream_upload(
+    transport: &Transport,
+    mut file: tokio::fs::File,
+    chunks: UploadChunks,
+    on_chunk: impl Fn(i32, u64) + Send + Sync + 'static,
+    cancel: CancellationToken,
+) -> Result<(video_analyzer::UploadResponse, u64), AppError> {
+    let filename = chunks.filename.clone();
+    let on_chunk = std::sync::Arc::new(on_chunk);
+    let max_retries = config::get().grpc.upload_max_retries;
+    let started = std::time::Instant::now();
+    let mut attempt = 0;
+    loop {
+        // Clones share the file position, rewound below before a retry
+        let attempt_file = file
+            .try_clone()
+            .await
+            .map_err(|e| AppError::io(format!("Failed to read {}", filename), e))?;
+        let status = match send_upload(
+            transport,
+            attempt_file,
+            chunks.clone(),
+            on_chunk.clone(),
+            &cancel,
+        )
+        .await?
+        {
+            Ok((response, bytes_sent, sha256)) => {
+                let secs = started.elapsed().as_secs_f64();
+                info!(
+                    "Uploaded {} bytes of {} in {:.1}s ({:.2} MB/s, compression: {})",
+                    bytes_sent,
+                    filename,
+                    secs,
+                    bytes_sent as f64 / 1_000_000.0 / secs.max(0.001),
+                    config::get().grpc.compression.as_str()
+                );
+                // A part's digest is checked once all parts are in
+                if chunks.part.is_none() {
+                    let sha256 = sha256.ok_or_else(|| {
+                        AppError::integrity(format!(
+                            "The backend answered before all of {} was sent; upload it again",
+                            filename
+                        ))
+                    })?;
+                    uploads::verify_upload_digest(&filename, &response, &sha256)?;
+                }
+                return Ok((response, bytes_sent));
+            }
+            Err(status) => status,
+        };
+
+        if attempt >= max_retries || !is_retryable_upload_error(&status) {
+            return Err(AppError::from(status));
+        }
+        attempt += 1;
+        warn!(
+            "Upload of {} was interrupted ({}), retrying ({}/{})",
+            filename, status, attempt, max_retries
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(1 << attempt.min(5))) => {}
+            _ = cancel.cancelled() => return Err(AppError::cancelled("Upload cancelled")),
+        }
+
+        let request = video_analyzer::UploadStatusRequest {
+            idempotency_key: chunks.idempotency_key.clone(),
+        };
+        match transport.get_upload_status(request).await {
+            Ok(done) if done.completed => {
+                info!(
+                    "Upload of {} had completed before the connection dropped",
+                    filename
+                );
+                let response = done.response.unwrap_or_default();
+                // A part's digest is checked once all parts are in
+                if chunks.part.is_none() {
+                    let sha256 = files::validation::hash_open_file(&mut file)
+                        .await
+                        .map_err(|e| AppError::io(format!("Failed to read {}", filename), e))?;
+                    uploads::verify_upload_digest(&filename, &response, &sha256)?;
+                }
+                let bytes_received = u64::try_from(done.bytes_received).unwrap_or(0);
+                return Ok((response, bytes_received));
+            }
+            Ok(_) => {}
+            // Without the check a retry could store the video twice
+            Err(e) => {
+                let check = AppError::rpc("GetUploadStatus", e);
+                return Err(AppError::from(status)
+                    .map_message(|message| format!("{} (not retried: {})", message, check)));
+            }
+        }
+        if let Err(e) = file.seek(std::io::SeekFrom::Start(chunks.offset())).await {
+            return Err(AppError::from(status).map_message(|message| {
+                format!(
+                    "{} (not retried: {} can't be re-read: {})",
+                    message, filename, e
+                )
+            }));
+        }
+    }
+}
+
+/// [`stream_upload`] of the file at `path` over `streams` UploadVideo
+/// streams at once, each sending its own range of the chunks under the
+/// same idempotency key
+///
+/// For very large files on links one stream can't fill. Each part retries
+/// on its own; if one fails for good the others are stopped. The backend
+/// answers the stream that completes the file with the video, checked
+/// against `sha256`. `on_chunk` gets the bytes sent across all parts.
+#[allow(clippy::too_many_arguments)]
+async fn stream_upload_parallel(
+    transport: &Transport,
+    path: &std::path::Path,
+    size_bytes: u64,
+    sha256: &str,
+    chunks: UploadChunks,
+    streams: usize,
+    on_chunk: impl Fn(i32, u64) + Send + Sync + 'static,
+    cancel: CancellationToken,
+) -> Result<(video_analyzer::UploadResponse, u64), AppError> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    let filename = chunks.filename.clone();
+    let ranges = uploads::part_ranges(size_bytes.div_ceil(chunks.chunk_size as u64), streams);
+    let count = ranges.len() as i32;
+    let on_chunk = Arc::new(on_chunk);
+    let sent = Arc::new(AtomicU64::new(0));
+    // Cancelled when a part fails, so the rest stop too
+    let parts_cancel = cancel.child_token();
+    let started = std::time::Instant::now();
+    let handles: Vec<_> = ranges
+        .into_iter()
+        .enumerate()
+        .map(|(index, (first, end))| {
+            let transport = transport.clone();
+            let path = path.to_path_buf();
+            let on_chunk = on_chunk.clone();
+            let sent = sent.clone();
+            let cancel = parts_cancel.clone();
+            let mut part = chunks.clone();
+            part.first_chunk = first as i32;
+            part.part = Some(UploadPart {
+                index: index as i32,
+                count,
+                end_chunk: end as i32,
+                file_size: size_bytes,
+            });
+            tauri::async_runtime::spawn(async move {
+                let start = part.offset();
+                let mut file = tokio::fs::File::open(files::paths::fs_path(&path))
+                    .await
+                    .map_err(|e| {
+                        AppError::io(format!("Failed to open file {}", path.display()), e)
+                    })?;
+                file.seek(std::io::SeekFrom::Start(start))
+                    .await
+                    .map_err(|e| AppError::io(format!("Failed to read {}", path.display()), e))?;
+                // A retried part starts its range over; only new bytes count
+                let reached = AtomicU64::new(start);
+                let progress = move |chunk_index, part_end: u64| {
+                    let before = reached.fetch_max(part_end, Ordering::SeqCst);
+                    let added = part_end.saturating_sub(before);
+                    let total = sent.fetch_add(added, Ordering::SeqCst) + added;
+                    on_chunk(chunk_index, total);
+                };
+                let uploaded =
+                    stream_upload(&transport, file, part, progress, cancel.clone()).await;
+                if uploaded.is_err() {
+                    cancel.cancel();
+                }
+                uploaded.map(|(response, part_end)| (response, part_end.saturating_sub(start)))
+            })
+        })
+        .collect();
+
+    let mut responses = Vec::new();
+    let mut bytes_sent = 0;
+    let mut failed: Option<AppError> = None;
+    for handle in handles {
+        let part = handle
+            .await
+            .map_err(|e| AppError::internal(format!("An upload stream stopped: {}", e)))
+            .and_then(|part| part);
+        match part {
+            Ok((response, part_bytes)) => {
+                bytes_sent += part_bytes;
+                responses.push(response);
+            }
+            // The first real failure, not the parts stopped because of it
+            Err(e) => {
+                if failed
+                    .as_ref()
+                    .is_none_or(|f| matches!(f, AppError::Cancelled { .. }))
+                {
+                    failed = Some(e);
+                }
+            }
+        }
+    }
+    if let Some(error) = failed {
+        return Err(if cancel.is_cancelled() {
+            AppError::cancelled("Upload cancelled")
+        } else {
+            error
+        });
+    }
+
+    let response = match responses.into_iter().find(|r| !r.file_id.is_empty()) {
+        Some(response) => response,
+        // Every part arrived, but their answers crossed with the assembly
+        None => {
+            let status = transport
+                .get_upload_status(video_analyzer::UploadStatusRequest {
+                    idempotency_key: chunks.idempotency_key.clone(),
+                })
+                .await
+                .map_err(|e| AppError::rpc("GetUploadStatus", e))?;
+            match status.response {
+                Some(response) if status.completed => response,
+                _ => {
+                    return Err(AppError::integrity(format!(
+                        "The backend has all {} parts of {} but didn't put them together; upload it again",
+                        count, filename
+                    )))
+                }
+            }
+        }
+    };
+    uploads::verify_upload_digest(&filename, &response, sha256)?;
+    let secs = started.elapsed().as_secs_f64();
+    info!(
+        "Uploaded {} bytes of {} over {} streams in {:.1}s ({:.2} MB/s)",
+        bytes_sent,
+        filename,
+        count,
+        secs,
+        bytes_sent as f64 / 1_000_000.0 / secs.max(0.001)
+    );
+    Ok((response, bytes_sent))
+}
+
+/// How one upload is cut into chunks and what they are labelled with
+#[derive(Clone)]
+struct UploadChunks {
+    filename: String,
+    analysis_options: Option<AnalysisOptions>,
+    /// Detected spoken language, see [`media::language`]
+    language_hint: String,
+    /// Digest of the picked file when a converted copy is sent
+    source_sha256: String,
+    idempotency_key: String,
+    chunk_size: usize,
+    /// Where a resumed upload starts; 0 for a new one
+    first_chunk: i32,
+    /// Set when this is one stream of a parallel upload
+    part: Option<UploadPart>,
+}
+
+/// One stream's share of a parallel upload, see [`stream_upload_parallel`]
+#[derive(Clone, Copy)]
+struct UploadPart {
+    index: i32,
+    count: i32,
+    /// Chunk the part stops before
+    end_chunk: i32,
+    file_size: u64,
+}
+
+impl UploadChunks {
+    /// A new upload in `video_chunk_size` pieces under a fresh key
+    fn new(filename: String, analysis_options: Option<AnalysisOptions>) -> Self {
+        Self {
+            filename,
+            analysis_options,
+            language_hint: String::new(),
+            source_sha256: String::new(),
+            idempotency_key: uuid::Uuid::new_v4().to_string(),
+            chunk_size: config::get().grpc.video_chunk_size,
+            first_chunk: 0,
+            part: None,
+        }
+    }
+
+    /// Byte offset of `first_chunk`
+    fn offset(&self) -> u64 {
+        self.first_chunk as u64 * self.chunk_size as u64
+    }
+}
+
+/// One attempt of [`stream_upload`], from wherever `file` is positioned
+///
+/// Each chunk carries its SHA-256 and the last one the whole file's, which
+/// is also returned; it is `None` for a part of a parallel upload, and if
+/// the backend answered before the whole file was sent. The outer error
+/// ends the upload; the inner one is the backend's and may be worth a
+/// retry.
+async fn send_upload(
+    transport: &Transport,
+    file: tokio::fs::File,
+    labels: UploadChunks,
+    on_chunk: std::sync::Arc<impl Fn(i32, u64) + Send + Sync + 'static>,
+    cancel: &CancellationToken,
+) -> Result<Result<(video_analyzer::UploadResponse, u64, Option<String>), tonic::Status>, AppError>
+{
+    // Channel-backed stream to avoid buffering entire file
+    let (tx, rx) = tokio::sync::mpsc::channel::<video_analyzer::VideoChunk>(8);
+    let (read_failed_tx, mut read_failed) = tokio::sync::oneshot::channel::<AppError>();
+
+    // Spawn a task to read and send chunks
+    let producer = tokio::spawn(async move {
+        match read_chunks(file, labels, on_chunk, &tx).await {
+            Ok(sent) => Some(sent),
+            Err(error) => {
+                read_failed_tx.send(error).ok();
+                // Hold the sender until the call is dropped, so the stream
+                // is reset rather than ended like a complete file
+                tx.closed().await;
+                None
+            }
+        }
+    });
+
+    let request_stream = ReceiverStream::new(rx);
+
+    let mut upload = transport.upload_video(request_stream);
+    let response = tokio::select! {
+        response = &mut upload => response,
+        Ok(error) = &mut read_failed => {
+            drop(upload);
+            producer.abort();
+            return Err(error);
+        }
+        _ = cancel.cancelled() => {
+            // Drop the call while the producer still holds the sender, so
+            // the stream is reset rather than ended like a complete file
+            drop(upload);
+            producer.abort();
+            return Err(AppError::cancelled("Upload cancelled"));
+        }
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(status) => {
+            producer.abort();
+            return Ok(Err(status));
+        }
+    };
+
+    // The producer holds on to a failed read until the call is gone
+    drop(upload);
+    match producer.await {
+        Ok(Some((bytes_sent, sha256))) => Ok(Ok((response, bytes_sent, sha256))),
+        Ok(None) => Err(read_failed
+            .try_recv()
+            .unwrap_or_else(|_| AppError::internal("The upload stopped reading the file"))),
+        Err(e) => Err(AppError::internal(format!(
+            "The upload stopped reading the file: {}",
+            e
+        ))),
+    }
+}
+
+/// Read `file` from where it is positioned and send it to `tx` as chunks
+/// labelled by `labels`; returns the offset reached and the file's digest
+///
+/// A chunk is held back one read, so the last can carry the digest, and
+/// is never sent if reading fails after it.
+async fn read_chunks(
+    mut file: tokio::fs::File,
+    labels: UploadChunks,
+    on_chunk: std::sync::Arc<impl Fn(i32, u64) + Send + Sync + 'static>,
+    tx: &tokio::sync::mpsc::Sender<video_analyzer::VideoChunk>,
+) -> Result<(u64, Option<String>), AppError> {
+    let read_error = |e| AppError::io(format!("Failed to read {}", labels.filename), e);
+    let mut first_chunk_options = labels.analysis_options.clone();
+    let mut language_hint = labels.language_hint.clone();
+    let mut source_sha256 = labels.source_sha256.clone();
+    let mut idempotency_key = labels.idempotency_key.clone();
+    let mut idx: i32 = labels.first_chunk;
+    let mut bytes_read: u64 = idx as u64 * labels.chunk_size as u64;
+    // The file's digest covers what a resumed upload already sent too;
+    // a part of a parallel upload leaves it to the caller
+    let mut file_hasher = match labels.part {
+        Some(_) => None,
+        None => Some(
+            files::validation::hash_prefix(&mut file, bytes_read)
+                .await
+                .map_err(read_error)?,
+        ),
+    };
+    let mut pending: Option<video_analyzer::VideoChunk> = None;
+    loop {
+        if labels.part.is_some_and(|part| idx >= part.end_chunk) {
+            break;
+        }
+        let mut buf = vec![0u8; labels.chunk_size];
+        let n = file.read(&mut buf).await.map_err(read_error)?;
+        if n == 0 {
+            break; // EOF
+        }
+        buf.truncate(n);
+        throttle::acquire(n).await;
+        let offset = bytes_read as i64;
+        bytes_read += n as u64;
+        if let Some(hasher) = &mut file_hasher {
+            sha2::Digest::update(hasher, &buf);
+        }
+        let chunk = video_analyzer::VideoChunk {
+            sha256: uploads::sha256_hex(&buf),
+            data: buf,
+            filename: labels.filename.clone(),
+            chunk_index: idx,
+            analysis_options: first_chunk_options.take(),
+            idempotency_key: std::mem::take(&mut idempotency_key),
+            offset,
+            language_hint: std::mem::take(&mut language_hint),
+            encryption: None,
+            file_sha256: String::new(),
+            stream_index: labels.part.map_or(0, |part| part.index),
+            stream_count: labels.part.map_or(0, |part| part.count),
+            file_size: labels.part.map_or(0, |part| part.file_size as i64),
+            source_sha256: std::mem::take(&mut source_sha256),
+        };
+        idx += 1;
+        if let Some(previous) = pending.replace(chunk) {
+            let chunk_index = previous.chunk_index;
+            let start = previous.offset as u64;
+            let end = start + previous.data.len() as u64;
+            if tx.send(previous).await.is_err() {
+                return Ok((start, None));
+            }
+            on_chunk(chunk_index, end);
+        }
+    }
+    let file_sha256 = file_hasher.map(|hasher| hex::encode(sha2::Digest::finalize(hasher)));
+    if let Some(mut last) = pending {
+        last.file_sha256 = file_sha256.clone().unwrap_or_default();
+        let chunk_index = last.chunk_index;
+        if tx.send(last).await.is_err() {
+            return Ok((bytes_read, None));
+        }
+        on_chunk(chunk_index, bytes_read);
+    }
+    Ok((bytes_read, file_sha256))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn register_local_video(
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    quota: tauri::State<'_, QuotaTracker>,
+    cache: tauri::State<'_, QueryCache>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    file_path: String,
+    display_name: String,
+    reference_only: bool,
+    analysis_options: Option<AnalysisOptions>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: register_local_video called with {}", file_path);
+    register_path(
+        &transport,
+        &library,
+        &quota,
+        &cache,
+        &pipeline,
+        file_path,
+        display_name,
+        reference_only,
+        analysis_options,
+        RegisterMode::New,
+    )
+    .await
+}
+
+/// Find the videos under `path` that pass `options` and register each one
+/// not already in the library; see [`import`] for the events along the way
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn import_directory<R: Runtime>(
+    app: AppHandle<R>,
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    quota: tauri::State<'_, QuotaTracker>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    path: String,
+    options: Option<import::ImportOptions>,
+    import_id: Option<String>,
+) -> Result<import::ImportSummary, AppError> {
+    println!("🦀 Rust: import_directory called with {}", path);
+    let options = options.unwrap_or_default();
+    let import_id = import_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let root = files::normalize_path(&path).map_err(AppError::validation)?;
+
+    let discovery = {
+        let app = app.clone();
+        let root = root.clone();
+        let options = options.clone();
+        let import_id = import_id.clone();
+        tokio::task::spawn_blocking(move || {
+            import::discover(&root, &options, std::time::SystemTime::now(), |found| {
+                let progress = import::ImportProgress {
+                    import_id: import_id.clone(),
+                    phase: import::ImportPhase::Discovering,
+                    scanned: found.scanned,
+                    found: found.candidates.len(),
+                    done: 0,
+                };
+                app.emit(&crate::event_name("import-progress"), progress)
+                    .ok();
+            })
+        })
+        .await
+        .map_err(|e| AppError::internal(format!("Directory walk failed: {}", e)))?
+        .map_err(AppError::storage)?
+    };
+    info!(
+        "import_directory: {} of {} entries under {} pass the filters",
+        discovery.candidates.len(),
+        discovery.scanned,
+        root.display()
+    );
+
+    // Compared by canonical path, the way the library records them
+    let known: std::collections::HashSet<String> = library
+        .entries()
+        .into_iter()
+        .map(|entry| entry.canonical_path)
+        .collect();
+    let mut summary = import::ImportSummary {
+        import_id: import_id.clone(),
+        root: root.to_string_lossy().into_owned(),
+        scanned: discovery.scanned,
+        filtered_out: discovery.filtered_out,
+        unreadable: discovery.unreadable,
+        found: discovery.candidates.len(),
+        registered: 0,
+        skipped: 0,
+        failed: 0,
+        files: Vec::new(),
+    };
+    for candidate in discovery.candidates {
+        let path = candidate.path.to_string_lossy().into_owned();
+        let mut file = import::ImportFile {
+            import_id: import_id.clone(),
+            path: path.clone(),
+            size_bytes: candidate.size_bytes,
+            state: import::ImportState::Found,
+            file_id: None,
+            error: None,
+        };
+        if options.register {
+            let canonical = files::resolve_path(&candidate.path)
+                .ok()
+                .and_then(|resolved| resolved.canonical.to_str().map(str::to_string));
+            if canonical.is_some_and(|canonical| known.contains(&canonical)) {
+                file.state = import::ImportState::Skipped;
+                summary.skipped += 1;
+            } else {
+                let display_name = files::FileName::of(&candidate.path).display;
+                let registered = register_path(
+                    &transport,
+                    &library,
+                    &quota,
+                    &app.state::<QueryCache>(),
+                    &pipeline,
+                    path,
+                    display_name,
+                    options.reference_only,
+                    None,
+                    RegisterMode::New,
+                )
+                .await;
+                match registered {
+                    Ok(value) => {
+                        file.state = import::ImportState::Registered;
+                        file.file_id = value["file_id"].as_str().map(str::to_string);
+                        summary.registered += 1;
+                    }
+                    Err(e) => {
+                        warn!("import_directory: {} failed: {}", file.path, e);
+                        file.state = import::ImportState::Failed;
+                        file.error = Some(e);
+                        summary.failed += 1;
+                    }
+                }
+            }
+            app.emit(&crate::event_name("import-file"), &file).ok();
+            let progress = import::ImportProgress {
+                import_id: import_id.clone(),
+                phase: import::ImportPhase::Registering,
+                scanned: summary.scanned,
+                found: summary.found,
+                done: summary.files.len() + 1,
+            };
+            app.emit(&crate::event_name("import-progress"), progress)
+                .ok();
+        }
+        summary.files.push(file);
+    }
+    Ok(summary)
+}
+
+/// Duration, resolution, codecs and container of a local video, and
+/// whether the backend can analyse it, see [`media::probe`]
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn probe_video(path: String) -> Result<media::probe::VideoProbe, AppError> {
+    println!("🦀 Rust: probe_video called with {}", path);
+    let path = files::normalize_path(&path).map_err(AppError::validation)?;
+    let resolved = files::resolve_path(&path).map_err(AppError::validation)?;
+    media::probe::probe(&resolved.canonical)
+        .await
+        .map_err(AppError::validation)
+}
+
+/// A frame of a library video, or of the file at a path, as a cached
+/// JPEG the UI can show by URL, see [`thumbnails`]
+///
+/// Without `timestamp_ms` the frame a tenth of the way in is used, or the
+/// first when the length can't be probed. `size` is the width in pixels.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_thumbnail(
+    library: tauri::State<'_, Library>,
+    thumbnails: tauri::State<'_, thumbnails::ThumbnailCache>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    path_or_video_id: String,
+    timestamp_ms: Option<i64>,
+    size: Option<u32>,
+) -> Result<thumbnails::Thumbnail, AppError> {
+    println!("🦀 Rust: get_thumbnail called for {}", path_or_video_id);
+    let width = size
+        .unwrap_or(thumbnails::DEFAULT_WIDTH)
+        .clamp(thumbnails::MIN_WIDTH, thumbnails::MAX_WIDTH);
+    // Registered videos carry their hash; anything else is hashed here
+    let (video_id, path, sha256) = match library.get(path_or_video_id.trim()) {
+        Some(entry) if !entry.sha256.is_empty() => {
+            (Some(entry.file_id.clone()), entry.path(), entry.sha256)
+        }
+        _ => {
+            let path = files::resolve_path(
+                &files::normalize_path(&path_or_video_id).map_err(AppError::validation)?,
+            )
+            .map_err(AppError::validation)?
+            .canonical;
+            let sha256 = thumbnails
+                .content_hash(&path)
+                .await
+                .map_err(AppError::storage)?;
+            (None, path, sha256)
+        }
+    };
+    let timestamp_ms = match timestamp_ms {
+        Some(ms) => ms.max(0),
+        None => media::probe::probe(&path)
+            .await
+            .ok()
+            .and_then(|probe| probe.duration_secs)
+            .map_or(0, |secs| (secs * 100.0) as i64),
+    };
+
+    let cached_path = thumbnails.path(&sha256, timestamp_ms, width);
+    let cached = cached_path.is_file();
+    let path = if cached {
+        cached_path
+    } else {
+        let jpeg = media::frame_image(
+            &pipeline,
+            &path,
+            timestamp_ms,
+            Some(width),
+            media::ImageFormat::Jpeg,
+        )
+        .await
+        .map_err(AppError::internal)?;
+        thumbnails
+            .put(&sha256, timestamp_ms, width, &jpeg)
+            .map_err(AppError::storage)?
+    };
+    Ok(thumbnails::Thumbnail {
+        video_id,
+        url: thumbnails::url(&thumbnails::relative(&sha256, timestamp_ms, width)),
+        path: path.to_string_lossy().into_owned(),
+        sha256,
+        timestamp_ms,
+        width,
+        cached,
+    })
+}
+
+/// The frame at `timestamp_ms` of a library video, or of the file at a
+/// path, at full size as PNG or JPEG
+///
+/// Written to `dest` when given, otherwise under a fresh name in the
+/// active account's `frames/` directory, where it can be attached to a chat
+/// question by path; `format` defaults to what `dest`'s extension says,
+/// else PNG. With `include_bytes` the image also comes back base64-encoded
+/// in `data`.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn extract_frame(
+    accounts: tauri::State<'_, Accounts>,
+    library: tauri::State<'_, Library>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    video_id_or_path: String,
+    timestamp_ms: i64,
+    format: Option<String>,
+    dest: Option<String>,
+    include_bytes: Option<bool>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: extract_frame called for {} at {} ms",
+        video_id_or_path, timestamp_ms
+    );
+    if timestamp_ms < 0 {
+        return Err(AppError::validation(format!(
+            "Timestamp must not be negative, got {} ms",
+            timestamp_ms
+        )));
+    }
+    let dest = dest
+        .map(|dest| files::normalize_path(&dest))
+        .transpose()
+        .map_err(AppError::validation)?;
+    let format = match &format {
+        Some(name) => media::ImageFormat::parse(name).map_err(AppError::validation)?,
+        None => dest
+            .as_deref()
+            .and_then(media::ImageFormat::from_path)
+            .unwrap_or(media::ImageFormat::Png),
+    };
+    let video = match library.get(video_id_or_path.trim()) {
+        Some(entry) => entry.path(),
+        None => {
+            files::resolve_path(
+                &files::normalize_path(&video_id_or_path).map_err(AppError::validation)?,
+            )
+            .map_err(AppError::validation)?
+            .canonical
+        }
+    };
+    if !video.is_file() {
+        return Err(AppError::validation(format!(
+            "{} is missing; relink the video first",
+            video.display()
+        )));
+    }
+
+    let image = media::frame_image(&pipeline, &video, timestamp_ms, None, format)
+        .await
+        .map_err(AppError::internal)?;
+    let dest = match dest {
+        Some(dest) => {
+            tokio::fs::write(&dest, &image)
+                .await
+                .map_err(|e| AppError::io(format!("Failed to write {}", dest.display()), e))?;
+            dest
+        }
+        None => {
+            // Per account, and never through a file or link already there
+            let dir = accounts.workspace().join("frames");
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .map_err(|e| AppError::io(format!("Failed to create {}", dir.display()), e))?;
+            let stem = video.file_stem().unwrap_or_default().to_string_lossy();
+            let dest = dir.join(format!(
+                "{}-{}ms-{}.{}",
+                stem,
+                timestamp_ms,
+                uuid::Uuid::new_v4(),
+                format.extension()
+            ));
+            let mut file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&dest)
+                .await
+                .map_err(|e| AppError::io(format!("Failed to create {}", dest.display()), e))?;
+            tokio::io::AsyncWriteExt::write_all(&mut file, &image)
+                .await
+                .map_err(|e| AppError::io(format!("Failed to write {}", dest.display()), e))?;
+            dest
+        }
+    };
+    info!(
+        "extract_frame: {} bytes at {} ms -> {}",
+        image.len(),
+        timestamp_ms,
+        dest.display()
+    );
+
+    let mut frame = serde_json::json!({
+        "path": dest.to_string_lossy(),
+        "timestamp_ms": timestamp_ms,
+        "mime_type": format.mime(),
+        "size_bytes": image.len(),
+    });
+    if include_bytes.unwrap_or(false) {
+        use base64::Engine;
+        frame["data"] = base64::engine::general_purpose::STANDARD
+            .encode(&image)
+            .into();
+    }
+    Ok(frame)
+}
+
+/// Let the user choose videos with the desktop's own dialog, see [`picker`]
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn open_video_picker(multi: bool) -> Result<Vec<picker::PickedFile>, AppError> {
+    println!("🦀 Rust: open_video_picker called (multi: {})", multi);
+    let paths = picker::pick(multi).await.map_err(AppError::internal)?;
+    info!("open_video_picker: {} chosen", paths.len());
+    paths
+        .iter()
+        .map(|path| picker::describe(path))
+        .collect::<Result<_, _>>()
+        .map_err(AppError::storage)
+}
+
+/// How `register_path` relates to an existing library entry
+enum RegisterMode<'a> {
+    New,
+    /// Same path, refreshed fingerprint (after the file was modified)
+    Reregister(&'a LibraryEntry),
+    /// New path, same content (after the file was moved)
+    Relink(&'a LibraryEntry),
+}
+
+/// Validate, fingerprint and register a local file; shared by
+/// `register_local_video`, `reregister_video` and `relink_video`
+///
+/// For re-registration and relinking the backend is asked to update the
+/// existing registration in place; relinking additionally refuses a file
+/// whose hash differs from the one recorded for it. Either drops the
+/// answers cached for the video, which may no longer fit its content.
+#[allow(clippy::too_many_arguments)]
+async fn register_path(
+    transport: &Transport,
+    library: &Library,
+    quota: &QuotaTracker,
+    cache: &QueryCache,
+    pipeline: &media::pipeline::Pipeline,
+    file_path: String,
+    display_name: String,
+    reference_only: bool,
+    analysis_options: Option<AnalysisOptions>,
+    mode: RegisterMode<'_>,
+) -> Result<Value, AppError> {
+    let existing = match mode {
+        RegisterMode::New => None,
+        RegisterMode::Reregister(entry) | RegisterMode::Relink(entry) => Some(entry),
+    };
+    if let Some(options) = &analysis_options {
+        analysis::validate(options).map_err(AppError::validation)?;
+    }
+
+    // The backend can't reach into another device's sandbox, so mobile
+    // files are only ever uploaded as copies
+    if files::uri::is_platform_uri(&file_path) {
+        return Err(AppError::validation(format!(
+            "{} can't be registered; upload it with upload_video_from_path instead",
+            file_path
+        )));
+    }
+    if reference_only && cfg!(mobile) {
+        return Err(AppError::validation(
+            "Registering by reference is not available on mobile; upload a copy instead",
+        ));
+    }
+
+    // Resolve symlinks first so the backend references the real file, not the link
+    let normalized = files::normalize_path(&file_path).map_err(AppError::validation)?;
+    let resolved = files::resolve_path(&normalized).map_err(AppError::validation)?;
+    let warnings = resolved.warnings(reference_only);
+    for w in &warnings {
+        warn!("register_local_video: {}", w);
+    }
+
+    // Reject missing/unreadable/non-video paths before bothering the backend
+    let validated = files::validate_video_file(&resolved.canonical)
+        .await
+        .map_err(AppError::validation)?;
+    info!(
+        "register_local_video validated: container={}, size={}, sha256={}, volume={:?}",
+        validated.container, validated.size_bytes, validated.sha256, resolved.volume
+    );
+    // Only copies consume backend storage; references are just a path
+    if !reference_only {
+        quota
+            .check(validated.size_bytes)
+            .map_err(AppError::validation)?;
+    }
+    if let RegisterMode::Relink(entry) = mode {
+        if !entry.sha256.is_empty() && entry.sha256 != validated.sha256 {
+            return Err(AppError::validation(format!(
+                "{} has different content than '{}'; register it as a new video instead",
+                resolved.canonical.display(),
+                entry.display_name
+            )));
+        }
+    }
+
+    // The backend receives the path as a proto string, so it can only ever
+    // open paths that are valid UTF-8; streaming the bytes works for any name
+    let canonical_path = resolved
+        .canonical
+        .to_str()
+        .ok_or_else(|| {
+            format!(
+                "Path {} is not valid UTF-8 and cannot be registered by reference; \
+                 upload the file instead",
+                resolved.canonical.display()
+            )
+        })
+        .map_err(AppError::validation)?
+        .to_string();
+    let name = files::FileName::of(&resolved.canonical);
+    // Unchanged content speaks the same language as last time
+    let detected_language = match existing {
+        Some(entry) if entry.sha256 == validated.sha256 && entry.detected_language.is_some() => {
+            entry.detected_language.clone()
+        }
+        _ => {
+            media::language::hint_for(pipeline, &resolved.canonical, analysis_options.as_ref())
+                .await
+        }
+    };
+    let request = RegisterVideoRequest {
+        file_path: canonical_path.clone(),
+        display_name,
+        reference_only,
+        size_bytes: validated.size_bytes as i64,
+        sha256: validated.sha256.clone(),
+        modified_at: validated.modified_at,
+        container: validated.container.clone(),
+        file_id: existing.map(|e| e.file_id.clone()).unwrap_or_default(),
+        analysis_options: analysis_options.clone(),
+        language_hint: detected_language
+            .as_ref()
+            .map(|d| d.code.clone())
+            .unwrap_or_default(),
+    };
+
+    let inner = transport
+        .register_local_video(request)
+        .await
+        .map_err(AppError::from)?;
+    // A backend that predates in-place updates hands out a new id; keep the
+    // library keyed by whatever the backend now knows the video as
+    let previous_id = existing
+        .map(|e| e.file_id.clone())
+        .filter(|id| *id != inner.file_id);
+    if inner.copied {
+        quota.adjust(inner.size_bytes);
+    }
+    if let Some(entry) = existing {
+        cache.clear_video(&entry.file_id);
+        cache.clear_video(&inner.file_id);
+    }
+    if !inner.file_id.is_empty() {
+        if let Some(id) = &previous_id {
+            library.remove(id).map_err(AppError::storage)?;
+        }
+        library
+            .upsert(LibraryEntry {
+                file_id: inner.file_id.clone(),
+                display_name: inner.display_name.clone(),
+                original_path: file_path,
+                canonical_path,
+                canonical_path_raw: None,
+                name_raw: name.raw_hex,
+                volume_id: resolved.volume.id.clone(),
+                volume_kind: resolved.volume.kind,
+                reference_only,
+                size_bytes: validated.size_bytes,
+                sha256: validated.sha256,
+                modified_at: validated.modified_at,
+                container: validated.container,
+                registered_at: inner.registered_at,
+                status: library::FileStatus::Ok,
+                last_verified_at: inner.registered_at,
+                analysis_options,
+                detected_language: detected_language.clone(),
+            })
+            .map_err(AppError::storage)?;
+    }
+
+    let mut shaped = serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    shaped["warnings"] = serde_json::json!(warnings);
+    shaped["volume"] = serde_json::json!(resolved.volume);
+    shaped["previous_video_id"] = serde_json::json!(previous_id);
+    shaped["detected_language"] = serde_json::json!(detected_language);
+    Ok(shaped)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn verify_video(
+    library: tauri::State<'_, Library>,
+    video_id: String,
+    deep: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: verify_video called for {}", video_id);
+
+    let entry = library
+        .get(&video_id)
+        .ok_or_else(|| format!("Unknown video: {}", video_id))
+        .map_err(AppError::validation)?;
+    let report = staleness::verify_and_record(&library, &entry, deep.unwrap_or(true)).await;
+    if report.status != library::FileStatus::Ok {
+        warn!("verify_video {}: {}", video_id, report.message);
+    }
+    serde_json::to_value(report)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn reregister_video(
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    quota: tauri::State<'_, QuotaTracker>,
+    cache: tauri::State<'_, QueryCache>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: reregister_video called for {}", video_id);
+
+    let entry = library
+        .get(&video_id)
+        .ok_or_else(|| format!("Unknown video: {}", video_id))
+        .map_err(AppError::validation)?;
+    register_path(
+        &transport,
+        &library,
+        &quota,
+        &cache,
+        &pipeline,
+        entry.canonical_path.clone(),
+        entry.display_name.clone(),
+        entry.reference_only,
+        entry.analysis_options.clone(),
+        RegisterMode::Reregister(&entry),
+    )
+    .await
+}
+
+/// Point a registered video at its new location after it was moved
+///
+/// `new_path` is typically the `new_path` of a `video-moved` event. The
+/// file must have the same content as the original registration.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn relink_video(
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    quota: tauri::State<'_, QuotaTracker>,
+    cache: tauri::State<'_, QueryCache>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    video_id: String,
+    new_path: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: relink_video called for {} -> {}",
+        video_id, new_path
+    );
+
+    let entry = library
+        .get(&video_id)
+        .ok_or_else(|| format!("Unknown video: {}", video_id))
+        .map_err(AppError::validation)?;
+    register_path(
+        &transport,
+        &library,
+        &quota,
+        &cache,
+        &pipeline,
+        new_path,
+        entry.display_name.clone(),
+        entry.reference_only,
+        entry.analysis_options.clone(),
+        RegisterMode::Relink(&entry),
+    )
+    .await
+}
+
+/// Change the analysis options of an existing video
+///
+/// The response's `reanalysis_required` tells the UI whether results
+/// already produced are now out of date.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn update_analysis_options(
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    video_id: String,
+    options: AnalysisOptions,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: update_analysis_options called for {}: {:?}",
+        video_id, options
+    );
+    analysis::validate(&options).map_err(AppError::validation)?;
+
+    let request = UpdateAnalysisOptionsRequest {
+        video_id: video_id.clone(),
+        options: Some(options.clone()),
+    };
+    let inner = transport
+        .update_analysis_options(request)
+        .await
+        .map_err(|e| AppError::rpc("UpdateAnalysisOptions", e))?;
+    info!(
+        "update_analysis_options response: success={}, reanalysis_required={}",
+        inner.success, inner.reanalysis_required
+    );
+    if inner.success {
+        // Keep what the backend says is in effect, falling back to what we sent
+        let effective = inner.options.clone().unwrap_or(options);
+        library
+            .update(&video_id, |e| e.analysis_options = Some(effective))
+            .map_err(AppError::storage)?;
+    }
+    serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Ask about `video_id`, or the active session's video when left out
+///
+/// `timeout_secs` overrides the configured chat deadline for this call.
+/// A question asked before is answered from the [`query_cache`], with
+/// every chunk marked `cached`, unless `bypass_cache` is set.
+///
+/// If the backend can't be reached, the question is queued in the
+/// [`outbox`] and answered with one progress chunk carrying its
+/// `outbox_id`; it is sent once the backend is ready again.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn process_query(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    session: tauri::State<'_, ActiveSession>,
+    transforms: tauri::State<'_, postprocess::Pipeline>,
+    shutdown: tauri::State<'_, Shutdown>,
+    library: tauri::State<'_, Library>,
+    cache: tauri::State<'_, QueryCache>,
+    outbox: tauri::State<'_, Outbox>,
+    video_id: Option<String>,
+    query: String,
+    query_type: Option<String>,
+    attachments: Option<Vec<String>>,
+    timeout_secs: Option<u64>,
+    bypass_cache: Option<bool>,
+) -> Result<Vec<ChatChunk>, AppError> {
+    let video_id = session.resolve(video_id).map_err(AppError::validation)?;
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    let query_type = query_type.unwrap_or_default();
+    let attachments = attachments.unwrap_or_default();
+    let pending = IncompleteResponse {
+        request_id: sequence::new_request_id(),
+        query: query.clone(),
+        query_type: query_type.clone(),
+        attachments: attachments.clone(),
+        range: None,
+        started_at: now,
+        updated_at: now,
+        chunks: Vec::new(),
+        error: None,
+    };
+    let lookup = cache.lookup(
+        &transport.endpoint(),
+        &library,
+        &history,
+        &video_id,
+        &pending,
+        bypass_cache.unwrap_or(false),
+    );
+    let answer = run_query(
+        &transport,
+        &history,
+        &transforms,
+        &shutdown,
+        &video_id,
+        pending,
+        Some(lookup),
+        |_| {},
+    );
+    let responses =
+        match crate::transport::with_timeout(timeout_override(timeout_secs), answer).await {
+            Err(e) if outbox::should_queue(&e) => {
+                let queued = outbox
+                    .enqueue(&video_id, query, query_type, attachments)
+                    .map_err(AppError::storage)?;
+                info!("process_query: backend unreachable, queued {}", queued.id);
+                return Ok(vec![outbox::queued_chunk(&queued)]);
+            }
+            answered => answered?,
+        };
+    responses::chat_chunks(responses).map_err(AppError::internal)
+}
+
+/// Like `process_query`, but the answer is sent as it streams
+///
+/// Returns `{ request_id }` straight away; each chunk then arrives as a
+/// `chat://chunk/<request_id>` event, and `chat://done/<request_id>` ends
+/// the answer with `{ ok, responses }` (the full array `process_query`
+/// would have returned) or `{ ok: false, error }`, plus the `video_id` it
+/// answered for. Pass `request_id` to subscribe before anything is sent.
+/// Cached answers arrive the same way, with every chunk marked `cached`.
+/// Each chunk carries `role`, `agent`, `is_partial` and `plain_text` for
+/// screen-reader announcements, see [`a11y`].
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn process_query_streaming<R: Runtime>(
+    app: AppHandle<R>,
+    video_id: Option<String>,
+    query: String,
+    query_type: Option<String>,
+    attachments: Option<Vec<String>>,
+    request_id: Option<String>,
+    timeout_secs: Option<u64>,
+    bypass_cache: Option<bool>,
+) -> Result<Value, AppError> {
+    let video_id = app
+        .state::<ActiveSession>()
+        .resolve(video_id)
+        .map_err(AppError::validation)?;
+    let query_type = query_type.unwrap_or_default();
+    query::QueryMode::parse(&query_type).map_err(AppError::validation)?;
+    let request_id = request_id
+        .filter(|id| !id.trim().is_empty())
+        .unwrap_or_else(sequence::new_request_id);
+    println!(
+        "🦀 Rust: process_query_streaming called for {}: {}",
+        video_id, request_id
+    );
+
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    let pending = IncompleteResponse {
+        request_id: request_id.clone(),
+        query,
+        query_type,
+        attachments: attachments.unwrap_or_default(),
+        range: None,
+        started_at: now,
+        updated_at: now,
+        chunks: Vec::new(),
+        error: None,
+    };
+    let chunk_event = format!("chat://chunk/{}", request_id);
+    let done_event = format!("chat://done/{}", request_id);
+    tauri::async_runtime::spawn(async move {
+        let transport = app.state::<Transport>();
+        let history = app.state::<LocalHistory>();
+        let transforms = app.state::<postprocess::Pipeline>();
+        let shutdown = app.state::<Shutdown>();
+        let lookup = app.state::<QueryCache>().inner().lookup(
+            &transport.endpoint(),
+            &app.state::<Library>(),
+            &history,
+            &video_id,
+            &pending,
+            bypass_cache.unwrap_or(false),
+        );
+        let answer = run_query(
+            &transport,
+            &history,
+            &transforms,
+            &shutdown,
+            &video_id,
+            pending,
+            Some(lookup),
+            |chunks| {
+                for chunk in chunks {
+                    app.emit(&chunk_event, chunk).ok();
+                }
+            },
+        );
+        let result = crate::transport::with_timeout(timeout_override(timeout_secs), answer).await;
+        let done = match result {
+            Ok(responses) => {
+                serde_json::json!({ "ok": true, "responses": responses, "video_id": video_id })
+            }
+            Err(error) => serde_json::json!({ "ok": false, "error": error, "video_id": video_id }),
+        };
+        app.emit(&done_event, done).ok();
+    });
+
+    Ok(serde_json::json!({ "request_id": request_id }))
+}
+
+/// Ask about one part of the video only, e.g. "what happens between 10:00
+/// and 12:30?"
+///
+/// The backend limits its analysis to `[start_ms, end_ms)`; otherwise this
+/// behaves like `process_query`, and the range is kept with the question in
+/// the local history.
+#[tauri::command(rename_all = "snake_case")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn process_query_range(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    session: tauri::State<'_, ActiveSession>,
+    transforms: tauri::State<'_, postprocess::Pipeline>,
+    shutdown: tauri::State<'_, Shutdown>,
+    library: tauri::State<'_, Library>,
+    cache: tauri::State<'_, QueryCache>,
+    video_id: Option<String>,
+    query: String,
+    start_ms: i64,
+    end_ms: i64,
+    query_type: Option<String>,
+    attachments: Option<Vec<String>>,
+    timeout_secs: Option<u64>,
+    bypass_cache: Option<bool>,
+) -> Result<Vec<ChatChunk>, AppError> {
+    let video_id = session.resolve(video_id).map_err(AppError::validation)?;
+    println!(
+        "🦀 Rust: process_query_range called for {} [{}, {}) ms",
+        video_id, start_ms, end_ms
+    );
+    let range = query::parse_range(start_ms, end_ms).map_err(AppError::validation)?;
+
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    let pending = IncompleteResponse {
+        request_id: sequence::new_request_id(),
+        query,
+        query_type: query_type.unwrap_or_default(),
+        attachments: attachments.unwrap_or_default(),
+        range: Some(range),
+        started_at: now,
+        updated_at: now,
+        chunks: Vec::new(),
+        error: None,
+    };
+    let lookup = cache.lookup(
+        &transport.endpoint(),
+        &library,
+        &history,
+        &video_id,
+        &pending,
+        bypass_cache.unwrap_or(false),
+    );
+    let answer = run_query(
+        &transport,
+        &history,
+        &transforms,
+        &shutdown,
+        &video_id,
+        pending,
+        Some(lookup),
+        |_| {},
+    );
+    let responses = crate::transport::with_timeout(timeout_override(timeout_secs), answer).await?;
+    responses::chat_chunks(responses).map_err(AppError::internal)
+}
+
+/// Override the model parameters for `video_id`'s chat
+///
+/// Unset fields use the backend defaults; sending all of them unset
+/// removes the overrides. They apply from the next question on.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_session_params(
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+    params: SessionParams,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: set_session_params called for {}: {:?}",
+        video_id, params
+    );
+    params::validate(&params).map_err(AppError::validation)?;
+
+    history
+        .set_session_params(&video_id, params.clone())
+        .map_err(AppError::storage)?;
+    serde_json::to_value(params)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// The model parameter overrides for `video_id`'s chat (all unset if none)
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_session_params(
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    let params = history.session_params(&video_id).unwrap_or_default();
+    serde_json::to_value(params)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Make `video_id` the active session (`null` clears it)
+///
+/// Chat commands called without a `video_id` then use it. Returns
+/// `{ video_id, previous }`; a change is also emitted as
+/// `video-analyzer://active-session`.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_active_session<R: Runtime>(
+    app: AppHandle<R>,
+    session: tauri::State<'_, ActiveSession>,
+    video_id: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: set_active_session called with {:?}", video_id);
+    let previous = session.set(&app, video_id);
+    Ok(serde_json::json!({ "video_id": session.get(), "previous": previous }))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_active_session(
+    session: tauri::State<'_, ActiveSession>,
+) -> Result<Value, AppError> {
+    Ok(serde_json::json!({ "video_id": session.get() }))
+}
+
+/// Answers from `video_id`'s chat whose stream broke off, oldest first
+///
+/// `content` is the partial answer; `error` is null when the app closed
+/// while the answer was still streaming.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_incomplete_responses(
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_incomplete_responses called for {}", video_id);
+
+    let responses: Vec<Value> = history
+        .incomplete(&video_id)
+        .into_iter()
+        .map(|r| {
+            let content = serde_json::to_value(&r.chunks)
+                .map(|chunks| final_content(&chunks))
+                .unwrap_or_default();
+            serde_json::json!({
+                "request_id": r.request_id,
+                "query": r.query,
+                "query_type": r.query_type,
+                "attachments": r.attachments,
+                "range": r.range,
+                "started_at": r.started_at,
+                "updated_at": r.updated_at,
+                "chunks_received": r.chunks.len(),
+                "content": content,
+                "error": r.error,
+            })
+        })
+        .collect();
+    Ok(Value::Array(responses))
+}
+
+/// Ask an interrupted question again
+///
+/// The request reuses its `request_id`, so a backend that can resume the
+/// stream only sends what is missing; the response then holds the whole
+/// answer, as from `process_query`.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn retry_incomplete_response(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    transforms: tauri::State<'_, postprocess::Pipeline>,
+    shutdown: tauri::State<'_, Shutdown>,
+    video_id: String,
+    request_id: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: retry_incomplete_response called for {}: {}",
+        video_id, request_id
+    );
+
+    let pending = history
+        .incomplete(&video_id)
+        .into_iter()
+        .find(|r| r.request_id == request_id)
+        .ok_or_else(|| format!("No incomplete response {} for {}", request_id, video_id))
+        .map_err(AppError::validation)?;
+    run_query(
+        &transport,
+        &history,
+        &transforms,
+        &shutdown,
+        &video_id,
+        pending,
+        None,
+        |_| {},
+    )
+    .await
+}
+
+/// Questions queued while the backend was unreachable, oldest first, see
+/// [`outbox`]
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_outbox(
+    outbox: tauri::State<'_, Outbox>,
+) -> Result<Vec<OutboxMessage>, AppError> {
+    println!("🦀 Rust: get_outbox called");
+    Ok(outbox.list())
+}
+
+/// Take a queued question back so it is never sent
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn discard_outbox_message(
+    outbox: tauri::State<'_, Outbox>,
+    id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: discard_outbox_message called for {}", id);
+    if !outbox.remove(&id).map_err(AppError::storage)? {
+        return Err(AppError::validation(format!("No queued message {}", id)));
+    }
+    Ok(serde_json::json!({ "discarded": id }))
+}
+
+/// Send the queued questions now rather than waiting for the health
+/// monitor to see the backend come back
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn flush_outbox<R: Runtime>(
+    app: AppHandle<R>,
+    outbox: tauri::State<'_, Outbox>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: flush_outbox called");
+    let queued = outbox.list().len();
+    if queued > 0 {
+        outbox::spawn_flush(app.clone());
+    }
+    Ok(serde_json::json!({ "queued": queued }))
+}
+
+/// Minimum time between saves of a streaming answer
+const INCOMPLETE_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Send a question and record the exchange in the local history
+///
+/// The answer is kept as an [`IncompleteResponse`] while it streams, and
+/// stays one if the stream breaks off. `forward` gets each chunk as it is
+/// ready, annotated and post-processed like the returned array, with the
+/// [`a11y`] fields. With a
+/// `cache` lookup, a stored answer is used instead of asking the backend,
+/// and a complete answer is stored. A [`crate::shutdown`] cuts the stream off
+/// like a broken one.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn run_query(
+    transport: &Transport,
+    history: &LocalHistory,
+    transforms: &postprocess::Pipeline,
+    shutdown: &Shutdown,
+    video_id: &str,
+    mut pending: IncompleteResponse,
+    cache: Option<query_cache::Lookup<'_>>,
+    mut forward: impl FnMut(&[Value]),
+) -> Result<Value, AppError> {
+    let mode = query::QueryMode::parse(&pending.query_type).map_err(AppError::validation)?;
+    let _stream = shutdown.track_stream().map_err(AppError::cancelled)?;
+
+    if let Some(responses) = cache.as_ref().and_then(|cache| cache.get()) {
+        info!("process_query: answering from the cache for {}", video_id);
+        if let Some(items) = responses.as_array() {
+            forward(items);
+        }
+        record_exchange(
+            history,
+            video_id,
+            pending.query,
+            Vec::new(),
+            pending.range,
+            &responses,
+            true,
+        );
+        return Ok(responses);
+    }
+
+    let paths = &pending.attachments;
+    if paths.len() > attachments::MAX_ATTACHMENTS {
+        return Err(AppError::validation(format!(
+            "Too many attachments ({}); at most {} per question",
+            paths.len(),
+            attachments::MAX_ATTACHMENTS
+        )));
+    }
+    let mut loaded = Vec::with_capacity(paths.len());
+    for path in paths {
+        loaded.push(
+            attachments::load_attachment(path)
+                .await
+                .map_err(AppError::validation)?,
+        );
+    }
+    let attachment_infos: Vec<_> = loaded.iter().map(attachments::info).collect();
+
+    let request = ChatRequest {
+        message: pending.query.clone(),
+        file_id: video_id.to_string(),
+        context: String::new(), // Empty context for now
+        query_type: mode.to_proto() as i32,
+        attachments: loaded,
+        request_id: pending.request_id.clone(),
+        params: history.session_params(video_id),
+        range: pending.range.clone(),
+    };
+
+    let stream = transport
+        .send_chat_message(request)
+        .await
+        .map_err(AppError::from)?;
+
+    // Save the answer as it streams so a broken stream survives a refresh
+    let save = |pending: &mut IncompleteResponse| {
+        pending.updated_at = files::unix_seconds(std::time::SystemTime::now());
+        if let Err(e) = history.set_incomplete(video_id, pending.clone()) {
+            warn!("process_query: failed to save partial answer: {}", e);
+        }
+    };
+    save(&mut pending);
+    let mut last_saved = std::time::Instant::now();
+    let mut forwarded = 0;
+    let mut forward_new = |chunks: &[ChatResponse]| {
+        if let Ok(mut batch) = serde_json::to_value(&chunks[forwarded..]) {
+            annotate_results(&mut batch, mode);
+            if let Some(batch) = batch.as_array_mut() {
+                transforms.apply(batch);
+                a11y::annotate(batch);
+                forward(batch);
+            }
+        }
+        forwarded = chunks.len();
+    };
+    let received = std::mem::take(&mut pending.chunks);
+    let cancel = shutdown.token();
+    let (chunks, interrupted) = collect_chat_stream(stream, received, &cancel, |chunks| {
+        forward_new(chunks);
+        if last_saved.elapsed() >= INCOMPLETE_SAVE_INTERVAL {
+            pending.chunks = chunks.to_vec();
+            save(&mut pending);
+            last_saved = std::time::Instant::now();
+        }
+    })
+    .await;
+
+    if let Some(error) = &interrupted {
+        pending.chunks = chunks.clone();
+        pending.error = Some(error.clone());
+        save(&mut pending);
+    }
+    // Chunks flushed at the end of the stream
+    forward_new(&chunks);
+    let mut responses = chat_value(chunks, interrupted.clone()).map_err(AppError::internal)?;
+    annotate_results(&mut responses, mode);
+    if let Some(items) = responses.as_array_mut() {
+        transforms.apply(items);
+        a11y::annotate(items);
+    }
+    if interrupted.is_some() {
+        if let Some(error_chunk) = responses.as_array().and_then(|items| items.last()) {
+            forward(std::slice::from_ref(error_chunk));
+        }
+        // Recorded once a retry completes it
+        return Ok(responses);
+    }
+    if let Err(e) = history.remove_incomplete(video_id, &pending.request_id) {
+        warn!("process_query: failed to drop partial answer: {}", e);
+    }
+    if let Some(cache) = &cache {
+        cache.put(&responses);
+    }
+
+    record_exchange(
+        history,
+        video_id,
+        pending.query,
+        attachment_infos,
+        pending.range,
+        &responses,
+        false,
+    );
+    Ok(responses)
+}
+
+/// Local record of an answered question, including what was attached and
+/// whether the answer came from the cache
+fn record_exchange(
+    history: &LocalHistory,
+    video_id: &str,
+    query: String,
+    attachments: Vec<AttachmentInfo>,
+    range: Option<TimeRange>,
+    responses: &Value,
+    cached: bool,
+) {
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    let exchange = [
+        LocalMessage {
+            role: "user".to_string(),
+            content: query,
+            timestamp: now,
+            attachments,
+            range,
+            feedback: None,
+            pinned_at: None,
+            cached,
+        },
+        LocalMessage {
+            role: "assistant".to_string(),
+            content: final_content(responses),
+            timestamp: now,
+            attachments: Vec::new(),
+            range: None,
+            feedback: None,
+            pinned_at: None,
+            cached,
+        },
+    ];
+    for message in exchange {
+        if let Err(e) = history.append(video_id, message) {
+            warn!("process_query: failed to record local history: {}", e);
+        }
+    }
+}
+
+/// The answer to show for a chat stream: the last RESULT, else the last non-empty chunk
+fn final_content(responses: &Value) -> String {
+    use video_analyzer::chat_response::ResponseType;
+
+    fn content(item: &Value) -> Option<&str> {
+        item["content"].as_str().filter(|c| !c.trim().is_empty())
+    }
+
+    let items = responses.as_array().map(Vec::as_slice).unwrap_or_default();
+    items
+        .iter()
+        .rev()
+        .filter(|item| item["type"] == ResponseType::Result as i32)
+        .find_map(content)
+        .or_else(|| items.iter().rev().find_map(content))
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The spoken language of the video at `file_path`, as detected locally
+///
+/// Returns `{ available: false }` when whisper is not installed, and a
+/// null `language` when there is too little audio to tell. Registering
+/// and uploading detect it on their own; this is for showing it earlier.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn detect_language(
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    file_path: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: detect_language called for {}", file_path);
+    if !media::language::available() {
+        return Ok(serde_json::json!({ "available": false, "language": null }));
+    }
+    let path = files::normalize_path(&file_path).map_err(AppError::validation)?;
+    let language = media::language::detect(&pipeline, &path)
+        .await
+        .map_err(AppError::internal)?;
+    Ok(serde_json::json!({ "available": true, "language": language }))
+}
+
+/// Check a file before it is attached, so the UI can show it (or the
+/// reason it was refused) as soon as it is picked
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn inspect_attachment(file_path: String) -> Result<Value, AppError> {
+    println!("🦀 Rust: inspect_attachment called with {}", file_path);
+
+    let attachment = attachments::load_attachment(&file_path)
+        .await
+        .map_err(AppError::validation)?;
+    serde_json::to_value(attachments::info(&attachment))
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Rate an assistant answer from the local history
+///
+/// The rating is always stored locally; forwarding it to the backend is
+/// best effort, and `sent` in the response says whether that worked.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn rate_response(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+    message_index: usize,
+    rating: String,
+    comment: Option<String>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: rate_response called for {}[{}]: {}",
+        video_id, message_index, rating
+    );
+
+    let rating = Rating::parse(&rating).map_err(AppError::validation)?;
+    let messages = history.messages(&video_id);
+    let answer = messages
+        .get(message_index)
+        .filter(|m| m.role == "assistant")
+        .ok_or_else(|| {
+            format!(
+                "No assistant message {} for video {}",
+                message_index, video_id
+            )
+        })
+        .map_err(AppError::validation)?;
+    let question = messages[..message_index]
+        .iter()
+        .rev()
+        .find(|m| m.role == "user")
+        .map(|m| m.content.clone())
+        .unwrap_or_default();
+
+    let feedback = Feedback {
+        rating,
+        comment: comment.unwrap_or_default(),
+        rated_at: files::unix_seconds(std::time::SystemTime::now()),
+        sent: false,
+    };
+    history
+        .update(&video_id, message_index, |m| {
+            m.feedback = Some(feedback.clone())
+        })
+        .map_err(AppError::storage)?;
+
+    let request = FeedbackRequest {
+        video_id: video_id.clone(),
+        message_index: message_index as i32,
+        rating: rating.to_proto() as i32,
+        comment: feedback.comment.clone(),
+        question,
+        answer: answer.content.clone(),
+        rated_at: feedback.rated_at,
+    };
+    let sent = transport
+        .submit_feedback(request)
+        .await
+        .map(|r| r.success)
+        .map_err(|e| AppError::rpc("SubmitFeedback", e));
+    let error = match &sent {
+        Ok(true) => {
+            history
+                .update(&video_id, message_index, |m| {
+                    if let Some(f) = m.feedback.as_mut() {
+                        f.sent = true;
+                    }
+                })
+                .map_err(AppError::storage)?;
+            None
+        }
+        Ok(false) => Some("Backend rejected the feedback".to_string()),
+        Err(e) => Some(e.to_string()),
+    };
+    if let Some(e) = &error {
+        warn!("rate_response: stored locally only: {}", e);
+    }
+
+    Ok(serde_json::json!({
+        "stored": true,
+        "sent": error.is_none(),
+        "error": error,
+    }))
+}
+
+/// The cached transcript, fetching it from the backend on first use, and
+/// whether it came from the cache
+///
+/// Check `available`: the backend may have no transcript for the video
+/// yet (analysis still running, or transcription disabled).
+async fn load_transcript(
+    transport: &Transport,
+    cache: &transcript::TranscriptCache,
+    video_id: &str,
+    refresh: bool,
+) -> Result<(transcript::CachedTranscript, bool), AppError> {
+    if !refresh {
+        if let Some(cached) = cache.get(video_id) {
+            return Ok((cached, true));
+        }
+    }
+    let response = transport
+        .get_transcript(TranscriptRequest {
+            video_id: video_id.to_string(),
+        })
+        .await
+        .map_err(|e| AppError::rpc("GetTranscript", e))?;
+    let cached = transcript::CachedTranscript {
+        fetched_at: files::unix_seconds(std::time::SystemTime::now()),
+        transcript: response,
+    };
+    if cached.transcript.available {
+        if let Err(e) = cache.put(video_id, &cached) {
+            warn!("get_transcript: failed to cache transcript: {}", e);
+        }
+    }
+    Ok((cached, false))
+}
+
+/// The timestamped transcript of a video, from the local cache if present
+///
+/// `refresh` fetches it again; `start_ms` / `end_ms` keep only the segments
+/// overlapping that window, e.g. to pick context for a question.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_transcript(
+    transport: tauri::State<'_, Transport>,
+    cache: tauri::State<'_, transcript::TranscriptCache>,
+    video_id: String,
+    refresh: Option<bool>,
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_transcript called for {}", video_id);
+
+    let (cached, from_cache) =
+        load_transcript(&transport, &cache, &video_id, refresh.unwrap_or(false)).await?;
+    let transcript = cached.transcript;
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "available": transcript.available,
+        "message": transcript.message,
+        "language": transcript.language,
+        "segments": transcript::in_range(&transcript.segments, start_ms, end_ms),
+        "total_segments": transcript.segments.len(),
+        "cached": from_cache,
+        "fetched_at": cached.fetched_at,
+    }))
+}
+
+/// Transcript segments containing `query` (case-insensitive), in time order
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn search_transcript(
+    transport: tauri::State<'_, Transport>,
+    cache: tauri::State<'_, transcript::TranscriptCache>,
+    video_id: String,
+    query: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: search_transcript called for {}: {:?}",
+        video_id, query
+    );
+
+    let (cached, _) = load_transcript(&transport, &cache, &video_id, false).await?;
+    if !cached.transcript.available {
+        return Err(AppError::validation(format!(
+            "No transcript for {} yet",
+            video_id
+        )));
+    }
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "query": query,
+        "matches": transcript::search(&cached.transcript.segments, &query),
+    }))
+}
+
+/// The transcript as subtitles, `srt` (default) or `vtt`
+///
+/// Returns the file contents and a suggested file name for the UI to save.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn export_subtitles(
+    transport: tauri::State<'_, Transport>,
+    cache: tauri::State<'_, transcript::TranscriptCache>,
+    library: tauri::State<'_, Library>,
+    video_id: String,
+    format: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: export_subtitles called for {}", video_id);
+
+    let format = transcript::SubtitleFormat::parse(format.as_deref().unwrap_or(""))
+        .map_err(AppError::validation)?;
+    let (cached, _) = load_transcript(&transport, &cache, &video_id, false).await?;
+    if !cached.transcript.available {
+        return Err(AppError::validation(format!(
+            "No transcript for {} yet",
+            video_id
+        )));
+    }
+    let stem = library
+        .get(&video_id)
+        .map(|e| e.display_name)
+        .and_then(|name| {
+            std::path::Path::new(&name)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| video_id.clone());
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "format": format.extension(),
+        "file_name": format!("{}.{}", offline::safe_file_name(&stem), format.extension()),
+        "content": transcript::to_subtitles(&cached.transcript.segments, format),
+    }))
+}
+
+/// One page of what was detected in a video, most confident first
+///
+/// Detections are fetched once and cached; `refresh` fetches them again.
+/// `filter` narrows by label, kind, confidence and time window (see
+/// [`detections::DetectionFilter`]); `page` is 0-based.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_detections(
+    transport: tauri::State<'_, Transport>,
+    cache: tauri::State<'_, detections::DetectionCache>,
+    video_id: String,
+    filter: Option<detections::DetectionFilter>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+    refresh: Option<bool>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: get_detections called for {}: {:?}",
+        video_id, filter
+    );
+
+    let cached = match cache.get(&video_id).filter(|_| !refresh.unwrap_or(false)) {
+        Some(cached) => cached,
+        None => {
+            let response = transport
+                .get_detections(DetectionsRequest {
+                    video_id: video_id.clone(),
+                })
+                .await
+                .map_err(|e| AppError::rpc("GetDetections", e))?;
+            if !response.available {
+                return Ok(serde_json::json!({
+                    "video_id": video_id,
+                    "available": false,
+                    "message": response.message,
+                }));
+            }
+            let cached = detections::CachedDetections {
+                fetched_at: files::unix_seconds(std::time::SystemTime::now()),
+                detections: response,
+            };
+            if let Err(e) = cache.put(&video_id, &cached) {
+                warn!("get_detections: failed to cache detections: {}", e);
+            }
+            cached
+        }
+    };
+
+    let page = detections::page(
+        &cached.detections.detections,
+        &filter.unwrap_or_default(),
+        page.unwrap_or(0),
+        page_size.unwrap_or(detections::DEFAULT_PAGE_SIZE),
+    );
+    let mut response = serde_json::to_value(page)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    response["video_id"] = serde_json::json!(video_id);
+    response["available"] = serde_json::json!(true);
+    response["fetched_at"] = serde_json::json!(cached.fetched_at);
+    Ok(response)
+}
+
+/// The video's scenes and events merged with the user's bookmarks
+///
+/// The backend part is cached after the first fetch (`refresh` fetches it
+/// again). Without it, e.g. offline or while analysis runs, the timeline
+/// holds just the bookmarks and `available` is false.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_timeline(
+    transport: tauri::State<'_, Transport>,
+    cache: tauri::State<'_, timeline::TimelineCache>,
+    bookmarks: tauri::State<'_, bookmarks::Bookmarks>,
+    video_id: String,
+    refresh: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_timeline called for {}", video_id);
+
+    let cached = match cache.get(&video_id).filter(|_| !refresh.unwrap_or(false)) {
+        Some(cached) => Ok(cached),
+        None => transport
+            .get_timeline(TimelineRequest {
+                video_id: video_id.clone(),
+            })
+            .await
+            .map(|response| {
+                let cached = timeline::CachedTimeline {
+                    fetched_at: files::unix_seconds(std::time::SystemTime::now()),
+                    timeline: response,
+                };
+                if cached.timeline.available {
+                    if let Err(e) = cache.put(&video_id, &cached) {
+                        warn!("get_timeline: failed to cache timeline: {}", e);
+                    }
+                }
+                cached
+            })
+            .map_err(|e| AppError::rpc("GetTimeline", e)),
+    };
+    let (events, available, message, fetched_at) = match cached {
+        Ok(cached) => (
+            cached.timeline.events,
+            cached.timeline.available,
+            cached.timeline.message,
+            Some(cached.fetched_at),
+        ),
+        Err(e) => {
+            warn!("get_timeline: {}", e);
+            (Vec::new(), false, e.to_string(), None)
+        }
+    };
+
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "available": available,
+        "message": message,
+        "fetched_at": fetched_at,
+        "items": timeline::merge(&events, &bookmarks.list(&video_id)),
+    }))
+}
+
+/// Longest a share link may stay valid: 30 days
+const MAX_SHARE_EXPIRY_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Ask the backend for a signed, read-only link to `video_id`'s results,
+/// for people without the app to open in a browser
+///
+/// The link expires after `expiry` seconds (the backend's default when
+/// unset, at most 30 days). Returns `{ video_id, url, expires_at }`; the
+/// frontend copies `url` to the clipboard.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn create_share_link(
+    transport: tauri::State<'_, Transport>,
+    video_id: String,
+    expiry: Option<u64>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: create_share_link called for {} (expiry {:?})",
+        video_id, expiry
+    );
+    if let Some(secs) = expiry {
+        if secs == 0 || secs > MAX_SHARE_EXPIRY_SECS {
+            return Err(AppError::validation(format!(
+                "Share link expiry must be between 1 and {} seconds",
+                MAX_SHARE_EXPIRY_SECS
+            )));
+        }
+    }
+
+    let inner = transport
+        .create_share_link(ShareLinkRequest {
+            video_id,
+            expires_in_secs: expiry.unwrap_or(0) as i64,
+        })
+        .await
+        .map_err(|e| AppError::rpc("CreateShareLink", e))?;
+    info!(
+        "create_share_link response: video_id={}, expires_at={}",
+        inner.video_id, inner.expires_at
+    );
+    serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// A running task past this long is flagged `stuck`: 30 minutes
+const DEFAULT_STUCK_AFTER_SECS: u64 = 30 * 60;
+
+/// The backend's pending and running analysis tasks and who they belong
+/// to, so users can see why their query is slow and admins can spot stuck
+/// work
+///
+/// Each task gains `waiting_secs` (since it was submitted), `running_secs`
+/// (since it started, running tasks only) and `stuck`, set on running
+/// tasks older than `stuck_after_secs` (30 minutes by default).
+/// `ahead_of_me` counts the tasks queued before the user's first pending
+/// one.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_backend_queue(
+    transport: tauri::State<'_, Transport>,
+    only_mine: Option<bool>,
+    stuck_after_secs: Option<u64>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: get_backend_queue called (only_mine {:?})",
+        only_mine
+    );
+    let inner = transport
+        .get_backend_queue(BackendQueueRequest {
+            only_mine: only_mine.unwrap_or(false),
+        })
+        .await
+        .map_err(|e| AppError::rpc("GetBackendQueue", e))?;
+
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    let stuck_after = stuck_after_secs.unwrap_or(DEFAULT_STUCK_AFTER_SECS) as f64;
+    let mut tasks = inner.tasks;
+    tasks.sort_by_key(|task| (task.state != "running", task.position));
+
+    let running = tasks.iter().filter(|t| t.state == "running").count();
+    let mine = tasks.iter().filter(|t| t.is_mine).count();
+    let ahead_of_me = tasks
+        .iter()
+        .filter(|t| t.state == "pending" && t.is_mine)
+        .map(|t| t.position)
+        .min()
+        .map(|first| {
+            tasks
+                .iter()
+                .filter(|t| t.state == "pending" && t.position < first)
+                .count()
+        });
+    let tasks: Vec<Value> = tasks
+        .into_iter()
+        .map(|task| {
+            let running_secs = (task.state == "running" && task.started_at > 0.0)
+                .then(|| (now - task.started_at).max(0.0));
+            let stuck = running_secs.is_some_and(|secs| secs > stuck_after);
+            let waiting_secs = (now - task.submitted_at).max(0.0);
+            let mut value = serde_json::to_value(task).unwrap_or_default();
+            value["waiting_secs"] = waiting_secs.into();
+            value["running_secs"] = running_secs.into();
+            value["stuck"] = stuck.into();
+            value
+        })
+        .collect();
+    let stuck = tasks.iter().filter(|t| t["stuck"] == true).count();
+    info!(
+        "get_backend_queue response: {} running, {} pending, {} stuck",
+        running,
+        tasks.len() - running,
+        stuck
+    );
+
+    Ok(serde_json::json!({
+        "tasks": tasks,
+        "workers": inner.workers,
+        "running": running,
+        "pending": tasks.len() - running,
+        "mine": mine,
+        "ahead_of_me": ahead_of_me,
+        "stuck": stuck,
+    }))
+}
+
+/// Stop the backend's pending and running analysis of `file_id`, e.g. a
+/// long video uploaded by mistake
+///
+/// Returns `{ success, message, cancelled_tasks }`; `cancelled_tasks` is 0
+/// when there was nothing left to stop. The video itself stays uploaded.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn cancel_processing(
+    transport: tauri::State<'_, Transport>,
+    file_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: cancel_processing called for {}", file_id);
+    if file_id.trim().is_empty() {
+        return Err(AppError::validation("file_id is required".to_string()));
+    }
+
+    let inner = transport
+        .cancel_processing(CancelProcessingRequest { video_id: file_id })
+        .await
+        .map_err(|e| AppError::rpc("CancelProcessing", e))?;
+    info!(
+        "cancel_processing response: success={}, cancelled_tasks={}",
+        inner.success, inner.cancelled_tasks
+    );
+    serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Delete `file_id` and its chat history on the backend, then everything
+/// kept of it locally, see [`deletion`]
+///
+/// Without `confirmation_token` nothing is deleted: the answer is
+/// `{ confirmation_required: true, confirmation_token, video_id,
+/// expires_in_secs }`, and calling again with that token deletes it.
+/// Then returns `{ success, message, deleted_messages, freed_bytes,
+/// local: { removed, errors } }`; local cleanup only runs once the backend
+/// reports success.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn delete_video<R: Runtime>(
+    app: AppHandle<R>,
+    transport: tauri::State<'_, Transport>,
+    confirmations: tauri::State<'_, deletion::DeleteConfirmations>,
+    quota: tauri::State<'_, QuotaTracker>,
+    file_id: String,
+    confirmation_token: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: delete_video called for {}", file_id);
+    let file_id = file_id.trim().to_string();
+    if file_id.is_empty() {
+        return Err(AppError::validation("file_id is required".to_string()));
+    }
+    let now = std::time::Instant::now();
+    let Some(token) = confirmation_token.filter(|t| !t.trim().is_empty()) else {
+        let confirmation = confirmations.issue(&file_id, now);
+        let mut answer = serde_json::json!(confirmation);
+        answer["confirmation_required"] = serde_json::json!(true);
+        return Ok(answer);
+    };
+    confirmations
+        .redeem(&token, &file_id, now)
+        .map_err(AppError::validation)?;
+
+    let inner = transport
+        .delete_video(DeleteVideoRequest {
+            video_id: file_id.clone(),
+        })
+        .await
+        .map_err(|e| AppError::rpc("DeleteVideo", e))?;
+    info!(
+        "delete_video response: success={}, deleted_messages={}, freed_bytes={}",
+        inner.success, inner.deleted_messages, inner.freed_bytes
+    );
+    let local = if inner.success {
+        quota.adjust(-inner.freed_bytes);
+        deletion::purge_local(&app, &file_id)
+    } else {
+        deletion::LocalCleanup::default()
+    };
+    let mut answer = serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    answer["local"] = serde_json::json!(local);
+    Ok(answer)
+}
+
+/// Give `video_id` a new display name, on the backend and everywhere it
+/// is shown from locally: the library, the session mirror and an offline
+/// copy
+///
+/// Returns `{ success, message, display_name }` with the name the backend
+/// kept; local copies only change once it reports success.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn rename_video(
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    sessions: tauri::State<'_, SessionCache>,
+    offline: tauri::State<'_, OfflineCache>,
+    video_id: String,
+    display_name: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: rename_video called for {}", video_id);
+    let video_id = video_id.trim().to_string();
+    if video_id.is_empty() {
+        return Err(AppError::validation("video_id is required".to_string()));
+    }
+    let display_name = library::display_name(&display_name).map_err(AppError::validation)?;
+
+    let mut inner = transport
+        .update_video_metadata(UpdateVideoMetadataRequest {
+            video_id: video_id.clone(),
+            display_name: display_name.clone(),
+        })
+        .await
+        .map_err(|e| AppError::rpc("UpdateVideoMetadata", e))?;
+    info!(
+        "rename_video response: success={}, display_name={}",
+        inner.success, inner.display_name
+    );
+    if inner.success {
+        if inner.display_name.is_empty() {
+            inner.display_name = display_name;
+        }
+        let name = inner.display_name.as_str();
+        library
+            .update(&video_id, |entry| entry.display_name = name.to_string())
+            .map_err(AppError::storage)?;
+        if let Err(e) = sessions.rename_video(&video_id, name) {
+            warn!("rename_video: failed to update the session mirror: {}", e);
+        }
+        if let Err(e) = offline.rename(&video_id, name) {
+            warn!("rename_video: failed to update the offline copy: {}", e);
+        }
+    }
+    serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// The text subtitle tracks embedded in a library video, or in the file
+/// at a path, as SRT or ASS
+///
+/// Returns `{ tracks, skipped }`: each track's text with its language and
+/// title, and the bitmap or unreadable ones with why. With `forward` the
+/// tracks also go to the backend with AddCaptions, so it can skip
+/// transcribing; that needs the video to be in the library, and the
+/// backend's answer comes back in `forwarded`.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn extract_subtitles(
+    transport: tauri::State<'_, Transport>,
+    library: tauri::State<'_, Library>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    video_id_or_path: String,
+    forward: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: extract_subtitles called for {}", video_id_or_path);
+    let (video_id, video) = match library.get(video_id_or_path.trim()) {
+        Some(entry) => (Some(entry.file_id.clone()), entry.path()),
+        None => {
+            let canonical = files::resolve_path(
+                &files::normalize_path(&video_id_or_path).map_err(AppError::validation)?,
+            )
+            .map_err(AppError::validation)?
+            .canonical;
+            // Compared by canonical path, the way the library records them
+            let recorded = canonical.to_string_lossy();
+            let video_id = library
+                .entries()
+                .into_iter()
+                .find(|entry| entry.canonical_path == recorded)
+                .map(|entry| entry.file_id);
+            (video_id, canonical)
+        }
+    };
+    if !video.is_file() {
+        return Err(AppError::validation(format!(
+            "{} is missing; relink the video first",
+            video.display()
+        )));
+    }
+    let forward_to = if forward.unwrap_or(false) {
+        Some(video_id.ok_or_else(|| {
+            AppError::validation(format!(
+                "{} is not in the library; register it before forwarding its subtitles",
+                video.display()
+            ))
+        })?)
+    } else {
+        None
+    };
+
+    let probe = media::probe::probe(&video)
+        .await
+        .map_err(AppError::validation)?;
+    let mut tracks = Vec::new();
+    let mut skipped = Vec::new();
+    for stream in &probe.subtitles {
+        match media::subtitles::extract(&pipeline, &video, stream).await {
+            Ok(track) => tracks.push(track),
+            Err(reason) => {
+                warn!(
+                    "extract_subtitles: skipping stream {} of {}: {}",
+                    stream.index,
+                    video.display(),
+                    reason
+                );
+                skipped.push(media::subtitles::SkippedTrack {
+                    index: stream.index,
+                    codec: stream.codec.clone(),
+                    reason,
+                });
+            }
+        }
+    }
+    info!(
+        "extract_subtitles: {} tracks, {} skipped in {}",
+        tracks.len(),
+        skipped.len(),
+        video.display()
+    );
+
+    let mut answer = serde_json::json!({ "tracks": tracks, "skipped": skipped });
+    if let Some(video_id) = forward_to.filter(|_| !tracks.is_empty()) {
+        let captions = tracks
+            .iter()
+            .map(|track| Caption {
+                language: track.language.clone().unwrap_or_default(),
+                format: track.format.name().to_string(),
+                text: track.text.clone(),
+                title: track.title.clone().unwrap_or_default(),
+            })
+            .collect();
+        let response = transport
+            .add_captions(AddCaptionsRequest { video_id, captions })
+            .await
+            .map_err(|e| AppError::rpc("AddCaptions", e))?;
+        info!(
+            "extract_subtitles: backend kept {} captions: {}",
+            response.captions_kept, response.message
+        );
+        answer["forwarded"] = serde_json::to_value(response)
+            .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    }
+    Ok(answer)
+}
+
+/// Multi-stage operations still running, as last sent on
+/// `video-analyzer://operation`
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_operations(
+    operations: tauri::State<'_, operations::Operations>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_operations called");
+    serde_json::to_value(operations.list())
+        .map_err(|e| AppError::internal(format!("Failed to serialize operations: {}", e)))
+}
+
+/// Local ffmpeg jobs that are queued or running, oldest first
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_media_jobs(
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_media_jobs called");
+    serde_json::to_value(pipeline.jobs())
+        .map_err(|e| AppError::internal(format!("Failed to serialize jobs: {}", e)))
+}
+
+/// Kill a local ffmpeg job; the command that started it then fails
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn cancel_media_job(
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    job_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: cancel_media_job called for {}", job_id);
+    let cancelled = pipeline.cancel(&job_id);
+    Ok(serde_json::json!({ "job_id": job_id, "cancelled": cancelled }))
+}
+
+/// Most frames `export_frames` puts on one contact sheet
+const MAX_EXPORT_FRAMES: usize = 48;
+
+/// Extract frames of a registered video and write them as a contact sheet
+///
+/// Each frame is captioned with its timestamp; `dest` ending in `.png` or
+/// `.pdf` picks the format. Runs locally with ffmpeg, so it works offline.
+/// Reported as an `export_frames` operation whose id is returned.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn export_frames<R: Runtime>(
+    app: AppHandle<R>,
+    library: tauri::State<'_, Library>,
+    pipeline: tauri::State<'_, media::pipeline::Pipeline>,
+    video_id: String,
+    timestamps: Vec<i64>,
+    dest: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: export_frames called for {}: {} frame(s) -> {}",
+        video_id,
+        timestamps.len(),
+        dest
+    );
+
+    if timestamps.is_empty() || timestamps.len() > MAX_EXPORT_FRAMES {
+        return Err(AppError::validation(format!(
+            "Pick between 1 and {} frames, got {}",
+            MAX_EXPORT_FRAMES,
+            timestamps.len()
+        )));
+    }
+    let dest = std::path::PathBuf::from(dest);
+    let format = media::sheet::SheetFormat::from_path(&dest).map_err(AppError::validation)?;
+    let video = library
+        .get(&video_id)
+        .ok_or_else(|| format!("{} isn't a video registered on this machine", video_id))
+        .map_err(AppError::validation)?
+        .path();
+    if !video.is_file() {
+        return Err(AppError::validation(format!(
+            "{} is missing; relink the video first",
+            video.display()
+        )));
+    }
+
+    let operation_id = uuid::Uuid::new_v4().to_string();
+    let operation = operations::begin(
+        &app,
+        &operation_id,
+        "export_frames",
+        &["extract", "compose", "write"],
+    );
+    operation.start("extract");
+    let extracted = operation.progress_fn("extract");
+    let mut frames = Vec::with_capacity(timestamps.len());
+    for &timestamp_ms in &timestamps {
+        let frame = operation
+            .check(
+                media::frame_rgb(
+                    &pipeline,
+                    &video,
+                    timestamp_ms,
+                    media::sheet::CELL_WIDTH,
+                    media::sheet::CELL_HEIGHT,
+                )
+                .await,
+            )
+            .map_err(AppError::internal)?;
+        frames.push((timestamp_ms, frame));
+        extracted(frames.len() as f64 / timestamps.len() as f64);
+    }
+    operation.start("compose");
+    let sheet = media::sheet::compose(&frames);
+    let bytes = operation
+        .check(match format {
+            media::sheet::SheetFormat::Png => media::sheet::to_png(&sheet),
+            media::sheet::SheetFormat::Pdf => media::sheet::to_pdf(&sheet),
+        })
+        .map_err(AppError::internal)?;
+    operation.start("write");
+    operation.check(
+        tokio::fs::write(&dest, &bytes)
+            .await
+            .map_err(|e| AppError::io(format!("Failed to write {}", dest.display()), e)),
+    )?;
+    operation.finish();
+
+    Ok(serde_json::json!({
+        "operation_id": operation_id,
+        "path": dest.to_string_lossy(),
+        "frames": timestamps.len(),
+        "width": sheet.width,
+        "height": sheet.height,
+        "size_bytes": bytes.len(),
+    }))
+}
+
+/// Bookmark a moment, or with `end_ms` a span, of a video
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn add_bookmark(
+    bookmarks: tauri::State<'_, bookmarks::Bookmarks>,
+    video_id: String,
+    start_ms: i64,
+    end_ms: Option<i64>,
+    label: String,
+    note: Option<String>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: add_bookmark called for {} at {} ms",
+        video_id, start_ms
+    );
+
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(AppError::validation("A bookmark needs a label".to_string()));
+    }
+    let bookmark = bookmarks::Bookmark {
+        id: uuid::Uuid::new_v4().to_string(),
+        start_ms,
+        end_ms,
+        label: label.to_string(),
+        note: note.unwrap_or_default(),
+        created_at: files::unix_seconds(std::time::SystemTime::now()),
+    };
+    bookmarks
+        .check(&video_id, &bookmark)
+        .map_err(AppError::validation)?;
+    bookmarks
+        .add(&video_id, bookmark.clone())
+        .map_err(AppError::storage)?;
+    serde_json::to_value(bookmark)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn remove_bookmark(
+    bookmarks: tauri::State<'_, bookmarks::Bookmarks>,
+    video_id: String,
+    bookmark_id: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: remove_bookmark called for {}: {}",
+        video_id, bookmark_id
+    );
+
+    if !bookmarks
+        .remove(&video_id, &bookmark_id)
+        .map_err(AppError::storage)?
+    {
+        return Err(AppError::validation(format!(
+            "No bookmark {} on {}",
+            bookmark_id, video_id
+        )));
+    }
+    Ok(serde_json::json!({ "removed": bookmark_id }))
+}
+
+/// The locally recorded exchanges for a video, for display and export
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn export_local_history(
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: export_local_history called for video_id: {}",
+        video_id
+    );
+
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "messages": history.messages(&video_id),
+        "pinned": history.pinned(&video_id),
+    }))
+}
+
+/// Pin (or with `pinned: false`, unpin) a message as a key finding
+///
+/// Pins are kept in the local history and listed in its export.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn pin_message(
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+    message_index: usize,
+    pinned: Option<bool>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: pin_message called for {}[{}]",
+        video_id, message_index
+    );
+
+    let pinned_at = pinned
+        .unwrap_or(true)
+        .then(|| files::unix_seconds(std::time::SystemTime::now()));
+    let message = history
+        .update(&video_id, message_index, |m| {
+            // Re-pinning keeps the original time, and so its place in the list
+            m.pinned_at = pinned_at.and(m.pinned_at).or(pinned_at);
+        })
+        .map_err(AppError::storage)?
+        .ok_or_else(|| format!("No message {} for video {}", message_index, video_id))
+        .map_err(AppError::validation)?;
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "message_index": message_index,
+        "pinned_at": message.pinned_at,
+    }))
+}
+
+/// `video_id`'s pinned messages, in the order they were pinned
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn list_pinned(
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: list_pinned called for {}", video_id);
+    Ok(serde_json::json!({
+        "video_id": video_id,
+        "pinned": history.pinned(&video_id),
+    }))
+}
+
+/// Archive the whole local workspace (settings, library, history,
+/// bookmarks, caches) to `dest`, e.g. to move it to another machine
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn backup_workspace<R: Runtime>(
+    app: AppHandle<R>,
+    dest: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: backup_workspace called for {}", dest);
+    let data_dir = app.state::<Accounts>().workspace().to_path_buf();
+    let dest_path = std::path::PathBuf::from(&dest);
+    let manifest = tokio::task::spawn_blocking(move || backup::backup(&data_dir, &dest_path))
+        .await
+        .map_err(|e| format!("Backup failed: {}", e))
+        .map_err(AppError::internal)?
+        .map_err(AppError::storage)?;
+    info!(
+        "backup_workspace: wrote {} entries to {}",
+        manifest.entries.len(),
+        dest
+    );
+    Ok(serde_json::json!({ "dest": dest, "manifest": manifest }))
+}
+
+/// Restore a workspace archived by `backup_workspace`
+///
+/// Refuses backups from a newer schema. The restore replaces everything
+/// local and takes effect on the next start, so `restart_required` is
+/// always true; the frontend should restart the app.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn restore_workspace<R: Runtime>(
+    app: AppHandle<R>,
+    src: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: restore_workspace called for {}", src);
+    let data_dir = app.state::<Accounts>().workspace().to_path_buf();
+    let src_path = std::path::PathBuf::from(&src);
+    let manifest = tokio::task::spawn_blocking(move || backup::stage_restore(&src_path, &data_dir))
+        .await
+        .map_err(|e| format!("Restore failed: {}", e))
+        .map_err(AppError::internal)?
+        .map_err(AppError::storage)?;
+    info!(
+        "restore_workspace: staged {} entries from {} (schema {})",
+        manifest.entries.len(),
+        src,
+        manifest.schema_version
+    );
+    Ok(serde_json::json!({ "manifest": manifest, "restart_required": true }))
+}
+
+/// Tag RESULT chunks with the query mode, whether `result_json` has the
+/// schema that mode promises, and the typed `result` parsed from it, so the
+/// UI knows which renderer to use and never parses `result_json` itself
+fn annotate_results(responses: &mut Value, mode: query::QueryMode) {
+    use video_analyzer::chat_response::ResponseType;
+
+    let Some(items) = responses.as_array_mut() else {
+        return;
+    };
+    for item in items {
+        if item["type"] != ResponseType::Result as i32 {
+            continue;
+        }
+        let result_json = item["result_json"].as_str().unwrap_or("").to_string();
+        let schema_ok = mode.result_matches(&result_json);
+        if !schema_ok {
+            warn!(
+                "process_query: RESULT does not match the {:?} schema (expected key {:?})",
+                mode,
+                mode.expected_result_key()
+            );
+        }
+        item["query_type"] = serde_json::json!(mode);
+        item["schema_ok"] = serde_json::json!(schema_ok);
+        match results::parse_result(&result_json) {
+            Ok(parsed) => item["result"] = serde_json::json!(parsed),
+            Err(e) => {
+                warn!("process_query: {}", e);
+                item["result_error"] = serde_json::json!(e);
+            }
+        }
+    }
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_last_session(
+    transport: tauri::State<'_, Transport>,
+    mirror: tauri::State<'_, SessionCache>,
+) -> Result<SessionInfo, AppError> {
+    println!("🦀 Rust: get_last_session called");
+
+    let inner = match transport.get_last_session().await {
+        Ok(inner) => inner,
+        Err(e) => {
+            // The last session stays browsable without the backend
+            if let Some((mut session, fetched_at)) = mirror.last_session() {
+                warn!("get_last_session: {}; using the local mirror", e);
+                session.offline = true;
+                session.cached_at = Some(fetched_at);
+                return Ok(session);
+            }
+            return Err(AppError::from(e));
+        }
+    };
+
+    info!(
+        "get_last_session response: has_session={}, video_id={:?}, video_name={:?}",
+        inner.has_session, inner.video_id, inner.video_name
+    );
+    let session = SessionInfo::from(inner);
+    if let Err(e) = mirror.put_session(&session) {
+        warn!("get_last_session: failed to mirror the session: {}", e);
+    }
+    Ok(session)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_chat_history(
+    transport: tauri::State<'_, Transport>,
+    offline: tauri::State<'_, OfflineCache>,
+    mirror: tauri::State<'_, SessionCache>,
+    video_id: String,
+    include_full_messages: bool,
+) -> Result<ChatHistory, AppError> {
+    println!(
+        "🦀 Rust: get_chat_history called for video_id: {}, include_full: {}",
+        video_id, include_full_messages
+    );
+
+    let request = GetHistoryRequest {
+        video_id: video_id.clone(),
+        include_full_messages,
+    };
+
+    let inner = match transport.get_chat_history(request).await {
+        Ok(inner) => inner,
+        Err(e) => {
+            // Pinned videos stay browsable without the backend
+            if let Some(mut pinned) = offline.history(&video_id) {
+                warn!(
+                    "get_chat_history: {}; using offline copy of {}",
+                    e, video_id
+                );
+                pinned.offline = true;
+                return Ok(pinned);
+            }
+            if let Some((mut mirrored, fetched_at)) = mirror.history(&video_id) {
+                warn!(
+                    "get_chat_history: {}; using the local mirror of {}",
+                    e, video_id
+                );
+                if !include_full_messages {
+                    mirrored.recent_messages.clear();
+                }
+                mirrored.offline = true;
+                mirrored.cached_at = Some(fetched_at);
+                return Ok(mirrored);
+            }
+            return Err(AppError::from(e));
+        }
+    };
+
+    let summary_len = inner.conversation_summary.len();
+    let msgs_len = inner.recent_messages.len();
+    info!(
+        "get_chat_history response: video_id={:?}, summary_len={}, recent_messages_len={}",
+        inner.video_id, summary_len, msgs_len
+    );
+    let history = ChatHistory::from(inner);
+    if let Err(e) = mirror.put_history(&history) {
+        warn!("get_chat_history: failed to mirror the history: {}", e);
+    }
+    Ok(history)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn resume_session(
+    transport: tauri::State<'_, Transport>,
+    mirror: tauri::State<'_, SessionCache>,
+    video_id: String,
+) -> Result<SessionInfo, AppError> {
+    println!("🦀 Rust: resume_session called for video_id: {}", video_id);
+
+    let request = ResumeRequest { video_id };
+
+    let inner = transport
+        .resume_session(request)
+        .await
+        .map_err(AppError::from)?;
+
+    info!(
+        "resume_session response: success={}, video_id={:?}, video_name={:?}",
+        inner.success, inner.video_id, inner.video_name
+    );
+    let session = SessionInfo::from(inner);
+    if session.has_session {
+        if let Err(e) = mirror.put_session(&session) {
+            warn!("resume_session: failed to mirror the session: {}", e);
+        }
+    }
+    Ok(session)
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn clear_chat_history(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    mirror: tauri::State<'_, SessionCache>,
+    cache: tauri::State<'_, QueryCache>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: clear_chat_history called for video_id: {}",
+        video_id
+    );
+
+    history.clear(&video_id).map_err(AppError::storage)?;
+    // A cleared conversation shouldn't come back as cached answers
+    cache.clear_video(&video_id);
+    mirror
+        .forget_history(&video_id)
+        .map_err(AppError::storage)?;
+    let request = ClearHistoryRequest { video_id };
+
+    let inner = transport
+        .clear_chat_history(request)
+        .await
+        .map_err(AppError::from)?;
+    info!(
+        "clear_chat_history response: success={}, message={}",
+        inner.success, inner.message
+    );
+    serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_storage_usage(
+    transport: tauri::State<'_, Transport>,
+    quota: tauri::State<'_, QuotaTracker>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_storage_usage called");
+
+    let mut inner = transport
+        .get_storage_usage()
+        .await
+        .map_err(|e| AppError::rpc("GetStorageUsage", e))?;
+
+    quota.set(inner.quota_bytes, inner.total_bytes);
+    // Biggest consumers first, which is what the user is looking for
+    inner
+        .videos
+        .sort_by_key(|v| std::cmp::Reverse(v.total_bytes));
+    info!(
+        "get_storage_usage response: videos={}, total_bytes={}, available_bytes={}",
+        inner.videos.len(),
+        inner.total_bytes,
+        inner.available_bytes
+    );
+    let mut shaped = serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    if let Some(snapshot) = quota.get() {
+        shaped["quota"] = snapshot.to_json(false);
+    }
+    Ok(shaped)
+}
+
+/// Current quota and usage for the UI's usage meter
+///
+/// Asks the backend for fresh numbers; if it can't be reached, falls back
+/// to the locally tracked values with `stale: true`.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_quota(
+    transport: tauri::State<'_, Transport>,
+    quota: tauri::State<'_, QuotaTracker>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_quota called");
+
+    match transport.get_storage_usage().await {
+        Ok(inner) => {
+            quota.set(inner.quota_bytes, inner.total_bytes);
+            Ok(quota.get().unwrap_or_default().to_json(false))
+        }
+        Err(e) => {
+            warn!("get_quota: using cached quota: {}", e);
+            match quota.get() {
+                Some(snapshot) => Ok(snapshot.to_json(true)),
+                None => Err(AppError::rpc("GetStorageUsage", e)),
+            }
+        }
+    }
+}
+
+/// Delete remote videos registered before `older_than` ago and/or not
+/// used for `unused_for`, e.g. `older_than: "30d"`. With `dry_run`
+/// (the default) nothing is deleted and the report shows what would be.
+///
+/// What was kept locally for each deleted video goes as with
+/// `delete_video`; `local` reports it by video id.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn cleanup_videos<R: Runtime>(
+    app: AppHandle<R>,
+    transport: tauri::State<'_, Transport>,
+    quota: tauri::State<'_, QuotaTracker>,
+    older_than: Option<String>,
+    unused_for: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: cleanup_videos called: older_than={:?}, unused_for={:?}, dry_run={:?}",
+        older_than, unused_for, dry_run
+    );
+
+    // Never send an empty filter: the backend would match every video
+    if older_than.as_deref().unwrap_or("").trim().is_empty()
+        && unused_for.as_deref().unwrap_or("").trim().is_empty()
+    {
+        return Err(AppError::validation(
+            "cleanup_videos needs older_than and/or unused_for".to_string(),
+        ));
+    }
+
+    let now = std::time::SystemTime::now();
+    let request = CleanupRequest {
+        registered_before: cleanup::cutoff(older_than.as_deref(), now)
+            .map_err(AppError::validation)?,
+        accessed_before: cleanup::cutoff(unused_for.as_deref(), now)
+            .map_err(AppError::validation)?,
+        dry_run: dry_run.unwrap_or(true),
+    };
+
+    let inner = transport
+        .cleanup_videos(request)
+        .await
+        .map_err(|e| AppError::rpc("CleanupVideos", e))?;
+    info!(
+        "cleanup_videos response: dry_run={}, videos={}, freed_bytes={}",
+        inner.dry_run,
+        inner.videos.len(),
+        inner.freed_bytes
+    );
+    let mut local = serde_json::Map::new();
+    if !inner.dry_run {
+        quota.adjust(-inner.freed_bytes);
+        for video in &inner.videos {
+            let cleanup = deletion::purge_local(&app, &video.video_id);
+            local.insert(video.video_id.clone(), serde_json::json!(cleanup));
+        }
+    }
+    let mut answer = serde_json::to_value(inner)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    answer["local"] = Value::Object(local);
+    Ok(answer)
+}
+
+/// Authenticate to the backend with bearer `token` from now on
+///
+/// With `persist` (default true) the token is kept in the OS credential
+/// store for later runs; `persisted` is false if that store is unavailable.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_auth_token(
+    token: String,
+    persist: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: set_auth_token called");
+    let token = token.trim().to_string();
+    if token.is_empty() {
+        return Err(AppError::validation(
+            "The auth token is empty; use clear_auth_token to remove it".to_string(),
+        ));
+    }
+    let persisted = crate::transport::auth::set_token(token, persist.unwrap_or(true));
+    Ok(serde_json::json!({ "persisted": persisted }))
+}
+
+/// Stop authenticating and remove the stored token
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn clear_auth_token() -> Result<Value, AppError> {
+    println!("🦀 Rust: clear_auth_token called");
+    crate::transport::auth::clear_token().map_err(AppError::internal)?;
+    Ok(serde_json::json!({ "cleared": true }))
+}
+
+/// Setup wizard: backends listening on this machine's usual ports
+///
+/// Each has `responds` (a video analyzer backend answered, rather than
+/// something else on the port) and `requires_auth`.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn detect_local_backend() -> Result<Value, AppError> {
+    println!("🦀 Rust: detect_local_backend called");
+    let backends = onboarding::detect_local_backends().await;
+    Ok(serde_json::json!({ "backends": backends }))
+}
+
+/// Setup wizard: try connecting to `url` without switching to it
+///
+/// Returns one check per step (url, reach, tls, auth, backend), each
+/// passed, failed or skipped after an earlier failure, so the wizard can
+/// show which setting to fix. `ok` is true when every step passed.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn test_connection(
+    url: String,
+    tls: Option<bool>,
+    token: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: test_connection called for {}", url);
+    let url = url.trim();
+    let tls = tls.unwrap_or_else(|| url.starts_with("https://"));
+    let token = token.as_deref().map(str::trim).filter(|t| !t.is_empty());
+    let checks = onboarding::test_connection(url, tls, token).await;
+    let ok = checks
+        .iter()
+        .all(|check| check.status == onboarding::CheckStatus::Passed);
+    Ok(serde_json::json!({ "ok": ok, "checks": checks }))
+}
+
+/// Setup wizard: save the chosen backend and switch to it
+///
+/// The profile is kept in `profile.json` and takes precedence over the
+/// plugin config on later starts (GRPC_SERVER_URL still wins). A `token`
+/// is stored as with `set_auth_token`. An `encryption_key`, 32 bytes in
+/// base64 shared with the backend, encrypts uploads to it; it is kept in
+/// the credential store, see `transport::crypto`.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn save_initial_profile<R: Runtime>(
+    app: AppHandle<R>,
+    server_url: String,
+    tls: Option<bool>,
+    token: Option<String>,
+    encryption_key: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: save_initial_profile called for {}", server_url);
+    let server_url = server_url.trim().to_string();
+    let upload_encryption_key = encryption_key
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty());
+    if let Some(key) = &upload_encryption_key {
+        crate::transport::crypto::ChunkCipher::new(key).map_err(AppError::validation)?;
+    }
+    let profile = crate::config::BackendProfile {
+        name: None,
+        tls: tls.unwrap_or_else(|| server_url.starts_with("https://")),
+        server_url,
+        upload_encryption_key,
+    };
+    let path = app
+        .state::<Accounts>()
+        .workspace()
+        .to_path_buf()
+        .join(onboarding::PROFILE_FILE);
+    onboarding::save_active_profile(&path, &profile).map_err(AppError::storage)?;
+    crate::config::set_profile(Some(profile.clone()));
+
+    let token_persisted = match token.map(|t| t.trim().to_string()) {
+        Some(token) if !token.is_empty() => Some(crate::transport::auth::set_token(token, true)),
+        _ => None,
+    };
+    app.state::<std::sync::Arc<crate::transport::grpc::GrpcClientManager>>()
+        .reset();
+    app.state::<QueryCache>().clear();
+    info!("Switched to backend {}", profile.server_url);
+    Ok(serde_json::json!({
+        "profile": profile.redacted(),
+        "upload_encrypted": profile.upload_encryption_key.is_some(),
+        "token_persisted": token_persisted,
+    }))
+}
+
+/// The backends in `backends.json`, marking the one in use
+///
+/// `server_url` is where calls actually go: when GRPC_SERVER_URL is set
+/// (`pinned_by_env`) that wins over any switch.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn list_backends<R: Runtime>(app: AppHandle<R>) -> Result<Value, AppError> {
+    println!("🦀 Rust: list_backends called");
+    let data_dir = app.state::<Accounts>().workspace().to_path_buf();
+    let path = backends::path(&data_dir);
+    let active = crate::config::profile().and_then(|p| p.name);
+    let listed: Vec<Value> = backends::load(&path)
+        .into_iter()
+        .map(|backend| {
+            let is_active = active.as_deref() == Some(backend.name.as_str());
+            let mut entry = serde_json::json!(backend);
+            entry["active"] = Value::Bool(is_active);
+            entry
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "backends": listed,
+        "active": active,
+        "server_url": config::get().grpc.server_url,
+        "pinned_by_env": config::get().grpc.server_url_pinned,
+        "file": path.display().to_string(),
+    }))
+}
+
+/// Switch to the backend called `name` in `backends.json`
+///
+/// The choice is saved as the profile, so it also holds on later starts,
+/// and the gRPC channel and cached answers are dropped: the next call
+/// connects to the new backend. Calls already in flight finish on the old
+/// one.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn switch_backend<R: Runtime>(
+    app: AppHandle<R>,
+    name: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: switch_backend called for {}", name);
+    let data_dir = app.state::<Accounts>().workspace().to_path_buf();
+    let listed = backends::load(&backends::path(&data_dir));
+    let profile = backends::find(&listed, &name)
+        .map_err(AppError::validation)?
+        .profile();
+    onboarding::save_active_profile(&data_dir.join(onboarding::PROFILE_FILE), &profile)
+        .map_err(AppError::storage)?;
+    crate::config::set_profile(Some(profile.clone()));
+    app.state::<std::sync::Arc<crate::transport::grpc::GrpcClientManager>>()
+        .reset();
+    app.state::<QueryCache>().clear();
+
+    let pinned_by_env = config::get().grpc.server_url_pinned;
+    if pinned_by_env {
+        warn!(
+            "Switched to backend {} but GRPC_SERVER_URL still wins",
+            name.trim()
+        );
+    } else {
+        info!(
+            "Switched to backend {} at {}",
+            name.trim(),
+            profile.server_url
+        );
+    }
+    Ok(serde_json::json!({
+        "profile": profile.redacted(),
+        "server_url": config::get().grpc.server_url,
+        "pinned_by_env": pinned_by_env,
+    }))
+}
+
+/// Optional subsystems and whether they can be used, see
+/// [`crate::capabilities`]; `refresh` probes them again
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_capabilities(
+    capabilities: tauri::State<'_, Capabilities>,
+    refresh: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_capabilities called");
+    let capabilities = capabilities.get(refresh.unwrap_or(false)).await;
+    let degraded = capabilities.iter().any(|capability| !capability.available);
+    Ok(serde_json::json!({ "capabilities": capabilities, "degraded": degraded }))
+}
+
+/// The accounts, marking the one in use
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn list_accounts(accounts: tauri::State<'_, Accounts>) -> Result<Value, AppError> {
+    println!("🦀 Rust: list_accounts called");
+    let active = accounts.active().id.clone();
+    let listed: Vec<Value> = accounts
+        .list()
+        .into_iter()
+        .map(|account| {
+            let is_active = account.id == active;
+            let mut entry = serde_json::json!(account);
+            entry["active"] = serde_json::json!(is_active);
+            entry
+        })
+        .collect();
+    Ok(serde_json::json!({ "active": active, "accounts": listed }))
+}
+
+/// Add an account with a workspace of its own, optionally with the
+/// backend it talks to and the token to use there; it is not switched to
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn create_account(
+    accounts: tauri::State<'_, Accounts>,
+    name: String,
+    server_url: Option<String>,
+    tls: Option<bool>,
+    token: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: create_account called for {}", name);
+    let account = accounts.create(&name).map_err(AppError::validation)?;
+    let workspace = accounts
+        .workspace_of(&account.id)
+        .map_err(AppError::validation)?;
+    let profile = server_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .map(|server_url| crate::config::BackendProfile {
+            name: None,
+            tls: tls.unwrap_or_else(|| server_url.starts_with("https://")),
+            server_url,
+            upload_encryption_key: None,
+        });
+    if let Some(profile) = &profile {
+        onboarding::save_profile(&workspace.join(onboarding::PROFILE_FILE), profile)
+            .map_err(AppError::storage)?;
+    }
+    let token_persisted = match token.map(|t| t.trim().to_string()) {
+        Some(token) if !token.is_empty() => Some(
+            crate::transport::auth::store_token_for(account.token_key(), &token)
+                .map_err(|e| warn!("create_account: {}", e))
+                .is_ok(),
+        ),
+        _ => None,
+    };
+    info!("Created account {} ({})", account.id, account.name);
+    Ok(serde_json::json!({
+        "account": account,
+        "profile": profile.map(|p| p.redacted()),
+        "token_persisted": token_persisted,
+    }))
+}
+
+/// Make `account_id` the active account and restart into it
+///
+/// Work in flight is drained first, like any exit. Nothing changes if it
+/// is already active.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn switch_account<R: Runtime>(
+    app: AppHandle<R>,
+    accounts: tauri::State<'_, Accounts>,
+    account_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: switch_account called for {}", account_id);
+    if accounts.active().id == account_id.trim() {
+        return Ok(serde_json::json!({ "account": accounts.active(), "restarting": false }));
+    }
+    let account = accounts
+        .set_active(&account_id)
+        .map_err(AppError::validation)?;
+    info!("Switching to account {}, restarting", account.id);
+    app.request_restart();
+    Ok(serde_json::json!({ "account": account, "restarting": true }))
+}
+
+/// Settings only read at startup, so a change needs a restart to apply
+const RESTART_SETTINGS: &[&str] = &[
+    "rest.transport",
+    "media.max_jobs",
+    "app.log_level",
+    "app.staleness_check_interval_secs",
+    "app.file_watch_interval_secs",
+    "app.history_sync_interval_secs",
+    "app.power_check_interval_secs",
+    "app.health_check_interval_secs",
+    "app.health_degraded_latency_ms",
+    "app.response_transforms",
+    "app.response_strip_tags",
+    "app.response_max_chars",
+];
+
+/// Read the settings again from the environment, profile and plugin config
+///
+/// `changed` lists the settings that differ, e.g. `grpc.server_url`; a
+/// change to the connection drops the gRPC channel so the next call uses
+/// it, and any gRPC or REST change drops the cached answers. Those in
+/// `needs_restart` only apply after a restart.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn reload_config<R: Runtime>(app: AppHandle<R>) -> Result<Value, AppError> {
+    println!("🦀 Rust: reload_config called");
+    let before = config::get();
+    let after = config::reload();
+    let changed = before.changes(&after);
+    if changed.iter().any(|name| name.starts_with("grpc.")) {
+        app.state::<std::sync::Arc<crate::transport::grpc::GrpcClientManager>>()
+            .reset();
+    }
+    // Cached answers are keyed by endpoint, but a changed key, TLS or
+    // gateway setting can mean another backend behind the same URL
+    if changed
+        .iter()
+        .any(|name| name.starts_with("grpc.") || name.starts_with("rest."))
+    {
+        app.state::<QueryCache>().clear();
+    }
+    let needs_restart: Vec<&String> = changed
+        .iter()
+        .filter(|name| RESTART_SETTINGS.contains(&name.as_str()))
+        .collect();
+    info!("Reloaded the config; changed: {:?}", changed);
+    Ok(serde_json::json!({
+        "changed": changed,
+        "needs_restart": needs_restart,
+    }))
+}
+
+/// What the startup integrity check found in the local data and repaired
+///
+/// `repairs` is empty when everything was intact; otherwise the UI should
+/// tell the user, since quarantined stores start over empty.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_integrity_report(
+    report: tauri::State<'_, integrity::IntegrityReport>,
+) -> Result<Value, AppError> {
+    serde_json::to_value(report.inner())
+        .map_err(|e| AppError::internal(format!("Failed to serialize integrity report: {}", e)))
+}
+
+/// Whether the backend answers within `timeout_secs` (default 3)
+///
+/// A one-off check; the UI should rather follow `backend://health` from
+/// the health monitor, or ask `get_backend_health` for its last result.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn check_backend_ready(
+    transport: tauri::State<'_, Transport>,
+    timeout_secs: Option<u64>,
+) -> Result<Value, AppError> {
+    info!(
+        "check_backend_ready: attempting ping via get_last_session ({})",
+        transport.name()
+    );
+
+    let limit = timeout_override(timeout_secs).unwrap_or(READY_TIMEOUT);
+    let ping = crate::transport::with_timeout(Some(limit), transport.get_last_session());
+    match tokio::time::timeout(limit, ping).await {
+        Ok(Ok(_)) => Ok(serde_json::json!({ "ready": true })),
+        Ok(Err(e)) if CertificateError::from_status(&e).is_some() => Ok(serde_json::json!({
+            "ready": false,
+            "message": e.message(),
+            "certificate_error": CertificateError::from_status(&e),
+        })),
+        Ok(Err(e)) if e.code() == tonic::Code::Unavailable => {
+            Ok(serde_json::json!({ "ready": false, "message": e.message() }))
+        }
+        Ok(Err(e)) => Ok(serde_json::json!({ "ready": false, "message": e.to_string() })),
+        Err(_) => Ok(serde_json::json!({ "ready": false, "message": "timeout" })),
+    }
+}
+
+/// The backend's health as of the monitor's last check: `state`
+/// (`starting`, `ready`, `degraded` or `down`), `since`, `checked_at`,
+/// `latency_ms`, `message` and `consecutive_failures`
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_backend_health(
+    health: tauri::State<'_, health::BackendHealth>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_backend_health called");
+    serde_json::to_value(health.get())
+        .map_err(|e| AppError::internal(format!("Failed to serialize backend health: {}", e)))
+}
+
+/// Versions of the backend and this client, for the about screen
+///
+/// Returns `{ client_version, client_proto_version, backend, compatible,
+/// version_mismatch, checked_at }`. `backend` is the backend's
+/// `ServerInfo`, null for a backend that predates it. Uses the handshake
+/// made when the channel last connected unless `refresh` is set or there
+/// was none yet.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_backend_info<R: Runtime>(
+    app: AppHandle<R>,
+    transport: tauri::State<'_, Transport>,
+    info: tauri::State<'_, compat::BackendInfo>,
+    refresh: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_backend_info called");
+    let handshake = match info.get() {
+        Some(handshake) if !refresh.unwrap_or(false) => handshake,
+        _ => {
+            let handshake = compat::handshake(&transport)
+                .await
+                .map_err(|e| AppError::rpc("GetServerInfo", e))?;
+            info.record(&app, handshake.clone());
+            handshake
+        }
+    };
+    Ok(serde_json::json!({
+        "client_version": compat::CLIENT_VERSION,
+        "client_proto_version": compat::PROTO_VERSION,
+        "backend": handshake.backend,
+        "compatible": handshake.version_mismatch.is_none(),
+        "version_mismatch": handshake.version_mismatch,
+        "checked_at": handshake.checked_at,
+    }))
+}
+
+/// The network conditions being simulated, null when none; dev mode only
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_network_simulation() -> Result<Value, AppError> {
+    println!("🦀 Rust: get_network_simulation called");
+    if !config::get().app.is_dev {
+        return Err(AppError::validation(
+            "Network simulation is only available in dev mode".to_string(),
+        ));
+    }
+    serde_json::to_value(crate::transport::simulate::get())
+        .map_err(|e| AppError::internal(format!("Failed to serialize network conditions: {}", e)))
+}
+
+/// Slow down and break calls to the backend on purpose, to reproduce
+/// flaky-network bugs; dev mode only
+///
+/// `conditions` takes `latency_ms`, `jitter_ms`, `bandwidth_bytes_per_sec`,
+/// `error_rate`, `stream_error_rate` and `seed`, see
+/// [`crate::transport::simulate`]; null turns the simulation off. The same
+/// `seed` replays the same failures.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_network_simulation(
+    conditions: Option<crate::transport::simulate::NetworkConditions>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: set_network_simulation called: {:?}", conditions);
+    if !config::get().app.is_dev {
+        return Err(AppError::validation(
+            "Network simulation is only available in dev mode".to_string(),
+        ));
+    }
+    crate::transport::simulate::set(conditions.clone()).map_err(AppError::validation)?;
+    Ok(serde_json::json!({ "conditions": conditions }))
+}
+
+/// Run the whole pipeline against the connected backend with a tiny
+/// embedded clip and report which steps pass
+///
+/// Meant for troubleshooting installs: connect, upload, wait for the
+/// backend to list the video, ask a canned question, check the answer,
+/// then delete the clip along with its chat history. Always returns a report;
+/// `passed` says whether the pipeline works end to end.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn run_self_test(
+    transport: tauri::State<'_, Transport>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: run_self_test called");
+    let mut report = selftest::Report::new();
+    let timeout = Duration::from_secs(selftest::STEP_TIMEOUT_SECS);
+
+    let started = std::time::Instant::now();
+    match transport.get_last_session().await {
+        Ok(_) => {
+            let detail = format!("Reached the backend over {}", transport.name());
+            report.record("connect", started, Ok(detail));
+        }
+        Err(e) => {
+            report.record("connect", started, Err(e.message().to_string()));
+            for step in ["upload", "processing", "query", "cleanup"] {
+                report.skip(step, "Could not connect to the backend");
+            }
+            return Ok(report.to_json());
+        }
+    }
+
+    let started = std::time::Instant::now();
+    let chunks = uploads::build_video_chunks(
+        selftest::SAMPLE_NAME,
+        selftest::SAMPLE_CLIP.to_vec(),
+        config::get().grpc.video_chunk_size,
+    );
+    let (tx, rx) = tokio::sync::mpsc::channel(chunks.len().max(1));
+    for chunk in chunks {
+        tx.try_send(chunk).ok();
+    }
+    drop(tx);
+    let upload = transport
+        .upload_video(ReceiverStream::new(rx))
+        .await
+        .map_err(AppError::from)
+        .and_then(|inner| {
+            if inner.success && !inner.file_id.is_empty() {
+                Ok(inner.file_id)
+            } else {
+                Err(AppError::validation(inner.message))
+            }
+        });
+    let video_id = match upload {
+        Ok(video_id) => {
+            report.record("upload", started, Ok(video_id.clone()));
+            video_id
+        }
+        Err(e) => {
+            report.record("upload", started, Err(e.to_string()));
+            for step in ["processing", "query", "cleanup"] {
+                report.skip(step, "The sample clip was not uploaded");
+            }
+            return Ok(report.to_json());
+        }
+    };
+
+    // The backend lists a video once it is stored and ready to query
+    let started = std::time::Instant::now();
+    let listed = tokio::time::timeout(timeout, async {
+        loop {
+            match transport.get_storage_usage().await {
+                Ok(usage) => {
+                    if usage.videos.iter().any(|v| v.video_id == video_id) {
+                        return Ok(true);
+                    }
+                }
+                Err(status) if status.code() == tonic::Code::Unimplemented => return Ok(false),
+                Err(status) => return Err(AppError::rpc("GetStorageUsage", status)),
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    })
+    .await;
+    let ready = match listed {
+        Ok(Ok(true)) => report.record(
+            "processing",
+            started,
+            Ok("The backend lists the sample clip".to_string()),
+        ),
+        Ok(Ok(false)) => {
+            report.skip(
+                "processing",
+                "The backend can't list videos; continuing without waiting",
+            );
+            true
+        }
+        Ok(Err(e)) => report.record("processing", started, Err(e.to_string())),
+        Err(_) => report.record(
+            "processing",
+            started,
+            Err(format!(
+                "The sample clip wasn't listed within {}s",
+                selftest::STEP_TIMEOUT_SECS
+            )),
+        ),
+    };
+
+    if ready {
+        let started = std::time::Instant::now();
+        let request = ChatRequest {
+            message: selftest::QUESTION.to_string(),
+            file_id: video_id.clone(),
+            request_id: sequence::new_request_id(),
+            ..Default::default()
+        };
+        let answer = tokio::time::timeout(timeout, async {
+            let stream = transport
+                .send_chat_message(request)
+                .await
+                .map_err(|e| AppError::from(e).to_string())?;
+            let (responses, interrupted) =
+                collect_chat_stream(stream, Vec::new(), &CancellationToken::new(), |_| {}).await;
+            selftest::check_answer(&chat_value(responses, interrupted)?)
+        })
+        .await
+        .unwrap_or_else(|_| Err(format!("No answer within {}s", selftest::STEP_TIMEOUT_SECS)));
+        report.record("query", started, answer);
+    } else {
+        report.skip("query", "The sample clip never became ready");
+    }
+
+    let started = std::time::Instant::now();
+    let deleted = delete_self_test_clip(&transport, &video_id).await;
+    report.record("cleanup", started, deleted);
+
+    let report = report.to_json();
+    info!("run_self_test finished: passed={}", report["passed"]);
+    Ok(report)
+}
+
+/// Delete the self-test's clip; a backend that keeps it fails the cleanup
+async fn delete_self_test_clip(transport: &Transport, video_id: &str) -> Result<String, String> {
+    let deleted = transport
+        .delete_video(DeleteVideoRequest {
+            video_id: video_id.to_string(),
+        })
+        .await
+        .map_err(|e| AppError::rpc("DeleteVideo", e).to_string())?;
+    if !deleted.success {
+        return Err(format!(
+            "The sample clip {} was not deleted: {}",
+            video_id, deleted.message
+        ));
+    }
+    Ok(format!(
+        "Deleted the sample clip and {} chat message(s)",
+        deleted.deleted_messages
+    ))
+}
+
+/// Save a bug report bundle locally and, with the user's consent, upload it
+///
+/// The bundle always lands in `<app data>/bug-reports/` first so nothing is
+/// lost if the upload fails. `upload` must be explicitly `true` for it to
+/// leave the machine, and BUG_REPORT_URL must be configured.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn submit_bug_report<R: Runtime>(
+    app: AppHandle<R>,
+    description: String,
+    upload: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: submit_bug_report called (upload={:?})", upload);
+
+    if description.trim().is_empty() {
+        return Err(AppError::validation(
+            "Please describe the problem".to_string(),
+        ));
+    }
+    let backend = check_backend_ready(app.state::<Transport>(), None).await?;
+    let mut report = diagnostics::collect_bundle(&app, backend).await;
+    report["description"] = serde_json::json!(description);
+
+    let dir = app
+        .state::<Accounts>()
+        .workspace()
+        .to_path_buf()
+        .join("bug-reports");
+    let saved_path = dir.join(format!(
+        "bug-report-{}.json",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0)
+    ));
+    store::save_json(&saved_path, &report).map_err(AppError::storage)?;
+    info!("submit_bug_report: saved {}", saved_path.display());
+
+    let mut uploaded = false;
+    let mut upload_error = None;
+    if upload.unwrap_or(false) {
+        match &config::get().app.bug_report_url {
+            None => upload_error = Some("No bug report endpoint is configured".to_string()),
+            Some(url) => match http::post_json(url, &report).await {
+                Ok((status, _)) if (200..300).contains(&status) => uploaded = true,
+                Ok((status, body)) => {
+                    upload_error = Some(format!("Upload rejected with HTTP {}: {}", status, body))
+                }
+                Err(e) => upload_error = Some(e),
+            },
+        }
+    }
+    if let Some(e) = &upload_error {
+        warn!("submit_bug_report: {}", e);
+    }
+
+    Ok(serde_json::json!({
+        "saved_path": saved_path.to_string_lossy(),
+        "uploaded": uploaded,
+        "upload_error": upload_error,
+        "upload_available": config::get().app.bug_report_url.is_some(),
+    }))
+}
+
+/// Structured changelog for the "what's new" dialog
+///
+/// `since_version` is the last version the user saw the dialog for; entries
+/// after it (up to the running version) come back in `new_entries`. With
+/// `fetch_remote`, release notes from RELEASE_NOTES_URL (`http://` or
+/// `https://`) are merged in.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_changelog<R: Runtime>(
+    app: AppHandle<R>,
+    since_version: Option<String>,
+    fetch_remote: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_changelog called (since={:?})", since_version);
+
+    let options = app.state::<PluginOptions>();
+    let mut entries = changelog::parse(options.changelog);
+    let mut remote_error = None;
+    if fetch_remote.unwrap_or(false) {
+        let url = options
+            .release_notes_url
+            .clone()
+            .or_else(|| config::get().app.release_notes_url.clone());
+        if let Some(url) = url {
+            let remote = http::get(&url).await.and_then(|(status, body)| {
+                if !(200..300).contains(&status) {
+                    return Err(format!("Release notes request failed with HTTP {}", status));
+                }
+                serde_json::from_str::<Vec<changelog::ChangelogEntry>>(&body)
+                    .map_err(|e| format!("Malformed release notes: {}", e))
+            });
+            match remote {
+                Ok(remote) => entries = changelog::merge(entries, remote),
+                Err(e) => {
+                    warn!("get_changelog: {}", e);
+                    remote_error = Some(e);
+                }
+            }
+        }
+    }
+
+    let current_version = app.package_info().version.to_string();
+    let new_entries = changelog::newer_than(&entries, since_version.as_deref(), &current_version);
+    Ok(serde_json::json!({
+        "current_version": current_version,
+        "has_new": !new_entries.is_empty(),
+        "new_entries": new_entries,
+        "entries": entries,
+        "remote_error": remote_error,
+    }))
+}
+
+/// Effective value and source of every feature flag
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_feature_flags(
+    flags: tauri::State<'_, flags::FeatureFlags>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_feature_flags called");
+    serde_json::to_value(flags.all())
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Toggle a flag for this user; `enabled: null` goes back to the default
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_feature_flag(
+    flags: tauri::State<'_, flags::FeatureFlags>,
+    name: String,
+    enabled: Option<bool>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: set_feature_flag called: {}={:?}", name, enabled);
+    let state = flags
+        .set_user(&name, enabled)
+        .map_err(AppError::validation)?;
+    serde_json::to_value(state)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Download a video's full history, thumbnail, key frames and report so it
+/// stays browsable offline
+///
+/// Backends without GetOfflineAssets still get the history pinned, with a
+/// warning. `max_key_frames` of 0 or null lets the backend decide.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn pin_for_offline(
+    transport: tauri::State<'_, Transport>,
+    offline: tauri::State<'_, OfflineCache>,
+    video_id: String,
+    max_key_frames: Option<i32>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: pin_for_offline called for {}", video_id);
+    let history = transport
+        .get_chat_history(GetHistoryRequest {
+            video_id: video_id.clone(),
+            include_full_messages: true,
+        })
+        .await
+        .map_err(AppError::from)?;
+    if history.created_at.is_empty() && history.total_messages == 0 {
+        return Err(AppError::validation(format!(
+            "The backend has no session for video {}",
+            video_id
+        )));
+    }
+    let video_name = history.video_name.clone();
+    let history_messages = history.recent_messages.len();
+
+    let staging = offline.staging_dir(&video_id);
+    tokio::fs::remove_dir_all(&staging).await.ok();
+    tokio::fs::create_dir_all(&staging)
+        .await
+        .map_err(|e| AppError::io(format!("Failed to create {}", staging.display()), e))?;
+    let history_json = serde_json::to_vec_pretty(&ChatHistory::from(history))
+        .map_err(|e| AppError::internal(format!("Failed to serialize history: {}", e)))?;
+    let mut total_bytes = history_json.len() as u64;
+    tokio::fs::write(staging.join("history.json"), history_json)
+        .await
+        .map_err(|e| AppError::io("Failed to save history", e))?;
+
+    let mut assets = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    let request = OfflineAssetsRequest {
+        video_id: video_id.clone(),
+        max_key_frames: max_key_frames.unwrap_or(0),
+    };
+    match transport.get_offline_assets(request).await {
+        Ok(mut stream) => {
+            while let Some(asset) = stream
+                .next()
+                .await
+                .transpose()
+                .map_err(|e| AppError::rpc("GetOfflineAssets", e))?
+            {
+                let kind = match asset.kind() {
+                    offline_asset::Kind::Thumbnail => "thumbnail",
+                    offline_asset::Kind::KeyFrame => "key_frame",
+                    offline_asset::Kind::Report => "report",
+                    offline_asset::Kind::Unspecified => "other",
+                };
+                // Prefix with the index so equal names from the backend can't collide
+                let file_name = format!(
+                    "{:03}-{}",
+                    assets.len(),
+                    offline::safe_file_name(&asset.name)
+                );
+                tokio::fs::write(staging.join(&file_name), &asset.data)
+                    .await
+                    .map_err(|e| AppError::io(format!("Failed to save {}", file_name), e))?;
+                total_bytes += asset.data.len() as u64;
+                assets.push(offline::OfflineAsset {
+                    kind: kind.to_string(),
+                    file_name,
+                    mime_type: asset.mime_type,
+                    size_bytes: asset.data.len() as u64,
+                    timestamp_seconds: asset.timestamp_seconds,
+                });
+            }
+        }
+        Err(status) => {
+            let msg = AppError::rpc("GetOfflineAssets", status);
+            warn!("pin_for_offline: {}", msg);
+            warnings.push(msg.to_string());
+        }
+    }
+
+    let pin = offline::PinnedVideo {
+        video_id: video_id.clone(),
+        video_name,
+        pinned_at: files::unix_seconds(std::time::SystemTime::now()),
+        history_messages,
+        assets,
+        total_bytes,
+    };
+    offline.commit(pin.clone()).map_err(AppError::storage)?;
+    info!(
+        "Pinned {} for offline use: {} assets, {} bytes",
+        video_id,
+        pin.assets.len(),
+        pin.total_bytes
+    );
+
+    let mut shaped = serde_json::to_value(pin)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))?;
+    shaped["dir"] = serde_json::json!(offline.dir(&video_id).to_string_lossy());
+    shaped["warnings"] = serde_json::json!(warnings);
+    Ok(shaped)
+}
+
+/// Remove a video's offline copy
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn unpin_offline(
+    offline: tauri::State<'_, OfflineCache>,
+    video_id: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: unpin_offline called for {}", video_id);
+    let removed = offline.remove(&video_id).map_err(AppError::storage)?;
+    Ok(serde_json::json!({ "video_id": video_id, "removed": removed.is_some() }))
+}
+
+/// Videos pinned for offline use, with the directory holding their files
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_offline_videos(
+    offline: tauri::State<'_, OfflineCache>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_offline_videos called");
+    let videos: Vec<Value> = offline
+        .list()
+        .into_iter()
+        .map(|pin| {
+            let dir = offline.dir(&pin.video_id);
+            let mut v = serde_json::to_value(pin).unwrap_or_default();
+            v["dir"] = serde_json::json!(dir.to_string_lossy());
+            v
+        })
+        .collect();
+    Ok(serde_json::json!({ "videos": videos }))
+}
+
+/// Sync the local history with the backend now
+///
+/// Syncs `video_id` only, or every locally cached session. Returns the
+/// per-session [`crate::history::SyncStatus`] keyed by video id.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn sync_now(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    video_id: Option<String>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: sync_now called for {:?}", video_id);
+    let results = sync::sync(&transport, &history, video_id.map(|id| vec![id]))
+        .await
+        .map_err(AppError::storage)?;
+    info!("sync_now synced {} session(s)", results.len());
+    serde_json::to_value(results)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Outcome of the last sync of every session, keyed by video id
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_sync_status(
+    history: tauri::State<'_, LocalHistory>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_sync_status called");
+    serde_json::to_value(history.sync_statuses())
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Sessions whose local and backend versions diverged, with both versions
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_sync_conflicts(
+    history: tauri::State<'_, LocalHistory>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_sync_conflicts called");
+    serde_json::to_value(history.conflicts())
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Resolve a sync conflict: `keep_local`, `keep_remote` or `merge`
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn resolve_sync_conflict(
+    transport: tauri::State<'_, Transport>,
+    history: tauri::State<'_, LocalHistory>,
+    video_id: String,
+    resolution: String,
+) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: resolve_sync_conflict called for {} with {}",
+        video_id, resolution
+    );
+    let resolution = sync::Resolution::parse(&resolution).map_err(AppError::validation)?;
+    let status = sync::resolve(&transport, &history, &video_id, resolution)
+        .await
+        .map_err(AppError::validation)?;
+    info!("Resolved sync conflict for {} ({:?})", video_id, resolution);
+    serde_json::to_value(status)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+/// Power-saving mode, the detected power source and whether saving is active
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_power_state(
+    power: tauri::State<'_, power::PowerState>,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_power_state called");
+    Ok(power.to_json())
+}
+
+/// Bytes a second uploads may use in total; 0 is no limit
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_upload_speed_limit() -> Result<Value, AppError> {
+    println!("🦀 Rust: get_upload_speed_limit called");
+    Ok(serde_json::json!({ "bytes_per_sec": throttle::limit() }))
+}
+
+/// Limit the bytes a second uploads may use in total, 0 for no limit
+///
+/// Applies to uploads already running, until the app restarts; the limit
+/// it starts with is UPLOAD_SPEED_LIMIT.
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_upload_speed_limit(bytes_per_sec: u64) -> Result<Value, AppError> {
+    println!(
+        "🦀 Rust: set_upload_speed_limit called with {}",
+        bytes_per_sec
+    );
+    throttle::set_limit(bytes_per_sec);
+    info!("Upload speed limit set to {} bytes/s", bytes_per_sec);
+    Ok(serde_json::json!({ "bytes_per_sec": bytes_per_sec }))
+}
+
+/// Set power-saving mode: `auto` (save on battery), `on` or `off`
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn set_power_saving(
+    power: tauri::State<'_, power::PowerState>,
+    mode: String,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: set_power_saving called with {}", mode);
+    power
+        .set_mode(power::PowerMode::parse(&mode).map_err(AppError::validation)?)
+        .map_err(AppError::storage)?;
+    info!("Power saving mode set to {}", mode);
+    Ok(power.to_json())
+}
+
+// Legacy endpoint for backward compatibility (deprecated)
+#[tauri::command(rename_all = "snake_case")]
+pub(crate) async fn get_processing_status(
+    transport: tauri::State<'_, Transport>,
+    _limit: i32,
+) -> Result<Value, AppError> {
+    println!("🦀 Rust: get_processing_status called (deprecated, use get_last_session)");
+
+    // Redirect to get_last_session for now
+    let response = transport.get_last_session().await.map_err(AppError::from)?;
+
+    serde_json::to_value(response)
+        .map_err(|e| AppError::internal(format!("Failed to serialize response: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::fake::FakeBackend;
+
+    /// A file of `len` bytes in the temp directory, removed when dropped
+    struct TempVideo(std::path::PathBuf);
+
+    impl TempVideo {
+        fn new(name: &str, len: usize) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "commands-test-{}-{}.mp4",
+                std::process::id(),
+                name
+            ));
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            std::fs::write(&path, data).unwrap();
+            Self(path)
+        }
+
+        async fn open(&self) -> tokio::fs::File {
+            tokio::fs::File::open(&self.0).await.unwrap()
+        }
+    }
+
+    impl Drop for TempVideo {
+        fn drop(&mut self) {
+            std::fs::remove_file(&self.0).ok();
+        }
+    }
+
+    fn upload_chunks(chunk_size: usize) -> UploadChunks {
+        UploadChunks {
+            chunk_size,
+            ..UploadChunks::new("a.mp4".to_string(), None)
+        }
+    }
+
+    async fn upload(
+        transport: &Transport,
+        video: &TempVideo,
+        sha256: &str,
+        chunks: UploadChunks,
+        streams: usize,
+    ) -> Result<(video_analyzer::UploadResponse, u64), AppError> {
+        let size = std::fs::metadata(&video.0).unwrap().len();
+        if streams > 1 {
+            stream_upload_parallel(
+                transport,
+                &video.0,
+                size,
+                sha256,
+                chunks,
+                streams,
+                |_, _| {},
+                CancellationToken::new(),
+            )
+            .await
+        } else {
+            let file = video.open().await;
+            stream_upload(transport, file, chunks, |_, _| {}, CancellationToken::new()).await
+        }
+    }
+
+    #[tokio::test]
+    async fn parallel_parts_make_up_the_whole_file() {
+        let video = TempVideo::new("parallel", 10 * 1024 + 7);
+        let backend = Arc::new(FakeBackend::default());
+        let transport: Transport = backend.clone();
+        let chunks = upload_chunks(1024);
+        let key = chunks.idempotency_key.clone();
+        let sha256 = files::validation::hash_file(&video.0).await.unwrap();
+
+        let (response, bytes_sent) = upload(&transport, &video, &sha256, chunks, 4)
+            .await
+            .unwrap();
+        assert_eq!(bytes_sent, 10 * 1024 + 7);
+        assert_eq!(response.file_id, "fake-video");
+        assert_eq!(
+            backend.stored(&key).unwrap(),
+            std::fs::read(&video.0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn retried_uploads_check_the_digest() {
+        let video = TempVideo::new("retried", 4 * 1024 + 3);
+        let sha256 = files::validation::hash_file(&video.0).await.unwrap();
+        let upload_once = |backend: FakeBackend| {
+            let video = &video;
+            let sha256 = &sha256;
+            async move {
+                backend.cut_off_next_upload();
+                let transport: Transport = Arc::new(backend);
+                upload(&transport, video, sha256, upload_chunks(1024), 1).await
+            }
+        };
+
+        let (response, _) = upload_once(FakeBackend::default()).await.unwrap();
+        assert_eq!(response.sha256, sha256);
+
+        let error = upload_once(FakeBackend::reporting(&uploads::sha256_hex(b"other")))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AppError::Integrity { .. }), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn existing_upload_falls_back_to_uploading() {
+        let found = |file_exists| async move {
+            let transport: Transport = Arc::new(FakeBackend::answering(file_exists));
+            existing_upload(&transport, "ab12", 10)
+                .await
+                .map(|found| found.file_id)
+        };
+        assert_eq!(found(Err(tonic::Code::Unimplemented)).await, None);
+        assert_eq!(found(Err(tonic::Code::Unavailable)).await, None);
+        assert_eq!(found(Ok(None)).await, None);
+        assert_eq!(
+            found(Ok(Some("v1".to_string()))).await,
+            Some("v1".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn audio_only_uploads_dont_stand_in_for_the_video() {
+        use media::transcode::Conversion;
+        let picked = TempVideo::new("picked", 4 * 1024);
+        let converted = TempVideo::new("converted", 1024);
+        let picked_sha256 = files::validation::hash_file(&picked.0).await.unwrap();
+        let converted_sha256 = files::validation::hash_file(&converted.0).await.unwrap();
+        let transport: Transport = Arc::new(FakeBackend::answering(Ok(None)));
+        let send_converted = |conversion: Conversion| {
+            let mut chunks = upload_chunks(1024);
+            chunks.source_sha256 = dedup_sha256(Some(&conversion), &picked_sha256)
+                .unwrap_or_default()
+                .to_string();
+            upload(&transport, &converted, &converted_sha256, chunks, 1)
+        };
+        let full_upload_finds = || async {
+            let sha256 = dedup_sha256(None, &picked_sha256).unwrap();
+            existing_upload(&transport, sha256, 4 * 1024)
+                .await
+                .is_some()
+        };
+
+        assert_eq!(
+            dedup_sha256(Some(&Conversion::AudioOnly), &picked_sha256),
+            None
+        );
+        send_converted(Conversion::AudioOnly).await.unwrap();
+        assert!(!full_upload_finds().await);
+
+        send_converted(Conversion::Video("HEVC".to_string()))
+            .await
+            .unwrap();
+        assert!(full_upload_finds().await);
+    }
+
+    #[tokio::test]
+    async fn self_test_cleanup_fails_if_the_clip_stays() {
+        let transport: Transport = Arc::new(FakeBackend::default());
+        let error = delete_self_test_clip(&transport, "v1").await.unwrap_err();
+        assert!(error.contains("DeleteVideo"), "{}", error);
+    }
+
+    /// Throughput of one stream against four, each held to the same rate
+    /// like connections on a link one can't fill
+    ///
+    /// Run with `cargo test --release --lib parallel_upload_benchmark --
+    /// --ignored --nocapture`; a debug build spends more time hashing than
+    /// sending. 64 MiB at 16 MB/s a stream took 4.41 s over one stream
+    /// (15.2 MB/s) and 1.18 s over four (56.9 MB/s).
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn parallel_upload_benchmark() {
+        const SIZE: usize = 64 * 1024 * 1024;
+        const PER_STREAM: u64 = 16 * 1000 * 1000;
+        let video = TempVideo::new("benchmark", SIZE);
+        let sha256 = files::validation::hash_file(&video.0).await.unwrap();
+        let transport: Transport = Arc::new(FakeBackend::limited(PER_STREAM));
+
+        let mut rates = Vec::new();
+        for streams in [1, 4] {
+            let chunks = upload_chunks(1024 * 1024);
+            let started = std::time::Instant::now();
+            let (_, bytes_sent) = upload(&transport, &video, &sha256, chunks, streams)
+                .await
+                .unwrap();
+            let secs = started.elapsed().as_secs_f64();
+            let rate = bytes_sent as f64 / 1_000_000.0 / secs;
+            println!("{} stream(s): {:.2}s, {:.1} MB/s", streams, secs, rate);
+            rates.push(rate);
+        }
+        assert!(rates[1] > rates[0] * 3.0);
+    }
+}
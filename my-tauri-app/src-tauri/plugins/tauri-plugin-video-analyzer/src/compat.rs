@@ -0,0 +1,240 @@
+//! Version handshake with the backend
+//!
+//! Each time the gRPC channel connects, [`spawn_handshake`] asks the
+//! backend for its [`ServerInfo`] and compares it against what this client
+//! was built with. A backend implementing an older revision of the proto
+//! than [`PROTO_VERSION`] lacks calls the client makes; a client older
+//! than the backend's `min_client_version` sends what the backend no
+//! longer understands. Either is a [`VersionMismatch`], kept in
+//! [`BackendInfo`] and emitted as `backend://version-mismatch`.
+//!
+//! Backends that predate `GetServerInfo` report nothing and are assumed
+//! compatible. The feature flags a backend reports are handed to
+//! [`FeatureFlags`].
+
+use crate::changelog::version_key;
+use crate::files;
+use crate::flags::FeatureFlags;
+use crate::transport::Transport;
+use crate::video_analyzer::ServerInfo;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tonic::Status;
+
+/// Revision of `video_analyzer.proto` this client needs; bumped whenever
+/// an RPC or field the client relies on is added. Optional features check
+/// their own revision with [`BackendInfo::reports_proto`] instead
+pub const PROTO_VERSION: i32 = 5;
+
+/// Revision that added `VideoChunk.encryption`; older backends would
+/// store the ciphertext as the video
+pub const ENCRYPTION_PROTO_VERSION: i32 = 6;
+
+/// Revision that added `VideoChunk.stream_index`, `stream_count` and
+/// `file_size`; older backends would take each part as a whole upload
+pub const PARALLEL_UPLOAD_PROTO_VERSION: i32 = 7;
+
+/// This client's release
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Which side is behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// The backend implements an older proto than the client needs
+    BackendTooOld,
+    /// The backend no longer supports this client release
+    ClientTooOld,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionMismatch {
+    pub kind: MismatchKind,
+    pub message: String,
+    pub backend_version: String,
+    pub backend_proto_version: i32,
+    pub client_version: String,
+    pub client_proto_version: i32,
+}
+
+impl std::fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for VersionMismatch {}
+
+/// Whether a backend reporting `info` works with this client
+pub fn check(info: &ServerInfo) -> Result<(), VersionMismatch> {
+    check_against(info, PROTO_VERSION, CLIENT_VERSION)
+}
+
+fn check_against(
+    info: &ServerInfo,
+    proto_version: i32,
+    client_version: &str,
+) -> Result<(), VersionMismatch> {
+    let mismatch = |kind, message: String| VersionMismatch {
+        kind,
+        message,
+        backend_version: info.version.clone(),
+        backend_proto_version: info.proto_version,
+        client_version: client_version.to_string(),
+        client_proto_version: proto_version,
+    };
+    // 0: the backend doesn't say
+    if info.proto_version > 0 && info.proto_version < proto_version {
+        return Err(mismatch(
+            MismatchKind::BackendTooOld,
+            format!(
+                "The backend ({}) is too old for this app: it implements protocol {}, the app needs {}; update the video analyzer backend",
+                info.version, info.proto_version, proto_version
+            ),
+        ));
+    }
+    let required = version_key(&info.min_client_version);
+    if required.is_some() && version_key(client_version) < required {
+        return Err(mismatch(
+            MismatchKind::ClientTooOld,
+            format!(
+                "The backend ({}) needs app version {} or later, this is {}; update the app",
+                info.version, info.min_client_version, client_version
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// What the last handshake found
+#[derive(Debug, Clone, Serialize)]
+pub struct Handshake {
+    /// `None` when the backend predates `GetServerInfo`
+    pub backend: Option<ServerInfo>,
+    pub version_mismatch: Option<VersionMismatch>,
+    /// Unix seconds
+    pub checked_at: f64,
+}
+
+/// Ask the backend for its version and check it
+pub async fn handshake(transport: &Transport) -> Result<Handshake, Status> {
+    let backend = match transport.get_server_info().await {
+        Ok(info) => Some(info),
+        Err(status) if status.code() == tonic::Code::Unimplemented => None,
+        Err(status) => return Err(status),
+    };
+    Ok(Handshake {
+        version_mismatch: backend.as_ref().and_then(|info| check(info).err()),
+        backend,
+        checked_at: files::unix_seconds(SystemTime::now()),
+    })
+}
+
+/// Managed state: the last [`Handshake`], if there was one
+#[derive(Default)]
+pub struct BackendInfo(Mutex<Option<Handshake>>);
+
+impl BackendInfo {
+    pub fn get(&self) -> Option<Handshake> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Whether the last handshake found a backend implementing at least
+    /// `proto_version`; false before there was one, and for backends that
+    /// predate GetServerInfo
+    pub fn reports_proto(&self, proto_version: i32) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|handshake| handshake.backend.as_ref())
+            .is_some_and(|info| info.proto_version >= proto_version)
+    }
+
+    /// Keep `handshake` and the feature flags it reported, logging and
+    /// emitting `backend://version-mismatch` if it found a mismatch
+    pub fn record<R: Runtime>(&self, app: &AppHandle<R>, handshake: Handshake) {
+        match (&handshake.backend, &handshake.version_mismatch) {
+            (_, Some(mismatch)) => {
+                log::warn!("{}", mismatch);
+                app.emit("backend://version-mismatch", mismatch).ok();
+            }
+            (Some(info), None) => log::info!(
+                "Backend {} (protocol {}) is compatible",
+                info.version,
+                info.proto_version
+            ),
+            (None, None) => log::info!("Backend predates GetServerInfo; assuming compatible"),
+        }
+        if let Some(flags) = app.try_state::<FeatureFlags>() {
+            let features = handshake
+                .backend
+                .as_ref()
+                .map(|info| info.features.clone())
+                .unwrap_or_default();
+            flags.set_backend(features);
+        }
+        *self.0.lock().unwrap() = Some(handshake);
+    }
+}
+
+/// Run the handshake in the background, e.g. after the channel connected
+pub fn spawn_handshake<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let transport = app.state::<Transport>().inner().clone();
+        match handshake(&transport).await {
+            Ok(handshake) => app.state::<BackendInfo>().record(&app, handshake),
+            Err(status) => log::warn!("Version handshake failed: {}", status.message()),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(proto_version: i32, min_client_version: &str) -> ServerInfo {
+        ServerInfo {
+            version: "2.0.0".to_string(),
+            proto_version,
+            min_client_version: min_client_version.to_string(),
+            started_at: 0.0,
+            features: Default::default(),
+        }
+    }
+
+    #[test]
+    fn versions_are_compared() {
+        let check = |info: &ServerInfo| check_against(info, 3, "1.2.0");
+        assert!(check(&info(3, "")).is_ok());
+        assert!(check(&info(4, "1.2.0")).is_ok());
+        assert!(check(&info(0, "")).is_ok());
+        assert_eq!(
+            check(&info(2, "")).unwrap_err().kind,
+            MismatchKind::BackendTooOld
+        );
+        let too_old = check(&info(3, "1.10.0")).unwrap_err();
+        assert_eq!(too_old.kind, MismatchKind::ClientTooOld);
+        assert_eq!(too_old.client_version, "1.2.0");
+        assert!(super::check(&info(PROTO_VERSION, CLIENT_VERSION)).is_ok());
+    }
+
+    #[test]
+    fn optional_features_dont_raise_the_minimum() {
+        assert!(super::check(&info(ENCRYPTION_PROTO_VERSION - 1, "")).is_ok());
+        assert!(super::check(&info(PARALLEL_UPLOAD_PROTO_VERSION - 1, "")).is_ok());
+    }
+
+    #[test]
+    fn the_backend_builds_against_the_same_proto() {
+        let backend = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../../video-analyser-backend/protos/video_analyzer.proto");
+        let backend = std::fs::read_to_string(&backend).unwrap();
+        assert!(
+            backend == include_str!("../proto/video_analyzer.proto"),
+            "video-analyser-backend/protos/video_analyzer.proto differs from proto/video_analyzer.proto; copy it over"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+//! Deleting a video, on the backend and from everything kept locally
+//!
+//! `delete_video` can't be undone, so it takes two calls: the first hands
+//! out a [`Confirmation`] token for the video, and only a second call
+//! carrying that token, within [`CONFIRMATION_TTL`], deletes it. Each
+//! token is good for one video and one use.
+//!
+//! Once the backend has deleted a video, by `delete_video` or in bulk by
+//! `cleanup_videos`, [`purge_local`] drops what this client kept of it: the library entry, local and mirrored history,
+//! the offline copy with its thumbnail and key frames, cached transcript,
+//! detections, timeline and answers, bookmarks and questions still in the
+//! outbox.
+
+use crate::bookmarks::Bookmarks;
+use crate::detections::DetectionCache;
+use crate::history::LocalHistory;
+use crate::library::Library;
+use crate::offline::OfflineCache;
+use crate::outbox::Outbox;
+use crate::query_cache::QueryCache;
+use crate::sequence;
+use crate::session::ActiveSession;
+use crate::storage::SessionCache;
+use crate::timeline::TimelineCache;
+use crate::transcript::TranscriptCache;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// How long a confirmation token stays good
+pub const CONFIRMATION_TTL: Duration = Duration::from_secs(120);
+
+/// What the first `delete_video` call answers with
+#[derive(Debug, Clone, Serialize)]
+pub struct Confirmation {
+    pub confirmation_token: String,
+    pub video_id: String,
+    pub expires_in_secs: u64,
+}
+
+/// Managed state: confirmation tokens handed out and not yet used
+#[derive(Debug, Default)]
+pub struct DeleteConfirmations(Mutex<HashMap<String, (String, Instant)>>);
+
+impl DeleteConfirmations {
+    /// A fresh token to delete `video_id`, issued at `now`
+    pub fn issue(&self, video_id: &str, now: Instant) -> Confirmation {
+        let token = sequence::new_request_id();
+        let mut tokens = self.0.lock().unwrap();
+        tokens.retain(|_, (_, issued)| now.duration_since(*issued) < CONFIRMATION_TTL);
+        tokens.insert(token.clone(), (video_id.to_string(), now));
+        Confirmation {
+            confirmation_token: token,
+            video_id: video_id.to_string(),
+            expires_in_secs: CONFIRMATION_TTL.as_secs(),
+        }
+    }
+
+    /// Use up `token`, which must have been issued for `video_id` less
+    /// than [`CONFIRMATION_TTL`] before `now`
+    pub fn redeem(&self, token: &str, video_id: &str, now: Instant) -> Result<(), String> {
+        let mut tokens = self.0.lock().unwrap();
+        let Some((issued_for, issued)) = tokens.remove(token.trim()) else {
+            return Err("Unknown or already used confirmation token".to_string());
+        };
+        if now.duration_since(issued) >= CONFIRMATION_TTL {
+            return Err("The confirmation token has expired; ask again".to_string());
+        }
+        if issued_for != video_id {
+            return Err(format!(
+                "The confirmation token is for {}, not {}",
+                issued_for, video_id
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What [`purge_local`] dropped, and what it couldn't
+#[derive(Debug, Default, Serialize)]
+pub struct LocalCleanup {
+    pub removed: Vec<&'static str>,
+    pub errors: Vec<String>,
+}
+
+impl LocalCleanup {
+    fn step(&mut self, name: &'static str, result: Result<bool, String>) {
+        match result {
+            Ok(true) => self.removed.push(name),
+            Ok(false) => {}
+            Err(e) => {
+                log::warn!("Failed to remove the {} of a deleted video: {}", name, e);
+                self.errors.push(format!("{}: {}", name, e));
+            }
+        }
+    }
+}
+
+/// Drop everything kept locally for `video_id`, carrying on past failures
+pub fn purge_local<R: Runtime>(app: &AppHandle<R>, video_id: &str) -> LocalCleanup {
+    let mut cleanup = LocalCleanup::default();
+    cleanup.step(
+        "library entry",
+        app.state::<Library>()
+            .remove(video_id)
+            .map(|entry| entry.is_some()),
+    );
+    cleanup.step(
+        "history",
+        app.state::<LocalHistory>().clear(video_id).map(|()| true),
+    );
+    cleanup.step(
+        "session cache",
+        app.state::<SessionCache>()
+            .forget_video(video_id)
+            .map(|()| true),
+    );
+    cleanup.step(
+        "offline copy",
+        app.state::<OfflineCache>()
+            .remove(video_id)
+            .map(|pin| pin.is_some()),
+    );
+    cleanup.step(
+        "transcript",
+        app.state::<TranscriptCache>()
+            .remove(video_id)
+            .map(|()| true),
+    );
+    cleanup.step(
+        "detections",
+        app.state::<DetectionCache>()
+            .remove(video_id)
+            .map(|()| true),
+    );
+    cleanup.step(
+        "timeline",
+        app.state::<TimelineCache>().remove(video_id).map(|()| true),
+    );
+    cleanup.step(
+        "bookmarks",
+        app.state::<Bookmarks>()
+            .remove_video(video_id)
+            .map(|removed| removed > 0),
+    );
+    cleanup.step(
+        "queued questions",
+        app.state::<Outbox>()
+            .remove_video(video_id)
+            .map(|removed| removed > 0),
+    );
+    app.state::<QueryCache>().clear_video(video_id);
+    let session = app.state::<ActiveSession>();
+    if session.get().as_deref() == Some(video_id) {
+        session.set(app, None);
+    }
+    cleanup
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_are_single_use_per_video() {
+        let confirmations = DeleteConfirmations::default();
+        let now = Instant::now();
+        let token = confirmations.issue("v1", now).confirmation_token;
+        assert!(confirmations.redeem(&token, "v2", now).is_err());
+        // A mismatch still uses the token up
+        assert!(confirmations.redeem(&token, "v1", now).is_err());
+
+        let token = confirmations.issue("v1", now).confirmation_token;
+        assert!(confirmations.redeem(&token, "v1", now).is_ok());
+        assert!(confirmations.redeem(&token, "v1", now).is_err());
+
+        let token = confirmations.issue("v1", now).confirmation_token;
+        let late = now + CONFIRMATION_TTL;
+        assert!(confirmations
+            .redeem(&token, "v1", late)
+            .unwrap_err()
+            .contains("expired"));
+    }
+}
@@ -0,0 +1,34 @@
+//! Desktop side of the plugin
+//!
+//! Desktop pickers return plain paths and desktop OSes don't suspend apps
+//! in the background, so there is nothing for the OS to do on our behalf.
+
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use tauri::plugin::PluginApi;
+use tauri::{AppHandle, Runtime};
+
+use crate::PlatformFile;
+
+pub fn init<R: Runtime, C: DeserializeOwned>(
+    _app: &AppHandle<R>,
+    _api: PluginApi<R, C>,
+) -> Result<VideoAnalyzer<R>, Box<dyn std::error::Error>> {
+    Ok(VideoAnalyzer(PhantomData))
+}
+
+pub struct VideoAnalyzer<R: Runtime>(PhantomData<fn() -> R>);
+
+impl<R: Runtime> VideoAnalyzer<R> {
+    pub fn open_uri(&self, uri: &str) -> Result<PlatformFile, String> {
+        Err(format!("{} can only be opened on Android or iOS", uri))
+    }
+
+    pub fn begin_transfer(&self, _name: &str) -> Transfer {
+        Transfer
+    }
+}
+
+/// No-op counterpart of the mobile background transfer guard
+pub struct Transfer;
@@ -0,0 +1,193 @@
+//! Cached object and entity detections
+//!
+//! `get_detections` fetches everything the backend detected in a video once
+//! and keeps it in `<app data>/detections/<video_id>.json`. Filtering and
+//! paging happen client-side, so the entities sidebar stays responsive and
+//! works without the backend once cached.
+
+use crate::store::VideoFiles;
+use crate::video_analyzer::{Detection, DetectionsResponse};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Page size when the UI doesn't ask for one
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+pub const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedDetections {
+    pub fetched_at: f64,
+    pub detections: DetectionsResponse,
+}
+
+/// Managed state: where detections are cached
+pub struct DetectionCache {
+    files: VideoFiles,
+}
+
+impl DetectionCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            files: VideoFiles::new(root),
+        }
+    }
+
+    pub fn get(&self, video_id: &str) -> Option<CachedDetections> {
+        self.files
+            .load::<CachedDetections>(video_id)
+            .filter(|cached| cached.detections.available)
+    }
+
+    pub fn put(&self, video_id: &str, cached: &CachedDetections) -> Result<(), String> {
+        self.files.save(video_id, cached)
+    }
+
+    pub fn remove(&self, video_id: &str) -> Result<(), String> {
+        self.files.remove(video_id)
+    }
+}
+
+/// What the sidebar narrows detections down to; every field is optional
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DetectionFilter {
+    /// Case-insensitive substring of the label
+    pub label: Option<String>,
+    /// Exact kind, e.g. "person"
+    pub kind: Option<String>,
+    pub min_confidence: Option<f32>,
+    /// Only detections on screen during `[start_ms, end_ms)`
+    pub start_ms: Option<i64>,
+    pub end_ms: Option<i64>,
+}
+
+impl DetectionFilter {
+    pub fn matches(&self, detection: &Detection) -> bool {
+        let label_ok = self.label.as_deref().is_none_or(|label| {
+            detection
+                .label
+                .to_lowercase()
+                .contains(&label.trim().to_lowercase())
+        });
+        let kind_ok = self
+            .kind
+            .as_deref()
+            .is_none_or(|kind| detection.kind.eq_ignore_ascii_case(kind.trim()));
+        let confidence_ok = self
+            .min_confidence
+            .is_none_or(|min| detection.confidence >= min);
+        let time_ok = (self.start_ms.is_none() && self.end_ms.is_none())
+            || detection.ranges.iter().any(|r| {
+                self.start_ms.is_none_or(|start| r.end_ms > start)
+                    && self.end_ms.is_none_or(|end| r.start_ms < end)
+            });
+        label_ok && kind_ok && confidence_ok && time_ok
+    }
+}
+
+/// One page of filtered detections, most confident first
+#[derive(Debug, Serialize)]
+pub struct DetectionPage {
+    pub detections: Vec<Detection>,
+    /// 0-based
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+    /// Labels of every match (not just this page) with their counts, for facets
+    pub labels: Vec<(String, usize)>,
+}
+
+pub fn page(
+    detections: &[Detection],
+    filter: &DetectionFilter,
+    page: usize,
+    page_size: usize,
+) -> DetectionPage {
+    let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+    let mut matched: Vec<&Detection> = detections.iter().filter(|d| filter.matches(d)).collect();
+    matched.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+
+    let mut labels: Vec<(String, usize)> = Vec::new();
+    for detection in &matched {
+        match labels.iter_mut().find(|(l, _)| *l == detection.label) {
+            Some((_, count)) => *count += 1,
+            None => labels.push((detection.label.clone(), 1)),
+        }
+    }
+    labels.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    DetectionPage {
+        detections: matched
+            .iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .map(|d| (*d).clone())
+            .collect(),
+        page,
+        page_size,
+        total: matched.len(),
+        labels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::video_analyzer::TimeRange;
+
+    fn detection(label: &str, kind: &str, confidence: f32, start_ms: i64) -> Detection {
+        Detection {
+            label: label.to_string(),
+            kind: kind.to_string(),
+            confidence,
+            ranges: vec![TimeRange {
+                start_ms,
+                end_ms: start_ms + 1000,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_and_page() {
+        let all = vec![
+            detection("car", "object", 0.6, 0),
+            detection("Person", "person", 0.9, 5000),
+            detection("car", "object", 0.95, 10_000),
+            detection("Acme logo", "logo", 0.4, 0),
+        ];
+
+        let cars = page(
+            &all,
+            &DetectionFilter {
+                label: Some("CAR".to_string()),
+                ..Default::default()
+            },
+            0,
+            10,
+        );
+        assert_eq!(cars.total, 2);
+        assert_eq!(cars.detections[0].confidence, 0.95);
+        assert_eq!(cars.labels, vec![("car".to_string(), 2)]);
+
+        let confident_early = DetectionFilter {
+            min_confidence: Some(0.5),
+            end_ms: Some(6000),
+            ..Default::default()
+        };
+        let labels: Vec<_> = page(&all, &confident_early, 0, 10)
+            .detections
+            .into_iter()
+            .map(|d| d.label)
+            .collect();
+        assert_eq!(labels, vec!["Person", "car"]);
+
+        let second = page(&all, &DetectionFilter::default(), 1, 3);
+        assert_eq!((second.total, second.detections.len()), (4, 1));
+        assert_eq!(second.detections[0].label, "Acme logo");
+    }
+}
@@ -0,0 +1,233 @@
+//! Files dropped onto an app window
+//!
+//! The webview only sees the raw paths of a drop. The plugin checks them
+//! here first and emits `files://dropped` with a [`DroppedFiles`] instead:
+//!
+//! - `files`: videos ready to upload or register, with size and times
+//! - `known`: videos already in the library, with the id to open them by
+//! - `rejected`: everything else, each with why
+//!
+//! A path dropped twice, or through a symlink to a file also dropped,
+//! only counts once.
+
+use crate::config;
+use crate::files;
+use crate::import::DEFAULT_EXTENSIONS;
+use crate::library::Library;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, DragDropEvent, Emitter, Manager, RunEvent, Runtime, WindowEvent};
+
+/// A dropped video that passed the checks
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedFile {
+    /// Path as dropped
+    pub path: String,
+    /// Path after resolving symlinks
+    pub canonical_path: String,
+    pub file_name: String,
+    pub extension: String,
+    pub size_bytes: u64,
+    pub modified_at: f64,
+}
+
+/// A dropped file the library already has
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KnownVideo {
+    pub path: String,
+    pub file_id: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Payload of a `files://dropped` event
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DroppedFiles {
+    /// Label of the window the files were dropped on
+    pub window: String,
+    /// Where in the window, in physical pixels
+    pub position: Option<(f64, f64)>,
+    pub files: Vec<DroppedFile>,
+    pub known: Vec<KnownVideo>,
+    pub rejected: Vec<RejectedFile>,
+}
+
+/// Check each of `paths`, at most `max_bytes` each (0 for no limit),
+/// against the library's videos in `known`, keyed by canonical path
+pub fn inspect(
+    paths: &[PathBuf],
+    known: &HashMap<PathBuf, KnownVideo>,
+    max_bytes: u64,
+) -> DroppedFiles {
+    let mut dropped = DroppedFiles::default();
+    let mut seen = HashSet::new();
+    for path in paths {
+        let shown = path.to_string_lossy().into_owned();
+        let reject = |reason: String| RejectedFile {
+            path: shown.clone(),
+            reason,
+        };
+        let canonical = match files::resolve_path(path) {
+            Ok(resolved) => resolved.canonical,
+            Err(e) => {
+                dropped.rejected.push(reject(e));
+                continue;
+            }
+        };
+        if !seen.insert(canonical.clone()) {
+            continue;
+        }
+        match check(&canonical, max_bytes) {
+            Err(reason) => dropped.rejected.push(reject(reason)),
+            Ok(_) if known.contains_key(&canonical) => dropped.known.push(KnownVideo {
+                path: shown,
+                ..known[&canonical].clone()
+            }),
+            Ok(metadata) => dropped.files.push(DroppedFile {
+                path: shown,
+                canonical_path: canonical.to_string_lossy().into_owned(),
+                file_name: files::FileName::of(&canonical).display,
+                extension: extension(&canonical),
+                size_bytes: metadata.len(),
+                modified_at: metadata.modified().map(files::unix_seconds).unwrap_or(0.0),
+            }),
+        }
+    }
+    dropped
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Whether `path` is a readable video of a known extension within `max_bytes`
+fn check(path: &Path, max_bytes: u64) -> Result<std::fs::Metadata, String> {
+    let io_path = files::paths::fs_path(path);
+    let metadata = std::fs::metadata(&io_path).map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        return Err("Folders can't be dropped; import them instead".to_string());
+    }
+    if !metadata.is_file() {
+        return Err("Not a regular file".to_string());
+    }
+    let extension = extension(path);
+    if !DEFAULT_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Not a video file (expected {})",
+            DEFAULT_EXTENSIONS.join(", ")
+        ));
+    }
+    if metadata.len() == 0 {
+        return Err("File is empty".to_string());
+    }
+    if max_bytes > 0 && metadata.len() > max_bytes {
+        return Err(format!(
+            "File is {} bytes, over the {} byte limit",
+            metadata.len(),
+            max_bytes
+        ));
+    }
+    std::fs::File::open(&io_path).map_err(|e| format!("File is not readable: {}", e))?;
+    Ok(metadata)
+}
+
+/// Plugin event hook: check the files of each drop and emit
+/// `files://dropped`
+pub fn on_event<R: Runtime>(app: &AppHandle<R>, event: &RunEvent) {
+    let RunEvent::WindowEvent {
+        label,
+        event: WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }),
+        ..
+    } = event
+    else {
+        return;
+    };
+    let app = app.clone();
+    let paths = paths.clone();
+    let mut dropped = DroppedFiles {
+        window: label.clone(),
+        position: Some((position.x, position.y)),
+        ..Default::default()
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let known = app
+            .state::<Library>()
+            .entries()
+            .into_iter()
+            .map(|entry| {
+                let video = KnownVideo {
+                    path: entry.original_path.clone(),
+                    file_id: entry.file_id.clone(),
+                    display_name: entry.display_name.clone(),
+                };
+                (entry.path(), video)
+            })
+            .collect();
+        let checked = inspect(&paths, &known, config::get().app.max_drop_bytes);
+        log::info!(
+            "Dropped on {}: {} new, {} known, {} rejected",
+            dropped.window,
+            checked.files.len(),
+            checked.known.len(),
+            checked.rejected.len()
+        );
+        dropped.files = checked.files;
+        dropped.known = checked.known;
+        dropped.rejected = checked.rejected;
+        app.emit("files://dropped", dropped).ok();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_are_sorted_into_new_known_and_rejected() {
+        let root = std::env::temp_dir().join(format!("dropped-test-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("folder")).unwrap();
+        let write = |name: &str, len: usize| {
+            let path = root.join(name);
+            std::fs::write(&path, vec![0u8; len]).unwrap();
+            path
+        };
+        let new = write("new.MP4", 10);
+        let old = write("old.mkv", 10);
+        let paths = [
+            new.clone(),
+            new.clone(),
+            old.clone(),
+            write("notes.txt", 10),
+            write("empty.mov", 0),
+            write("huge.webm", 100),
+            root.join("folder"),
+            root.join("missing.mp4"),
+        ];
+        let known = HashMap::from([(
+            std::fs::canonicalize(&old).unwrap(),
+            KnownVideo {
+                path: String::new(),
+                file_id: "v1".to_string(),
+                display_name: "Old".to_string(),
+            },
+        )]);
+
+        let dropped = inspect(&paths, &known, 50);
+        let names: Vec<&str> = dropped.files.iter().map(|f| f.file_name.as_str()).collect();
+        assert_eq!(names, ["new.MP4"]);
+        assert_eq!(dropped.files[0].extension, "mp4");
+        assert_eq!(dropped.known.len(), 1);
+        assert_eq!(dropped.known[0].file_id, "v1");
+        assert_eq!(dropped.known[0].path, old.to_string_lossy());
+        assert_eq!(dropped.rejected.len(), 5);
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
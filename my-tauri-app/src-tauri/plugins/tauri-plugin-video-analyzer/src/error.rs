@@ -0,0 +1,306 @@
+//! Errors returned by the plugin's commands
+//!
+//! Commands fail with an [`AppError`] rather than a bare message, so the
+//! frontend can branch on what went wrong instead of parsing text. It
+//! reaches JavaScript as
+//!
+//! ```json
+//! { "kind": "transport", "code": "unavailable", "message": "..." }
+//! ```
+//!
+//! `kind` is one of `transport`, `backend`, `io`, `integrity`,
+//! `validation`, `cancelled` or `internal`; `code` is an [`ErrorCode`].
+//! Both are stable, the message is for people and may change.
+//!
+//! Failed backend calls are sorted by their gRPC status. The plugin's own
+//! helpers return `String` messages, which each command sorts itself, e.g.
+//! with [`AppError::validation`] or [`AppError::storage`].
+
+use crate::transport::tls::CertificateError;
+use serde::Serialize;
+use tonic::{Code, Status};
+
+/// What went wrong, more precisely than the kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    // Transport
+    /// The backend can't be reached
+    Unavailable,
+    /// The call outlived its deadline
+    DeadlineExceeded,
+    /// The backend's TLS certificate was refused, or ours is misconfigured
+    TlsCertificate,
+
+    // Backend
+    /// The auth token is missing or wrong
+    Unauthenticated,
+    PermissionDenied,
+    NotFound,
+    AlreadyExists,
+    /// The backend refused the request as it stands, e.g. a bad argument
+    InvalidArgument,
+    /// The backend can't do it in its current state
+    FailedPrecondition,
+    /// Out of space, quota or capacity
+    ResourceExhausted,
+    /// The backend predates the call
+    Unimplemented,
+    /// Anything else the backend reported
+    BackendError,
+
+    // Io
+    FileNotFound,
+    FileAccessDenied,
+    Io,
+
+    // Integrity
+    /// What arrived doesn't hash to what was sent
+    ChecksumMismatch,
+
+    // Validation
+    InvalidInput,
+
+    // Cancelled
+    Cancelled,
+
+    // Internal
+    Internal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AppError {
+    /// The backend couldn't be reached, or the connection failed mid-call
+    #[error("{message}")]
+    Transport { code: ErrorCode, message: String },
+    /// The backend answered, with an error
+    #[error("{message}")]
+    Backend { code: ErrorCode, message: String },
+    /// A local file couldn't be read or written
+    #[error("{message}")]
+    Io { code: ErrorCode, message: String },
+    /// Data was damaged on the way, e.g. an upload the backend hashed
+    /// differently from the file
+    #[error("{message}")]
+    Integrity { code: ErrorCode, message: String },
+    /// The request doesn't make sense as given
+    #[error("{message}")]
+    Validation { code: ErrorCode, message: String },
+    /// Stopped on purpose, by the user or a shutdown
+    #[error("{message}")]
+    Cancelled { code: ErrorCode, message: String },
+    /// A bug or an environment problem on our side
+    #[error("{message}")]
+    Internal { code: ErrorCode, message: String },
+}
+
+impl AppError {
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::Validation {
+            code: ErrorCode::InvalidInput,
+            message: message.into(),
+        }
+    }
+
+    pub fn integrity(message: impl Into<String>) -> Self {
+        Self::Integrity {
+            code: ErrorCode::ChecksumMismatch,
+            message: message.into(),
+        }
+    }
+
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        Self::Cancelled {
+            code: ErrorCode::Cancelled,
+            message: message.into(),
+        }
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::Internal {
+            code: ErrorCode::Internal,
+            message: message.into(),
+        }
+    }
+
+    /// A local file that couldn't be read or written, reported by a helper
+    /// that already described it
+    pub fn storage(message: impl Into<String>) -> Self {
+        Self::Io {
+            code: ErrorCode::Io,
+            message: message.into(),
+        }
+    }
+
+    /// A failed file operation, described as `context: error`
+    pub fn io(context: impl std::fmt::Display, error: std::io::Error) -> Self {
+        Self::Io {
+            code: io_code(&error),
+            message: format!("{}: {}", context, error),
+        }
+    }
+
+    /// A failed `rpc`, sorted by its status and described for people,
+    /// calling out backends that predate the RPC
+    pub fn rpc(rpc: &str, status: Status) -> Self {
+        let message = match status.code() {
+            Code::Unimplemented => format!(
+                "The backend does not support {} yet; update the video analyzer backend",
+                rpc
+            ),
+            Code::Unauthenticated => format!(
+                "The backend refused {}: {}; check the auth token (set_auth_token)",
+                rpc,
+                status.message()
+            ),
+            _ => format!("gRPC call failed: {}", status),
+        };
+        Self::from_status(&status, message)
+    }
+
+    fn from_status(status: &Status, message: String) -> Self {
+        if let Some(error) = CertificateError::from_status(status) {
+            return Self::Transport {
+                code: ErrorCode::TlsCertificate,
+                message: format!("TLS certificate error: {}", error.message),
+            };
+        }
+        let transport = |code| Self::Transport {
+            code,
+            message: message.clone(),
+        };
+        let backend = |code| Self::Backend {
+            code,
+            message: message.clone(),
+        };
+        match status.code() {
+            Code::Unavailable => transport(ErrorCode::Unavailable),
+            Code::DeadlineExceeded => transport(ErrorCode::DeadlineExceeded),
+            Code::Cancelled => Self::cancelled(message.clone()),
+            Code::Unauthenticated => backend(ErrorCode::Unauthenticated),
+            Code::PermissionDenied => backend(ErrorCode::PermissionDenied),
+            Code::NotFound => backend(ErrorCode::NotFound),
+            Code::AlreadyExists => backend(ErrorCode::AlreadyExists),
+            Code::InvalidArgument | Code::OutOfRange => backend(ErrorCode::InvalidArgument),
+            Code::FailedPrecondition | Code::Aborted => backend(ErrorCode::FailedPrecondition),
+            Code::ResourceExhausted => backend(ErrorCode::ResourceExhausted),
+            Code::Unimplemented => backend(ErrorCode::Unimplemented),
+            Code::DataLoss => Self::integrity(message.clone()),
+            Code::Ok | Code::Unknown | Code::Internal => backend(ErrorCode::BackendError),
+        }
+    }
+
+    /// The same error with its message rewritten by `f`
+    pub fn map_message(self, f: impl FnOnce(String) -> String) -> Self {
+        match self {
+            Self::Transport { code, message } => Self::Transport {
+                code,
+                message: f(message),
+            },
+            Self::Backend { code, message } => Self::Backend {
+                code,
+                message: f(message),
+            },
+            Self::Io { code, message } => Self::Io {
+                code,
+                message: f(message),
+            },
+            Self::Integrity { code, message } => Self::Integrity {
+                code,
+                message: f(message),
+            },
+            Self::Validation { code, message } => Self::Validation {
+                code,
+                message: f(message),
+            },
+            Self::Cancelled { code, message } => Self::Cancelled {
+                code,
+                message: f(message),
+            },
+            Self::Internal { code, message } => Self::Internal {
+                code,
+                message: f(message),
+            },
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::Transport { code, .. }
+            | Self::Backend { code, .. }
+            | Self::Io { code, .. }
+            | Self::Integrity { code, .. }
+            | Self::Validation { code, .. }
+            | Self::Cancelled { code, .. }
+            | Self::Internal { code, .. } => *code,
+        }
+    }
+}
+
+impl From<Status> for AppError {
+    fn from(status: Status) -> Self {
+        let message = format!("gRPC call failed: {}", status);
+        Self::from_status(&status, message)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io {
+            code: io_code(&error),
+            message: error.to_string(),
+        }
+    }
+}
+
+fn io_code(error: &std::io::Error) -> ErrorCode {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => ErrorCode::FileNotFound,
+        std::io::ErrorKind::PermissionDenied => ErrorCode::FileAccessDenied,
+        _ => ErrorCode::Io,
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::internal(format!("Failed to serialize response: {}", error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_serialize_with_kind_and_code() {
+        let error = AppError::rpc("GetTimeline", Status::unimplemented("no"));
+        assert_eq!(error.code(), ErrorCode::Unimplemented);
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({
+                "kind": "backend",
+                "code": "unimplemented",
+                "message": "The backend does not support GetTimeline yet; update the video analyzer backend",
+            })
+        );
+
+        let error = AppError::from(Status::unavailable("connection refused"));
+        assert!(matches!(
+            error,
+            AppError::Transport {
+                code: ErrorCode::Unavailable,
+                ..
+            }
+        ));
+        assert_eq!(
+            serde_json::to_value(AppError::storage("Failed to write library.json")).unwrap()
+                ["kind"],
+            "io"
+        );
+        assert_eq!(
+            AppError::from(Status::data_loss("chunk 3 does not match its sha256")).code(),
+            ErrorCode::ChecksumMismatch
+        );
+    }
+}
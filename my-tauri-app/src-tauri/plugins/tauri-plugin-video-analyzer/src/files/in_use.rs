@@ -0,0 +1,207 @@
+//! Detection of files that are still being written
+//!
+//! Uploading a recording while OBS (or a copy job) is still writing it
+//! produces a truncated video on the backend. Before an upload we look for
+//! open writers where the platform lets us, and for size/mtime churn
+//! everywhere; after the upload we check the file did not change under us.
+
+use super::paths::fs_path;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// A file modified this recently is treated as possibly still being written
+const RECENT_WRITE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Gap between the two size/mtime samples used to detect growth
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(750);
+
+/// How long to wait for a busy file to settle before giving up
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Size and modification time of a file at one instant
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileSnapshot {
+    pub size_bytes: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileSnapshot {
+    pub fn take(path: &Path) -> Result<Self, String> {
+        let metadata = std::fs::metadata(fs_path(path))
+            .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?;
+        Ok(Self {
+            size_bytes: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Why a file looks like it is still in use
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InUseReport {
+    /// Another process holds the file open for writing
+    pub open_for_writing: bool,
+    /// Size or mtime changed between two samples
+    pub changing: bool,
+    /// Modified within the last few seconds
+    pub recently_modified: bool,
+}
+
+impl InUseReport {
+    pub fn is_busy(&self) -> bool {
+        self.open_for_writing || self.changing || self.recently_modified
+    }
+
+    pub fn describe(&self) -> String {
+        let mut reasons = Vec::new();
+        if self.open_for_writing {
+            reasons.push("another process has it open for writing");
+        }
+        if self.changing {
+            reasons.push("its size is still changing");
+        }
+        if self.recently_modified {
+            reasons.push("it was modified in the last few seconds");
+        }
+        reasons.join(", ")
+    }
+}
+
+/// Sample the file twice and look for open writers
+pub async fn check_in_use(path: &Path) -> Result<(InUseReport, FileSnapshot), String> {
+    let first = FileSnapshot::take(path)?;
+    tokio::time::sleep(SAMPLE_INTERVAL).await;
+    let second = FileSnapshot::take(path)?;
+
+    let recently_modified = second
+        .modified
+        .and_then(|m| SystemTime::now().duration_since(m).ok())
+        .map(|age| age < RECENT_WRITE_WINDOW)
+        .unwrap_or(false);
+
+    let report = InUseReport {
+        open_for_writing: has_open_writer(path),
+        changing: first != second,
+        recently_modified,
+    };
+    Ok((report, second))
+}
+
+/// Wait up to [`SETTLE_TIMEOUT`] for a busy file to become idle
+///
+/// Returns the last report (busy or not) and the snapshot it was based on.
+pub async fn wait_until_idle(path: &Path) -> Result<(InUseReport, FileSnapshot), String> {
+    let deadline = tokio::time::Instant::now() + SETTLE_TIMEOUT;
+    loop {
+        let (report, snapshot) = check_in_use(path).await?;
+        if !report.is_busy() || tokio::time::Instant::now() >= deadline {
+            return Ok((report, snapshot));
+        }
+        log::debug!(
+            "{} is busy ({}), waiting",
+            path.display(),
+            report.describe()
+        );
+    }
+}
+
+/// Best-effort check for another process holding the file open for writing
+#[cfg(target_os = "linux")]
+fn has_open_writer(path: &Path) -> bool {
+    let Ok(target) = std::fs::canonicalize(path) else {
+        return false;
+    };
+    let Ok(procs) = std::fs::read_dir("/proc") else {
+        return false;
+    };
+    let own_pid = std::process::id().to_string();
+
+    for proc_entry in procs.flatten() {
+        let pid = proc_entry.file_name();
+        let pid = pid.to_string_lossy();
+        if !pid.bytes().all(|b| b.is_ascii_digit()) || pid == own_pid {
+            continue;
+        }
+        // Other users' processes are unreadable; that's fine, skip them
+        let Ok(fds) = std::fs::read_dir(proc_entry.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if std::fs::read_link(fd.path()).ok().as_deref() != Some(target.as_path()) {
+                continue;
+            }
+            let fdinfo = proc_entry.path().join("fdinfo").join(fd.file_name());
+            if std::fs::read_to_string(fdinfo)
+                .ok()
+                .and_then(|info| parse_fdinfo_flags(&info))
+                .map(|flags| flags & 0o3 != 0) // O_WRONLY | O_RDWR
+                .unwrap_or(false)
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// Try to open the file while denying other writers; a sharing violation
+/// means someone already has it open for writing
+#[cfg(windows)]
+fn has_open_writer(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_SHARE_READ: u32 = 0x1;
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+
+    match std::fs::OpenOptions::new()
+        .read(true)
+        .share_mode(FILE_SHARE_READ)
+        .open(fs_path(path))
+    {
+        Ok(_) => false,
+        Err(e) => e.raw_os_error() == Some(ERROR_SHARING_VIOLATION),
+    }
+}
+
+/// No cheap way to enumerate open handles here; rely on size/mtime churn
+#[cfg(not(any(target_os = "linux", windows)))]
+fn has_open_writer(_path: &Path) -> bool {
+    false
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_fdinfo_flags(fdinfo: &str) -> Option<u32> {
+    fdinfo
+        .lines()
+        .find_map(|line| line.strip_prefix("flags:"))
+        .and_then(|v| u32::from_str_radix(v.trim(), 8).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fdinfo_flags() {
+        let info = "pos:\t0\nflags:\t0100001\nmnt_id:\t29\n";
+        let flags = parse_fdinfo_flags(info).unwrap();
+        assert_eq!(flags & 0o3, 0o1);
+        assert_eq!(parse_fdinfo_flags("pos:\t0\n"), None);
+    }
+
+    #[test]
+    fn test_report_describe() {
+        let report = InUseReport {
+            changing: true,
+            recently_modified: true,
+            ..Default::default()
+        };
+        assert!(report.is_busy());
+        assert_eq!(
+            report.describe(),
+            "its size is still changing, it was modified in the last few seconds"
+        );
+        assert!(!InUseReport::default().is_busy());
+    }
+}
@@ -0,0 +1,23 @@
+//! Local file handling for videos passed in from the frontend
+//!
+//! Everything that touches a user-supplied path (upload, registration)
+//! goes through this module before anything is sent to the backend.
+
+pub mod in_use;
+pub mod names;
+pub mod paths;
+pub mod uri;
+pub mod validation;
+pub mod volume;
+
+pub use names::FileName;
+pub use paths::normalize_path;
+pub use validation::validate_video_file;
+pub use volume::resolve_path;
+
+/// Seconds since the Unix epoch, the timestamp format used across the proto
+pub fn unix_seconds(t: std::time::SystemTime) -> f64 {
+    t.duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
@@ -0,0 +1,137 @@
+//! File names that are not valid UTF-8
+//!
+//! Linux paths are arbitrary bytes and Windows paths can contain unpaired
+//! surrogates, so `to_str()` is not a safe way to get a name. We keep two
+//! forms: a lossy display string for the UI/backend, and the original OS
+//! bytes (hex-encoded) as the identity stored in the library DB.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+/// Name used when a path has no final component at all (e.g. `/`)
+const FALLBACK_NAME: &str = "video.mp4";
+
+/// A file name in display and lossless form
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileName {
+    /// Lossy UTF-8 rendering, safe to show and to send over gRPC
+    pub display: String,
+    /// Hex-encoded original OS bytes, stable across runs
+    pub raw_hex: String,
+    /// True when `display` lost information relative to the original name
+    pub is_lossy: bool,
+}
+
+impl FileName {
+    pub fn of(path: &Path) -> Self {
+        match path.file_name() {
+            Some(name) => Self {
+                display: name.to_string_lossy().to_string(),
+                raw_hex: os_to_hex(name),
+                is_lossy: name.to_str().is_none(),
+            },
+            None => Self {
+                display: FALLBACK_NAME.to_string(),
+                raw_hex: String::new(),
+                is_lossy: false,
+            },
+        }
+    }
+}
+
+/// Encode an `OsStr` losslessly as hex
+///
+/// Unix: the raw bytes. Windows: the UTF-16 code units, little-endian.
+pub fn os_to_hex(s: &OsStr) -> String {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        hex::encode(s.as_bytes())
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+        let bytes: Vec<u8> = s.encode_wide().flat_map(|u| u.to_le_bytes()).collect();
+        hex::encode(bytes)
+    }
+}
+
+/// Inverse of [`os_to_hex`]
+pub fn os_from_hex(encoded: &str) -> Option<OsString> {
+    let bytes = hex::decode(encoded).ok()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(OsString::from_vec(bytes))
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStringExt;
+        if bytes.len() % 2 != 0 {
+            return None;
+        }
+        let wide: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        Some(OsString::from_wide(&wide))
+    }
+}
+
+/// Lossless encoding of a full path, only needed when it is not valid UTF-8
+pub fn raw_path_if_lossy(path: &Path) -> Option<String> {
+    match path.to_str() {
+        Some(_) => None,
+        None => Some(os_to_hex(path.as_os_str())),
+    }
+}
+
+/// Rebuild a path from its stored lossy string and optional raw form
+pub fn path_from_stored(lossy: &str, raw_hex: Option<&str>) -> PathBuf {
+    raw_hex
+        .and_then(os_from_hex)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(lossy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_name_is_not_lossy() {
+        let name = FileName::of(Path::new("/videos/café.mp4"));
+        assert_eq!(name.display, "café.mp4");
+        assert!(!name.is_lossy);
+        assert_eq!(name.raw_hex, hex::encode("café.mp4"));
+    }
+
+    #[test]
+    fn test_missing_name_falls_back() {
+        let name = FileName::of(Path::new("/"));
+        assert_eq!(name.display, FALLBACK_NAME);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_name_is_preserved() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let raw = OsStr::from_bytes(b"clip-\xff\xfe.mp4");
+        let path = Path::new("/videos").join(raw);
+        let name = FileName::of(&path);
+
+        assert!(name.is_lossy);
+        assert_eq!(name.display, "clip-\u{fffd}\u{fffd}.mp4");
+        assert_eq!(name.raw_hex, hex::encode(b"clip-\xff\xfe.mp4"));
+        assert_eq!(
+            raw_path_if_lossy(&path),
+            Some(hex::encode(b"/videos/clip-\xff\xfe.mp4"))
+        );
+        assert_eq!(raw_path_if_lossy(Path::new("/videos/ok.mp4")), None);
+
+        let stored = raw_path_if_lossy(&path);
+        assert_eq!(path_from_stored("ignored", stored.as_deref()), path);
+        assert_eq!(os_from_hex(&name.raw_hex).as_deref(), Some(raw));
+    }
+}
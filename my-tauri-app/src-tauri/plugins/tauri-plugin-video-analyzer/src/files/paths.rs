@@ -0,0 +1,267 @@
+//! Normalization of paths coming from the frontend
+//!
+//! Paths cross the JS ↔ Rust boundary as plain strings and arrive in all
+//! sorts of shapes: `file://` URLs, quoted paste from Explorer, forward
+//! slashes, `\\?\` verbatim prefixes from a previous canonicalize, UNC
+//! shares and mapped drives. Every command that accepts a path runs it
+//! through [`normalize_path`] first so the rest of the code sees one form.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Longest path Win32 APIs accept without the `\\?\` prefix
+///
+/// MAX_PATH is 260 including the NUL terminator, but directory APIs cap
+/// out at 248 (room for an 8.3 file name), so stay under that.
+const WINDOWS_MAX_PATH: usize = 247;
+
+/// Normalize a user/frontend supplied path string into a `PathBuf`
+///
+/// Does not touch the filesystem; existence and symlinks are handled by
+/// validation and [`super::volume::resolve_path`].
+pub fn normalize_path(input: &str) -> Result<PathBuf, String> {
+    let trimmed = input.trim().trim_matches('"').trim();
+    if trimmed.is_empty() {
+        return Err("Path is empty".to_string());
+    }
+
+    // File URLs may percent-encode bytes that are not valid UTF-8, so keep
+    // the decoded path as an OS path rather than going back through a String
+    if trimmed.starts_with("file://") {
+        let path = url::Url::parse(trimmed)
+            .ok()
+            .and_then(|u| u.to_file_path().ok())
+            .ok_or_else(|| format!("Invalid file URL: {}", trimmed))?;
+        return Ok(match path.to_str() {
+            Some(s) if cfg!(windows) => PathBuf::from(normalize_windows(s)),
+            _ => path,
+        });
+    }
+
+    if cfg!(windows) {
+        Ok(PathBuf::from(normalize_windows(trimmed)))
+    } else {
+        Ok(PathBuf::from(trimmed))
+    }
+}
+
+/// Canonical textual form of a Windows path
+///
+/// - `/` becomes `\` and repeated separators collapse
+/// - `\\?\C:\...` becomes `C:\...` and `\\?\UNC\srv\share` becomes `\\srv\share`
+/// - `//srv/share` is recognised as UNC
+/// - drive letters are upper-cased so `c:\a` and `C:\a` compare equal
+///
+/// Pure string manipulation so it is testable on every platform.
+pub fn normalize_windows(path: &str) -> String {
+    let unified = path.replace('/', "\\");
+
+    // Strip verbatim prefixes. Keep `\\?\` for anything that is not a plain
+    // drive or UNC path (e.g. `\\?\Volume{GUID}`), since it can't be expressed
+    // without it.
+    let (is_unc, rest) = if let Some(rest) = strip_prefix_ci(&unified, r"\\?\UNC\") {
+        (true, rest.to_string())
+    } else if let Some(rest) = unified.strip_prefix(r"\\?\") {
+        if is_drive_path(rest) {
+            (false, rest.to_string())
+        } else {
+            return unified;
+        }
+    } else if let Some(rest) = unified.strip_prefix(r"\\") {
+        (true, rest.to_string())
+    } else {
+        (false, unified)
+    };
+
+    let mut out = String::with_capacity(rest.len() + 2);
+    if is_unc {
+        out.push_str(r"\\");
+    }
+    let mut prev_sep = is_unc;
+    for c in rest.chars() {
+        if c == '\\' {
+            if prev_sep {
+                continue;
+            }
+            prev_sep = true;
+        } else {
+            prev_sep = false;
+        }
+        out.push(c);
+    }
+
+    if !is_unc && is_drive_path(&out) {
+        out[..1].make_ascii_uppercase();
+        // A bare `C:` means "current dir on C", which is never what a user picked
+        if out.len() == 2 {
+            out.push('\\');
+        }
+    }
+    out
+}
+
+/// Path to hand to filesystem calls
+///
+/// On Windows, absolute paths longer than MAX_PATH are converted to their
+/// extended-length (`\\?\`) form so deeply nested files can still be
+/// opened. Everywhere else, and for short paths, this is a no-op.
+pub fn fs_path(path: &Path) -> Cow<'_, Path> {
+    if !cfg!(windows) {
+        return Cow::Borrowed(path);
+    }
+    match path.to_str() {
+        Some(s) if s.len() > WINDOWS_MAX_PATH => match extended_length_windows(s) {
+            Some(extended) => Cow::Owned(PathBuf::from(extended)),
+            None => Cow::Borrowed(path),
+        },
+        _ => Cow::Borrowed(path),
+    }
+}
+
+/// Convert an absolute Windows path to its `\\?\` extended-length form
+///
+/// Verbatim paths bypass Win32 normalization, so `.`/`..` segments and
+/// forward slashes are resolved here first. Returns `None` for relative
+/// paths, which cannot be expressed in verbatim form.
+pub fn extended_length_windows(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") {
+        return Some(path.to_string());
+    }
+    let normalized = normalize_windows(path);
+    // `root_len` leading segments (drive, or UNC server + share) are never popped by `..`
+    let (prefix, rest, root_len) = if let Some(rest) = normalized.strip_prefix(r"\\") {
+        (r"\\?\UNC\", rest, 2)
+    } else if is_drive_path(&normalized) && normalized[2..].starts_with('\\') {
+        (r"\\?\", normalized.as_str(), 1)
+    } else {
+        return None;
+    };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('\\') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.len() > root_len {
+                    parts.pop();
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(format!("{}{}", prefix, parts.join("\\")))
+}
+
+fn is_drive_path(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+fn strip_prefix_ci<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.len() >= prefix.len() && s[..prefix.len()].eq_ignore_ascii_case(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_slashes_and_drive_case() {
+        assert_eq!(
+            normalize_windows("c:/Users/me/video.mp4"),
+            r"C:\Users\me\video.mp4"
+        );
+        assert_eq!(
+            normalize_windows(r"C:\foo/bar\\baz.mkv"),
+            r"C:\foo\bar\baz.mkv"
+        );
+        assert_eq!(normalize_windows("d:"), r"D:\");
+    }
+
+    #[test]
+    fn test_unc_paths() {
+        assert_eq!(
+            normalize_windows(r"\\server\share\video.mp4"),
+            r"\\server\share\video.mp4"
+        );
+        assert_eq!(
+            normalize_windows("//server/share/video.mp4"),
+            r"\\server\share\video.mp4"
+        );
+        assert_eq!(
+            normalize_windows(r"\\server\\share\video.mp4"),
+            r"\\server\share\video.mp4"
+        );
+    }
+
+    #[test]
+    fn test_verbatim_prefixes() {
+        assert_eq!(
+            normalize_windows(r"\\?\C:\videos\a.mp4"),
+            r"C:\videos\a.mp4"
+        );
+        assert_eq!(
+            normalize_windows(r"\\?\UNC\nas\media\a.mp4"),
+            r"\\nas\media\a.mp4"
+        );
+        assert_eq!(
+            normalize_windows(r"\\?\unc\nas\media\a.mp4"),
+            r"\\nas\media\a.mp4"
+        );
+        assert_eq!(
+            normalize_windows(r"\\?\Volume{1234}\a.mp4"),
+            r"\\?\Volume{1234}\a.mp4"
+        );
+    }
+
+    #[test]
+    fn test_normalize_path_trims_quotes() {
+        let p = normalize_path("  \"/tmp/clip.mp4\"  ").unwrap();
+        if cfg!(windows) {
+            assert_eq!(p, PathBuf::from(r"\tmp\clip.mp4"));
+        } else {
+            assert_eq!(p, PathBuf::from("/tmp/clip.mp4"));
+        }
+        assert!(normalize_path("   ").is_err());
+    }
+
+    #[test]
+    fn test_extended_length_paths() {
+        assert_eq!(
+            extended_length_windows(r"C:\deep\.\nested\..\video.mp4").as_deref(),
+            Some(r"\\?\C:\deep\video.mp4")
+        );
+        assert_eq!(
+            extended_length_windows("//nas/media/../x/a.mp4").as_deref(),
+            Some(r"\\?\UNC\nas\media\x\a.mp4")
+        );
+        assert_eq!(
+            extended_length_windows(r"C:\..\..\a.mp4").as_deref(),
+            Some(r"\\?\C:\a.mp4")
+        );
+        assert_eq!(extended_length_windows(r"relative\a.mp4"), None);
+        assert_eq!(extended_length_windows(r"C:relative.mp4"), None);
+    }
+
+    #[test]
+    fn test_fs_path_leaves_short_paths_alone() {
+        let short = Path::new("/tmp/a.mp4");
+        assert_eq!(fs_path(short), Cow::Borrowed(short));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_url() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let p = normalize_path("file:///tmp/my%20clip.mp4").unwrap();
+        assert_eq!(p, PathBuf::from("/tmp/my clip.mp4"));
+
+        let p = normalize_path("file:///tmp/clip-%FF.mp4").unwrap();
+        assert_eq!(p.as_os_str().as_bytes(), b"/tmp/clip-\xff.mp4");
+    }
+}
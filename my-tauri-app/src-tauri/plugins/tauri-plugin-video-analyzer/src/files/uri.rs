@@ -0,0 +1,45 @@
+//! URIs that only the mobile OS can open
+//!
+//! Android pickers hand out `content://` URIs under scoped storage, and iOS
+//! hands out security-scoped `file://` URLs outside the app sandbox. Neither
+//! can be opened with a plain `File::open`, so they go through the native
+//! side of the plugin instead of [`super::paths`].
+
+/// Whether `input` has to be opened through the platform rather than as a path
+pub fn is_platform_uri(input: &str) -> bool {
+    needs_platform(input, cfg!(target_os = "ios"))
+}
+
+fn needs_platform(input: &str, ios: bool) -> bool {
+    let input = input.trim().trim_matches('"').trim();
+    let scheme = |prefix: &str| {
+        input
+            .get(..prefix.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(prefix))
+    };
+    scheme("content://") || (ios && scheme("file://"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_uris_need_platform() {
+        assert!(needs_platform(
+            "content://com.android.providers.media.documents/document/video%3A42",
+            false
+        ));
+        assert!(needs_platform(
+            " \"CONTENT://media/external/video/1\" ",
+            false
+        ));
+        assert!(!needs_platform("/sdcard/Movies/clip.mp4", false));
+    }
+
+    #[test]
+    fn test_file_urls_need_platform_only_on_ios() {
+        assert!(!needs_platform("file:///tmp/clip.mp4", false));
+        assert!(needs_platform("file:///private/var/mobile/clip.mov", true));
+    }
+}
@@ -0,0 +1,185 @@
+//! Pre-flight checks for local video files
+//!
+//! `register_local_video` used to forward whatever path it was given, so a
+//! typo or a half-copied file only surfaced as an error deep inside the
+//! backend. These checks run first and also collect the size/hash/mtime
+//! fingerprint the backend stores to detect stale references later.
+
+use super::paths::fs_path;
+use super::unix_seconds;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Bytes read from the start of the file to sniff the container format
+const SNIFF_LEN: usize = 8192;
+
+/// Read buffer used while hashing
+const HASH_BUF_LEN: usize = 1024 * 1024;
+
+/// A local video that passed validation, with its fingerprint
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ValidatedVideo {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the full file contents
+    pub sha256: String,
+    /// Last modification time as seconds since the Unix epoch
+    pub modified_at: f64,
+    /// Container detected from the file header (e.g. "mp4", "mkv")
+    pub container: String,
+}
+
+/// Validate that `path` points at a readable, regular video file
+///
+/// Checks, in order:
+/// 1. The path exists
+/// 2. It is a regular file (not a directory, device, etc.)
+/// 3. It is non-empty and can be opened for reading
+/// 4. The header matches a known video container
+///
+/// On success the full file is hashed, so this can take a while on large
+/// files; callers should run it before any network work starts.
+pub async fn validate_video_file(path: &Path) -> Result<ValidatedVideo, String> {
+    let io_path = fs_path(path);
+    let metadata = tokio::fs::metadata(&io_path)
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!("File not found: {}", path.display()),
+            std::io::ErrorKind::PermissionDenied => {
+                format!("Permission denied reading {}", path.display())
+            }
+            _ => format!("Failed to stat {}: {}", path.display(), e),
+        })?;
+
+    if !metadata.is_file() {
+        return Err(format!("Not a regular file: {}", path.display()));
+    }
+    if metadata.len() == 0 {
+        return Err(format!("File is empty: {}", path.display()));
+    }
+
+    let modified_at = metadata.modified().map(unix_seconds).unwrap_or(0.0);
+
+    let mut file = tokio::fs::File::open(&io_path)
+        .await
+        .map_err(|e| format!("File is not readable {}: {}", path.display(), e))?;
+
+    let mut header = vec![0u8; SNIFF_LEN];
+    let n = read_up_to(&mut file, &mut header)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    header.truncate(n);
+
+    let container = sniff_container(&header).ok_or_else(|| {
+        format!(
+            "Unrecognized video container: {} (expected mp4, mov, mkv, webm, avi, ...)",
+            path.display()
+        )
+    })?;
+
+    // Hash the header we already read, then the rest of the file
+    let mut hasher = Sha256::new();
+    hasher.update(&header);
+    let sha256 = hash_remaining(&mut file, hasher)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    Ok(ValidatedVideo {
+        path: path.to_path_buf(),
+        size_bytes: metadata.len(),
+        sha256,
+        modified_at,
+        container: container.to_string(),
+    })
+}
+
+/// Hex-encoded SHA-256 of the whole file at `path`
+pub async fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = tokio::fs::File::open(fs_path(path))
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    hash_remaining(&mut file, Sha256::new())
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// A hasher fed the first `len` bytes of `file`, which is left at `len`
+pub async fn hash_prefix(file: &mut tokio::fs::File, len: u64) -> std::io::Result<Sha256> {
+    file.seek(std::io::SeekFrom::Start(0)).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; HASH_BUF_LEN];
+    let mut left = len;
+    while left > 0 {
+        let want = buf.len().min(usize::try_from(left).unwrap_or(usize::MAX));
+        let n = file.read(&mut buf[..want]).await?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        hasher.update(&buf[..n]);
+        left -= n as u64;
+    }
+    Ok(hasher)
+}
+
+/// Hex-encoded SHA-256 of all of `file`, which is left at its end
+pub async fn hash_open_file(file: &mut tokio::fs::File) -> std::io::Result<String> {
+    let hasher = hash_prefix(file, 0).await?;
+    hash_remaining(file, hasher).await
+}
+
+/// Feed the rest of `file` into `hasher` and return the hex digest
+async fn hash_remaining(file: &mut tokio::fs::File, mut hasher: Sha256) -> std::io::Result<String> {
+    let mut buf = vec![0u8; HASH_BUF_LEN];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Detect the video container from the first bytes of a file
+///
+/// Returns the canonical extension of the container, or `None` when the
+/// header does not look like any video format we know about.
+pub fn sniff_container(header: &[u8]) -> Option<&'static str> {
+    infer::get(header)
+        .filter(|kind| kind.matcher_type() == infer::MatcherType::Video)
+        .map(|kind| kind.extension())
+}
+
+/// Fill `buf` as far as possible, stopping early only at EOF
+async fn read_up_to(file: &mut tokio::fs::File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = file.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_mp4() {
+        let header = [
+            0x00, 0x00, 0x00, 0x18, b'f', b't', b'y', b'p', b'i', b's', b'o', b'm', 0x00, 0x00,
+            0x02, 0x00,
+        ];
+        assert_eq!(sniff_container(&header), Some("mp4"));
+    }
+
+    #[test]
+    fn test_sniff_rejects_non_video() {
+        assert_eq!(sniff_container(b"%PDF-1.7 not a video"), None);
+        assert_eq!(sniff_container(b""), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Symlink resolution and volume detection for referenced videos
+//!
+//! Reference-only registration stores a path, not a copy. That breaks in
+//! ways the user never sees coming when the path is a symlink to a USB
+//! stick or lives on an SMB share, so we resolve the real location and
+//! record which volume it is on.
+
+use super::paths::fs_path;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Rough classification of the volume a file lives on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeKind {
+    Local,
+    Removable,
+    Network,
+    Unknown,
+}
+
+/// The volume a file lives on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeInfo {
+    pub kind: VolumeKind,
+    /// Stable identifier for the volume (device id, serial number or share root)
+    pub id: String,
+    /// Filesystem type where the platform reports one (e.g. "ext4", "cifs")
+    pub fs_type: String,
+}
+
+/// A user-supplied path resolved to its real on-disk location
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedPath {
+    pub original: PathBuf,
+    pub canonical: PathBuf,
+    pub is_symlink: bool,
+    pub volume: VolumeInfo,
+}
+
+impl ResolvedPath {
+    /// Human-readable warnings about how fragile a reference to this path is
+    pub fn warnings(&self, reference_only: bool) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.is_symlink {
+            warnings.push(format!(
+                "{} is a symlink; the video will be registered at its target {}",
+                self.original.display(),
+                self.canonical.display()
+            ));
+        }
+        if reference_only {
+            match self.volume.kind {
+                VolumeKind::Removable => warnings.push(
+                    "The video is on a removable drive; analysis will fail while it is unplugged. \
+                     Consider copying it into storage instead."
+                        .to_string(),
+                ),
+                VolumeKind::Network => warnings.push(
+                    "The video is on a network share; access may be slow or unavailable offline. \
+                     Consider copying it into storage instead."
+                        .to_string(),
+                ),
+                VolumeKind::Local | VolumeKind::Unknown => {}
+            }
+        }
+        warnings
+    }
+}
+
+/// Resolve symlinks in `path` and detect the volume of the target
+pub fn resolve_path(path: &Path) -> Result<ResolvedPath, String> {
+    let io_path = fs_path(path);
+    let is_symlink = std::fs::symlink_metadata(&io_path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    // dunce avoids handing `\\?\` verbatim paths to the backend on Windows
+    let canonical = dunce::canonicalize(&io_path)
+        .map_err(|e| format!("Failed to resolve path {}: {}", path.display(), e))?;
+    let volume = detect_volume(&canonical);
+
+    Ok(ResolvedPath {
+        original: path.to_path_buf(),
+        canonical,
+        is_symlink,
+        volume,
+    })
+}
+
+/// Classify a mount by filesystem type and mount point
+fn classify_mount(fs_type: &str, mount_point: &str) -> VolumeKind {
+    const NETWORK_FS: &[&str] = &[
+        "nfs",
+        "nfs4",
+        "cifs",
+        "smb3",
+        "smbfs",
+        "afpfs",
+        "webdav",
+        "davfs",
+        "9p",
+        "afs",
+        "fuse.sshfs",
+        "fuse.rclone",
+    ];
+    const REMOVABLE_ROOTS: &[&str] = &["/media/", "/run/media/", "/Volumes/"];
+
+    if NETWORK_FS.contains(&fs_type) {
+        VolumeKind::Network
+    } else if REMOVABLE_ROOTS
+        .iter()
+        .any(|root| mount_point.starts_with(root))
+    {
+        VolumeKind::Removable
+    } else {
+        VolumeKind::Local
+    }
+}
+
+/// Find the mount containing `path` in /proc/self/mountinfo contents
+///
+/// Returns `(fs_type, mount_point, major:minor)` of the longest matching
+/// mount point.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn find_mount(mountinfo: &str, path: &Path) -> Option<(String, String, String)> {
+    mountinfo
+        .lines()
+        .filter_map(|line| {
+            // <id> <parent> <major:minor> <root> <mount point> <opts> [optional...] - <fstype> <source> <super opts>
+            let (pre, post) = line.split_once(" - ")?;
+            let fields: Vec<&str> = pre.split(' ').collect();
+            let dev = fields.get(2)?;
+            let mount_point = fields.get(4)?.replace("\\040", " ");
+            let fs_type = post.split(' ').next()?;
+            Some((fs_type.to_string(), mount_point, dev.to_string()))
+        })
+        .filter(|(_, mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(_, mount_point, _)| mount_point.len())
+}
+
+#[cfg(target_os = "linux")]
+fn detect_volume(path: &Path) -> VolumeInfo {
+    let found = std::fs::read_to_string("/proc/self/mountinfo")
+        .ok()
+        .and_then(|info| find_mount(&info, path));
+
+    match found {
+        Some((fs_type, mount_point, dev)) => VolumeInfo {
+            kind: classify_mount(&fs_type, &mount_point),
+            id: format!("dev:{}", dev),
+            fs_type,
+        },
+        None => unix_fallback(path),
+    }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn detect_volume(path: &Path) -> VolumeInfo {
+    #[cfg(target_os = "macos")]
+    {
+        use std::ffi::{CStr, CString};
+        use std::os::unix::ffi::OsStrExt;
+
+        if let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) {
+            let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+            if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } == 0 {
+                let fs_type = unsafe { CStr::from_ptr(stat.f_fstypename.as_ptr()) }
+                    .to_string_lossy()
+                    .to_string();
+                let mount_point = unsafe { CStr::from_ptr(stat.f_mntonname.as_ptr()) }
+                    .to_string_lossy()
+                    .to_string();
+                let mut kind = classify_mount(&fs_type, &format!("{}/", mount_point));
+                if kind == VolumeKind::Removable && stat.f_flags & libc::MNT_LOCAL as u32 == 0 {
+                    kind = VolumeKind::Network;
+                }
+                return VolumeInfo {
+                    kind,
+                    id: unix_fallback(path).id,
+                    fs_type,
+                };
+            }
+        }
+    }
+    unix_fallback(path)
+}
+
+#[cfg(unix)]
+fn unix_fallback(path: &Path) -> VolumeInfo {
+    use std::os::unix::fs::MetadataExt;
+
+    VolumeInfo {
+        kind: VolumeKind::Unknown,
+        id: std::fs::metadata(path)
+            .map(|m| format!("dev:{}", m.dev()))
+            .unwrap_or_default(),
+        fs_type: String::new(),
+    }
+}
+
+#[cfg(windows)]
+fn detect_volume(path: &Path) -> VolumeInfo {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Prefix};
+    use windows_sys::Win32::Storage::FileSystem::{GetDriveTypeW, GetVolumeInformationW};
+
+    const DRIVE_REMOVABLE: u32 = 2;
+    const DRIVE_FIXED: u32 = 3;
+    const DRIVE_REMOTE: u32 = 4;
+    const DRIVE_CDROM: u32 = 5;
+
+    // Volume root, e.g. `C:\` or `\\server\share\`
+    let (root, is_unc) = match path.components().next() {
+        Some(Component::Prefix(prefix)) => {
+            let is_unc = matches!(prefix.kind(), Prefix::UNC(..) | Prefix::VerbatimUNC(..));
+            let mut root = prefix.as_os_str().to_os_string();
+            root.push("\\");
+            (root, is_unc)
+        }
+        _ => {
+            return VolumeInfo {
+                kind: VolumeKind::Unknown,
+                id: String::new(),
+                fs_type: String::new(),
+            }
+        }
+    };
+    let wide: Vec<u16> = root.encode_wide().chain(std::iter::once(0)).collect();
+
+    let drive_type = unsafe { GetDriveTypeW(wide.as_ptr()) };
+    let kind = match drive_type {
+        _ if is_unc => VolumeKind::Network,
+        DRIVE_REMOTE => VolumeKind::Network,
+        DRIVE_REMOVABLE | DRIVE_CDROM => VolumeKind::Removable,
+        DRIVE_FIXED => VolumeKind::Local,
+        _ => VolumeKind::Unknown,
+    };
+
+    let mut serial: u32 = 0;
+    let mut fs_name = [0u16; 64];
+    let ok = unsafe {
+        GetVolumeInformationW(
+            wide.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            &mut serial,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            fs_name.as_mut_ptr(),
+            fs_name.len() as u32,
+        )
+    } != 0;
+
+    let fs_type = if ok {
+        let len = fs_name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(fs_name.len());
+        String::from_utf16_lossy(&fs_name[..len])
+    } else {
+        String::new()
+    };
+    let id = if ok && !is_unc {
+        format!("serial:{:08X}", serial)
+    } else {
+        root.to_string_lossy().to_lowercase()
+    };
+
+    VolumeInfo { kind, id, fs_type }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+40 22 0:35 / /mnt/share rw,relatime shared:20 - cifs //nas/videos rw,vers=3.0
+41 22 8:17 / /media/alice/USB\\040STICK rw,nosuid shared:21 - vfat /dev/sdb1 rw
+";
+
+    #[test]
+    fn test_find_mount_prefers_longest_prefix() {
+        let (fs, mp, dev) = find_mount(MOUNTINFO, Path::new("/mnt/share/a.mp4")).unwrap();
+        assert_eq!(
+            (fs.as_str(), mp.as_str(), dev.as_str()),
+            ("cifs", "/mnt/share", "0:35")
+        );
+        assert_eq!(classify_mount(&fs, &mp), VolumeKind::Network);
+
+        let (fs, _, _) = find_mount(MOUNTINFO, Path::new("/home/alice/a.mp4")).unwrap();
+        assert_eq!(fs, "ext4");
+    }
+
+    #[test]
+    fn test_removable_mount_with_escaped_space() {
+        let (fs, mp, _) =
+            find_mount(MOUNTINFO, Path::new("/media/alice/USB STICK/clip.mov")).unwrap();
+        assert_eq!(mp, "/media/alice/USB STICK");
+        assert_eq!(classify_mount(&fs, &mp), VolumeKind::Removable);
+    }
+}
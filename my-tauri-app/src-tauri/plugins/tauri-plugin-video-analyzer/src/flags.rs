@@ -0,0 +1,183 @@
+//! Feature flags for experimental subsystems
+//!
+//! Experimental code paths ship dark and are switched on per user. A flag's
+//! value is resolved from, lowest to highest precedence:
+//!
+//! 1. the default embedded below
+//! 2. the backend, through `features` in its `ServerInfo`, kept from each
+//!    version handshake (see [`crate::compat`])
+//! 3. the FEATURE_FLAGS environment variable, e.g. `parallel_uploads=on`
+//! 4. the user's own toggle, persisted in `feature_flags.json`
+
+use crate::store::{load_json, save_json};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Every known flag: name, default, description
+const DEFAULTS: &[(&str, bool, &str)] = &[
+    (
+        "parallel_uploads",
+        false,
+        "Upload large videos over several concurrent streams",
+    ),
+    (
+        "local_transcode",
+        false,
+        "Transcode videos with the bundled ffmpeg before uploading",
+    ),
+];
+
+/// Where a flag's effective value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagSource {
+    Default,
+    Backend,
+    Config,
+    User,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlagState {
+    pub enabled: bool,
+    pub source: FlagSource,
+    pub description: &'static str,
+}
+
+/// Managed state resolving flags from all sources
+pub struct FeatureFlags {
+    user_path: PathBuf,
+    user: Mutex<HashMap<String, bool>>,
+    backend: Mutex<HashMap<String, bool>>,
+}
+
+impl FeatureFlags {
+    pub fn load(user_path: PathBuf) -> Self {
+        let user = load_json(&user_path);
+        Self {
+            user_path,
+            user: Mutex::new(user),
+            backend: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<FlagState> {
+        let (_, default, description) = DEFAULTS.iter().find(|(n, _, _)| *n == name)?;
+        let config = config_overrides();
+        let (enabled, source) = if let Some(v) = self.user.lock().unwrap().get(name) {
+            (*v, FlagSource::User)
+        } else if let Some(v) = config.get(name) {
+            (*v, FlagSource::Config)
+        } else if let Some(v) = self.backend.lock().unwrap().get(name) {
+            (*v, FlagSource::Backend)
+        } else {
+            (*default, FlagSource::Default)
+        };
+        Some(FlagState {
+            enabled,
+            source,
+            description,
+        })
+    }
+
+    /// Whether `name` is on; unknown flags are off
+    pub fn enabled(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|state| state.enabled)
+    }
+
+    /// Replace what the backend says about flags with `features`
+    pub fn set_backend(&self, features: HashMap<String, bool>) {
+        *self.backend.lock().unwrap() = features;
+    }
+
+    pub fn all(&self) -> HashMap<&'static str, FlagState> {
+        DEFAULTS
+            .iter()
+            .filter_map(|(name, _, _)| self.get(name).map(|state| (*name, state)))
+            .collect()
+    }
+
+    /// Set (`Some`) or clear (`None`) the user's toggle for a flag
+    pub fn set_user(&self, name: &str, enabled: Option<bool>) -> Result<FlagState, String> {
+        if !DEFAULTS.iter().any(|(n, _, _)| *n == name) {
+            return Err(format!("Unknown feature flag '{}'", name));
+        }
+        {
+            let mut user = self.user.lock().unwrap();
+            match enabled {
+                Some(v) => user.insert(name.to_string(), v),
+                None => user.remove(name),
+            };
+            save_json(&self.user_path, &*user)?;
+        }
+        self.get(name)
+            .ok_or_else(|| format!("Unknown feature flag '{}'", name))
+    }
+}
+
+/// Parse FEATURE_FLAGS (`name=on,other=off`; a bare name means on)
+fn config_overrides() -> HashMap<String, bool> {
+    crate::config::get()
+        .app
+        .feature_flags
+        .as_deref()
+        .map(parse_overrides)
+        .unwrap_or_default()
+}
+
+fn parse_overrides(value: &str) -> HashMap<String, bool> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .filter_map(|item| {
+            let (name, v) = item.split_once('=').unwrap_or((item, "on"));
+            let enabled = match v.trim().to_ascii_lowercase().as_str() {
+                "on" | "true" | "1" | "yes" => true,
+                "off" | "false" | "0" | "no" => false,
+                _ => {
+                    log::warn!("Ignoring FEATURE_FLAGS entry '{}'", item);
+                    return None;
+                }
+            };
+            Some((name.trim().to_string(), enabled))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides() {
+        let flags = parse_overrides("parallel_uploads, local_transcode=off,bogus=maybe,");
+        assert_eq!(flags.get("parallel_uploads"), Some(&true));
+        assert_eq!(flags.get("local_transcode"), Some(&false));
+        assert!(!flags.contains_key("bogus"));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let path = std::env::temp_dir().join(format!("flags-test-{}.json", std::process::id()));
+        let flags = FeatureFlags::load(path.clone());
+        assert_eq!(
+            flags.get("local_transcode").unwrap().source,
+            FlagSource::Default
+        );
+
+        flags.set_backend(HashMap::from([("local_transcode".to_string(), true)]));
+        assert!(flags.enabled("local_transcode"));
+        let state = flags.get("local_transcode").unwrap();
+        assert!(state.enabled);
+        assert_eq!(state.source, FlagSource::Backend);
+
+        let state = flags.set_user("local_transcode", Some(false)).unwrap();
+        assert!(!state.enabled);
+        assert_eq!(state.source, FlagSource::User);
+        assert!(flags.set_user("nope", Some(true)).is_err());
+        std::fs::remove_file(path).ok();
+    }
+}
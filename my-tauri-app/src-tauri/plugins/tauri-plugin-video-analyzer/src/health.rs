@@ -0,0 +1,199 @@
+//! Continuous backend health monitoring
+//!
+//! [`spawn_monitor`] pings the backend every HEALTH_CHECK_INTERVAL_SECS
+//! ([`power::HEALTH_SLOWDOWN`] times less often while saving power) and
+//! keeps its [`Health`] in managed state, emitting `backend://health`
+//! whenever the state changes, so the UI can subscribe instead of polling
+//! `check_backend_ready`:
+//!
+//! - `starting`: not reached yet since the app started
+//! - `ready`: answering within HEALTH_DEGRADED_LATENCY_MS
+//! - `degraded`: answering slowly, or failed a check after being reached
+//! - `down`: failed [`DOWN_AFTER_FAILURES`] checks in a row
+//!
+//! Becoming `ready` also sends the questions queued in the
+//! [`crate::outbox`] while the backend was away.
+
+use crate::commands::READY_TIMEOUT;
+use crate::config;
+use crate::files;
+use crate::power::{self, PowerState};
+use crate::transport::Transport;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Failed checks in a row before the backend counts as down
+pub const DOWN_AFTER_FAILURES: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    #[default]
+    Starting,
+    Ready,
+    Degraded,
+    Down,
+}
+
+/// What one check found: the latency of an answer, or why there was none
+type Outcome = Result<Duration, String>;
+
+/// The backend's health as of the last check
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Health {
+    pub state: HealthState,
+    /// When `state` was entered (Unix seconds)
+    pub since: f64,
+    /// When the backend was last checked (Unix seconds), 0 before the first check
+    pub checked_at: f64,
+    pub latency_ms: Option<u64>,
+    /// Why the last check failed or counted as slow
+    pub message: Option<String>,
+    pub consecutive_failures: u32,
+}
+
+impl Health {
+    /// Fold in a check made at `now`; true when the state changed
+    fn record(&mut self, outcome: Outcome, degraded_after: Duration, now: f64) -> bool {
+        let state = match &outcome {
+            Ok(latency) if *latency > degraded_after => HealthState::Degraded,
+            Ok(_) => HealthState::Ready,
+            Err(_) if self.consecutive_failures + 1 >= DOWN_AFTER_FAILURES => HealthState::Down,
+            Err(_) => match self.state {
+                HealthState::Ready => HealthState::Degraded,
+                state => state,
+            },
+        };
+        match outcome {
+            Ok(latency) => {
+                self.consecutive_failures = 0;
+                self.latency_ms = Some(latency.as_millis() as u64);
+                self.message = (state == HealthState::Degraded)
+                    .then(|| format!("Answered in {} ms", latency.as_millis()));
+            }
+            Err(message) => {
+                self.consecutive_failures += 1;
+                self.latency_ms = None;
+                self.message = Some(message);
+            }
+        }
+        self.checked_at = now;
+        let changed = state != self.state;
+        if changed {
+            self.state = state;
+            self.since = now;
+        }
+        changed
+    }
+}
+
+/// Managed state: the backend's last known [`Health`]
+pub struct BackendHealth(Mutex<Health>);
+
+impl Default for BackendHealth {
+    fn default() -> Self {
+        Self(Mutex::new(Health {
+            since: files::unix_seconds(SystemTime::now()),
+            ..Health::default()
+        }))
+    }
+}
+
+impl BackendHealth {
+    pub fn get(&self) -> Health {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Ping the backend the way `check_backend_ready` does
+async fn check(transport: &Transport) -> Outcome {
+    let started = Instant::now();
+    let ping = crate::transport::with_timeout(Some(READY_TIMEOUT), transport.get_last_session());
+    match tokio::time::timeout(READY_TIMEOUT, ping).await {
+        Ok(Ok(_)) => Ok(started.elapsed()),
+        Ok(Err(e)) => Err(e.message().to_string()),
+        Err(_) => Err("timeout".to_string()),
+    }
+}
+
+/// Check the backend on an interval and emit `backend://health` on
+/// transitions
+pub fn spawn_monitor<R: Runtime>(app: AppHandle<R>) {
+    let config = config::get();
+    let interval_secs = config.app.health_check_interval_secs;
+    let degraded_after = Duration::from_millis(config.app.health_degraded_latency_ms);
+    if interval_secs == 0 {
+        log::info!("Backend health monitor disabled");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        let mut ticks: u64 = 0;
+        loop {
+            interval.tick().await;
+            ticks += 1;
+            // Always check on the first tick, so startup isn't left waiting
+            if ticks > 1
+                && app.state::<PowerState>().is_saving()
+                && !ticks.is_multiple_of(power::HEALTH_SLOWDOWN)
+            {
+                continue;
+            }
+            let transport = app.state::<Transport>().inner().clone();
+            let outcome = check(&transport).await;
+            let now = files::unix_seconds(SystemTime::now());
+            let state = app.state::<BackendHealth>();
+            let health = {
+                let mut health = state.0.lock().unwrap();
+                if !health.record(outcome, degraded_after, now) {
+                    continue;
+                }
+                health.clone()
+            };
+            log::info!(
+                "Backend health: {:?} ({})",
+                health.state,
+                health.message.as_deref().unwrap_or("ok")
+            );
+            app.emit("backend://health", &health).ok();
+            if health.state == HealthState::Ready {
+                crate::outbox::spawn_flush(app.clone());
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SLOW: Duration = Duration::from_secs(2);
+
+    #[test]
+    fn state_follows_the_checks() {
+        let mut health = Health::default();
+        let fail = || Err("connection refused".to_string());
+
+        // Still starting while the backend comes up
+        assert!(!health.record(fail(), SLOW, 1.0));
+        assert_eq!(health.state, HealthState::Starting);
+        assert!(health.record(Ok(Duration::from_millis(40)), SLOW, 2.0));
+        assert_eq!(health.state, HealthState::Ready);
+        assert_eq!(health.since, 2.0);
+
+        assert!(health.record(Ok(Duration::from_secs(3)), SLOW, 3.0));
+        assert_eq!(health.state, HealthState::Degraded);
+        assert!(health.record(Ok(Duration::from_millis(40)), SLOW, 4.0));
+
+        assert!(health.record(fail(), SLOW, 5.0));
+        assert_eq!(health.state, HealthState::Degraded);
+        assert!(!health.record(fail(), SLOW, 6.0));
+        assert!(health.record(fail(), SLOW, 7.0));
+        assert_eq!(health.state, HealthState::Down);
+        assert_eq!(health.consecutive_failures, DOWN_AFTER_FAILURES);
+        assert_eq!(health.message.as_deref(), Some("connection refused"));
+    }
+}
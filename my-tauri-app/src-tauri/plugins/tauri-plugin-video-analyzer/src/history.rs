@@ -0,0 +1,438 @@
+//! Local record of chat exchanges
+//!
+//! The backend keeps the canonical conversation, but some things only the
+//! client knows about (which files were attached, ratings) are recorded
+//! here, per video, in `history.json` in the app data directory. The
+//! [`crate::sync`] task keeps it in step with the backend.
+//!
+//! Answers still streaming are saved as they arrive, as an
+//! [`IncompleteResponse`]; one whose stream broke off stays there (and out
+//! of the message list) until it is retried or the history is cleared.
+
+use crate::store::{load_json, save_json};
+use crate::video_analyzer::{feedback_request, ChatResponse, SessionParams, TimeRange};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Metadata of a file attached to a question; the bytes are not kept
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentInfo {
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalMessage {
+    /// "user" or "assistant", as in the backend's `ChatMessage`
+    pub role: String,
+    pub content: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AttachmentInfo>,
+    /// Part of the video a question was limited to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<TimeRange>,
+    /// User's rating of an assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub feedback: Option<Feedback>,
+    /// When the user pinned it as a key finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_at: Option<f64>,
+    /// Answered from the [`crate::query_cache`]: the backend never saw the
+    /// exchange, so history sync leaves it out
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+}
+
+/// A pinned message and where it sits in the conversation
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedMessage {
+    pub message_index: usize,
+    #[serde(flatten)]
+    pub message: LocalMessage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rating {
+    Up,
+    Down,
+    /// The answer is factually wrong, not just unhelpful
+    Wrong,
+}
+
+impl Rating {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "up" | "good" => Ok(Self::Up),
+            "down" | "bad" => Ok(Self::Down),
+            "wrong" => Ok(Self::Wrong),
+            other => Err(format!(
+                "Unknown rating '{}': expected up, down or wrong",
+                other
+            )),
+        }
+    }
+
+    pub fn to_proto(self) -> feedback_request::Rating {
+        match self {
+            Self::Up => feedback_request::Rating::Up,
+            Self::Down => feedback_request::Rating::Down,
+            Self::Wrong => feedback_request::Rating::Wrong,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Feedback {
+    pub rating: Rating,
+    #[serde(default)]
+    pub comment: String,
+    pub rated_at: f64,
+    /// Whether the backend has acknowledged it
+    #[serde(default)]
+    pub sent: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncState {
+    Synced,
+    /// The backend no longer has the session, so the local copy was removed
+    Deleted,
+    /// Both sides changed; waiting for the user to pick a version
+    Conflict,
+    Error,
+}
+
+/// Outcome of the last sync of one session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    /// Seconds since the Unix epoch
+    pub last_synced_at: f64,
+    /// Messages the backend reported for the session
+    pub remote_count: i32,
+    #[serde(default)]
+    pub remote_updated_at: String,
+    /// Messages pulled from the backend in that sync
+    #[serde(default)]
+    pub pulled: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Local message count and hash right after that sync, to tell later
+    /// appends apart from clears and rewrites
+    #[serde(default)]
+    pub synced_count: usize,
+    #[serde(default)]
+    pub synced_hash: String,
+    /// Hash of the backend's recent messages at that sync
+    #[serde(default)]
+    pub remote_hash: String,
+}
+
+/// Local and backend versions of a session that diverged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    pub video_id: String,
+    pub detected_at: f64,
+    pub local: Vec<LocalMessage>,
+    /// The backend's recent messages; it may hold older ones too
+    pub remote: Vec<LocalMessage>,
+    pub remote_total: i32,
+}
+
+/// A chat answer that has not (yet) streamed to the end
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncompleteResponse {
+    /// Sent again on retry so the backend can resume the stream
+    pub request_id: String,
+    pub query: String,
+    #[serde(default)]
+    pub query_type: String,
+    /// Paths of the attached files, read again on retry
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    #[serde(default)]
+    pub range: Option<TimeRange>,
+    pub started_at: f64,
+    pub updated_at: f64,
+    /// Chunks received so far, in order
+    pub chunks: Vec<ChatResponse>,
+    /// Why the stream stopped; `None` if the app closed while it streamed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryFile {
+    videos: HashMap<String, Vec<LocalMessage>>,
+    #[serde(default)]
+    sync: HashMap<String, SyncStatus>,
+    #[serde(default)]
+    conflicts: HashMap<String, SyncConflict>,
+    #[serde(default)]
+    incomplete: HashMap<String, Vec<IncompleteResponse>>,
+    /// Model overrides per video; kept when the chat is cleared
+    #[serde(default)]
+    params: HashMap<String, SessionParams>,
+}
+
+pub struct LocalHistory {
+    path: PathBuf,
+    data: Mutex<HistoryFile>,
+}
+
+impl LocalHistory {
+    pub fn load(path: PathBuf) -> Self {
+        let data = load_json(&path);
+        Self {
+            path,
+            data: Mutex::new(data),
+        }
+    }
+
+    /// Append a message and persist; returns its index within the video's history
+    pub fn append(&self, video_id: &str, message: LocalMessage) -> Result<usize, String> {
+        let mut data = self.data.lock().unwrap();
+        let messages = data.videos.entry(video_id.to_string()).or_default();
+        messages.push(message);
+        let index = messages.len() - 1;
+        save_json(&self.path, &*data)?;
+        Ok(index)
+    }
+
+    /// Append several messages (e.g. pulled from the backend) and persist
+    pub fn extend(&self, video_id: &str, messages: Vec<LocalMessage>) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        data.videos
+            .entry(video_id.to_string())
+            .or_default()
+            .extend(messages);
+        save_json(&self.path, &*data)
+    }
+
+    /// Replace a video's messages (resolving a sync conflict) and persist
+    pub fn replace(&self, video_id: &str, messages: Vec<LocalMessage>) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        data.videos.insert(video_id.to_string(), messages);
+        save_json(&self.path, &*data)
+    }
+
+    /// Videos with a local history
+    pub fn video_ids(&self) -> Vec<String> {
+        self.data.lock().unwrap().videos.keys().cloned().collect()
+    }
+
+    pub fn messages(&self, video_id: &str) -> Vec<LocalMessage> {
+        self.data
+            .lock()
+            .unwrap()
+            .videos
+            .get(video_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// `video_id`'s pinned messages, in the order they were pinned
+    pub fn pinned(&self, video_id: &str) -> Vec<PinnedMessage> {
+        let mut pinned: Vec<PinnedMessage> = self
+            .messages(video_id)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, m)| m.pinned_at.is_some())
+            .map(|(message_index, message)| PinnedMessage {
+                message_index,
+                message,
+            })
+            .collect();
+        pinned.sort_by(|a, b| {
+            let at = |p: &PinnedMessage| p.message.pinned_at.unwrap_or_default();
+            at(a).total_cmp(&at(b))
+        });
+        pinned
+    }
+
+    /// Modify one message in place and persist; `None` if there is no such message
+    pub fn update<F>(
+        &self,
+        video_id: &str,
+        index: usize,
+        f: F,
+    ) -> Result<Option<LocalMessage>, String>
+    where
+        F: FnOnce(&mut LocalMessage),
+    {
+        let mut data = self.data.lock().unwrap();
+        let Some(message) = data.videos.get_mut(video_id).and_then(|m| m.get_mut(index)) else {
+            return Ok(None);
+        };
+        f(message);
+        let updated = message.clone();
+        save_json(&self.path, &*data)?;
+        Ok(Some(updated))
+    }
+
+    pub fn sync_status(&self, video_id: &str) -> Option<SyncStatus> {
+        self.data.lock().unwrap().sync.get(video_id).cloned()
+    }
+
+    pub fn sync_statuses(&self) -> HashMap<String, SyncStatus> {
+        self.data.lock().unwrap().sync.clone()
+    }
+
+    pub fn set_sync_status(&self, video_id: &str, status: SyncStatus) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        data.sync.insert(video_id.to_string(), status);
+        save_json(&self.path, &*data)
+    }
+
+    pub fn conflict(&self, video_id: &str) -> Option<SyncConflict> {
+        self.data.lock().unwrap().conflicts.get(video_id).cloned()
+    }
+
+    pub fn conflicts(&self) -> Vec<SyncConflict> {
+        self.data
+            .lock()
+            .unwrap()
+            .conflicts
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Record (`Some`) or drop (`None`) a conflict and persist
+    pub fn set_conflict(
+        &self,
+        video_id: &str,
+        conflict: Option<SyncConflict>,
+    ) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        match conflict {
+            Some(c) => data.conflicts.insert(video_id.to_string(), c),
+            None => data.conflicts.remove(video_id),
+        };
+        save_json(&self.path, &*data)
+    }
+
+    pub fn incomplete(&self, video_id: &str) -> Vec<IncompleteResponse> {
+        self.data
+            .lock()
+            .unwrap()
+            .incomplete
+            .get(video_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Record or update (by `request_id`) an incomplete answer and persist
+    pub fn set_incomplete(
+        &self,
+        video_id: &str,
+        response: IncompleteResponse,
+    ) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        let responses = data.incomplete.entry(video_id.to_string()).or_default();
+        match responses
+            .iter_mut()
+            .find(|r| r.request_id == response.request_id)
+        {
+            Some(existing) => *existing = response,
+            None => responses.push(response),
+        }
+        save_json(&self.path, &*data)
+    }
+
+    /// Drop an incomplete answer once it has completed
+    pub fn remove_incomplete(&self, video_id: &str, request_id: &str) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        let Some(responses) = data.incomplete.get_mut(video_id) else {
+            return Ok(());
+        };
+        let before = responses.len();
+        responses.retain(|r| r.request_id != request_id);
+        if responses.len() == before {
+            return Ok(());
+        }
+        if responses.is_empty() {
+            data.incomplete.remove(video_id);
+        }
+        save_json(&self.path, &*data)
+    }
+
+    pub fn session_params(&self, video_id: &str) -> Option<SessionParams> {
+        self.data.lock().unwrap().params.get(video_id).cloned()
+    }
+
+    /// Store `video_id`'s model overrides; all-default params remove them
+    pub fn set_session_params(&self, video_id: &str, params: SessionParams) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        if params == SessionParams::default() {
+            if data.params.remove(video_id).is_none() {
+                return Ok(());
+            }
+        } else {
+            data.params.insert(video_id.to_string(), params);
+        }
+        save_json(&self.path, &*data)
+    }
+
+    pub fn clear(&self, video_id: &str) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        let had_messages = data.videos.remove(video_id).is_some();
+        let had_incomplete = data.incomplete.remove(video_id).is_some();
+        if had_messages || had_incomplete {
+            save_json(&self.path, &*data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rating_parse() {
+        assert_eq!(Rating::parse("Up").unwrap(), Rating::Up);
+        assert_eq!(Rating::parse("bad").unwrap(), Rating::Down);
+        assert_eq!(Rating::parse(" wrong ").unwrap(), Rating::Wrong);
+        assert!(Rating::parse("5 stars").is_err());
+    }
+
+    #[test]
+    fn pinned_messages_keep_their_index() {
+        let path = std::env::temp_dir().join(format!("history-test-{}.json", std::process::id()));
+        let history = LocalHistory::load(path.clone());
+        for content in ["What happens?", "A dog runs", "Which colour?", "Brown"] {
+            let message = LocalMessage {
+                role: "user".to_string(),
+                content: content.to_string(),
+                timestamp: 0.0,
+                attachments: Vec::new(),
+                range: None,
+                feedback: None,
+                pinned_at: None,
+                cached: false,
+            };
+            history.append("v1", message).unwrap();
+        }
+        history
+            .update("v1", 3, |m| m.pinned_at = Some(1.0))
+            .unwrap();
+        history
+            .update("v1", 1, |m| m.pinned_at = Some(2.0))
+            .unwrap();
+
+        let pinned = LocalHistory::load(path.clone()).pinned("v1");
+        let indexes: Vec<_> = pinned.iter().map(|p| p.message_index).collect();
+        assert_eq!(indexes, [3, 1]);
+        assert_eq!(pinned[1].message.content, "A dog runs");
+        std::fs::remove_file(path).ok();
+    }
+}
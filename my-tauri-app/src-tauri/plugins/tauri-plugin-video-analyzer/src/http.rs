@@ -0,0 +1,172 @@
+//! Minimal HTTP client for the few non-gRPC calls the app makes
+//!
+//! Like the gRPC channel, this speaks `https://` with rustls and the
+//! system's trusted roots, or plain `http://`. Bug reports carry logs and
+//! local paths, so they only go out in cleartext to this machine.
+
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request};
+use hyper_rustls::HttpsConnector;
+use std::net::IpAddr;
+use std::time::Duration;
+
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A client for `http://` and `https://` URLs, trusting the system's roots
+pub fn client() -> Client<HttpsConnector<HttpConnector>> {
+    let connector = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .https_or_http()
+        .enable_http1()
+        .build();
+    Client::builder().build(connector)
+}
+
+/// POST a JSON body; returns the status code and response body
+///
+/// The body leaves the machine, so `url` has to be `https://` unless it
+/// names this machine.
+pub async fn post_json(url: &str, body: &serde_json::Value) -> Result<(u16, String), String> {
+    let uri = parse_private_uri(url)?;
+    let payload =
+        serde_json::to_vec(body).map_err(|e| format!("Failed to serialize request: {}", e))?;
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(payload))
+        .map_err(|e| format!("Failed to build request: {}", e))?;
+    send(url, request).await
+}
+
+/// GET a URL; returns the status code and response body
+pub async fn get(url: &str) -> Result<(u16, String), String> {
+    let uri = parse_uri(url)?;
+    let request = Request::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .body(Body::empty())
+        .map_err(|e| format!("Failed to build request: {}", e))?;
+    send(url, request).await
+}
+
+/// `url` as an `http://` or `https://` URI
+pub fn parse_uri(url: &str) -> Result<hyper::Uri, String> {
+    let uri: hyper::Uri = url
+        .parse()
+        .map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    if !matches!(uri.scheme_str(), Some("http" | "https")) {
+        return Err(format!(
+            "Unsupported URL scheme in {}: only http:// and https:// endpoints are supported",
+            url
+        ));
+    }
+    Ok(uri)
+}
+
+/// `url` as a URI that is safe to send private data to: `https://`, or
+/// `http://` to this machine
+fn parse_private_uri(url: &str) -> Result<hyper::Uri, String> {
+    let uri = parse_uri(url)?;
+    if uri.scheme_str() == Some("http") && !is_loopback(uri.host().unwrap_or_default()) {
+        return Err(format!(
+            "Refusing to send data to {} in cleartext; use an https:// endpoint",
+            url
+        ));
+    }
+    Ok(uri)
+}
+
+/// Whether `host` is this machine, e.g. `localhost`, `127.0.0.1` or `[::1]`
+fn is_loopback(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+async fn send(url: &str, request: Request<Body>) -> Result<(u16, String), String> {
+    let response = tokio::time::timeout(REQUEST_TIMEOUT, client().request(request))
+        .await
+        .map_err(|_| format!("Request to {} timed out", url))?
+        .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+    let status = response.status().as_u16();
+    let bytes = hyper::body::to_bytes(response.into_body())
+        .await
+        .map_err(|e| format!("Failed to read response from {}: {}", url, e))?;
+    Ok((status, String::from_utf8_lossy(&bytes).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uri_schemes() {
+        assert!(parse_uri("http://10.0.0.2:8080/v1").is_ok());
+        assert!(parse_uri("https://reports.example.com/upload").is_ok());
+        assert!(parse_uri("ftp://reports.example.com/upload")
+            .unwrap_err()
+            .contains("Unsupported URL scheme"));
+        assert!(parse_uri("not a url").is_err());
+    }
+
+    #[test]
+    fn test_private_data_needs_https_off_this_machine() {
+        assert!(parse_private_uri("https://reports.example.com/upload").is_ok());
+        for local in [
+            "http://localhost:8080/upload",
+            "http://127.0.0.1:8080/upload",
+            "http://[::1]:8080/upload",
+        ] {
+            assert!(parse_private_uri(local).is_ok(), "{local}");
+        }
+        assert!(parse_private_uri("http://reports.example.com/upload")
+            .unwrap_err()
+            .contains("cleartext"));
+        assert!(parse_private_uri("http://10.0.0.2/upload").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_schemes() {
+        // Answers one request with a fixed body
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let (mut request, mut buf) = (Vec::new(), [0u8; 1024]);
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let read = socket.read(&mut buf).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n[]")
+                .await
+                .unwrap();
+        });
+        assert_eq!(
+            get(&format!("http://{}/notes.json", addr)).await.unwrap(),
+            (200, "[]".to_string())
+        );
+
+        // https gets as far as connecting; nothing listens there
+        let closed = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let error = get(&format!("https://{}/notes.json", closed))
+            .await
+            .unwrap_err();
+        assert!(error.contains("failed"), "{error}");
+
+        let error = get("ftp://notes.example.com/notes.json").await.unwrap_err();
+        assert!(error.contains("Unsupported URL scheme"), "{error}");
+    }
+}
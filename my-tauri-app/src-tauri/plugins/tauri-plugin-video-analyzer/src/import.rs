@@ -0,0 +1,321 @@
+//! Finding videos to import in a directory tree
+//!
+//! `import_directory` walks a folder with [`discover`], keeping the files
+//! that pass the [`ImportOptions`] filters, then registers each one the
+//! way `register_local_video` would. Hidden files and directories are
+//! skipped unless asked for, and symlinked directories are never entered,
+//! so a link back up the tree can't loop. Along the way:
+//!
+//! - `video-analyzer://import-progress` with [`ImportProgress`] while
+//!   discovering, and after each file while registering
+//! - `video-analyzer://import-file` with an [`ImportFile`] once each file
+//!   is registered, skipped as already in the library, or failed
+//!
+//! One file failing doesn't stop the others; the [`ImportSummary`] lists
+//! how each went.
+
+use crate::cleanup;
+use crate::error::AppError;
+use crate::files;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Extensions imported when the options don't list any
+pub const DEFAULT_EXTENSIONS: &[&str] = &["mp4", "m4v", "mov", "mkv", "webm", "avi"];
+
+/// Entries looked at between two discovery progress reports
+pub const PROGRESS_EVERY: usize = 50;
+
+/// What `import_directory` takes and what it does with it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ImportOptions {
+    /// File extensions to take, without the dot, in any case; empty for
+    /// [`DEFAULT_EXTENSIONS`]
+    pub extensions: Vec<String>,
+    pub min_size_bytes: Option<u64>,
+    pub max_size_bytes: Option<u64>,
+    /// Only files modified within this age, e.g. "30d", see
+    /// [`cleanup::parse_age`]
+    pub modified_within: Option<String>,
+    /// Descend into subdirectories
+    pub recursive: bool,
+    /// Levels below the root to descend, when recursive; unlimited if unset
+    pub max_depth: Option<usize>,
+    pub include_hidden: bool,
+    /// Register what is found; without it the import only reports it
+    pub register: bool,
+    /// Register files by reference rather than copying them to the backend
+    pub reference_only: bool,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            extensions: Vec::new(),
+            min_size_bytes: None,
+            max_size_bytes: None,
+            modified_within: None,
+            recursive: true,
+            max_depth: None,
+            include_hidden: false,
+            register: true,
+            reference_only: true,
+        }
+    }
+}
+
+/// A file that passed the filters
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    pub modified_at: f64,
+}
+
+/// What a walk found
+#[derive(Debug, Default, Serialize)]
+pub struct Discovery {
+    /// Files and directories looked at
+    pub scanned: usize,
+    /// Files that didn't pass the filters
+    pub filtered_out: usize,
+    /// Directories that couldn't be read, with why
+    pub unreadable: Vec<String>,
+    pub candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportPhase {
+    Discovering,
+    Registering,
+}
+
+/// Payload of a `video-analyzer://import-progress` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub import_id: String,
+    pub phase: ImportPhase,
+    pub scanned: usize,
+    pub found: usize,
+    /// Files registered, skipped or failed so far
+    pub done: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportState {
+    /// Found by an import that only reports
+    Found,
+    Registered,
+    /// Already in the library under the same path
+    Skipped,
+    Failed,
+}
+
+/// One file found and what became of it
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportFile {
+    pub import_id: String,
+    pub path: String,
+    pub size_bytes: u64,
+    pub state: ImportState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<AppError>,
+}
+
+/// What `import_directory` answers with
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub import_id: String,
+    pub root: String,
+    pub scanned: usize,
+    pub filtered_out: usize,
+    pub unreadable: Vec<String>,
+    pub found: usize,
+    pub registered: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// In path order
+    pub files: Vec<ImportFile>,
+}
+
+/// [`ImportOptions`] checked and ready to match files against
+struct Filter {
+    extensions: Vec<String>,
+    min_size: u64,
+    max_size: u64,
+    modified_after: f64,
+}
+
+impl Filter {
+    fn new(options: &ImportOptions, now: SystemTime) -> Result<Self, String> {
+        let extensions: Vec<String> = if options.extensions.is_empty() {
+            DEFAULT_EXTENSIONS.iter().map(|e| e.to_string()).collect()
+        } else {
+            options
+                .extensions
+                .iter()
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .filter(|e| !e.is_empty())
+                .collect()
+        };
+        let min_size = options.min_size_bytes.unwrap_or(0);
+        let max_size = options.max_size_bytes.unwrap_or(u64::MAX);
+        if min_size > max_size {
+            return Err(format!(
+                "min_size_bytes ({}) is larger than max_size_bytes ({})",
+                min_size, max_size
+            ));
+        }
+        Ok(Self {
+            extensions,
+            min_size,
+            max_size,
+            modified_after: cleanup::cutoff(options.modified_within.as_deref(), now)?,
+        })
+    }
+
+    fn matches(&self, path: &Path, size: u64, modified_at: f64) -> bool {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        self.extensions.contains(&extension)
+            && (self.min_size..=self.max_size).contains(&size)
+            && modified_at >= self.modified_after
+    }
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// Walk `root` for files passing `options`, in path order; `on_progress`
+/// hears how far the walk is every [`PROGRESS_EVERY`] entries
+pub fn discover(
+    root: &Path,
+    options: &ImportOptions,
+    now: SystemTime,
+    mut on_progress: impl FnMut(&Discovery),
+) -> Result<Discovery, String> {
+    let filter = Filter::new(options, now)?;
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", root.display()));
+    }
+    let mut discovery = Discovery::default();
+    let mut pending = vec![(root.to_path_buf(), 0usize)];
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = match std::fs::read_dir(files::paths::fs_path(&dir)) {
+            Ok(entries) => entries,
+            Err(e) => {
+                discovery
+                    .unreadable
+                    .push(format!("{}: {}", dir.display(), e));
+                continue;
+            }
+        };
+        let mut entries: Vec<_> = entries.flatten().collect();
+        entries.sort_by_key(|entry| entry.file_name());
+        let mut subdirs = Vec::new();
+        for entry in entries {
+            let path = entry.path();
+            discovery.scanned += 1;
+            if discovery.scanned % PROGRESS_EVERY == 0 {
+                on_progress(&discovery);
+            }
+            if !options.include_hidden && is_hidden(&path) {
+                continue;
+            }
+            // Not followed through symlinks, so a link can't loop the walk
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let deeper = options.max_depth.is_none_or(|max| depth < max);
+                if options.recursive && deeper {
+                    subdirs.push((path, depth + 1));
+                }
+                continue;
+            }
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified_at = metadata.modified().map(files::unix_seconds).unwrap_or(0.0);
+            if filter.matches(&path, metadata.len(), modified_at) {
+                discovery.candidates.push(Candidate {
+                    path,
+                    size_bytes: metadata.len(),
+                    modified_at,
+                });
+            } else {
+                discovery.filtered_out += 1;
+            }
+        }
+        // Popped from the end, so push in reverse to walk in path order
+        pending.extend(subdirs.into_iter().rev());
+    }
+    on_progress(&discovery);
+    Ok(discovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walk_applies_the_filters() {
+        let root = std::env::temp_dir().join(format!("import-test-{}", std::process::id()));
+        let write = |path: &str, len: usize| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, vec![0u8; len]).unwrap();
+        };
+        write("a.mp4", 10);
+        write("notes.txt", 10);
+        write("tiny.MKV", 1);
+        write("season/one/b.mov", 10);
+        write(".cache/c.mp4", 10);
+
+        let names = |options: &ImportOptions| -> Vec<String> {
+            discover(&root, options, SystemTime::now(), |_| {})
+                .unwrap()
+                .candidates
+                .iter()
+                .map(|c| {
+                    c.path
+                        .strip_prefix(&root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect()
+        };
+        let options = ImportOptions::default();
+        assert_eq!(names(&options), ["a.mp4", "tiny.MKV", "season/one/b.mov"]);
+
+        let options = ImportOptions {
+            min_size_bytes: Some(5),
+            max_depth: Some(1),
+            include_hidden: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&options), ["a.mp4", ".cache/c.mp4"]);
+
+        let options = ImportOptions {
+            extensions: vec![".TXT".to_string()],
+            recursive: false,
+            ..Default::default()
+        };
+        assert_eq!(names(&options), ["notes.txt"]);
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
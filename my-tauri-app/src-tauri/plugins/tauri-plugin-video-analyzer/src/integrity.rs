@@ -0,0 +1,348 @@
+//! Startup check of the local data, repairing what it can
+//!
+//! Runs before any store is loaded. [`load_json`](crate::store::load_json)
+//! would quietly start a damaged store from empty and overwrite it on the
+//! next save, losing any chance to recover it; instead damaged files are
+//! moved to `quarantine/<unix time>/` and the store rebuilds from empty
+//! (caches are fetched from the backend again). What was found and done
+//! is kept as an [`IntegrityReport`] for `get_integrity_report`, along
+//! with any store that still couldn't be opened and runs in memory.
+//!
+//! Checked:
+//! - the JSON stores and per-video cache files parse
+//! - the session cache's SQLite database passes `PRAGMA integrity_check`
+//! - no half-written `*.json.tmp` files or `*.partial` pin downloads are
+//!   left over from a crash
+//! - every pinned offline video still has all the files its manifest in
+//!   `offline.json` lists, at the recorded sizes
+
+use crate::offline::PinnedVideo;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Whole-file JSON stores
+const STORES: &[&str] = &[
+    "library.json",
+    "uploads.json",
+    "history.json",
+    "feature_flags.json",
+    "bookmarks.json",
+    "power.json",
+    "profile.json",
+    "backends.json",
+    "outbox.json",
+    "offline/offline.json",
+];
+
+/// Directories of per-video JSON cache files
+const CACHE_DIRS: &[&str] = &["detections", "timeline", "transcripts"];
+
+const OFFLINE_INDEX: &str = "offline/offline.json";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Repair {
+    /// Relative to the app data directory
+    pub path: String,
+    pub problem: String,
+    /// "quarantined", "removed" or "unpinned"
+    pub action: &'static str,
+}
+
+/// Managed state: what the startup check found
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    pub checked_at: f64,
+    /// Files examined
+    pub checked: usize,
+    pub repairs: Vec<Repair>,
+    /// Where damaged files were moved, if any were
+    pub quarantine_dir: Option<String>,
+    /// Repairs that could not be made
+    pub errors: Vec<String>,
+    /// Stores that couldn't be opened after the check and are only kept in
+    /// memory for this run
+    pub in_memory: Vec<String>,
+}
+
+struct Checker<'a> {
+    data_dir: &'a Path,
+    quarantine: PathBuf,
+    report: IntegrityReport,
+}
+
+impl Checker<'_> {
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(self.data_dir)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    /// Move `path` into the quarantine directory, keeping its relative path
+    fn quarantine(&mut self, path: &Path, problem: String) {
+        let relative = self.relative(path);
+        let dest = self.quarantine.join(&relative);
+        let moved = dest
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::rename(path, &dest));
+        match moved {
+            Ok(()) => {
+                self.report.quarantine_dir = Some(self.quarantine.display().to_string());
+                self.repair(relative, problem, "quarantined");
+            }
+            Err(e) => self
+                .report
+                .errors
+                .push(format!("Failed to quarantine {}: {}", relative, e)),
+        }
+    }
+
+    fn remove(&mut self, path: &Path, problem: String) {
+        let relative = self.relative(path);
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(path)
+        } else {
+            std::fs::remove_file(path)
+        };
+        match removed {
+            Ok(()) => self.repair(relative, problem, "removed"),
+            Err(e) => self
+                .report
+                .errors
+                .push(format!("Failed to remove {}: {}", relative, e)),
+        }
+    }
+
+    fn repair(&mut self, path: String, problem: String, action: &'static str) {
+        log::warn!("Integrity check: {} {} ({})", action, path, problem);
+        self.report.repairs.push(Repair {
+            path,
+            problem,
+            action,
+        });
+    }
+
+    /// Quarantine `path` unless it is missing or parses as JSON
+    fn check_json(&mut self, path: &Path) {
+        let Ok(contents) = std::fs::read(path) else {
+            return;
+        };
+        self.report.checked += 1;
+        if let Err(e) = serde_json::from_slice::<serde_json::Value>(&contents) {
+            self.quarantine(path, format!("Not valid JSON: {}", e));
+        }
+    }
+
+    /// Quarantine the SQLite database at `path`, and its journal, unless it
+    /// is missing or passes `PRAGMA integrity_check`
+    fn check_sqlite(&mut self, path: &Path) {
+        if !path.is_file() {
+            return;
+        }
+        self.report.checked += 1;
+        let Some(problem) = sqlite_problem(path) else {
+            return;
+        };
+        let mut journal = path.as_os_str().to_owned();
+        journal.push("-journal");
+        let journal = PathBuf::from(journal);
+        self.quarantine(path, problem);
+        if journal.is_file() {
+            self.quarantine(&journal, "Journal of a damaged database".to_string());
+        }
+    }
+
+    /// Remove files a crash left half-written in `dir`
+    fn sweep_leftovers(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with(".json.tmp") {
+                self.remove(&path, "Half-written file".to_string());
+            } else if name.ends_with(".partial") && path.is_dir() {
+                self.remove(&path, "Unfinished offline download".to_string());
+            }
+        }
+    }
+
+    fn check_cache_dir(&mut self, dir: &Path) {
+        self.sweep_leftovers(dir);
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                self.check_json(&path);
+            }
+        }
+    }
+
+    /// Unpin offline videos whose files don't match the manifest
+    fn check_offline_pins(&mut self) {
+        let index = self.data_dir.join(OFFLINE_INDEX);
+        let Ok(contents) = std::fs::read(&index) else {
+            return;
+        };
+        let Ok(mut pins) = serde_json::from_slice::<HashMap<String, PinnedVideo>>(&contents) else {
+            self.quarantine(&index, "Unreadable offline index".to_string());
+            return;
+        };
+        let root = index.parent().unwrap_or(self.data_dir).to_path_buf();
+        let mut broken = Vec::new();
+        for (video_id, pin) in &pins {
+            let dir = root.join(crate::offline::safe_file_name(video_id));
+            self.report.checked += 1;
+            if let Some(problem) = pin_problem(&dir, pin) {
+                broken.push((video_id.clone(), dir, problem));
+            }
+        }
+        if broken.is_empty() {
+            return;
+        }
+        for (video_id, dir, problem) in broken {
+            pins.remove(&video_id);
+            if dir.exists() {
+                self.quarantine(&dir, problem.clone());
+            }
+            self.repair(self.relative(&dir), problem, "unpinned");
+        }
+        if let Err(e) = crate::store::save_json(&index, &pins) {
+            self.report.errors.push(e);
+        }
+    }
+}
+
+/// What is wrong with the SQLite database at `path`, if anything; opened
+/// read-write so a crash's hot journal is rolled back rather than counted
+fn sqlite_problem(path: &Path) -> Option<String> {
+    let db = match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE) {
+        Ok(db) => db,
+        Err(e) => return Some(format!("Can't be opened: {}", e)),
+    };
+    match db.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0)) {
+        Ok(result) if result == "ok" => None,
+        Ok(result) => Some(format!("Failed the integrity check: {}", result)),
+        Err(e) => Some(format!("Not a readable SQLite database: {}", e)),
+    }
+}
+
+/// What is wrong with pinned video `pin` stored in `dir`, if anything
+fn pin_problem(dir: &Path, pin: &PinnedVideo) -> Option<String> {
+    if !dir.join("history.json").is_file() {
+        return Some("Offline copy is missing its chat history".to_string());
+    }
+    pin.assets.iter().find_map(
+        |asset| match std::fs::metadata(dir.join(&asset.file_name)) {
+            Ok(meta) if meta.len() == asset.size_bytes => None,
+            Ok(meta) => Some(format!(
+                "{} is {} bytes, expected {}",
+                asset.file_name,
+                meta.len(),
+                asset.size_bytes
+            )),
+            Err(_) => Some(format!("{} is missing", asset.file_name)),
+        },
+    )
+}
+
+/// Check the data in `data_dir`, repairing what is damaged
+pub fn check_and_repair(data_dir: &Path) -> IntegrityReport {
+    let checked_at = crate::files::unix_seconds(std::time::SystemTime::now());
+    let mut checker = Checker {
+        data_dir,
+        quarantine: data_dir
+            .join("quarantine")
+            .join(format!("{}", checked_at as u64)),
+        report: IntegrityReport {
+            checked_at,
+            ..IntegrityReport::default()
+        },
+    };
+
+    checker.sweep_leftovers(data_dir);
+    checker.sweep_leftovers(&data_dir.join("offline"));
+    for store in STORES {
+        checker.check_json(&data_dir.join(store));
+    }
+    checker.check_sqlite(&data_dir.join(crate::storage::DB_FILE));
+    for dir in CACHE_DIRS {
+        checker.check_cache_dir(&data_dir.join(dir));
+    }
+    checker.check_offline_pins();
+    checker.report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damaged_files_are_quarantined_and_leftovers_removed() {
+        let dir = std::env::temp_dir().join(format!("integrity-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("transcripts")).unwrap();
+        std::fs::write(dir.join("library.json"), "{}").unwrap();
+        std::fs::write(dir.join("history.json"), "{\"v1\": [").unwrap();
+        std::fs::write(dir.join("bookmarks.json.tmp"), "{").unwrap();
+        std::fs::write(dir.join("transcripts/v1.json"), "").unwrap();
+
+        let report = check_and_repair(&dir);
+        assert_eq!(report.checked, 3);
+        let actions: Vec<_> = report
+            .repairs
+            .iter()
+            .map(|r| (r.path.as_str(), r.action))
+            .collect();
+        assert!(actions.contains(&("history.json", "quarantined")));
+        assert!(actions.contains(&("bookmarks.json.tmp", "removed")));
+        assert!(actions.contains(&("transcripts/v1.json", "quarantined")));
+        assert!(dir.join("library.json").exists());
+        assert!(!dir.join("history.json").exists());
+        let quarantine = PathBuf::from(report.quarantine_dir.unwrap());
+        assert!(quarantine.join("history.json").exists());
+
+        assert!(check_and_repair(&dir).repairs.is_empty());
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn outbox_and_backends_are_checked() {
+        let dir = std::env::temp_dir().join(format!("integrity-stores-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("outbox.json"), "[{").unwrap();
+        std::fs::write(dir.join("backends.json"), "{\"default\"").unwrap();
+
+        let report = check_and_repair(&dir);
+        let quarantined: Vec<_> = report
+            .repairs
+            .iter()
+            .filter(|r| r.action == "quarantined")
+            .map(|r| r.path.as_str())
+            .collect();
+        assert!(quarantined.contains(&"outbox.json"));
+        assert!(quarantined.contains(&"backends.json"));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn a_damaged_session_cache_is_quarantined() {
+        let dir = std::env::temp_dir().join(format!("integrity-sqlite-{}", std::process::id()));
+        crate::storage::SessionCache::load(&dir);
+        assert_eq!(check_and_repair(&dir).checked, 1);
+        assert!(dir.join(crate::storage::DB_FILE).exists());
+
+        std::fs::write(dir.join(crate::storage::DB_FILE), "not a database").unwrap();
+        let report = check_and_repair(&dir);
+        assert_eq!(report.repairs.len(), 1);
+        assert_eq!(report.repairs[0].path, crate::storage::DB_FILE);
+        assert_eq!(report.repairs[0].action, "quarantined");
+        assert!(!dir.join(crate::storage::DB_FILE).exists());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,518 @@
+//! Tauri plugin for the Python video analyzer backend
+//!
+//! Bundles the gRPC client, the local library/history stores and the
+//! background checkers so any Tauri app can talk to the backend:
+//!
+//! ```rust,ignore
+//! tauri::Builder::default()
+//!     .plugin(
+//!         tauri_plugin_video_analyzer::Builder::new()
+//!             .changelog(include_str!("../CHANGELOG.md"))
+//!             .build(),
+//!     )
+//! ```
+//!
+//! Commands are invoked as `plugin:video-analyzer|<command>` and need the
+//! `video-analyzer:default` permission (or the individual `allow-*` ones).
+//! Events are emitted as `video-analyzer://<event>`, except the live chat
+//! streams of `process_query_streaming` (`chat://chunk/<request_id>` and
+//! `chat://done/<request_id>`), `upload://progress` from
+//! `upload_video_from_path`, and `backend://reconnecting` /
+//! `backend://connected` as the gRPC channel loses and regains the backend,
+//! `backend://health` as the health monitor sees its state change,
+//! `backend://version-mismatch` when the backend and this client don't
+//! fit together, and `files://dropped` for files dropped on a window.
+//!
+//! A failed command rejects with an [`error::AppError`], serialized as
+//! `{ kind, code, message }`, rather than a bare string.
+//!
+//! On Android and iOS the plugin also has a native half (`android/`,
+//! `ios/`) for files that are only reachable through the OS, such as
+//! `content://` URIs from the system picker.
+//!
+//! The host app should hold its exit for [`shutdown::drain`], so uploads
+//! and chat streams in flight wind down instead of being dropped:
+//!
+//! ```rust,ignore
+//! .run(|app, event| {
+//!     if let tauri::RunEvent::ExitRequested { api, .. } = event {
+//!         if !app.state::<tauri_plugin_video_analyzer::shutdown::Shutdown>().is_drained() {
+//!             api.prevent_exit();
+//!             let app = app.clone();
+//!             tauri::async_runtime::spawn(async move {
+//!                 tauri_plugin_video_analyzer::shutdown::drain(&app).await;
+//!                 app.exit(0);
+//!             });
+//!         }
+//!     }
+//! })
+//! ```
+//!
+//! Settings can also come from `tauri.conf.json`:
+//!
+//! ```json
+//! { "plugins": { "video-analyzer": { "serverUrl": "http://127.0.0.1:50051" } } }
+//! ```
+
+use serde::Deserialize;
+use tauri::plugin::TauriPlugin;
+use tauri::{Manager, Runtime};
+
+mod a11y;
+mod accounts;
+mod analysis;
+mod attachments;
+mod backends;
+mod backup;
+mod batch;
+mod bookmarks;
+mod capabilities;
+mod changelog;
+mod cleanup;
+mod commands;
+mod compat;
+pub mod config;
+mod deletion;
+#[cfg(desktop)]
+mod desktop;
+mod detections;
+mod diagnostics;
+mod dropped;
+pub mod error;
+mod files;
+mod flags;
+mod health;
+mod history;
+mod http;
+mod import;
+mod integrity;
+mod library;
+mod media;
+#[cfg(mobile)]
+mod mobile;
+mod offline;
+mod onboarding;
+mod operations;
+mod outbox;
+mod params;
+mod picker;
+pub mod postprocess;
+mod power;
+mod query;
+mod query_cache;
+mod quota;
+mod responses;
+mod results;
+mod selftest;
+mod sequence;
+mod session;
+pub mod shutdown;
+mod staleness;
+mod storage;
+mod store;
+mod sync;
+mod throttle;
+mod thumbnails;
+mod timeline;
+mod transcript;
+pub mod transport;
+mod uploads;
+mod watcher;
+
+pub mod video_analyzer {
+    tonic::include_proto!("video_analyzer");
+}
+
+#[cfg(desktop)]
+use desktop::VideoAnalyzer;
+#[cfg(mobile)]
+use mobile::VideoAnalyzer;
+
+const PLUGIN_NAME: &str = "video-analyzer";
+
+/// Full name of a plugin event, e.g. `video-analyzer://video-stale`
+pub(crate) fn event_name(event: &str) -> String {
+    format!("{PLUGIN_NAME}://{event}")
+}
+
+/// Plugin section of `tauri.conf.json`; builder settings take precedence
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    pub server_url: Option<String>,
+    pub video_chunk_size: Option<usize>,
+    pub release_notes_url: Option<String>,
+    /// `grpc` or `rest`, see [`transport`]
+    pub transport: Option<String>,
+    pub rest_base_url: Option<String>,
+    /// ffmpeg hardware acceleration, see [`config::MediaConfig::hwaccel`]
+    pub hwaccel: Option<String>,
+    /// ffmpeg to use when the bundled one is missing or unusable
+    pub ffmpeg_path: Option<String>,
+    /// Client certificate and key files for mutual TLS, see
+    /// [`config::GrpcConfig::client_cert`]
+    pub client_cert: Option<String>,
+    pub client_key: Option<String>,
+    /// HTTP proxy for the backend connection, see
+    /// [`config::GrpcConfig::proxy`]
+    pub proxy: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+/// Options the commands read at runtime, managed as plugin state
+pub(crate) struct PluginOptions {
+    pub changelog: &'static str,
+    pub release_notes_url: Option<String>,
+}
+
+/// A file the OS opened for us, see [`files::uri`]
+pub(crate) struct PlatformFile {
+    pub file: std::fs::File,
+    pub name: String,
+    pub size: Option<u64>,
+}
+
+/// Access to the platform half of the plugin from any manager
+pub(crate) trait VideoAnalyzerExt<R: Runtime> {
+    fn video_analyzer(&self) -> &VideoAnalyzer<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> VideoAnalyzerExt<R> for T {
+    fn video_analyzer(&self) -> &VideoAnalyzer<R> {
+        self.state::<VideoAnalyzer<R>>().inner()
+    }
+}
+
+/// Initialization options for the plugin
+#[derive(Default)]
+pub struct Builder {
+    server_url: Option<String>,
+    video_chunk_size: Option<usize>,
+    changelog: &'static str,
+    release_notes_url: Option<String>,
+    transport: Option<transport::Transport>,
+    client_identity: Option<config::ClientIdentityPem>,
+    response_transforms: Vec<Box<dyn postprocess::Transform>>,
+}
+
+impl Builder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Backend URL to use when GRPC_SERVER_URL is not set
+    pub fn server_url(mut self, url: impl Into<String>) -> Self {
+        self.server_url = Some(url.into());
+        self
+    }
+
+    /// Upload chunk size to use when VIDEO_CHUNK_SIZE is not set
+    pub fn video_chunk_size(mut self, bytes: usize) -> Self {
+        self.video_chunk_size = Some(bytes);
+        self
+    }
+
+    /// The host app's `CHANGELOG.md`, served by `get_changelog`
+    pub fn changelog(mut self, markdown: &'static str) -> Self {
+        self.changelog = markdown;
+        self
+    }
+
+    /// Where `get_changelog` fetches remote release notes from
+    pub fn release_notes_url(mut self, url: impl Into<String>) -> Self {
+        self.release_notes_url = Some(url.into());
+        self
+    }
+
+    /// Client certificate and key (PEM) for a backend that requires mutual
+    /// TLS, for hosts that keep them in the OS keyring; certificate files
+    /// from GRPC_CLIENT_CERT/GRPC_CLIENT_KEY or the plugin config win
+    pub fn client_identity(
+        mut self,
+        cert_pem: impl Into<String>,
+        key_pem: impl Into<String>,
+    ) -> Self {
+        self.client_identity = Some(config::ClientIdentityPem {
+            cert: cert_pem.into(),
+            key: key_pem.into(),
+        });
+        self
+    }
+
+    /// Post-process every chat answer with `transform`, after the built-in
+    /// transforms from RESPONSE_TRANSFORMS, see [`postprocess`]
+    pub fn response_transform(mut self, transform: impl postprocess::Transform + 'static) -> Self {
+        self.response_transforms.push(Box::new(transform));
+        self
+    }
+
+    /// Reach the backend through `transport` instead of gRPC
+    pub fn transport(
+        mut self,
+        transport: impl transport::VideoAnalyzerTransport + 'static,
+    ) -> Self {
+        self.transport = Some(std::sync::Arc::new(transport));
+        self
+    }
+
+    pub fn build<R: Runtime>(self) -> TauriPlugin<R, Option<Config>> {
+        let handler: fn(tauri::ipc::Invoke<R>) -> bool = tauri::generate_handler![
+            commands::upload_video,
+            commands::upload_video_from_path,
+            commands::upload_videos,
+            commands::cancel_upload,
+            commands::resume_upload,
+            commands::get_resumable_uploads,
+            commands::register_local_video,
+            commands::import_directory,
+            commands::open_video_picker,
+            commands::probe_video,
+            commands::get_thumbnail,
+            commands::extract_frame,
+            commands::upload_audio_only,
+            commands::extract_subtitles,
+            commands::verify_video,
+            commands::reregister_video,
+            commands::relink_video,
+            commands::update_analysis_options,
+            commands::inspect_attachment,
+            commands::detect_language,
+            commands::export_local_history,
+            commands::pin_message,
+            commands::list_pinned,
+            commands::backup_workspace,
+            commands::restore_workspace,
+            commands::rate_response,
+            commands::submit_bug_report,
+            commands::get_changelog,
+            commands::get_feature_flags,
+            commands::set_feature_flag,
+            commands::get_transcript,
+            commands::get_detections,
+            commands::get_timeline,
+            commands::create_share_link,
+            commands::get_backend_queue,
+            commands::cancel_processing,
+            commands::delete_video,
+            commands::rename_video,
+            commands::export_frames,
+            commands::get_media_jobs,
+            commands::get_operations,
+            commands::cancel_media_job,
+            commands::add_bookmark,
+            commands::remove_bookmark,
+            commands::search_transcript,
+            commands::export_subtitles,
+            commands::pin_for_offline,
+            commands::unpin_offline,
+            commands::get_offline_videos,
+            commands::sync_now,
+            commands::get_sync_status,
+            commands::get_sync_conflicts,
+            commands::resolve_sync_conflict,
+            commands::get_power_state,
+            commands::set_power_saving,
+            commands::get_upload_speed_limit,
+            commands::set_upload_speed_limit,
+            commands::process_query,
+            commands::process_query_range,
+            commands::process_query_streaming,
+            commands::set_session_params,
+            commands::get_session_params,
+            commands::set_active_session,
+            commands::get_active_session,
+            commands::get_incomplete_responses,
+            commands::retry_incomplete_response,
+            commands::get_outbox,
+            commands::discard_outbox_message,
+            commands::flush_outbox,
+            commands::get_last_session,
+            commands::get_chat_history,
+            commands::resume_session,
+            commands::clear_chat_history,
+            commands::get_processing_status, // Legacy, kept for backward compatibility
+            commands::check_backend_ready,
+            commands::get_backend_health,
+            commands::get_backend_info,
+            commands::get_capabilities,
+            commands::get_network_simulation,
+            commands::set_network_simulation,
+            commands::set_auth_token,
+            commands::clear_auth_token,
+            commands::detect_local_backend,
+            commands::test_connection,
+            commands::save_initial_profile,
+            commands::list_backends,
+            commands::switch_backend,
+            commands::list_accounts,
+            commands::create_account,
+            commands::switch_account,
+            commands::reload_config,
+            commands::get_integrity_report,
+            commands::run_self_test,
+            commands::get_storage_usage,
+            commands::cleanup_videos,
+            commands::get_quota
+        ];
+
+        tauri::plugin::Builder::<R, Option<Config>>::new(PLUGIN_NAME)
+            // Journal every command for bug reports, then dispatch as usual
+            .invoke_handler(move |invoke| {
+                if let Some(journal) = invoke
+                    .message
+                    .webview()
+                    .try_state::<diagnostics::CommandJournal>()
+                {
+                    journal.record(invoke.message.command());
+                }
+                handler(invoke)
+            })
+            .on_event(dropped::on_event)
+            .register_uri_scheme_protocol(thumbnails::SCHEME, thumbnails::protocol)
+            .setup(move |app, api| {
+                let config = api.config().clone();
+                let config = config.as_ref();
+                config::set_overrides(config::ConfigOverrides {
+                    server_url: self
+                        .server_url
+                        .or_else(|| config.and_then(|c| c.server_url.clone())),
+                    video_chunk_size: self
+                        .video_chunk_size
+                        .or_else(|| config.and_then(|c| c.video_chunk_size)),
+                    transport: config.and_then(|c| c.transport.clone()),
+                    rest_base_url: config.and_then(|c| c.rest_base_url.clone()),
+                    hwaccel: config.and_then(|c| c.hwaccel.clone()),
+                    ffmpeg_path: config.and_then(|c| c.ffmpeg_path.clone()),
+                    client_cert: config.and_then(|c| c.client_cert.clone()),
+                    client_key: config.and_then(|c| c.client_key.clone()),
+                    proxy: config.and_then(|c| c.proxy.clone()),
+                    no_proxy: config.and_then(|c| c.no_proxy.clone()),
+                    client_identity: self.client_identity,
+                });
+                app.manage(PluginOptions {
+                    changelog: self.changelog,
+                    release_notes_url: self
+                        .release_notes_url
+                        .or_else(|| config.and_then(|c| c.release_notes_url.clone())),
+                });
+
+                #[cfg(desktop)]
+                let video_analyzer = desktop::init(app, api)?;
+                #[cfg(mobile)]
+                let video_analyzer = mobile::init(app, api)?;
+                app.manage(video_analyzer);
+
+                let accounts = accounts::Accounts::load(&app.path().app_data_dir()?);
+                let data_dir = accounts.workspace().to_path_buf();
+                log::info!("Using account {}", accounts.active().id);
+                match backup::apply_pending_restore(&data_dir) {
+                    Ok(true) => log::info!("Restored the workspace from a backup"),
+                    Ok(false) => {}
+                    Err(e) => log::error!("Failed to restore the workspace: {}", e),
+                }
+                let mut integrity = integrity::check_and_repair(&data_dir);
+                let session_cache = storage::SessionCache::load(&data_dir);
+                integrity
+                    .in_memory
+                    .extend(session_cache.open_error().map(str::to_string));
+                app.manage(integrity);
+                app.manage(capabilities::Capabilities::new(data_dir.clone()));
+                transport::auth::use_account(
+                    accounts.active().token_key().map(str::to_string),
+                );
+                transport::auth::load_stored_token();
+                // After use_account, so the key comes from this account's entry
+                config::set_profile(onboarding::load_profile(
+                    &data_dir.join(onboarding::PROFILE_FILE),
+                ));
+
+                let grpc_clients =
+                    std::sync::Arc::new(transport::grpc::GrpcClientManager::default());
+                app.manage(accounts);
+                app.manage(grpc_clients.clone());
+                app.manage(compat::BackendInfo::default());
+                forward_connection_events(app.clone(), &grpc_clients);
+                let mut transport = self
+                    .transport
+                    .unwrap_or_else(|| transport::from_config(grpc_clients));
+                if config::get().app.is_dev {
+                    transport = transport::simulate::SimulatedTransport::wrap(transport);
+                }
+                log::info!("Reaching the backend over {}", transport.name());
+                app.manage(transport);
+                app.manage(library::Library::load(data_dir.join("library.json")));
+                app.manage(quota::QuotaTracker::default());
+                app.manage(uploads::UploadRegistry::default());
+                app.manage(shutdown::Shutdown::default());
+                app.manage(operations::Operations::default());
+                app.manage(session::ActiveSession::default());
+                app.manage(postprocess::Pipeline::from_config(self.response_transforms));
+                app.manage(query_cache::QueryCache::default());
+                app.manage(uploads::UploadManifest::load(data_dir.join("uploads.json")));
+                app.manage(history::LocalHistory::load(data_dir.join("history.json")));
+                app.manage(session_cache);
+                app.manage(outbox::Outbox::load(data_dir.join("outbox.json")));
+                app.manage(deletion::DeleteConfirmations::default());
+                app.manage(diagnostics::CommandJournal::default());
+                app.manage(flags::FeatureFlags::load(
+                    data_dir.join("feature_flags.json"),
+                ));
+                app.manage(offline::OfflineCache::load(data_dir.join("offline")));
+                app.manage(detections::DetectionCache::new(data_dir.join("detections")));
+                app.manage(timeline::TimelineCache::new(data_dir.join("timeline")));
+                app.manage(thumbnails::ThumbnailCache::new(data_dir.join("thumbnails")));
+                app.manage(media::pipeline::Pipeline::default());
+                app.manage(bookmarks::Bookmarks::load(data_dir.join("bookmarks.json")));
+                app.manage(transcript::TranscriptCache::new(
+                    data_dir.join("transcripts"),
+                ));
+                app.manage(power::PowerState::load(data_dir.join("power.json")));
+                power::spawn_monitor(app.clone());
+                app.manage(health::BackendHealth::default());
+                health::spawn_monitor(app.clone());
+                staleness::spawn_checker(app.clone());
+                sync::spawn_sync(app.clone());
+                watcher::spawn_watcher(app.clone());
+                media::locator::spawn_preflight();
+                capabilities::spawn_probe(app.clone());
+                Ok(())
+            })
+            .build()
+    }
+}
+
+/// Re-emit the gRPC channel's [`transport::grpc::ConnectionEvent`]s as
+/// `backend://reconnecting` and `backend://connected`, checking the
+/// backend's version on each connect
+fn forward_connection_events<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    clients: &transport::grpc::GrpcClientManager,
+) {
+    use tauri::Emitter;
+    use tokio::sync::broadcast::error::RecvError;
+    use transport::grpc::ConnectionEvent;
+
+    let mut events = clients.subscribe();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            };
+            let name = match event {
+                ConnectionEvent::Reconnecting { .. } => "backend://reconnecting",
+                ConnectionEvent::Connected { .. } => {
+                    compat::spawn_handshake(app.clone());
+                    "backend://connected"
+                }
+            };
+            app.emit(name, event).ok();
+        }
+    });
+}
+
+/// Plugin with default options and no changelog
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<Config>> {
+    Builder::new().build()
+}
@@ -0,0 +1,170 @@
+//! Local library DB of registered videos
+//!
+//! The backend owns the videos themselves; this is the client-side record
+//! of where each registered file actually lives on this machine (canonical
+//! path, volume, fingerprint), persisted as a JSON file in the app data
+//! directory and kept in Tauri managed state.
+
+use crate::files::names::path_from_stored;
+use crate::files::volume::VolumeKind;
+use crate::media::language::DetectedLanguage;
+use crate::store::{load_json, save_json};
+use crate::video_analyzer::AnalysisOptions;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// One registered video as known to this client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub file_id: String,
+    pub display_name: String,
+    /// Path exactly as the user supplied it
+    pub original_path: String,
+    /// Path after resolving symlinks; what the backend was given
+    pub canonical_path: String,
+    /// Hex-encoded OS bytes of `canonical_path`, only set when it is not valid UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical_path_raw: Option<String>,
+    /// Hex-encoded OS bytes of the file name; the lossless identity of the name
+    #[serde(default)]
+    pub name_raw: String,
+    pub volume_id: String,
+    pub volume_kind: VolumeKind,
+    pub reference_only: bool,
+    pub size_bytes: u64,
+    pub sha256: String,
+    pub modified_at: f64,
+    pub container: String,
+    pub registered_at: f64,
+    /// Result of the last staleness check
+    #[serde(default)]
+    pub status: FileStatus,
+    #[serde(default)]
+    pub last_verified_at: f64,
+    /// Options the video was (or will next be) analysed with
+    #[serde(default)]
+    pub analysis_options: Option<AnalysisOptions>,
+    /// Spoken language detected before it was sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detected_language: Option<DetectedLanguage>,
+}
+
+impl LibraryEntry {
+    /// Path of the file on disk, using the lossless form when there is one
+    pub fn path(&self) -> PathBuf {
+        path_from_stored(&self.canonical_path, self.canonical_path_raw.as_deref())
+    }
+}
+
+/// Longest display name accepted, in characters
+pub const MAX_DISPLAY_NAME_CHARS: usize = 255;
+
+/// `name` trimmed, if it can name a video: not empty, no control
+/// characters and at most [`MAX_DISPLAY_NAME_CHARS`] long
+pub fn display_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("display_name can't be empty".to_string());
+    }
+    if name.chars().any(char::is_control) {
+        return Err("display_name can't contain control characters".to_string());
+    }
+    let chars = name.chars().count();
+    if chars > MAX_DISPLAY_NAME_CHARS {
+        return Err(format!(
+            "display_name is {} characters long; at most {} are allowed",
+            chars, MAX_DISPLAY_NAME_CHARS
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Whether the file on disk still matches what was registered
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileStatus {
+    #[default]
+    Ok,
+    /// Size, mtime or content differ from the registration fingerprint
+    Modified,
+    /// The file no longer exists at the registered path
+    Missing,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LibraryFile {
+    videos: HashMap<String, LibraryEntry>,
+}
+
+/// JSON-file backed store of [`LibraryEntry`] records, keyed by file id
+pub struct Library {
+    path: PathBuf,
+    data: Mutex<LibraryFile>,
+}
+
+impl Library {
+    /// Load the library from `path`, starting empty if it does not exist yet
+    pub fn load(path: PathBuf) -> Self {
+        let data = load_json(&path);
+        Self {
+            path,
+            data: Mutex::new(data),
+        }
+    }
+
+    pub fn get(&self, file_id: &str) -> Option<LibraryEntry> {
+        self.data.lock().unwrap().videos.get(file_id).cloned()
+    }
+
+    pub fn entries(&self) -> Vec<LibraryEntry> {
+        self.data.lock().unwrap().videos.values().cloned().collect()
+    }
+
+    /// Modify an existing entry in place and persist; `None` if unknown
+    pub fn update<F>(&self, file_id: &str, f: F) -> Result<Option<LibraryEntry>, String>
+    where
+        F: FnOnce(&mut LibraryEntry),
+    {
+        let mut data = self.data.lock().unwrap();
+        let Some(entry) = data.videos.get_mut(file_id) else {
+            return Ok(None);
+        };
+        f(entry);
+        let updated = entry.clone();
+        save_json(&self.path, &*data)?;
+        Ok(Some(updated))
+    }
+
+    /// Drop an entry and persist; returns the removed entry if there was one
+    pub fn remove(&self, file_id: &str) -> Result<Option<LibraryEntry>, String> {
+        let mut data = self.data.lock().unwrap();
+        let removed = data.videos.remove(file_id);
+        if removed.is_some() {
+            save_json(&self.path, &*data)?;
+        }
+        Ok(removed)
+    }
+
+    /// Insert or replace an entry and persist the library
+    pub fn upsert(&self, entry: LibraryEntry) -> Result<(), String> {
+        let mut data = self.data.lock().unwrap();
+        data.videos.insert(entry.file_id.clone(), entry);
+        save_json(&self.path, &*data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names_are_trimmed_and_checked() {
+        assert_eq!(display_name("  Keynote 2024  ").unwrap(), "Keynote 2024");
+        assert!(display_name(" \t ").is_err());
+        assert!(display_name("line\nbreak").is_err());
+        assert!(display_name(&"é".repeat(MAX_DISPLAY_NAME_CHARS)).is_ok());
+        assert!(display_name(&"é".repeat(MAX_DISPLAY_NAME_CHARS + 1)).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Hardware acceleration for local ffmpeg work
+//!
+//! The first ffmpeg job probes which accelerator this machine actually has
+//! (a tiny test encode per candidate: NVENC, Quick Sync or VideoToolbox)
+//! and remembers the answer. FFMPEG_HWACCEL (or `hwaccel` in the plugin
+//! config) overrides the probe: `none` for CPU only, or an accelerator
+//! name. Jobs that fail on the accelerator are retried on the CPU.
+
+use crate::config;
+use serde::Serialize;
+use tokio::sync::OnceCell;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HwAccel {
+    Nvenc,
+    Qsv,
+    VideoToolbox,
+}
+
+impl HwAccel {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "nvenc" | "cuda" | "nvidia" => Some(Self::Nvenc),
+            "qsv" | "quicksync" | "intel" => Some(Self::Qsv),
+            "videotoolbox" | "vt" => Some(Self::VideoToolbox),
+            _ => None,
+        }
+    }
+
+    /// Input options that decode on the accelerator
+    pub fn decode_args(self) -> [&'static str; 2] {
+        match self {
+            Self::Nvenc => ["-hwaccel", "cuda"],
+            Self::Qsv => ["-hwaccel", "qsv"],
+            Self::VideoToolbox => ["-hwaccel", "videotoolbox"],
+        }
+    }
+
+    /// H.264 encoder running on the accelerator
+    pub fn h264_encoder(self) -> &'static str {
+        match self {
+            Self::Nvenc => "h264_nvenc",
+            Self::Qsv => "h264_qsv",
+            Self::VideoToolbox => "h264_videotoolbox",
+        }
+    }
+}
+
+/// Accelerators worth probing on this platform, in order of preference
+fn candidates() -> &'static [HwAccel] {
+    if cfg!(target_os = "macos") {
+        &[HwAccel::VideoToolbox]
+    } else {
+        &[HwAccel::Nvenc, HwAccel::Qsv]
+    }
+}
+
+/// Whether a one-frame test encode works on `accel`
+async fn probe(accel: HwAccel) -> bool {
+    let Ok(ffmpeg) = super::ffmpeg_binary().await else {
+        return false;
+    };
+    tokio::process::Command::new(ffmpeg)
+        .args(["-hide_banner", "-loglevel", "error"])
+        .args(["-f", "lavfi", "-i", "color=black:s=256x256:d=0.1"])
+        .args([
+            "-frames:v",
+            "1",
+            "-c:v",
+            accel.h264_encoder(),
+            "-f",
+            "null",
+            "-",
+        ])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+static DETECTED: OnceCell<Option<HwAccel>> = OnceCell::const_new();
+
+/// The first working accelerator, probed once per run
+pub async fn detected() -> Option<HwAccel> {
+    *DETECTED
+        .get_or_init(|| async {
+            for &accel in candidates() {
+                if probe(accel).await {
+                    log::info!("ffmpeg hardware acceleration: {:?}", accel);
+                    return Some(accel);
+                }
+            }
+            log::info!("ffmpeg hardware acceleration: none, using the CPU");
+            None
+        })
+        .await
+}
+
+/// What the FFMPEG_HWACCEL setting asks for
+#[derive(Debug, PartialEq, Eq)]
+enum Choice {
+    Auto,
+    Cpu,
+    Forced(HwAccel),
+}
+
+fn choice(setting: &str) -> Choice {
+    match setting.trim().to_ascii_lowercase().as_str() {
+        "" | "auto" => Choice::Auto,
+        "none" | "off" | "cpu" => Choice::Cpu,
+        other => HwAccel::parse(other).map_or_else(
+            || {
+                log::warn!("Unknown FFMPEG_HWACCEL '{}', detecting instead", other);
+                Choice::Auto
+            },
+            Choice::Forced,
+        ),
+    }
+}
+
+/// The accelerator ffmpeg jobs should use, if any
+pub async fn selected() -> Option<HwAccel> {
+    match choice(&config::get().media.hwaccel) {
+        Choice::Auto => detected().await,
+        Choice::Cpu => None,
+        Choice::Forced(accel) => Some(accel),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choice() {
+        assert_eq!(choice(""), Choice::Auto);
+        assert_eq!(choice("CPU"), Choice::Cpu);
+        assert_eq!(choice("cuda"), Choice::Forced(HwAccel::Nvenc));
+        assert_eq!(
+            choice("videotoolbox"),
+            Choice::Forced(HwAccel::VideoToolbox)
+        );
+        assert_eq!(choice("vaapi"), Choice::Auto);
+    }
+}
@@ -0,0 +1,211 @@
+//! Detecting the spoken language of a video before it is sent
+//!
+//! ffmpeg cuts [`CLIP_SECS`] of 16 kHz mono audio, and a whisper.cpp CLI
+//! (`whisper-cli`) with a small model, `ggml-tiny.bin` being plenty,
+//! reports the language. The result travels to the backend as a
+//! `language_hint`, so transcription doesn't have to guess from a noisy
+//! intro, and is kept in the library for the UI.
+//!
+//! whisper is optional: looked for next to the app's executable, then
+//! WHISPER_PATH, then on PATH, with the model from WHISPER_MODEL or next
+//! to the executable. Without either, detection is skipped.
+
+use super::locator::{self, beside_exe};
+use super::pipeline::{Job, Pipeline};
+use crate::config;
+use crate::video_analyzer::AnalysisOptions;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Length of the audio listened to
+const CLIP_SECS: u32 = 30;
+
+/// Where the clip starts, past most intros; videos shorter than that are
+/// listened to from the start
+const CLIP_OFFSET_SECS: u32 = 30;
+
+/// Least audio worth running detection on
+const MIN_CLIP_SECS: u32 = 2;
+
+/// Longest whisper is given
+const DETECT_TIMEOUT: Duration = Duration::from_secs(60);
+
+const MODEL_FILE: &str = "ggml-tiny.bin";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, specta::Type)]
+pub struct DetectedLanguage {
+    /// e.g. "en"
+    pub code: String,
+    /// whisper's confidence, 0 to 1
+    pub probability: f64,
+}
+
+/// The whisper binary and model to use, if both are there
+fn whisper() -> Option<(PathBuf, PathBuf)> {
+    let config = config::get();
+    let name = locator::binary_name("whisper-cli");
+    let binary = beside_exe(&name)
+        .or_else(|| config.media.whisper_path.as_ref().map(PathBuf::from))
+        .or_else(|| locator::on_path(&name))?;
+    let model = config
+        .media
+        .whisper_model
+        .as_ref()
+        .map(PathBuf::from)
+        .or_else(|| beside_exe(MODEL_FILE))
+        .filter(|model| model.is_file())?;
+    Some((binary, model))
+}
+
+/// Whether detection can run on this machine
+pub fn available() -> bool {
+    whisper().is_some()
+}
+
+/// Whether to detect the language for a video sent with `options`: not
+/// when the user chose one, or turned transcription off
+pub fn wanted(options: Option<&AnalysisOptions>) -> bool {
+    config::get().media.language_detection
+        && options.is_none_or(|o| {
+            matches!(o.language.as_str(), "" | "auto") && o.enable_transcription != Some(false)
+        })
+}
+
+/// `(code, probability)` from whisper.cpp's
+/// `auto-detected language: en (p = 0.975271)` line
+fn parse_detection(output: &str) -> Option<DetectedLanguage> {
+    let line = output
+        .lines()
+        .find_map(|line| line.split("auto-detected language:").nth(1))?;
+    let mut parts = line.split_whitespace();
+    let code = parts.next()?.to_string();
+    let probability = line
+        .split("p =")
+        .nth(1)
+        .and_then(|p| p.trim().trim_end_matches(')').parse().ok())
+        .unwrap_or(0.0);
+    Some(DetectedLanguage { code, probability })
+}
+
+/// 16 kHz mono WAV of up to [`CLIP_SECS`] from `offset_secs`
+async fn clip(pipeline: &Pipeline, video: &Path, offset_secs: u32) -> Result<Vec<u8>, String> {
+    let job = Job::new(format!("audio sample of {}", video.display()))
+        .args(["-ss", &offset_secs.to_string(), "-i"])
+        .arg(video)
+        .args([
+            "-t",
+            &CLIP_SECS.to_string(),
+            "-vn",
+            "-ac",
+            "1",
+            "-ar",
+            "16000",
+        ])
+        .args(["-f", "wav", "-"]);
+    pipeline.run(job, |_| {}).await
+}
+
+/// The language spoken in `video`; `Ok(None)` when whisper is not
+/// available or there is too little audio to tell
+pub async fn detect(pipeline: &Pipeline, video: &Path) -> Result<Option<DetectedLanguage>, String> {
+    let Some((binary, model)) = whisper() else {
+        return Ok(None);
+    };
+    // 16-bit samples at 16 kHz, after the 44-byte WAV header
+    let enough = |wav: &Vec<u8>| wav.len() > 44 + (MIN_CLIP_SECS * 16_000 * 2) as usize;
+    let mut wav = clip(pipeline, video, CLIP_OFFSET_SECS)
+        .await
+        .unwrap_or_default();
+    if !enough(&wav) {
+        wav = clip(pipeline, video, 0).await?;
+    }
+    if !enough(&wav) {
+        log::info!(
+            "{} has too little audio to detect its language",
+            video.display()
+        );
+        return Ok(None);
+    }
+
+    let sample = std::env::temp_dir().join(format!("language-{}.wav", uuid::Uuid::new_v4()));
+    tokio::fs::write(&sample, &wav)
+        .await
+        .map_err(|e| format!("Failed to write audio sample: {}", e))?;
+    let run = tokio::process::Command::new(&binary)
+        .arg("-m")
+        .arg(&model)
+        .arg("-f")
+        .arg(&sample)
+        .args(["-l", "auto", "--detect-language"])
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(DETECT_TIMEOUT, run).await;
+    tokio::fs::remove_file(&sample).await.ok();
+    let output = output
+        .map_err(|_| "Language detection timed out".to_string())?
+        .map_err(|e| format!("Failed to run {}: {}", binary.display(), e))?;
+
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stderr),
+        String::from_utf8_lossy(&output.stdout)
+    );
+    match parse_detection(&text) {
+        Some(detected) => Ok(Some(detected)),
+        None if output.status.success() => Ok(None),
+        None => Err(format!(
+            "Language detection failed: {}",
+            text.lines()
+                .rev()
+                .find(|l| !l.trim().is_empty())
+                .unwrap_or_default()
+        )),
+    }
+}
+
+/// [`detect`] for a video about to be sent with `options`, if
+/// [`wanted`]; failures are logged and give no hint
+pub async fn hint_for(
+    pipeline: &Pipeline,
+    video: &Path,
+    options: Option<&AnalysisOptions>,
+) -> Option<DetectedLanguage> {
+    if !wanted(options) {
+        return None;
+    }
+    match detect(pipeline, video).await {
+        Ok(Some(detected)) => {
+            log::info!(
+                "Detected {} (p = {:.2}) in {}",
+                detected.code,
+                detected.probability,
+                video.display()
+            );
+            Some(detected)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            log::warn!("{}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_detection() {
+        let output = "whisper_init_from_file: loading model\nwhisper_full_with_state: auto-detected language: pt (p = 0.912345)\n";
+        assert_eq!(
+            parse_detection(output),
+            Some(DetectedLanguage {
+                code: "pt".to_string(),
+                probability: 0.912345
+            })
+        );
+        assert_eq!(parse_detection("main: processing 30 s of audio"), None);
+    }
+}
@@ -0,0 +1,215 @@
+//! Finding a usable ffmpeg
+//!
+//! Candidates are tried in order, and the first that passes validation
+//! (runs, is at least [`MIN_MAJOR_VERSION`], has the filters the media
+//! jobs use) wins:
+//!
+//! 1. the bundled sidecar next to the app's executable
+//! 2. `ffmpeg` on PATH
+//! 3. FFMPEG_PATH, or `ffmpegPath` in the plugin config
+//!
+//! The search runs once, at startup ([`spawn_preflight`]); when nothing
+//! qualifies every media job fails with the same explanation of what was
+//! tried.
+
+use crate::config;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::sync::OnceCell;
+
+pub const MIN_MAJOR_VERSION: u32 = 4;
+
+/// Filters the media jobs rely on
+const REQUIRED_FILTERS: &[&str] = &["scale", "pad"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Bundled,
+    Path,
+    Configured,
+}
+
+/// The ffmpeg in use
+#[derive(Debug, Clone, Serialize)]
+pub struct Ffmpeg {
+    pub path: PathBuf,
+    pub source: Source,
+    /// e.g. "6.1.1"; git builds report something like "N-112345-g..."
+    pub version: String,
+}
+
+/// `stem` as an executable name on this platform, e.g. "ffmpeg.exe"
+pub(crate) fn binary_name(stem: &str) -> String {
+    if cfg!(windows) {
+        format!("{}.exe", stem)
+    } else {
+        stem.to_string()
+    }
+}
+
+/// `name` in the directory of the running executable, if it is there
+pub(crate) fn beside_exe(name: &str) -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(name)))
+        .filter(|path| path.is_file())
+}
+
+/// The first `name` in a PATH directory
+pub(crate) fn on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+fn candidates() -> Vec<(Source, Option<PathBuf>)> {
+    let name = binary_name("ffmpeg");
+    let configured = config::get().media.ffmpeg_path.as_ref().map(PathBuf::from);
+    vec![
+        (Source::Bundled, beside_exe(&name)),
+        (Source::Path, on_path(&name)),
+        (Source::Configured, configured),
+    ]
+}
+
+/// `(major, minor)` from `ffmpeg -version`'s first line; `None` for builds
+/// without a release number
+fn parse_version(first_line: &str) -> Option<(u32, u32)> {
+    let version = first_line
+        .strip_prefix("ffmpeg version ")?
+        .split_whitespace()
+        .next()?
+        .trim_start_matches('n');
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// Filter names listed by `ffmpeg -filters` (lines like ` ... scale  V->V  Scale the input`)
+fn has_filters(listing: &str, required: &[&str]) -> Result<(), String> {
+    let available: Vec<&str> = listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .collect();
+    let missing: Vec<&str> = required
+        .iter()
+        .copied()
+        .filter(|f| !available.contains(f))
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("missing filters: {}", missing.join(", ")))
+    }
+}
+
+async fn run(path: &Path, arg: &str) -> Result<String, String> {
+    let output = tokio::process::Command::new(path)
+        .args(["-hide_banner", arg])
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("does not run: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("`{}` failed", arg));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The version, if `path` is an ffmpeg the media jobs can use
+async fn validate(path: &Path) -> Result<String, String> {
+    let version_output = run(path, "-version").await?;
+    let first_line = version_output.lines().next().unwrap_or_default();
+    let version = first_line
+        .strip_prefix("ffmpeg version ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .ok_or_else(|| "is not ffmpeg".to_string())?
+        .to_string();
+    if let Some((major, minor)) = parse_version(first_line) {
+        if major < MIN_MAJOR_VERSION {
+            return Err(format!(
+                "is version {}.{}, need {} or later",
+                major, minor, MIN_MAJOR_VERSION
+            ));
+        }
+    }
+    has_filters(&run(path, "-filters").await?, REQUIRED_FILTERS)?;
+    Ok(version)
+}
+
+async fn locate() -> Result<Ffmpeg, String> {
+    let mut tried = Vec::new();
+    for (source, path) in candidates() {
+        let Some(path) = path else {
+            tried.push(format!("{:?}: not found", source));
+            continue;
+        };
+        match validate(&path).await {
+            Ok(version) => {
+                return Ok(Ffmpeg {
+                    path,
+                    source,
+                    version,
+                })
+            }
+            Err(e) => tried.push(format!("{:?} {}: {}", source, path.display(), e)),
+        }
+    }
+    Err(format!(
+        "No usable ffmpeg (version {}+) found. Tried: {}. Install ffmpeg or set FFMPEG_PATH.",
+        MIN_MAJOR_VERSION,
+        tried.join("; ")
+    ))
+}
+
+static LOCATED: OnceCell<Result<Ffmpeg, String>> = OnceCell::const_new();
+
+/// The ffmpeg to run, or why there is none; searched once per run
+pub async fn ffmpeg() -> Result<Ffmpeg, String> {
+    LOCATED.get_or_init(locate).await.clone()
+}
+
+/// Look for ffmpeg in the background at startup and log the outcome
+pub fn spawn_preflight() {
+    tauri::async_runtime::spawn(async {
+        match ffmpeg().await {
+            Ok(found) => log::info!(
+                "Using ffmpeg {} ({:?}) at {}",
+                found.version,
+                found.source,
+                found.path.display()
+            ),
+            Err(e) => log::warn!("{}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            parse_version("ffmpeg version 6.1.1-3ubuntu5 Copyright (c) 2000-2023"),
+            Some((6, 1))
+        );
+        assert_eq!(parse_version("ffmpeg version n5.0 Copyright"), Some((5, 0)));
+        assert_eq!(parse_version("ffmpeg version 7 Copyright"), Some((7, 0)));
+        assert_eq!(parse_version("ffmpeg version N-112345-gabcdef"), None);
+        assert_eq!(parse_version("ffprobe version 6.1"), None);
+    }
+
+    #[test]
+    fn test_has_filters() {
+        let listing = "Filters:\n  T.. = Timeline support\n ... pad               V->V       Pad the input video.\n ..C scale             V->V       Scale the input video size.\n";
+        assert!(has_filters(listing, &["scale", "pad"]).is_ok());
+        assert_eq!(
+            has_filters(listing, &["scale", "drawtext"]),
+            Err("missing filters: drawtext".to_string())
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Local media work done with ffmpeg
+//!
+//! The app bundles ffmpeg as a sidecar (`externalBin` in `tauri.conf.json`);
+//! [`locator`] finds it, or another suitable install. Every job runs
+//! through the [`pipeline`] queue and uses [`hwaccel`] when the machine
+//! has it. [`language`] also runs whisper, when installed, and [`probe`]
+//! ffprobe. Uploads can be re-encoded first by [`transcode`], and
+//! [`subtitles`] pulls out the captions a video already has.
+
+pub mod hwaccel;
+pub mod language;
+pub mod locator;
+pub mod pipeline;
+pub mod probe;
+pub mod sheet;
+pub mod subtitles;
+pub mod transcode;
+
+use pipeline::{Job, Pipeline};
+use std::path::{Path, PathBuf};
+
+/// The ffmpeg binary to run
+pub async fn ffmpeg_binary() -> Result<PathBuf, String> {
+    locator::ffmpeg().await.map(|found| found.path)
+}
+
+/// The frame at `timestamp_ms` as `width` x `height` RGB, letterboxed to fit
+pub async fn frame_rgb(
+    pipeline: &Pipeline,
+    video: &Path,
+    timestamp_ms: i64,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, String> {
+    if let Some(accel) = hwaccel::selected().await {
+        match frame_rgb_with(pipeline, video, timestamp_ms, width, height, Some(accel)).await {
+            Ok(frame) => return Ok(frame),
+            Err(e) => log::warn!("{:?} frame extraction failed, using the CPU: {}", accel, e),
+        }
+    }
+    frame_rgb_with(pipeline, video, timestamp_ms, width, height, None).await
+}
+
+/// Still image formats frames are encoded as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+}
+
+impl ImageFormat {
+    /// "png", "jpeg" or "jpg", in any case
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "png" => Ok(Self::Png),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            other => Err(format!(
+                "Unknown image format '{}' (use png or jpeg)",
+                other
+            )),
+        }
+    }
+
+    /// The format a file name's extension asks for, if any
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(|e| Self::parse(e).ok())
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+/// The frame at `timestamp_ms` encoded as `format`, scaled to `width`
+/// pixels wide keeping the aspect ratio, or at full size without one
+pub async fn frame_image(
+    pipeline: &Pipeline,
+    video: &Path,
+    timestamp_ms: i64,
+    width: Option<u32>,
+    format: ImageFormat,
+) -> Result<Vec<u8>, String> {
+    let mut job = Job::new(format!("frame at {} ms", timestamp_ms))
+        .arg("-ss")
+        .arg(format!("{:.3}", timestamp_ms as f64 / 1000.0))
+        .arg("-i")
+        .arg(video)
+        .args(["-frames:v", "1"]);
+    if let Some(width) = width {
+        job = job.args(["-vf", &format!("scale={}:-2", width)]);
+    }
+    let job = match format {
+        ImageFormat::Png => job.args(["-c:v", "png"]),
+        ImageFormat::Jpeg => job.args(["-c:v", "mjpeg", "-q:v", "3"]),
+    }
+    .args(["-f", "image2pipe", "-"]);
+    let output = pipeline.run(job, |_| {}).await?;
+    if output.is_empty() {
+        return Err(format!(
+            "No frame at {} ms; is it past the end of the video?",
+            timestamp_ms
+        ));
+    }
+    Ok(output)
+}
+
+async fn frame_rgb_with(
+    pipeline: &Pipeline,
+    video: &Path,
+    timestamp_ms: i64,
+    width: u32,
+    height: u32,
+    accel: Option<hwaccel::HwAccel>,
+) -> Result<Vec<u8>, String> {
+    let fit = format!(
+        "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+        w = width,
+        h = height
+    );
+    let job = Job::new(format!("frame at {} ms", timestamp_ms))
+        .args(accel.map(hwaccel::HwAccel::decode_args).unwrap_or_default())
+        .arg("-ss")
+        .arg(format!("{:.3}", timestamp_ms as f64 / 1000.0))
+        .arg("-i")
+        .arg(video)
+        .args(["-frames:v", "1", "-vf", &fit])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgb24", "-"]);
+    let output = pipeline.run(job, |_| {}).await?;
+    let expected = (width * height * 3) as usize;
+    if output.len() < expected {
+        return Err(format!(
+            "No frame at {} ms; is it past the end of the video?",
+            timestamp_ms
+        ));
+    }
+    Ok(output[..expected].to_vec())
+}
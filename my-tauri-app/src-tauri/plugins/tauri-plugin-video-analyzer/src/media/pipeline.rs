@@ -0,0 +1,250 @@
+//! Queue that every local ffmpeg job runs through
+//!
+//! At most [`MediaConfig::max_jobs`](config::MediaConfig::max_jobs) ffmpeg processes run at once; later
+//! jobs wait their turn. A job is listed with its progress, parsed from
+//! ffmpeg's `-progress` report on stderr, until it ends, and can be
+//! cancelled by id, which kills its ffmpeg.
+
+use crate::config;
+use serde::Serialize;
+use std::ffi::{OsStr, OsString};
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+/// One ffmpeg invocation: its arguments after the common ones
+#[derive(Debug, Clone)]
+pub struct Job {
+    label: String,
+    args: Vec<OsString>,
+}
+
+impl Job {
+    /// `label` names the job in listings and errors, e.g. "frame at 1500 ms"
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.args.push(arg.as_ref().to_os_string());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args
+            .extend(args.into_iter().map(|a| a.as_ref().to_os_string()));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Queued,
+    Running,
+}
+
+/// A queued or running job, as listed by `get_media_jobs`
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub label: String,
+    pub state: JobState,
+    /// Output written so far
+    pub processed_ms: i64,
+}
+
+struct Entry {
+    info: JobInfo,
+    cancel: CancellationToken,
+}
+
+/// Managed state: the ffmpeg jobs waiting or running
+pub struct Pipeline {
+    permits: Semaphore,
+    jobs: Mutex<Vec<Entry>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new(config::get().media.max_jobs)
+    }
+}
+
+impl Pipeline {
+    pub fn new(max_jobs: usize) -> Self {
+        Self {
+            permits: Semaphore::new(max_jobs.max(1)),
+            jobs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Every job not yet finished, oldest first
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.iter().map(|entry| entry.info.clone()).collect()
+    }
+
+    /// Cancel job `id`; false if it already finished
+    pub fn cancel(&self, id: &str) -> bool {
+        let jobs = self.jobs.lock().unwrap();
+        match jobs.iter().find(|entry| entry.info.id == id) {
+            Some(entry) => {
+                entry.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Run `job` once a slot is free and return what ffmpeg wrote to stdout
+    ///
+    /// `on_progress` is called each time ffmpeg reports progress.
+    pub async fn run(&self, job: Job, on_progress: impl Fn(&JobInfo)) -> Result<Vec<u8>, String> {
+        let cancel = CancellationToken::new();
+        let id = uuid::Uuid::new_v4().to_string();
+        self.jobs.lock().unwrap().push(Entry {
+            info: JobInfo {
+                id: id.clone(),
+                label: job.label.clone(),
+                state: JobState::Queued,
+                processed_ms: 0,
+            },
+            cancel: cancel.clone(),
+        });
+        let _listed = Listed {
+            pipeline: self,
+            id: &id,
+        };
+        let cancelled = || format!("{} was cancelled", job.label);
+
+        let _permit = tokio::select! {
+            permit = self.permits.acquire() => permit.map_err(|e| e.to_string())?,
+            _ = cancel.cancelled() => return Err(cancelled()),
+        };
+        self.update(&id, |info| info.state = JobState::Running);
+
+        let mut child = tokio::process::Command::new(super::ffmpeg_binary().await?)
+            .args(["-hide_banner", "-loglevel", "error", "-nostats"])
+            .args(["-progress", "pipe:2"])
+            .args(&job.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let stderr = child.stderr.take().expect("stderr is piped");
+
+        let read_stdout = async {
+            let mut out = Vec::new();
+            stdout.read_to_end(&mut out).await.map(|_| out)
+        };
+        let read_stderr = async {
+            let mut lines = BufReader::new(stderr).lines();
+            let mut messages = Vec::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if let Some(ms) = processed_ms(&line) {
+                    if let Some(info) = self.update(&id, |info| info.processed_ms = ms) {
+                        on_progress(&info);
+                    }
+                } else if !is_progress_report(&line) {
+                    messages.push(line);
+                }
+            }
+            messages
+        };
+        let finish = async {
+            let (out, messages) = tokio::join!(read_stdout, read_stderr);
+            (out, messages, child.wait().await)
+        };
+        // Dropping `finish` on cancel drops the child, which kills ffmpeg
+        let (out, messages, status) = tokio::select! {
+            finished = finish => finished,
+            _ = cancel.cancelled() => return Err(cancelled()),
+        };
+
+        let status = status.map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+        if !status.success() {
+            return Err(format!(
+                "ffmpeg failed on {}: {}",
+                job.label,
+                messages.last().map_or("unknown error", String::as_str)
+            ));
+        }
+        out.map_err(|e| format!("Failed to read ffmpeg output: {}", e))
+    }
+
+    /// Apply `change` to job `id`, returning the updated listing
+    fn update(&self, id: &str, change: impl FnOnce(&mut JobInfo)) -> Option<JobInfo> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let entry = jobs.iter_mut().find(|entry| entry.info.id == id)?;
+        change(&mut entry.info);
+        Some(entry.info.clone())
+    }
+}
+
+/// Removes a job from the listing however `run` returns
+struct Listed<'a> {
+    pipeline: &'a Pipeline,
+    id: &'a str,
+}
+
+impl Drop for Listed<'_> {
+    fn drop(&mut self) {
+        let mut jobs = self.pipeline.jobs.lock().unwrap();
+        jobs.retain(|entry| entry.info.id != self.id);
+    }
+}
+
+/// Output time from an `out_time_us=` (or older `out_time_ms=`, also in
+/// microseconds) line of the `-progress` report, in milliseconds
+fn processed_ms(line: &str) -> Option<i64> {
+    let micros = line
+        .strip_prefix("out_time_us=")
+        .or_else(|| line.strip_prefix("out_time_ms="))?;
+    micros.trim().parse::<i64>().ok().map(|us| us / 1000)
+}
+
+/// Whether `line` is a `key=value` line of the `-progress` report rather
+/// than a log message
+fn is_progress_report(line: &str) -> bool {
+    line.split_once('=').is_some_and(|(key, _)| {
+        !key.is_empty()
+            && key
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_progress_report() {
+        assert_eq!(processed_ms("out_time_us=1500000"), Some(1500));
+        assert_eq!(processed_ms("out_time_ms=2000000"), Some(2000));
+        assert_eq!(processed_ms("out_time_us=N/A"), None);
+        assert_eq!(processed_ms("frame=12"), None);
+
+        assert!(is_progress_report("progress=continue"));
+        assert!(is_progress_report("stream_0_0_q=28.0"));
+        assert!(!is_progress_report(
+            "Error opening input file clip.mp4: No such file"
+        ));
+        assert!(!is_progress_report(
+            "[h264 @ 0x1] decode_slice_header error"
+        ));
+    }
+}
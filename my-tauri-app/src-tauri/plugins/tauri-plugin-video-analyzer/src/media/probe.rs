@@ -0,0 +1,273 @@
+//! Reading a video's streams and container with ffprobe
+//!
+//! ffprobe ships with ffmpeg, so it is looked for beside the ffmpeg in
+//! use first, then on PATH. Its JSON report is boiled down to a
+//! [`VideoProbe`], which also says whether the backend can analyse the
+//! video: it needs a video stream in one of [`SUPPORTED_VIDEO_CODECS`].
+
+use super::locator;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Video codecs the backend decodes
+pub const SUPPORTED_VIDEO_CODECS: &[&str] =
+    &["h264", "hevc", "vp8", "vp9", "av1", "mpeg4", "mpeg2video"];
+
+/// Longest ffprobe is given; it only reads headers
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What ffprobe found in a file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VideoProbe {
+    /// Short container names, e.g. "mov,mp4,m4a,3gp,3g2,mj2"
+    pub container: String,
+    pub container_long_name: String,
+    pub duration_secs: Option<f64>,
+    /// Overall bits per second
+    pub bit_rate: Option<u64>,
+    pub size_bytes: Option<u64>,
+    /// The first video stream
+    pub video: Option<VideoStream>,
+    pub audio: Vec<AudioStream>,
+    pub subtitles: Vec<SubtitleStream>,
+    /// Whether the backend can analyse it; `problems` says why not
+    pub supported: bool,
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VideoStream {
+    pub codec: String,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: Option<f64>,
+    pub bit_rate: Option<u64>,
+    pub pixel_format: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AudioStream {
+    pub codec: String,
+    pub channels: Option<u32>,
+    pub sample_rate: Option<u32>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SubtitleStream {
+    /// Index of the stream in the file, as ffmpeg's `-map 0:<index>` takes
+    pub index: u32,
+    pub codec: String,
+    pub language: Option<String>,
+    pub title: Option<String>,
+}
+
+/// The parts of `ffprobe -print_format json -show_format -show_streams`
+/// used; ffprobe prints numbers other than sizes as strings
+#[derive(Deserialize)]
+struct Report {
+    #[serde(default)]
+    streams: Vec<RawStream>,
+    format: RawFormat,
+}
+
+#[derive(Deserialize)]
+struct RawStream {
+    #[serde(default)]
+    index: u32,
+    #[serde(default)]
+    codec_type: String,
+    #[serde(default)]
+    codec_name: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    avg_frame_rate: Option<String>,
+    bit_rate: Option<String>,
+    pix_fmt: Option<String>,
+    channels: Option<u32>,
+    sample_rate: Option<String>,
+    #[serde(default)]
+    tags: RawTags,
+}
+
+#[derive(Default, Deserialize)]
+struct RawTags {
+    language: Option<String>,
+    title: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawFormat {
+    #[serde(default)]
+    format_name: String,
+    #[serde(default)]
+    format_long_name: String,
+    duration: Option<String>,
+    bit_rate: Option<String>,
+    size: Option<String>,
+}
+
+fn number<T: std::str::FromStr>(value: &Option<String>) -> Option<T> {
+    value.as_deref().and_then(|v| v.parse().ok())
+}
+
+/// "30000/1001" as frames per second; ffprobe says "0/0" when it doesn't know
+fn frame_rate(value: &str) -> Option<f64> {
+    let (num, den) = value.split_once('/')?;
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    (num > 0.0 && den > 0.0).then_some(num / den)
+}
+
+/// A [`VideoProbe`] from ffprobe's JSON report
+pub fn parse(json: &str) -> Result<VideoProbe, String> {
+    let report: Report =
+        serde_json::from_str(json).map_err(|e| format!("Unreadable ffprobe report: {}", e))?;
+    let video = report
+        .streams
+        .iter()
+        .find(|s| s.codec_type == "video")
+        .map(|s| VideoStream {
+            codec: s.codec_name.clone(),
+            width: s.width.unwrap_or(0),
+            height: s.height.unwrap_or(0),
+            frame_rate: s.avg_frame_rate.as_deref().and_then(frame_rate),
+            bit_rate: number(&s.bit_rate),
+            pixel_format: s.pix_fmt.clone(),
+        });
+    let audio = report
+        .streams
+        .iter()
+        .filter(|s| s.codec_type == "audio")
+        .map(|s| AudioStream {
+            codec: s.codec_name.clone(),
+            channels: s.channels,
+            sample_rate: number(&s.sample_rate),
+            language: s.tags.language.clone().filter(|l| l != "und"),
+        })
+        .collect();
+    let duration_secs = number(&report.format.duration);
+
+    let mut problems = Vec::new();
+    match &video {
+        None => problems.push("No video stream".to_string()),
+        Some(stream) if !SUPPORTED_VIDEO_CODECS.contains(&stream.codec.as_str()) => {
+            problems.push(format!(
+                "Video codec {} is not supported (expected one of {})",
+                stream.codec,
+                SUPPORTED_VIDEO_CODECS.join(", ")
+            ))
+        }
+        Some(_) => {}
+    }
+    if duration_secs.is_some_and(|d: f64| d <= 0.0) {
+        problems.push("The video has no length".to_string());
+    }
+
+    Ok(VideoProbe {
+        container: report.format.format_name,
+        container_long_name: report.format.format_long_name,
+        duration_secs,
+        bit_rate: number(&report.format.bit_rate),
+        size_bytes: number(&report.format.size),
+        video,
+        audio,
+        subtitles: report
+            .streams
+            .iter()
+            .filter(|s| s.codec_type == "subtitle")
+            .map(|s| SubtitleStream {
+                index: s.index,
+                codec: s.codec_name.clone(),
+                language: s.tags.language.clone().filter(|l| l != "und"),
+                title: s.tags.title.clone(),
+            })
+            .collect(),
+        supported: problems.is_empty(),
+        problems,
+    })
+}
+
+/// The ffprobe to run: beside the ffmpeg in use, else on PATH
+pub async fn ffprobe_binary() -> Option<PathBuf> {
+    let name = locator::binary_name("ffprobe");
+    let beside_ffmpeg = locator::ffmpeg()
+        .await
+        .ok()
+        .and_then(|found| found.path.parent().map(|dir| dir.join(&name)))
+        .filter(|path| path.is_file());
+    beside_ffmpeg.or_else(|| locator::on_path(&name))
+}
+
+/// Probe the video at `path`
+pub async fn probe(path: &Path) -> Result<VideoProbe, String> {
+    let ffprobe = ffprobe_binary()
+        .await
+        .ok_or_else(|| "ffprobe not found; install ffmpeg or set FFMPEG_PATH".to_string())?;
+    let run = tokio::process::Command::new(&ffprobe)
+        .args(["-v", "error", "-print_format", "json"])
+        .args(["-show_format", "-show_streams"])
+        .arg(path)
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(PROBE_TIMEOUT, run)
+        .await
+        .map_err(|_| {
+            format!(
+                "ffprobe took over {:?} on {}",
+                PROBE_TIMEOUT,
+                path.display()
+            )
+        })?
+        .map_err(|e| format!("Failed to run {}: {}", ffprobe.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} is not a readable video: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_is_summarised() {
+        let probe = parse(
+            r#"{
+                "streams": [
+                    {"codec_type": "video", "codec_name": "h264", "width": 1920,
+                     "height": 1080, "avg_frame_rate": "30000/1001", "pix_fmt": "yuv420p"},
+                    {"codec_type": "audio", "codec_name": "aac", "channels": 2,
+                     "sample_rate": "48000", "tags": {"language": "und"}},
+                    {"index": 2, "codec_type": "subtitle", "codec_name": "mov_text",
+                     "tags": {"language": "eng", "title": "SDH"}}
+                ],
+                "format": {"format_name": "mov,mp4,m4a,3gp,3g2,mj2",
+                           "duration": "12.500000", "bit_rate": "4000000", "size": "6250000"}
+            }"#,
+        )
+        .unwrap();
+        assert!(probe.supported);
+        let video = probe.video.unwrap();
+        assert_eq!((video.width, video.height), (1920, 1080));
+        assert!((video.frame_rate.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(probe.duration_secs, Some(12.5));
+        assert_eq!(probe.audio[0].sample_rate, Some(48000));
+        assert_eq!(probe.audio[0].language, None);
+        assert_eq!(probe.subtitles[0].index, 2);
+        assert_eq!(probe.subtitles[0].language.as_deref(), Some("eng"));
+        assert_eq!(probe.subtitles[0].title.as_deref(), Some("SDH"));
+
+        let audio_only = parse(
+            r#"{"streams": [{"codec_type": "audio", "codec_name": "mp3"}],
+                "format": {"format_name": "mp3", "duration": "3.0"}}"#,
+        )
+        .unwrap();
+        assert!(!audio_only.supported);
+        assert_eq!(audio_only.problems, ["No video stream"]);
+    }
+}
@@ -0,0 +1,271 @@
+//! Contact sheets: a grid of frames, each captioned with its timestamp
+//!
+//! Composed in memory as RGB and written as PNG, or as a one-page PDF
+//! embedding that image. Captions use a built-in bitmap font, so nothing
+//! depends on the fonts (or the ffmpeg build) installed on the machine.
+
+use std::io::Write;
+
+pub const CELL_WIDTH: u32 = 320;
+pub const CELL_HEIGHT: u32 = 180;
+pub const MAX_COLUMNS: u32 = 4;
+
+const MARGIN: u32 = 12;
+const CAPTION_HEIGHT: u32 = 28;
+/// Each font pixel is drawn as a SCALE x SCALE block
+const SCALE: u32 = 2;
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const INK: [u8; 3] = [40, 40, 40];
+
+/// An RGB image
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+/// Output formats, from the destination's extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetFormat {
+    Png,
+    Pdf,
+}
+
+impl SheetFormat {
+    pub fn from_path(path: &std::path::Path) -> Result<Self, String> {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("png") => Ok(Self::Png),
+            Some("pdf") => Ok(Self::Pdf),
+            _ => Err(format!(
+                "Unsupported contact sheet file {}: use a .png or .pdf name",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// `HH:MM:SS.mmm`
+pub fn caption(timestamp_ms: i64) -> String {
+    let ms = timestamp_ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+/// Lay `frames` (timestamp, CELL_WIDTH x CELL_HEIGHT RGB) out in rows
+pub fn compose(frames: &[(i64, Vec<u8>)]) -> Sheet {
+    let count = frames.len().max(1) as u32;
+    let columns = count.min(MAX_COLUMNS);
+    let rows = count.div_ceil(columns);
+    let width = MARGIN + columns * (CELL_WIDTH + MARGIN);
+    let height = MARGIN + rows * (CELL_HEIGHT + CAPTION_HEIGHT + MARGIN);
+    let mut sheet = Sheet {
+        width,
+        height,
+        rgb: BACKGROUND.repeat((width * height) as usize),
+    };
+
+    for (i, (timestamp_ms, frame)) in frames.iter().enumerate() {
+        let x = MARGIN + (i as u32 % columns) * (CELL_WIDTH + MARGIN);
+        let y = MARGIN + (i as u32 / columns) * (CELL_HEIGHT + CAPTION_HEIGHT + MARGIN);
+        for row in 0..CELL_HEIGHT {
+            let src = (row * CELL_WIDTH * 3) as usize;
+            let dst = (((y + row) * width + x) * 3) as usize;
+            let len = (CELL_WIDTH * 3) as usize;
+            if let Some(line) = frame.get(src..src + len) {
+                sheet.rgb[dst..dst + len].copy_from_slice(line);
+            }
+        }
+        let text = caption(*timestamp_ms);
+        let text_width = text.len() as u32 * 6 * SCALE;
+        let text_x = x + CELL_WIDTH.saturating_sub(text_width) / 2;
+        let text_y = y + CELL_HEIGHT + (CAPTION_HEIGHT - 7 * SCALE) / 2;
+        draw_text(&mut sheet, text_x, text_y, &text);
+    }
+    sheet
+}
+
+/// 5x7 glyphs, one byte per row, high bit on the left
+fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0x70, 0x88, 0x98, 0xA8, 0xC8, 0x88, 0x70],
+        '1' => [0x20, 0x60, 0x20, 0x20, 0x20, 0x20, 0x70],
+        '2' => [0x70, 0x88, 0x08, 0x10, 0x20, 0x40, 0xF8],
+        '3' => [0xF8, 0x10, 0x20, 0x10, 0x08, 0x88, 0x70],
+        '4' => [0x10, 0x30, 0x50, 0x90, 0xF8, 0x10, 0x10],
+        '5' => [0xF8, 0x80, 0xF0, 0x08, 0x08, 0x88, 0x70],
+        '6' => [0x30, 0x40, 0x80, 0xF0, 0x88, 0x88, 0x70],
+        '7' => [0xF8, 0x08, 0x10, 0x20, 0x40, 0x40, 0x40],
+        '8' => [0x70, 0x88, 0x88, 0x70, 0x88, 0x88, 0x70],
+        '9' => [0x70, 0x88, 0x88, 0x78, 0x08, 0x10, 0x60],
+        ':' => [0x00, 0x60, 0x60, 0x00, 0x60, 0x60, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x60, 0x60],
+        _ => [0; 7],
+    }
+}
+
+fn draw_text(sheet: &mut Sheet, x: u32, y: u32, text: &str) {
+    for (i, c) in text.chars().enumerate() {
+        let left = x + i as u32 * 6 * SCALE;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x80 >> col) == 0 {
+                    continue;
+                }
+                for dy in 0..SCALE {
+                    for dx in 0..SCALE {
+                        let px = left + col * SCALE + dx;
+                        let py = y + row as u32 * SCALE + dy;
+                        if px < sheet.width && py < sheet.height {
+                            let at = ((py * sheet.width + px) * 3) as usize;
+                            sheet.rgb[at..at + 3].copy_from_slice(&INK);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn to_png(sheet: &Sheet) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    let mut encoder = png::Encoder::new(&mut out, sheet.width, sheet.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&sheet.rgb))
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(out)
+}
+
+/// A one-page PDF showing the sheet at 72 dpi
+pub fn to_pdf(sheet: &Sheet) -> Result<Vec<u8>, String> {
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(&sheet.rgb)
+        .map_err(|e| format!("Failed to compress image: {}", e))?;
+    let image = encoder
+        .finish()
+        .map_err(|e| format!("Failed to compress image: {}", e))?;
+    let (w, h) = (sheet.width, sheet.height);
+    let content = format!("q {} 0 0 {} 0 0 cm /Im0 Do Q", w, h);
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::new();
+    let mut object = |pdf: &mut Vec<u8>, body: &[u8]| {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", offsets.len()).as_bytes());
+        pdf.extend_from_slice(body);
+        pdf.extend_from_slice(b"\nendobj\n");
+    };
+    object(&mut pdf, b"<< /Type /Catalog /Pages 2 0 R >>");
+    object(&mut pdf, b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>");
+    object(
+        &mut pdf,
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /XObject << /Im0 4 0 R >> >> /Contents 5 0 R >>",
+            w, h
+        )
+        .as_bytes(),
+    );
+    let mut stream = format!(
+        "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB \
+         /BitsPerComponent 8 /Filter /FlateDecode /Length {} >>\nstream\n",
+        w,
+        h,
+        image.len()
+    )
+    .into_bytes();
+    stream.extend_from_slice(&image);
+    stream.extend_from_slice(b"\nendstream");
+    object(&mut pdf, &stream);
+    object(
+        &mut pdf,
+        format!(
+            "<< /Length {} >>\nstream\n{}\nendstream",
+            content.len(),
+            content
+        )
+        .as_bytes(),
+    );
+
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1).as_bytes(),
+    );
+    for offset in &offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    Ok(pdf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(shade: u8) -> Vec<u8> {
+        vec![shade; (CELL_WIDTH * CELL_HEIGHT * 3) as usize]
+    }
+
+    #[test]
+    fn test_compose_grid_and_captions() {
+        let frames: Vec<_> = (0..5).map(|i| (i * 61_500, frame(100))).collect();
+        let sheet = compose(&frames);
+        assert_eq!(sheet.width, MARGIN + 4 * (CELL_WIDTH + MARGIN));
+        assert_eq!(
+            sheet.height,
+            MARGIN + 2 * (CELL_HEIGHT + CAPTION_HEIGHT + MARGIN)
+        );
+        // Top-left frame copied in, and some caption ink below it
+        let at = ((MARGIN * sheet.width + MARGIN) * 3) as usize;
+        assert_eq!(&sheet.rgb[at..at + 3], &[100, 100, 100]);
+        let row = |y: u32| (y * sheet.width * 3) as usize;
+        let strip = row(MARGIN + CELL_HEIGHT)..row(MARGIN + CELL_HEIGHT + CAPTION_HEIGHT);
+        assert!(sheet.rgb[strip].chunks(3).any(|px| px == INK));
+        assert_eq!(caption(3_723_004), "01:02:03.004");
+    }
+
+    #[test]
+    fn test_outputs() {
+        let sheet = compose(&[(0, frame(0))]);
+        let png = to_png(&sheet).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let pdf = to_pdf(&sheet).unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4"));
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref"));
+
+        assert_eq!(
+            SheetFormat::from_path(std::path::Path::new("a/sheet.PDF")),
+            Ok(SheetFormat::Pdf)
+        );
+        assert!(SheetFormat::from_path(std::path::Path::new("sheet.jpg")).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+//! Subtitle tracks already in a video
+//!
+//! MKV and MP4 files often carry their own captions. `extract_subtitles`
+//! has ffmpeg pull the text tracks out as SRT, or as ASS when that is what
+//! they were so their styling survives, and can hand them to the backend
+//! with AddCaptions so the speech they cover isn't transcribed again.
+//! Bitmap subtitles (PGS, VobSub, DVB) would need OCR and are skipped.
+
+use super::pipeline::{Job, Pipeline};
+use super::probe::SubtitleStream;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubtitleFormat {
+    Srt,
+    Ass,
+}
+
+impl SubtitleFormat {
+    /// What a track in `codec` is extracted as; `None` for bitmap subtitles
+    pub fn for_codec(codec: &str) -> Option<Self> {
+        match codec {
+            "ass" | "ssa" => Some(Self::Ass),
+            "subrip" | "srt" | "mov_text" | "webvtt" | "text" | "microdvd" | "subviewer" => {
+                Some(Self::Srt)
+            }
+            _ => None,
+        }
+    }
+
+    /// ffmpeg's name for both the encoder and the muxer
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Ass => "ass",
+        }
+    }
+}
+
+/// An extracted track
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtitleTrack {
+    pub index: u32,
+    pub codec: String,
+    pub language: Option<String>,
+    pub title: Option<String>,
+    pub format: SubtitleFormat,
+    pub text: String,
+}
+
+/// A track that couldn't be extracted, and why
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedTrack {
+    pub index: u32,
+    pub codec: String,
+    pub reason: String,
+}
+
+/// Extract `stream` of `video` as text
+pub async fn extract(
+    pipeline: &Pipeline,
+    video: &Path,
+    stream: &SubtitleStream,
+) -> Result<SubtitleTrack, String> {
+    let format = SubtitleFormat::for_codec(&stream.codec).ok_or_else(|| {
+        format!(
+            "{} subtitles are images, not text; they would need OCR",
+            stream.codec
+        )
+    })?;
+    let job = Job::new(format!("subtitles {} of {}", stream.index, video.display()))
+        .arg("-i")
+        .arg(video)
+        .arg("-map")
+        .arg(format!("0:{}", stream.index))
+        .args(["-c:s", format.name(), "-f", format.name(), "-"]);
+    let output = pipeline.run(job, |_| {}).await?;
+    let text = String::from_utf8_lossy(&output).into_owned();
+    if text.trim().is_empty() {
+        return Err("The track is empty".to_string());
+    }
+    Ok(SubtitleTrack {
+        index: stream.index,
+        codec: stream.codec.clone(),
+        language: stream.language.clone(),
+        title: stream.title.clone(),
+        format,
+        text,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_codecs_are_extracted_and_bitmaps_are_not() {
+        assert_eq!(
+            SubtitleFormat::for_codec("mov_text"),
+            Some(SubtitleFormat::Srt)
+        );
+        assert_eq!(SubtitleFormat::for_codec("ssa"), Some(SubtitleFormat::Ass));
+        assert_eq!(SubtitleFormat::for_codec("hdmv_pgs_subtitle"), None);
+        assert_eq!(SubtitleFormat::for_codec("dvd_subtitle"), None);
+    }
+}
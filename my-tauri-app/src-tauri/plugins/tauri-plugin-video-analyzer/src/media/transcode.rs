@@ -0,0 +1,253 @@
+//! Re-encoding videos to H.264/AAC before they are uploaded
+//!
+//! ProRes masters and high-bitrate HEVC take far longer to upload than
+//! the analysis needs, and the backend may not decode them at all. With
+//! the `local_transcode` feature flag on and TRANSCODE set,
+//! `upload_video_from_path` re-encodes such files to an MP4 at the target
+//! bitrates first and uploads that instead:
+//!
+//! - `off` (default): never
+//! - `auto`: when the video isn't H.264, or its bitrate is over
+//!   [`OVER_TARGET`] times the target
+//! - `always`: every upload
+//!
+//! The encode runs through the [`pipeline`](super::pipeline) on the
+//! [`hwaccel`](super::hwaccel) encoder when there is one, falling back to
+//! libx264 if that fails. While [saving power](crate::power) a conversion
+//! waits to start until saving ends; one already running goes on.
+//!
+//! `upload_audio_only` goes further and sends just the first audio track,
+//! as AAC in an M4A, for analysis that only needs the transcript.
+
+use super::hwaccel::{self, HwAccel};
+use super::pipeline::{Job, Pipeline};
+use super::probe::VideoProbe;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// How far over the target bitrate `auto` lets a video through
+pub const OVER_TARGET: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Off,
+    Auto,
+    Always,
+}
+
+impl Mode {
+    /// The TRANSCODE setting; anything unknown is off
+    pub fn parse(setting: &str) -> Self {
+        match setting.trim().to_ascii_lowercase().as_str() {
+            "auto" => Self::Auto,
+            "always" | "on" | "1" | "true" => Self::Always,
+            "" | "off" | "0" | "false" => Self::Off,
+            other => {
+                log::warn!("Unknown TRANSCODE '{}', not transcoding", other);
+                Self::Off
+            }
+        }
+    }
+}
+
+/// Bitrates to encode at, in kbit/s
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    pub video_kbps: u32,
+    pub audio_kbps: u32,
+}
+
+/// Why `probe` should be transcoded under `mode`, or `None` to upload it
+/// as it is
+pub fn reason(mode: Mode, probe: &VideoProbe, target: Target) -> Option<String> {
+    // Without a video stream there is nothing re-encoding would fix
+    let video = probe.video.as_ref()?;
+    match mode {
+        Mode::Off => None,
+        Mode::Always => Some("transcoding every upload".to_string()),
+        Mode::Auto if video.codec != "h264" => Some(format!("{} video", video.codec)),
+        Mode::Auto => {
+            let kbps = probe.bit_rate? as f64 / 1000.0;
+            (kbps > target.video_kbps as f64 * OVER_TARGET).then(|| {
+                format!(
+                    "{:.0} kbit/s, over the {} kbit/s target",
+                    kbps, target.video_kbps
+                )
+            })
+        }
+    }
+}
+
+/// What an upload sends in place of the file itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conversion {
+    /// The video re-encoded, for the reason given
+    Video(String),
+    /// Only the audio
+    AudioOnly,
+}
+
+impl Conversion {
+    /// Name of the upload operation's stage that runs it
+    pub fn stage(&self) -> &'static str {
+        match self {
+            Self::Video(_) => "transcode",
+            Self::AudioOnly => "extract_audio",
+        }
+    }
+
+    /// Whether the converted copy is still the picked video, so the backend
+    /// may match one for the other; an audio track alone is not
+    pub fn keeps_the_video(&self) -> bool {
+        matches!(self, Self::Video(_))
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Video(_) => "mp4",
+            Self::AudioOnly => "m4a",
+        }
+    }
+}
+
+/// Where the converted copy for upload `upload_id` goes; it is kept until
+/// the upload finishes, so a cut-off one can still be resumed
+pub fn scratch_path(upload_id: &str, conversion: &Conversion) -> PathBuf {
+    std::env::temp_dir()
+        .join("video-analyzer-transcodes")
+        .join(format!("{}.{}", upload_id, conversion.extension()))
+}
+
+fn audio_job(src: &Path, dest: &Path, target: Target) -> Job {
+    Job::new(format!("extract audio of {}", src.display()))
+        .arg("-y")
+        .arg("-i")
+        .arg(src)
+        .args(["-map", "0:a:0", "-vn", "-c:a", "aac"])
+        .arg("-b:a")
+        .arg(format!("{}k", target.audio_kbps))
+        .args(["-movflags", "+faststart", "-f", "ipod"])
+        .arg(dest)
+}
+
+fn job(src: &Path, dest: &Path, target: Target, accel: Option<HwAccel>) -> Job {
+    let encoder = accel.map_or("libx264", HwAccel::h264_encoder);
+    let mut job = Job::new(format!("transcode {}", src.display()))
+        .arg("-y")
+        .arg("-i")
+        .arg(src)
+        .args(["-map", "0:v:0", "-map", "0:a?"])
+        .args(["-c:v", encoder]);
+    if accel.is_none() {
+        job = job.args(["-preset", "veryfast"]);
+    }
+    job.arg("-b:v")
+        .arg(format!("{}k", target.video_kbps))
+        .arg("-maxrate")
+        .arg(format!("{}k", target.video_kbps))
+        .arg("-bufsize")
+        .arg(format!("{}k", target.video_kbps * 2))
+        .args(["-pix_fmt", "yuv420p", "-c:a", "aac"])
+        .arg("-b:a")
+        .arg(format!("{}k", target.audio_kbps))
+        .args(["-movflags", "+faststart", "-f", "mp4"])
+        .arg(dest)
+}
+
+/// Write `src` converted at `dest`; `on_progress` hears how many
+/// milliseconds of it are done
+pub async fn convert(
+    pipeline: &Pipeline,
+    conversion: &Conversion,
+    src: &Path,
+    dest: &Path,
+    target: Target,
+    on_progress: impl Fn(i64),
+) -> Result<(), String> {
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    if *conversion == Conversion::AudioOnly {
+        return pipeline
+            .run(audio_job(src, dest, target), |info| {
+                on_progress(info.processed_ms)
+            })
+            .await
+            .map(|_| ());
+    }
+    if let Some(accel) = hwaccel::selected().await {
+        let run = pipeline.run(job(src, dest, target, Some(accel)), |info| {
+            on_progress(info.processed_ms)
+        });
+        match run.await {
+            Ok(_) => return Ok(()),
+            Err(e) => log::warn!("{:?} transcode failed, using the CPU: {}", accel, e),
+        }
+    }
+    pipeline
+        .run(job(src, dest, target, None), |info| {
+            on_progress(info.processed_ms)
+        })
+        .await
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::media::probe::VideoStream;
+
+    #[test]
+    fn auto_transcodes_other_codecs_and_high_bitrates() {
+        let target = Target {
+            video_kbps: 4000,
+            audio_kbps: 128,
+        };
+        let probe = |codec: &str, bit_rate: u64| VideoProbe {
+            container: "mov,mp4,m4a,3gp,3g2,mj2".to_string(),
+            container_long_name: String::new(),
+            duration_secs: Some(10.0),
+            bit_rate: Some(bit_rate),
+            size_bytes: None,
+            video: Some(VideoStream {
+                codec: codec.to_string(),
+                width: 1920,
+                height: 1080,
+                frame_rate: None,
+                bit_rate: None,
+                pixel_format: None,
+            }),
+            audio: Vec::new(),
+            subtitles: Vec::new(),
+            supported: true,
+            problems: Vec::new(),
+        };
+        assert_eq!(reason(Mode::Auto, &probe("h264", 5_000_000), target), None);
+        assert!(reason(Mode::Auto, &probe("h264", 7_000_000), target).is_some());
+        assert_eq!(
+            reason(Mode::Auto, &probe("prores", 1_000_000), target).as_deref(),
+            Some("prores video")
+        );
+        assert_eq!(reason(Mode::Off, &probe("prores", 1_000_000), target), None);
+        assert!(reason(Mode::Always, &probe("h264", 1_000_000), target).is_some());
+        assert_eq!(Mode::parse("AUTO"), Mode::Auto);
+    }
+
+    #[test]
+    fn audio_only_is_staged_and_kept_as_m4a() {
+        let conversion = Conversion::AudioOnly;
+        assert_eq!(conversion.stage(), "extract_audio");
+        assert_eq!(
+            scratch_path("abc", &conversion).file_name().unwrap(),
+            "abc.m4a"
+        );
+        assert_eq!(
+            scratch_path("abc", &Conversion::Video(String::new()))
+                .file_name()
+                .unwrap(),
+            "abc.mp4"
+        );
+    }
+}
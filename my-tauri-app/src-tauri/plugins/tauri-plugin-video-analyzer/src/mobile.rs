@@ -0,0 +1,103 @@
+//! Android/iOS side of the plugin
+//!
+//! The native halves live in `android/` (Kotlin) and `ios/` (Swift). They
+//! resolve URIs the app can't open itself and hand back a file descriptor
+//! that Rust then owns, and keep the process alive while uploads run in the
+//! background (a foreground service on Android, a background task on iOS).
+
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tauri::plugin::{PluginApi, PluginHandle};
+use tauri::{AppHandle, Runtime};
+
+use crate::PlatformFile;
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_video_analyzer);
+
+pub fn init<R: Runtime, C: DeserializeOwned>(
+    _app: &AppHandle<R>,
+    api: PluginApi<R, C>,
+) -> Result<VideoAnalyzer<R>, Box<dyn std::error::Error>> {
+    #[cfg(target_os = "android")]
+    let handle = api.register_android_plugin("com.plugin.videoanalyzer", "VideoAnalyzerPlugin")?;
+    #[cfg(target_os = "ios")]
+    let handle = api.register_ios_plugin(init_plugin_video_analyzer)?;
+    Ok(VideoAnalyzer(handle))
+}
+
+pub struct VideoAnalyzer<R: Runtime>(PluginHandle<R>);
+
+#[derive(Serialize)]
+struct OpenUriArgs<'a> {
+    uri: &'a str,
+}
+
+#[derive(Deserialize)]
+struct OpenedUri {
+    fd: i32,
+    name: String,
+    /// -1 when the provider doesn't know the size
+    size: i64,
+}
+
+#[derive(Serialize)]
+struct BeginTransferArgs<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TransferId {
+    id: i32,
+}
+
+impl<R: Runtime> VideoAnalyzer<R> {
+    pub fn open_uri(&self, uri: &str) -> Result<PlatformFile, String> {
+        let opened: OpenedUri = self
+            .0
+            .run_mobile_plugin("openUri", OpenUriArgs { uri })
+            .map_err(|e| format!("Failed to open {}: {}", uri, e))?;
+        // SAFETY: the native side detached the descriptor from its own
+        // wrapper, so nothing else will close it
+        let file = unsafe { <std::fs::File as std::os::fd::FromRawFd>::from_raw_fd(opened.fd) };
+        Ok(PlatformFile {
+            file,
+            name: opened.name,
+            size: u64::try_from(opened.size).ok(),
+        })
+    }
+    /// Ask the OS to keep running until the returned guard is dropped
+    ///
+    /// Best effort: if the native side refuses (e.g. notifications are
+    /// blocked) the upload still runs, it just may be suspended.
+    pub fn begin_transfer(&self, name: &str) -> Transfer<R> {
+        let id = self
+            .0
+            .run_mobile_plugin::<TransferId>("beginTransfer", BeginTransferArgs { name })
+            .map(|t| t.id)
+            .map_err(|e| warn!("Background transfer for {} not started: {}", name, e))
+            .ok();
+        Transfer {
+            handle: self.0.clone(),
+            id,
+        }
+    }
+}
+
+/// Keeps the app alive in the background while an upload is in flight
+pub struct Transfer<R: Runtime> {
+    handle: PluginHandle<R>,
+    id: Option<i32>,
+}
+
+impl<R: Runtime> Drop for Transfer<R> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.handle
+                .run_mobile_plugin::<serde_json::Value>("endTransfer", TransferId { id })
+                .map_err(|e| warn!("Failed to end background transfer {}: {}", id, e))
+                .ok();
+        }
+    }
+}
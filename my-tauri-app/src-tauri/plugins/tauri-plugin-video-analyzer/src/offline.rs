@@ -0,0 +1,156 @@
+//! Videos pinned for offline viewing
+//!
+//! `pin_for_offline` copies everything needed to browse a video without the
+//! backend into `<app data>/offline/<video_id>/`:
+//!
+//! - `history.json`: the full chat history, shaped like `get_chat_history`
+//! - the thumbnail, key frames and analysis report from `GetOfflineAssets`
+//!
+//! `offline.json` indexes what is pinned. Re-pinning replaces the old copy
+//! only once the new one is complete.
+
+use crate::responses::ChatHistory;
+use crate::store::{load_json, save_json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfflineAsset {
+    /// "thumbnail", "key_frame" or "report"
+    pub kind: String,
+    /// File name within the video's offline directory
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub timestamp_seconds: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedVideo {
+    pub video_id: String,
+    pub video_name: String,
+    pub pinned_at: f64,
+    pub history_messages: usize,
+    pub assets: Vec<OfflineAsset>,
+    pub total_bytes: u64,
+}
+
+/// Managed state: the pinned videos and where their files live
+pub struct OfflineCache {
+    root: PathBuf,
+    pins: Mutex<HashMap<String, PinnedVideo>>,
+}
+
+impl OfflineCache {
+    pub fn load(root: PathBuf) -> Self {
+        let pins = load_json(&root.join("offline.json"));
+        Self {
+            root,
+            pins: Mutex::new(pins),
+        }
+    }
+
+    /// Directory holding a video's files
+    pub fn dir(&self, video_id: &str) -> PathBuf {
+        self.root.join(safe_file_name(video_id))
+    }
+
+    /// Scratch directory a pin is downloaded into before it replaces `dir`
+    pub fn staging_dir(&self, video_id: &str) -> PathBuf {
+        self.root
+            .join(format!("{}.partial", safe_file_name(video_id)))
+    }
+
+    pub fn get(&self, video_id: &str) -> Option<PinnedVideo> {
+        self.pins.lock().unwrap().get(video_id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<PinnedVideo> {
+        let mut pins: Vec<PinnedVideo> = self.pins.lock().unwrap().values().cloned().collect();
+        pins.sort_by(|a, b| a.video_name.cmp(&b.video_name));
+        pins
+    }
+
+    /// Show `video_id` as `name` from now on; false if it isn't pinned
+    pub fn rename(&self, video_id: &str, name: &str) -> Result<bool, String> {
+        let mut pins = self.pins.lock().unwrap();
+        let Some(pin) = pins.get_mut(video_id) else {
+            return Ok(false);
+        };
+        pin.video_name = name.to_string();
+        save_json(&self.root.join("offline.json"), &*pins)?;
+        Ok(true)
+    }
+
+    /// Move a completed staging directory into place and index it
+    pub fn commit(&self, pin: PinnedVideo) -> Result<(), String> {
+        let dir = self.dir(&pin.video_id);
+        let staging = self.staging_dir(&pin.video_id);
+        let mut pins = self.pins.lock().unwrap();
+        remove_dir(&dir)?;
+        std::fs::rename(&staging, &dir)
+            .map_err(|e| format!("Failed to move {} into place: {}", dir.display(), e))?;
+        pins.insert(pin.video_id.clone(), pin);
+        save_json(&self.root.join("offline.json"), &*pins)
+    }
+
+    pub fn remove(&self, video_id: &str) -> Result<Option<PinnedVideo>, String> {
+        let mut pins = self.pins.lock().unwrap();
+        let removed = pins.remove(video_id);
+        remove_dir(&self.dir(video_id))?;
+        save_json(&self.root.join("offline.json"), &*pins)?;
+        Ok(removed)
+    }
+
+    /// The pinned chat history, for when the backend can't be reached
+    pub fn history(&self, video_id: &str) -> Option<ChatHistory> {
+        self.get(video_id)?;
+        let text = std::fs::read_to_string(self.dir(video_id).join("history.json")).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+}
+
+fn remove_dir(dir: &Path) -> Result<(), String> {
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {}", dir.display(), e)),
+    }
+}
+
+/// Make a backend-supplied id or name safe to use as a single path component
+pub fn safe_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches('.');
+    if cleaned.is_empty() {
+        "_".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_file_name() {
+        assert_eq!(safe_file_name("frame_0001.jpg"), "frame_0001.jpg");
+        assert_eq!(safe_file_name("../../etc/passwd"), "_.._etc_passwd");
+        assert_eq!(safe_file_name("a/b\\c:d"), "a_b_c_d");
+        assert_eq!(safe_file_name(".."), "_");
+        assert_eq!(safe_file_name(""), "_");
+    }
+}
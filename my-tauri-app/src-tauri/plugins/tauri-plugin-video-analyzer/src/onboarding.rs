@@ -0,0 +1,336 @@
+//! Steps behind the first-run backend setup wizard
+//!
+//! The frontend walks the user through them in order:
+//!
+//! 1. `detect_local_backend`: look for a backend on this machine
+//! 2. `test_connection`: try a URL, TLS setting and token, getting a
+//!    [`Check`] per step so the wizard can point at what to fix
+//! 3. `save_initial_profile`: keep the choice in `profile.json` and switch
+//!    to it straight away
+//!
+//! Nothing here touches the shared gRPC channel until the profile is saved.
+
+use crate::config::BackendProfile;
+use crate::store::{load_json, save_json};
+use crate::transport::local::{self, LocalAddress};
+use crate::transport::{crypto, grpc, tls::CertificateError};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Where the saved profile lives, in the app data directory
+pub const PROFILE_FILE: &str = "profile.json";
+
+/// Ports a local backend is looked for on, the default first
+const LOCAL_PORTS: &[u16] = &[50051, 50052, 50053];
+
+/// How long to wait for a port or a backend to answer
+const STEP_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// Not tried because an earlier step failed
+    Skipped,
+}
+
+/// Outcome of one step of [`test_connection`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// "url", "reach", "tls", "auth" or "backend"
+    pub step: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl Check {
+    fn new(step: &'static str, result: Result<String, String>) -> Self {
+        let (status, message) = match result {
+            Ok(message) => (CheckStatus::Passed, message),
+            Err(message) => (CheckStatus::Failed, message),
+        };
+        Self {
+            step,
+            status,
+            message,
+        }
+    }
+}
+
+/// Host and port `server_url` points at
+fn host_port(server_url: &str) -> Result<(String, u16), String> {
+    let url = url::Url::parse(server_url)
+        .map_err(|e| format!("{} is not a valid URL: {}", server_url, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "{} should start with http:// or https://",
+            server_url
+        ));
+    }
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("{} has no host", server_url))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| format!("{} has no port", server_url))?;
+    Ok((host.to_string(), port))
+}
+
+/// Where a server URL points
+enum Target {
+    Network { host: String, port: u16 },
+    Local(LocalAddress),
+}
+
+impl Target {
+    fn parse(server_url: &str) -> Result<Self, String> {
+        match local::parse(server_url) {
+            Some(address) => address.map(Self::Local),
+            None => host_port(server_url).map(|(host, port)| Self::Network { host, port }),
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Network { host, port } => write!(f, "{}:{}", host, port),
+            Self::Local(address) => address.fmt(f),
+        }
+    }
+}
+
+async fn reach(target: &Target) -> Result<String, String> {
+    let connect = async {
+        match target {
+            Target::Network { host, port } => {
+                tokio::net::TcpStream::connect((host.as_str(), *port))
+                    .await
+                    .map(drop)
+            }
+            Target::Local(address) => local::connect(address).await.map(drop),
+        }
+    };
+    match tokio::time::timeout(STEP_TIMEOUT, connect).await {
+        Ok(Ok(())) => Ok(format!("{} is accepting connections", target)),
+        Ok(Err(e)) => Err(format!("Could not reach {}: {}", target, e)),
+        Err(_) => Err(format!("{} did not answer in time", target)),
+    }
+}
+
+/// Try the backend at `server_url` step by step, stopping at the first
+/// failure
+pub async fn test_connection(server_url: &str, tls: bool, token: Option<&str>) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let skip = |checks: &mut Vec<Check>, from: usize| {
+        for step in &["url", "reach", "tls", "auth", "backend"][from..] {
+            checks.push(Check {
+                step,
+                status: CheckStatus::Skipped,
+                message: String::new(),
+            });
+        }
+    };
+
+    let target = match Target::parse(server_url) {
+        Ok(target) => target,
+        Err(e) => {
+            checks.push(Check::new("url", Err(e)));
+            skip(&mut checks, 1);
+            return checks;
+        }
+    };
+    checks.push(Check::new("url", Ok(target.to_string())));
+
+    let reached = reach(&target).await;
+    let reached_ok = reached.is_ok();
+    checks.push(Check::new("reach", reached));
+    if !reached_ok {
+        skip(&mut checks, 2);
+        return checks;
+    }
+
+    let probed = tokio::time::timeout(STEP_TIMEOUT, grpc::probe(server_url, tls, token)).await;
+    let status = match probed {
+        Ok(Ok(_)) => None,
+        Ok(Err(status)) => Some(status),
+        Err(_) => Some(tonic::Status::deadline_exceeded(
+            "The backend did not answer in time",
+        )),
+    };
+    let tls_message = if tls {
+        "TLS handshake succeeded"
+    } else {
+        "TLS is off"
+    };
+    match status {
+        None => {
+            checks.push(Check::new("tls", Ok(tls_message.to_string())));
+            let auth = if token.is_some() {
+                "Token accepted"
+            } else {
+                "No token needed"
+            };
+            checks.push(Check::new("auth", Ok(auth.to_string())));
+            checks.push(Check::new(
+                "backend",
+                Ok("The backend answered".to_string()),
+            ));
+        }
+        Some(status) if CertificateError::from_status(&status).is_some() => {
+            checks.push(Check::new("tls", Err(status.message().to_string())));
+            skip(&mut checks, 3);
+        }
+        Some(status) if status.code() == tonic::Code::Unauthenticated => {
+            checks.push(Check::new("tls", Ok(tls_message.to_string())));
+            let message = match token {
+                Some(_) => format!("The backend refused the token: {}", status.message()),
+                None => "The backend requires an auth token".to_string(),
+            };
+            checks.push(Check::new("auth", Err(message)));
+            skip(&mut checks, 4);
+        }
+        Some(status) => {
+            // The port is open but no gRPC backend answered: most often
+            // TLS on one side only, or another program on the port
+            let hint = if tls {
+                "; is the backend serving TLS?"
+            } else {
+                "; does the backend need TLS?"
+            };
+            checks.push(Check::new(
+                "tls",
+                Err(format!("{}{}", status.message(), hint)),
+            ));
+            skip(&mut checks, 3);
+        }
+    }
+    checks
+}
+
+/// A backend found on this machine
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalBackend {
+    pub server_url: String,
+    /// Whether it answered a call, not just accepted a connection
+    pub responds: bool,
+    pub requires_auth: bool,
+    pub message: String,
+}
+
+/// Backends listening on the usual local ports
+pub async fn detect_local_backends() -> Vec<LocalBackend> {
+    let mut found = Vec::new();
+    for port in LOCAL_PORTS {
+        let target = Target::Network {
+            host: "127.0.0.1".to_string(),
+            port: *port,
+        };
+        if reach(&target).await.is_err() {
+            continue;
+        }
+        let server_url = format!("http://127.0.0.1:{}", port);
+        let probed =
+            tokio::time::timeout(STEP_TIMEOUT, grpc::probe(&server_url, false, None)).await;
+        let (responds, requires_auth, message) = match probed {
+            Ok(Ok(_)) => (true, false, "Video analyzer backend".to_string()),
+            Ok(Err(status)) if status.code() == tonic::Code::Unauthenticated => (
+                true,
+                true,
+                "Video analyzer backend requiring a token".to_string(),
+            ),
+            Ok(Err(status)) => (false, false, status.message().to_string()),
+            Err(_) => (false, false, "Did not answer in time".to_string()),
+        };
+        found.push(LocalBackend {
+            server_url,
+            responds,
+            requires_auth,
+            message,
+        });
+    }
+    found
+}
+
+/// The profile saved by an earlier run, if any, with the active account's
+/// upload encryption key from the credential store
+pub fn load_profile(path: &Path) -> Option<BackendProfile> {
+    let mut profile = load_json::<Option<BackendProfile>>(path)?;
+    match &profile.upload_encryption_key {
+        // Written by a release that kept the key in the file; move it
+        Some(key) => match crypto::store_key(key) {
+            Ok(()) => {
+                if let Err(e) = save_json(path, &Some(&profile)) {
+                    log::warn!("{}", e);
+                }
+            }
+            Err(e) => log::warn!("{}; the key stays in {}", e, path.display()),
+        },
+        None => profile.upload_encryption_key = crypto::stored_key(),
+    }
+    Some(profile)
+}
+
+/// Check and save `profile` to `path`, without its encryption key
+pub fn save_profile(path: &Path, profile: &BackendProfile) -> Result<(), String> {
+    Target::parse(&profile.server_url)?;
+    save_json(path, &Some(profile))
+}
+
+/// [`save_profile`] for the account in use, keeping its encryption key,
+/// or removing a stored one, in the credential store
+pub fn save_active_profile(path: &Path, profile: &BackendProfile) -> Result<(), String> {
+    Target::parse(&profile.server_url)?;
+    match &profile.upload_encryption_key {
+        Some(key) => crypto::store_key(key)?,
+        None => {
+            if let Err(e) = crypto::clear_stored_key() {
+                log::warn!("{}", e);
+            }
+        }
+    }
+    save_profile(path, profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_profiles_leave_out_the_encryption_key() {
+        let path = std::env::temp_dir().join(format!("profile-test-{}.json", std::process::id()));
+        let profile = BackendProfile {
+            name: None,
+            server_url: "http://127.0.0.1:50051".to_string(),
+            tls: false,
+            upload_encryption_key: Some("secret".to_string()),
+        };
+        save_profile(&path, &profile).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(!saved.contains("secret"));
+        assert!(!saved.contains("upload_encryption_key"));
+    }
+
+    #[test]
+    fn server_urls_are_validated() {
+        assert_eq!(
+            host_port("http://192.168.1.10:50051").unwrap(),
+            ("192.168.1.10".to_string(), 50051)
+        );
+        assert_eq!(
+            host_port("https://backend.example.com").unwrap(),
+            ("backend.example.com".to_string(), 443)
+        );
+        assert!(host_port("backend:50051").is_err());
+        assert!(host_port("ftp://backend:21").is_err());
+        assert_eq!(
+            Target::parse("unix:///tmp/analyzer.sock")
+                .unwrap()
+                .to_string(),
+            "Unix socket /tmp/analyzer.sock"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Multi-stage operations (e.g. hash → upload) reported as one unit
+//!
+//! A command that runs several steps begins an [`Operation`] naming its
+//! stages in order, then moves through them. Every change is emitted on
+//! the single `video-analyzer://operation` event with the whole operation,
+//! so the UI can keep one progress card per `id` instead of piecing
+//! together unrelated notifications. Running operations are also listed by
+//! `get_operations`, for a UI that reloads mid-way.
+
+use crate::files::unix_seconds;
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageState {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Stage {
+    pub name: String,
+    pub state: StageState,
+    /// 0.0 to 1.0, for stages that can tell how far along they are
+    pub progress: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Operation {
+    pub id: String,
+    /// What is being done, e.g. "upload" or "export_frames"
+    pub kind: String,
+    pub stages: Vec<Stage>,
+    pub done: bool,
+    pub error: Option<String>,
+    pub started_at: f64,
+}
+
+impl Operation {
+    fn new(id: &str, kind: &str, stages: &[&str]) -> Self {
+        Self {
+            id: id.to_string(),
+            kind: kind.to_string(),
+            stages: stages
+                .iter()
+                .map(|name| Stage {
+                    name: name.to_string(),
+                    state: StageState::Pending,
+                    progress: None,
+                })
+                .collect(),
+            done: false,
+            error: None,
+            started_at: unix_seconds(std::time::SystemTime::now()),
+        }
+    }
+
+    /// Run stage `name`; every stage before it is done by now
+    fn start(&mut self, name: &str) {
+        let Some(index) = self.stages.iter().position(|s| s.name == name) else {
+            return;
+        };
+        for stage in &mut self.stages[..index] {
+            stage.state = StageState::Done;
+            stage.progress = stage.progress.map(|_| 1.0);
+        }
+        self.stages[index].state = StageState::Running;
+    }
+
+    fn set_progress(&mut self, name: &str, fraction: f64) {
+        if let Some(stage) = self.stages.iter_mut().find(|s| s.name == name) {
+            stage.progress = Some(fraction.clamp(0.0, 1.0));
+        }
+    }
+
+    fn finish(&mut self) {
+        for stage in &mut self.stages {
+            stage.state = StageState::Done;
+            stage.progress = stage.progress.map(|_| 1.0);
+        }
+        self.done = true;
+    }
+
+    /// End with `error`, blaming the running stage
+    fn fail(&mut self, error: &str) {
+        for stage in &mut self.stages {
+            if stage.state == StageState::Running {
+                stage.state = StageState::Failed;
+            }
+        }
+        self.done = true;
+        self.error = Some(error.to_string());
+    }
+}
+
+/// Managed state: the operations still running
+#[derive(Debug, Default)]
+pub struct Operations {
+    running: Mutex<Vec<Operation>>,
+}
+
+impl Operations {
+    /// Every running operation, oldest first
+    pub fn list(&self) -> Vec<Operation> {
+        self.running.lock().unwrap().clone()
+    }
+}
+
+/// Begin operation `id` with `stages`, all pending
+pub fn begin<R: Runtime>(
+    app: &AppHandle<R>,
+    id: &str,
+    kind: &str,
+    stages: &[&str],
+) -> OperationHandle<R> {
+    let operation = Operation::new(id, kind, stages);
+    app.state::<Operations>()
+        .running
+        .lock()
+        .unwrap()
+        .push(operation.clone());
+    app.emit(&crate::event_name("operation"), &operation).ok();
+    OperationHandle {
+        app: app.clone(),
+        id: id.to_string(),
+    }
+}
+
+/// Apply `change` to operation `id` and emit the result; a finished one is
+/// dropped from the running list
+fn update<R: Runtime>(app: &AppHandle<R>, id: &str, change: impl FnOnce(&mut Operation)) {
+    let operations = app.state::<Operations>();
+    let mut running = operations.running.lock().unwrap();
+    let Some(index) = running.iter().position(|op| op.id == id) else {
+        return;
+    };
+    change(&mut running[index]);
+    let operation = if running[index].done {
+        running.remove(index)
+    } else {
+        running[index].clone()
+    };
+    drop(running);
+    app.emit(&crate::event_name("operation"), &operation).ok();
+}
+
+/// A running operation; one dropped without `finish` or `fail` is reported
+/// as interrupted
+pub struct OperationHandle<R: Runtime> {
+    app: AppHandle<R>,
+    id: String,
+}
+
+impl<R: Runtime> OperationHandle<R> {
+    pub fn start(&self, stage: &str) {
+        update(&self.app, &self.id, |op| op.start(stage));
+    }
+
+    /// Report progress of `stage` from anywhere, e.g. an upload's reader task
+    pub fn progress_fn(&self, stage: &str) -> impl Fn(f64) + Send + Sync + 'static {
+        let app = self.app.clone();
+        let id = self.id.clone();
+        let stage = stage.to_string();
+        move |fraction| update(&app, &id, |op| op.set_progress(&stage, fraction))
+    }
+
+    pub fn finish(self) {
+        update(&self.app, &self.id, Operation::finish);
+    }
+
+    pub fn fail(self, error: &str) {
+        update(&self.app, &self.id, |op| op.fail(error));
+    }
+
+    /// Pass a step's result through, failing the operation on error
+    pub fn check<T, E: std::fmt::Display>(&self, result: Result<T, E>) -> Result<T, E> {
+        if let Err(error) = &result {
+            update(&self.app, &self.id, |op| op.fail(&error.to_string()));
+        }
+        result
+    }
+}
+
+impl<R: Runtime> Drop for OperationHandle<R> {
+    fn drop(&mut self) {
+        update(&self.app, &self.id, |op| op.fail("Interrupted"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_advance_in_order() {
+        let mut op = Operation::new("u1", "upload", &["hash", "upload"]);
+        op.start("hash");
+        op.set_progress("hash", 0.5);
+        op.start("upload");
+        assert_eq!(op.stages[0].state, StageState::Done);
+        assert_eq!(op.stages[0].progress, Some(1.0));
+        assert_eq!(op.stages[1].state, StageState::Running);
+
+        op.set_progress("upload", 2.0);
+        assert_eq!(op.stages[1].progress, Some(1.0));
+
+        op.fail("Upload cancelled");
+        assert_eq!(op.stages[1].state, StageState::Failed);
+        assert!(op.done);
+        assert_eq!(op.error.as_deref(), Some("Upload cancelled"));
+    }
+}
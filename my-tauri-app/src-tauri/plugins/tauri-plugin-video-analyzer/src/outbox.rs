@@ -0,0 +1,272 @@
+//! Questions asked while the backend was unreachable
+//!
+//! When `process_query` can't reach the backend, the question is queued
+//! in `outbox.json` in the workspace instead of failing, and answered
+//! with a single progress chunk carrying its `outbox_id` so the UI can
+//! show it as pending. Once the health monitor sees the backend ready
+//! again, [`spawn_flush`] sends the queue in the order it was asked:
+//!
+//! - `outbox-sent` with `{ id, video_id, responses }` for each answered one
+//! - `outbox-failed` with `{ id, video_id, error }` for each the backend
+//!   refused, which is dropped from the queue
+//!
+//! If the backend drops out again mid-flush, the rest stays queued, still
+//! in order, for the next time it comes back.
+
+use crate::commands::run_query;
+use crate::error::{AppError, ErrorCode};
+use crate::files;
+use crate::history::{IncompleteResponse, LocalHistory};
+use crate::postprocess;
+use crate::responses::{self, ChatChunk};
+use crate::sequence;
+use crate::shutdown::Shutdown;
+use crate::store::{load_json, save_json};
+use crate::transport::Transport;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// A question waiting for the backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    pub id: String,
+    pub video_id: String,
+    pub query: String,
+    #[serde(default)]
+    pub query_type: String,
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    pub queued_at: f64,
+    /// Flushes that found the backend gone again
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OutboxFile {
+    messages: Vec<OutboxMessage>,
+}
+
+/// Managed state: the queue, oldest first
+pub struct Outbox {
+    path: PathBuf,
+    messages: Mutex<OutboxFile>,
+    /// Held while flushing so two flushes can't send a message twice
+    flushing: tokio::sync::Mutex<()>,
+}
+
+impl Outbox {
+    pub fn load(path: PathBuf) -> Self {
+        Self {
+            messages: Mutex::new(load_json(&path)),
+            path,
+            flushing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Queue a question to `video_id` behind the others
+    pub fn enqueue(
+        &self,
+        video_id: &str,
+        query: String,
+        query_type: String,
+        attachments: Vec<String>,
+    ) -> Result<OutboxMessage, String> {
+        let message = OutboxMessage {
+            id: sequence::new_request_id(),
+            video_id: video_id.to_string(),
+            query,
+            query_type,
+            attachments,
+            queued_at: files::unix_seconds(std::time::SystemTime::now()),
+            attempts: 0,
+            last_error: None,
+        };
+        let mut file = self.messages.lock().unwrap();
+        file.messages.push(message.clone());
+        save_json(&self.path, &*file)?;
+        Ok(message)
+    }
+
+    /// Every queued question, oldest first
+    pub fn list(&self) -> Vec<OutboxMessage> {
+        self.messages.lock().unwrap().messages.clone()
+    }
+
+    /// Drop `id` from the queue; false if it wasn't there
+    pub fn remove(&self, id: &str) -> Result<bool, String> {
+        let mut file = self.messages.lock().unwrap();
+        let before = file.messages.len();
+        file.messages.retain(|message| message.id != id);
+        if file.messages.len() == before {
+            return Ok(false);
+        }
+        save_json(&self.path, &*file)?;
+        Ok(true)
+    }
+
+    /// Drop every question queued for `video_id`; returns how many there were
+    pub fn remove_video(&self, video_id: &str) -> Result<usize, String> {
+        let mut file = self.messages.lock().unwrap();
+        let before = file.messages.len();
+        file.messages.retain(|message| message.video_id != video_id);
+        let removed = before - file.messages.len();
+        if removed > 0 {
+            save_json(&self.path, &*file)?;
+        }
+        Ok(removed)
+    }
+
+    /// Note that sending `id` found the backend gone again
+    fn record_attempt(&self, id: &str, error: &str) -> Result<(), String> {
+        let mut file = self.messages.lock().unwrap();
+        if let Some(message) = file.messages.iter_mut().find(|m| m.id == id) {
+            message.attempts += 1;
+            message.last_error = Some(error.to_string());
+        }
+        save_json(&self.path, &*file)
+    }
+}
+
+/// Whether `error` means the question should wait for the backend rather
+/// than fail
+pub fn should_queue(error: &AppError) -> bool {
+    matches!(
+        error,
+        AppError::Transport {
+            code: ErrorCode::Unavailable,
+            ..
+        }
+    )
+}
+
+/// The chunk `process_query` answers with for a queued question
+pub fn queued_chunk(message: &OutboxMessage) -> ChatChunk {
+    let content = "The backend is unreachable; your question will be sent when it is back";
+    ChatChunk {
+        kind: 1,
+        content: content.to_string(),
+        role: "status".to_string(),
+        plain_text: content.to_string(),
+        outbox_id: Some(message.id.clone()),
+        ..Default::default()
+    }
+}
+
+/// Send the queue, in order, in the background
+pub fn spawn_flush<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        let outbox = app.state::<Outbox>();
+        let _flushing = outbox.flushing.lock().await;
+        for message in outbox.list() {
+            match send(&app, &message).await {
+                Ok(responses) => {
+                    log::info!("Outbox: sent {} to {}", message.id, message.video_id);
+                    app.emit(
+                        &crate::event_name("outbox-sent"),
+                        json!({
+                            "id": message.id,
+                            "video_id": message.video_id,
+                            "responses": responses,
+                        }),
+                    )
+                    .ok();
+                }
+                Err(e) if should_queue(&e) => {
+                    log::info!("Outbox: backend gone again, {} stays queued", message.id);
+                    if let Err(e) = outbox.record_attempt(&message.id, &e.to_string()) {
+                        log::warn!("Outbox: failed to save: {}", e);
+                    }
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("Outbox: {} was refused: {}", message.id, e);
+                    app.emit(
+                        &crate::event_name("outbox-failed"),
+                        json!({
+                            "id": message.id,
+                            "video_id": message.video_id,
+                            "error": e,
+                        }),
+                    )
+                    .ok();
+                }
+            }
+            if let Err(e) = outbox.remove(&message.id) {
+                log::warn!("Outbox: failed to save: {}", e);
+            }
+        }
+    });
+}
+
+/// Ask `message` the way `process_query` would have
+async fn send<R: Runtime>(
+    app: &AppHandle<R>,
+    message: &OutboxMessage,
+) -> Result<Vec<ChatChunk>, AppError> {
+    let now = files::unix_seconds(std::time::SystemTime::now());
+    let pending = IncompleteResponse {
+        request_id: message.id.clone(),
+        query: message.query.clone(),
+        query_type: message.query_type.clone(),
+        attachments: message.attachments.clone(),
+        range: None,
+        started_at: now,
+        updated_at: now,
+        chunks: Vec::new(),
+        error: None,
+    };
+    let responses = run_query(
+        &app.state::<Transport>(),
+        &app.state::<LocalHistory>(),
+        &app.state::<postprocess::Pipeline>(),
+        &app.state::<Shutdown>(),
+        &message.video_id,
+        pending,
+        None,
+        |_| {},
+    )
+    .await?;
+    responses::chat_chunks(responses).map_err(AppError::internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_keeps_its_order_across_restarts() {
+        let path = std::env::temp_dir().join(format!("outbox-test-{}.json", std::process::id()));
+        let outbox = Outbox::load(path.clone());
+        let first = outbox
+            .enqueue(
+                "v1",
+                "what happens first?".into(),
+                String::new(),
+                Vec::new(),
+            )
+            .unwrap();
+        let second = outbox
+            .enqueue("v2", "and then?".into(), "summary".into(), Vec::new())
+            .unwrap();
+        outbox
+            .record_attempt(&first.id, "connection refused")
+            .unwrap();
+
+        let reopened = Outbox::load(path.clone());
+        let queued = reopened.list();
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[0].id, first.id);
+        assert_eq!(queued[0].attempts, 1);
+        assert_eq!(queued[1], second);
+        assert!(reopened.remove(&first.id).unwrap());
+        assert!(!reopened.remove(&first.id).unwrap());
+        assert_eq!(Outbox::load(path.clone()).list(), vec![second]);
+        std::fs::remove_file(&path).ok();
+    }
+}
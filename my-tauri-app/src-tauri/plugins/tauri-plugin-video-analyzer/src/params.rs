@@ -0,0 +1,76 @@
+//! Per-session model parameters
+//!
+//! Power users can override the backend's model, temperature and answer
+//! length for one video's chat. The overrides are the proto
+//! `SessionParams` message, kept in the local history and sent with every
+//! `ChatRequest`; this module checks them before they are stored.
+
+use crate::video_analyzer::SessionParams;
+
+/// Sampling temperature range the backend models accept
+pub const TEMPERATURE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+
+/// Upper bound on `max_tokens`
+pub const MAX_TOKENS_LIMIT: u32 = 32_768;
+
+/// Check overrides before they are stored
+pub fn validate(params: &SessionParams) -> Result<(), String> {
+    if let Some(temperature) = params.temperature {
+        if !TEMPERATURE_RANGE.contains(&temperature) {
+            return Err(format!(
+                "Temperature must be between {} and {}, got {}",
+                TEMPERATURE_RANGE.start(),
+                TEMPERATURE_RANGE.end(),
+                temperature
+            ));
+        }
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        if !(1..=MAX_TOKENS_LIMIT).contains(&max_tokens) {
+            return Err(format!(
+                "Max tokens must be between 1 and {}, got {}",
+                MAX_TOKENS_LIMIT, max_tokens
+            ));
+        }
+    }
+    if !is_model_name(&params.model) {
+        return Err(format!(
+            "Invalid model name '{}': use letters, digits and . _ - : /",
+            params.model
+        ));
+    }
+    Ok(())
+}
+
+/// Empty (backend default), or a name like `llava:13b` or `org/model-v2`
+fn is_model_name(name: &str) -> bool {
+    name.len() <= 128
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._-:/".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert!(validate(&SessionParams::default()).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_out_of_range() {
+        let params = |temperature: f32, max_tokens: u32, model: &str| SessionParams {
+            temperature: Some(temperature),
+            max_tokens: Some(max_tokens),
+            model: model.to_string(),
+        };
+        assert!(validate(&params(0.7, 1024, "llava:13b")).is_ok());
+        assert!(validate(&params(2.5, 1024, "")).is_err());
+        assert!(validate(&params(f32::NAN, 1024, "")).is_err());
+        assert!(validate(&params(0.7, 0, "")).is_err());
+        assert!(validate(&params(0.7, MAX_TOKENS_LIMIT + 1, "")).is_err());
+        assert!(validate(&params(0.7, 1024, "gpt 4")).is_err());
+    }
+}
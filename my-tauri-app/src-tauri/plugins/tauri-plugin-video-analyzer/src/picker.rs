@@ -0,0 +1,207 @@
+//! The desktop's own open-file dialog, limited to videos
+//!
+//! `open_video_picker` runs whichever dialog the platform ships with:
+//! `OpenFileDialog` through PowerShell on Windows, `choose file` through
+//! `osascript` on macOS, and `zenity`, or `kdialog` without it, on Linux.
+//! Each is filtered to [`DEFAULT_EXTENSIONS`] and prints the chosen paths
+//! one per line. Closing the dialog without choosing is not an error; it
+//! just picks nothing.
+//!
+//! On Android and iOS the webview's own file input already goes through
+//! the system picker, so there is no dialog here.
+
+use crate::files;
+use crate::import::DEFAULT_EXTENSIONS;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A picked file, resolved
+#[derive(Debug, Clone, Serialize)]
+pub struct PickedFile {
+    /// Path as the dialog returned it
+    pub path: String,
+    /// Path after resolving symlinks
+    pub canonical_path: String,
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub modified_at: f64,
+}
+
+/// A dialog program and how to run it
+struct Dialog {
+    program: &'static str,
+    args: Vec<String>,
+}
+
+#[cfg(target_os = "windows")]
+fn dialogs(multi: bool) -> Vec<Dialog> {
+    let patterns: Vec<String> = DEFAULT_EXTENSIONS
+        .iter()
+        .map(|e| format!("*.{}", e))
+        .collect();
+    let script = format!(
+        "[Console]::OutputEncoding = [Text.Encoding]::UTF8; \
+         Add-Type -AssemblyName System.Windows.Forms; \
+         $d = New-Object System.Windows.Forms.OpenFileDialog; \
+         $d.Title = 'Choose a video'; \
+         $d.Filter = 'Videos|{}'; \
+         $d.Multiselect = ${}; \
+         if ($d.ShowDialog() -eq 'OK') {{ $d.FileNames -join \"`n\" }}",
+        patterns.join(";"),
+        multi
+    );
+    vec![Dialog {
+        program: "powershell",
+        args: vec![
+            "-NoProfile".into(),
+            "-STA".into(),
+            "-Command".into(),
+            script,
+        ],
+    }]
+}
+
+#[cfg(target_os = "macos")]
+fn dialogs(multi: bool) -> Vec<Dialog> {
+    let types: Vec<String> = DEFAULT_EXTENSIONS
+        .iter()
+        .map(|e| format!("\"{}\"", e))
+        .collect();
+    let script = format!(
+        "set chosen to choose file with prompt \"Choose a video\" of type {{{}}}{}\n\
+         if class of chosen is not list then set chosen to {{chosen}}\n\
+         set out to \"\"\n\
+         repeat with f in chosen\n\
+         set out to out & POSIX path of f & linefeed\n\
+         end repeat\n\
+         return out",
+        types.join(", "),
+        if multi {
+            " with multiple selections allowed"
+        } else {
+            ""
+        }
+    );
+    vec![Dialog {
+        program: "osascript",
+        args: vec!["-e".into(), script],
+    }]
+}
+
+#[cfg(all(unix, desktop, not(target_os = "macos")))]
+fn dialogs(multi: bool) -> Vec<Dialog> {
+    // Neither dialog matches extensions case-insensitively
+    let patterns: Vec<String> = DEFAULT_EXTENSIONS
+        .iter()
+        .flat_map(|e| [format!("*.{}", e), format!("*.{}", e.to_uppercase())])
+        .collect();
+    let mut zenity = vec![
+        "--file-selection".to_string(),
+        "--title=Choose a video".to_string(),
+        format!("--file-filter=Videos | {}", patterns.join(" ")),
+        "--separator=\n".to_string(),
+    ];
+    let mut kdialog = vec![
+        "--title".to_string(),
+        "Choose a video".to_string(),
+        "--getopenfilename".to_string(),
+        ".".to_string(),
+        format!("{}|Videos", patterns.join(" ")),
+    ];
+    if multi {
+        zenity.push("--multiple".to_string());
+        kdialog.extend(["--multiple".to_string(), "--separate-output".to_string()]);
+    }
+    vec![
+        Dialog {
+            program: "zenity",
+            args: zenity,
+        },
+        Dialog {
+            program: "kdialog",
+            args: kdialog,
+        },
+    ]
+}
+
+#[cfg(mobile)]
+fn dialogs(_multi: bool) -> Vec<Dialog> {
+    Vec::new()
+}
+
+/// The paths a dialog printed, one per line
+fn parse_output(stdout: &str) -> Vec<PathBuf> {
+    stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Show the platform's dialog and wait for the paths chosen; empty if it
+/// was closed without choosing
+pub async fn pick(multi: bool) -> Result<Vec<PathBuf>, String> {
+    for dialog in dialogs(multi) {
+        let output = match tokio::process::Command::new(dialog.program)
+            .args(&dialog.args)
+            .kill_on_drop(true)
+            .output()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                log::debug!("open_video_picker: {} is not installed", dialog.program);
+                continue;
+            }
+            Err(e) => return Err(format!("Failed to run {}: {}", dialog.program, e)),
+        };
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if output.status.success() {
+            let mut paths = parse_output(&stdout);
+            paths.truncate(if multi { usize::MAX } else { 1 });
+            return Ok(paths);
+        }
+        // Cancelling exits 1 with nothing printed on every platform
+        if output.status.code() == Some(1) && stdout.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        return Err(format!(
+            "{} failed: {}",
+            dialog.program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Err("No file dialog is available on this system".to_string())
+}
+
+/// Resolve a picked `path` and read its size and modification time
+pub fn describe(path: &Path) -> Result<PickedFile, String> {
+    let canonical = files::resolve_path(path)?.canonical;
+    let metadata = std::fs::metadata(files::paths::fs_path(&canonical))
+        .map_err(|e| format!("Failed to stat {}: {}", canonical.display(), e))?;
+    Ok(PickedFile {
+        path: path.to_string_lossy().into_owned(),
+        canonical_path: canonical.to_string_lossy().into_owned(),
+        file_name: files::FileName::of(&canonical).display,
+        size_bytes: metadata.len(),
+        modified_at: metadata.modified().map(files::unix_seconds).unwrap_or(0.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_is_one_path_per_line() {
+        let paths = parse_output("/videos/a b.mp4\r\n\n/videos/c.mkv\n");
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/videos/a b.mp4"),
+                PathBuf::from("/videos/c.mkv")
+            ]
+        );
+        assert!(parse_output("  \n").is_empty());
+    }
+}
@@ -0,0 +1,263 @@
+//! Post-processing of chat answers before the frontend sees them
+//!
+//! Every chunk of an answer, streamed or returned whole, goes through the
+//! [`Pipeline`] in managed state after it is annotated and before it is
+//! emitted, returned or saved to the local history. The pipeline is the
+//! built-in transforms named in RESPONSE_TRANSFORMS, in that order,
+//! followed by any the host app adds with `Builder::response_transform`.
+//!
+//! Built in:
+//! - `strip-tags`: removes backend-internal tags such as
+//!   `<internal>…</internal>` along with their contents
+//! - `citations`: turns `[cite:1:05]` or `[cite:65-70.5]` markers into
+//!   `[1]`, `[2]`… and lists them as `citations` on the chunk, with times in
+//!   milliseconds
+//! - `max-length`: cuts the text at RESPONSE_MAX_CHARS, marking the chunk
+//!   `truncated`
+//!
+//! Transforms see one chunk at a time, so a tag or marker split across two
+//! chunks is left as it is.
+
+use crate::config;
+use serde_json::Value;
+
+/// One step of the [`Pipeline`]
+pub trait Transform: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Rewrite `chunk`, a `ChatResponse` as sent to the frontend
+    fn apply(&self, chunk: &mut Value);
+}
+
+/// Managed state: the transforms applied to every answer, in order
+#[derive(Default)]
+pub struct Pipeline {
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Pipeline {
+    /// The built-in transforms from the config, then `extra`
+    pub fn from_config(extra: Vec<Box<dyn Transform>>) -> Self {
+        let config = config::get();
+        let mut transforms: Vec<Box<dyn Transform>> = Vec::new();
+        for name in &config.app.response_transforms {
+            match name.as_str() {
+                "strip-tags" => transforms.push(Box::new(StripTags::new(
+                    config.app.response_strip_tags.clone(),
+                ))),
+                "citations" => transforms.push(Box::new(Citations)),
+                "max-length" => transforms.push(Box::new(MaxLength(config.app.response_max_chars))),
+                other => log::warn!("Ignoring unknown response transform '{}'", other),
+            }
+        }
+        transforms.extend(extra);
+        Self { transforms }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.transforms.iter().map(|t| t.name()).collect()
+    }
+
+    /// Run every transform over each chunk in `chunks`
+    pub fn apply(&self, chunks: &mut [Value]) {
+        for chunk in chunks {
+            for transform in &self.transforms {
+                transform.apply(chunk);
+            }
+        }
+    }
+}
+
+/// The chunk's text, if it has any
+fn content(chunk: &mut Value) -> Option<&mut String> {
+    match chunk.get_mut("content") {
+        Some(Value::String(content)) if !content.is_empty() => Some(content),
+        _ => None,
+    }
+}
+
+/// Removes `<tag>…</tag>` spans for each of its tags; an unclosed tag
+/// hides the rest of the chunk
+pub struct StripTags {
+    tags: Vec<String>,
+}
+
+impl StripTags {
+    pub fn new(tags: Vec<String>) -> Self {
+        Self { tags }
+    }
+
+    fn strip(text: &str, tag: &str) -> String {
+        let open = format!("<{}", tag);
+        let close = format!("</{}>", tag);
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find(&open) {
+            // `<internal>` or `<internal attr="…">`, not `<internals>`
+            let after = &rest[start + open.len()..];
+            if !after.starts_with(['>', ' ', '/']) {
+                out.push_str(&rest[..start + open.len()]);
+                rest = after;
+                continue;
+            }
+            out.push_str(&rest[..start]);
+            rest = match after.find(&close) {
+                Some(end) => &after[end + close.len()..],
+                None => "",
+            };
+        }
+        out.push_str(rest);
+        out.replace(&close, "")
+    }
+}
+
+impl Transform for StripTags {
+    fn name(&self) -> &str {
+        "strip-tags"
+    }
+
+    fn apply(&self, chunk: &mut Value) {
+        if let Some(content) = content(chunk) {
+            for tag in &self.tags {
+                if content.contains(&format!("<{}", tag))
+                    || content.contains(&format!("</{}>", tag))
+                {
+                    *content = Self::strip(content, tag);
+                }
+            }
+        }
+    }
+}
+
+const CITATION_MARKER: &str = "[cite:";
+
+/// Milliseconds in `12.5`, `1:05` or `1:02:03.5`
+fn parse_time_ms(value: &str) -> Option<i64> {
+    let mut seconds = 0.0;
+    for part in value.trim().split(':') {
+        let part: f64 = part.parse().ok().filter(|p: &f64| *p >= 0.0)?;
+        seconds = seconds * 60.0 + part;
+    }
+    Some((seconds * 1000.0).round() as i64)
+}
+
+/// Turns `[cite:<time>]` and `[cite:<start>-<end>]` markers into numbered
+/// references listed in the chunk's `citations`
+pub struct Citations;
+
+impl Transform for Citations {
+    fn name(&self) -> &str {
+        "citations"
+    }
+
+    fn apply(&self, chunk: &mut Value) {
+        let mut citations = Vec::new();
+        let Some(content) = content(chunk) else {
+            return;
+        };
+        if !content.contains(CITATION_MARKER) {
+            return;
+        }
+        let mut out = String::with_capacity(content.len());
+        let mut rest = content.as_str();
+        while let Some(start) = rest.find(CITATION_MARKER) {
+            let body_start = start + CITATION_MARKER.len();
+            let parsed = rest[body_start..].find(']').and_then(|len| {
+                let body = &rest[body_start..body_start + len];
+                let (from, to) = match body.split_once('-') {
+                    Some((from, to)) => (from, Some(to)),
+                    None => (body, None),
+                };
+                let start_ms = parse_time_ms(from)?;
+                let end_ms = match to {
+                    Some(to) => Some(parse_time_ms(to)?),
+                    None => None,
+                };
+                Some((len, start_ms, end_ms))
+            });
+            let Some((len, start_ms, end_ms)) = parsed else {
+                // Not a marker we understand; keep it as text
+                out.push_str(&rest[..body_start]);
+                rest = &rest[body_start..];
+                continue;
+            };
+            citations.push(serde_json::json!({
+                "index": citations.len() + 1,
+                "start_ms": start_ms,
+                "end_ms": end_ms,
+            }));
+            out.push_str(&rest[..start]);
+            out.push_str(&format!("[{}]", citations.len()));
+            rest = &rest[body_start + len + 1..];
+        }
+        out.push_str(rest);
+        *content = out;
+        if !citations.is_empty() {
+            chunk["citations"] = Value::Array(citations);
+        }
+    }
+}
+
+/// Cuts text longer than its limit in characters
+pub struct MaxLength(pub usize);
+
+impl Transform for MaxLength {
+    fn name(&self) -> &str {
+        "max-length"
+    }
+
+    fn apply(&self, chunk: &mut Value) {
+        let Some(content) = content(chunk) else {
+            return;
+        };
+        if let Some((cut, _)) = content.char_indices().nth(self.0) {
+            content.truncate(cut);
+            content.push('…');
+            chunk["truncated"] = Value::Bool(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(content: &str) -> Value {
+        serde_json::json!({ "type": 0, "content": content })
+    }
+
+    #[test]
+    fn transforms_compose_in_order() {
+        let pipeline = Pipeline {
+            transforms: vec![
+                Box::new(StripTags::new(vec!["internal".to_string()])),
+                Box::new(Citations),
+                Box::new(MaxLength(24)),
+            ],
+        };
+        let mut chunks = [chunk(
+            "A dog <internal>agent=vision</internal>runs [cite:1:05-1:10.5] then [cite:90] sits down",
+        )];
+        pipeline.apply(&mut chunks);
+        assert_eq!(chunks[0]["content"], "A dog runs [1] then [2] …");
+        assert_eq!(chunks[0]["truncated"], true);
+        assert_eq!(chunks[0]["citations"][0]["start_ms"], 65_000);
+        assert_eq!(chunks[0]["citations"][0]["end_ms"], 70_500);
+        assert_eq!(chunks[0]["citations"][1]["start_ms"], 90_000);
+        assert!(chunks[0]["citations"][1]["end_ms"].is_null());
+    }
+
+    #[test]
+    fn unrecognised_markup_is_left_alone() {
+        let pipeline = Pipeline {
+            transforms: vec![
+                Box::new(StripTags::new(vec!["internal".to_string()])),
+                Box::new(Citations),
+            ],
+        };
+        let mut chunks = [chunk("<internals> [cite:soon] <internal>hidden")];
+        pipeline.apply(&mut chunks);
+        assert_eq!(chunks[0]["content"], "<internals> [cite:soon] ");
+        assert!(chunks[0].get("citations").is_none());
+    }
+}
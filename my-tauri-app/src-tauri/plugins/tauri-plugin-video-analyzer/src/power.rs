@@ -0,0 +1,287 @@
+//! Power-saving mode
+//!
+//! On a laptop running on battery the background work backs off: the
+//! staleness checker (which may hash whole files) pauses, the file
+//! watcher and the backend health monitor poll less often, batch uploads
+//! send one file at a time and conversions before an upload wait until
+//! saving ends.
+//! The mode is a user setting persisted in
+//! `power.json`:
+//!
+//! - `auto` (default): save power while the machine is on battery
+//! - `on`: always save power
+//! - `off`: never save power
+//!
+//! The power source is polled by [`spawn_monitor`], which emits
+//! `video-analyzer://power-saving-changed` whenever saving turns on or off.
+
+use crate::config;
+use crate::store::{load_json, save_json};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// While saving power the file watcher only checks on every Nth tick
+pub const WATCH_SLOWDOWN: u64 = 6;
+
+/// Likewise for the backend health monitor
+pub const HEALTH_SLOWDOWN: u64 = 4;
+
+/// How often work held back while saving power checks whether it can go on
+pub const RESUME_CHECK: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerMode {
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl PowerMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "on" | "true" => Ok(Self::On),
+            "off" | "false" => Ok(Self::Off),
+            other => Err(format!(
+                "Unknown power saving mode '{}' (expected auto, on or off)",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    Unknown,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Settings {
+    mode: PowerMode,
+}
+
+/// Managed state: the user's mode and the last seen power source
+pub struct PowerState {
+    path: PathBuf,
+    settings: Mutex<Settings>,
+    source: Mutex<PowerSource>,
+}
+
+impl PowerState {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        Self {
+            path,
+            settings: Mutex::new(settings),
+            source: Mutex::new(PowerSource::Unknown),
+        }
+    }
+
+    pub fn set_mode(&self, mode: PowerMode) -> Result<(), String> {
+        let mut settings = self.settings.lock().unwrap();
+        settings.mode = mode;
+        save_json(&self.path, &*settings)
+    }
+
+    pub fn set_source(&self, source: PowerSource) {
+        *self.source.lock().unwrap() = source;
+    }
+
+    pub fn is_saving(&self) -> bool {
+        is_saving(
+            self.settings.lock().unwrap().mode,
+            *self.source.lock().unwrap(),
+        )
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "mode": self.settings.lock().unwrap().mode,
+            "source": *self.source.lock().unwrap(),
+            "saving": self.is_saving(),
+        })
+    }
+}
+
+fn is_saving(mode: PowerMode, source: PowerSource) -> bool {
+    match mode {
+        PowerMode::On => true,
+        PowerMode::Off => false,
+        PowerMode::Auto => source == PowerSource::Battery,
+    }
+}
+
+/// Current power source, `Unknown` where it can't be determined
+pub fn read_source() -> PowerSource {
+    #[cfg(target_os = "linux")]
+    {
+        linux_source()
+    }
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .map(|out| parse_pmset(&String::from_utf8_lossy(&out.stdout)))
+            .unwrap_or(PowerSource::Unknown)
+    }
+    #[cfg(windows)]
+    {
+        windows_source()
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    {
+        // Mobile OSes manage power themselves
+        PowerSource::Unknown
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_source() -> PowerSource {
+    let read = |dir: &std::path::Path, name: &str| {
+        std::fs::read_to_string(dir.join(name))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default()
+    };
+    let supplies: Vec<(String, String, String)> = std::fs::read_dir("/sys/class/power_supply")
+        .map(|dir| {
+            dir.flatten()
+                .map(|e| {
+                    let path = e.path();
+                    (
+                        read(&path, "type"),
+                        read(&path, "online"),
+                        read(&path, "status"),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    classify_linux(&supplies)
+}
+
+/// Decide from `(type, online, status)` of each entry in /sys/class/power_supply
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn classify_linux(supplies: &[(String, String, String)]) -> PowerSource {
+    let mains_online = supplies
+        .iter()
+        .any(|(kind, online, _)| kind == "Mains" && online == "1");
+    let discharging = supplies
+        .iter()
+        .any(|(kind, _, status)| kind == "Battery" && status == "Discharging");
+    if mains_online {
+        PowerSource::Ac
+    } else if discharging {
+        PowerSource::Battery
+    } else if supplies.iter().any(|(kind, _, _)| kind == "Battery") {
+        // Full or charging without a Mains entry reporting
+        PowerSource::Ac
+    } else {
+        PowerSource::Unknown
+    }
+}
+
+/// First line of `pmset -g batt`: "Now drawing from 'Battery Power'"
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> PowerSource {
+    let first = output.lines().next().unwrap_or_default();
+    if first.contains("'AC Power'") {
+        PowerSource::Ac
+    } else if first.contains("'Battery Power'") {
+        PowerSource::Battery
+    } else {
+        PowerSource::Unknown
+    }
+}
+
+#[cfg(windows)]
+fn windows_source() -> PowerSource {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return PowerSource::Unknown;
+    }
+    match status.ACLineStatus {
+        0 => PowerSource::Battery,
+        1 => PowerSource::Ac,
+        _ => PowerSource::Unknown,
+    }
+}
+
+/// Poll the power source and emit `power-saving-changed` on transitions
+pub fn spawn_monitor<R: Runtime>(app: AppHandle<R>) {
+    let interval_secs = config::get().app.power_check_interval_secs;
+    if interval_secs == 0 {
+        log::info!("Power monitor disabled");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            let source = tokio::task::spawn_blocking(read_source)
+                .await
+                .unwrap_or(PowerSource::Unknown);
+            let power = app.state::<PowerState>();
+            let was_saving = power.is_saving();
+            power.set_source(source);
+            if power.is_saving() != was_saving {
+                log::info!("Power saving {} ({:?})", power.is_saving(), source);
+                app.emit(&crate::event_name("power-saving-changed"), power.to_json())
+                    .ok();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn supply(kind: &str, online: &str, status: &str) -> (String, String, String) {
+        (kind.to_string(), online.to_string(), status.to_string())
+    }
+
+    #[test]
+    fn test_classify_linux() {
+        let laptop_unplugged = [
+            supply("Mains", "0", ""),
+            supply("Battery", "", "Discharging"),
+        ];
+        assert_eq!(classify_linux(&laptop_unplugged), PowerSource::Battery);
+        let laptop_plugged = [supply("Mains", "1", ""), supply("Battery", "", "Charging")];
+        assert_eq!(classify_linux(&laptop_plugged), PowerSource::Ac);
+        assert_eq!(classify_linux(&[]), PowerSource::Unknown);
+    }
+
+    #[test]
+    fn test_parse_pmset() {
+        let out = "Now drawing from 'Battery Power'\n -InternalBattery-0\t85%; discharging";
+        assert_eq!(parse_pmset(out), PowerSource::Battery);
+        assert_eq!(
+            parse_pmset("Now drawing from 'AC Power'\n"),
+            PowerSource::Ac
+        );
+        assert_eq!(parse_pmset(""), PowerSource::Unknown);
+    }
+
+    #[test]
+    fn test_mode_decides_saving() {
+        assert!(is_saving(PowerMode::Auto, PowerSource::Battery));
+        assert!(!is_saving(PowerMode::Auto, PowerSource::Unknown));
+        assert!(is_saving(PowerMode::On, PowerSource::Ac));
+        assert!(!is_saving(PowerMode::Off, PowerSource::Battery));
+        assert_eq!(PowerMode::parse(" On ").unwrap(), PowerMode::On);
+        assert!(PowerMode::parse("sometimes").is_err());
+    }
+}
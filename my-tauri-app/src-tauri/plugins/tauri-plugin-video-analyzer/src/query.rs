@@ -0,0 +1,122 @@
+//! Query modes for `process_query`
+//!
+//! The backend routes a chat request to different agents depending on its
+//! mode, and each mode produces a different `result_json` shape. The mode
+//! travels in `ChatRequest.query_type`; the client uses it to know which
+//! top-level key to expect in RESULT chunks.
+//!
+//! A question can also be limited to part of the video with a
+//! [`TimeRange`], which the backend enforces.
+
+use crate::video_analyzer::chat_request::QueryType;
+use crate::video_analyzer::TimeRange;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QueryMode {
+    Summary,
+    Search,
+    Timeline,
+    ObjectDetection,
+    FreeForm,
+}
+
+impl QueryMode {
+    /// Parse the mode sent by the frontend; empty and "custom" mean free-form
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "summary" => Ok(Self::Summary),
+            "search" => Ok(Self::Search),
+            "timeline" => Ok(Self::Timeline),
+            "object-detection" | "detection" | "detections" => Ok(Self::ObjectDetection),
+            "free-form" | "freeform" | "custom" | "" => Ok(Self::FreeForm),
+            other => Err(format!(
+                "Unknown query_type '{}': expected summary, search, timeline, object-detection or free-form",
+                other
+            )),
+        }
+    }
+
+    pub fn to_proto(self) -> QueryType {
+        match self {
+            Self::Summary => QueryType::Summary,
+            Self::Search => QueryType::Search,
+            Self::Timeline => QueryType::Timeline,
+            Self::ObjectDetection => QueryType::ObjectDetection,
+            Self::FreeForm => QueryType::FreeForm,
+        }
+    }
+
+    /// Top-level key a RESULT chunk's `result_json` must have in this mode
+    pub fn expected_result_key(self) -> Option<&'static str> {
+        match self {
+            Self::Summary => Some("summary"),
+            Self::Search => Some("matches"),
+            Self::Timeline => Some("timeline"),
+            Self::ObjectDetection => Some("detections"),
+            Self::FreeForm => None,
+        }
+    }
+
+    /// Whether `result_json` has the shape this mode expects
+    pub fn result_matches(self, result_json: &str) -> bool {
+        match self.expected_result_key() {
+            None => true,
+            Some(key) => serde_json::from_str::<serde_json::Value>(result_json)
+                .map(|v| v.get(key).is_some())
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Check the window of a range-scoped question
+pub fn parse_range(start_ms: i64, end_ms: i64) -> Result<TimeRange, String> {
+    if start_ms < 0 {
+        return Err(format!(
+            "Range start must not be negative, got {} ms",
+            start_ms
+        ));
+    }
+    if end_ms <= start_ms {
+        return Err(format!(
+            "Range end ({} ms) must be after its start ({} ms)",
+            end_ms, start_ms
+        ));
+    }
+    Ok(TimeRange { start_ms, end_ms })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_aliases() {
+        assert_eq!(QueryMode::parse("custom").unwrap(), QueryMode::FreeForm);
+        assert_eq!(QueryMode::parse("").unwrap(), QueryMode::FreeForm);
+        assert_eq!(
+            QueryMode::parse("object_detection").unwrap(),
+            QueryMode::ObjectDetection
+        );
+        assert_eq!(QueryMode::parse("Timeline").unwrap(), QueryMode::Timeline);
+        assert!(QueryMode::parse("translate").is_err());
+    }
+
+    #[test]
+    fn test_result_matches() {
+        let mode = QueryMode::ObjectDetection;
+        assert!(mode.result_matches(r#"{"detections": []}"#));
+        assert!(!mode.result_matches(r#"{"summary": "..."}"#));
+        assert!(!mode.result_matches("not json"));
+        assert!(QueryMode::FreeForm.result_matches("anything"));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        let range = parse_range(600_000, 750_000).unwrap();
+        assert_eq!((range.start_ms, range.end_ms), (600_000, 750_000));
+        assert!(parse_range(-1, 1000).is_err());
+        assert!(parse_range(5000, 5000).is_err());
+    }
+}
@@ -0,0 +1,365 @@
+//! Answers to questions already asked, kept in memory
+//!
+//! Asking the same thing about the same video again would re-run the
+//! backend's whole analysis. `run_query` looks answers up here first, keyed
+//! by the URL of the transport in use, the video and its content hash, the
+//! question with case, spacing and trailing punctuation normalized, the
+//! query mode, the range, and the analysis options and model parameters in
+//! effect. Hits are served at once with every chunk marked `cached`.
+//!
+//! Answers expire after QUERY_CACHE_TTL_SECS (0 turns the cache off).
+//! Questions with attachments are never cached, and interrupted answers
+//! are never stored. The chat commands' `bypass_cache` skips the lookup
+//! but still stores the fresh answer. Clearing a video's chat history,
+//! deleting, re-registering or relinking the video drops its answers;
+//! switching to another backend drops them all.
+
+use crate::config;
+use crate::history::{IncompleteResponse, LocalHistory};
+use crate::library::Library;
+use crate::video_analyzer::{AnalysisOptions, SessionParams};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Answers kept at most; the oldest go first
+const MAX_ENTRIES: usize = 200;
+
+struct CachedAnswer {
+    video_id: String,
+    stored: Instant,
+    /// Unix seconds, reported as `cached_at`
+    stored_at: f64,
+    responses: Value,
+}
+
+/// Managed state: recent answers by [`key`]
+#[derive(Default)]
+pub struct QueryCache {
+    answers: Mutex<HashMap<String, CachedAnswer>>,
+}
+
+/// Lower case, single spaces, no trailing `?`, `.` or `!`
+fn normalize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .trim_end_matches(['?', '.', '!', ' '])
+        .to_string()
+}
+
+/// The cache key for `pending` about `video_id`, whose content hashes to
+/// `sha256`, asked of the backend at `endpoint`; `None` when the question
+/// can't be cached
+pub fn key(
+    endpoint: &str,
+    video_id: &str,
+    sha256: &str,
+    pending: &IncompleteResponse,
+    options: Option<&AnalysisOptions>,
+    params: Option<&SessionParams>,
+) -> Option<String> {
+    if !pending.attachments.is_empty() {
+        return None;
+    }
+    let key = serde_json::json!({
+        "endpoint": endpoint,
+        "video_id": video_id,
+        "sha256": sha256,
+        "query": normalize(&pending.query),
+        "query_type": pending.query_type.trim().to_lowercase(),
+        "range": pending.range,
+        "options": options,
+        "params": params,
+    });
+    Some(key.to_string())
+}
+
+impl QueryCache {
+    /// How `pending` about `video_id`, asked of the backend at `endpoint`,
+    /// uses the cache, with the analysis options and model parameters
+    /// currently set for the video
+    pub fn lookup<'a>(
+        &'a self,
+        endpoint: &str,
+        library: &Library,
+        history: &LocalHistory,
+        video_id: &str,
+        pending: &IncompleteResponse,
+        bypass: bool,
+    ) -> Lookup<'a> {
+        let entry = library.get(video_id);
+        let sha256 = entry
+            .as_ref()
+            .map(|e| e.sha256.as_str())
+            .unwrap_or_default();
+        let options = entry.as_ref().and_then(|e| e.analysis_options.as_ref());
+        let params = history.session_params(video_id);
+        Lookup {
+            cache: self,
+            video_id: video_id.to_string(),
+            key: key(
+                endpoint,
+                video_id,
+                sha256,
+                pending,
+                options,
+                params.as_ref(),
+            ),
+            bypass,
+        }
+    }
+
+    fn ttl() -> Duration {
+        Duration::from_secs(config::get().app.query_cache_ttl_secs)
+    }
+
+    /// The stored answer for `key`, with each chunk marked `cached`
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.get_within(key, Self::ttl(), Instant::now())
+    }
+
+    fn get_within(&self, key: &str, ttl: Duration, now: Instant) -> Option<Value> {
+        let mut answers = self.answers.lock().unwrap();
+        let answer = answers.get(key)?;
+        if now.duration_since(answer.stored) >= ttl {
+            answers.remove(key);
+            return None;
+        }
+        let mut responses = answer.responses.clone();
+        for chunk in responses.as_array_mut().into_iter().flatten() {
+            chunk["cached"] = Value::Bool(true);
+            chunk["cached_at"] = answer.stored_at.into();
+        }
+        Some(responses)
+    }
+
+    /// Keep `responses` as the answer for `key` about `video_id`
+    pub fn put(&self, video_id: &str, key: String, responses: &Value) {
+        if Self::ttl().is_zero() {
+            return;
+        }
+        let mut answers = self.answers.lock().unwrap();
+        if answers.len() >= MAX_ENTRIES && !answers.contains_key(&key) {
+            let oldest = answers
+                .iter()
+                .min_by_key(|(_, answer)| answer.stored)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                answers.remove(&oldest);
+            }
+        }
+        answers.insert(
+            key,
+            CachedAnswer {
+                video_id: video_id.to_string(),
+                stored: Instant::now(),
+                stored_at: crate::files::unix_seconds(SystemTime::now()),
+                responses: responses.clone(),
+            },
+        );
+    }
+
+    /// Forget every answer about `video_id`
+    pub fn clear_video(&self, video_id: &str) {
+        self.answers
+            .lock()
+            .unwrap()
+            .retain(|_, answer| answer.video_id != video_id);
+    }
+
+    /// Forget every answer, e.g. after switching to another backend
+    pub fn clear(&self) {
+        self.answers.lock().unwrap().clear();
+    }
+}
+
+/// How one question uses the cache: where to look, under which key, and
+/// whether to skip the lookup
+pub struct Lookup<'a> {
+    pub cache: &'a QueryCache,
+    pub video_id: String,
+    pub key: Option<String>,
+    pub bypass: bool,
+}
+
+impl Lookup<'_> {
+    /// The stored answer, unless bypassed or uncacheable
+    pub fn get(&self) -> Option<Value> {
+        match &self.key {
+            Some(key) if !self.bypass => self.cache.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn put(&self, responses: &Value) {
+        if let Some(key) = &self.key {
+            self.cache.put(&self.video_id, key.clone(), responses);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "http://127.0.0.1:50051";
+
+    fn pending(query: &str) -> IncompleteResponse {
+        IncompleteResponse {
+            request_id: "r1".to_string(),
+            query: query.to_string(),
+            query_type: "summary".to_string(),
+            attachments: Vec::new(),
+            range: None,
+            started_at: 0.0,
+            updated_at: 0.0,
+            chunks: Vec::new(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn same_question_same_key() {
+        let a = key(
+            URL,
+            "v1",
+            "aa",
+            &pending("What happens  at the end?"),
+            None,
+            None,
+        );
+        let b = key(
+            URL,
+            "v1",
+            "aa",
+            &pending(" what happens at the END "),
+            None,
+            None,
+        );
+        assert!(a.is_some());
+        assert_eq!(a, b);
+        assert_ne!(
+            a,
+            key(
+                URL,
+                "v2",
+                "aa",
+                &pending("What happens at the end?"),
+                None,
+                None
+            )
+        );
+        // Changed content under the same id, or another backend
+        assert_ne!(
+            a,
+            key(
+                URL,
+                "v1",
+                "bb",
+                &pending("What happens at the end?"),
+                None,
+                None
+            )
+        );
+        assert_ne!(
+            a,
+            key(
+                "http://10.0.0.2:50051",
+                "v1",
+                "aa",
+                &pending("What happens at the end?"),
+                None,
+                None
+            )
+        );
+
+        let options = AnalysisOptions {
+            language: "de".to_string(),
+            ..Default::default()
+        };
+        assert_ne!(
+            a,
+            key(
+                URL,
+                "v1",
+                "aa",
+                &pending("What happens at the end?"),
+                Some(&options),
+                None
+            )
+        );
+
+        let mut attached = pending("What is this?");
+        attached.attachments = vec!["/tmp/photo.jpg".to_string()];
+        assert_eq!(key(URL, "v1", "aa", &attached, None, None), None);
+    }
+
+    #[test]
+    fn transports_key_apart() {
+        use crate::transport::grpc::GrpcTransport;
+        use crate::transport::rest::RestTransport;
+        use crate::transport::VideoAnalyzerTransport;
+
+        let grpc = GrpcTransport::new(Default::default());
+        let question = pending("What happens at the end?");
+        assert_ne!(grpc.endpoint(), RestTransport.endpoint());
+        assert_ne!(
+            key(&grpc.endpoint(), "v1", "aa", &question, None, None),
+            key(&RestTransport.endpoint(), "v1", "aa", &question, None, None)
+        );
+    }
+
+    #[test]
+    fn answers_expire() {
+        let cache = QueryCache::default();
+        let key = key(URL, "v1", "aa", &pending("Who is there?"), None, None).unwrap();
+        cache.answers.lock().unwrap().insert(
+            key.clone(),
+            CachedAnswer {
+                video_id: "v1".to_string(),
+                stored: Instant::now(),
+                stored_at: 100.0,
+                responses: serde_json::json!([{ "type": 3, "content": "Two people" }]),
+            },
+        );
+        let ttl = Duration::from_secs(60);
+
+        let hit = cache.get_within(&key, ttl, Instant::now()).unwrap();
+        assert_eq!(hit[0]["cached"], true);
+        assert_eq!(hit[0]["cached_at"], 100.0);
+
+        let later = Instant::now() + Duration::from_secs(61);
+        assert!(cache.get_within(&key, ttl, later).is_none());
+        assert!(cache.answers.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn clearing_a_video_keeps_the_others() {
+        let cache = QueryCache::default();
+        let answer = serde_json::json!([{ "type": 3, "content": "Two people" }]);
+        for video_id in ["v1", "v2"] {
+            let key = key(URL, video_id, "aa", &pending("Who is there?"), None, None).unwrap();
+            cache.answers.lock().unwrap().insert(
+                key,
+                CachedAnswer {
+                    video_id: video_id.to_string(),
+                    stored: Instant::now(),
+                    stored_at: 100.0,
+                    responses: answer.clone(),
+                },
+            );
+        }
+        cache.clear_video("v1");
+        {
+            let answers = cache.answers.lock().unwrap();
+            assert_eq!(answers.len(), 1);
+            assert!(answers.values().all(|answer| answer.video_id == "v2"));
+        }
+        cache.clear();
+        assert!(cache.answers.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+//! Client-side view of the backend storage quota
+//!
+//! The backend reports its quota alongside storage usage. We keep the last
+//! report in managed state and adjust it as uploads and cleanups happen, so
+//! an upload that cannot fit is refused before any bytes are streamed.
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Warn once usage crosses this fraction of the quota
+const NEAR_LIMIT_RATIO: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct QuotaSnapshot {
+    /// 0 when the backend does not enforce a quota
+    pub quota_bytes: u64,
+    pub used_bytes: u64,
+    /// When the backend last reported usage (Unix seconds)
+    pub updated_at: f64,
+}
+
+impl QuotaSnapshot {
+    pub fn has_quota(&self) -> bool {
+        self.quota_bytes > 0
+    }
+
+    pub fn remaining_bytes(&self) -> u64 {
+        self.quota_bytes.saturating_sub(self.used_bytes)
+    }
+
+    /// Error message if storing `bytes` more would exceed the quota
+    pub fn check(&self, bytes: u64) -> Result<(), String> {
+        if self.has_quota() && bytes > self.remaining_bytes() {
+            return Err(format!(
+                "Not enough backend storage: need {} bytes, {} of {} bytes remaining",
+                bytes,
+                self.remaining_bytes(),
+                self.quota_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn is_near_limit(&self) -> bool {
+        self.has_quota() && self.used_bytes as f64 >= self.quota_bytes as f64 * NEAR_LIMIT_RATIO
+    }
+
+    pub fn to_json(self, stale: bool) -> serde_json::Value {
+        let percent_used = if self.has_quota() {
+            self.used_bytes as f64 / self.quota_bytes as f64 * 100.0
+        } else {
+            0.0
+        };
+        serde_json::json!({
+            "has_quota": self.has_quota(),
+            "quota_bytes": self.quota_bytes,
+            "used_bytes": self.used_bytes,
+            "remaining_bytes": self.remaining_bytes(),
+            "percent_used": percent_used,
+            "near_limit": self.is_near_limit(),
+            "updated_at": self.updated_at,
+            "stale": stale,
+        })
+    }
+}
+
+/// Managed state holding the last known quota, `None` until first reported
+#[derive(Default)]
+pub struct QuotaTracker {
+    snapshot: Mutex<Option<QuotaSnapshot>>,
+}
+
+impl QuotaTracker {
+    pub fn get(&self) -> Option<QuotaSnapshot> {
+        *self.snapshot.lock().unwrap()
+    }
+
+    /// Replace with a fresh backend report
+    pub fn set(&self, quota_bytes: i64, used_bytes: i64) {
+        *self.snapshot.lock().unwrap() = Some(QuotaSnapshot {
+            quota_bytes: quota_bytes.max(0) as u64,
+            used_bytes: used_bytes.max(0) as u64,
+            updated_at: crate::files::unix_seconds(std::time::SystemTime::now()),
+        });
+    }
+
+    /// Pre-flight check; passes when no quota has been reported yet
+    pub fn check(&self, bytes: u64) -> Result<(), String> {
+        self.get().map(|s| s.check(bytes)).unwrap_or(Ok(()))
+    }
+
+    /// Account for bytes stored (positive) or freed (negative) since the last report
+    pub fn adjust(&self, delta_bytes: i64) {
+        if let Some(s) = self.snapshot.lock().unwrap().as_mut() {
+            s.used_bytes = s.used_bytes.saturating_add_signed(delta_bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_against_quota() {
+        let tracker = QuotaTracker::default();
+        assert!(tracker.check(u64::MAX).is_ok());
+
+        tracker.set(1000, 850);
+        assert!(tracker.check(150).is_ok());
+        assert!(tracker.check(151).is_err());
+
+        tracker.adjust(100);
+        assert!(tracker.get().unwrap().is_near_limit());
+        tracker.adjust(-900);
+        assert_eq!(tracker.get().unwrap().used_bytes, 50);
+    }
+
+    #[test]
+    fn test_no_quota_never_blocks() {
+        let tracker = QuotaTracker::default();
+        tracker.set(0, 1 << 40);
+        assert!(tracker.check(1 << 40).is_ok());
+        assert!(!tracker.get().unwrap().is_near_limit());
+    }
+}
@@ -0,0 +1,239 @@
+//! Typed responses of the commands the frontend leans on most
+//!
+//! Most commands still answer with loose JSON; these shapes are fixed so
+//! the frontend doesn't have to guess them. Each derives [`specta::Type`],
+//! and this module's tests generate the app's `src/bindings/videoAnalyzer.ts`
+//! from them: change a struct here and the test fails until the file is
+//! regenerated with `UPDATE_BINDINGS=1`.
+
+use crate::media::language::DetectedLanguage;
+use crate::video_analyzer::{
+    GetChatHistoryResponse, LastSessionResponse, ResumeResponse, UploadResponse,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use specta::Type;
+
+/// Answer of `upload_video`, `upload_video_from_path` and `resume_upload`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Type)]
+pub struct UploadResult {
+    /// The video's id on the backend; empty if the upload failed
+    pub file_id: String,
+    pub success: bool,
+    pub message: String,
+    /// Id of the upload for `upload://progress` and `cancel_upload`; not
+    /// set for `upload_video`, which can't be followed or cancelled
+    pub upload_id: Option<String>,
+    /// Problems that didn't stop the upload, e.g. a nearly full quota
+    pub warnings: Vec<String>,
+    /// Spoken language found in the audio, when it was checked
+    pub detected_language: Option<DetectedLanguage>,
+    /// The file changed while it was read, so the copy may be incomplete
+    pub changed_during_upload: bool,
+    /// Whether the video was added to the local library; not when it
+    /// changed during the upload
+    pub tracked: bool,
+    /// Chunk `resume_upload` carried on from; not set when it started over
+    pub resumed_from_chunk: Option<i32>,
+    /// The backend already had a file with the same content, so nothing
+    /// was sent and `file_id` is that video
+    pub already_uploaded: bool,
+}
+
+impl From<UploadResponse> for UploadResult {
+    fn from(response: UploadResponse) -> Self {
+        Self {
+            file_id: response.file_id,
+            success: response.success,
+            message: response.message,
+            ..Default::default()
+        }
+    }
+}
+
+/// Answer of `get_last_session` and `resume_session`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct SessionInfo {
+    /// Whether there is a session to go back to, or it was resumed
+    pub has_session: bool,
+    pub video_id: String,
+    pub video_name: String,
+    pub video_path: String,
+    /// Messages in the session; 0 from `resume_session`, which doesn't say
+    pub message_count: i32,
+    pub last_updated: String,
+    /// The backend's note on `resume_session`
+    pub message: String,
+    /// Read from the local mirror because the backend was unreachable
+    pub offline: bool,
+    /// When the mirrored copy was fetched, if it is one
+    pub cached_at: Option<f64>,
+}
+
+impl From<LastSessionResponse> for SessionInfo {
+    fn from(response: LastSessionResponse) -> Self {
+        Self {
+            has_session: response.has_session,
+            video_id: response.video_id,
+            video_name: response.video_name,
+            video_path: response.video_path,
+            message_count: response.message_count,
+            last_updated: response.last_updated,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ResumeResponse> for SessionInfo {
+    fn from(response: ResumeResponse) -> Self {
+        Self {
+            has_session: response.success,
+            video_id: response.video_id,
+            video_name: response.video_name,
+            video_path: response.video_path,
+            message: response.message,
+            ..Default::default()
+        }
+    }
+}
+
+/// Answer of `get_chat_history`, also what `pin_for_offline` keeps
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct ChatHistory {
+    pub video_id: String,
+    pub video_name: String,
+    pub conversation_summary: String,
+    /// Only with `include_full_messages`
+    pub recent_messages: Vec<HistoryMessage>,
+    pub total_messages: i32,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Read from the offline copy or the local mirror because the
+    /// backend was unreachable
+    pub offline: bool,
+    /// When the mirrored copy was fetched, if it is one
+    pub cached_at: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct HistoryMessage {
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+}
+
+impl From<GetChatHistoryResponse> for ChatHistory {
+    fn from(response: GetChatHistoryResponse) -> Self {
+        Self {
+            video_id: response.video_id,
+            video_name: response.video_name,
+            conversation_summary: response.conversation_summary,
+            recent_messages: response
+                .recent_messages
+                .into_iter()
+                .map(|m| HistoryMessage {
+                    role: m.role,
+                    content: m.content,
+                    timestamp: m.timestamp,
+                })
+                .collect(),
+            total_messages: response.total_messages,
+            created_at: response.created_at,
+            updated_at: response.updated_at,
+            ..Default::default()
+        }
+    }
+}
+
+/// One chunk of an answer, as `process_query` returns them and
+/// `chat://chunk` streams them, after post-processing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Type)]
+pub struct ChatChunk {
+    /// `ChatResponse.ResponseType`: 0 message, 1 progress, 2 result, 3 error
+    #[serde(rename = "type")]
+    pub kind: i32,
+    pub content: String,
+    #[serde(default)]
+    pub agent_name: String,
+    #[serde(default)]
+    pub result_json: String,
+    /// Position in the stream; 0 when the backend doesn't number chunks
+    #[serde(default)]
+    pub sequence: u64,
+    /// `assistant`, `status` or `alert`, see [`crate::a11y`]
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub agent: String,
+    #[serde(default)]
+    pub is_partial: bool,
+    #[serde(default)]
+    pub plain_text: String,
+    /// The query mode, on results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query_type: Option<String>,
+    /// Whether `result_json` has the mode's schema, on results
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_ok: Option<bool>,
+    /// `result_json` parsed, on results that have a schema
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_error: Option<String>,
+    /// Answered from the query cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+    /// Id in the [`crate::outbox`] of a question queued until the backend
+    /// is back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbox_id: Option<String>,
+    /// Fields added by response transforms, see [`crate::postprocess`]
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// `responses`, a post-processed JSON array of chunks, as [`ChatChunk`]s
+pub fn chat_chunks(responses: Value) -> Result<Vec<ChatChunk>, String> {
+    serde_json::from_value(responses).map_err(|e| format!("Malformed chat chunk: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specta_typescript::{BigIntExportBehavior, Typescript};
+
+    /// The app's TypeScript bindings, relative to this crate
+    const BINDINGS: &str = "../../../src/bindings/videoAnalyzer.ts";
+
+    const HEADER: &str =
+        "// From src-tauri/plugins/tauri-plugin-video-analyzer/src/responses.rs;\n\
+         // regenerate with `UPDATE_BINDINGS=1 cargo test bindings`";
+
+    #[test]
+    fn bindings_are_generated_from_the_responses() {
+        let mut types = specta::TypeCollection::default();
+        types
+            .register::<UploadResult>()
+            .register::<SessionInfo>()
+            .register::<ChatHistory>()
+            .register::<ChatChunk>();
+        let generated = Typescript::default()
+            .header(HEADER)
+            .bigint(BigIntExportBehavior::Number)
+            .export(&types)
+            .unwrap();
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(BINDINGS);
+        if std::env::var_os("UPDATE_BINDINGS").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let current = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            current == generated,
+            "{} is out of date; regenerate it with UPDATE_BINDINGS=1 cargo test bindings",
+            BINDINGS
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! Typed models for RESULT `result_json` payloads
+//!
+//! The backend sends structured results as a JSON string. Rather than have
+//! every UI component `JSON.parse` and guess at the shape, known schemas are
+//! parsed and validated here and handed to the frontend as a tagged object
+//! (`{"kind": "detections", ...}`). Unknown shapes are left as raw JSON.
+
+use serde::{Deserialize, Serialize};
+
+/// One object detected in a sampled frame (see the backend's vision agent)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Detection {
+    #[serde(rename = "class", alias = "label")]
+    pub label: String,
+    pub confidence: f64,
+    /// Seconds from the start of the video
+    pub timestamp: f64,
+    #[serde(default)]
+    pub frame: Option<u64>,
+    #[serde(default)]
+    pub bbox: Option<BoundingBox>,
+}
+
+/// Box in pixels of the analysed frame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl BoundingBox {
+    fn is_valid(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.width >= 0.0 && self.height >= 0.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectionResult {
+    pub detections: Vec<Detection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub start: f64,
+    pub end: f64,
+    #[serde(alias = "description")]
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineResult {
+    pub timeline: Vec<TimelineEvent>,
+}
+
+/// A Whisper-style transcript segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptResult {
+    #[serde(alias = "segments")]
+    pub transcript: Vec<TranscriptSegment>,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// The orchestrator's envelope around a multi-agent run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentRunResult {
+    pub selected_agents: Vec<String>,
+    #[serde(default)]
+    pub llm_calls: u32,
+    #[serde(default)]
+    pub agent_results: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A parsed and validated `result_json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StructuredResult {
+    Detections(DetectionResult),
+    Timeline(TimelineResult),
+    Transcript(TranscriptResult),
+    AgentRun(AgentRunResult),
+}
+
+/// Parse `result_json` into a known schema
+///
+/// `Ok(None)` for empty strings and shapes we don't have a model for;
+/// `Err` when the shape is recognised but the content is malformed.
+pub fn parse_result(result_json: &str) -> Result<Option<StructuredResult>, String> {
+    if result_json.trim().is_empty() {
+        return Ok(None);
+    }
+    let value: serde_json::Value = serde_json::from_str(result_json)
+        .map_err(|e| format!("result_json is not valid JSON: {}", e))?;
+
+    let parsed = if value.get("detections").is_some() {
+        StructuredResult::Detections(from_value(value, "detections")?)
+    } else if value.get("timeline").is_some() {
+        StructuredResult::Timeline(from_value(value, "timeline")?)
+    } else if value.get("transcript").is_some() || value.get("segments").is_some() {
+        StructuredResult::Transcript(from_value(value, "transcript")?)
+    } else if value.get("selected_agents").is_some() {
+        StructuredResult::AgentRun(from_value(value, "agent run")?)
+    } else {
+        return Ok(None);
+    };
+    parsed.validate()?;
+    Ok(Some(parsed))
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(
+    value: serde_json::Value,
+    kind: &str,
+) -> Result<T, String> {
+    serde_json::from_value(value).map_err(|e| format!("Malformed {} result: {}", kind, e))
+}
+
+impl StructuredResult {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Detections(r) => r.detections.iter().enumerate().try_for_each(|(i, d)| {
+                if !(0.0..=1.0).contains(&d.confidence) {
+                    return Err(format!(
+                        "Detection {} has confidence {} outside 0..1",
+                        i, d.confidence
+                    ));
+                }
+                check_time(d.timestamp, d.timestamp, "Detection", i)?;
+                match d.bbox {
+                    Some(b) if !b.is_valid() => {
+                        Err(format!("Detection {} has an invalid bounding box", i))
+                    }
+                    _ => Ok(()),
+                }
+            }),
+            Self::Timeline(r) => r
+                .timeline
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, e)| check_time(e.start, e.end, "Timeline event", i)),
+            Self::Transcript(r) => r
+                .transcript
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, s)| check_time(s.start, s.end, "Transcript segment", i)),
+            Self::AgentRun(_) => Ok(()),
+        }
+    }
+}
+
+fn check_time(start: f64, end: f64, what: &str, index: usize) -> Result<(), String> {
+    if start.is_finite() && end.is_finite() && start >= 0.0 && end >= start {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} {} has an invalid time range {}..{}",
+            what, index, start, end
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vision_agent_detections() {
+        let json = r#"{"detections": [{"frame": 30, "timestamp": 1.0, "class": "person", "confidence": 0.91}]}"#;
+        let Some(StructuredResult::Detections(r)) = parse_result(json).unwrap() else {
+            panic!("expected detections");
+        };
+        assert_eq!(r.detections[0].label, "person");
+        assert_eq!(r.detections[0].frame, Some(30));
+    }
+
+    #[test]
+    fn test_parse_whisper_segments() {
+        let json =
+            r#"{"segments": [{"start": 0.0, "end": 2.5, "text": "hello"}], "language": "en"}"#;
+        let parsed = parse_result(json).unwrap().unwrap();
+        let value = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(value["kind"], "transcript");
+        assert_eq!(value["transcript"][0]["text"], "hello");
+    }
+
+    #[test]
+    fn test_invalid_content_is_rejected() {
+        assert!(parse_result(r#"{"timeline": [{"start": 5, "end": 1, "label": "x"}]}"#).is_err());
+        assert!(parse_result(
+            r#"{"detections": [{"timestamp": 1, "class": "cat", "confidence": 7}]}"#
+        )
+        .is_err());
+        assert!(parse_result(r#"{"detections": "nope"}"#).is_err());
+    }
+
+    #[test]
+    fn test_unknown_and_empty_are_passed_through() {
+        assert_eq!(parse_result("").unwrap(), None);
+        assert_eq!(parse_result(r#"{"something": 1}"#).unwrap(), None);
+    }
+}
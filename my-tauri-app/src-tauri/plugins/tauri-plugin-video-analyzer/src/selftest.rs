@@ -0,0 +1,183 @@
+//! End-to-end self-test against the connected backend
+//!
+//! `run_self_test` uploads [`SAMPLE_CLIP`], waits for the backend to list
+//! it, asks [`QUESTION`] and checks the streamed answer, then deletes the
+//! clip and its chat history. Each step lands in a [`Report`] so a failed
+//! install shows exactly where the pipeline breaks.
+
+use crate::video_analyzer::chat_response::ResponseType;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Instant;
+
+/// 2 s, 32x24 uncompressed AVI of a red square moving across a grey background
+pub const SAMPLE_CLIP: &[u8] = include_bytes!("../assets/self_test.avi");
+pub const SAMPLE_NAME: &str = "video-analyzer-self-test.avi";
+
+pub const QUESTION: &str = "Briefly describe what happens in this video.";
+
+/// How long the backend gets to list the upload, and to answer
+pub const STEP_TIMEOUT_SECS: u64 = 120;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Outcome {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+struct Step {
+    name: &'static str,
+    outcome: Outcome,
+    duration_ms: u64,
+    detail: String,
+}
+
+/// Outcome of each step, in the order they ran
+#[derive(Debug)]
+pub struct Report {
+    started: Instant,
+    steps: Vec<Step>,
+}
+
+impl Report {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Record a finished step; returns whether it passed
+    pub fn record(
+        &mut self,
+        name: &'static str,
+        started: Instant,
+        result: Result<String, String>,
+    ) -> bool {
+        let passed = result.is_ok();
+        let (outcome, detail) = match result {
+            Ok(detail) => (Outcome::Passed, detail),
+            Err(detail) => (Outcome::Failed, detail),
+        };
+        self.steps.push(Step {
+            name,
+            outcome,
+            duration_ms: started.elapsed().as_millis() as u64,
+            detail,
+        });
+        passed
+    }
+
+    pub fn skip(&mut self, name: &'static str, detail: impl Into<String>) {
+        self.steps.push(Step {
+            name,
+            outcome: Outcome::Skipped,
+            duration_ms: 0,
+            detail: detail.into(),
+        });
+    }
+
+    /// Passed when no step failed and the final answer was checked
+    pub fn passed(&self) -> bool {
+        !self
+            .steps
+            .iter()
+            .any(|s| matches!(s.outcome, Outcome::Failed))
+            && self
+                .steps
+                .iter()
+                .any(|s| s.name == "query" && matches!(s.outcome, Outcome::Passed))
+    }
+
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "passed": self.passed(),
+            "duration_ms": self.started.elapsed().as_millis() as u64,
+            "steps": self.steps,
+        })
+    }
+}
+
+/// Check a streamed chat answer: no ERROR chunks and some actual text
+///
+/// Returns the answer on success. The content isn't judged, only that the
+/// models produced something.
+pub fn check_answer(responses: &Value) -> Result<String, String> {
+    let items = responses.as_array().map(Vec::as_slice).unwrap_or_default();
+    if let Some(error) = items
+        .iter()
+        .find(|item| item["type"] == ResponseType::Error as i32)
+    {
+        return Err(format!(
+            "The backend reported an error: {}",
+            error["content"].as_str().unwrap_or_default()
+        ));
+    }
+    let answer = items
+        .iter()
+        .rev()
+        .filter(|item| {
+            item["type"] == ResponseType::Message as i32
+                || item["type"] == ResponseType::Result as i32
+        })
+        .find_map(|item| item["content"].as_str().filter(|c| !c.trim().is_empty()))
+        .ok_or_else(|| format!("No answer in {} streamed chunks", items.len()))?;
+    Ok(answer.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(kind: ResponseType, content: &str) -> Value {
+        serde_json::json!({ "type": kind as i32, "content": content })
+    }
+
+    #[test]
+    fn test_check_answer() {
+        let ok = Value::Array(vec![
+            chunk(ResponseType::Progress, "Processing your request..."),
+            chunk(ResponseType::Message, " A red square moves to the right. "),
+        ]);
+        assert_eq!(
+            check_answer(&ok).unwrap(),
+            "A red square moves to the right."
+        );
+
+        let only_progress = Value::Array(vec![chunk(ResponseType::Progress, "Processing")]);
+        assert!(check_answer(&only_progress).is_err());
+
+        let errored = Value::Array(vec![
+            chunk(ResponseType::Message, "partial"),
+            chunk(ResponseType::Error, "Stream interrupted"),
+        ]);
+        assert!(check_answer(&errored)
+            .unwrap_err()
+            .contains("Stream interrupted"));
+    }
+
+    #[test]
+    fn test_report_needs_a_checked_answer() {
+        let mut report = Report::new();
+        assert!(report.record("connect", Instant::now(), Ok(String::new())));
+        report.skip("query", "an earlier step failed");
+        assert!(!report.passed());
+
+        let mut report = Report::new();
+        report.record("query", Instant::now(), Ok("answer".to_string()));
+        report.record("cleanup", Instant::now(), Err("boom".to_string()));
+        assert!(!report.passed());
+        assert_eq!(report.to_json()["steps"][1]["outcome"], "failed");
+    }
+
+    #[test]
+    fn test_sample_clip_is_a_video() {
+        assert_eq!(
+            crate::files::validation::sniff_container(SAMPLE_CLIP),
+            Some("avi")
+        );
+    }
+}
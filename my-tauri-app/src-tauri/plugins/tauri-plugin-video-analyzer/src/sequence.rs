@@ -0,0 +1,150 @@
+//! Ordering of streamed chat responses
+//!
+//! Each chat request carries a client-generated `request_id`, and the
+//! backend numbers the responses for it 1, 2, 3... in `sequence`. When a
+//! stream is resumed or retried the same chunks can arrive twice, or out of
+//! order; [`Reorder`] drops the repeats and holds early chunks back until
+//! the gap before them is filled.
+//!
+//! Backends that predate sequencing send 0, which passes straight through.
+//!
+//! A retried request reuses its `request_id`, and [`Reorder::resume`] picks
+//! up after the chunks that were already received.
+
+use crate::video_analyzer::ChatResponse;
+use std::collections::BTreeMap;
+
+/// A fresh id for one chat request
+pub fn new_request_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Per-request reordering buffer
+#[derive(Debug)]
+pub struct Reorder {
+    next: u64,
+    pending: BTreeMap<u64, ChatResponse>,
+    duplicates: usize,
+}
+
+impl Default for Reorder {
+    fn default() -> Self {
+        Self {
+            next: 1,
+            pending: BTreeMap::new(),
+            duplicates: 0,
+        }
+    }
+}
+
+impl Reorder {
+    /// Carry on after `received`, the chunks already delivered for this request
+    ///
+    /// Only a fully sequenced prefix can be resumed from; otherwise `None`,
+    /// and the answer starts over.
+    pub fn resume(received: &[ChatResponse]) -> Option<Self> {
+        if received.iter().any(|c| c.sequence == 0) {
+            return None;
+        }
+        let last = received.iter().map(|c| c.sequence).max()?;
+        Some(Self {
+            next: last + 1,
+            ..Self::default()
+        })
+    }
+
+    /// Add a received chunk; returns the chunks now ready, in order
+    pub fn push(&mut self, response: ChatResponse) -> Vec<ChatResponse> {
+        let sequence = response.sequence;
+        if sequence == 0 {
+            return vec![response];
+        }
+        if sequence < self.next || self.pending.contains_key(&sequence) {
+            self.duplicates += 1;
+            return Vec::new();
+        }
+        self.pending.insert(sequence, response);
+        let mut ready = Vec::new();
+        while let Some(response) = self.pending.remove(&self.next) {
+            ready.push(response);
+            self.next += 1;
+        }
+        ready
+    }
+
+    /// End of stream: whatever is still held back, in order, past any gaps
+    pub fn finish(&mut self) -> Vec<ChatResponse> {
+        if let Some(&last) = self.pending.keys().next_back() {
+            log::warn!(
+                "Chat stream ended with {} missing chunk(s) before #{}",
+                last + 1 - self.next - self.pending.len() as u64,
+                last
+            );
+            self.next = last + 1;
+        }
+        std::mem::take(&mut self.pending).into_values().collect()
+    }
+
+    /// Chunks dropped because they had already been received
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(sequence: u64) -> ChatResponse {
+        ChatResponse {
+            content: format!("#{}", sequence),
+            sequence,
+            ..Default::default()
+        }
+    }
+
+    fn sequences(chunks: Vec<ChatResponse>) -> Vec<u64> {
+        chunks.into_iter().map(|c| c.sequence).collect()
+    }
+
+    #[test]
+    fn test_reorders_and_drops_duplicates() {
+        let mut order = Reorder::default();
+        assert_eq!(sequences(order.push(chunk(2))), Vec::<u64>::new());
+        assert_eq!(sequences(order.push(chunk(1))), vec![1, 2]);
+        assert!(order.push(chunk(2)).is_empty());
+        assert_eq!(sequences(order.push(chunk(3))), vec![3]);
+        assert!(order.push(chunk(1)).is_empty());
+        assert_eq!(order.duplicates(), 2);
+    }
+
+    #[test]
+    fn test_finish_flushes_past_gaps() {
+        let mut order = Reorder::default();
+        assert!(order.push(chunk(3)).is_empty());
+        assert!(order.push(chunk(5)).is_empty());
+        assert!(order.push(chunk(5)).is_empty());
+        assert_eq!(sequences(order.finish()), vec![3, 5]);
+        assert_eq!(order.duplicates(), 1);
+    }
+
+    #[test]
+    fn test_resume_skips_received_chunks() {
+        let mut order = Reorder::resume(&[chunk(1), chunk(2)]).unwrap();
+        assert!(order.push(chunk(1)).is_empty());
+        assert!(order.push(chunk(2)).is_empty());
+        assert_eq!(sequences(order.push(chunk(3))), vec![3]);
+        assert_eq!(order.duplicates(), 2);
+
+        assert!(Reorder::resume(&[]).is_none());
+        assert!(Reorder::resume(&[chunk(1), chunk(0)]).is_none());
+    }
+
+    #[test]
+    fn test_unsequenced_pass_through() {
+        let mut order = Reorder::default();
+        assert_eq!(order.push(chunk(0)).len(), 1);
+        assert_eq!(order.push(chunk(0)).len(), 1);
+        assert!(order.finish().is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+//! The video the user is working on, held on the Rust side
+//!
+//! The frontend calls `set_active_session` when the user switches videos.
+//! Chat commands whose `video_id` is left out then resolve against it, so
+//! a question asked mid-switch goes to the video the app has settled on
+//! rather than whichever id the sending component last saw. Every change
+//! is emitted as `video-analyzer://active-session` with
+//! `{ video_id, previous }`.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Runtime};
+
+#[derive(Debug, Clone, Serialize)]
+struct SessionChange {
+    video_id: Option<String>,
+    previous: Option<String>,
+}
+
+/// Managed state: the active video, if any
+#[derive(Debug, Default)]
+pub struct ActiveSession {
+    video_id: Mutex<Option<String>>,
+}
+
+impl ActiveSession {
+    pub fn get(&self) -> Option<String> {
+        self.video_id.lock().unwrap().clone()
+    }
+
+    /// Make `video_id` active (`None` clears it), emitting the change
+    pub fn set<R: Runtime>(&self, app: &AppHandle<R>, video_id: Option<String>) -> Option<String> {
+        let video_id = video_id.filter(|id| !id.trim().is_empty());
+        let previous = std::mem::replace(&mut *self.video_id.lock().unwrap(), video_id.clone());
+        if previous != video_id {
+            let change = SessionChange {
+                video_id,
+                previous: previous.clone(),
+            };
+            app.emit(&crate::event_name("active-session"), change).ok();
+        }
+        previous
+    }
+
+    /// `video_id` if given, else the active video
+    pub fn resolve(&self, video_id: Option<String>) -> Result<String, String> {
+        video_id
+            .filter(|id| !id.trim().is_empty())
+            .or_else(|| self.get())
+            .ok_or_else(|| "No video_id given and no active session".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_video_id_wins_over_the_active_one() {
+        let session = ActiveSession::default();
+        assert!(session.resolve(None).is_err());
+
+        *session.video_id.lock().unwrap() = Some("active".to_string());
+        assert_eq!(session.resolve(None).unwrap(), "active");
+        assert_eq!(session.resolve(Some(" ".to_string())).unwrap(), "active");
+        assert_eq!(session.resolve(Some("other".to_string())).unwrap(), "other");
+    }
+}
@@ -0,0 +1,161 @@
+//! Draining in-flight work before the app exits
+//!
+//! Without this, closing the window drops uploads and chat streams
+//! mid-way. The host app calls [`drain`] from `RunEvent::ExitRequested`,
+//! holding the exit until it returns:
+//!
+//! 1. New questions are refused and answers still streaming are cut off;
+//!    what arrived is kept as an incomplete response, to retry later.
+//! 2. Running uploads get the grace period (SHUTDOWN_GRACE_SECS) to
+//!    finish. Any still running are then paused: stopped, but left in the
+//!    upload manifest so `resume_upload` finishes them after a restart.
+//! 3. The upload manifest is written out with the latest progress.
+//!
+//! Stopping the backend sidecar is left to the host app, which launched it.
+
+use crate::config;
+use crate::uploads::{UploadManifest, UploadRegistry};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// How often [`drain`] checks whether the work it waits for has ended
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Time cancelled work gets to save what it has, on top of the grace period
+const UNWIND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Managed state: whether the app is shutting down, and the chat streams
+/// still running
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    streams: AtomicUsize,
+    drained: AtomicBool,
+}
+
+impl Shutdown {
+    /// Cancelled once the app starts shutting down
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Count a chat stream until the returned guard is dropped; refused
+    /// once the app is shutting down
+    pub fn track_stream(&self) -> Result<StreamGuard<'_>, String> {
+        if self.token.is_cancelled() {
+            return Err("The app is shutting down".to_string());
+        }
+        self.streams.fetch_add(1, Ordering::SeqCst);
+        Ok(StreamGuard(self))
+    }
+
+    /// Whether [`drain`] has finished, so the exit can go ahead
+    pub fn is_drained(&self) -> bool {
+        self.drained.load(Ordering::SeqCst)
+    }
+}
+
+/// A running chat stream; stops counting it when dropped
+pub struct StreamGuard<'a>(&'a Shutdown);
+
+impl Drop for StreamGuard<'_> {
+    fn drop(&mut self) {
+        self.0.streams.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// What [`drain`] had to cut short
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DrainReport {
+    /// Chat streams cut off, kept as incomplete responses
+    pub streams_cancelled: usize,
+    /// Uploads that finished within the grace period
+    pub uploads_finished: usize,
+    /// Uploads paused, to resume after a restart
+    pub uploads_paused: Vec<String>,
+    /// Whether everything had ended before the deadline
+    pub clean: bool,
+}
+
+/// Wind down the plugin's work before the app exits, see the module docs
+///
+/// Runs once; later calls wait for the first to finish and return an
+/// empty report.
+pub async fn drain<R: Runtime>(app: &AppHandle<R>) -> DrainReport {
+    let shutdown = app.state::<Shutdown>();
+    let uploads = app.state::<UploadRegistry>();
+    let mut report = DrainReport::default();
+    let grace = Duration::from_secs(config::get().app.shutdown_grace_secs);
+    if shutdown.token.is_cancelled() {
+        wait_until(Instant::now() + grace + UNWIND_TIMEOUT, || {
+            shutdown.is_drained()
+        })
+        .await;
+        return report;
+    }
+    log::info!(
+        "Shutting down: {} chat stream(s) and {} upload(s) in flight",
+        shutdown.streams.load(Ordering::SeqCst),
+        uploads.len()
+    );
+
+    report.streams_cancelled = shutdown.streams.load(Ordering::SeqCst);
+    shutdown.token.cancel();
+
+    let running = uploads.len();
+    let deadline = Instant::now() + grace;
+    wait_until(deadline, || uploads.is_empty()).await;
+    report.uploads_paused = uploads.pause_all();
+    report.uploads_finished = running.saturating_sub(report.uploads_paused.len());
+
+    report.clean = wait_until(Instant::now() + UNWIND_TIMEOUT, || {
+        uploads.is_empty() && shutdown.streams.load(Ordering::SeqCst) == 0
+    })
+    .await;
+    if let Err(e) = app.state::<UploadManifest>().flush() {
+        log::warn!("Failed to save the upload manifest: {}", e);
+    }
+    if !report.uploads_paused.is_empty() {
+        log::info!(
+            "Paused {} upload(s) for the next start: {}",
+            report.uploads_paused.len(),
+            report.uploads_paused.join(", ")
+        );
+    }
+    shutdown.drained.store(true, Ordering::SeqCst);
+    report
+}
+
+/// Wait for `done` until `deadline`; whether it came true
+async fn wait_until(deadline: Instant, done: impl Fn() -> bool) -> bool {
+    loop {
+        if done() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep_until(deadline.min(Instant::now() + POLL_INTERVAL)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_are_refused_once_shutting_down() {
+        let shutdown = Shutdown::default();
+        let guard = shutdown.track_stream().unwrap();
+        assert_eq!(shutdown.streams.load(Ordering::SeqCst), 1);
+
+        shutdown.token.cancel();
+        assert!(shutdown.track_stream().is_err());
+        drop(guard);
+        assert_eq!(shutdown.streams.load(Ordering::SeqCst), 0);
+    }
+}
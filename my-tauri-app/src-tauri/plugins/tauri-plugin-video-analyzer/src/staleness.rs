@@ -0,0 +1,259 @@
+//! Staleness detection for registered videos
+//!
+//! A reference-only registration points the backend at a file it does not
+//! own. If that file is later edited or deleted, the analysis context no
+//! longer matches what is on disk and nothing tells the user. This module
+//! compares the file against the fingerprint recorded at registration,
+//! both on demand (`verify_video`) and periodically in the background.
+
+use crate::config;
+use crate::files::in_use::FileSnapshot;
+use crate::files::{unix_seconds, validation};
+use crate::library::{FileStatus, Library, LibraryEntry};
+use crate::power::PowerState;
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// mtimes round-trip through f64 seconds, so allow for float noise
+const MTIME_TOLERANCE_SECS: f64 = 1e-3;
+
+/// Outcome of checking one library entry against the file on disk
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyReport {
+    pub video_id: String,
+    pub display_name: String,
+    pub path: String,
+    pub status: FileStatus,
+    pub previous_status: FileStatus,
+    pub expected_size: u64,
+    pub actual_size: Option<u64>,
+    /// True when the content hash was compared, not just size/mtime
+    pub hash_checked: bool,
+    /// The file exists, so registering it again would refresh the backend's view
+    pub can_reregister: bool,
+    pub message: String,
+}
+
+/// Cheap check: same size and mtime as recorded at registration
+pub fn matches_fingerprint(snapshot: &FileSnapshot, entry: &LibraryEntry) -> bool {
+    let mtime = snapshot.modified.map(unix_seconds).unwrap_or(0.0);
+    snapshot.size_bytes == entry.size_bytes
+        && (mtime - entry.modified_at).abs() < MTIME_TOLERANCE_SECS
+}
+
+/// Compare an entry with the file on disk
+///
+/// The cheap check is size + mtime. With `deep`, a size/mtime mismatch is
+/// confirmed by re-hashing, so a file that was merely touched stays `Ok`.
+pub async fn verify_entry(entry: &LibraryEntry, deep: bool) -> VerifyReport {
+    let path = entry.path();
+    let mut report = VerifyReport {
+        video_id: entry.file_id.clone(),
+        display_name: entry.display_name.clone(),
+        path: entry.canonical_path.clone(),
+        status: FileStatus::Ok,
+        previous_status: entry.status,
+        expected_size: entry.size_bytes,
+        actual_size: None,
+        hash_checked: false,
+        can_reregister: false,
+        message: String::new(),
+    };
+
+    let snapshot = match FileSnapshot::take(&path) {
+        Ok(s) => s,
+        Err(_) => {
+            report.status = FileStatus::Missing;
+            report.message = format!("File no longer exists at {}", path.display());
+            return report;
+        }
+    };
+    report.actual_size = Some(snapshot.size_bytes);
+    report.can_reregister = true;
+
+    if matches_fingerprint(&snapshot, entry) {
+        report.message = "File matches its registration".to_string();
+        return report;
+    }
+
+    if deep && !entry.sha256.is_empty() {
+        report.hash_checked = true;
+        match validation::hash_file(&path).await {
+            Ok(hash) if hash == entry.sha256 => {
+                report.message = "File was touched but its content is unchanged".to_string();
+                return report;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                report.status = FileStatus::Missing;
+                report.can_reregister = false;
+                report.message = e;
+                return report;
+            }
+        }
+    }
+
+    report.status = FileStatus::Modified;
+    report.message = format!(
+        "File changed since registration ({} → {} bytes); re-register to refresh the analysis",
+        entry.size_bytes, snapshot.size_bytes
+    );
+    report
+}
+
+/// Verify an entry and persist the resulting status in the library
+pub async fn verify_and_record(
+    library: &Library,
+    entry: &LibraryEntry,
+    deep: bool,
+) -> VerifyReport {
+    let report = verify_entry(entry, deep).await;
+    let now = unix_seconds(SystemTime::now());
+    let status = report.status;
+    if let Err(e) = library.update(&entry.file_id, |e| {
+        e.status = status;
+        e.last_verified_at = now;
+    }) {
+        log::warn!("Failed to record status for {}: {}", entry.file_id, e);
+    }
+    report
+}
+
+/// Periodically re-check every reference-only video
+///
+/// Emits `video-analyzer://video-stale` with a [`VerifyReport`] whenever a video's status
+/// changes, so the UI can offer re-registration. A file that no longer
+/// matches its fingerprint is re-hashed before it is flagged, so one that
+/// was only touched stays `Ok`; files already flagged aren't hashed again.
+/// Paused in power-saving mode.
+pub fn spawn_checker<R: Runtime>(app: AppHandle<R>) {
+    let interval_secs = config::get().app.staleness_check_interval_secs;
+    if interval_secs == 0 {
+        log::info!("Staleness checker disabled");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        loop {
+            interval.tick().await;
+            if app.state::<PowerState>().is_saving() {
+                continue;
+            }
+            let library = app.state::<Library>();
+            for entry in library.entries().into_iter().filter(|e| e.reference_only) {
+                let deep = entry.status != FileStatus::Modified;
+                let report = verify_and_record(&library, &entry, deep).await;
+                if report.status != report.previous_status {
+                    log::info!(
+                        "Video {} is now {:?}: {}",
+                        report.video_id,
+                        report.status,
+                        report.message
+                    );
+                    app.emit(&crate::event_name("video-stale"), &report).ok();
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::volume::VolumeKind;
+    use std::path::Path;
+
+    fn entry_for(path: &Path) -> LibraryEntry {
+        let snapshot = FileSnapshot::take(path).unwrap();
+        LibraryEntry {
+            file_id: "vid".to_string(),
+            display_name: "clip.mp4".to_string(),
+            original_path: path.to_string_lossy().to_string(),
+            canonical_path: path.to_string_lossy().to_string(),
+            canonical_path_raw: None,
+            name_raw: String::new(),
+            volume_id: String::new(),
+            volume_kind: VolumeKind::Local,
+            reference_only: true,
+            size_bytes: snapshot.size_bytes,
+            sha256: String::new(),
+            modified_at: snapshot.modified.map(unix_seconds).unwrap_or(0.0),
+            container: "mp4".to_string(),
+            registered_at: 0.0,
+            status: FileStatus::Ok,
+            last_verified_at: 0.0,
+            analysis_options: None,
+            detected_language: None,
+        }
+    }
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("staleness-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let dir = test_dir("missing");
+        let path = dir.join("clip.mp4");
+        std::fs::write(&path, b"not really a video").unwrap();
+        let entry = entry_for(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let report = verify_entry(&entry, true).await;
+        assert_eq!(report.status, FileStatus::Missing);
+        assert!(!report.can_reregister);
+        assert_eq!(report.actual_size, None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_modified_file() {
+        let dir = test_dir("modified");
+        let path = dir.join("clip.mp4");
+        std::fs::write(&path, b"not really a video").unwrap();
+        let mut entry = entry_for(&path);
+        entry.sha256 = validation::hash_file(&path).await.unwrap();
+        assert!(matches_fingerprint(
+            &FileSnapshot::take(&path).unwrap(),
+            &entry
+        ));
+
+        std::fs::write(&path, b"not really a video, edited").unwrap();
+        assert!(!matches_fingerprint(
+            &FileSnapshot::take(&path).unwrap(),
+            &entry
+        ));
+        for deep in [false, true] {
+            let report = verify_entry(&entry, deep).await;
+            assert_eq!(report.status, FileStatus::Modified, "deep: {deep}");
+            assert_eq!(report.hash_checked, deep);
+            assert!(report.can_reregister);
+        }
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_touched_file_is_unchanged_when_hashed() {
+        let dir = test_dir("touched");
+        let path = dir.join("clip.mp4");
+        std::fs::write(&path, b"not really a video").unwrap();
+        let mut entry = entry_for(&path);
+        entry.sha256 = validation::hash_file(&path).await.unwrap();
+        // As if the file was touched after it was registered
+        entry.modified_at -= 60.0;
+
+        let report = verify_entry(&entry, true).await;
+        assert_eq!(report.status, FileStatus::Ok);
+        assert!(report.hash_checked);
+        // The cheap check alone can't tell
+        assert_eq!(
+            verify_entry(&entry, false).await.status,
+            FileStatus::Modified
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,370 @@
+//! Local mirror of sessions, videos and recent chat, for offline browsing
+//!
+//! What `get_last_session`, `resume_session` and `get_chat_history` fetch
+//! from the backend is mirrored in the workspace's `session_cache.db`, a
+//! SQLite database: the last session, what's known of each video, and each
+//! video's history (its summary and up to [`MAX_MESSAGES`] recent
+//! messages). When the backend can't be reached, those commands answer
+//! from the mirror straight away, marked `offline` and with the time it
+//! was fetched, instead of failing.
+//!
+//! A history pinned with `pin_for_offline` is complete and wins over the
+//! mirror.
+
+use crate::files;
+use crate::responses::{ChatHistory, SessionInfo};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Recent messages kept per video
+pub const MAX_MESSAGES: usize = 50;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS last_session (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        fetched_at REAL NOT NULL,
+        session TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS videos (
+        video_id TEXT PRIMARY KEY,
+        video_name TEXT NOT NULL DEFAULT '',
+        video_path TEXT NOT NULL DEFAULT '',
+        total_messages INTEGER NOT NULL DEFAULT 0,
+        last_seen_at REAL NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS histories (
+        video_id TEXT PRIMARY KEY,
+        fetched_at REAL NOT NULL,
+        history TEXT NOT NULL
+    );
+";
+
+fn db_error(e: rusqlite::Error) -> String {
+    format!("Failed to update the session cache: {}", e)
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value)
+        .map_err(|e| format!("Failed to serialize the session cache: {}", e))
+}
+
+/// The mirror's database, in the workspace
+pub const DB_FILE: &str = "session_cache.db";
+
+/// Managed state: the mirror of the workspace it was opened in
+pub struct SessionCache {
+    db: Mutex<Connection>,
+    /// Why the database couldn't be opened, when mirroring in memory
+    open_error: Option<String>,
+}
+
+impl SessionCache {
+    /// The mirror in `dir`, the workspace; an empty one kept in memory if
+    /// the database can't be opened
+    pub fn load(dir: &Path) -> Self {
+        let path = dir.join(DB_FILE);
+        let opened = std::fs::create_dir_all(dir)
+            .map_err(|e| e.to_string())
+            .and_then(|()| Self::open(&path).map_err(|e| e.to_string()));
+        match opened {
+            Ok(db) => Self {
+                db: Mutex::new(db),
+                open_error: None,
+            },
+            Err(e) => {
+                log::warn!(
+                    "Failed to open {}, mirroring in memory: {}",
+                    path.display(),
+                    e
+                );
+                Self {
+                    db: Mutex::new(Self::open_in_memory().expect("an in-memory database opens")),
+                    open_error: Some(format!("Failed to open {}: {}", path.display(), e)),
+                }
+            }
+        }
+    }
+
+    /// Why the mirror is only kept in memory for this run, if it is
+    pub fn open_error(&self) -> Option<&str> {
+        self.open_error.as_deref()
+    }
+
+    fn open(path: &Path) -> rusqlite::Result<Connection> {
+        let db = Connection::open(path)?;
+        db.execute_batch(SCHEMA)?;
+        Ok(db)
+    }
+
+    fn open_in_memory() -> rusqlite::Result<Connection> {
+        let db = Connection::open_in_memory()?;
+        db.execute_batch(SCHEMA)?;
+        Ok(db)
+    }
+
+    /// Keep `session` as fetched now; one without a video only clears the
+    /// last session
+    pub fn put_session(&self, session: &SessionInfo) -> Result<(), String> {
+        let now = files::unix_seconds(std::time::SystemTime::now());
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction().map_err(db_error)?;
+        if !session.has_session || session.video_id.is_empty() {
+            tx.execute("DELETE FROM last_session", [])
+                .map_err(db_error)?;
+        } else {
+            tx.execute(
+                "INSERT INTO videos (video_id, video_name, video_path, total_messages, last_seen_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (video_id) DO UPDATE SET
+                     video_name = excluded.video_name,
+                     video_path = excluded.video_path,
+                     total_messages = MAX(total_messages, excluded.total_messages),
+                     last_seen_at = excluded.last_seen_at",
+                params![
+                    session.video_id,
+                    session.video_name,
+                    session.video_path,
+                    session.message_count,
+                    now
+                ],
+            )
+            .map_err(db_error)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO last_session (id, fetched_at, session) VALUES (0, ?1, ?2)",
+                params![now, to_json(session)?],
+            )
+            .map_err(db_error)?;
+        }
+        tx.commit().map_err(db_error)
+    }
+
+    /// The last session seen and when it was fetched
+    pub fn last_session(&self) -> Option<(SessionInfo, f64)> {
+        let db = self.db.lock().unwrap();
+        let (fetched_at, session): (f64, String) = db
+            .query_row(
+                "SELECT fetched_at, session FROM last_session WHERE id = 0",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .unwrap_or_else(|e| {
+                log::warn!(
+                    "Failed to read the last session from the session cache: {}",
+                    e
+                );
+                None
+            })?;
+        Some((serde_json::from_str(&session).ok()?, fetched_at))
+    }
+
+    /// Keep `history` as fetched now; a summary fetched without messages
+    /// keeps the messages mirrored before
+    pub fn put_history(&self, history: &ChatHistory) -> Result<(), String> {
+        if history.video_id.is_empty() {
+            return Ok(());
+        }
+        let now = files::unix_seconds(std::time::SystemTime::now());
+        let mut history = history.clone();
+        history.offline = false;
+        history.cached_at = None;
+        if history.recent_messages.is_empty() {
+            if let Some((previous, _)) = self.history(&history.video_id) {
+                history.recent_messages = previous.recent_messages;
+            }
+        }
+        let skip = history.recent_messages.len().saturating_sub(MAX_MESSAGES);
+        history.recent_messages.drain(..skip);
+
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction().map_err(db_error)?;
+        tx.execute(
+            "INSERT INTO videos (video_id, video_name, total_messages, last_seen_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (video_id) DO UPDATE SET
+                 video_name = excluded.video_name,
+                 total_messages = excluded.total_messages,
+                 last_seen_at = excluded.last_seen_at",
+            params![
+                history.video_id,
+                history.video_name,
+                history.total_messages,
+                now
+            ],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "INSERT OR REPLACE INTO histories (video_id, fetched_at, history) VALUES (?1, ?2, ?3)",
+            params![history.video_id, now, to_json(&history)?],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Forget the mirrored history of `video_id`, e.g. once it is cleared
+    pub fn forget_history(&self, video_id: &str) -> Result<(), String> {
+        let db = self.db.lock().unwrap();
+        db.execute("DELETE FROM histories WHERE video_id = ?1", [video_id])
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    /// Show `video_id` as `name` in everything mirrored of it
+    pub fn rename_video(&self, video_id: &str, name: &str) -> Result<(), String> {
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction().map_err(db_error)?;
+        tx.execute(
+            "UPDATE videos SET video_name = ?2 WHERE video_id = ?1",
+            params![video_id, name],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "UPDATE last_session SET session = json_set(session, '$.video_name', ?2)
+             WHERE json_extract(session, '$.video_id') = ?1",
+            params![video_id, name],
+        )
+        .map_err(db_error)?;
+        tx.execute(
+            "UPDATE histories SET history = json_set(history, '$.video_name', ?2)
+             WHERE video_id = ?1",
+            params![video_id, name],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// Forget everything mirrored of `video_id`, e.g. once it is deleted
+    pub fn forget_video(&self, video_id: &str) -> Result<(), String> {
+        let mut db = self.db.lock().unwrap();
+        let tx = db.transaction().map_err(db_error)?;
+        tx.execute("DELETE FROM videos WHERE video_id = ?1", [video_id])
+            .map_err(db_error)?;
+        tx.execute("DELETE FROM histories WHERE video_id = ?1", [video_id])
+            .map_err(db_error)?;
+        tx.execute(
+            "DELETE FROM last_session WHERE json_extract(session, '$.video_id') = ?1",
+            [video_id],
+        )
+        .map_err(db_error)?;
+        tx.commit().map_err(db_error)
+    }
+
+    /// The mirrored history of `video_id` and when it was fetched
+    pub fn history(&self, video_id: &str) -> Option<(ChatHistory, f64)> {
+        let db = self.db.lock().unwrap();
+        let (fetched_at, history): (f64, String) = db
+            .query_row(
+                "SELECT fetched_at, history FROM histories WHERE video_id = ?1",
+                [video_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .unwrap_or_else(|e| {
+                log::warn!(
+                    "Failed to read history {} from the session cache: {}",
+                    video_id,
+                    e
+                );
+                None
+            })?;
+        Some((serde_json::from_str(&history).ok()?, fetched_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::responses::HistoryMessage;
+
+    #[test]
+    fn summaries_keep_the_mirrored_messages() {
+        let dir = std::env::temp_dir().join(format!("storage-test-{}", std::process::id()));
+        let cache = SessionCache::load(&dir);
+        cache
+            .put_session(&SessionInfo {
+                has_session: true,
+                video_id: "v1".to_string(),
+                video_name: "talk.mp4".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        let messages: Vec<HistoryMessage> = (0..MAX_MESSAGES + 5)
+            .map(|i| HistoryMessage {
+                role: "user".to_string(),
+                content: format!("question {}", i),
+                timestamp: String::new(),
+            })
+            .collect();
+        let mut history = ChatHistory {
+            video_id: "v1".to_string(),
+            conversation_summary: "first".to_string(),
+            recent_messages: messages,
+            ..Default::default()
+        };
+        cache.put_history(&history).unwrap();
+        history.conversation_summary = "second".to_string();
+        history.recent_messages.clear();
+        cache.put_history(&history).unwrap();
+
+        let reopened = SessionCache::load(&dir);
+        let (session, _) = reopened.last_session().unwrap();
+        assert_eq!(session.video_name, "talk.mp4");
+        let (history, _) = reopened.history("v1").unwrap();
+        assert_eq!(history.conversation_summary, "second");
+        assert_eq!(history.recent_messages.len(), MAX_MESSAGES);
+        assert_eq!(history.recent_messages[0].content, "question 5");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn renaming_and_forgetting_reach_everything_mirrored() {
+        let dir = std::env::temp_dir().join(format!("storage-forget-{}", std::process::id()));
+        let cache = SessionCache::load(&dir);
+        cache
+            .put_session(&SessionInfo {
+                has_session: true,
+                video_id: "v1".to_string(),
+                video_name: "talk.mp4".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        for video_id in ["v1", "v2"] {
+            cache
+                .put_history(&ChatHistory {
+                    video_id: video_id.to_string(),
+                    video_name: "talk.mp4".to_string(),
+                    ..Default::default()
+                })
+                .unwrap();
+        }
+
+        cache.rename_video("v1", "keynote.mp4").unwrap();
+        assert_eq!(cache.last_session().unwrap().0.video_name, "keynote.mp4");
+        assert_eq!(cache.history("v1").unwrap().0.video_name, "keynote.mp4");
+        assert_eq!(cache.history("v2").unwrap().0.video_name, "talk.mp4");
+
+        cache.forget_video("v1").unwrap();
+        assert!(cache.last_session().is_none());
+        assert!(cache.history("v1").is_none());
+        assert!(cache.history("v2").is_some());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn an_unopenable_database_is_mirrored_in_memory() {
+        let file = std::env::temp_dir().join(format!("storage-file-{}", std::process::id()));
+        std::fs::write(&file, "not a directory").unwrap();
+        let cache = SessionCache::load(&file);
+        assert!(cache.open_error().is_some());
+        cache
+            .put_session(&SessionInfo {
+                has_session: true,
+                video_id: "v1".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(cache.last_session().is_some());
+        std::fs::remove_file(&file).ok();
+    }
+}
@@ -0,0 +1,71 @@
+//! JSON files in the app data directory
+//!
+//! Small client-side stores (library, local chat history) are whole-file
+//! JSON documents held in managed state; these helpers load them leniently
+//! and write them atomically. Larger per-video data fetched from the
+//! backend (transcripts, detections) gets a file per video in
+//! [`VideoFiles`] instead.
+
+use crate::offline::safe_file_name;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Load `path`, starting from `T::default()` if it is missing or unreadable
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("{} is unreadable, starting empty: {}", path.display(), e);
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Write via a temp file + rename so a crash never leaves a half-written file
+pub fn save_json<T: Serialize>(path: &Path, data: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(data)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// A directory of `<video_id>.json` files
+pub struct VideoFiles {
+    root: PathBuf,
+}
+
+impl VideoFiles {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, video_id: &str) -> PathBuf {
+        self.root.join(format!("{}.json", safe_file_name(video_id)))
+    }
+
+    /// `None` if nothing is stored for the video
+    pub fn load<T: DeserializeOwned + Default>(&self, video_id: &str) -> Option<T> {
+        let path = self.path(video_id);
+        path.exists().then(|| load_json(&path))
+    }
+
+    pub fn save<T: Serialize>(&self, video_id: &str, data: &T) -> Result<(), String> {
+        save_json(&self.path(video_id), data)
+    }
+
+    /// Drop what is stored for the video, if anything
+    pub fn remove(&self, video_id: &str) -> Result<(), String> {
+        let path = self.path(video_id);
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+}
@@ -0,0 +1,541 @@
+//! Background sync of the local history with the backend
+//!
+//! Other devices talking to the same backend add messages to a session, and
+//! sessions get cleared or cleaned up there. Every
+//! HISTORY_SYNC_INTERVAL_SECS (and on `sync_now`) each locally cached
+//! session is compared with `GetChatHistory`:
+//!
+//! - messages the backend has after the last local one are appended locally
+//! - a session the backend no longer has, but had on an earlier sync, is
+//!   removed locally
+//! - if the local copy was also changed in some other way since the last
+//!   sync (cleared while offline, imported, rewritten), the session is put
+//!   in conflict: both versions are kept in a [`SyncConflict`] and nothing
+//!   changes until the user resolves it with [`resolve`]
+//!
+//! Divergence is detected from message counts and hashes recorded at each
+//! sync. Exchanges answered from the query cache never reached the backend
+//! and are left out of both. The outcome is recorded per session as a
+//! [`SyncStatus`].
+
+use crate::config;
+use crate::files::unix_seconds;
+use crate::history::{LocalHistory, LocalMessage, SyncConflict, SyncState, SyncStatus};
+use crate::power::PowerState;
+use crate::transport::Transport;
+use crate::video_analyzer::{ChatMessage, ClearHistoryRequest, GetHistoryRequest};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Backend messages that come after everything `local` already has
+///
+/// The backend only returns its recent messages, so they are aligned on the
+/// last local message. If that isn't among them, every remote message not
+/// already held locally is taken instead.
+pub fn missing_messages<'a>(
+    local: &[LocalMessage],
+    remote: &'a [ChatMessage],
+) -> Vec<&'a ChatMessage> {
+    let same = |l: &LocalMessage, r: &ChatMessage| l.role == r.role && l.content == r.content;
+    let Some(last) = local.last() else {
+        return remote.iter().collect();
+    };
+    match remote.iter().rposition(|r| same(last, r)) {
+        Some(i) => remote[i + 1..].iter().collect(),
+        None => remote
+            .iter()
+            .filter(|r| !local.iter().any(|l| same(l, r)))
+            .collect(),
+    }
+}
+
+/// How the user wants a conflict resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the local version; a local clear is re-sent to the backend
+    KeepLocal,
+    /// Replace the local version with the backend's
+    KeepRemote,
+    /// Keep local messages and append the backend's missing ones
+    Merge,
+}
+
+impl Resolution {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "keep_local" | "local" => Ok(Self::KeepLocal),
+            "keep_remote" | "remote" => Ok(Self::KeepRemote),
+            "merge" => Ok(Self::Merge),
+            other => Err(format!(
+                "Unknown resolution '{}': expected keep_local, keep_remote or merge",
+                other
+            )),
+        }
+    }
+}
+
+/// What a sync should do with a session
+#[derive(Debug, PartialEq)]
+pub enum Divergence<'a> {
+    InSync,
+    /// Only the backend moved on; append these
+    Pull(Vec<&'a ChatMessage>),
+    Conflict,
+}
+
+/// Hash of a conversation's roles and contents
+pub fn messages_hash<'a>(messages: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut hasher = Sha256::new();
+    for (role, content) in messages {
+        hasher.update(role.as_bytes());
+        hasher.update([0]);
+        hasher.update(content.as_bytes());
+        hasher.update([0]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// What the backend has seen of `local`: all but cache-answered exchanges
+fn shared(local: &[LocalMessage]) -> Vec<LocalMessage> {
+    local.iter().filter(|m| !m.cached).cloned().collect()
+}
+
+fn local_hash(messages: &[LocalMessage]) -> String {
+    messages_hash(
+        messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str())),
+    )
+}
+
+fn remote_hash(messages: &[ChatMessage]) -> String {
+    messages_hash(
+        messages
+            .iter()
+            .map(|m| (m.role.as_str(), m.content.as_str())),
+    )
+}
+
+/// Compare the local history with the backend's, given the last sync
+pub fn compare<'a>(
+    local: &[LocalMessage],
+    base: Option<&SyncStatus>,
+    remote: &'a [ChatMessage],
+) -> Divergence<'a> {
+    let local = &shared(local)[..];
+    // Nothing new on the backend since the last sync
+    if base.is_some_and(|b| b.remote_hash == remote_hash(remote)) {
+        return Divergence::InSync;
+    }
+    let missing = missing_messages(local, remote);
+    if missing.is_empty() {
+        return Divergence::InSync;
+    }
+    // Anything but appends since the last sync: a clear, import or rewrite
+    let local_rewritten = base.is_some_and(|b| {
+        local.len() < b.synced_count || local_hash(&local[..b.synced_count]) != b.synced_hash
+    });
+    // Messages added locally since the last sync that the backend never saw
+    let unseen = |l: &LocalMessage| {
+        !remote
+            .iter()
+            .any(|r| l.role == r.role && l.content == r.content)
+    };
+    let local_ahead = !remote.is_empty()
+        && match base {
+            Some(b) => local
+                .get(b.synced_count..)
+                .unwrap_or_default()
+                .iter()
+                .any(unseen),
+            None => local.last().is_some_and(unseen),
+        };
+    if local_rewritten || local_ahead {
+        Divergence::Conflict
+    } else {
+        Divergence::Pull(missing)
+    }
+}
+
+fn to_local(message: &ChatMessage, fallback: f64) -> LocalMessage {
+    LocalMessage {
+        role: message.role.clone(),
+        content: message.content.clone(),
+        timestamp: parse_timestamp(&message.timestamp).unwrap_or(fallback),
+        attachments: Vec::new(),
+        range: None,
+        feedback: None,
+        pinned_at: None,
+        cached: false,
+    }
+}
+
+/// Backend timestamps are naive local ISO 8601, e.g. `2025-01-01T12:00:00.123456`
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    let local = naive.and_local_timezone(chrono::Local).earliest()?;
+    Some(local.timestamp_millis() as f64 / 1000.0)
+}
+
+fn synced(now: f64, remote_total: i32, local: &[LocalMessage], remote_hash: String) -> SyncStatus {
+    let local = shared(local);
+    SyncStatus {
+        state: SyncState::Synced,
+        last_synced_at: now,
+        remote_count: remote_total,
+        remote_updated_at: String::new(),
+        pulled: 0,
+        error: None,
+        synced_count: local.len(),
+        synced_hash: local_hash(&local),
+        remote_hash,
+    }
+}
+
+async fn sync_session(
+    transport: &Transport,
+    history: &LocalHistory,
+    video_id: &str,
+) -> Result<SyncStatus, String> {
+    let now = unix_seconds(SystemTime::now());
+    let previous = history.sync_status(video_id);
+    // Leave conflicted sessions alone until the user has picked a side
+    if let Some(previous) = previous
+        .as_ref()
+        .filter(|_| history.conflict(video_id).is_some())
+    {
+        return Ok(SyncStatus {
+            state: SyncState::Conflict,
+            ..previous.clone()
+        });
+    }
+
+    let request = GetHistoryRequest {
+        video_id: video_id.to_string(),
+        include_full_messages: true,
+    };
+    let remote = match transport.get_chat_history(request).await {
+        Ok(remote) => remote,
+        // Nothing to record when the backend can't be reached at all
+        Err(status) if status.code() == tonic::Code::Unavailable => {
+            return Err(status.message().to_string())
+        }
+        Err(status) => {
+            let mut failed = previous.unwrap_or_else(|| synced(0.0, 0, &[], String::new()));
+            failed.state = SyncState::Error;
+            failed.pulled = 0;
+            failed.error = Some(format!("gRPC call failed: {}", status));
+            return Ok(failed);
+        }
+    };
+
+    let local = history.messages(video_id);
+    let mut status = synced(
+        now,
+        remote.total_messages,
+        &local,
+        remote_hash(&remote.recent_messages),
+    );
+    status.remote_updated_at = remote.updated_at.clone();
+
+    // An empty answer for a session the backend used to have means it was
+    // cleared or cleaned up there
+    let gone = remote.created_at.is_empty() && remote.total_messages == 0;
+    if gone {
+        if previous.is_some_and(|p| p.remote_count > 0) {
+            if let Err(e) = history.clear(video_id) {
+                status.state = SyncState::Error;
+                status.error = Some(e);
+            } else {
+                status.state = SyncState::Deleted;
+                status.synced_count = 0;
+                status.synced_hash = local_hash(&[]);
+            }
+        }
+        return Ok(status);
+    }
+
+    match compare(&local, previous.as_ref(), &remote.recent_messages) {
+        Divergence::InSync => {}
+        Divergence::Pull(missing) => {
+            let pulled: Vec<LocalMessage> = missing.into_iter().map(|m| to_local(m, now)).collect();
+            let mut merged = shared(&local);
+            let pulled_count = pulled.len();
+            merged.extend(pulled.iter().cloned());
+            match history.extend(video_id, pulled) {
+                Ok(()) => {
+                    status.pulled = pulled_count;
+                    status.synced_count = merged.len();
+                    status.synced_hash = local_hash(&merged);
+                }
+                Err(e) => {
+                    status.state = SyncState::Error;
+                    status.error = Some(e);
+                }
+            }
+        }
+        Divergence::Conflict => {
+            let conflict = SyncConflict {
+                video_id: video_id.to_string(),
+                detected_at: now,
+                remote: remote
+                    .recent_messages
+                    .iter()
+                    .map(|m| to_local(m, now))
+                    .collect(),
+                local,
+                remote_total: remote.total_messages,
+            };
+            // Keep the base of the last good sync so the resolution can
+            // still tell what changed
+            if let Some(previous) = previous {
+                status.synced_count = previous.synced_count;
+                status.synced_hash = previous.synced_hash;
+                status.remote_hash = previous.remote_hash;
+            }
+            match history.set_conflict(video_id, Some(conflict)) {
+                Ok(()) => status.state = SyncState::Conflict,
+                Err(e) => {
+                    status.state = SyncState::Error;
+                    status.error = Some(e);
+                }
+            }
+        }
+    }
+    Ok(status)
+}
+
+/// Apply the user's choice for a conflicted session
+pub async fn resolve(
+    transport: &Transport,
+    history: &LocalHistory,
+    video_id: &str,
+    resolution: Resolution,
+) -> Result<SyncStatus, String> {
+    let conflict = history
+        .conflict(video_id)
+        .ok_or_else(|| format!("No sync conflict for video {}", video_id))?;
+    let same = |a: &LocalMessage, b: &LocalMessage| a.role == b.role && a.content == b.content;
+
+    let mut remote_total = conflict.remote_total;
+    let seen_remote_hash = local_hash(&conflict.remote);
+    let messages = match resolution {
+        Resolution::KeepLocal => {
+            // A clear made while offline never reached the backend
+            if shared(&conflict.local).is_empty() {
+                transport
+                    .clear_chat_history(ClearHistoryRequest {
+                        video_id: video_id.to_string(),
+                    })
+                    .await
+                    .map_err(|e| format!("gRPC call failed: {}", e))?;
+                remote_total = 0;
+            }
+            conflict.local
+        }
+        Resolution::KeepRemote => conflict
+            .remote
+            .into_iter()
+            .map(|mut r| {
+                // Keep what only the client knows about matching messages
+                if let Some(l) = conflict.local.iter().find(|l| same(l, &r)) {
+                    r.timestamp = l.timestamp;
+                    r.attachments = l.attachments.clone();
+                    r.feedback = l.feedback.clone();
+                }
+                r
+            })
+            .collect(),
+        Resolution::Merge => {
+            let mut merged = conflict.local.clone();
+            for r in conflict.remote {
+                if !merged.iter().any(|l| same(l, &r)) {
+                    merged.push(r);
+                }
+            }
+            merged
+        }
+    };
+
+    history.replace(video_id, messages.clone())?;
+    history.set_conflict(video_id, None)?;
+    let now = unix_seconds(SystemTime::now());
+    // After clearing the backend it has nothing; otherwise accept its
+    // state as seen in the conflict so it doesn't re-trigger
+    let remote_hash = if remote_total == 0 {
+        remote_hash(&[])
+    } else {
+        seen_remote_hash
+    };
+    let status = synced(now, remote_total, &messages, remote_hash);
+    history.set_sync_status(video_id, status.clone())?;
+    Ok(status)
+}
+
+/// Sync the given sessions (all local ones if `None`) and record the outcome
+pub async fn sync(
+    transport: &Transport,
+    history: &LocalHistory,
+    video_ids: Option<Vec<String>>,
+) -> Result<HashMap<String, SyncStatus>, String> {
+    let video_ids = video_ids.unwrap_or_else(|| history.video_ids());
+    let mut results = HashMap::new();
+    for video_id in video_ids {
+        let status = sync_session(transport, history, &video_id).await?;
+        if let Some(error) = &status.error {
+            log::warn!("History sync of {} failed: {}", video_id, error);
+        }
+        history.set_sync_status(&video_id, status.clone())?;
+        results.insert(video_id, status);
+    }
+    Ok(results)
+}
+
+/// Periodically sync every session, emitting `history-synced` with the
+/// sessions that changed. Skipped in power-saving mode.
+pub fn spawn_sync<R: Runtime>(app: AppHandle<R>) {
+    let interval_secs = config::get().app.history_sync_interval_secs;
+    if interval_secs == 0 {
+        log::info!("History sync disabled");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        // The first tick fires immediately; give startup a full interval
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if app.state::<PowerState>().is_saving() {
+                continue;
+            }
+            let history = app.state::<LocalHistory>();
+            match sync(&app.state::<Transport>(), &history, None).await {
+                Ok(results) => {
+                    let changed: HashMap<_, _> = results
+                        .into_iter()
+                        .filter(|(_, s)| s.pulled > 0 || s.state == SyncState::Deleted)
+                        .collect();
+                    if !changed.is_empty() {
+                        app.emit(&crate::event_name("history-synced"), &changed)
+                            .ok();
+                    }
+                }
+                Err(e) => log::debug!("History sync skipped: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(role: &str, content: &str) -> LocalMessage {
+        LocalMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: 0.0,
+            attachments: Vec::new(),
+            range: None,
+            feedback: None,
+            pinned_at: None,
+            cached: false,
+        }
+    }
+
+    fn remote(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_missing_after_last_local() {
+        let l = [local("user", "hi"), local("assistant", "hello")];
+        let r = [
+            remote("user", "hi"),
+            remote("assistant", "hello"),
+            remote("user", "from phone"),
+            remote("assistant", "answer"),
+        ];
+        let missing = missing_messages(&l, &r);
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].content, "from phone");
+        assert!(missing_messages(&l, &r[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_missing_without_overlap() {
+        // The last local message never reached the backend
+        let l = [local("user", "kept"), local("user", "local only")];
+        let r = [remote("user", "kept"), remote("user", "new")];
+        let missing = missing_messages(&l, &r);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].content, "new");
+        assert_eq!(missing_messages(&[], &r).len(), 2);
+    }
+
+    fn base(local: &[LocalMessage], remote: &[ChatMessage]) -> SyncStatus {
+        synced(0.0, remote.len() as i32, local, remote_hash(remote))
+    }
+
+    #[test]
+    fn test_compare_pulls_remote_appends() {
+        let l = [local("user", "hi"), local("assistant", "hello")];
+        let r = [remote("user", "hi"), remote("assistant", "hello")];
+        let b = base(&l, &r);
+        assert_eq!(compare(&l, Some(&b), &r), Divergence::InSync);
+        let r2 = [r[0].clone(), r[1].clone(), remote("user", "from phone")];
+        assert_eq!(compare(&l, Some(&b), &r2), Divergence::Pull(vec![&r2[2]]));
+    }
+
+    #[test]
+    fn test_compare_flags_offline_clear() {
+        let l = [local("user", "hi"), local("assistant", "hello")];
+        let r = [remote("user", "hi"), remote("assistant", "hello")];
+        let b = base(&l, &r);
+        // Cleared locally, backend moved on meanwhile
+        let r2 = [r[0].clone(), r[1].clone(), remote("user", "more")];
+        assert_eq!(compare(&[], Some(&b), &r2), Divergence::Conflict);
+    }
+
+    #[test]
+    fn test_compare_flags_both_sides_appending() {
+        let l = [local("user", "hi")];
+        let r = [remote("user", "hi")];
+        let b = base(&l, &r);
+        let l2 = [local("user", "hi"), local("user", "imported")];
+        let r2 = [remote("user", "hi"), remote("user", "from phone")];
+        assert_eq!(compare(&l2, Some(&b), &r2), Divergence::Conflict);
+        assert_eq!(Resolution::parse("Merge").unwrap(), Resolution::Merge);
+        assert!(Resolution::parse("both").is_err());
+    }
+
+    #[test]
+    fn test_compare_ignores_cached_answers() {
+        let l = [local("user", "hi"), local("assistant", "hello")];
+        let r = [remote("user", "hi"), remote("assistant", "hello")];
+        let b = base(&l, &r);
+        let mut l2 = l.to_vec();
+        for mut message in [local("user", "again?"), local("assistant", "hello")] {
+            message.cached = true;
+            l2.push(message);
+        }
+        assert_eq!(compare(&l2, Some(&b), &r), Divergence::InSync);
+        let r2 = [r[0].clone(), r[1].clone(), remote("user", "from phone")];
+        assert_eq!(compare(&l2, Some(&b), &r2), Divergence::Pull(vec![&r2[2]]));
+        assert_eq!(base(&l2, &r).synced_hash, b.synced_hash);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert!(parse_timestamp("2025-01-01T12:00:00").is_some());
+        assert!(parse_timestamp("2025-01-01T12:00:00.123456").is_some());
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+}
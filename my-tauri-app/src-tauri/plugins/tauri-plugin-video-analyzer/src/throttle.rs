@@ -0,0 +1,100 @@
+//! Upload speed limit
+//!
+//! So a background upload doesn't take the whole connection, chunk
+//! producers [`acquire`] each chunk's bytes before handing it to the
+//! transport. One token bucket covers every upload and every stream of a
+//! parallel one, so the limit is on the total. It starts at
+//! UPLOAD_SPEED_LIMIT and `set_upload_speed_limit` changes it until the
+//! app restarts; 0 means no limit.
+
+use crate::config;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Limit set at runtime, over the configured one
+static LIMIT: Mutex<Option<u64>> = Mutex::new(None);
+
+static BUCKET: Mutex<Bucket> = Mutex::new(Bucket {
+    tokens: 0.0,
+    at: None,
+});
+
+/// Bytes that may be sent; negative while paying off a chunk sent early
+struct Bucket {
+    tokens: f64,
+    at: Option<Instant>,
+}
+
+impl Bucket {
+    /// Take `bytes` at `rate` bytes a second and say how long to wait
+    /// before sending them
+    fn reserve(&mut self, bytes: u64, rate: u64, now: Instant) -> Duration {
+        let rate = rate as f64;
+        let idle = self
+            .at
+            .map_or(0.0, |at| now.saturating_duration_since(at).as_secs_f64());
+        // No more than a second's worth saved up while idle
+        self.tokens = (self.tokens + idle * rate).min(rate);
+        self.at = Some(now);
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+/// Bytes a second uploads may use in total, 0 for no limit
+pub fn limit() -> u64 {
+    LIMIT
+        .lock()
+        .unwrap()
+        .unwrap_or_else(|| config::get().grpc.upload_speed_limit)
+}
+
+/// Change the limit, starting from now for uploads already running
+pub fn set_limit(bytes_per_sec: u64) {
+    *LIMIT.lock().unwrap() = Some(bytes_per_sec);
+    *BUCKET.lock().unwrap() = Bucket {
+        tokens: 0.0,
+        at: None,
+    };
+}
+
+/// Wait until `bytes` more may be sent
+pub async fn acquire(bytes: usize) {
+    let rate = limit();
+    if rate == 0 {
+        return;
+    }
+    let wait = BUCKET
+        .lock()
+        .unwrap()
+        .reserve(bytes as u64, rate, Instant::now());
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_holds_back_what_is_over_the_rate() {
+        let start = Instant::now();
+        let mut bucket = Bucket {
+            tokens: 0.0,
+            at: None,
+        };
+        assert_eq!(bucket.reserve(500, 1000, start), Duration::from_millis(500));
+        // The first chunk's debt is paid off by then
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.reserve(1000, 1000, later), Duration::from_secs(1));
+        // Long idle saves up at most a second's worth
+        let idle = later + Duration::from_secs(60);
+        assert_eq!(bucket.reserve(1000, 1000, idle), Duration::ZERO);
+        assert_eq!(bucket.reserve(250, 1000, idle), Duration::from_millis(250));
+    }
+}
@@ -0,0 +1,178 @@
+//! Video thumbnails, cached on disk by content
+//!
+//! `get_thumbnail` has ffmpeg grab a JPEG frame and keeps it as
+//! `thumbnails/<sha256>/<ms>-<width>.jpg` in the workspace, so the same
+//! video under another path, or registered twice, shares its thumbnails.
+//! The library grid loads them through the `thumbnail` URI scheme rather
+//! than as bytes over IPC: `thumbnail://localhost/<sha256>/<ms>-<width>.jpg`,
+//! or `http://thumbnail.localhost/...` on Windows and Android.
+
+use crate::files;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+use tauri::http::{header, Request, Response, StatusCode};
+use tauri::{Manager, Runtime, UriSchemeContext};
+
+/// URI scheme thumbnails are served on
+pub const SCHEME: &str = "thumbnail";
+
+pub const DEFAULT_WIDTH: u32 = 320;
+pub const MIN_WIDTH: u32 = 32;
+pub const MAX_WIDTH: u32 = 1920;
+
+/// What `get_thumbnail` answers with
+#[derive(Debug, Clone, Serialize)]
+pub struct Thumbnail {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_id: Option<String>,
+    pub sha256: String,
+    pub timestamp_ms: i64,
+    pub width: u32,
+    /// For an `<img>`; see the module docs
+    pub url: String,
+    pub path: String,
+    /// Whether it was already cached
+    pub cached: bool,
+}
+
+/// Managed state: the thumbnail directory, and the content hashes of the
+/// files thumbnailed by path so far
+pub struct ThumbnailCache {
+    root: PathBuf,
+    /// By path, with the size and modification time they were hashed at
+    hashes: Mutex<HashMap<PathBuf, (u64, SystemTime, String)>>,
+}
+
+/// `<sha256>/<ms>-<width>.jpg`
+pub fn relative(sha256: &str, timestamp_ms: i64, width: u32) -> String {
+    format!("{}/{}-{}.jpg", sha256, timestamp_ms, width)
+}
+
+/// What the webview loads `relative` from
+pub fn url(relative: &str) -> String {
+    if cfg!(any(windows, target_os = "android")) {
+        format!("http://{}.localhost/{}", SCHEME, relative)
+    } else {
+        format!("{}://localhost/{}", SCHEME, relative)
+    }
+}
+
+impl ThumbnailCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            hashes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Where the thumbnail would be, whether or not it is yet
+    pub fn path(&self, sha256: &str, timestamp_ms: i64, width: u32) -> PathBuf {
+        self.root.join(relative(sha256, timestamp_ms, width))
+    }
+
+    /// Keep `jpeg` as the thumbnail; returns where
+    pub fn put(
+        &self,
+        sha256: &str,
+        timestamp_ms: i64,
+        width: u32,
+        jpeg: &[u8],
+    ) -> Result<PathBuf, String> {
+        let path = self.path(sha256, timestamp_ms, width);
+        let dir = path.parent().expect("thumbnails live in a directory");
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        // Written aside first so the protocol never serves half a file
+        let partial = path.with_extension("part");
+        std::fs::write(&partial, jpeg)
+            .and_then(|()| std::fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// SHA-256 of the file at `path`, hashed again only once it changes
+    pub async fn content_hash(&self, path: &Path) -> Result<String, String> {
+        let metadata = std::fs::metadata(files::paths::fs_path(path))
+            .map_err(|e| format!("Failed to stat {}: {}", path.display(), e))?;
+        let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        if let Some((size, at, sha256)) = self.hashes.lock().unwrap().get(path) {
+            if (*size, *at) == (metadata.len(), modified) {
+                return Ok(sha256.clone());
+            }
+        }
+        let sha256 = files::validation::hash_file(path).await?;
+        self.hashes.lock().unwrap().insert(
+            path.to_path_buf(),
+            (metadata.len(), modified, sha256.clone()),
+        );
+        Ok(sha256)
+    }
+
+    /// The cached file a URI path such as `/<sha256>/<ms>-<width>.jpg`
+    /// names, if it is one this cache could have written
+    pub fn resolve(&self, uri_path: &str) -> Option<PathBuf> {
+        let (sha256, name) = uri_path.trim_start_matches('/').split_once('/')?;
+        let (timestamp_ms, width) = name.strip_suffix(".jpg")?.split_once('-')?;
+        let is_hash = sha256.len() == 64 && sha256.bytes().all(|b| b.is_ascii_hexdigit());
+        let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !(is_hash && is_number(timestamp_ms) && is_number(width)) {
+            return None;
+        }
+        Some(self.root.join(sha256).join(name))
+    }
+}
+
+fn respond(status: StatusCode, content_type: &str, body: Vec<u8>) -> Response<Cow<'static, [u8]>> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .body(Cow::Owned(body))
+        .expect("static headers are valid")
+}
+
+/// Handler of the [`SCHEME`] URI scheme
+pub fn protocol<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+) -> Response<Cow<'static, [u8]>> {
+    let not_found = || respond(StatusCode::NOT_FOUND, "text/plain", b"Not found".to_vec());
+    let Some(cache) = ctx.app_handle().try_state::<ThumbnailCache>() else {
+        return not_found();
+    };
+    let Some(path) = cache.resolve(request.uri().path()) else {
+        return not_found();
+    };
+    match std::fs::read(&path) {
+        Ok(jpeg) => respond(StatusCode::OK, "image/jpeg", jpeg),
+        Err(_) => not_found(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_cache_names_resolve() {
+        let cache = ThumbnailCache::new(PathBuf::from("/cache"));
+        let sha256 = "ab".repeat(32);
+        let name = relative(&sha256, 1500, 320);
+        assert_eq!(
+            cache.resolve(&format!("/{}", name)),
+            Some(cache.path(&sha256, 1500, 320))
+        );
+        for bad in [
+            format!("/{}/../../secret-1.jpg", sha256),
+            format!("/{}/1500-320.png", sha256),
+            "/../1500-320.jpg".to_string(),
+            format!("/{}/-1-320.jpg", sha256),
+        ] {
+            assert_eq!(cache.resolve(&bad), None, "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! A video's timeline: backend scenes and events plus the user's bookmarks
+//!
+//! `get_timeline` fetches the backend's scene/event summaries once and
+//! keeps them in `<app data>/timeline/<video_id>.json`, then merges them
+//! with [`crate::bookmarks`] into one time-ordered list for the UI.
+
+use crate::bookmarks::Bookmark;
+use crate::store::VideoFiles;
+use crate::video_analyzer::{TimelineEvent, TimelineResponse};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedTimeline {
+    pub fetched_at: f64,
+    pub timeline: TimelineResponse,
+}
+
+/// Managed state: where timelines are cached
+pub struct TimelineCache {
+    files: VideoFiles,
+}
+
+impl TimelineCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            files: VideoFiles::new(root),
+        }
+    }
+
+    pub fn get(&self, video_id: &str) -> Option<CachedTimeline> {
+        self.files
+            .load::<CachedTimeline>(video_id)
+            .filter(|cached| cached.timeline.available)
+    }
+
+    pub fn put(&self, video_id: &str, cached: &CachedTimeline) -> Result<(), String> {
+        self.files.save(video_id, cached)
+    }
+
+    pub fn remove(&self, video_id: &str) -> Result<(), String> {
+        self.files.remove(video_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemSource {
+    Backend,
+    Bookmark,
+}
+
+/// One entry of the merged timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineItem {
+    pub source: ItemSource,
+    /// "scene" or "event" from the backend, "bookmark" for the user's own
+    pub kind: String,
+    pub start_ms: i64,
+    pub end_ms: Option<i64>,
+    pub title: String,
+    pub summary: String,
+    /// Set for bookmarks, to edit or remove them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bookmark_id: Option<String>,
+}
+
+/// Events and bookmarks in time order; at the same moment, backend first
+pub fn merge(events: &[TimelineEvent], bookmarks: &[Bookmark]) -> Vec<TimelineItem> {
+    let mut items: Vec<TimelineItem> = events
+        .iter()
+        .map(|e| TimelineItem {
+            source: ItemSource::Backend,
+            kind: if e.kind.is_empty() {
+                "event".to_string()
+            } else {
+                e.kind.clone()
+            },
+            start_ms: e.start_ms,
+            end_ms: (e.end_ms > e.start_ms).then_some(e.end_ms),
+            title: e.title.clone(),
+            summary: e.summary.clone(),
+            bookmark_id: None,
+        })
+        .chain(bookmarks.iter().map(|b| TimelineItem {
+            source: ItemSource::Bookmark,
+            kind: "bookmark".to_string(),
+            start_ms: b.start_ms,
+            end_ms: b.end_ms,
+            title: b.label.clone(),
+            summary: b.note.clone(),
+            bookmark_id: Some(b.id.clone()),
+        }))
+        .collect();
+    items.sort_by_key(|item| (item.start_ms, item.source == ItemSource::Bookmark));
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_orders_by_time() {
+        let events = vec![
+            TimelineEvent {
+                start_ms: 0,
+                end_ms: 30_000,
+                kind: "scene".to_string(),
+                title: "Parking lot".to_string(),
+                ..Default::default()
+            },
+            TimelineEvent {
+                start_ms: 12_000,
+                title: "Car arrives".to_string(),
+                ..Default::default()
+            },
+        ];
+        let bookmarks = vec![Bookmark {
+            id: "b1".to_string(),
+            start_ms: 12_000,
+            end_ms: None,
+            label: "Plate visible".to_string(),
+            note: "check the reflection".to_string(),
+            created_at: 0.0,
+        }];
+
+        let items = merge(&events, &bookmarks);
+        let titles: Vec<_> = items.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Parking lot", "Car arrives", "Plate visible"]);
+        assert_eq!(items[0].end_ms, Some(30_000));
+        assert_eq!((items[1].kind.as_str(), items[1].end_ms), ("event", None));
+        assert_eq!(items[2].bookmark_id.as_deref(), Some("b1"));
+    }
+}
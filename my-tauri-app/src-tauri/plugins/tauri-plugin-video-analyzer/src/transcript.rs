@@ -0,0 +1,203 @@
+//! Cached video transcripts
+//!
+//! `get_transcript` fetches the backend's timestamped transcript once and
+//! keeps it in `<app data>/transcripts/<video_id>.json`; transcript search,
+//! subtitle export and time-range context all read the cached copy.
+//!
+//! A transcript the backend reports as not (yet) available is never
+//! cached, so a later call picks it up once analysis has finished.
+
+use crate::store::VideoFiles;
+use crate::video_analyzer::{TranscriptResponse, TranscriptSegment};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CachedTranscript {
+    pub fetched_at: f64,
+    pub transcript: TranscriptResponse,
+}
+
+/// Managed state: where transcript are cached
+pub struct TranscriptCache {
+    files: VideoFiles,
+}
+
+impl TranscriptCache {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            files: VideoFiles::new(root),
+        }
+    }
+
+    pub fn get(&self, video_id: &str) -> Option<CachedTranscript> {
+        self.files
+            .load::<CachedTranscript>(video_id)
+            .filter(|cached| cached.transcript.available)
+    }
+
+    pub fn put(&self, video_id: &str, cached: &CachedTranscript) -> Result<(), String> {
+        self.files.save(video_id, cached)
+    }
+
+    pub fn remove(&self, video_id: &str) -> Result<(), String> {
+        self.files.remove(video_id)
+    }
+}
+
+/// A search hit: the segment and where the query occurs in its text
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchMatch {
+    pub index: usize,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+}
+
+/// Segments containing `query`, case-insensitively, in time order
+pub fn search(segments: &[TranscriptSegment], query: &str) -> Vec<SearchMatch> {
+    let needle = query.trim().to_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    segments
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.text.to_lowercase().contains(&needle))
+        .map(|(index, s)| SearchMatch {
+            index,
+            start_ms: s.start_ms,
+            end_ms: s.end_ms,
+            text: s.text.clone(),
+        })
+        .collect()
+}
+
+/// Segments overlapping `[start_ms, end_ms)`; `None` bounds are open
+pub fn in_range(
+    segments: &[TranscriptSegment],
+    start_ms: Option<i64>,
+    end_ms: Option<i64>,
+) -> Vec<TranscriptSegment> {
+    segments
+        .iter()
+        .filter(|s| start_ms.is_none_or(|start| s.end_ms > start))
+        .filter(|s| end_ms.is_none_or(|end| s.start_ms < end))
+        .cloned()
+        .collect()
+}
+
+/// Subtitle formats `export_subtitles` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        match format.trim().to_lowercase().as_str() {
+            "" | "srt" => Ok(Self::Srt),
+            "vtt" | "webvtt" => Ok(Self::Vtt),
+            other => Err(format!(
+                "Unknown subtitle format '{}': expected srt or vtt",
+                other
+            )),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+        }
+    }
+}
+
+/// Render segments as SRT or WebVTT; speakers become a `Name: ` prefix
+pub fn to_subtitles(segments: &[TranscriptSegment], format: SubtitleFormat) -> String {
+    let mut out = String::new();
+    if format == SubtitleFormat::Vtt {
+        out.push_str("WEBVTT\n\n");
+    }
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+    for (i, segment) in segments.iter().enumerate() {
+        if format == SubtitleFormat::Srt {
+            out.push_str(&format!("{}\n", i + 1));
+        }
+        out.push_str(&format!(
+            "{} --> {}\n",
+            timestamp(segment.start_ms, separator),
+            timestamp(segment.end_ms.max(segment.start_ms), separator)
+        ));
+        if !segment.speaker.is_empty() {
+            out.push_str(&format!("{}: ", segment.speaker));
+        }
+        out.push_str(segment.text.trim());
+        out.push_str("\n\n");
+    }
+    out
+}
+
+/// `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT)
+fn timestamp(ms: i64, separator: char) -> String {
+    let ms = ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start_ms: i64, end_ms: i64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_search_and_range() {
+        let segments = vec![
+            segment(0, 2000, "A red square appears"),
+            segment(2000, 4000, "It moves right"),
+            segment(4000, 6000, "The RED square leaves"),
+        ];
+        let hits = search(&segments, " red ");
+        assert_eq!(hits.iter().map(|m| m.index).collect::<Vec<_>>(), vec![0, 2]);
+        assert!(search(&segments, "  ").is_empty());
+
+        assert_eq!(in_range(&segments, Some(2000), Some(4000)).len(), 1);
+        assert_eq!(in_range(&segments, Some(1999), None).len(), 3);
+        assert_eq!(in_range(&segments, None, Some(2000)).len(), 1);
+    }
+
+    #[test]
+    fn test_subtitles() {
+        let mut speaking = segment(3_723_004, 3_725_500, " Hello ");
+        speaking.speaker = "Ana".to_string();
+        let segments = vec![segment(0, 1500, "Intro"), speaking];
+
+        assert_eq!(
+            to_subtitles(&segments, SubtitleFormat::Srt),
+            "1\n00:00:00,000 --> 00:00:01,500\nIntro\n\n\
+             2\n01:02:03,004 --> 01:02:05,500\nAna: Hello\n\n"
+        );
+        assert!(to_subtitles(&segments, SubtitleFormat::Vtt)
+            .starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nIntro\n"));
+        assert_eq!(SubtitleFormat::parse("WebVTT"), Ok(SubtitleFormat::Vtt));
+        assert!(SubtitleFormat::parse("ass").is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! Bearer token for a backend that requires authentication
+//!
+//! Set by `set_auth_token` (or GRPC_AUTH_TOKEN), the token is sent as
+//! `authorization: Bearer <token>` on every call: by [`AuthInterceptor`]
+//! over gRPC and as a header over REST. A token the user asks to keep is
+//! stored in the OS credential store (Keychain, Credential Manager, Secret
+//! Service) rather than in the app's files, and loaded again on start.
+//! Each [`crate::accounts`] account has its own entry there, as do other
+//! secrets kept with [`credential`].
+
+use crate::config;
+use std::sync::RwLock;
+use tonic::metadata::MetadataValue;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Credential store entry the token is kept under
+const KEYRING_SERVICE: &str = "video-analyzer";
+const KEYRING_USER: &str = "backend-token";
+
+static TOKEN: RwLock<Option<String>> = RwLock::new(None);
+
+/// The account whose stored token is used, see [`use_account`]
+static ACCOUNT: RwLock<Option<String>> = RwLock::new(None);
+
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring_entry_for(ACCOUNT.read().unwrap().as_deref())
+}
+
+/// The entry for `account`; the default account keeps the entry tokens
+/// were stored under before there were accounts
+fn keyring_entry_for(account: Option<&str>) -> Result<keyring::Entry, String> {
+    entry(KEYRING_USER, account)
+}
+
+fn entry(name: &str, account: Option<&str>) -> Result<keyring::Entry, String> {
+    let user = match account {
+        Some(account) => format!("{}@{}", name, account),
+        None => name.to_string(),
+    };
+    keyring::Entry::new(KEYRING_SERVICE, &user)
+        .map_err(|e| format!("Credential store unavailable: {}", e))
+}
+
+/// The credential store entry called `name` of the account in use, for
+/// secrets other than the token
+pub fn credential(name: &str) -> Result<keyring::Entry, String> {
+    entry(name, ACCOUNT.read().unwrap().as_deref())
+}
+
+/// Store and load tokens under `account` (`None` for the default one);
+/// set once at start, before [`load_stored_token`]
+pub fn use_account(account: Option<String>) {
+    *ACCOUNT.write().unwrap() = account;
+}
+
+/// Keep `token` for `account` without using it now, for an account that
+/// isn't active yet
+pub fn store_token_for(account: Option<&str>, token: &str) -> Result<(), String> {
+    keyring_entry_for(account)?
+        .set_password(token)
+        .map_err(|e| format!("Failed to store the token: {}", e))
+}
+
+/// Use `token` from now on; `persist` also keeps it in the credential store
+///
+/// Returns whether it was persisted; if the store is unavailable the token
+/// still applies until the app quits.
+pub fn set_token(token: String, persist: bool) -> bool {
+    let persisted = persist
+        && keyring_entry()
+            .and_then(|entry| {
+                entry
+                    .set_password(&token)
+                    .map_err(|e| format!("Failed to store the token: {}", e))
+            })
+            .map_err(|e| log::warn!("{}", e))
+            .is_ok();
+    *TOKEN.write().unwrap() = Some(token);
+    persisted
+}
+
+/// Stop sending a token and remove any stored one
+pub fn clear_token() -> Result<(), String> {
+    *TOKEN.write().unwrap() = None;
+    match keyring_entry()?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("Failed to remove the stored token: {}", e)),
+    }
+}
+
+/// Whether the credential store can be used, or why not (e.g. a locked
+/// keyring, or none on this system)
+pub fn credential_store_status() -> Result<(), String> {
+    match keyring_entry()?.get_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) | Err(keyring::Error::BadEncoding(_)) => Ok(()),
+        Err(e) => Err(format!("Credential store unavailable: {}", e)),
+    }
+}
+
+/// Pick up a token stored by an earlier run
+pub fn load_stored_token() {
+    match keyring_entry().and_then(|entry| entry.get_password().map_err(|e| e.to_string())) {
+        Ok(token) => *TOKEN.write().unwrap() = Some(token),
+        Err(e) => log::debug!("No stored backend token: {}", e),
+    }
+}
+
+/// The token to send, if any
+pub fn token() -> Option<String> {
+    TOKEN
+        .read()
+        .unwrap()
+        .clone()
+        .or_else(|| config::get().grpc.auth_token.clone())
+}
+
+/// The `authorization` header value, if there is a token
+pub fn bearer() -> Option<String> {
+    token().map(|token| format!("Bearer {}", token))
+}
+
+/// Adds the bearer token to every gRPC request
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuthInterceptor;
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(bearer) = bearer() {
+            let value: MetadataValue<_> = bearer
+                .parse()
+                .map_err(|_| Status::unauthenticated("The auth token has invalid characters"))?;
+            request.metadata_mut().insert("authorization", value);
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interceptor_sends_the_bearer_token() {
+        assert!(!set_token("abc123".to_string(), false));
+        let request = AuthInterceptor.call(Request::new(())).unwrap();
+        assert_eq!(
+            request.metadata().get("authorization").unwrap(),
+            "Bearer abc123"
+        );
+
+        *TOKEN.write().unwrap() = None;
+        let request = AuthInterceptor.call(Request::new(())).unwrap();
+        assert!(request.metadata().get("authorization").is_none());
+    }
+}
@@ -0,0 +1,167 @@
+//! Client-side encryption of upload chunks
+//!
+//! For a backend reached through a relay that shouldn't see the videos:
+//! with [`GrpcConfig::upload_encryption_key`](config::GrpcConfig::upload_encryption_key)
+//! set, every chunk's `data` is sealed with AES-256-GCM under a fresh
+//! nonce before it leaves the client, and its `encryption` field tells the
+//! backend how to open it. The key is shared with the backend out of band;
+//! only its `key_id` travels with the chunks. A saved key is kept in the OS
+//! credential store like the auth token, never in `profile.json`, and only
+//! backends reporting [`compat::ENCRYPTION_PROTO_VERSION`](crate::compat::ENCRYPTION_PROTO_VERSION)
+//! are sent encrypted chunks. Sealed chunks go without
+//! their plaintext SHA-256s, which would fingerprint the video for the
+//! relay; the GCM tag already authenticates each one.
+
+use crate::config;
+use crate::video_analyzer::{ChunkEncryption, VideoChunk};
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use sha2::{Digest, Sha256};
+
+pub const ALGORITHM: &str = "aes-256-gcm";
+
+/// Credential store entry a saved key is kept under, see [`super::auth`]
+const KEYRING_NAME: &str = "upload-encryption-key";
+
+/// Keep `key` in the credential store for later starts
+pub fn store_key(key: &str) -> Result<(), String> {
+    super::auth::credential(KEYRING_NAME)?
+        .set_password(key)
+        .map_err(|e| format!("Failed to store the upload encryption key: {}", e))
+}
+
+/// Remove a stored key, if there is one
+pub fn clear_stored_key() -> Result<(), String> {
+    match super::auth::credential(KEYRING_NAME)?.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!(
+            "Failed to remove the stored upload encryption key: {}",
+            e
+        )),
+    }
+}
+
+/// The key stored by an earlier run, if any
+pub fn stored_key() -> Option<String> {
+    match super::auth::credential(KEYRING_NAME)
+        .and_then(|entry| entry.get_password().map_err(|e| e.to_string()))
+    {
+        Ok(key) => Some(key),
+        Err(e) => {
+            log::debug!("No stored upload encryption key: {}", e);
+            None
+        }
+    }
+}
+
+/// Seals upload chunks with one key
+pub struct ChunkCipher {
+    key: LessSafeKey,
+    key_id: String,
+}
+
+impl ChunkCipher {
+    /// A cipher for `key`, 32 bytes in base64
+    pub fn new(key: &str) -> Result<Self, String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(key.trim())
+            .map_err(|e| format!("The upload encryption key is not valid base64: {}", e))?;
+        let unbound = UnboundKey::new(&AES_256_GCM, &bytes).map_err(|_| {
+            format!(
+                "The upload encryption key must be 32 bytes, got {}",
+                bytes.len()
+            )
+        })?;
+        Ok(Self {
+            key: LessSafeKey::new(unbound),
+            key_id: hex::encode(&Sha256::digest(&bytes)[..8]),
+        })
+    }
+
+    /// The cipher for the configured key, if one is set
+    pub fn from_config() -> Result<Option<Self>, String> {
+        config::get()
+            .grpc
+            .upload_encryption_key
+            .as_deref()
+            .map(Self::new)
+            .transpose()
+    }
+
+    /// Bound to the chunk's position, so chunks can't be swapped
+    fn aad(chunk: &VideoChunk) -> String {
+        format!("{}:{}", chunk.chunk_index, chunk.offset)
+    }
+
+    /// Encrypt `chunk.data` in place, describe how in `chunk.encryption`
+    /// and drop its digests
+    pub fn seal(&self, chunk: &mut VideoChunk) {
+        chunk.sha256.clear();
+        chunk.file_sha256.clear();
+        chunk.source_sha256.clear();
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let aad = Self::aad(chunk);
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad.as_bytes()),
+                &mut chunk.data,
+            )
+            .expect("upload chunks are far below AES-GCM's size limit");
+        chunk.encryption = Some(ChunkEncryption {
+            algorithm: ALGORITHM.to_string(),
+            nonce: nonce.to_vec(),
+            key_id: self.key_id.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+
+    #[test]
+    fn sealed_chunks_open_with_the_same_key() {
+        let cipher = ChunkCipher::new(KEY).unwrap();
+        let mut chunk = VideoChunk {
+            data: b"frame data".to_vec(),
+            chunk_index: 3,
+            offset: 3 * 1024,
+            sha256: "plaintext digest".to_string(),
+            file_sha256: "file digest".to_string(),
+            source_sha256: "source digest".to_string(),
+            ..Default::default()
+        };
+        cipher.seal(&mut chunk);
+        assert!(chunk.sha256.is_empty() && chunk.file_sha256.is_empty());
+        assert!(chunk.source_sha256.is_empty());
+        let encryption = chunk.encryption.clone().unwrap();
+        assert_eq!(encryption.algorithm, ALGORITHM);
+        assert_eq!(encryption.key_id.len(), 16);
+        assert_ne!(chunk.data, b"frame data");
+
+        let nonce = Nonce::try_assume_unique_for_key(&encryption.nonce).unwrap();
+        let mut data = chunk.data.clone();
+        let plain = cipher
+            .key
+            .open_in_place(nonce, Aad::from(b"3:3072"), &mut data)
+            .unwrap();
+        assert_eq!(plain, b"frame data");
+
+        // Moved to another position, the chunk no longer opens
+        let nonce = Nonce::try_assume_unique_for_key(&encryption.nonce).unwrap();
+        let mut data = chunk.data.clone();
+        assert!(cipher
+            .key
+            .open_in_place(nonce, Aad::from(b"4:4096"), &mut data)
+            .is_err());
+    }
+
+    #[test]
+    fn keys_must_be_32_bytes() {
+        assert!(ChunkCipher::new("c2hvcnQ=").is_err());
+        assert!(ChunkCipher::new("not base64!").is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! An in-memory backend for tests
+//!
+//! [`FakeBackend`] takes uploads the way the real backend does: chunks are
+//! put together by `offset` under the idempotency key of the stream's
+//! first chunk, and the parts of a parallel upload count towards one file.
+//! Each stream can be held to `bytes_per_sec`, like one connection on a
+//! link it can't fill. An upload can be cut off once the backend has it
+//! all, like a connection dropped before the answer, and the backend can
+//! misreport what it stored. Calls the tests don't need are unimplemented.
+
+use super::{ChatStream, OfflineAssetStream, UploadStream, VideoAnalyzerTransport};
+use crate::uploads::sha256_hex;
+use crate::video_analyzer::{
+    AddCaptionsRequest, AddCaptionsResponse, BackendQueueRequest, BackendQueueResponse,
+    CancelProcessingRequest, CancelProcessingResponse, ChatRequest, CheckFileExistsRequest,
+    CheckFileExistsResponse, CleanupRequest, CleanupResponse, ClearHistoryRequest,
+    ClearHistoryResponse, DeleteVideoRequest, DeleteVideoResponse, DetectionsRequest,
+    DetectionsResponse, FeedbackRequest, FeedbackResponse, GetChatHistoryResponse,
+    GetHistoryRequest, LastSessionResponse, OfflineAssetsRequest, RegisterVideoRequest,
+    RegisterVideoResponse, ResumeRequest, ResumeResponse, ServerInfo, ShareLinkRequest,
+    ShareLinkResponse, StorageUsageResponse, TimelineRequest, TimelineResponse, TranscriptRequest,
+    TranscriptResponse, UpdateAnalysisOptionsRequest, UpdateAnalysisOptionsResponse,
+    UpdateVideoMetadataRequest, UpdateVideoMetadataResponse, UploadOffsetRequest,
+    UploadOffsetResponse, UploadResponse, UploadStatusRequest, UploadStatusResponse,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+use tonic::{Code, Status};
+
+/// What the fake backend has and how it answers
+pub struct FakeBackend {
+    /// Per-stream cap on uploads, 0 for none
+    pub bytes_per_sec: u64,
+    /// CheckFileExists' answer: the video with the content, or a failure;
+    /// `Ok(None)` looks through what was uploaded
+    pub file_exists: Result<Option<String>, Code>,
+    /// SHA-256 reported for every stored upload instead of its own
+    pub digest: Option<String>,
+    /// Whether the next upload fails as Unavailable after it is stored
+    cut_off: AtomicBool,
+    uploads: Mutex<HashMap<String, Upload>>,
+}
+
+#[derive(Default)]
+struct Upload {
+    data: Vec<u8>,
+    received: u64,
+    /// Set by the parts of a parallel upload
+    file_size: Option<u64>,
+    /// Digest of the picked file a converted copy stands in for
+    source_sha256: String,
+}
+
+impl Upload {
+    fn response(&self) -> UploadResponse {
+        UploadResponse {
+            file_id: "fake-video".to_string(),
+            success: true,
+            message: String::new(),
+            sha256: sha256_hex(&self.data),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.file_size.is_none_or(|size| self.received >= size)
+    }
+}
+
+impl Default for FakeBackend {
+    fn default() -> Self {
+        Self {
+            bytes_per_sec: 0,
+            file_exists: Err(Code::Unimplemented),
+            digest: None,
+            cut_off: AtomicBool::new(false),
+            uploads: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl FakeBackend {
+    /// A backend each upload stream reaches at `bytes_per_sec`
+    pub fn limited(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec,
+            ..Default::default()
+        }
+    }
+
+    /// A backend answering CheckFileExists with `file_exists`
+    pub fn answering(file_exists: Result<Option<String>, Code>) -> Self {
+        Self {
+            file_exists,
+            ..Default::default()
+        }
+    }
+
+    /// A backend reporting `sha256` for whatever it stored
+    pub fn reporting(sha256: &str) -> Self {
+        Self {
+            digest: Some(sha256.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Drop the connection of the next upload once it is stored
+    pub fn cut_off_next_upload(&self) {
+        self.cut_off.store(true, Ordering::SeqCst);
+    }
+
+    fn response(&self, upload: &Upload) -> UploadResponse {
+        let mut response = upload.response();
+        if let Some(digest) = &self.digest {
+            response.sha256 = digest.clone();
+        }
+        response
+    }
+
+    /// The file stored under `idempotency_key`, if one was uploaded
+    pub fn stored(&self, idempotency_key: &str) -> Option<Vec<u8>> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .get(idempotency_key)
+            .map(|upload| upload.data.clone())
+    }
+}
+
+fn unimplemented(rpc: &str) -> Status {
+    Status::unimplemented(format!("The fake backend has no {}", rpc))
+}
+
+#[async_trait::async_trait]
+impl VideoAnalyzerTransport for FakeBackend {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    fn endpoint(&self) -> String {
+        "fake://".to_string()
+    }
+
+    async fn upload_video(&self, mut chunks: UploadStream) -> Result<UploadResponse, Status> {
+        let mut key = String::new();
+        while let Some(chunk) = chunks.next().await {
+            if self.bytes_per_sec > 0 {
+                let secs = chunk.data.len() as f64 / self.bytes_per_sec as f64;
+                tokio::time::sleep(Duration::from_secs_f64(secs)).await;
+            }
+            if !chunk.idempotency_key.is_empty() {
+                key = chunk.idempotency_key.clone();
+            }
+            let mut uploads = self.uploads.lock().unwrap();
+            let upload = uploads.entry(key.clone()).or_default();
+            let start = chunk.offset as usize;
+            let end = start + chunk.data.len();
+            if upload.data.len() < end {
+                upload.data.resize(end, 0);
+            }
+            upload.data[start..end].copy_from_slice(&chunk.data);
+            upload.received += chunk.data.len() as u64;
+            if chunk.stream_count > 0 {
+                upload.file_size = Some(chunk.file_size as u64);
+            }
+            if !chunk.source_sha256.is_empty() {
+                upload.source_sha256 = chunk.source_sha256.clone();
+            }
+        }
+        if self.cut_off.swap(false, Ordering::SeqCst) {
+            return Err(Status::unavailable("Connection reset"));
+        }
+        let uploads = self.uploads.lock().unwrap();
+        let upload = uploads
+            .get(&key)
+            .ok_or_else(|| Status::invalid_argument("Nothing to upload"))?;
+        Ok(if upload.is_complete() {
+            self.response(upload)
+        } else {
+            // Another part completes the file
+            UploadResponse {
+                success: true,
+                ..Default::default()
+            }
+        })
+    }
+
+    async fn get_upload_status(
+        &self,
+        request: UploadStatusRequest,
+    ) -> Result<UploadStatusResponse, Status> {
+        let uploads = self.uploads.lock().unwrap();
+        let upload = uploads.get(&request.idempotency_key);
+        Ok(UploadStatusResponse {
+            completed: upload.is_some_and(Upload::is_complete),
+            response: upload.filter(|u| u.is_complete()).map(|u| self.response(u)),
+            bytes_received: upload.map_or(0, |u| u.received as i64),
+        })
+    }
+
+    async fn check_file_exists(
+        &self,
+        request: CheckFileExistsRequest,
+    ) -> Result<CheckFileExistsResponse, Status> {
+        let uploaded = || {
+            let uploads = self.uploads.lock().unwrap();
+            uploads
+                .values()
+                .filter(|upload| upload.is_complete())
+                .find(|upload| {
+                    upload.source_sha256 == request.sha256
+                        || sha256_hex(&upload.data) == request.sha256
+                })
+                .map(|upload| upload.response().file_id)
+        };
+        match &self.file_exists {
+            Ok(None) => {
+                let file_id = uploaded();
+                Ok(CheckFileExistsResponse {
+                    exists: file_id.is_some(),
+                    file_id: file_id.unwrap_or_default(),
+                    display_name: String::new(),
+                })
+            }
+            Ok(file_id) => Ok(CheckFileExistsResponse {
+                exists: file_id.is_some(),
+                file_id: file_id.clone().unwrap_or_default(),
+                display_name: String::new(),
+            }),
+            Err(code) => Err(Status::new(*code, "CheckFileExists failed")),
+        }
+    }
+
+    async fn negotiate_upload_offset(
+        &self,
+        _: UploadOffsetRequest,
+    ) -> Result<UploadOffsetResponse, Status> {
+        Err(unimplemented("negotiate_upload_offset"))
+    }
+
+    async fn register_local_video(
+        &self,
+        _: RegisterVideoRequest,
+    ) -> Result<RegisterVideoResponse, Status> {
+        Err(unimplemented("register_local_video"))
+    }
+
+    async fn update_analysis_options(
+        &self,
+        _: UpdateAnalysisOptionsRequest,
+    ) -> Result<UpdateAnalysisOptionsResponse, Status> {
+        Err(unimplemented("update_analysis_options"))
+    }
+
+    async fn get_storage_usage(&self) -> Result<StorageUsageResponse, Status> {
+        Err(unimplemented("get_storage_usage"))
+    }
+
+    async fn cleanup_videos(&self, _: CleanupRequest) -> Result<CleanupResponse, Status> {
+        Err(unimplemented("cleanup_videos"))
+    }
+
+    async fn get_offline_assets(
+        &self,
+        _: OfflineAssetsRequest,
+    ) -> Result<OfflineAssetStream, Status> {
+        Err(unimplemented("get_offline_assets"))
+    }
+
+    async fn get_transcript(&self, _: TranscriptRequest) -> Result<TranscriptResponse, Status> {
+        Err(unimplemented("get_transcript"))
+    }
+
+    async fn get_detections(&self, _: DetectionsRequest) -> Result<DetectionsResponse, Status> {
+        Err(unimplemented("get_detections"))
+    }
+
+    async fn get_timeline(&self, _: TimelineRequest) -> Result<TimelineResponse, Status> {
+        Err(unimplemented("get_timeline"))
+    }
+
+    async fn create_share_link(&self, _: ShareLinkRequest) -> Result<ShareLinkResponse, Status> {
+        Err(unimplemented("create_share_link"))
+    }
+
+    async fn send_chat_message(&self, _: ChatRequest) -> Result<ChatStream, Status> {
+        Err(unimplemented("send_chat_message"))
+    }
+
+    async fn submit_feedback(&self, _: FeedbackRequest) -> Result<FeedbackResponse, Status> {
+        Err(unimplemented("submit_feedback"))
+    }
+
+    async fn get_chat_history(
+        &self,
+        _: GetHistoryRequest,
+    ) -> Result<GetChatHistoryResponse, Status> {
+        Err(unimplemented("get_chat_history"))
+    }
+
+    async fn clear_chat_history(
+        &self,
+        _: ClearHistoryRequest,
+    ) -> Result<ClearHistoryResponse, Status> {
+        Err(unimplemented("clear_chat_history"))
+    }
+
+    async fn get_last_session(&self) -> Result<LastSessionResponse, Status> {
+        Err(unimplemented("get_last_session"))
+    }
+
+    async fn resume_session(&self, _: ResumeRequest) -> Result<ResumeResponse, Status> {
+        Err(unimplemented("resume_session"))
+    }
+
+    async fn get_backend_queue(
+        &self,
+        _: BackendQueueRequest,
+    ) -> Result<BackendQueueResponse, Status> {
+        Err(unimplemented("get_backend_queue"))
+    }
+
+    async fn cancel_processing(
+        &self,
+        _: CancelProcessingRequest,
+    ) -> Result<CancelProcessingResponse, Status> {
+        Err(unimplemented("cancel_processing"))
+    }
+
+    async fn delete_video(&self, _: DeleteVideoRequest) -> Result<DeleteVideoResponse, Status> {
+        Err(unimplemented("delete_video"))
+    }
+
+    async fn update_video_metadata(
+        &self,
+        _: UpdateVideoMetadataRequest,
+    ) -> Result<UpdateVideoMetadataResponse, Status> {
+        Err(unimplemented("update_video_metadata"))
+    }
+
+    async fn add_captions(&self, _: AddCaptionsRequest) -> Result<AddCaptionsResponse, Status> {
+        Err(unimplemented("add_captions"))
+    }
+
+    async fn get_server_info(&self) -> Result<ServerInfo, Status> {
+        Err(unimplemented("get_server_info"))
+    }
+}
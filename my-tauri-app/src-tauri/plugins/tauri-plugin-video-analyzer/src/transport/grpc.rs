@@ -0,0 +1,701 @@
+//! [`VideoAnalyzerTransport`] over gRPC, the backend's native protocol
+//!
+//! Every call shares one HTTP/2 channel, held by [`GrpcClientManager`] in
+//! managed state, instead of opening a connection per call. The channel
+//! is TLS when [`GrpcConfig::tls`](config::GrpcConfig::tls) says so, e.g. for an `https://` backend,
+//! with the settings from [`super::tls`]. Every call carries the auth token
+//! from [`super::auth`], if one is set, and goes through the proxy from
+//! [`super::proxy`] when one is configured.
+
+use super::auth::AuthInterceptor;
+use super::crypto::ChunkCipher;
+use super::local::{self, LocalConnector};
+use super::proxy::{proxy_for, redacted, ProxyConnector};
+use super::tls::{is_certificate_failure, tls_config, CertificateError, CertificateErrorKind};
+use super::{ChatStream, OfflineAssetStream, UploadStream, VideoAnalyzerTransport};
+use crate::compat;
+use crate::config::{self, CallKind, Compression};
+use crate::video_analyzer::{
+    video_analyzer_service_client::VideoAnalyzerServiceClient, AddCaptionsRequest,
+    AddCaptionsResponse, BackendQueueRequest, BackendQueueResponse, CancelProcessingRequest,
+    CancelProcessingResponse, ChatRequest, CheckFileExistsRequest, CheckFileExistsResponse,
+    CleanupRequest, CleanupResponse, ClearHistoryRequest, ClearHistoryResponse, DeleteVideoRequest,
+    DeleteVideoResponse, DetectionsRequest, DetectionsResponse, Empty, FeedbackRequest,
+    FeedbackResponse, GetChatHistoryResponse, GetHistoryRequest, LastSessionResponse,
+    OfflineAssetsRequest, RegisterVideoRequest, RegisterVideoResponse, ResumeRequest,
+    ResumeResponse, ServerInfo, ShareLinkRequest, ShareLinkResponse, StorageUsageResponse,
+    TimelineRequest, TimelineResponse, TranscriptRequest, TranscriptResponse,
+    UpdateAnalysisOptionsRequest, UpdateAnalysisOptionsResponse, UpdateVideoMetadataRequest,
+    UpdateVideoMetadataResponse, UploadOffsetRequest, UploadOffsetResponse, UploadResponse,
+    UploadStatusRequest, UploadStatusResponse,
+};
+use log::{info, warn};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+
+/// Client for the backend service, sending the auth token on every call
+pub type ServiceClient = VideoAnalyzerServiceClient<InterceptedService<Channel, AuthInterceptor>>;
+
+/// A client on `channel` with the compression from GRPC_COMPRESSION
+fn service_client(channel: Channel) -> ServiceClient {
+    let client = VideoAnalyzerServiceClient::with_interceptor(channel, AuthInterceptor)
+        .accept_compressed(CompressionEncoding::Gzip);
+    match config::get().grpc.compression {
+        Compression::Gzip => client.send_compressed(CompressionEncoding::Gzip),
+        Compression::None => client,
+    }
+}
+
+/// `message` as a request with the deadline for `kind`, see
+/// [`super::call_timeout`]
+fn with_deadline<T>(message: T, kind: CallKind) -> Request<T> {
+    let mut request = Request::new(message);
+    if let Some(timeout) = super::call_timeout(kind) {
+        request.set_timeout(timeout);
+    }
+    request
+}
+
+/// `url` with an `https://` scheme, which tonic needs before it will use TLS
+fn https_url(url: &str) -> String {
+    match url.strip_prefix("http://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    }
+}
+
+async fn connect_channel() -> Result<Channel, Status> {
+    let config = config::get();
+    connect_to(&config.grpc.server_url, config.grpc.tls).await
+}
+
+/// A channel to `server_url`, with the TLS settings from the config when
+/// `tls` is on
+///
+/// `server_url` may also name a local socket or pipe, see [`super::local`].
+async fn connect_to(server_url: &str, mut tls: bool) -> Result<Channel, Status> {
+    let local = local::parse(server_url)
+        .transpose()
+        .map_err(Status::invalid_argument)?;
+    if local.is_some() && tls {
+        warn!("Ignoring TLS for the local backend at {}", server_url);
+        tls = false;
+    }
+    let mut server_url = server_url.to_string();
+    if tls {
+        server_url = https_url(&server_url);
+    }
+    info!(
+        "Connecting to gRPC server at {}{}",
+        server_url,
+        if tls { " over TLS" } else { "" }
+    );
+    // Over a local socket the URI only supplies the HTTP/2 authority
+    let endpoint_url = match local {
+        Some(_) => "http://localhost".to_string(),
+        None => server_url.clone(),
+    };
+    let mut endpoint = Endpoint::from_shared(endpoint_url)
+        .map_err(|e| {
+            Status::invalid_argument(format!("Invalid gRPC server URL {}: {}", server_url, e))
+        })?
+        .connect_timeout(Duration::from_secs(config::get().grpc.connect_timeout_secs));
+    if tls {
+        endpoint = endpoint
+            .tls_config(tls_config().map_err(CertificateError::into_status)?)
+            .map_err(|e| {
+                CertificateError::config(format!("Invalid TLS settings for {}: {}", server_url, e))
+                    .into_status()
+            })?;
+    }
+    let keepalive = config::get().grpc.keepalive_interval_secs;
+    if keepalive > 0 {
+        endpoint = endpoint
+            .http2_keep_alive_interval(Duration::from_secs(keepalive))
+            .keep_alive_while_idle(true);
+    }
+    let proxy = proxy_for(&server_url).map_err(Status::invalid_argument)?;
+    let connected = match (local, proxy) {
+        (Some(address), _) => {
+            endpoint
+                .connect_with_connector(LocalConnector::new(address))
+                .await
+        }
+        (None, Some(proxy)) => {
+            info!("Tunnelling through proxy {}", redacted(&proxy));
+            endpoint
+                .connect_with_connector(ProxyConnector::new(proxy))
+                .await
+        }
+        (None, None) => endpoint.connect().await,
+    };
+    connected.map_err(|e| {
+        let message = format!("Failed to connect to gRPC server at {}: {}", server_url, e);
+        if tls && is_certificate_failure(&e) {
+            CertificateError {
+                kind: CertificateErrorKind::Handshake,
+                message,
+            }
+            .into_status()
+        } else {
+            Status::unavailable(message)
+        }
+    })
+}
+
+/// Whether the channel can still take requests
+async fn healthy(channel: &mut Channel) -> bool {
+    use tower_service::Service;
+
+    let ready = std::future::poll_fn(|cx| channel.poll_ready(cx));
+    tokio::time::timeout(Duration::from_secs(1), ready)
+        .await
+        .is_ok_and(|ready| ready.is_ok())
+}
+
+/// Longest wait between two connection attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// Wait before reconnect attempt `attempt` (from 1): doubling from 250 ms
+/// up to [`MAX_RECONNECT_DELAY`], with the upper half jittered so clients
+/// cut off together don't all come back at once
+fn reconnect_delay(attempt: u32) -> Duration {
+    let ceiling = (Duration::from_millis(250) * 2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RECONNECT_DELAY);
+    let half = ceiling / 2;
+    half + half.mul_f64(rand::random::<f64>())
+}
+
+/// A change in the connection, see [`GrpcClientManager::subscribe`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum ConnectionEvent {
+    /// Connecting failed; trying again after `delay_ms`
+    Reconnecting {
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+        error: String,
+    },
+    /// A new channel is up; `connects` counts them, so above 1 it replaced
+    /// a lost one
+    Connected { connects: u64 },
+}
+
+/// Ask the backend at `server_url` for its last session on a channel of
+/// its own, sending `token` instead of the configured one
+///
+/// For trying a backend out before switching to it, e.g. in the setup
+/// wizard; the shared channel is left alone.
+pub async fn probe(
+    server_url: &str,
+    tls: bool,
+    token: Option<&str>,
+) -> Result<LastSessionResponse, Status> {
+    let channel = connect_to(server_url, tls).await?;
+    let mut request = with_deadline(Empty {}, CallKind::Default);
+    if let Some(token) = token {
+        let bearer = format!("Bearer {}", token)
+            .parse()
+            .map_err(|_| Status::unauthenticated("The auth token has invalid characters"))?;
+        request.metadata_mut().insert("authorization", bearer);
+    }
+    let response = VideoAnalyzerServiceClient::new(channel)
+        .get_last_session(request)
+        .await?;
+    Ok(response.into_inner())
+}
+
+/// Managed state: the gRPC channel shared by every call
+///
+/// Connected on first use and reused after that. A channel that fails its
+/// readiness check, or whose last call came back `Unavailable`, is
+/// replaced by a fresh connection on the next call. While the backend is
+/// unreachable, e.g. restarting, connecting is retried with exponential
+/// backoff up to [`GrpcConfig::reconnect_max_attempts`](config::GrpcConfig::reconnect_max_attempts) times, so the call
+/// waits for it to come back instead of failing. A call that fails after
+/// it was sent is not retried here, since it may not be safe to repeat.
+#[derive(Debug)]
+pub struct GrpcClientManager {
+    channel: tokio::sync::Mutex<Option<Channel>>,
+    broken: AtomicBool,
+    connects: AtomicU64,
+    events: broadcast::Sender<ConnectionEvent>,
+}
+
+impl Default for GrpcClientManager {
+    fn default() -> Self {
+        Self {
+            channel: tokio::sync::Mutex::default(),
+            broken: AtomicBool::default(),
+            connects: AtomicU64::default(),
+            events: broadcast::channel(16).0,
+        }
+    }
+}
+
+impl GrpcClientManager {
+    /// Connection changes from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Connect, retrying while the backend is unreachable
+    async fn connect(&self) -> Result<Channel, Status> {
+        let max_attempts = config::get().grpc.reconnect_max_attempts;
+        let mut attempt = 0;
+        let channel = loop {
+            match connect_channel().await {
+                Ok(channel) => break channel,
+                Err(status)
+                    if status.code() == tonic::Code::Unavailable && attempt < max_attempts =>
+                {
+                    attempt += 1;
+                    let delay = reconnect_delay(attempt);
+                    warn!(
+                        "{}; reconnecting in {:?} ({}/{})",
+                        status.message(),
+                        delay,
+                        attempt,
+                        max_attempts
+                    );
+                    self.events
+                        .send(ConnectionEvent::Reconnecting {
+                            attempt,
+                            max_attempts,
+                            delay_ms: delay.as_millis() as u64,
+                            error: status.message().to_string(),
+                        })
+                        .ok();
+                    tokio::time::sleep(delay).await;
+                }
+                Err(status) => return Err(status),
+            }
+        };
+        let connects = self.connects.fetch_add(1, Ordering::SeqCst) + 1;
+        self.events
+            .send(ConnectionEvent::Connected { connects })
+            .ok();
+        Ok(channel)
+    }
+
+    /// A client on the shared channel, (re)connecting if needed
+    pub async fn client(&self) -> Result<ServiceClient, Status> {
+        let mut cached = self.channel.lock().await;
+        if self.broken.swap(false, Ordering::SeqCst) {
+            *cached = None;
+        }
+        if let Some(channel) = cached.as_mut() {
+            if healthy(channel).await {
+                return Ok(service_client(channel.clone()));
+            }
+            warn!("gRPC channel is broken, reconnecting");
+        }
+        let channel = self.connect().await?;
+        *cached = Some(channel.clone());
+        Ok(service_client(channel))
+    }
+
+    /// Drop the channel so the next call connects afresh, e.g. after the
+    /// backend URL changed
+    pub fn reset(&self) {
+        self.broken.store(true, Ordering::SeqCst);
+    }
+
+    /// Pass a call's error through, noting when the channel needs replacing
+    pub fn observe(&self, status: Status) -> Status {
+        if status.code() == tonic::Code::Unavailable {
+            self.broken.store(true, Ordering::SeqCst);
+        }
+        status
+    }
+
+    /// Connections made so far; more than one means the channel was replaced
+    pub fn connects(&self) -> u64 {
+        self.connects.load(Ordering::SeqCst)
+    }
+}
+
+/// The Python backend over gRPC at GRPC_SERVER_URL
+#[derive(Debug, Default)]
+pub struct GrpcTransport {
+    clients: Arc<GrpcClientManager>,
+}
+
+impl GrpcTransport {
+    pub fn new(clients: Arc<GrpcClientManager>) -> Self {
+        Self { clients }
+    }
+
+    async fn client(&self) -> Result<ServiceClient, Status> {
+        self.clients.client().await
+    }
+
+    /// Refuse to encrypt uploads to a backend that doesn't say it can
+    /// decrypt them
+    async fn check_encryption_support(&self) -> Result<(), Status> {
+        let reported = match self.get_server_info().await {
+            Ok(info) => info.proto_version,
+            Err(status) if status.code() == tonic::Code::Unimplemented => 0,
+            Err(status) => return Err(status),
+        };
+        if reported >= compat::ENCRYPTION_PROTO_VERSION {
+            return Ok(());
+        }
+        Err(Status::failed_precondition(format!(
+            "The backend doesn't support encrypted uploads (protocol {} or later, it reports {}); update it, or remove the upload encryption key",
+            compat::ENCRYPTION_PROTO_VERSION, reported
+        )))
+    }
+}
+
+#[async_trait::async_trait]
+impl VideoAnalyzerTransport for GrpcTransport {
+    fn name(&self) -> &'static str {
+        "grpc"
+    }
+
+    fn endpoint(&self) -> String {
+        config::get().grpc.server_url.clone()
+    }
+
+    async fn upload_video(&self, chunks: UploadStream) -> Result<UploadResponse, Status> {
+        let cipher = ChunkCipher::from_config().map_err(Status::failed_precondition)?;
+        if cipher.is_some() {
+            self.check_encryption_support().await?;
+        }
+        let chunks = chunks.map(move |mut chunk| {
+            if let Some(cipher) = &cipher {
+                cipher.seal(&mut chunk);
+            }
+            chunk
+        });
+        let response = self
+            .client()
+            .await?
+            .upload_video(with_deadline(chunks, CallKind::Upload))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_upload_status(
+        &self,
+        request: UploadStatusRequest,
+    ) -> Result<UploadStatusResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_upload_status(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn negotiate_upload_offset(
+        &self,
+        request: UploadOffsetRequest,
+    ) -> Result<UploadOffsetResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .negotiate_upload_offset(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn check_file_exists(
+        &self,
+        request: CheckFileExistsRequest,
+    ) -> Result<CheckFileExistsResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .check_file_exists(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn register_local_video(
+        &self,
+        request: RegisterVideoRequest,
+    ) -> Result<RegisterVideoResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .register_local_video(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn update_analysis_options(
+        &self,
+        request: UpdateAnalysisOptionsRequest,
+    ) -> Result<UpdateAnalysisOptionsResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .update_analysis_options(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_storage_usage(&self) -> Result<StorageUsageResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_storage_usage(with_deadline(Empty {}, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn cleanup_videos(&self, request: CleanupRequest) -> Result<CleanupResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .cleanup_videos(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_offline_assets(
+        &self,
+        request: OfflineAssetsRequest,
+    ) -> Result<OfflineAssetStream, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_offline_assets(with_deadline(request, CallKind::Upload))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(Box::pin(response.into_inner()))
+    }
+
+    async fn get_transcript(
+        &self,
+        request: TranscriptRequest,
+    ) -> Result<TranscriptResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_transcript(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_detections(
+        &self,
+        request: DetectionsRequest,
+    ) -> Result<DetectionsResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_detections(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_timeline(&self, request: TimelineRequest) -> Result<TimelineResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_timeline(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn create_share_link(
+        &self,
+        request: ShareLinkRequest,
+    ) -> Result<ShareLinkResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .create_share_link(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn send_chat_message(&self, request: ChatRequest) -> Result<ChatStream, Status> {
+        let response = self
+            .client()
+            .await?
+            .send_chat_message(with_deadline(request, CallKind::Chat))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(Box::pin(response.into_inner()))
+    }
+
+    async fn submit_feedback(&self, request: FeedbackRequest) -> Result<FeedbackResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .submit_feedback(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_chat_history(
+        &self,
+        request: GetHistoryRequest,
+    ) -> Result<GetChatHistoryResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_chat_history(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn clear_chat_history(
+        &self,
+        request: ClearHistoryRequest,
+    ) -> Result<ClearHistoryResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .clear_chat_history(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_last_session(&self) -> Result<LastSessionResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_last_session(with_deadline(Empty {}, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn resume_session(&self, request: ResumeRequest) -> Result<ResumeResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .resume_session(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_backend_queue(
+        &self,
+        request: BackendQueueRequest,
+    ) -> Result<BackendQueueResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_backend_queue(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn cancel_processing(
+        &self,
+        request: CancelProcessingRequest,
+    ) -> Result<CancelProcessingResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .cancel_processing(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn delete_video(
+        &self,
+        request: DeleteVideoRequest,
+    ) -> Result<DeleteVideoResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .delete_video(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn update_video_metadata(
+        &self,
+        request: UpdateVideoMetadataRequest,
+    ) -> Result<UpdateVideoMetadataResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .update_video_metadata(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn add_captions(
+        &self,
+        request: AddCaptionsRequest,
+    ) -> Result<AddCaptionsResponse, Status> {
+        let response = self
+            .client()
+            .await?
+            .add_captions(with_deadline(request, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+
+    async fn get_server_info(&self) -> Result<ServerInfo, Status> {
+        let response = self
+            .client()
+            .await?
+            .get_server_info(with_deadline(Empty {}, CallKind::Default))
+            .await
+            .map_err(|status| self.clients.observe(status))?;
+        Ok(response.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_the_cap() {
+        for attempt in 1..=10 {
+            let ceiling =
+                (Duration::from_millis(250) * 2u32.pow(attempt - 1)).min(MAX_RECONNECT_DELAY);
+            let delay = reconnect_delay(attempt);
+            assert!(
+                delay >= ceiling / 2 && delay <= ceiling,
+                "{attempt}: {delay:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_https_url() {
+        assert_eq!(https_url("http://backend:50051"), "https://backend:50051");
+        assert_eq!(https_url("https://backend:50051"), "https://backend:50051");
+    }
+}
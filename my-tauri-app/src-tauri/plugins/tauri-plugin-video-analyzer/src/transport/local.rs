@@ -0,0 +1,150 @@
+//! Reaching a backend on the same machine without TCP
+//!
+//! A server URL of `unix:///tmp/analyzer.sock` connects over that Unix
+//! domain socket, and `pipe://analyzer` over the Windows named pipe
+//! `\\.\pipe\analyzer`, so a local backend needs no free port. HTTP/2 runs
+//! over the socket as usual; TLS and proxies don't apply.
+
+use std::io;
+use std::path::PathBuf;
+use std::task::{Context, Poll};
+use std::{future::Future, pin::Pin};
+use tonic::transport::Uri;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalAddress {
+    Unix(PathBuf),
+    /// Full pipe name, `\\.\pipe\<name>`
+    Pipe(String),
+}
+
+impl std::fmt::Display for LocalAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "Unix socket {}", path.display()),
+            Self::Pipe(name) => write!(f, "named pipe {}", name),
+        }
+    }
+}
+
+/// The socket or pipe `server_url` names, or `None` for a network URL
+pub fn parse(server_url: &str) -> Option<Result<LocalAddress, String>> {
+    let server_url = server_url.trim();
+    if let Some(path) = server_url.strip_prefix("unix:") {
+        // unix:///abs/path, unix:/abs/path or unix:relative/path
+        let path = path.strip_prefix("//").unwrap_or(path);
+        return Some(if path.is_empty() {
+            Err(format!("{} names no socket file", server_url))
+        } else {
+            Ok(LocalAddress::Unix(PathBuf::from(path)))
+        });
+    }
+    if let Some(name) = server_url.strip_prefix("pipe:") {
+        // pipe://analyzer or pipe:////./pipe/analyzer
+        let name = name.trim_start_matches('/');
+        let name = name.strip_prefix("./pipe/").unwrap_or(name);
+        return Some(if name.is_empty() || name.contains(['/', '\\']) {
+            Err(format!("{} is not a valid pipe name", server_url))
+        } else {
+            Ok(LocalAddress::Pipe(format!(r"\\.\pipe\{}", name)))
+        });
+    }
+    None
+}
+
+#[cfg(unix)]
+pub type LocalStream = tokio::net::UnixStream;
+#[cfg(windows)]
+pub type LocalStream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+fn unsupported(address: &LocalAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("A {} can't be used on this platform", address),
+    )
+}
+
+/// Open a connection to `address`
+#[cfg(unix)]
+pub async fn connect(address: &LocalAddress) -> io::Result<LocalStream> {
+    match address {
+        LocalAddress::Unix(path) => tokio::net::UnixStream::connect(path).await,
+        LocalAddress::Pipe(_) => Err(unsupported(address)),
+    }
+}
+
+/// Open a connection to `address`
+#[cfg(windows)]
+pub async fn connect(address: &LocalAddress) -> io::Result<LocalStream> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+    // Every instance of the pipe is in use; one frees up as soon as the
+    // server accepts its pending connection
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    let LocalAddress::Pipe(name) = address else {
+        return Err(unsupported(address));
+    };
+    loop {
+        match ClientOptions::new().open(name) {
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await
+            }
+            opened => return opened,
+        }
+    }
+}
+
+/// Connector for [`tonic::transport::Endpoint::connect_with_connector`]
+/// that ignores the endpoint URI and connects to a local socket or pipe
+#[derive(Debug, Clone)]
+pub struct LocalConnector {
+    address: LocalAddress,
+}
+
+impl LocalConnector {
+    pub fn new(address: LocalAddress) -> Self {
+        Self { address }
+    }
+}
+
+impl tower_service::Service<Uri> for LocalConnector {
+    type Response = LocalStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<LocalStream>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _uri: Uri) -> Self::Future {
+        let address = self.address.clone();
+        Box::pin(async move { connect(&address).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn socket_and_pipe_urls_are_recognised() {
+        assert_eq!(
+            parse("unix:///tmp/analyzer.sock").unwrap().unwrap(),
+            LocalAddress::Unix(PathBuf::from("/tmp/analyzer.sock"))
+        );
+        assert_eq!(
+            parse("unix:analyzer.sock").unwrap().unwrap(),
+            LocalAddress::Unix(PathBuf::from("analyzer.sock"))
+        );
+        assert_eq!(
+            parse("pipe://analyzer").unwrap().unwrap(),
+            LocalAddress::Pipe(r"\\.\pipe\analyzer".to_string())
+        );
+        assert_eq!(
+            parse("pipe:////./pipe/analyzer").unwrap().unwrap(),
+            LocalAddress::Pipe(r"\\.\pipe\analyzer".to_string())
+        );
+        assert!(parse("unix://").unwrap().is_err());
+        assert!(parse("http://127.0.0.1:50051").is_none());
+    }
+}
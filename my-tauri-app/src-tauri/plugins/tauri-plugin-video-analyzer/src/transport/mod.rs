@@ -0,0 +1,191 @@
+//! How the plugin reaches the backend
+//!
+//! Commands and background tasks never build a tonic client themselves:
+//! they call the [`VideoAnalyzerTransport`] held in managed state as
+//! [`Transport`]. Two implementations ship with the plugin, picked by
+//! BACKEND_TRANSPORT (or `transport` in the plugin config):
+//!
+//! - `grpc` (default): [`grpc::GrpcTransport`], straight to the Python backend
+//! - `rest`: [`rest::RestTransport`], through a REST+SSE gateway for
+//!   networks that block HTTP/2
+//!
+//! Anything else (a mock, gRPC-web) only has to implement the trait and be
+//! handed to [`crate::Builder::transport`]. In dev mode, whichever it is
+//! goes through [`simulate::SimulatedTransport`] so QA can slow it down
+//! and make it fail.
+//!
+//! Requests and responses are the proto messages whatever the wire format,
+//! and failures are reported as [`tonic::Status`] so callers can still tell
+//! e.g. an unsupported call (`Unimplemented`) from an unreachable backend
+//! (`Unavailable`). Calls that outlive their deadline (see
+//! [`call_timeout`]) fail with `DeadlineExceeded`.
+
+use crate::config::{self, CallKind};
+use crate::video_analyzer::{
+    AddCaptionsRequest, AddCaptionsResponse, BackendQueueRequest, BackendQueueResponse,
+    CancelProcessingRequest, CancelProcessingResponse, ChatRequest, ChatResponse,
+    CheckFileExistsRequest, CheckFileExistsResponse, CleanupRequest, CleanupResponse,
+    ClearHistoryRequest, ClearHistoryResponse, DeleteVideoRequest, DeleteVideoResponse,
+    DetectionsRequest, DetectionsResponse, FeedbackRequest, FeedbackResponse,
+    GetChatHistoryResponse, GetHistoryRequest, LastSessionResponse, OfflineAsset,
+    OfflineAssetsRequest, RegisterVideoRequest, RegisterVideoResponse, ResumeRequest,
+    ResumeResponse, ServerInfo, ShareLinkRequest, ShareLinkResponse, StorageUsageResponse,
+    TimelineRequest, TimelineResponse, TranscriptRequest, TranscriptResponse,
+    UpdateAnalysisOptionsRequest, UpdateAnalysisOptionsResponse, UpdateVideoMetadataRequest,
+    UpdateVideoMetadataResponse, UploadOffsetRequest, UploadOffsetResponse, UploadResponse,
+    UploadStatusRequest, UploadStatusResponse, VideoChunk,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+use tonic::Status;
+
+pub mod auth;
+pub mod crypto;
+#[cfg(test)]
+pub mod fake;
+pub mod grpc;
+pub mod local;
+pub mod proxy;
+pub mod rest;
+pub mod simulate;
+pub mod tls;
+
+tokio::task_local! {
+    static TIMEOUT_OVERRIDE: Option<Duration>;
+}
+
+/// Run `calls` with every backend call in them given `timeout` instead of
+/// the configured deadline; `None` keeps the configured one
+///
+/// For commands that let the frontend pick a deadline per call. Work
+/// spawned onto another task has to be wrapped separately.
+pub async fn with_timeout<F: Future>(timeout: Option<Duration>, calls: F) -> F::Output {
+    TIMEOUT_OVERRIDE.scope(timeout, calls).await
+}
+
+/// Deadline for a call of `kind` made now: the override from
+/// [`with_timeout`], else [`GrpcConfig::timeout`](config::GrpcConfig::timeout)
+pub fn call_timeout(kind: CallKind) -> Option<Duration> {
+    TIMEOUT_OVERRIDE
+        .try_with(|timeout| *timeout)
+        .ok()
+        .flatten()
+        .or_else(|| config::get().grpc.timeout(kind))
+}
+
+/// Chunks of a video being uploaded, in order, fed by a reader task
+pub type UploadStream = ReceiverStream<VideoChunk>;
+/// Streamed answer to a chat message
+pub type ChatStream = Pin<Box<dyn Stream<Item = Result<ChatResponse, Status>> + Send>>;
+/// Files needed to browse a video offline
+pub type OfflineAssetStream = Pin<Box<dyn Stream<Item = Result<OfflineAsset, Status>> + Send>>;
+
+/// Every call the plugin makes to the backend
+#[async_trait::async_trait]
+pub trait VideoAnalyzerTransport: Send + Sync {
+    /// Short name for logs and diagnostics, e.g. "grpc"
+    fn name(&self) -> &'static str;
+    /// URL calls go to right now, e.g. the gRPC server URL
+    fn endpoint(&self) -> String;
+
+    // Videos
+    async fn upload_video(&self, chunks: UploadStream) -> Result<UploadResponse, Status>;
+    async fn get_upload_status(
+        &self,
+        request: UploadStatusRequest,
+    ) -> Result<UploadStatusResponse, Status>;
+    async fn negotiate_upload_offset(
+        &self,
+        request: UploadOffsetRequest,
+    ) -> Result<UploadOffsetResponse, Status>;
+    async fn check_file_exists(
+        &self,
+        request: CheckFileExistsRequest,
+    ) -> Result<CheckFileExistsResponse, Status>;
+    async fn register_local_video(
+        &self,
+        request: RegisterVideoRequest,
+    ) -> Result<RegisterVideoResponse, Status>;
+    async fn update_analysis_options(
+        &self,
+        request: UpdateAnalysisOptionsRequest,
+    ) -> Result<UpdateAnalysisOptionsResponse, Status>;
+    async fn get_storage_usage(&self) -> Result<StorageUsageResponse, Status>;
+    async fn cleanup_videos(&self, request: CleanupRequest) -> Result<CleanupResponse, Status>;
+    async fn get_offline_assets(
+        &self,
+        request: OfflineAssetsRequest,
+    ) -> Result<OfflineAssetStream, Status>;
+    async fn get_transcript(
+        &self,
+        request: TranscriptRequest,
+    ) -> Result<TranscriptResponse, Status>;
+    async fn get_detections(
+        &self,
+        request: DetectionsRequest,
+    ) -> Result<DetectionsResponse, Status>;
+    async fn get_timeline(&self, request: TimelineRequest) -> Result<TimelineResponse, Status>;
+    async fn create_share_link(
+        &self,
+        request: ShareLinkRequest,
+    ) -> Result<ShareLinkResponse, Status>;
+
+    // Chat
+    async fn send_chat_message(&self, request: ChatRequest) -> Result<ChatStream, Status>;
+    async fn submit_feedback(&self, request: FeedbackRequest) -> Result<FeedbackResponse, Status>;
+
+    // History and sessions
+    async fn get_chat_history(
+        &self,
+        request: GetHistoryRequest,
+    ) -> Result<GetChatHistoryResponse, Status>;
+    async fn clear_chat_history(
+        &self,
+        request: ClearHistoryRequest,
+    ) -> Result<ClearHistoryResponse, Status>;
+    async fn get_last_session(&self) -> Result<LastSessionResponse, Status>;
+    async fn resume_session(&self, request: ResumeRequest) -> Result<ResumeResponse, Status>;
+
+    // Backend status
+    async fn get_backend_queue(
+        &self,
+        request: BackendQueueRequest,
+    ) -> Result<BackendQueueResponse, Status>;
+    async fn cancel_processing(
+        &self,
+        request: CancelProcessingRequest,
+    ) -> Result<CancelProcessingResponse, Status>;
+    async fn delete_video(
+        &self,
+        request: DeleteVideoRequest,
+    ) -> Result<DeleteVideoResponse, Status>;
+    async fn update_video_metadata(
+        &self,
+        request: UpdateVideoMetadataRequest,
+    ) -> Result<UpdateVideoMetadataResponse, Status>;
+    async fn add_captions(
+        &self,
+        request: AddCaptionsRequest,
+    ) -> Result<AddCaptionsResponse, Status>;
+    async fn get_server_info(&self) -> Result<ServerInfo, Status>;
+}
+
+/// Managed state: the transport every command goes through
+pub type Transport = Arc<dyn VideoAnalyzerTransport>;
+
+/// The transport named by [`RestConfig::transport`](config::RestConfig::transport); gRPC goes over the
+/// channel held by `grpc_clients`
+pub fn from_config(grpc_clients: Arc<grpc::GrpcClientManager>) -> Transport {
+    match config::get().rest.transport.trim().to_lowercase().as_str() {
+        "rest" => Arc::new(rest::RestTransport),
+        "grpc" => Arc::new(grpc::GrpcTransport::new(grpc_clients)),
+        other => {
+            log::warn!("Unknown transport '{}', using grpc", other);
+            Arc::new(grpc::GrpcTransport::new(grpc_clients))
+        }
+    }
+}
@@ -0,0 +1,748 @@
+//! [`VideoAnalyzerTransport`] over a REST+SSE gateway
+//!
+//! For locked-down networks that block HTTP/2. Everything is HTTP/1.1
+//! against REST_BASE_URL, over TLS when it is `https://`:
+//!
+//! | Call                    | Request                                          |
+//! |-------------------------|--------------------------------------------------|
+//! | upload_video            | `POST /v1/videos` (multipart: `options`, `idempotency_key`, `file`, `sha256`) |
+//! | get_upload_status       | `GET /v1/uploads/{idempotency_key}`              |
+//! | negotiate_upload_offset | `POST /v1/uploads/{idempotency_key}/offset`      |
+//! | check_file_exists       | `GET /v1/files/{sha256}?size_bytes=`             |
+//! | register_local_video    | `POST /v1/videos/register`                       |
+//! | update_analysis_options | `POST /v1/videos/{id}/analysis-options`          |
+//! | get_offline_assets      | `GET /v1/videos/{id}/offline-assets` (SSE)       |
+//! | get_transcript          | `GET /v1/videos/{id}/transcript`                 |
+//! | get_detections          | `GET /v1/videos/{id}/detections`                 |
+//! | get_timeline            | `GET /v1/videos/{id}/timeline`                   |
+//! | create_share_link       | `POST /v1/videos/{id}/share`                     |
+//! | cancel_processing       | `POST /v1/videos/{id}/cancel`                    |
+//! | delete_video            | `DELETE /v1/videos/{id}`                         |
+//! | update_video_metadata   | `PATCH /v1/videos/{id}`                          |
+//! | add_captions            | `POST /v1/videos/{id}/captions`                  |
+//! | get_storage_usage       | `GET /v1/storage`                                |
+//! | cleanup_videos          | `POST /v1/storage/cleanup`                       |
+//! | send_chat_message       | `POST /v1/chat` (SSE)                            |
+//! | submit_feedback         | `POST /v1/feedback`                              |
+//! | get_chat_history        | `GET /v1/sessions/{id}/history`                  |
+//! | clear_chat_history      | `DELETE /v1/sessions/{id}/history`               |
+//! | get_last_session        | `GET /v1/sessions/last`                          |
+//! | resume_session          | `POST /v1/sessions/{id}/resume`                  |
+//! | get_backend_queue       | `GET /v1/queue?only_mine=`                       |
+//! | get_server_info         | `GET /v1/info`                                   |
+//!
+//! Bodies are the proto messages as JSON, with the proto field names.
+//! Requests carry the auth token from [`super::auth`] as a bearer header.
+//! Streams are server-sent events carrying one message per `data:`; an
+//! `event: error` ends the stream with its data as the error message.
+//! HTTP errors are mapped back to the closest gRPC status. Each call gets
+//! the deadline gRPC would give it, see [`super::call_timeout`]; a stream's
+//! covers it to the last event.
+
+use super::{ChatStream, OfflineAssetStream, UploadStream, VideoAnalyzerTransport};
+use crate::config::{self, CallKind};
+use crate::http;
+use crate::video_analyzer::{
+    AddCaptionsRequest, AddCaptionsResponse, BackendQueueRequest, BackendQueueResponse,
+    CancelProcessingRequest, CancelProcessingResponse, ChatRequest, CheckFileExistsRequest,
+    CheckFileExistsResponse, CleanupRequest, CleanupResponse, ClearHistoryRequest,
+    ClearHistoryResponse, DeleteVideoRequest, DeleteVideoResponse, DetectionsRequest,
+    DetectionsResponse, FeedbackRequest, FeedbackResponse, GetChatHistoryResponse,
+    GetHistoryRequest, LastSessionResponse, OfflineAssetsRequest, RegisterVideoRequest,
+    RegisterVideoResponse, ResumeRequest, ResumeResponse, ServerInfo, ShareLinkRequest,
+    ShareLinkResponse, StorageUsageResponse, TimelineRequest, TimelineResponse, TranscriptRequest,
+    TranscriptResponse, UpdateAnalysisOptionsRequest, UpdateAnalysisOptionsResponse,
+    UpdateVideoMetadataRequest, UpdateVideoMetadataResponse, UploadOffsetRequest,
+    UploadOffsetResponse, UploadResponse, UploadStatusRequest, UploadStatusResponse, VideoChunk,
+};
+use hyper::body::HttpBody;
+use hyper::{Body, Method, Request, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+use tokio::time::Instant;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Code, Status};
+
+/// The backend through a REST+SSE gateway at REST_BASE_URL
+#[derive(Debug, Default)]
+pub struct RestTransport;
+
+impl RestTransport {
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        content_type: &str,
+        body: Body,
+    ) -> Result<Response<Body>, Status> {
+        let base = config::get().rest.base_url.clone();
+        let url = format!("{}{}", base, path);
+        let mut request = Request::builder()
+            .method(method)
+            .uri(http::parse_uri(&url).map_err(Status::invalid_argument)?)
+            .header("content-type", content_type)
+            .header("accept", "application/json, text/event-stream");
+        if let Some(bearer) = super::auth::bearer() {
+            request = request.header("authorization", bearer);
+        }
+        let request = request
+            .body(body)
+            .map_err(|e| Status::internal(format!("Failed to build request: {}", e)))?;
+        let response = http::client().request(request).await.map_err(|e| {
+            Status::unavailable(format!(
+                "Failed to reach the REST gateway at {}: {}",
+                base, e
+            ))
+        })?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let code = response.status().as_u16();
+        let bytes = hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap_or_default();
+        Err(status_from_http(code, &String::from_utf8_lossy(&bytes)))
+    }
+
+    /// A JSON request answered with a single JSON message
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<T, Status> {
+        let body = json_body(body).map_err(Status::internal)?;
+        let exchange = async {
+            let response = self.send(method, path, "application/json", body).await?;
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .map_err(|e| Status::unavailable(format!("Failed to read response: {}", e)))
+        };
+        let bytes = within(deadline(CallKind::Default), path, exchange).await?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| Status::internal(format!("Unexpected response from {}: {}", path, e)))
+    }
+
+    /// A JSON request answered with a stream of server-sent events, all
+    /// of which have to arrive within the deadline for `kind`
+    async fn stream<T: DeserializeOwned + Send + 'static>(
+        &self,
+        kind: CallKind,
+        method: Method,
+        path: &str,
+        body: Option<&impl Serialize>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>, Status> {
+        let body = json_body(body).map_err(Status::internal)?;
+        let deadline = deadline(kind);
+        let response = within(
+            deadline,
+            path,
+            self.send(method, path, "application/json", body),
+        )
+        .await?;
+        Ok(sse_stream(response.into_body(), path.to_string(), deadline))
+    }
+}
+
+#[async_trait::async_trait]
+impl VideoAnalyzerTransport for RestTransport {
+    fn name(&self) -> &'static str {
+        "rest"
+    }
+
+    fn endpoint(&self) -> String {
+        config::get().rest.base_url.clone()
+    }
+
+    async fn upload_video(&self, mut chunks: UploadStream) -> Result<UploadResponse, Status> {
+        // The multipart body loses the chunk boundaries the backend would
+        // need to open each chunk
+        if config::get().grpc.upload_encryption_key.is_some() {
+            return Err(Status::failed_precondition(
+                "Encrypted uploads need the gRPC transport (BACKEND_TRANSPORT=grpc)",
+            ));
+        }
+        let first = chunks
+            .next()
+            .await
+            .ok_or_else(|| Status::invalid_argument("Nothing to upload"))?;
+        let boundary = format!(
+            "video-analyzer-{:x}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default()
+        );
+        let head = multipart_head(&boundary, &first);
+
+        // Stream the parts as the chunks arrive instead of buffering the video
+        let (mut sender, body) = Body::channel();
+        let content_type = format!("multipart/form-data; boundary={}", boundary);
+        tokio::spawn(async move {
+            // Per-chunk digests have no place in one multipart body; the
+            // whole file's, from the last chunk, follows it instead
+            let mut file_sha256 = first.file_sha256;
+            if sender.send_data(head.into()).await.is_err()
+                || sender.send_data(first.data.into()).await.is_err()
+            {
+                return;
+            }
+            while let Some(chunk) = chunks.next().await {
+                if !chunk.file_sha256.is_empty() {
+                    file_sha256 = chunk.file_sha256;
+                }
+                if sender.send_data(chunk.data.into()).await.is_err() {
+                    return;
+                }
+            }
+            let tail = multipart_tail(&boundary, &file_sha256);
+            sender.send_data(tail.into()).await.ok();
+        });
+
+        let exchange = async {
+            let response = self
+                .send(Method::POST, "/v1/videos", &content_type, body)
+                .await?;
+            hyper::body::to_bytes(response.into_body())
+                .await
+                .map_err(|e| Status::unavailable(format!("Failed to read response: {}", e)))
+        };
+        let bytes = within(deadline(CallKind::Upload), "/v1/videos", exchange).await?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| Status::internal(format!("Unexpected upload response: {}", e)))
+    }
+
+    async fn get_upload_status(
+        &self,
+        request: UploadStatusRequest,
+    ) -> Result<UploadStatusResponse, Status> {
+        let path = format!("/v1/uploads/{}", path_segment(&request.idempotency_key));
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn negotiate_upload_offset(
+        &self,
+        request: UploadOffsetRequest,
+    ) -> Result<UploadOffsetResponse, Status> {
+        let path = format!(
+            "/v1/uploads/{}/offset",
+            path_segment(&request.idempotency_key)
+        );
+        self.call(Method::POST, &path, Some(&request)).await
+    }
+
+    async fn check_file_exists(
+        &self,
+        request: CheckFileExistsRequest,
+    ) -> Result<CheckFileExistsResponse, Status> {
+        let path = format!(
+            "/v1/files/{}?size_bytes={}",
+            path_segment(&request.sha256),
+            request.size_bytes
+        );
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn register_local_video(
+        &self,
+        request: RegisterVideoRequest,
+    ) -> Result<RegisterVideoResponse, Status> {
+        self.call(Method::POST, "/v1/videos/register", Some(&request))
+            .await
+    }
+
+    async fn update_analysis_options(
+        &self,
+        request: UpdateAnalysisOptionsRequest,
+    ) -> Result<UpdateAnalysisOptionsResponse, Status> {
+        let path = format!(
+            "/v1/videos/{}/analysis-options",
+            path_segment(&request.video_id)
+        );
+        self.call(Method::POST, &path, Some(&request)).await
+    }
+
+    async fn get_storage_usage(&self) -> Result<StorageUsageResponse, Status> {
+        self.call(Method::GET, "/v1/storage", None::<&()>).await
+    }
+
+    async fn cleanup_videos(&self, request: CleanupRequest) -> Result<CleanupResponse, Status> {
+        self.call(Method::POST, "/v1/storage/cleanup", Some(&request))
+            .await
+    }
+
+    async fn get_offline_assets(
+        &self,
+        request: OfflineAssetsRequest,
+    ) -> Result<OfflineAssetStream, Status> {
+        let path = format!(
+            "/v1/videos/{}/offline-assets?max_key_frames={}",
+            path_segment(&request.video_id),
+            request.max_key_frames
+        );
+        self.stream(CallKind::Upload, Method::GET, &path, None::<&()>)
+            .await
+    }
+
+    async fn get_transcript(
+        &self,
+        request: TranscriptRequest,
+    ) -> Result<TranscriptResponse, Status> {
+        let path = format!("/v1/videos/{}/transcript", path_segment(&request.video_id));
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn get_detections(
+        &self,
+        request: DetectionsRequest,
+    ) -> Result<DetectionsResponse, Status> {
+        let path = format!("/v1/videos/{}/detections", path_segment(&request.video_id));
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn get_timeline(&self, request: TimelineRequest) -> Result<TimelineResponse, Status> {
+        let path = format!("/v1/videos/{}/timeline", path_segment(&request.video_id));
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn create_share_link(
+        &self,
+        request: ShareLinkRequest,
+    ) -> Result<ShareLinkResponse, Status> {
+        let path = format!("/v1/videos/{}/share", path_segment(&request.video_id));
+        self.call(Method::POST, &path, Some(&request)).await
+    }
+
+    async fn send_chat_message(&self, request: ChatRequest) -> Result<ChatStream, Status> {
+        self.stream(CallKind::Chat, Method::POST, "/v1/chat", Some(&request))
+            .await
+    }
+
+    async fn submit_feedback(&self, request: FeedbackRequest) -> Result<FeedbackResponse, Status> {
+        self.call(Method::POST, "/v1/feedback", Some(&request))
+            .await
+    }
+
+    async fn get_chat_history(
+        &self,
+        request: GetHistoryRequest,
+    ) -> Result<GetChatHistoryResponse, Status> {
+        let path = format!(
+            "/v1/sessions/{}/history?include_full_messages={}",
+            path_segment(&request.video_id),
+            request.include_full_messages
+        );
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn clear_chat_history(
+        &self,
+        request: ClearHistoryRequest,
+    ) -> Result<ClearHistoryResponse, Status> {
+        let path = format!("/v1/sessions/{}/history", path_segment(&request.video_id));
+        self.call(Method::DELETE, &path, None::<&()>).await
+    }
+
+    async fn get_last_session(&self) -> Result<LastSessionResponse, Status> {
+        self.call(Method::GET, "/v1/sessions/last", None::<&()>)
+            .await
+    }
+
+    async fn resume_session(&self, request: ResumeRequest) -> Result<ResumeResponse, Status> {
+        let path = format!("/v1/sessions/{}/resume", path_segment(&request.video_id));
+        self.call(Method::POST, &path, Some(&request)).await
+    }
+
+    async fn get_backend_queue(
+        &self,
+        request: BackendQueueRequest,
+    ) -> Result<BackendQueueResponse, Status> {
+        let path = format!("/v1/queue?only_mine={}", request.only_mine);
+        self.call(Method::GET, &path, None::<&()>).await
+    }
+
+    async fn cancel_processing(
+        &self,
+        request: CancelProcessingRequest,
+    ) -> Result<CancelProcessingResponse, Status> {
+        let path = format!("/v1/videos/{}/cancel", path_segment(&request.video_id));
+        self.call(Method::POST, &path, Some(&request)).await
+    }
+
+    async fn delete_video(
+        &self,
+        request: DeleteVideoRequest,
+    ) -> Result<DeleteVideoResponse, Status> {
+        let path = format!("/v1/videos/{}", path_segment(&request.video_id));
+        self.call(Method::DELETE, &path, None::<&()>).await
+    }
+
+    async fn update_video_metadata(
+        &self,
+        request: UpdateVideoMetadataRequest,
+    ) -> Result<UpdateVideoMetadataResponse, Status> {
+        let path = format!("/v1/videos/{}", path_segment(&request.video_id));
+        self.call(Method::PATCH, &path, Some(&request)).await
+    }
+
+    async fn add_captions(
+        &self,
+        request: AddCaptionsRequest,
+    ) -> Result<AddCaptionsResponse, Status> {
+        let path = format!("/v1/videos/{}/captions", path_segment(&request.video_id));
+        self.call(Method::POST, &path, Some(&request)).await
+    }
+
+    async fn get_server_info(&self) -> Result<ServerInfo, Status> {
+        self.call(Method::GET, "/v1/info", None::<&()>).await
+    }
+}
+
+/// When a call of `kind` made now has to be done by
+fn deadline(kind: CallKind) -> Option<Instant> {
+    super::call_timeout(kind).map(|timeout| Instant::now() + timeout)
+}
+
+/// `exchange`, failed with `DeadlineExceeded` if it isn't done by `deadline`
+async fn within<T>(
+    deadline: Option<Instant>,
+    path: &str,
+    exchange: impl Future<Output = Result<T, Status>>,
+) -> Result<T, Status> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, exchange)
+            .await
+            .map_err(|_| Status::deadline_exceeded(format!("{} timed out", path)))?,
+        None => exchange.await,
+    }
+}
+
+fn json_body(body: Option<&impl Serialize>) -> Result<Body, String> {
+    match body {
+        Some(value) => serde_json::to_vec(value)
+            .map(Body::from)
+            .map_err(|e| format!("Failed to serialize request: {}", e)),
+        None => Ok(Body::empty()),
+    }
+}
+
+/// Everything before the video bytes: the optional `options` JSON,
+/// `idempotency_key`, `offset` and parallel upload parts, and the headers
+/// of the `file` part
+fn multipart_head(boundary: &str, first: &VideoChunk) -> String {
+    let mut head = String::new();
+    if let Some(options) = &first.analysis_options {
+        head.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"options\"\r\n\
+             Content-Type: application/json\r\n\r\n{}\r\n",
+            boundary,
+            serde_json::to_string(options).unwrap_or_default()
+        ));
+    }
+    if !first.language_hint.is_empty() {
+        head.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"language_hint\"\r\n\r\n{}\r\n",
+            boundary, first.language_hint
+        ));
+    }
+    if !first.idempotency_key.is_empty() {
+        head.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"idempotency_key\"\r\n\r\n{}\r\n",
+            boundary, first.idempotency_key
+        ));
+    }
+    if first.offset > 0 {
+        head.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"offset\"\r\n\r\n{}\r\n",
+            boundary, first.offset
+        ));
+    }
+    if !first.source_sha256.is_empty() {
+        head.push_str(&format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"source_sha256\"\r\n\r\n{}\r\n",
+            boundary, first.source_sha256
+        ));
+    }
+    if first.stream_count > 0 {
+        for (name, value) in [
+            ("stream_index", i64::from(first.stream_index)),
+            ("stream_count", i64::from(first.stream_count)),
+            ("file_size", first.file_size),
+        ] {
+            head.push_str(&format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            ));
+        }
+    }
+    let filename: String = first
+        .filename
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .replace('"', "%22");
+    head.push_str(&format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        boundary, filename
+    ));
+    head
+}
+
+/// Everything after the video bytes: the `sha256` part, when known, and
+/// the closing boundary
+fn multipart_tail(boundary: &str, file_sha256: &str) -> String {
+    let mut tail = format!("\r\n--{}", boundary);
+    if !file_sha256.is_empty() {
+        tail.push_str(&format!(
+            "\r\nContent-Disposition: form-data; name=\"sha256\"\r\n\r\n{}\r\n--{}",
+            file_sha256, boundary
+        ));
+    }
+    tail.push_str("--\r\n");
+    tail
+}
+
+/// Percent-encode an id for use as one path segment
+fn path_segment(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
+/// The gRPC status closest to an HTTP error, with the gateway's message
+fn status_from_http(code: u16, body: &str) -> Status {
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| {
+            ["error", "message", "detail"]
+                .iter()
+                .find_map(|k| v[k].as_str().map(str::to_string))
+        })
+        .unwrap_or_else(|| body.trim().to_string());
+    let message = if message.is_empty() {
+        format!("HTTP {}", code)
+    } else {
+        message
+    };
+    let code = match code {
+        400 | 422 => Code::InvalidArgument,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::NotFound,
+        409 => Code::AlreadyExists,
+        413 | 429 => Code::ResourceExhausted,
+        501 => Code::Unimplemented,
+        502 | 503 => Code::Unavailable,
+        504 => Code::DeadlineExceeded,
+        _ => Code::Internal,
+    };
+    Status::new(code, message)
+}
+
+/// One server-sent event
+#[derive(Debug, Default, PartialEq)]
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+/// Splits a byte stream into server-sent events
+#[derive(Debug, Default)]
+struct SseParser {
+    buf: Vec<u8>,
+}
+
+impl SseParser {
+    /// Add received bytes; returns the events they completed
+    fn push(&mut self, bytes: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend(bytes.iter().filter(|b| **b != b'\r'));
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buf.drain(..end + 2).collect();
+            let mut event = SseEvent::default();
+            let mut data: Vec<&str> = Vec::new();
+            let text = String::from_utf8_lossy(&block);
+            for line in text.lines() {
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match field {
+                    "event" => event.event = value.to_string(),
+                    "data" => data.push(value),
+                    // Comments (keep-alives) and unknown fields
+                    _ => {}
+                }
+            }
+            if !data.is_empty() {
+                event.data = data.join("\n");
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+fn decode_event<T: DeserializeOwned>(event: &SseEvent) -> Result<T, String> {
+    if event.event == "error" {
+        return Err(event.data.clone());
+    }
+    serde_json::from_str(&event.data)
+        .map_err(|e| format!("Malformed event from the REST gateway: {}", e))
+}
+
+fn sse_stream<T: DeserializeOwned + Send + 'static>(
+    mut body: Body,
+    path: String,
+    deadline: Option<Instant>,
+) -> Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(16);
+    tokio::spawn(async move {
+        let mut parser = SseParser::default();
+        loop {
+            let next = match within(deadline, &path, async { Ok(body.data().await) }).await {
+                Ok(Some(next)) => next,
+                Ok(None) => return,
+                Err(status) => {
+                    tx.send(Err(status)).await.ok();
+                    return;
+                }
+            };
+            let bytes = match next {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    let status = Status::unavailable(format!("Event stream interrupted: {}", e));
+                    tx.send(Err(status)).await.ok();
+                    return;
+                }
+            };
+            for event in parser.push(&bytes) {
+                let item = decode_event(&event).map_err(Status::internal);
+                let failed = item.is_err();
+                if tx.send(item).await.is_err() || failed {
+                    return;
+                }
+            }
+        }
+    });
+    Box::pin(ReceiverStream::new(rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_stalled_server_hits_the_deadline() {
+        // Takes the connection, never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/chat", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let request = async {
+            let uri = http::parse_uri(&url).map_err(Status::invalid_argument)?;
+            http::client()
+                .get(uri)
+                .await
+                .map_err(|e| Status::unavailable(e.to_string()))
+        };
+        let started = Instant::now();
+        let status = super::super::with_timeout(Some(Duration::from_millis(200)), async {
+            within(deadline(CallKind::Chat), "/v1/chat", request).await
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_hits_the_deadline() {
+        let (mut sender, body) = Body::channel();
+        sender
+            .send_data("data: {\"a\":1}\n\n".into())
+            .await
+            .unwrap();
+        let mut events = super::super::with_timeout(Some(Duration::from_millis(200)), async {
+            sse_stream::<serde_json::Value>(body, "/v1/chat".to_string(), deadline(CallKind::Chat))
+        })
+        .await;
+
+        assert_eq!(events.next().await.unwrap().unwrap()["a"], 1);
+        let status = events.next().await.unwrap().unwrap_err();
+        assert_eq!(status.code(), Code::DeadlineExceeded);
+        assert!(events.next().await.is_none());
+        drop(sender);
+    }
+
+    #[test]
+    fn test_sse_parser_across_reads() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b": keep-alive\n\ndata: {\"a\"").is_empty());
+        let events = parser.push(b":1}\r\n\r\nevent: error\ndata: boom\ndata: again\n\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: String::new(),
+                    data: "{\"a\":1}".to_string(),
+                },
+                SseEvent {
+                    event: "error".to_string(),
+                    data: "boom\nagain".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            decode_event::<serde_json::Value>(&events[1]).unwrap_err(),
+            "boom\nagain"
+        );
+    }
+
+    #[test]
+    fn test_status_from_http() {
+        let status = status_from_http(501, r#"{"detail": "no offline assets"}"#);
+        assert_eq!(status.code(), Code::Unimplemented);
+        assert_eq!(status.message(), "no offline assets");
+        assert_eq!(status_from_http(404, "").message(), "HTTP 404");
+        assert_eq!(status_from_http(503, "down").code(), Code::Unavailable);
+    }
+
+    #[test]
+    fn test_multipart_head() {
+        let chunk = VideoChunk {
+            filename: "my \"clip\".mp4".to_string(),
+            ..Default::default()
+        };
+        let head = multipart_head("B", &chunk);
+        assert_eq!(
+            head,
+            "--B\r\nContent-Disposition: form-data; name=\"file\"; filename=\"my %22clip%22.mp4\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n"
+        );
+        let chunk = VideoChunk {
+            filename: "a.mp4".to_string(),
+            idempotency_key: "k1".to_string(),
+            offset: 1024,
+            ..Default::default()
+        };
+        assert!(multipart_head("B", &chunk).starts_with(
+            "--B\r\nContent-Disposition: form-data; name=\"idempotency_key\"\r\n\r\nk1\r\n\
+             --B\r\nContent-Disposition: form-data; name=\"offset\"\r\n\r\n1024\r\n--B\r\n"
+        ));
+        assert_eq!(path_segment("a b/c"), "a%20b%2Fc");
+        assert_eq!(multipart_tail("B", ""), "\r\n--B--\r\n");
+        assert_eq!(
+            multipart_tail("B", "ab12"),
+            "\r\n--B\r\nContent-Disposition: form-data; name=\"sha256\"\r\n\r\nab12\r\n--B--\r\n"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! Simulated network conditions, for QA in dev mode
+//!
+//! In dev mode the transport in managed state is wrapped in
+//! [`SimulatedTransport`], which does nothing until `set_network_simulation`
+//! hands it [`NetworkConditions`]. From then on every call waits out the
+//! latency first and may fail outright as if the backend were unreachable;
+//! uploads and streamed answers are held to the bandwidth cap and may break
+//! off mid-stream.
+//!
+//! The random choices come from a generator seeded with
+//! [`NetworkConditions::seed`] whenever the conditions are set, so the same
+//! seed and the same sequence of calls fail the same way every time.
+
+use super::{ChatStream, OfflineAssetStream, Transport, UploadStream, VideoAnalyzerTransport};
+use crate::video_analyzer::{
+    AddCaptionsRequest, AddCaptionsResponse, BackendQueueRequest, BackendQueueResponse,
+    CancelProcessingRequest, CancelProcessingResponse, ChatRequest, CheckFileExistsRequest,
+    CheckFileExistsResponse, CleanupRequest, CleanupResponse, ClearHistoryRequest,
+    ClearHistoryResponse, DeleteVideoRequest, DeleteVideoResponse, DetectionsRequest,
+    DetectionsResponse, FeedbackRequest, FeedbackResponse, GetChatHistoryResponse,
+    GetHistoryRequest, LastSessionResponse, OfflineAssetsRequest, RegisterVideoRequest,
+    RegisterVideoResponse, ResumeRequest, ResumeResponse, ServerInfo, ShareLinkRequest,
+    ShareLinkResponse, StorageUsageResponse, TimelineRequest, TimelineResponse, TranscriptRequest,
+    TranscriptResponse, UpdateAnalysisOptionsRequest, UpdateAnalysisOptionsResponse,
+    UpdateVideoMetadataRequest, UpdateVideoMetadataResponse, UploadOffsetRequest,
+    UploadOffsetResponse, UploadResponse, UploadStatusRequest, UploadStatusResponse,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::Status;
+
+/// What the simulated network does to calls
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConditions {
+    /// Added before every call
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Up to this much more, picked at random per call
+    #[serde(default)]
+    pub jitter_ms: u64,
+    /// Cap for uploads and streamed answers, 0 for none
+    #[serde(default)]
+    pub bandwidth_bytes_per_sec: u64,
+    /// Chance, 0.0 to 1.0, that a call fails with `Unavailable`
+    #[serde(default)]
+    pub error_rate: f64,
+    /// Chance, 0.0 to 1.0, that a stream breaks off at each message
+    #[serde(default)]
+    pub stream_error_rate: f64,
+    /// Seed for the random choices; the same seed replays the same failures
+    #[serde(default)]
+    pub seed: u64,
+}
+
+impl NetworkConditions {
+    fn validate(&self) -> Result<(), String> {
+        for (name, rate) in [
+            ("error_rate", self.error_rate),
+            ("stream_error_rate", self.stream_error_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("{} must be between 0 and 1, got {}", name, rate));
+            }
+        }
+        Ok(())
+    }
+
+    /// How long `bytes` take at the bandwidth cap
+    fn transfer_time(&self, bytes: usize) -> Duration {
+        match self.bandwidth_bytes_per_sec {
+            0 => Duration::ZERO,
+            rate => Duration::from_secs_f64(bytes as f64 / rate as f64),
+        }
+    }
+}
+
+static CONDITIONS: RwLock<Option<NetworkConditions>> = RwLock::new(None);
+static RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Simulate `conditions` from now on, or stop with `None`
+pub fn set(conditions: Option<NetworkConditions>) -> Result<(), String> {
+    if let Some(conditions) = &conditions {
+        conditions.validate()?;
+        *RNG.lock().unwrap() = Some(StdRng::seed_from_u64(conditions.seed));
+        log::warn!("Simulating network conditions: {:?}", conditions);
+    } else {
+        log::info!("Network simulation off");
+    }
+    *CONDITIONS.write().unwrap() = conditions;
+    Ok(())
+}
+
+/// The conditions being simulated, if any
+pub fn get() -> Option<NetworkConditions> {
+    CONDITIONS.read().unwrap().clone()
+}
+
+/// Whether the next random choice with chance `rate` comes up
+fn roll(rate: f64) -> bool {
+    rate > 0.0
+        && RNG
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| StdRng::seed_from_u64(0))
+            .gen_bool(rate)
+}
+
+fn jitter(max_ms: u64) -> Duration {
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    let ms = RNG
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| StdRng::seed_from_u64(0))
+        .gen_range(0..=max_ms);
+    Duration::from_millis(ms)
+}
+
+/// A transport that passes calls to `inner` through the simulated network
+pub struct SimulatedTransport {
+    inner: Transport,
+}
+
+impl SimulatedTransport {
+    pub fn wrap(inner: Transport) -> Transport {
+        Arc::new(Self { inner })
+    }
+
+    /// Wait out the latency, then maybe fail the call; the conditions in
+    /// effect, if any
+    async fn before_call(&self) -> Result<Option<NetworkConditions>, Status> {
+        let Some(conditions) = get() else {
+            return Ok(None);
+        };
+        let delay = Duration::from_millis(conditions.latency_ms) + jitter(conditions.jitter_ms);
+        tokio::time::sleep(delay).await;
+        if roll(conditions.error_rate) {
+            return Err(Status::unavailable("Simulated network failure"));
+        }
+        Ok(Some(conditions))
+    }
+}
+
+/// `stream` held to the bandwidth cap, breaking off at random
+fn throttle<T: Send + 'static>(
+    stream: Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>,
+    conditions: NetworkConditions,
+    size: fn(&T) -> usize,
+) -> Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>> {
+    let (tx, rx) = mpsc::channel(1);
+    tokio::spawn(async move {
+        let mut stream = stream;
+        while let Some(item) = stream.next().await {
+            if let Ok(message) = &item {
+                tokio::time::sleep(conditions.transfer_time(size(message))).await;
+                if roll(conditions.stream_error_rate) {
+                    let error = Status::unavailable("Simulated stream error");
+                    tx.send(Err(error)).await.ok();
+                    return;
+                }
+            }
+            if tx.send(item).await.is_err() {
+                return;
+            }
+        }
+    });
+    Box::pin(ReceiverStream::new(rx))
+}
+
+#[async_trait::async_trait]
+impl VideoAnalyzerTransport for SimulatedTransport {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn endpoint(&self) -> String {
+        self.inner.endpoint()
+    }
+
+    async fn upload_video(&self, chunks: UploadStream) -> Result<UploadResponse, Status> {
+        let Some(conditions) = self.before_call().await? else {
+            return self.inner.upload_video(chunks).await;
+        };
+        let (tx, rx) = mpsc::channel(1);
+        let (failed_tx, failed_rx) = oneshot::channel();
+        let forwarder = tokio::spawn(async move {
+            let mut chunks = chunks;
+            while let Some(chunk) = chunks.next().await {
+                tokio::time::sleep(conditions.transfer_time(chunk.data.len())).await;
+                if roll(conditions.stream_error_rate) {
+                    failed_tx.send(()).ok();
+                    // Hold the sender so the call is reset, not ended
+                    return std::future::pending().await;
+                }
+                if tx.send(chunk).await.is_err() {
+                    return;
+                }
+            }
+        });
+        let response = tokio::select! {
+            response = self.inner.upload_video(ReceiverStream::new(rx)) => response,
+            Ok(()) = failed_rx => Err(Status::unavailable("Simulated stream error")),
+        };
+        forwarder.abort();
+        response
+    }
+
+    async fn get_upload_status(
+        &self,
+        request: UploadStatusRequest,
+    ) -> Result<UploadStatusResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_upload_status(request).await
+    }
+
+    async fn negotiate_upload_offset(
+        &self,
+        request: UploadOffsetRequest,
+    ) -> Result<UploadOffsetResponse, Status> {
+        self.before_call().await?;
+        self.inner.negotiate_upload_offset(request).await
+    }
+
+    async fn check_file_exists(
+        &self,
+        request: CheckFileExistsRequest,
+    ) -> Result<CheckFileExistsResponse, Status> {
+        self.before_call().await?;
+        self.inner.check_file_exists(request).await
+    }
+
+    async fn register_local_video(
+        &self,
+        request: RegisterVideoRequest,
+    ) -> Result<RegisterVideoResponse, Status> {
+        self.before_call().await?;
+        self.inner.register_local_video(request).await
+    }
+
+    async fn update_analysis_options(
+        &self,
+        request: UpdateAnalysisOptionsRequest,
+    ) -> Result<UpdateAnalysisOptionsResponse, Status> {
+        self.before_call().await?;
+        self.inner.update_analysis_options(request).await
+    }
+
+    async fn get_storage_usage(&self) -> Result<StorageUsageResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_storage_usage().await
+    }
+
+    async fn cleanup_videos(&self, request: CleanupRequest) -> Result<CleanupResponse, Status> {
+        self.before_call().await?;
+        self.inner.cleanup_videos(request).await
+    }
+
+    async fn get_offline_assets(
+        &self,
+        request: OfflineAssetsRequest,
+    ) -> Result<OfflineAssetStream, Status> {
+        let conditions = self.before_call().await?;
+        let stream = self.inner.get_offline_assets(request).await?;
+        Ok(match conditions {
+            Some(conditions) => throttle(stream, conditions, |asset| asset.data.len()),
+            None => stream,
+        })
+    }
+
+    async fn get_transcript(
+        &self,
+        request: TranscriptRequest,
+    ) -> Result<TranscriptResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_transcript(request).await
+    }
+
+    async fn get_detections(
+        &self,
+        request: DetectionsRequest,
+    ) -> Result<DetectionsResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_detections(request).await
+    }
+
+    async fn get_timeline(&self, request: TimelineRequest) -> Result<TimelineResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_timeline(request).await
+    }
+
+    async fn create_share_link(
+        &self,
+        request: ShareLinkRequest,
+    ) -> Result<ShareLinkResponse, Status> {
+        self.before_call().await?;
+        self.inner.create_share_link(request).await
+    }
+
+    async fn send_chat_message(&self, request: ChatRequest) -> Result<ChatStream, Status> {
+        let conditions = self.before_call().await?;
+        let stream = self.inner.send_chat_message(request).await?;
+        Ok(match conditions {
+            Some(conditions) => throttle(stream, conditions, |chunk| {
+                chunk.content.len() + chunk.result_json.len()
+            }),
+            None => stream,
+        })
+    }
+
+    async fn submit_feedback(&self, request: FeedbackRequest) -> Result<FeedbackResponse, Status> {
+        self.before_call().await?;
+        self.inner.submit_feedback(request).await
+    }
+
+    async fn get_chat_history(
+        &self,
+        request: GetHistoryRequest,
+    ) -> Result<GetChatHistoryResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_chat_history(request).await
+    }
+
+    async fn clear_chat_history(
+        &self,
+        request: ClearHistoryRequest,
+    ) -> Result<ClearHistoryResponse, Status> {
+        self.before_call().await?;
+        self.inner.clear_chat_history(request).await
+    }
+
+    async fn get_last_session(&self) -> Result<LastSessionResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_last_session().await
+    }
+
+    async fn resume_session(&self, request: ResumeRequest) -> Result<ResumeResponse, Status> {
+        self.before_call().await?;
+        self.inner.resume_session(request).await
+    }
+
+    async fn get_backend_queue(
+        &self,
+        request: BackendQueueRequest,
+    ) -> Result<BackendQueueResponse, Status> {
+        self.before_call().await?;
+        self.inner.get_backend_queue(request).await
+    }
+
+    async fn cancel_processing(
+        &self,
+        request: CancelProcessingRequest,
+    ) -> Result<CancelProcessingResponse, Status> {
+        self.before_call().await?;
+        self.inner.cancel_processing(request).await
+    }
+
+    async fn delete_video(
+        &self,
+        request: DeleteVideoRequest,
+    ) -> Result<DeleteVideoResponse, Status> {
+        self.before_call().await?;
+        self.inner.delete_video(request).await
+    }
+
+    async fn update_video_metadata(
+        &self,
+        request: UpdateVideoMetadataRequest,
+    ) -> Result<UpdateVideoMetadataResponse, Status> {
+        self.before_call().await?;
+        self.inner.update_video_metadata(request).await
+    }
+
+    async fn add_captions(
+        &self,
+        request: AddCaptionsRequest,
+    ) -> Result<AddCaptionsResponse, Status> {
+        self.before_call().await?;
+        self.inner.add_captions(request).await
+    }
+
+    async fn get_server_info(&self) -> Result<ServerInfo, Status> {
+        self.before_call().await?;
+        self.inner.get_server_info().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_failures() {
+        let conditions = NetworkConditions {
+            error_rate: 0.5,
+            seed: 42,
+            ..Default::default()
+        };
+        let run = || {
+            set(Some(conditions.clone())).unwrap();
+            (0..32)
+                .map(|_| roll(conditions.error_rate))
+                .collect::<Vec<_>>()
+        };
+        let first = run();
+        assert!(first.contains(&true) && first.contains(&false));
+        assert_eq!(run(), first);
+        set(None).unwrap();
+
+        let invalid = NetworkConditions {
+            stream_error_rate: 1.5,
+            ..Default::default()
+        };
+        assert!(set(Some(invalid)).is_err());
+        assert_eq!(get(), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! TLS settings for the gRPC channel, including the client certificate
+//! (mutual TLS) for backends that require one
+//!
+//! Certificate problems, ours or the backend's, come back as
+//! `Unauthenticated` with an `x-certificate-error` marker, so
+//! [`CertificateError::from_status`] can tell them apart from a backend
+//! that is merely down and the UI can ask for a new certificate instead of
+//! retrying.
+
+use crate::config;
+use serde::Serialize;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
+use tonic::Status;
+
+/// Metadata key marking a [`Status`] as a certificate problem
+const CERTIFICATE_ERROR_KEY: &str = "x-certificate-error";
+
+/// Where a certificate problem was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CertificateErrorKind {
+    /// Our settings: a CA or client certificate or key missing or unreadable
+    Config,
+    /// The handshake: the backend refused our certificate or we refused its
+    Handshake,
+}
+
+impl CertificateErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Config => "config",
+            Self::Handshake => "handshake",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CertificateError {
+    pub kind: CertificateErrorKind,
+    pub message: String,
+}
+
+impl CertificateError {
+    pub fn config(message: impl Into<String>) -> Self {
+        Self {
+            kind: CertificateErrorKind::Config,
+            message: message.into(),
+        }
+    }
+
+    /// The certificate problem `status` reports, if it is one
+    pub fn from_status(status: &Status) -> Option<Self> {
+        let kind = match status
+            .metadata()
+            .get(CERTIFICATE_ERROR_KEY)?
+            .to_str()
+            .ok()?
+        {
+            "config" => CertificateErrorKind::Config,
+            _ => CertificateErrorKind::Handshake,
+        };
+        Some(Self {
+            kind,
+            message: status.message().to_string(),
+        })
+    }
+
+    pub fn into_status(self) -> Status {
+        let mut status = Status::unauthenticated(self.message);
+        status
+            .metadata_mut()
+            .insert(CERTIFICATE_ERROR_KEY, self.kind.as_str().parse().unwrap());
+        status
+    }
+}
+
+/// Whether a failed connect came down to a certificate, judging by the
+/// rustls error somewhere in its chain
+pub fn is_certificate_failure(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if error.to_string().to_lowercase().contains("certificate") {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+fn read_pem(setting: &str, path: &str) -> Result<Vec<u8>, CertificateError> {
+    let pem = std::fs::read(path).map_err(|e| {
+        CertificateError::config(format!("Failed to read {} {}: {}", setting, path, e))
+    })?;
+    if !pem.windows(10).any(|w| w == b"-----BEGIN") {
+        return Err(CertificateError::config(format!(
+            "{} {} is not a PEM file",
+            setting, path
+        )));
+    }
+    Ok(pem)
+}
+
+/// The client certificate and key to present, from GRPC_CLIENT_CERT and
+/// GRPC_CLIENT_KEY or else handed over by the host app
+fn client_identity() -> Result<Option<Identity>, CertificateError> {
+    let config = config::get();
+    match (&config.grpc.client_cert, &config.grpc.client_key) {
+        (Some(cert), Some(key)) => Ok(Some(Identity::from_pem(
+            read_pem("GRPC_CLIENT_CERT", cert)?,
+            read_pem("GRPC_CLIENT_KEY", key)?,
+        ))),
+        (None, None) => Ok(config
+            .grpc
+            .client_identity_pem
+            .as_ref()
+            .map(|identity| Identity::from_pem(&identity.cert, &identity.key))),
+        _ => Err(CertificateError::config(
+            "GRPC_CLIENT_CERT and GRPC_CLIENT_KEY must be set together",
+        )),
+    }
+}
+
+/// TLS settings from GRPC_CA_CERT, GRPC_DOMAIN and the client identity
+pub fn tls_config() -> Result<ClientTlsConfig, CertificateError> {
+    let config = config::get();
+    let mut tls = ClientTlsConfig::new();
+    if let Some(path) = &config.grpc.ca_cert {
+        tls = tls.ca_certificate(Certificate::from_pem(read_pem("GRPC_CA_CERT", path)?));
+    }
+    if let Some(domain) = &config.grpc.domain {
+        tls = tls.domain_name(domain);
+    }
+    if let Some(identity) = client_identity()? {
+        tls = tls.identity(identity);
+    }
+    Ok(tls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_errors_survive_the_status_round_trip() {
+        let status = CertificateError::config("GRPC_CLIENT_KEY is not a PEM file").into_status();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert_eq!(
+            CertificateError::from_status(&status),
+            Some(CertificateError::config(
+                "GRPC_CLIENT_KEY is not a PEM file"
+            ))
+        );
+        assert_eq!(
+            CertificateError::from_status(&Status::unauthenticated("bad token")),
+            None
+        );
+    }
+
+    #[test]
+    fn unreadable_pem_is_a_config_error() {
+        let error = read_pem("GRPC_CLIENT_CERT", "/nonexistent/client.pem").unwrap_err();
+        assert_eq!(error.kind, CertificateErrorKind::Config);
+    }
+}
@@ -0,0 +1,378 @@
+//! Uploads in flight, by upload id, so `cancel_upload` can stop them and
+//! `resume_upload` can finish them
+//!
+//! Each upload registers a [`CancellationToken`] for as long as it runs;
+//! cancelling it drops the backend call, which resets the stream instead
+//! of ending it, so the backend never mistakes a partial file for a whole
+//! one. Uploads paused for a shutdown are stopped the same way but stay
+//! resumable.
+//!
+//! Uploads from a path are also written to the [`UploadManifest`] in
+//! `uploads.json` until they succeed or are cancelled, so one cut off by a
+//! crash or a dead backend can be resumed after a restart.
+//!
+//! Very large files can be sent over several streams at once, each with
+//! a range of the file's chunks; see [`part_ranges`].
+//!
+//! Every chunk carries its SHA-256 and the last one the whole file's, and
+//! the backend's digest of what it stored is checked against what was
+//! sent with [`verify_upload_digest`].
+
+use crate::error::AppError;
+use crate::store::{load_json, save_json};
+use crate::video_analyzer::{AnalysisOptions, UploadResponse, VideoChunk};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Most often `record_sent` rewrites `uploads.json`
+const MANIFEST_SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Managed state: the cancellation token of every running upload
+#[derive(Debug, Default)]
+pub struct UploadRegistry {
+    uploads: Mutex<HashMap<String, CancellationToken>>,
+    /// Stopped by [`Self::pause_all`] rather than cancelled
+    paused: Mutex<HashSet<String>>,
+}
+
+impl UploadRegistry {
+    /// Track `upload_id` until the returned guard is dropped
+    pub fn register(&self, upload_id: &str) -> Result<UploadGuard<'_>, String> {
+        let mut uploads = self.uploads.lock().unwrap();
+        if uploads.contains_key(upload_id) {
+            return Err(format!("Upload {} is already in progress", upload_id));
+        }
+        let token = CancellationToken::new();
+        uploads.insert(upload_id.to_string(), token.clone());
+        Ok(UploadGuard {
+            registry: self,
+            upload_id: upload_id.to_string(),
+            token,
+        })
+    }
+
+    /// Cancel `upload_id`; false if no such upload is running
+    pub fn cancel(&self, upload_id: &str) -> bool {
+        match self.uploads.lock().unwrap().get(upload_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.uploads.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Stop every running upload but keep it resumable, e.g. at shutdown;
+    /// returns their ids
+    pub fn pause_all(&self) -> Vec<String> {
+        let uploads = self.uploads.lock().unwrap();
+        let mut paused = self.paused.lock().unwrap();
+        for (upload_id, token) in uploads.iter() {
+            paused.insert(upload_id.clone());
+            token.cancel();
+        }
+        uploads.keys().cloned().collect()
+    }
+}
+
+/// A registered upload; unregisters it when dropped
+pub struct UploadGuard<'a> {
+    registry: &'a UploadRegistry,
+    upload_id: String,
+    token: CancellationToken,
+}
+
+impl UploadGuard<'_> {
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    /// Whether the upload was stopped by [`UploadRegistry::cancel`], and
+    /// can be forgotten
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() && !self.is_paused()
+    }
+
+    /// Whether the upload was stopped by [`UploadRegistry::pause_all`]
+    pub fn is_paused(&self) -> bool {
+        self.registry
+            .paused
+            .lock()
+            .unwrap()
+            .contains(&self.upload_id)
+    }
+}
+
+impl Drop for UploadGuard<'_> {
+    fn drop(&mut self) {
+        self.registry
+            .uploads
+            .lock()
+            .unwrap()
+            .remove(&self.upload_id);
+        self.registry.paused.lock().unwrap().remove(&self.upload_id);
+    }
+}
+
+/// What's needed to pick an upload back up after a restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub upload_id: String,
+    /// The path as the caller gave it
+    pub path: String,
+    pub filename: String,
+    pub size_bytes: u64,
+    /// Hex SHA-256, to refuse resuming a file that changed since
+    pub sha256: String,
+    pub chunk_size: usize,
+    pub idempotency_key: String,
+    #[serde(default)]
+    pub analysis_options: Option<AnalysisOptions>,
+    /// The re-encoded copy being sent in place of `path`, when the upload
+    /// was transcoded; see [`crate::media::transcode`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcoded_path: Option<String>,
+    /// Chunks handed to the transport; the backend may have stored fewer,
+    /// so resuming asks it where to continue
+    pub chunks_sent: u64,
+    pub started_at: f64,
+    pub updated_at: f64,
+}
+
+/// Managed state: unfinished uploads, persisted in `uploads.json`
+pub struct UploadManifest {
+    path: PathBuf,
+    uploads: Mutex<HashMap<String, ManifestEntry>>,
+    saved_at: Mutex<Option<Instant>>,
+}
+
+impl UploadManifest {
+    pub fn load(path: PathBuf) -> Self {
+        let uploads = load_json(&path);
+        Self {
+            path,
+            uploads: Mutex::new(uploads),
+            saved_at: Mutex::new(None),
+        }
+    }
+
+    /// Every unfinished upload, oldest first
+    pub fn list(&self) -> Vec<ManifestEntry> {
+        let mut entries: Vec<_> = self.uploads.lock().unwrap().values().cloned().collect();
+        entries.sort_by(|a, b| a.started_at.total_cmp(&b.started_at));
+        entries
+    }
+
+    pub fn get(&self, upload_id: &str) -> Option<ManifestEntry> {
+        self.uploads.lock().unwrap().get(upload_id).cloned()
+    }
+
+    pub fn insert(&self, entry: ManifestEntry) -> Result<(), String> {
+        let mut uploads = self.uploads.lock().unwrap();
+        uploads.insert(entry.upload_id.clone(), entry);
+        self.save(&uploads)
+    }
+
+    /// Note progress; written out at most once per [`MANIFEST_SAVE_INTERVAL`]
+    pub fn record_sent(&self, upload_id: &str, chunks_sent: u64) {
+        let mut uploads = self.uploads.lock().unwrap();
+        let Some(entry) = uploads.get_mut(upload_id) else {
+            return;
+        };
+        entry.chunks_sent = chunks_sent;
+        entry.updated_at = crate::files::unix_seconds(std::time::SystemTime::now());
+        let due = self
+            .saved_at
+            .lock()
+            .unwrap()
+            .is_none_or(|at| at.elapsed() >= MANIFEST_SAVE_INTERVAL);
+        if due {
+            self.save(&uploads)
+                .map_err(|e| log::warn!("Failed to save upload progress: {}", e))
+                .ok();
+        }
+    }
+
+    /// Forget an upload that finished or was abandoned
+    pub fn remove(&self, upload_id: &str) -> Result<(), String> {
+        let mut uploads = self.uploads.lock().unwrap();
+        if uploads.remove(upload_id).is_none() {
+            return Ok(());
+        }
+        self.save(&uploads)
+    }
+
+    /// Write out progress `record_sent` hasn't saved yet
+    pub fn flush(&self) -> Result<(), String> {
+        let uploads = self.uploads.lock().unwrap();
+        self.save(&uploads)
+    }
+
+    fn save(&self, uploads: &HashMap<String, ManifestEntry>) -> Result<(), String> {
+        *self.saved_at.lock().unwrap() = Some(Instant::now());
+        save_json(&self.path, uploads)
+    }
+}
+
+/// The chunk ranges, `(first, end)` with `end` exclusive, that `streams`
+/// parallel streams send of a file of `chunks` chunks, as even as whole
+/// chunks allow; fewer ranges than streams for a file of fewer chunks
+pub fn part_ranges(chunks: u64, streams: usize) -> Vec<(u64, u64)> {
+    let streams = (streams.max(1) as u64).min(chunks.max(1));
+    let (per_part, longer) = (chunks / streams, chunks % streams);
+    let mut first = 0;
+    (0..streams)
+        .map(|part| {
+            let end = first + per_part + u64::from(part < longer);
+            let range = (first, end);
+            first = end;
+            range
+        })
+        .collect()
+}
+
+/// `video_data` cut into `chunk_size` chunks, the last carrying the
+/// whole file's digest
+pub fn build_video_chunks(
+    filename: &str,
+    video_data: Vec<u8>,
+    chunk_size: usize,
+) -> Vec<VideoChunk> {
+    let mut chunks: Vec<VideoChunk> = video_data
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(idx, chunk)| VideoChunk {
+            data: chunk.to_vec(),
+            filename: filename.to_string(),
+            chunk_index: idx as i32,
+            offset: (idx * chunk_size) as i64,
+            sha256: sha256_hex(chunk),
+            ..Default::default()
+        })
+        .collect();
+    if let Some(last) = chunks.last_mut() {
+        last.file_sha256 = sha256_hex(&video_data);
+    }
+    chunks
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest;
+    hex::encode(sha2::Sha256::digest(data))
+}
+
+/// Fail with an integrity error unless the backend's digest of an upload,
+/// when it gives one, is `sent`
+pub fn verify_upload_digest(
+    filename: &str,
+    response: &UploadResponse,
+    sent: &str,
+) -> Result<(), AppError> {
+    if response.sha256.is_empty() || response.sha256.eq_ignore_ascii_case(sent) {
+        return Ok(());
+    }
+    Err(AppError::integrity(format!(
+        "The backend stored {} with SHA-256 {}, but {} was sent; upload it again",
+        filename, response.sha256, sent
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_reaches_the_registered_upload_until_it_ends() {
+        let registry = UploadRegistry::default();
+        let guard = registry.register("a").unwrap();
+        assert!(registry.register("a").is_err());
+
+        assert!(registry.cancel("a"));
+        assert!(guard.token().is_cancelled());
+
+        drop(guard);
+        assert!(!registry.cancel("a"));
+        let guard = registry.register("a").unwrap();
+
+        assert_eq!(registry.pause_all(), vec!["a".to_string()]);
+        assert!(guard.token().is_cancelled());
+        assert!(guard.is_paused() && !guard.is_cancelled());
+    }
+
+    #[test]
+    fn parts_cover_every_chunk_once() {
+        assert_eq!(part_ranges(10, 3), [(0, 4), (4, 7), (7, 10)]);
+        assert_eq!(part_ranges(2, 4), [(0, 1), (1, 2)]);
+        assert_eq!(part_ranges(5, 1), [(0, 5)]);
+    }
+
+    #[test]
+    fn only_the_last_chunk_carries_the_file_digest() {
+        let chunks = build_video_chunks("a.mp4", b"0123456789".to_vec(), 4);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[2].offset, 8);
+        assert_eq!(chunks[2].sha256, sha256_hex(b"89"));
+        assert!(chunks[..2].iter().all(|chunk| chunk.file_sha256.is_empty()));
+        assert_eq!(chunks[2].file_sha256, sha256_hex(b"0123456789"));
+    }
+
+    #[test]
+    fn a_different_backend_digest_is_an_integrity_error() {
+        let sent = sha256_hex(b"video");
+        let stored = |sha256: &str| UploadResponse {
+            sha256: sha256.to_string(),
+            ..Default::default()
+        };
+        assert!(verify_upload_digest("a.mp4", &stored(&sent.to_uppercase()), &sent).is_ok());
+        // Backends that predate checksums don't report one
+        assert!(verify_upload_digest("a.mp4", &stored(""), &sent).is_ok());
+        let error = verify_upload_digest("a.mp4", &stored(&sha256_hex(b"other")), &sent);
+        assert!(matches!(error, Err(AppError::Integrity { .. })));
+    }
+
+    #[test]
+    fn manifest_survives_a_reload() {
+        let dir = std::env::temp_dir().join(format!("uploads-test-{}", std::process::id()));
+        let path = dir.join("uploads.json");
+        let manifest = UploadManifest::load(path.clone());
+        manifest
+            .insert(ManifestEntry {
+                upload_id: "u1".to_string(),
+                path: "/videos/a.mp4".to_string(),
+                filename: "a.mp4".to_string(),
+                size_bytes: 10,
+                sha256: "ab".to_string(),
+                chunk_size: 4,
+                idempotency_key: "k".to_string(),
+                analysis_options: None,
+                transcoded_path: None,
+                chunks_sent: 0,
+                started_at: 1.0,
+                updated_at: 1.0,
+            })
+            .unwrap();
+        manifest.record_sent("u1", 2);
+
+        let reloaded = UploadManifest::load(path.clone());
+        assert_eq!(reloaded.list().len(), 1);
+        reloaded.remove("u1").unwrap();
+        assert!(UploadManifest::load(path).get("u1").is_none());
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,179 @@
+//! Watch the directories of reference-only videos
+//!
+//! A referenced file that is renamed or deleted used to break its chat
+//! session with no explanation. The watcher polls each referenced path and,
+//! when one disappears, looks through its directory for a file with the
+//! same fingerprint: a match is reported as `video-moved` (with the
+//! suggested new path for `relink_video`), otherwise as `video-missing`.
+//!
+//! Polling rather than OS notifications keeps this identical on every
+//! platform and volume type, including network shares where inotify and
+//! friends are unreliable anyway.
+
+use crate::config;
+use crate::files::in_use::FileSnapshot;
+use crate::library::{FileStatus, Library, LibraryEntry};
+use crate::power::{self, PowerState};
+use crate::staleness;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+
+/// Payload of the `video-missing` and `video-moved` events
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLocationEvent {
+    pub video_id: String,
+    pub display_name: String,
+    pub old_path: String,
+    /// Likely new location; only set for `video-moved`
+    pub new_path: Option<String>,
+}
+
+/// Look in the entry's original directory for a file with the same fingerprint
+///
+/// Only an unambiguous match is returned; two identical candidates are
+/// left for the user to choose between.
+pub fn find_moved(entry: &LibraryEntry) -> Option<PathBuf> {
+    let old_path = entry.path();
+    let dir = old_path.parent()?;
+    let mut candidates = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p != &old_path && is_same_file(p, entry));
+    let first = candidates.next()?;
+    match candidates.next() {
+        Some(_) => None,
+        None => Some(first),
+    }
+}
+
+fn is_same_file(path: &Path, entry: &LibraryEntry) -> bool {
+    path.is_file()
+        && FileSnapshot::take(path)
+            .map(|s| staleness::matches_fingerprint(&s, entry))
+            .unwrap_or(false)
+}
+
+/// Check one entry; returns the event to emit, if its location changed
+fn check_entry(
+    library: &Library,
+    entry: &LibraryEntry,
+) -> Option<(&'static str, FileLocationEvent)> {
+    let exists = entry.path().exists();
+    match (exists, entry.status) {
+        (true, FileStatus::Missing) => {
+            // Came back (remounted drive, undone rename); let the staleness
+            // checker decide whether it is Ok or Modified
+            library
+                .update(&entry.file_id, |e| e.status = FileStatus::Ok)
+                .ok();
+            None
+        }
+        (false, status) if status != FileStatus::Missing => {
+            library
+                .update(&entry.file_id, |e| e.status = FileStatus::Missing)
+                .ok();
+            let new_path = find_moved(entry);
+            let event = FileLocationEvent {
+                video_id: entry.file_id.clone(),
+                display_name: entry.display_name.clone(),
+                old_path: entry.canonical_path.clone(),
+                new_path: new_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            };
+            let name = if new_path.is_some() {
+                "video-moved"
+            } else {
+                "video-missing"
+            };
+            Some((name, event))
+        }
+        _ => None,
+    }
+}
+
+/// Poll reference-only videos and emit location events
+///
+/// In power-saving mode only every [`power::WATCH_SLOWDOWN`]th tick polls.
+pub fn spawn_watcher<R: Runtime>(app: AppHandle<R>) {
+    let interval_secs = config::get().app.file_watch_interval_secs;
+    if interval_secs == 0 {
+        log::info!("File watcher disabled");
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+        let mut ticks: u64 = 0;
+        loop {
+            interval.tick().await;
+            ticks += 1;
+            if app.state::<PowerState>().is_saving() && !ticks.is_multiple_of(power::WATCH_SLOWDOWN)
+            {
+                continue;
+            }
+            let library = app.state::<Library>();
+            for entry in library.entries().into_iter().filter(|e| e.reference_only) {
+                if let Some((name, event)) = check_entry(&library, &entry) {
+                    log::warn!(
+                        "{}: {} ({}) -> {:?}",
+                        name,
+                        event.video_id,
+                        event.old_path,
+                        event.new_path
+                    );
+                    app.emit(&crate::event_name(name), &event).ok();
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::{unix_seconds, volume::VolumeKind};
+
+    fn entry_for(path: &Path) -> LibraryEntry {
+        let snapshot = FileSnapshot::take(path).unwrap();
+        LibraryEntry {
+            file_id: "vid".to_string(),
+            display_name: "clip.mp4".to_string(),
+            original_path: path.to_string_lossy().to_string(),
+            canonical_path: path.to_string_lossy().to_string(),
+            canonical_path_raw: None,
+            name_raw: String::new(),
+            volume_id: String::new(),
+            volume_kind: VolumeKind::Local,
+            reference_only: true,
+            size_bytes: snapshot.size_bytes,
+            sha256: String::new(),
+            modified_at: snapshot.modified.map(unix_seconds).unwrap_or(0.0),
+            container: "mp4".to_string(),
+            registered_at: 0.0,
+            status: FileStatus::Ok,
+            last_verified_at: 0.0,
+            analysis_options: None,
+            detected_language: None,
+        }
+    }
+
+    #[test]
+    fn test_find_moved_after_rename() {
+        let dir = std::env::temp_dir().join(format!("watcher-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let old = dir.join("clip.mp4");
+        std::fs::write(&old, b"not really a video").unwrap();
+        std::fs::write(dir.join("other.mp4"), b"different").unwrap();
+        let entry = entry_for(&old);
+
+        let new = dir.join("renamed.mp4");
+        std::fs::rename(&old, &new).unwrap();
+        assert_eq!(find_moved(&entry), Some(new.clone()));
+
+        std::fs::remove_file(&new).unwrap();
+        assert_eq!(find_moved(&entry), None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
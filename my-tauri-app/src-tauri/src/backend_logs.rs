@@ -0,0 +1,220 @@
+//! The backend sidecar's output, for the debug panel
+//!
+//! Every line the supervised Python process writes is parsed for its log
+//! level and kept in a ring buffer of the last [`MAX_LINES`], which
+//! `get_backend_logs` reads. After `subscribe_backend_logs` each line is
+//! also emitted as a `backend://log` event as it arrives.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_video_analyzer::error::AppError;
+
+/// Lines kept for `get_backend_logs`
+pub const MAX_LINES: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    /// The level a Python log line names, e.g. `INFO:root:...` or
+    /// `2024-01-01 10:00:00,000 - server - WARNING - ...`
+    fn parse(line: &str) -> Option<Self> {
+        line.split(|c: char| !c.is_ascii_alphabetic())
+            .take(12)
+            .find_map(|word| match word {
+                "DEBUG" => Some(Self::Debug),
+                "INFO" => Some(Self::Info),
+                "WARNING" | "WARN" => Some(Self::Warning),
+                "ERROR" | "CRITICAL" | "FATAL" | "Traceback" => Some(Self::Error),
+                _ => None,
+            })
+    }
+
+    fn log_level(self) -> log::Level {
+        match self {
+            Self::Debug => log::Level::Debug,
+            Self::Info => log::Level::Info,
+            Self::Warning => log::Level::Warn,
+            Self::Error => log::Level::Error,
+        }
+    }
+}
+
+/// One line of output; payload of `backend://log`
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    /// Increasing, to spot lines dropped from the buffer
+    pub seq: u64,
+    /// Unix milliseconds
+    pub timestamp: u64,
+    /// `stdout` or `stderr`
+    pub stream: &'static str,
+    pub level: Level,
+    pub message: String,
+}
+
+/// Managed state: the recent output and whether to emit it
+#[derive(Default)]
+pub struct BackendLogs {
+    lines: Mutex<VecDeque<LogLine>>,
+    next_seq: AtomicU64,
+    subscribed: AtomicBool,
+}
+
+impl BackendLogs {
+    /// Keep, log and (when subscribed) emit a line from `stream`
+    pub fn record(&self, app: &AppHandle, stream: &'static str, message: String) {
+        let line = self.push(stream, message);
+        if self.subscribed.load(Ordering::SeqCst) {
+            app.emit("backend://log", &line).ok();
+        }
+    }
+
+    /// Keep and log a line from `stream`
+    ///
+    /// Indented lines without a level of their own take the last line's,
+    /// so a traceback stays an error all the way down.
+    fn push(&self, stream: &'static str, message: String) -> LogLine {
+        let mut lines = self.lines.lock().unwrap();
+        let level = Level::parse(&message)
+            .or_else(|| {
+                let continued = message.starts_with(char::is_whitespace);
+                lines.back().filter(|_| continued).map(|last| last.level)
+            })
+            .unwrap_or(Level::Info);
+        log::log!(target: "backend", level.log_level(), "{message}");
+        let line = LogLine {
+            seq: self.next_seq.fetch_add(1, Ordering::SeqCst),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            stream,
+            level,
+            message,
+        };
+        if lines.len() >= MAX_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.clone());
+        line
+    }
+
+    /// Start or stop emitting `backend://log`
+    pub fn subscribe(&self, subscribed: bool) {
+        self.subscribed.store(subscribed, Ordering::SeqCst);
+    }
+
+    /// The last `limit` lines at `min_level` or above, oldest first
+    pub fn recent(&self, limit: usize, min_level: Level) -> Vec<LogLine> {
+        let lines = self.lines.lock().unwrap();
+        let mut recent: Vec<LogLine> = lines
+            .iter()
+            .rev()
+            .filter(|line| line.level >= min_level)
+            .take(limit)
+            .cloned()
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    /// Lines in the buffer
+    pub fn buffered(&self) -> usize {
+        self.lines.lock().unwrap().len()
+    }
+}
+
+/// `level` as the frontend names it
+pub fn parse_level(level: &str) -> Result<Level, AppError> {
+    match level.trim().to_lowercase().as_str() {
+        "debug" => Ok(Level::Debug),
+        "info" => Ok(Level::Info),
+        "warning" | "warn" => Ok(Level::Warning),
+        "error" => Ok(Level::Error),
+        other => Err(AppError::validation(format!("Unknown log level: {other}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_read_from_python_log_lines() {
+        assert_eq!(Level::parse("INFO:root:Server started"), Some(Level::Info));
+        assert_eq!(
+            Level::parse("2024-01-01 10:00:00,000 - server - WARNING - Slow model"),
+            Some(Level::Warning)
+        );
+        assert_eq!(
+            Level::parse("Traceback (most recent call last):"),
+            Some(Level::Error)
+        );
+        assert_eq!(
+            Level::parse("CRITICAL:root:Out of memory"),
+            Some(Level::Error)
+        );
+        assert_eq!(Level::parse("Loading weights"), None);
+
+        assert_eq!(parse_level(" Warn "), Ok(Level::Warning));
+        assert_eq!(parse_level("error"), Ok(Level::Error));
+        assert!(parse_level("verbose").is_err());
+    }
+
+    #[test]
+    fn continuation_lines_keep_the_previous_level() {
+        let logs = BackendLogs::default();
+        logs.push("stderr", "Traceback (most recent call last):".to_string());
+        let continued = logs.push("stderr", "  File \"server.py\", line 3".to_string());
+        assert_eq!(continued.level, Level::Error);
+        // Unindented and without a level of its own: info
+        let plain = logs.push("stdout", "Loading weights".to_string());
+        assert_eq!(plain.level, Level::Info);
+    }
+
+    #[test]
+    fn the_buffer_keeps_the_last_lines() {
+        let logs = BackendLogs::default();
+        for n in 0..MAX_LINES + 5 {
+            logs.push("stdout", format!("INFO:root:line {n}"));
+        }
+        assert_eq!(logs.buffered(), MAX_LINES);
+        let recent = logs.recent(MAX_LINES, Level::Debug);
+        assert_eq!(recent[0].seq, 5);
+        assert_eq!(recent[0].message, "INFO:root:line 5");
+    }
+
+    #[test]
+    fn recent_filters_by_level_oldest_first() {
+        let logs = BackendLogs::default();
+        logs.push("stderr", "DEBUG:root:a".to_string());
+        logs.push("stderr", "ERROR:root:b".to_string());
+        logs.push("stderr", "INFO:root:c".to_string());
+        logs.push("stderr", "WARNING:root:d".to_string());
+        logs.push("stderr", "ERROR:root:e".to_string());
+
+        let messages = |lines: Vec<LogLine>| -> Vec<String> {
+            lines.into_iter().map(|line| line.message).collect()
+        };
+        assert_eq!(
+            messages(logs.recent(10, Level::Warning)),
+            ["ERROR:root:b", "WARNING:root:d", "ERROR:root:e"]
+        );
+        assert_eq!(
+            messages(logs.recent(2, Level::Info)),
+            ["WARNING:root:d", "ERROR:root:e"]
+        );
+        assert_eq!(logs.recent(10, Level::Debug).len(), 5);
+    }
+}
@@ -0,0 +1,353 @@
+//! Runs the bundled Python backend as a managed sidecar
+//!
+//! On desktop the app starts the gRPC server itself instead of asking the
+//! user to: [`BackendSupervisor::start`] spawns the sidecar, forwards its
+//! stdout/stderr to the log and [`BackendLogs`] (and so `backend://log`),
+//! and waits until the gRPC port accepts connections. The child is killed
+//! when the app exits.
+//!
+//! A backend that exits emits `backend://exited` and, unless the app
+//! stopped it, is restarted after a growing delay, announced as
+//! `backend://restarting`. A restart that fails to spawn or come up, and
+//! giving up after [`MAX_RESTARTS`] crashes within [`RESTART_WINDOW`], are
+//! reported as `backend://restart-failed`, leaving the user to restart the
+//! app.
+//!
+//! Nothing is spawned in dev mode (TAURI_ENV=development), when the
+//! configured server is not on this machine, or when a backend already
+//! answers on its port, e.g. one started by hand. If something else holds
+//! the port, the sidecar gets a free one instead, passed as GRPC_PORT, and
+//! the plugin is pointed at it with [`config::set_launched_backend`].
+
+use crate::backend_logs::BackendLogs;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+use tauri_plugin_video_analyzer::config;
+use tauri_plugin_video_analyzer::error::AppError;
+use tauri_plugin_video_analyzer::transport::grpc::GrpcClientManager;
+use tauri_plugin_video_analyzer::transport::Transport;
+use tokio::net::TcpStream;
+use tokio::time::{sleep, Duration};
+
+/// The backend binary, as listed in `externalBin`
+pub const BACKEND_SIDECAR: &str = "video_analyzer_backend/video_analyzer_backend";
+
+/// Port checks before giving up on a starting backend: 60 × 500 ms
+const READY_RETRIES: usize = 60;
+const READY_DELAY_MS: u64 = 500;
+
+/// Crashes within [`RESTART_WINDOW`] after which the backend is left down
+pub const MAX_RESTARTS: usize = 5;
+pub const RESTART_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// Delay before the first restart, doubling for each further crash in the
+/// window up to [`MAX_RESTART_DELAY`]
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+
+/// How long to wait before restart `attempt`, counting from 1
+fn restart_delay(attempt: usize) -> Duration {
+    let doublings = attempt.saturating_sub(1).min(16) as u32;
+    (RESTART_DELAY * 2u32.pow(doublings)).min(MAX_RESTART_DELAY)
+}
+
+/// Payload of `backend://restarting`
+#[derive(Clone, Serialize)]
+struct Restarting {
+    attempt: usize,
+    max_attempts: usize,
+    delay_ms: u64,
+    exit_code: Option<i32>,
+}
+
+/// Payload of `backend://restart-failed`: the crash that led to it, and
+/// why the restart failed, or none when the supervisor gave up
+#[derive(Clone, Serialize)]
+struct RestartFailed {
+    attempt: usize,
+    exit_code: Option<i32>,
+    error: Option<String>,
+}
+
+/// Dev builds bundle no sidecars (see `tauri.dev.json`); the backend is
+/// run by hand
+pub fn is_dev() -> bool {
+    std::env::var("TAURI_ENV")
+        .map(|v| v == "development")
+        .unwrap_or(false)
+}
+
+/// Whether this platform and build should launch the backend at all
+pub fn should_launch() -> bool {
+    !cfg!(mobile) && !is_dev()
+}
+
+/// The port of `server_url` when it names this machine
+fn local_port(server_url: &str) -> Option<u16> {
+    let authority = server_url.split("://").nth(1)?.split('/').next()?;
+    let (host, port) = authority.rsplit_once(':')?;
+    if !matches!(host, "127.0.0.1" | "localhost" | "[::1]") {
+        return None;
+    }
+    port.parse().ok()
+}
+
+/// How long whatever already holds the port has to answer as a backend
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A port nothing listens on right now, picked by the OS
+fn free_port() -> Result<u16, AppError> {
+    std::net::TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| AppError::internal(format!("No free port for the backend: {e}")))
+}
+
+/// Whether the server at the configured address answers like a backend
+async fn backend_answers(app: &AppHandle) -> bool {
+    let transport = app.state::<Transport>().inner().clone();
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, transport.get_last_session()).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Check if a TCP port is open (used to detect when backend is ready)
+async fn wait_for_port(port: u16, retries: usize, delay_ms: u64) -> bool {
+    for _ in 0..retries {
+        if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+            return true;
+        }
+        sleep(Duration::from_millis(delay_ms)).await;
+    }
+    false
+}
+
+/// Start a sidecar and log the result
+async fn start_sidecar(
+    app: &AppHandle,
+    name: &str,
+    args: &[&str],
+    port: u16,
+) -> Result<(Receiver<CommandEvent>, CommandChild), AppError> {
+    let sidecar = app
+        .shell()
+        .sidecar(name)
+        .map_err(|e| AppError::internal(format!("Sidecar '{name}' not found: {e}")))?;
+
+    let (rx, child) = sidecar
+        .args(args)
+        .env("GRPC_PORT", port.to_string())
+        .spawn()
+        .map_err(|e| AppError::internal(format!("Failed to spawn sidecar '{name}': {e}")))?;
+
+    println!("🟢 Started sidecar: {name}");
+    Ok((rx, child))
+}
+
+/// Managed state: the backend process this app started, if any
+#[derive(Default)]
+pub struct BackendSupervisor {
+    child: Mutex<Option<CommandChild>>,
+    /// Held while starting, so two callers can't spawn two backends
+    starting: tauri::async_runtime::Mutex<()>,
+    /// Set by [`Self::stop`], so the exit it causes isn't taken for a crash
+    stopping: AtomicBool,
+    /// When the backend crashed, within the last [`RESTART_WINDOW`]
+    crashes: Mutex<Vec<Instant>>,
+}
+
+impl BackendSupervisor {
+    /// Launch the backend unless one is already up, then wait until it
+    /// accepts connections
+    pub async fn start(&self, app: &AppHandle) -> Result<(), AppError> {
+        let _starting = self.starting.lock().await;
+        let server_url = config::get().grpc.server_url.clone();
+        let Some(mut port) = local_port(&server_url) else {
+            log::info!("Backend at {server_url} is not local; not launching the sidecar");
+            return Ok(());
+        };
+
+        let running = self.child.lock().unwrap().is_some();
+        if !running {
+            if TcpStream::connect(("127.0.0.1", port)).await.is_ok() {
+                if backend_answers(app).await {
+                    log::info!(
+                        "A backend is already listening on port {port}; not launching another"
+                    );
+                    return Ok(());
+                }
+                // The address the settings name, before any earlier move
+                let configured = match config::launched_backend() {
+                    Some((configured, actual)) if actual == server_url => configured,
+                    _ => server_url.clone(),
+                };
+                let taken = port;
+                port = free_port()?;
+                log::warn!(
+                    "Port {taken} is taken by something else; launching the backend on {port}"
+                );
+                config::set_launched_backend(configured, format!("http://127.0.0.1:{port}"));
+                app.state::<Arc<GrpcClientManager>>().reset();
+            }
+            let (rx, child) = start_sidecar(app, BACKEND_SIDECAR, &[], port).await?;
+            log::info!("Backend sidecar started with pid {}", child.pid());
+            forward_output(app.clone(), child.pid(), rx);
+            *self.child.lock().unwrap() = Some(child);
+            app.emit("status", "🐍 Starting backend…").ok();
+        }
+
+        if wait_for_port(port, READY_RETRIES, READY_DELAY_MS).await {
+            app.emit("status", "✅ Backend ready!").ok();
+            Ok(())
+        } else {
+            Err(AppError::internal("Backend did not start in time."))
+        }
+    }
+
+    /// Kill the backend this app started; called on exit
+    pub fn stop(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        if let Some(child) = self.child.lock().unwrap().take() {
+            let pid = child.pid();
+            match child.kill() {
+                Ok(()) => log::info!("Stopped backend sidecar (pid {pid})"),
+                Err(e) => log::error!("Failed to stop backend sidecar (pid {pid}): {e}"),
+            }
+        }
+    }
+
+    /// Forget the child `pid` once it has exited on its own; true when it
+    /// was the current backend
+    fn exited(&self, pid: u32) -> bool {
+        let mut child = self.child.lock().unwrap();
+        if child.as_ref().is_some_and(|c| c.pid() == pid) {
+            *child = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Start the backend again after it crashed with `exit_code`, unless
+    /// the app is exiting or it has crashed too often already
+    async fn restart(&self, app: &AppHandle, exit_code: Option<i32>) {
+        if self.stopping.load(Ordering::SeqCst) {
+            return;
+        }
+        let attempt = {
+            let mut crashes = self.crashes.lock().unwrap();
+            let now = Instant::now();
+            crashes.retain(|at| now.duration_since(*at) < RESTART_WINDOW);
+            crashes.push(now);
+            crashes.len()
+        };
+        if attempt > MAX_RESTARTS {
+            log::error!(
+                "Backend crashed {attempt} times in {} minutes; not restarting it again",
+                RESTART_WINDOW.as_secs() / 60
+            );
+            app.emit(
+                "backend://restart-failed",
+                RestartFailed {
+                    attempt,
+                    exit_code,
+                    error: None,
+                },
+            )
+            .ok();
+            return;
+        }
+
+        let delay = restart_delay(attempt);
+        log::warn!("Restarting the backend in {delay:?} (attempt {attempt} of {MAX_RESTARTS})");
+        app.emit(
+            "backend://restarting",
+            Restarting {
+                attempt,
+                max_attempts: MAX_RESTARTS,
+                delay_ms: delay.as_millis() as u64,
+                exit_code,
+            },
+        )
+        .ok();
+        sleep(delay).await;
+        if self.stopping.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Err(e) = self.start(app).await {
+            log::error!("Failed to restart the backend: {e}");
+            app.emit(
+                "backend://restart-failed",
+                RestartFailed {
+                    attempt,
+                    exit_code,
+                    error: Some(e.to_string()),
+                },
+            )
+            .ok();
+        }
+    }
+}
+
+/// Hand the sidecar's output to [`BackendLogs`] and watch for its exit
+fn forward_output(app: AppHandle, pid: u32, mut rx: Receiver<CommandEvent>) {
+    tauri::async_runtime::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            match event {
+                CommandEvent::Stdout(line) => {
+                    let line = String::from_utf8_lossy(&line).trim_end().to_string();
+                    app.state::<BackendLogs>().record(&app, "stdout", line);
+                }
+                CommandEvent::Stderr(line) => {
+                    // Python logs to stderr by default, so this isn't only errors
+                    let line = String::from_utf8_lossy(&line).trim_end().to_string();
+                    app.state::<BackendLogs>().record(&app, "stderr", line);
+                }
+                CommandEvent::Error(e) => log::error!(target: "backend", "{e}"),
+                CommandEvent::Terminated(payload) => {
+                    log::warn!(
+                        "Backend sidecar (pid {pid}) exited with code {:?}, signal {:?}",
+                        payload.code,
+                        payload.signal
+                    );
+                    app.emit("backend://exited", payload.code).ok();
+                    let supervisor = app.state::<BackendSupervisor>();
+                    if supervisor.exited(pid) {
+                        supervisor.restart(&app, payload.code).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_port_only_for_this_machine() {
+        assert_eq!(local_port("http://localhost:50051"), Some(50051));
+        assert_eq!(local_port("http://127.0.0.1:50052/"), Some(50052));
+        assert_eq!(local_port("https://[::1]:50053"), Some(50053));
+        assert_eq!(local_port("http://backend.example.com:50051"), None);
+        assert_eq!(local_port("http://127.0.0.1"), None);
+        assert_eq!(local_port("http://[::1]"), None);
+    }
+
+    #[test]
+    fn restart_delay_doubles_up_to_the_cap() {
+        let delays: Vec<u64> = (1..=7).map(|n| restart_delay(n).as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30]);
+        assert_eq!(restart_delay(100), MAX_RESTART_DELAY);
+    }
+}
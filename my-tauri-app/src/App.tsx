@@ -4,6 +4,7 @@ import { ChatComponent } from "./components/ChatComponent";
 import type { ConversationEntry } from "./components/chat/types";
 import { appLayoutConfig, isFullscreenViewport, historyConfig } from "./configs";
 import { invoke } from "@tauri-apps/api/core";
+import type { ChatHistory, SessionInfo } from "./bindings/videoAnalyzer";
 // Removed localStorage persistence; backend is the source of truth
 
 function App() {
@@ -60,7 +61,7 @@ function App() {
       let attempts = 0;
       while (!cancelled && attempts < 30) {
         try {
-          const resp = await invoke("check_backend_ready");
+          const resp = await invoke("plugin:video-analyzer|check_backend_ready");
           const res = resp as { ready?: boolean; message?: string };
           console.log("[Ready] check_backend_ready:", res);
           if (res.ready) {
@@ -99,12 +100,7 @@ function App() {
           return;
         }
         console.log("[Resume] Checking last session (backend ready)…");
-        const resp = await invoke("get_last_session");
-        const last = resp as {
-          has_session?: boolean;
-          video_id?: string;
-          video_name?: string;
-        };
+        const last = await invoke<SessionInfo>("plugin:video-analyzer|get_last_session");
 
         console.log("[Resume] get_last_session response:", last);
         if (!last?.has_session) {
@@ -125,21 +121,17 @@ function App() {
           console.log("[Resume] Prefetching chat history to decide prompt for:", last.video_id);
           // Ensure backend restores the previous session context first
           try {
-            const resumeResp = await invoke("resume_session", { video_id: last.video_id });
+            const resumeResp = await invoke("plugin:video-analyzer|resume_session", { video_id: last.video_id });
             console.log("[Resume] resume_session response:", resumeResp);
           } catch (resumeErr) {
             console.warn("[Resume] resume_session failed (continuing anyway):", resumeErr);
           }
 
           const includeFullForDecision = true; // fetch recent messages to check if any exist
-          const histResp = await invoke("get_chat_history", {
+          const preHistory = await invoke<ChatHistory>("plugin:video-analyzer|get_chat_history", {
             video_id: last.video_id,
             include_full_messages: includeFullForDecision,
           });
-          const preHistory = histResp as {
-            conversation_summary?: string;
-            recent_messages?: { role?: string; content?: string }[];
-          };
 
           const hasSummary = !!preHistory?.conversation_summary && preHistory.conversation_summary.trim().length > 0;
           const hasMessages = !!preHistory?.recent_messages && preHistory.recent_messages.length > 0;
@@ -151,14 +143,14 @@ function App() {
             return;
           }
 
-          const name = last.video_name ?? "previous video";
+          const name = last.video_name || "previous video";
           const shouldContinue = window.confirm(
             `Continue from previous session with \"${name}\"?\nClick OK to resume, or Cancel to start fresh.`
           );
 
           if (shouldContinue) {
             // Set active video and seed using the pre-fetched history according to config
-            setCurrentVideo({ id: last.video_id, name: last.video_name ?? "Untitled" });
+            setCurrentVideo({ id: last.video_id, name: last.video_name || "Untitled" });
 
             if (historyConfig.resumeUseSummary && hasSummary) {
               const msg = preHistory.conversation_summary!.trim();
@@ -180,7 +172,7 @@ function App() {
             // User opted out; clear server-side history and start fresh
             try {
               console.log("[Resume] Clearing chat history for:", last.video_id);
-              await invoke("clear_chat_history", { video_id: last.video_id });
+              await invoke("plugin:video-analyzer|clear_chat_history", { video_id: last.video_id });
             } catch (err) {
               console.error("Failed to clear chat history:", err);
             } finally {
@@ -0,0 +1,144 @@
+// From src-tauri/plugins/tauri-plugin-video-analyzer/src/responses.rs;
+// regenerate with `UPDATE_BINDINGS=1 cargo test bindings`
+// This file has been generated by Specta. DO NOT EDIT.
+
+/**
+ * One chunk of an answer, as `process_query` returns them and
+ * `chat://chunk` streams them, after post-processing
+ */
+export type ChatChunk = 
+/**
+ * Fields added by response transforms, see [`crate::postprocess`]
+ */
+(Partial<{ [key in string]: null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }> }>) & { 
+/**
+ * `ChatResponse.ResponseType`: 0 message, 1 progress, 2 result, 3 error
+ */
+type: number; content: string; agent_name?: string; result_json?: string; 
+/**
+ * Position in the stream; 0 when the backend doesn't number chunks
+ */
+sequence?: number; 
+/**
+ * `assistant`, `status` or `alert`, see [`crate::a11y`]
+ */
+role?: string; agent?: string; is_partial?: boolean; plain_text?: string; 
+/**
+ * The query mode, on results
+ */
+query_type?: string | null; 
+/**
+ * Whether `result_json` has the mode's schema, on results
+ */
+schema_ok?: boolean | null; 
+/**
+ * `result_json` parsed, on results that have a schema
+ */
+result?: JsonValue | null; result_error?: string | null; 
+/**
+ * Answered from the query cache
+ */
+cached?: boolean | null; 
+/**
+ * Id in the [`crate::outbox`] of a question queued until the backend
+ * is back
+ */
+outbox_id?: string | null }
+
+/**
+ * Answer of `get_chat_history`, also what `pin_for_offline` keeps
+ */
+export type ChatHistory = { video_id: string; video_name: string; conversation_summary: string; 
+/**
+ * Only with `include_full_messages`
+ */
+recent_messages: HistoryMessage[]; total_messages: number; created_at: string; updated_at: string; 
+/**
+ * Read from the offline copy or the local mirror because the
+ * backend was unreachable
+ */
+offline: boolean; 
+/**
+ * When the mirrored copy was fetched, if it is one
+ */
+cached_at: number | null }
+
+export type DetectedLanguage = { 
+/**
+ * e.g. "en"
+ */
+code: string; 
+/**
+ * whisper's confidence, 0 to 1
+ */
+probability: number }
+
+export type HistoryMessage = { role: string; content: string; timestamp: string }
+
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+
+/**
+ * Answer of `get_last_session` and `resume_session`
+ */
+export type SessionInfo = { 
+/**
+ * Whether there is a session to go back to, or it was resumed
+ */
+has_session: boolean; video_id: string; video_name: string; video_path: string; 
+/**
+ * Messages in the session; 0 from `resume_session`, which doesn't say
+ */
+message_count: number; last_updated: string; 
+/**
+ * The backend's note on `resume_session`
+ */
+message: string; 
+/**
+ * Read from the local mirror because the backend was unreachable
+ */
+offline: boolean; 
+/**
+ * When the mirrored copy was fetched, if it is one
+ */
+cached_at: number | null }
+
+/**
+ * Answer of `upload_video`, `upload_video_from_path` and `resume_upload`
+ */
+export type UploadResult = { 
+/**
+ * The video's id on the backend; empty if the upload failed
+ */
+file_id: string; success: boolean; message: string; 
+/**
+ * Id of the upload for `upload://progress` and `cancel_upload`; not
+ * set for `upload_video`, which can't be followed or cancelled
+ */
+upload_id: string | null; 
+/**
+ * Problems that didn't stop the upload, e.g. a nearly full quota
+ */
+warnings: string[]; 
+/**
+ * Spoken language found in the audio, when it was checked
+ */
+detected_language: DetectedLanguage | null; 
+/**
+ * The file changed while it was read, so the copy may be incomplete
+ */
+changed_during_upload: boolean; 
+/**
+ * Whether the video was added to the local library; not when it
+ * changed during the upload
+ */
+tracked: boolean; 
+/**
+ * Chunk `resume_upload` carried on from; not set when it started over
+ */
+resumed_from_chunk: number | null; 
+/**
+ * The backend already had a file with the same content, so nothing
+ * was sent and `file_id` is that video
+ */
+already_uploaded: boolean }
+
@@ -1,7 +1,10 @@
 import { useEffect, useRef, useState, type ChangeEvent } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { LiveChat } from "./chat/LiveChat";
 import type { ChatResponseItem, ConversationEntry } from "./chat/types";
+import { errorMessage } from "../utils/errors";
+import type { ChatChunk, UploadResult } from "../bindings/videoAnalyzer";
 
 interface ChatComponentProps {
   videoId: string;
@@ -32,6 +35,36 @@ export function ChatComponent({ videoId, activeVideoName, onVideoUploaded, onCha
     setConversation([]);
   }, [videoId]);
 
+  // Answers to questions queued while the backend was unreachable
+  useEffect(() => {
+    const settle = (id: string) =>
+      setConversation((prev) =>
+        prev.map((entry) => (entry.pendingOutboxId === id ? { ...entry, pendingOutboxId: undefined } : entry))
+      );
+    const sent = listen<{ id: string; video_id: string; responses: ChatChunk[] }>(
+      "video-analyzer://outbox-sent",
+      ({ payload }) => {
+        if (payload.video_id !== videoId) return;
+        const stream = payload.responses as ChatResponseItem[];
+        const summary = summarizeStream(stream);
+        settle(payload.id);
+        addConversationEntry("assistant", summary);
+      }
+    );
+    const failed = listen<{ id: string; video_id: string; error: unknown }>(
+      "video-analyzer://outbox-failed",
+      ({ payload }) => {
+        if (payload.video_id !== videoId) return;
+        settle(payload.id);
+        addConversationEntry("assistant", `❌ Error sending queued query: ${errorMessage(payload.error)}`);
+      }
+    );
+    return () => {
+      sent.then((unlisten) => unlisten());
+      failed.then((unlisten) => unlisten());
+    };
+  }, [videoId]);
+
   // Seed assistant message when resuming a session
   useEffect(() => {
     const msg = initialAssistantMessage?.trim();
@@ -109,13 +142,11 @@ export function ChatComponent({ videoId, activeVideoName, onVideoUploaded, onCha
       // Send a Uint8Array directly to Tauri (avoids huge Array<number> copy)
       const videoData = new Uint8Array(arrayBuffer);
 
-      const response = await invoke("upload_video", {
+      const result = await invoke<UploadResult>("plugin:video-analyzer|upload_video", {
         filename: file.name,
         video_data: videoData,
       });
-
-      const result = response as { file_id?: string; fileId?: string; success: boolean; message?: string };
-      const fileId = result.file_id ?? result.fileId ?? "";
+      const fileId = result.file_id;
 
       if (result.success && fileId) {
         setUploadStatus(`✅ Uploaded ${file.name}`);
@@ -124,7 +155,7 @@ export function ChatComponent({ videoId, activeVideoName, onVideoUploaded, onCha
         setUploadStatus(`❌ Upload failed${result.message ? `: ${result.message}` : ""}`);
       }
     } catch (error) {
-      setUploadStatus(`❌ Upload error: ${error}`);
+      setUploadStatus(`❌ Upload error: ${errorMessage(error)}`);
     } finally {
       event.target.value = "";
     }
@@ -167,11 +198,10 @@ export function ChatComponent({ videoId, activeVideoName, onVideoUploaded, onCha
     }
     setUploadStatus("Uploading...");
     try {
-      const response = await invoke("upload_video_from_path", {
+      const result = await invoke<UploadResult>("plugin:video-analyzer|upload_video_from_path", {
         file_path: filePath,
       });
-      const result = response as { file_id?: string; fileId?: string; success: boolean; message?: string };
-      const fileId = result.file_id ?? result.fileId ?? "";
+      const fileId = result.file_id;
       const name = filePath.split(/[\\/]/).pop() || "video.mp4";
       if (result.success && fileId) {
         setUploadStatus(`✅ Uploaded ${name}`);
@@ -180,7 +210,7 @@ export function ChatComponent({ videoId, activeVideoName, onVideoUploaded, onCha
         setUploadStatus(`❌ Upload failed${result.message ? `: ${result.message}` : ""}`);
       }
     } catch (error) {
-      setUploadStatus(`❌ Upload error: ${error}`);
+      setUploadStatus(`❌ Upload error: ${errorMessage(error)}`);
     }
   }
 
@@ -193,7 +223,7 @@ export function ChatComponent({ videoId, activeVideoName, onVideoUploaded, onCha
     setClearing(true);
     try {
       console.log("[Chat] Clearing server chat history for:", videoId);
-      await invoke("clear_chat_history", { video_id: videoId });
+      await invoke("plugin:video-analyzer|clear_chat_history", { video_id: videoId });
       setConversation([]);
       // Optionally clear the active video in the parent so it doesn't persist across restarts
       onClearActiveVideo?.();
@@ -291,13 +321,21 @@ function renderConversationContent(text: string) {
     setLoading(true);
 
     try {
-      const response = await invoke("process_query", {
+      const response = await invoke("plugin:video-analyzer|process_query", {
         video_id: videoId,
         query,
         query_type: "custom"
       });
 
-      if (Array.isArray(response)) {
+      const queued = Array.isArray(response) ? (response as ChatChunk[]).find((chunk) => chunk.outbox_id) : undefined;
+      if (queued?.outbox_id) {
+        console.log("[Chat] Backend unreachable, query queued:", queued.outbox_id);
+        const outboxId = queued.outbox_id;
+        setConversation((prev) => {
+          const last = prev.map((entry) => entry.role).lastIndexOf("user");
+          return prev.map((entry, i) => (i === last ? { ...entry, pendingOutboxId: outboxId } : entry));
+        });
+      } else if (Array.isArray(response)) {
         const stream = response as ChatResponseItem[];
         const summary = summarizeStream(stream);
         onChatAction(query, summary, stream);
@@ -315,9 +353,9 @@ function renderConversationContent(text: string) {
         }
       }
     } catch (error) {
-      const errorMessage = `❌ Error sending query: ${error}`;
-      addConversationEntry("assistant", errorMessage);
-      onChatAction(query, errorMessage, []);
+      const failure = `❌ Error sending query: ${errorMessage(error)}`;
+      addConversationEntry("assistant", failure);
+      onChatAction(query, failure, []);
     } finally {
       console.log("[Chat] Done handling query");
       setLoading(false);
@@ -344,7 +382,7 @@ function renderConversationContent(text: string) {
         customQuery={customQuery}
         onQueryChange={(value) => setCustomQuery(value)}
         onSend={processQuery}
-        canSend={!!customQuery.trim() && !!videoId && !loading && !resumeLoading && !!backendReady}
+        canSend={!!customQuery.trim() && !!videoId && !loading && !resumeLoading}
         loading={loading}
         resumeLoading={!!resumeLoading}
         backendReady={!!backendReady}
@@ -141,6 +141,7 @@ export function LiveChat({
                     }}
                   >
                     {isUser ? "You" : "Assistant"}
+                    {entry.pendingOutboxId && " · pending, sends when the backend is back"}
                   </div>
                   {renderContent(entry.content)}
                 </div>
@@ -327,7 +328,7 @@ export function LiveChat({
             color: "var(--text)",
             minHeight: "96px"
           }}
-          disabled={loading || !!resumeLoading || !backendReady}
+          disabled={loading || !!resumeLoading}
         />
         <button
           onClick={onSend}
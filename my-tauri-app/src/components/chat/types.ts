@@ -3,8 +3,41 @@ export interface ChatResponseItem {
   content: string;
   agent_name?: string;
   result_json?: string;
+  // Set on RESULT items by process_query
+  query_type?: "summary" | "search" | "timeline" | "object-detection" | "free-form";
+  schema_ok?: boolean;
+  // Typed form of result_json, parsed and validated on the Rust side
+  result?: StructuredResult | null;
+  result_error?: string;
 }
 
+export interface Detection {
+  class: string;
+  confidence: number;
+  timestamp: number;
+  frame?: number | null;
+  bbox?: { x: number; y: number; width: number; height: number } | null;
+}
+
+export interface TimelineEvent {
+  start: number;
+  end: number;
+  label: string;
+}
+
+export interface TranscriptSegment {
+  start: number;
+  end: number;
+  text: string;
+  speaker?: string | null;
+}
+
+export type StructuredResult =
+  | { kind: "detections"; detections: Detection[] }
+  | { kind: "timeline"; timeline: TimelineEvent[] }
+  | { kind: "transcript"; transcript: TranscriptSegment[]; language?: string | null }
+  | { kind: "agent_run"; selected_agents: string[]; llm_calls: number; agent_results: Record<string, unknown> };
+
 export interface ChatMessage {
   role?: string;
   content?: string;
@@ -15,4 +48,14 @@ export interface ConversationEntry {
   id: string;
   role: "user" | "assistant";
   content: string;
+  // Queued in the outbox until the backend is back
+  pendingOutboxId?: string;
+}
+
+// Returned by inspect_attachment and recorded in export_local_history
+export interface AttachmentInfo {
+  filename: string;
+  mime_type: string;
+  size_bytes: number;
+  sha256: string;
 }
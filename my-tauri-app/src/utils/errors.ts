@@ -0,0 +1,37 @@
+/**
+ * Errors rejected by the video-analyzer plugin's commands
+ */
+
+export type AppErrorKind =
+  | "transport"
+  | "backend"
+  | "io"
+  | "integrity"
+  | "validation"
+  | "cancelled"
+  | "internal";
+
+export interface AppError {
+  kind: AppErrorKind;
+  code: string;
+  message: string;
+}
+
+export function isAppError(error: unknown): error is AppError {
+  return (
+    typeof error === "object" &&
+    error !== null &&
+    "kind" in error &&
+    "code" in error &&
+    typeof (error as AppError).message === "string"
+  );
+}
+
+/**
+ * Human-readable text for anything a command or promise rejected with
+ */
+export function errorMessage(error: unknown): string {
+  if (isAppError(error)) return error.message;
+  if (error instanceof Error) return error.message;
+  return String(error);
+}
@@ -24,41 +24,147 @@ _sym_db = _symbol_database.Default()
 
 
 
-DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x1bprotos/video_analyzer.proto\x12\x0evideo_analyzer\"A\n\nVideoChunk\x12\x0c\n\x04\x64\x61ta\x18\x01 \x01(\x0c\x12\x10\n\x08\x66ilename\x18\x02 \x01(\t\x12\x13\n\x0b\x63hunk_index\x18\x03 \x01(\x05\"C\n\x0eUploadResponse\x12\x0f\n\x07\x66ile_id\x18\x01 \x01(\t\x12\x0f\n\x07success\x18\x02 \x01(\x08\x12\x0f\n\x07message\x18\x03 \x01(\t\"W\n\x14RegisterVideoRequest\x12\x11\n\tfile_path\x18\x01 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x02 \x01(\t\x12\x16\n\x0ereference_only\x18\x03 \x01(\x08\"\x9f\x01\n\x15RegisterVideoResponse\x12\x0f\n\x07\x66ile_id\x18\x01 \x01(\t\x12\x13\n\x0bstored_path\x18\x02 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x03 \x01(\t\x12\x0e\n\x06\x63opied\x18\x04 \x01(\x08\x12\x12\n\nsize_bytes\x18\x05 \x01(\x03\x12\x15\n\rregistered_at\x18\x06 \x01(\x01\x12\x0f\n\x07message\x18\x07 \x01(\t\"@\n\x0b\x43hatRequest\x12\x0f\n\x07message\x18\x01 \x01(\t\x12\x0f\n\x07\x66ile_id\x18\x02 \x01(\t\x12\x0f\n\x07\x63ontext\x18\x03 \x01(\t\"\xc3\x01\n\x0c\x43hatResponse\x12\x37\n\x04type\x18\x01 \x01(\x0e\x32).video_analyzer.ChatResponse.ResponseType\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\x12\x12\n\nagent_name\x18\x03 \x01(\t\x12\x13\n\x0bresult_json\x18\x04 \x01(\t\"@\n\x0cResponseType\x12\x0b\n\x07MESSAGE\x10\x00\x12\x0c\n\x08PROGRESS\x10\x01\x12\n\n\x06RESULT\x10\x02\x12\t\n\x05\x45RROR\x10\x03\"\x07\n\x05\x45mpty\"\x91\x01\n\x13LastSessionResponse\x12\x13\n\x0bhas_session\x18\x01 \x01(\x08\x12\x10\n\x08video_id\x18\x02 \x01(\t\x12\x12\n\nvideo_name\x18\x03 \x01(\t\x12\x12\n\nvideo_path\x18\x04 \x01(\t\x12\x15\n\rmessage_count\x18\x05 \x01(\x05\x12\x14\n\x0clast_updated\x18\x06 \x01(\t\"D\n\x11GetHistoryRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x1d\n\x15include_full_messages\x18\x02 \x01(\x08\"\xd2\x01\n\x16GetChatHistoryResponse\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x12\n\nvideo_name\x18\x02 \x01(\t\x12\x1c\n\x14\x63onversation_summary\x18\x03 \x01(\t\x12\x34\n\x0frecent_messages\x18\x04 \x03(\x0b\x32\x1b.video_analyzer.ChatMessage\x12\x16\n\x0etotal_messages\x18\x05 \x01(\x05\x12\x12\n\ncreated_at\x18\x06 \x01(\t\x12\x12\n\nupdated_at\x18\x07 \x01(\t\"\'\n\x13\x43learHistoryRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"8\n\x14\x43learHistoryResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\"?\n\x0b\x43hatMessage\x12\x0c\n\x04role\x18\x01 \x01(\t\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\x12\x11\n\ttimestamp\x18\x03 \x01(\t2\xa0\x04\n\x14VideoAnalyzerService\x12K\n\x0bUploadVideo\x12\x1a.video_analyzer.VideoChunk\x1a\x1e.video_analyzer.UploadResponse(\x01\x12\x61\n\x12RegisterLocalVideo\x12$.video_analyzer.RegisterVideoRequest\x1a%.video_analyzer.RegisterVideoResponse\x12N\n\x0fSendChatMessage\x12\x1b.video_analyzer.ChatRequest\x1a\x1c.video_analyzer.ChatResponse0\x01\x12L\n\x0eGetLastSession\x12\x15.video_analyzer.Empty\x1a#.video_analyzer.LastSessionResponse\x12[\n\x0eGetChatHistory\x12!.video_analyzer.GetHistoryRequest\x1a&.video_analyzer.GetChatHistoryResponse\x12]\n\x10\x43learChatHistory\x12#.video_analyzer.ClearHistoryRequest\x1a$.video_analyzer.ClearHistoryResponseb\x06proto3')
+DESCRIPTOR = _descriptor_pool.Default().AddSerializedFile(b'\n\x1bprotos/video_analyzer.proto\x12\x0evideo_analyzer\"\xec\x02\n\nVideoChunk\x12\x0c\n\x04\x64\x61ta\x18\x01 \x01(\x0c\x12\x10\n\x08\x66ilename\x18\x02 \x01(\t\x12\x13\n\x0b\x63hunk_index\x18\x03 \x01(\x05\x12\x39\n\x10\x61nalysis_options\x18\x04 \x01(\x0b\x32\x1f.video_analyzer.AnalysisOptions\x12\x17\n\x0fidempotency_key\x18\x05 \x01(\t\x12\x0e\n\x06offset\x18\x06 \x01(\x03\x12\x15\n\rlanguage_hint\x18\x07 \x01(\t\x12\x33\n\nencryption\x18\x08 \x01(\x0b\x32\x1f.video_analyzer.ChunkEncryption\x12\x0e\n\x06sha256\x18\t \x01(\t\x12\x13\n\x0b\x66ile_sha256\x18\n \x01(\t\x12\x14\n\x0cstream_index\x18\x0b \x01(\x05\x12\x14\n\x0cstream_count\x18\x0c \x01(\x05\x12\x11\n\tfile_size\x18\r \x01(\x03\x12\x15\n\rsource_sha256\x18\x0e \x01(\t\"C\n\x0f\x43hunkEncryption\x12\x11\n\talgorithm\x18\x01 \x01(\t\x12\r\n\x05nonce\x18\x02 \x01(\x0c\x12\x0e\n\x06key_id\x18\x03 \x01(\t\"\x8e\x01\n\x0f\x41nalysisOptions\x12\x12\n\nmodel_tier\x18\x01 \x01(\t\x12\x10\n\x08language\x18\x02 \x01(\t\x12\x19\n\x11\x66rame_sample_rate\x18\x03 \x01(\x01\x12!\n\x14\x65nable_transcription\x18\x04 \x01(\x08H\x00\x88\x01\x01\x42\x17\n\x15_enable_transcription\"S\n\x0eUploadResponse\x12\x0f\n\x07\x66ile_id\x18\x01 \x01(\t\x12\x0f\n\x07success\x18\x02 \x01(\x08\x12\x0f\n\x07message\x18\x03 \x01(\t\x12\x0e\n\x06sha256\x18\x04 \x01(\t\".\n\x13UploadStatusRequest\x12\x17\n\x0fidempotency_key\x18\x01 \x01(\t\"s\n\x14UploadStatusResponse\x12\x11\n\tcompleted\x18\x01 \x01(\x08\x12\x30\n\x08response\x18\x02 \x01(\x0b\x32\x1e.video_analyzer.UploadResponse\x12\x16\n\x0e\x62ytes_received\x18\x03 \x01(\x03\"f\n\x13UploadOffsetRequest\x12\x17\n\x0fidempotency_key\x18\x01 \x01(\t\x12\x0e\n\x06sha256\x18\x02 \x01(\t\x12\x12\n\nsize_bytes\x18\x03 \x01(\x03\x12\x12\n\nchunk_size\x18\x04 \x01(\x03\"u\n\x14UploadOffsetResponse\x12\x18\n\x10next_chunk_index\x18\x01 \x01(\x03\x12\x11\n\tcompleted\x18\x02 \x01(\x08\x12\x30\n\x08response\x18\x03 \x01(\x0b\x32\x1e.video_analyzer.UploadResponse\"<\n\x16\x43heckFileExistsRequest\x12\x0e\n\x06sha256\x18\x01 \x01(\t\x12\x12\n\nsize_bytes\x18\x02 \x01(\x03\"P\n\x17\x43heckFileExistsResponse\x12\x0e\n\x06\x65xists\x18\x01 \x01(\x08\x12\x0f\n\x07\x66ile_id\x18\x02 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x03 \x01(\t\"\x86\x02\n\x14RegisterVideoRequest\x12\x11\n\tfile_path\x18\x01 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x02 \x01(\t\x12\x16\n\x0ereference_only\x18\x03 \x01(\x08\x12\x12\n\nsize_bytes\x18\x04 \x01(\x03\x12\x0e\n\x06sha256\x18\x05 \x01(\t\x12\x13\n\x0bmodified_at\x18\x06 \x01(\x01\x12\x11\n\tcontainer\x18\x07 \x01(\t\x12\x0f\n\x07\x66ile_id\x18\x08 \x01(\t\x12\x39\n\x10\x61nalysis_options\x18\t \x01(\x0b\x32\x1f.video_analyzer.AnalysisOptions\x12\x15\n\rlanguage_hint\x18\n \x01(\t\"\x9f\x01\n\x15RegisterVideoResponse\x12\x0f\n\x07\x66ile_id\x18\x01 \x01(\t\x12\x13\n\x0bstored_path\x18\x02 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x03 \x01(\t\x12\x0e\n\x06\x63opied\x18\x04 \x01(\x08\x12\x12\n\nsize_bytes\x18\x05 \x01(\x03\x12\x15\n\rregistered_at\x18\x06 \x01(\x01\x12\x0f\n\x07message\x18\x07 \x01(\t\"\xf2\x02\n\x0b\x43hatRequest\x12\x0f\n\x07message\x18\x01 \x01(\t\x12\x0f\n\x07\x66ile_id\x18\x02 \x01(\t\x12\x0f\n\x07\x63ontext\x18\x03 \x01(\t\x12\x39\n\nquery_type\x18\x04 \x01(\x0e\x32%.video_analyzer.ChatRequest.QueryType\x12/\n\x0b\x61ttachments\x18\x05 \x03(\x0b\x32\x1a.video_analyzer.Attachment\x12\x12\n\nrequest_id\x18\x06 \x01(\t\x12-\n\x06params\x18\x07 \x01(\x0b\x32\x1d.video_analyzer.SessionParams\x12(\n\x05range\x18\x08 \x01(\x0b\x32\x19.video_analyzer.TimeRange\"W\n\tQueryType\x12\r\n\tFREE_FORM\x10\x00\x12\x0b\n\x07SUMMARY\x10\x01\x12\n\n\x06SEARCH\x10\x02\x12\x0c\n\x08TIMELINE\x10\x03\x12\x14\n\x10OBJECT_DETECTION\x10\x04\"-\n\tTimeRange\x12\x10\n\x08start_ms\x18\x01 \x01(\x03\x12\x0e\n\x06\x65nd_ms\x18\x02 \x01(\x03\"p\n\rSessionParams\x12\x18\n\x0btemperature\x18\x01 \x01(\x02H\x00\x88\x01\x01\x12\x17\n\nmax_tokens\x18\x02 \x01(\rH\x01\x88\x01\x01\x12\r\n\x05model\x18\x03 \x01(\tB\x0e\n\x0c_temperatureB\r\n\x0b_max_tokens\"c\n\nAttachment\x12\x10\n\x08\x66ilename\x18\x01 \x01(\t\x12\x11\n\tmime_type\x18\x02 \x01(\t\x12\x0c\n\x04\x64\x61ta\x18\x03 \x01(\x0c\x12\x12\n\nsize_bytes\x18\x04 \x01(\x03\x12\x0e\n\x06sha256\x18\x05 \x01(\t\"\xd5\x01\n\x0c\x43hatResponse\x12\x37\n\x04type\x18\x01 \x01(\x0e\x32).video_analyzer.ChatResponse.ResponseType\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\x12\x12\n\nagent_name\x18\x03 \x01(\t\x12\x13\n\x0bresult_json\x18\x04 \x01(\t\x12\x10\n\x08sequence\x18\x05 \x01(\x04\"@\n\x0cResponseType\x12\x0b\n\x07MESSAGE\x10\x00\x12\x0c\n\x08PROGRESS\x10\x01\x12\n\n\x06RESULT\x10\x02\x12\t\n\x05\x45RROR\x10\x03\"\x07\n\x05\x45mpty\"\x91\x01\n\x13LastSessionResponse\x12\x13\n\x0bhas_session\x18\x01 \x01(\x08\x12\x10\n\x08video_id\x18\x02 \x01(\t\x12\x12\n\nvideo_name\x18\x03 \x01(\t\x12\x12\n\nvideo_path\x18\x04 \x01(\t\x12\x15\n\rmessage_count\x18\x05 \x01(\x05\x12\x14\n\x0clast_updated\x18\x06 \x01(\t\"D\n\x11GetHistoryRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x1d\n\x15include_full_messages\x18\x02 \x01(\x08\"\xd2\x01\n\x16GetChatHistoryResponse\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x12\n\nvideo_name\x18\x02 \x01(\t\x12\x1c\n\x14\x63onversation_summary\x18\x03 \x01(\t\x12\x34\n\x0frecent_messages\x18\x04 \x03(\x0b\x32\x1b.video_analyzer.ChatMessage\x12\x16\n\x0etotal_messages\x18\x05 \x01(\x05\x12\x12\n\ncreated_at\x18\x06 \x01(\t\x12\x12\n\nupdated_at\x18\x07 \x01(\t\"\'\n\x13\x43learHistoryRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"8\n\x14\x43learHistoryResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\"?\n\x0b\x43hatMessage\x12\x0c\n\x04role\x18\x01 \x01(\t\x12\x0f\n\x07\x63ontent\x18\x02 \x01(\t\x12\x11\n\ttimestamp\x18\x03 \x01(\t\"!\n\rResumeRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"l\n\x0eResumeResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\x12\x10\n\x08video_id\x18\x03 \x01(\t\x12\x12\n\nvideo_name\x18\x04 \x01(\t\x12\x12\n\nvideo_path\x18\x05 \x01(\t\"\xa4\x01\n\x11VideoStorageUsage\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x12\n\nvideo_name\x18\x02 \x01(\t\x12\x13\n\x0bvideo_bytes\x18\x03 \x01(\x03\x12\x15\n\rderived_bytes\x18\x04 \x01(\x03\x12\x13\n\x0btotal_bytes\x18\x05 \x01(\x03\x12\x0e\n\x06\x63opied\x18\x06 \x01(\x08\x12\x18\n\x10last_accessed_at\x18\x07 \x01(\x01\"\x8c\x01\n\x14StorageUsageResponse\x12\x31\n\x06videos\x18\x01 \x03(\x0b\x32!.video_analyzer.VideoStorageUsage\x12\x13\n\x0btotal_bytes\x18\x02 \x01(\x03\x12\x17\n\x0f\x61vailable_bytes\x18\x03 \x01(\x03\x12\x13\n\x0bquota_bytes\x18\x04 \x01(\x03\"U\n\x0e\x43leanupRequest\x12\x19\n\x11registered_before\x18\x01 \x01(\x01\x12\x17\n\x0f\x61\x63\x63\x65ssed_before\x18\x02 \x01(\x01\x12\x0f\n\x07\x64ry_run\x18\x03 \x01(\x08\"{\n\x0f\x43leanupResponse\x12\x31\n\x06videos\x18\x01 \x03(\x0b\x32!.video_analyzer.VideoStorageUsage\x12\x13\n\x0b\x66reed_bytes\x18\x02 \x01(\x03\x12\x0f\n\x07\x64ry_run\x18\x03 \x01(\x08\x12\x0f\n\x07message\x18\x04 \x01(\t\"b\n\x1cUpdateAnalysisOptionsRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x30\n\x07options\x18\x02 \x01(\x0b\x32\x1f.video_analyzer.AnalysisOptions\"\x90\x01\n\x1dUpdateAnalysisOptionsResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\x12\x30\n\x07options\x18\x03 \x01(\x0b\x32\x1f.video_analyzer.AnalysisOptions\x12\x1b\n\x13reanalysis_required\x18\x04 \x01(\x08\"\xf6\x01\n\x0f\x46\x65\x65\x64\x62\x61\x63kRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x15\n\rmessage_index\x18\x02 \x01(\x05\x12\x36\n\x06rating\x18\x03 \x01(\x0e\x32&.video_analyzer.FeedbackRequest.Rating\x12\x0f\n\x07\x63omment\x18\x04 \x01(\t\x12\x10\n\x08question\x18\x05 \x01(\t\x12\x0e\n\x06\x61nswer\x18\x06 \x01(\t\x12\x10\n\x08rated_at\x18\x07 \x01(\x01\"=\n\x06Rating\x12\x16\n\x12RATING_UNSPECIFIED\x10\x00\x12\x06\n\x02UP\x10\x01\x12\x08\n\x04\x44OWN\x10\x02\x12\t\n\x05WRONG\x10\x03\"4\n\x10\x46\x65\x65\x64\x62\x61\x63kResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\"@\n\x14OfflineAssetsRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x16\n\x0emax_key_frames\x18\x02 \x01(\x05\"\xd1\x01\n\x0cOfflineAsset\x12/\n\x04kind\x18\x01 \x01(\x0e\x32!.video_analyzer.OfflineAsset.Kind\x12\x0c\n\x04name\x18\x02 \x01(\t\x12\x11\n\tmime_type\x18\x03 \x01(\t\x12\x0c\n\x04\x64\x61ta\x18\x04 \x01(\x0c\x12\x19\n\x11timestamp_seconds\x18\x05 \x01(\x01\"F\n\x04Kind\x12\x14\n\x10KIND_UNSPECIFIED\x10\x00\x12\r\n\tTHUMBNAIL\x10\x01\x12\r\n\tKEY_FRAME\x10\x02\x12\n\n\x06REPORT\x10\x03\"%\n\x11TranscriptRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"T\n\x11TranscriptSegment\x12\x10\n\x08start_ms\x18\x01 \x01(\x03\x12\x0e\n\x06\x65nd_ms\x18\x02 \x01(\x03\x12\x0c\n\x04text\x18\x03 \x01(\t\x12\x0f\n\x07speaker\x18\x04 \x01(\t\"\x91\x01\n\x12TranscriptResponse\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x11\n\tavailable\x18\x02 \x01(\x08\x12\x10\n\x08language\x18\x03 \x01(\t\x12\x33\n\x08segments\x18\x04 \x03(\x0b\x32!.video_analyzer.TranscriptSegment\x12\x0f\n\x07message\x18\x05 \x01(\t\"%\n\x11\x44\x65tectionsRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"X\n\x0b\x42oundingBox\x12\x14\n\x0ctimestamp_ms\x18\x01 \x01(\x03\x12\t\n\x01x\x18\x02 \x01(\x02\x12\t\n\x01y\x18\x03 \x01(\x02\x12\r\n\x05width\x18\x04 \x01(\x02\x12\x0e\n\x06height\x18\x05 \x01(\x02\"\x93\x01\n\tDetection\x12\r\n\x05label\x18\x01 \x01(\t\x12\x0c\n\x04kind\x18\x02 \x01(\t\x12\x12\n\nconfidence\x18\x03 \x01(\x02\x12)\n\x06ranges\x18\x04 \x03(\x0b\x32\x19.video_analyzer.TimeRange\x12*\n\x05\x62oxes\x18\x05 \x03(\x0b\x32\x1b.video_analyzer.BoundingBox\"y\n\x12\x44\x65tectionsResponse\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x11\n\tavailable\x18\x02 \x01(\x08\x12-\n\ndetections\x18\x03 \x03(\x0b\x32\x19.video_analyzer.Detection\x12\x0f\n\x07message\x18\x04 \x01(\t\"#\n\x0fTimelineRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"_\n\rTimelineEvent\x12\x10\n\x08start_ms\x18\x01 \x01(\x03\x12\x0e\n\x06\x65nd_ms\x18\x02 \x01(\x03\x12\x0c\n\x04kind\x18\x03 \x01(\t\x12\r\n\x05title\x18\x04 \x01(\t\x12\x0f\n\x07summary\x18\x05 \x01(\t\"w\n\x10TimelineResponse\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x11\n\tavailable\x18\x02 \x01(\x08\x12-\n\x06\x65vents\x18\x03 \x03(\x0b\x32\x1d.video_analyzer.TimelineEvent\x12\x0f\n\x07message\x18\x04 \x01(\t\"=\n\x10ShareLinkRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x17\n\x0f\x65xpires_in_secs\x18\x02 \x01(\x03\"F\n\x11ShareLinkResponse\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x0b\n\x03url\x18\x02 \x01(\t\x12\x12\n\nexpires_at\x18\x03 \x01(\x03\"(\n\x13\x42\x61\x63kendQueueRequest\x12\x11\n\tonly_mine\x18\x01 \x01(\x08\"\xba\x01\n\nQueuedTask\x12\x0f\n\x07task_id\x18\x01 \x01(\t\x12\x10\n\x08video_id\x18\x02 \x01(\t\x12\r\n\x05owner\x18\x03 \x01(\t\x12\x0f\n\x07is_mine\x18\x04 \x01(\x08\x12\x0c\n\x04kind\x18\x05 \x01(\t\x12\r\n\x05state\x18\x06 \x01(\t\x12\x10\n\x08position\x18\x07 \x01(\x05\x12\x14\n\x0csubmitted_at\x18\x08 \x01(\x01\x12\x12\n\nstarted_at\x18\t \x01(\x01\x12\x10\n\x08progress\x18\n \x01(\x02\"R\n\x14\x42\x61\x63kendQueueResponse\x12)\n\x05tasks\x18\x01 \x03(\x0b\x32\x1a.video_analyzer.QueuedTask\x12\x0f\n\x07workers\x18\x02 \x01(\x05\"+\n\x17\x43\x61ncelProcessingRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"U\n\x18\x43\x61ncelProcessingResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\x12\x17\n\x0f\x63\x61ncelled_tasks\x18\x03 \x01(\x05\"&\n\x12\x44\x65leteVideoRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\"f\n\x13\x44\x65leteVideoResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\x12\x18\n\x10\x64\x65leted_messages\x18\x03 \x01(\x05\x12\x13\n\x0b\x66reed_bytes\x18\x04 \x01(\x03\"D\n\x1aUpdateVideoMetadataRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x02 \x01(\t\"U\n\x1bUpdateVideoMetadataResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\x12\x14\n\x0c\x64isplay_name\x18\x03 \x01(\t\"H\n\x07\x43\x61ption\x12\x10\n\x08language\x18\x01 \x01(\t\x12\x0e\n\x06\x66ormat\x18\x02 \x01(\t\x12\x0c\n\x04text\x18\x03 \x01(\t\x12\r\n\x05title\x18\x04 \x01(\t\"Q\n\x12\x41\x64\x64\x43\x61ptionsRequest\x12\x10\n\x08video_id\x18\x01 \x01(\t\x12)\n\x08\x63\x61ptions\x18\x02 \x03(\x0b\x32\x17.video_analyzer.Caption\"N\n\x13\x41\x64\x64\x43\x61ptionsResponse\x12\x0f\n\x07success\x18\x01 \x01(\x08\x12\x0f\n\x07message\x18\x02 \x01(\t\x12\x15\n\rcaptions_kept\x18\x03 \x01(\x05\"\xd1\x01\n\nServerInfo\x12\x0f\n\x07version\x18\x01 \x01(\t\x12\x15\n\rproto_version\x18\x02 \x01(\x05\x12\x1a\n\x12min_client_version\x18\x03 \x01(\t\x12\x12\n\nstarted_at\x18\x04 \x01(\x01\x12:\n\x08\x66\x65\x61tures\x18\x05 \x03(\x0b\x32(.video_analyzer.ServerInfo.FeaturesEntry\x1a/\n\rFeaturesEntry\x12\x0b\n\x03key\x18\x01 \x01(\t\x12\r\n\x05value\x18\x02 \x01(\x08:\x02\x38\x01\x32\xe0\x11\n\x14VideoAnalyzerService\x12K\n\x0bUploadVideo\x12\x1a.video_analyzer.VideoChunk\x1a\x1e.video_analyzer.UploadResponse(\x01\x12\\\n\x0fGetUploadStatus\x12#.video_analyzer.UploadStatusRequest\x1a$.video_analyzer.UploadStatusResponse\x12\x62\n\x15NegotiateUploadOffset\x12#.video_analyzer.UploadOffsetRequest\x1a$.video_analyzer.UploadOffsetResponse\x12\x62\n\x0f\x43heckFileExists\x12&.video_analyzer.CheckFileExistsRequest\x1a\'.video_analyzer.CheckFileExistsResponse\x12\x61\n\x12RegisterLocalVideo\x12$.video_analyzer.RegisterVideoRequest\x1a%.video_analyzer.RegisterVideoResponse\x12N\n\x0fSendChatMessage\x12\x1b.video_analyzer.ChatRequest\x1a\x1c.video_analyzer.ChatResponse0\x01\x12L\n\x0eGetLastSession\x12\x15.video_analyzer.Empty\x1a#.video_analyzer.LastSessionResponse\x12[\n\x0eGetChatHistory\x12!.video_analyzer.GetHistoryRequest\x1a&.video_analyzer.GetChatHistoryResponse\x12]\n\x10\x43learChatHistory\x12#.video_analyzer.ClearHistoryRequest\x1a$.video_analyzer.ClearHistoryResponse\x12N\n\rResumeSession\x12\x1d.video_analyzer.ResumeRequest\x1a\x1e.video_analyzer.ResumeResponse\x12N\n\x0fGetStorageUsage\x12\x15.video_analyzer.Empty\x1a$.video_analyzer.StorageUsageResponse\x12P\n\rCleanupVideos\x12\x1e.video_analyzer.CleanupRequest\x1a\x1f.video_analyzer.CleanupResponse\x12t\n\x15UpdateAnalysisOptions\x12,.video_analyzer.UpdateAnalysisOptionsRequest\x1a-.video_analyzer.UpdateAnalysisOptionsResponse\x12S\n\x0eSubmitFeedback\x12\x1f.video_analyzer.FeedbackRequest\x1a .video_analyzer.FeedbackResponse\x12X\n\x10GetOfflineAssets\x12$.video_analyzer.OfflineAssetsRequest\x1a\x1c.video_analyzer.OfflineAsset0\x01\x12V\n\rGetTranscript\x12!.video_analyzer.TranscriptRequest\x1a\".video_analyzer.TranscriptResponse\x12V\n\rGetDetections\x12!.video_analyzer.DetectionsRequest\x1a\".video_analyzer.DetectionsResponse\x12P\n\x0bGetTimeline\x12\x1f.video_analyzer.TimelineRequest\x1a .video_analyzer.TimelineResponse\x12V\n\x0f\x43reateShareLink\x12 .video_analyzer.ShareLinkRequest\x1a!.video_analyzer.ShareLinkResponse\x12\\\n\x0fGetBackendQueue\x12#.video_analyzer.BackendQueueRequest\x1a$.video_analyzer.BackendQueueResponse\x12\x65\n\x10\x43\x61ncelProcessing\x12\'.video_analyzer.CancelProcessingRequest\x1a(.video_analyzer.CancelProcessingResponse\x12V\n\x0b\x44\x65leteVideo\x12\".video_analyzer.DeleteVideoRequest\x1a#.video_analyzer.DeleteVideoResponse\x12n\n\x13UpdateVideoMetadata\x12*.video_analyzer.UpdateVideoMetadataRequest\x1a+.video_analyzer.UpdateVideoMetadataResponse\x12V\n\x0b\x41\x64\x64\x43\x61ptions\x12\".video_analyzer.AddCaptionsRequest\x1a#.video_analyzer.AddCaptionsResponse\x12\x42\n\rGetServerInfo\x12\x15.video_analyzer.Empty\x1a\x1a.video_analyzer.ServerInfob\x06proto3')
 
 _globals = globals()
 _builder.BuildMessageAndEnumDescriptors(DESCRIPTOR, _globals)
 _builder.BuildTopDescriptorsAndMessages(DESCRIPTOR, 'protos.video_analyzer_pb2', _globals)
 if not _descriptor._USE_C_DESCRIPTORS:
   DESCRIPTOR._loaded_options = None
-  _globals['_VIDEOCHUNK']._serialized_start=47
-  _globals['_VIDEOCHUNK']._serialized_end=112
-  _globals['_UPLOADRESPONSE']._serialized_start=114
-  _globals['_UPLOADRESPONSE']._serialized_end=181
-  _globals['_REGISTERVIDEOREQUEST']._serialized_start=183
-  _globals['_REGISTERVIDEOREQUEST']._serialized_end=270
-  _globals['_REGISTERVIDEORESPONSE']._serialized_start=273
-  _globals['_REGISTERVIDEORESPONSE']._serialized_end=432
-  _globals['_CHATREQUEST']._serialized_start=434
-  _globals['_CHATREQUEST']._serialized_end=498
-  _globals['_CHATRESPONSE']._serialized_start=501
-  _globals['_CHATRESPONSE']._serialized_end=696
-  _globals['_CHATRESPONSE_RESPONSETYPE']._serialized_start=632
-  _globals['_CHATRESPONSE_RESPONSETYPE']._serialized_end=696
-  _globals['_EMPTY']._serialized_start=698
-  _globals['_EMPTY']._serialized_end=705
-  _globals['_LASTSESSIONRESPONSE']._serialized_start=708
-  _globals['_LASTSESSIONRESPONSE']._serialized_end=853
-  _globals['_GETHISTORYREQUEST']._serialized_start=855
-  _globals['_GETHISTORYREQUEST']._serialized_end=923
-  _globals['_GETCHATHISTORYRESPONSE']._serialized_start=926
-  _globals['_GETCHATHISTORYRESPONSE']._serialized_end=1136
-  _globals['_CLEARHISTORYREQUEST']._serialized_start=1138
-  _globals['_CLEARHISTORYREQUEST']._serialized_end=1177
-  _globals['_CLEARHISTORYRESPONSE']._serialized_start=1179
-  _globals['_CLEARHISTORYRESPONSE']._serialized_end=1235
-  _globals['_CHATMESSAGE']._serialized_start=1237
-  _globals['_CHATMESSAGE']._serialized_end=1300
-  _globals['_VIDEOANALYZERSERVICE']._serialized_start=1303
-  _globals['_VIDEOANALYZERSERVICE']._serialized_end=1847
+  _globals['_SERVERINFO_FEATURESENTRY']._loaded_options = None
+  _globals['_SERVERINFO_FEATURESENTRY']._serialized_options = b'8\001'
+  _globals['_VIDEOCHUNK']._serialized_start=48
+  _globals['_VIDEOCHUNK']._serialized_end=412
+  _globals['_CHUNKENCRYPTION']._serialized_start=414
+  _globals['_CHUNKENCRYPTION']._serialized_end=481
+  _globals['_ANALYSISOPTIONS']._serialized_start=484
+  _globals['_ANALYSISOPTIONS']._serialized_end=626
+  _globals['_UPLOADRESPONSE']._serialized_start=628
+  _globals['_UPLOADRESPONSE']._serialized_end=711
+  _globals['_UPLOADSTATUSREQUEST']._serialized_start=713
+  _globals['_UPLOADSTATUSREQUEST']._serialized_end=759
+  _globals['_UPLOADSTATUSRESPONSE']._serialized_start=761
+  _globals['_UPLOADSTATUSRESPONSE']._serialized_end=876
+  _globals['_UPLOADOFFSETREQUEST']._serialized_start=878
+  _globals['_UPLOADOFFSETREQUEST']._serialized_end=980
+  _globals['_UPLOADOFFSETRESPONSE']._serialized_start=982
+  _globals['_UPLOADOFFSETRESPONSE']._serialized_end=1099
+  _globals['_CHECKFILEEXISTSREQUEST']._serialized_start=1101
+  _globals['_CHECKFILEEXISTSREQUEST']._serialized_end=1161
+  _globals['_CHECKFILEEXISTSRESPONSE']._serialized_start=1163
+  _globals['_CHECKFILEEXISTSRESPONSE']._serialized_end=1243
+  _globals['_REGISTERVIDEOREQUEST']._serialized_start=1246
+  _globals['_REGISTERVIDEOREQUEST']._serialized_end=1508
+  _globals['_REGISTERVIDEORESPONSE']._serialized_start=1511
+  _globals['_REGISTERVIDEORESPONSE']._serialized_end=1670
+  _globals['_CHATREQUEST']._serialized_start=1673
+  _globals['_CHATREQUEST']._serialized_end=2043
+  _globals['_CHATREQUEST_QUERYTYPE']._serialized_start=1956
+  _globals['_CHATREQUEST_QUERYTYPE']._serialized_end=2043
+  _globals['_TIMERANGE']._serialized_start=2045
+  _globals['_TIMERANGE']._serialized_end=2090
+  _globals['_SESSIONPARAMS']._serialized_start=2092
+  _globals['_SESSIONPARAMS']._serialized_end=2204
+  _globals['_ATTACHMENT']._serialized_start=2206
+  _globals['_ATTACHMENT']._serialized_end=2305
+  _globals['_CHATRESPONSE']._serialized_start=2308
+  _globals['_CHATRESPONSE']._serialized_end=2521
+  _globals['_CHATRESPONSE_RESPONSETYPE']._serialized_start=2457
+  _globals['_CHATRESPONSE_RESPONSETYPE']._serialized_end=2521
+  _globals['_EMPTY']._serialized_start=2523
+  _globals['_EMPTY']._serialized_end=2530
+  _globals['_LASTSESSIONRESPONSE']._serialized_start=2533
+  _globals['_LASTSESSIONRESPONSE']._serialized_end=2678
+  _globals['_GETHISTORYREQUEST']._serialized_start=2680
+  _globals['_GETHISTORYREQUEST']._serialized_end=2748
+  _globals['_GETCHATHISTORYRESPONSE']._serialized_start=2751
+  _globals['_GETCHATHISTORYRESPONSE']._serialized_end=2961
+  _globals['_CLEARHISTORYREQUEST']._serialized_start=2963
+  _globals['_CLEARHISTORYREQUEST']._serialized_end=3002
+  _globals['_CLEARHISTORYRESPONSE']._serialized_start=3004
+  _globals['_CLEARHISTORYRESPONSE']._serialized_end=3060
+  _globals['_CHATMESSAGE']._serialized_start=3062
+  _globals['_CHATMESSAGE']._serialized_end=3125
+  _globals['_RESUMEREQUEST']._serialized_start=3127
+  _globals['_RESUMEREQUEST']._serialized_end=3160
+  _globals['_RESUMERESPONSE']._serialized_start=3162
+  _globals['_RESUMERESPONSE']._serialized_end=3270
+  _globals['_VIDEOSTORAGEUSAGE']._serialized_start=3273
+  _globals['_VIDEOSTORAGEUSAGE']._serialized_end=3437
+  _globals['_STORAGEUSAGERESPONSE']._serialized_start=3440
+  _globals['_STORAGEUSAGERESPONSE']._serialized_end=3580
+  _globals['_CLEANUPREQUEST']._serialized_start=3582
+  _globals['_CLEANUPREQUEST']._serialized_end=3667
+  _globals['_CLEANUPRESPONSE']._serialized_start=3669
+  _globals['_CLEANUPRESPONSE']._serialized_end=3792
+  _globals['_UPDATEANALYSISOPTIONSREQUEST']._serialized_start=3794
+  _globals['_UPDATEANALYSISOPTIONSREQUEST']._serialized_end=3892
+  _globals['_UPDATEANALYSISOPTIONSRESPONSE']._serialized_start=3895
+  _globals['_UPDATEANALYSISOPTIONSRESPONSE']._serialized_end=4039
+  _globals['_FEEDBACKREQUEST']._serialized_start=4042
+  _globals['_FEEDBACKREQUEST']._serialized_end=4288
+  _globals['_FEEDBACKREQUEST_RATING']._serialized_start=4227
+  _globals['_FEEDBACKREQUEST_RATING']._serialized_end=4288
+  _globals['_FEEDBACKRESPONSE']._serialized_start=4290
+  _globals['_FEEDBACKRESPONSE']._serialized_end=4342
+  _globals['_OFFLINEASSETSREQUEST']._serialized_start=4344
+  _globals['_OFFLINEASSETSREQUEST']._serialized_end=4408
+  _globals['_OFFLINEASSET']._serialized_start=4411
+  _globals['_OFFLINEASSET']._serialized_end=4620
+  _globals['_OFFLINEASSET_KIND']._serialized_start=4550
+  _globals['_OFFLINEASSET_KIND']._serialized_end=4620
+  _globals['_TRANSCRIPTREQUEST']._serialized_start=4622
+  _globals['_TRANSCRIPTREQUEST']._serialized_end=4659
+  _globals['_TRANSCRIPTSEGMENT']._serialized_start=4661
+  _globals['_TRANSCRIPTSEGMENT']._serialized_end=4745
+  _globals['_TRANSCRIPTRESPONSE']._serialized_start=4748
+  _globals['_TRANSCRIPTRESPONSE']._serialized_end=4893
+  _globals['_DETECTIONSREQUEST']._serialized_start=4895
+  _globals['_DETECTIONSREQUEST']._serialized_end=4932
+  _globals['_BOUNDINGBOX']._serialized_start=4934
+  _globals['_BOUNDINGBOX']._serialized_end=5022
+  _globals['_DETECTION']._serialized_start=5025
+  _globals['_DETECTION']._serialized_end=5172
+  _globals['_DETECTIONSRESPONSE']._serialized_start=5174
+  _globals['_DETECTIONSRESPONSE']._serialized_end=5295
+  _globals['_TIMELINEREQUEST']._serialized_start=5297
+  _globals['_TIMELINEREQUEST']._serialized_end=5332
+  _globals['_TIMELINEEVENT']._serialized_start=5334
+  _globals['_TIMELINEEVENT']._serialized_end=5429
+  _globals['_TIMELINERESPONSE']._serialized_start=5431
+  _globals['_TIMELINERESPONSE']._serialized_end=5550
+  _globals['_SHARELINKREQUEST']._serialized_start=5552
+  _globals['_SHARELINKREQUEST']._serialized_end=5613
+  _globals['_SHARELINKRESPONSE']._serialized_start=5615
+  _globals['_SHARELINKRESPONSE']._serialized_end=5685
+  _globals['_BACKENDQUEUEREQUEST']._serialized_start=5687
+  _globals['_BACKENDQUEUEREQUEST']._serialized_end=5727
+  _globals['_QUEUEDTASK']._serialized_start=5730
+  _globals['_QUEUEDTASK']._serialized_end=5916
+  _globals['_BACKENDQUEUERESPONSE']._serialized_start=5918
+  _globals['_BACKENDQUEUERESPONSE']._serialized_end=6000
+  _globals['_CANCELPROCESSINGREQUEST']._serialized_start=6002
+  _globals['_CANCELPROCESSINGREQUEST']._serialized_end=6045
+  _globals['_CANCELPROCESSINGRESPONSE']._serialized_start=6047
+  _globals['_CANCELPROCESSINGRESPONSE']._serialized_end=6132
+  _globals['_DELETEVIDEOREQUEST']._serialized_start=6134
+  _globals['_DELETEVIDEOREQUEST']._serialized_end=6172
+  _globals['_DELETEVIDEORESPONSE']._serialized_start=6174
+  _globals['_DELETEVIDEORESPONSE']._serialized_end=6276
+  _globals['_UPDATEVIDEOMETADATAREQUEST']._serialized_start=6278
+  _globals['_UPDATEVIDEOMETADATAREQUEST']._serialized_end=6346
+  _globals['_UPDATEVIDEOMETADATARESPONSE']._serialized_start=6348
+  _globals['_UPDATEVIDEOMETADATARESPONSE']._serialized_end=6433
+  _globals['_CAPTION']._serialized_start=6435
+  _globals['_CAPTION']._serialized_end=6507
+  _globals['_ADDCAPTIONSREQUEST']._serialized_start=6509
+  _globals['_ADDCAPTIONSREQUEST']._serialized_end=6590
+  _globals['_ADDCAPTIONSRESPONSE']._serialized_start=6592
+  _globals['_ADDCAPTIONSRESPONSE']._serialized_end=6670
+  _globals['_SERVERINFO']._serialized_start=6673
+  _globals['_SERVERINFO']._serialized_end=6882
+  _globals['_SERVERINFO_FEATURESENTRY']._serialized_start=6835
+  _globals['_SERVERINFO_FEATURESENTRY']._serialized_end=6882
+  _globals['_VIDEOANALYZERSERVICE']._serialized_start=6885
+  _globals['_VIDEOANALYZERSERVICE']._serialized_end=9157
 # @@protoc_insertion_point(module_scope)
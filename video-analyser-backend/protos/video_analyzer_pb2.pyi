@@ -8,34 +8,150 @@ from typing import ClassVar as _ClassVar, Optional as _Optional, Union as _Union
 DESCRIPTOR: _descriptor.FileDescriptor
 
 class VideoChunk(_message.Message):
-    __slots__ = ("data", "filename", "chunk_index")
+    __slots__ = ("data", "filename", "chunk_index", "analysis_options", "idempotency_key", "offset", "language_hint", "encryption", "sha256", "file_sha256", "stream_index", "stream_count", "file_size", "source_sha256")
     DATA_FIELD_NUMBER: _ClassVar[int]
     FILENAME_FIELD_NUMBER: _ClassVar[int]
     CHUNK_INDEX_FIELD_NUMBER: _ClassVar[int]
+    ANALYSIS_OPTIONS_FIELD_NUMBER: _ClassVar[int]
+    IDEMPOTENCY_KEY_FIELD_NUMBER: _ClassVar[int]
+    OFFSET_FIELD_NUMBER: _ClassVar[int]
+    LANGUAGE_HINT_FIELD_NUMBER: _ClassVar[int]
+    ENCRYPTION_FIELD_NUMBER: _ClassVar[int]
+    SHA256_FIELD_NUMBER: _ClassVar[int]
+    FILE_SHA256_FIELD_NUMBER: _ClassVar[int]
+    STREAM_INDEX_FIELD_NUMBER: _ClassVar[int]
+    STREAM_COUNT_FIELD_NUMBER: _ClassVar[int]
+    FILE_SIZE_FIELD_NUMBER: _ClassVar[int]
+    SOURCE_SHA256_FIELD_NUMBER: _ClassVar[int]
     data: bytes
     filename: str
     chunk_index: int
-    def __init__(self, data: _Optional[bytes] = ..., filename: _Optional[str] = ..., chunk_index: _Optional[int] = ...) -> None: ...
+    analysis_options: AnalysisOptions
+    idempotency_key: str
+    offset: int
+    language_hint: str
+    encryption: ChunkEncryption
+    sha256: str
+    file_sha256: str
+    stream_index: int
+    stream_count: int
+    file_size: int
+    source_sha256: str
+    def __init__(self, data: _Optional[bytes] = ..., filename: _Optional[str] = ..., chunk_index: _Optional[int] = ..., analysis_options: _Optional[_Union[AnalysisOptions, _Mapping]] = ..., idempotency_key: _Optional[str] = ..., offset: _Optional[int] = ..., language_hint: _Optional[str] = ..., encryption: _Optional[_Union[ChunkEncryption, _Mapping]] = ..., sha256: _Optional[str] = ..., file_sha256: _Optional[str] = ..., stream_index: _Optional[int] = ..., stream_count: _Optional[int] = ..., file_size: _Optional[int] = ..., source_sha256: _Optional[str] = ...) -> None: ...
+
+class ChunkEncryption(_message.Message):
+    __slots__ = ("algorithm", "nonce", "key_id")
+    ALGORITHM_FIELD_NUMBER: _ClassVar[int]
+    NONCE_FIELD_NUMBER: _ClassVar[int]
+    KEY_ID_FIELD_NUMBER: _ClassVar[int]
+    algorithm: str
+    nonce: bytes
+    key_id: str
+    def __init__(self, algorithm: _Optional[str] = ..., nonce: _Optional[bytes] = ..., key_id: _Optional[str] = ...) -> None: ...
+
+class AnalysisOptions(_message.Message):
+    __slots__ = ("model_tier", "language", "frame_sample_rate", "enable_transcription")
+    MODEL_TIER_FIELD_NUMBER: _ClassVar[int]
+    LANGUAGE_FIELD_NUMBER: _ClassVar[int]
+    FRAME_SAMPLE_RATE_FIELD_NUMBER: _ClassVar[int]
+    ENABLE_TRANSCRIPTION_FIELD_NUMBER: _ClassVar[int]
+    model_tier: str
+    language: str
+    frame_sample_rate: float
+    enable_transcription: bool
+    def __init__(self, model_tier: _Optional[str] = ..., language: _Optional[str] = ..., frame_sample_rate: _Optional[float] = ..., enable_transcription: bool = ...) -> None: ...
 
 class UploadResponse(_message.Message):
-    __slots__ = ("file_id", "success", "message")
+    __slots__ = ("file_id", "success", "message", "sha256")
     FILE_ID_FIELD_NUMBER: _ClassVar[int]
     SUCCESS_FIELD_NUMBER: _ClassVar[int]
     MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    SHA256_FIELD_NUMBER: _ClassVar[int]
     file_id: str
     success: bool
     message: str
-    def __init__(self, file_id: _Optional[str] = ..., success: bool = ..., message: _Optional[str] = ...) -> None: ...
+    sha256: str
+    def __init__(self, file_id: _Optional[str] = ..., success: bool = ..., message: _Optional[str] = ..., sha256: _Optional[str] = ...) -> None: ...
+
+class UploadStatusRequest(_message.Message):
+    __slots__ = ("idempotency_key",)
+    IDEMPOTENCY_KEY_FIELD_NUMBER: _ClassVar[int]
+    idempotency_key: str
+    def __init__(self, idempotency_key: _Optional[str] = ...) -> None: ...
+
+class UploadStatusResponse(_message.Message):
+    __slots__ = ("completed", "response", "bytes_received")
+    COMPLETED_FIELD_NUMBER: _ClassVar[int]
+    RESPONSE_FIELD_NUMBER: _ClassVar[int]
+    BYTES_RECEIVED_FIELD_NUMBER: _ClassVar[int]
+    completed: bool
+    response: UploadResponse
+    bytes_received: int
+    def __init__(self, completed: bool = ..., response: _Optional[_Union[UploadResponse, _Mapping]] = ..., bytes_received: _Optional[int] = ...) -> None: ...
+
+class UploadOffsetRequest(_message.Message):
+    __slots__ = ("idempotency_key", "sha256", "size_bytes", "chunk_size")
+    IDEMPOTENCY_KEY_FIELD_NUMBER: _ClassVar[int]
+    SHA256_FIELD_NUMBER: _ClassVar[int]
+    SIZE_BYTES_FIELD_NUMBER: _ClassVar[int]
+    CHUNK_SIZE_FIELD_NUMBER: _ClassVar[int]
+    idempotency_key: str
+    sha256: str
+    size_bytes: int
+    chunk_size: int
+    def __init__(self, idempotency_key: _Optional[str] = ..., sha256: _Optional[str] = ..., size_bytes: _Optional[int] = ..., chunk_size: _Optional[int] = ...) -> None: ...
+
+class UploadOffsetResponse(_message.Message):
+    __slots__ = ("next_chunk_index", "completed", "response")
+    NEXT_CHUNK_INDEX_FIELD_NUMBER: _ClassVar[int]
+    COMPLETED_FIELD_NUMBER: _ClassVar[int]
+    RESPONSE_FIELD_NUMBER: _ClassVar[int]
+    next_chunk_index: int
+    completed: bool
+    response: UploadResponse
+    def __init__(self, next_chunk_index: _Optional[int] = ..., completed: bool = ..., response: _Optional[_Union[UploadResponse, _Mapping]] = ...) -> None: ...
+
+class CheckFileExistsRequest(_message.Message):
+    __slots__ = ("sha256", "size_bytes")
+    SHA256_FIELD_NUMBER: _ClassVar[int]
+    SIZE_BYTES_FIELD_NUMBER: _ClassVar[int]
+    sha256: str
+    size_bytes: int
+    def __init__(self, sha256: _Optional[str] = ..., size_bytes: _Optional[int] = ...) -> None: ...
+
+class CheckFileExistsResponse(_message.Message):
+    __slots__ = ("exists", "file_id", "display_name")
+    EXISTS_FIELD_NUMBER: _ClassVar[int]
+    FILE_ID_FIELD_NUMBER: _ClassVar[int]
+    DISPLAY_NAME_FIELD_NUMBER: _ClassVar[int]
+    exists: bool
+    file_id: str
+    display_name: str
+    def __init__(self, exists: bool = ..., file_id: _Optional[str] = ..., display_name: _Optional[str] = ...) -> None: ...
 
 class RegisterVideoRequest(_message.Message):
-    __slots__ = ("file_path", "display_name", "reference_only")
+    __slots__ = ("file_path", "display_name", "reference_only", "size_bytes", "sha256", "modified_at", "container", "file_id", "analysis_options", "language_hint")
     FILE_PATH_FIELD_NUMBER: _ClassVar[int]
     DISPLAY_NAME_FIELD_NUMBER: _ClassVar[int]
     REFERENCE_ONLY_FIELD_NUMBER: _ClassVar[int]
+    SIZE_BYTES_FIELD_NUMBER: _ClassVar[int]
+    SHA256_FIELD_NUMBER: _ClassVar[int]
+    MODIFIED_AT_FIELD_NUMBER: _ClassVar[int]
+    CONTAINER_FIELD_NUMBER: _ClassVar[int]
+    FILE_ID_FIELD_NUMBER: _ClassVar[int]
+    ANALYSIS_OPTIONS_FIELD_NUMBER: _ClassVar[int]
+    LANGUAGE_HINT_FIELD_NUMBER: _ClassVar[int]
     file_path: str
     display_name: str
     reference_only: bool
-    def __init__(self, file_path: _Optional[str] = ..., display_name: _Optional[str] = ..., reference_only: bool = ...) -> None: ...
+    size_bytes: int
+    sha256: str
+    modified_at: float
+    container: str
+    file_id: str
+    analysis_options: AnalysisOptions
+    language_hint: str
+    def __init__(self, file_path: _Optional[str] = ..., display_name: _Optional[str] = ..., reference_only: bool = ..., size_bytes: _Optional[int] = ..., sha256: _Optional[str] = ..., modified_at: _Optional[float] = ..., container: _Optional[str] = ..., file_id: _Optional[str] = ..., analysis_options: _Optional[_Union[AnalysisOptions, _Mapping]] = ..., language_hint: _Optional[str] = ...) -> None: ...
 
 class RegisterVideoResponse(_message.Message):
     __slots__ = ("file_id", "stored_path", "display_name", "copied", "size_bytes", "registered_at", "message")
@@ -56,17 +172,71 @@ class RegisterVideoResponse(_message.Message):
     def __init__(self, file_id: _Optional[str] = ..., stored_path: _Optional[str] = ..., display_name: _Optional[str] = ..., copied: bool = ..., size_bytes: _Optional[int] = ..., registered_at: _Optional[float] = ..., message: _Optional[str] = ...) -> None: ...
 
 class ChatRequest(_message.Message):
-    __slots__ = ("message", "file_id", "context")
+    __slots__ = ("message", "file_id", "context", "query_type", "attachments", "request_id", "params", "range")
+    class QueryType(int, metaclass=_enum_type_wrapper.EnumTypeWrapper):
+        __slots__ = ()
+        FREE_FORM: _ClassVar[ChatRequest.QueryType]
+        SUMMARY: _ClassVar[ChatRequest.QueryType]
+        SEARCH: _ClassVar[ChatRequest.QueryType]
+        TIMELINE: _ClassVar[ChatRequest.QueryType]
+        OBJECT_DETECTION: _ClassVar[ChatRequest.QueryType]
+    FREE_FORM: ChatRequest.QueryType
+    SUMMARY: ChatRequest.QueryType
+    SEARCH: ChatRequest.QueryType
+    TIMELINE: ChatRequest.QueryType
+    OBJECT_DETECTION: ChatRequest.QueryType
     MESSAGE_FIELD_NUMBER: _ClassVar[int]
     FILE_ID_FIELD_NUMBER: _ClassVar[int]
     CONTEXT_FIELD_NUMBER: _ClassVar[int]
+    QUERY_TYPE_FIELD_NUMBER: _ClassVar[int]
+    ATTACHMENTS_FIELD_NUMBER: _ClassVar[int]
+    REQUEST_ID_FIELD_NUMBER: _ClassVar[int]
+    PARAMS_FIELD_NUMBER: _ClassVar[int]
+    RANGE_FIELD_NUMBER: _ClassVar[int]
     message: str
     file_id: str
     context: str
-    def __init__(self, message: _Optional[str] = ..., file_id: _Optional[str] = ..., context: _Optional[str] = ...) -> None: ...
+    query_type: ChatRequest.QueryType
+    attachments: _containers.RepeatedCompositeFieldContainer[Attachment]
+    request_id: str
+    params: SessionParams
+    range: TimeRange
+    def __init__(self, message: _Optional[str] = ..., file_id: _Optional[str] = ..., context: _Optional[str] = ..., query_type: _Optional[_Union[ChatRequest.QueryType, str]] = ..., attachments: _Optional[_Iterable[_Union[Attachment, _Mapping]]] = ..., request_id: _Optional[str] = ..., params: _Optional[_Union[SessionParams, _Mapping]] = ..., range: _Optional[_Union[TimeRange, _Mapping]] = ...) -> None: ...
+
+class TimeRange(_message.Message):
+    __slots__ = ("start_ms", "end_ms")
+    START_MS_FIELD_NUMBER: _ClassVar[int]
+    END_MS_FIELD_NUMBER: _ClassVar[int]
+    start_ms: int
+    end_ms: int
+    def __init__(self, start_ms: _Optional[int] = ..., end_ms: _Optional[int] = ...) -> None: ...
+
+class SessionParams(_message.Message):
+    __slots__ = ("temperature", "max_tokens", "model")
+    TEMPERATURE_FIELD_NUMBER: _ClassVar[int]
+    MAX_TOKENS_FIELD_NUMBER: _ClassVar[int]
+    MODEL_FIELD_NUMBER: _ClassVar[int]
+    temperature: float
+    max_tokens: int
+    model: str
+    def __init__(self, temperature: _Optional[float] = ..., max_tokens: _Optional[int] = ..., model: _Optional[str] = ...) -> None: ...
+
+class Attachment(_message.Message):
+    __slots__ = ("filename", "mime_type", "data", "size_bytes", "sha256")
+    FILENAME_FIELD_NUMBER: _ClassVar[int]
+    MIME_TYPE_FIELD_NUMBER: _ClassVar[int]
+    DATA_FIELD_NUMBER: _ClassVar[int]
+    SIZE_BYTES_FIELD_NUMBER: _ClassVar[int]
+    SHA256_FIELD_NUMBER: _ClassVar[int]
+    filename: str
+    mime_type: str
+    data: bytes
+    size_bytes: int
+    sha256: str
+    def __init__(self, filename: _Optional[str] = ..., mime_type: _Optional[str] = ..., data: _Optional[bytes] = ..., size_bytes: _Optional[int] = ..., sha256: _Optional[str] = ...) -> None: ...
 
 class ChatResponse(_message.Message):
-    __slots__ = ("type", "content", "agent_name", "result_json")
+    __slots__ = ("type", "content", "agent_name", "result_json", "sequence")
     class ResponseType(int, metaclass=_enum_type_wrapper.EnumTypeWrapper):
         __slots__ = ()
         MESSAGE: _ClassVar[ChatResponse.ResponseType]
@@ -81,11 +251,13 @@ class ChatResponse(_message.Message):
     CONTENT_FIELD_NUMBER: _ClassVar[int]
     AGENT_NAME_FIELD_NUMBER: _ClassVar[int]
     RESULT_JSON_FIELD_NUMBER: _ClassVar[int]
+    SEQUENCE_FIELD_NUMBER: _ClassVar[int]
     type: ChatResponse.ResponseType
     content: str
     agent_name: str
     result_json: str
-    def __init__(self, type: _Optional[_Union[ChatResponse.ResponseType, str]] = ..., content: _Optional[str] = ..., agent_name: _Optional[str] = ..., result_json: _Optional[str] = ...) -> None: ...
+    sequence: int
+    def __init__(self, type: _Optional[_Union[ChatResponse.ResponseType, str]] = ..., content: _Optional[str] = ..., agent_name: _Optional[str] = ..., result_json: _Optional[str] = ..., sequence: _Optional[int] = ...) -> None: ...
 
 class Empty(_message.Message):
     __slots__ = ()
@@ -156,3 +328,432 @@ class ChatMessage(_message.Message):
     content: str
     timestamp: str
     def __init__(self, role: _Optional[str] = ..., content: _Optional[str] = ..., timestamp: _Optional[str] = ...) -> None: ...
+
+class ResumeRequest(_message.Message):
+    __slots__ = ("video_id",)
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    def __init__(self, video_id: _Optional[str] = ...) -> None: ...
+
+class ResumeResponse(_message.Message):
+    __slots__ = ("success", "message", "video_id", "video_name", "video_path")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    VIDEO_NAME_FIELD_NUMBER: _ClassVar[int]
+    VIDEO_PATH_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    video_id: str
+    video_name: str
+    video_path: str
+    def __init__(self, success: bool = ..., message: _Optional[str] = ..., video_id: _Optional[str] = ..., video_name: _Optional[str] = ..., video_path: _Optional[str] = ...) -> None: ...
+
+class VideoStorageUsage(_message.Message):
+    __slots__ = ("video_id", "video_name", "video_bytes", "derived_bytes", "total_bytes", "copied", "last_accessed_at")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    VIDEO_NAME_FIELD_NUMBER: _ClassVar[int]
+    VIDEO_BYTES_FIELD_NUMBER: _ClassVar[int]
+    DERIVED_BYTES_FIELD_NUMBER: _ClassVar[int]
+    TOTAL_BYTES_FIELD_NUMBER: _ClassVar[int]
+    COPIED_FIELD_NUMBER: _ClassVar[int]
+    LAST_ACCESSED_AT_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    video_name: str
+    video_bytes: int
+    derived_bytes: int
+    total_bytes: int
+    copied: bool
+    last_accessed_at: float
+    def __init__(self, video_id: _Optional[str] = ..., video_name: _Optional[str] = ..., video_bytes: _Optional[int] = ..., derived_bytes: _Optional[int] = ..., total_bytes: _Optional[int] = ..., copied: bool = ..., last_accessed_at: _Optional[float] = ...) -> None: ...
+
+class StorageUsageResponse(_message.Message):
+    __slots__ = ("videos", "total_bytes", "available_bytes", "quota_bytes")
+    VIDEOS_FIELD_NUMBER: _ClassVar[int]
+    TOTAL_BYTES_FIELD_NUMBER: _ClassVar[int]
+    AVAILABLE_BYTES_FIELD_NUMBER: _ClassVar[int]
+    QUOTA_BYTES_FIELD_NUMBER: _ClassVar[int]
+    videos: _containers.RepeatedCompositeFieldContainer[VideoStorageUsage]
+    total_bytes: int
+    available_bytes: int
+    quota_bytes: int
+    def __init__(self, videos: _Optional[_Iterable[_Union[VideoStorageUsage, _Mapping]]] = ..., total_bytes: _Optional[int] = ..., available_bytes: _Optional[int] = ..., quota_bytes: _Optional[int] = ...) -> None: ...
+
+class CleanupRequest(_message.Message):
+    __slots__ = ("registered_before", "accessed_before", "dry_run")
+    REGISTERED_BEFORE_FIELD_NUMBER: _ClassVar[int]
+    ACCESSED_BEFORE_FIELD_NUMBER: _ClassVar[int]
+    DRY_RUN_FIELD_NUMBER: _ClassVar[int]
+    registered_before: float
+    accessed_before: float
+    dry_run: bool
+    def __init__(self, registered_before: _Optional[float] = ..., accessed_before: _Optional[float] = ..., dry_run: bool = ...) -> None: ...
+
+class CleanupResponse(_message.Message):
+    __slots__ = ("videos", "freed_bytes", "dry_run", "message")
+    VIDEOS_FIELD_NUMBER: _ClassVar[int]
+    FREED_BYTES_FIELD_NUMBER: _ClassVar[int]
+    DRY_RUN_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    videos: _containers.RepeatedCompositeFieldContainer[VideoStorageUsage]
+    freed_bytes: int
+    dry_run: bool
+    message: str
+    def __init__(self, videos: _Optional[_Iterable[_Union[VideoStorageUsage, _Mapping]]] = ..., freed_bytes: _Optional[int] = ..., dry_run: bool = ..., message: _Optional[str] = ...) -> None: ...
+
+class UpdateAnalysisOptionsRequest(_message.Message):
+    __slots__ = ("video_id", "options")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    OPTIONS_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    options: AnalysisOptions
+    def __init__(self, video_id: _Optional[str] = ..., options: _Optional[_Union[AnalysisOptions, _Mapping]] = ...) -> None: ...
+
+class UpdateAnalysisOptionsResponse(_message.Message):
+    __slots__ = ("success", "message", "options", "reanalysis_required")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    OPTIONS_FIELD_NUMBER: _ClassVar[int]
+    REANALYSIS_REQUIRED_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    options: AnalysisOptions
+    reanalysis_required: bool
+    def __init__(self, success: bool = ..., message: _Optional[str] = ..., options: _Optional[_Union[AnalysisOptions, _Mapping]] = ..., reanalysis_required: bool = ...) -> None: ...
+
+class FeedbackRequest(_message.Message):
+    __slots__ = ("video_id", "message_index", "rating", "comment", "question", "answer", "rated_at")
+    class Rating(int, metaclass=_enum_type_wrapper.EnumTypeWrapper):
+        __slots__ = ()
+        RATING_UNSPECIFIED: _ClassVar[FeedbackRequest.Rating]
+        UP: _ClassVar[FeedbackRequest.Rating]
+        DOWN: _ClassVar[FeedbackRequest.Rating]
+        WRONG: _ClassVar[FeedbackRequest.Rating]
+    RATING_UNSPECIFIED: FeedbackRequest.Rating
+    UP: FeedbackRequest.Rating
+    DOWN: FeedbackRequest.Rating
+    WRONG: FeedbackRequest.Rating
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_INDEX_FIELD_NUMBER: _ClassVar[int]
+    RATING_FIELD_NUMBER: _ClassVar[int]
+    COMMENT_FIELD_NUMBER: _ClassVar[int]
+    QUESTION_FIELD_NUMBER: _ClassVar[int]
+    ANSWER_FIELD_NUMBER: _ClassVar[int]
+    RATED_AT_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    message_index: int
+    rating: FeedbackRequest.Rating
+    comment: str
+    question: str
+    answer: str
+    rated_at: float
+    def __init__(self, video_id: _Optional[str] = ..., message_index: _Optional[int] = ..., rating: _Optional[_Union[FeedbackRequest.Rating, str]] = ..., comment: _Optional[str] = ..., question: _Optional[str] = ..., answer: _Optional[str] = ..., rated_at: _Optional[float] = ...) -> None: ...
+
+class FeedbackResponse(_message.Message):
+    __slots__ = ("success", "message")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    def __init__(self, success: bool = ..., message: _Optional[str] = ...) -> None: ...
+
+class OfflineAssetsRequest(_message.Message):
+    __slots__ = ("video_id", "max_key_frames")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    MAX_KEY_FRAMES_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    max_key_frames: int
+    def __init__(self, video_id: _Optional[str] = ..., max_key_frames: _Optional[int] = ...) -> None: ...
+
+class OfflineAsset(_message.Message):
+    __slots__ = ("kind", "name", "mime_type", "data", "timestamp_seconds")
+    class Kind(int, metaclass=_enum_type_wrapper.EnumTypeWrapper):
+        __slots__ = ()
+        KIND_UNSPECIFIED: _ClassVar[OfflineAsset.Kind]
+        THUMBNAIL: _ClassVar[OfflineAsset.Kind]
+        KEY_FRAME: _ClassVar[OfflineAsset.Kind]
+        REPORT: _ClassVar[OfflineAsset.Kind]
+    KIND_UNSPECIFIED: OfflineAsset.Kind
+    THUMBNAIL: OfflineAsset.Kind
+    KEY_FRAME: OfflineAsset.Kind
+    REPORT: OfflineAsset.Kind
+    KIND_FIELD_NUMBER: _ClassVar[int]
+    NAME_FIELD_NUMBER: _ClassVar[int]
+    MIME_TYPE_FIELD_NUMBER: _ClassVar[int]
+    DATA_FIELD_NUMBER: _ClassVar[int]
+    TIMESTAMP_SECONDS_FIELD_NUMBER: _ClassVar[int]
+    kind: OfflineAsset.Kind
+    name: str
+    mime_type: str
+    data: bytes
+    timestamp_seconds: float
+    def __init__(self, kind: _Optional[_Union[OfflineAsset.Kind, str]] = ..., name: _Optional[str] = ..., mime_type: _Optional[str] = ..., data: _Optional[bytes] = ..., timestamp_seconds: _Optional[float] = ...) -> None: ...
+
+class TranscriptRequest(_message.Message):
+    __slots__ = ("video_id",)
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    def __init__(self, video_id: _Optional[str] = ...) -> None: ...
+
+class TranscriptSegment(_message.Message):
+    __slots__ = ("start_ms", "end_ms", "text", "speaker")
+    START_MS_FIELD_NUMBER: _ClassVar[int]
+    END_MS_FIELD_NUMBER: _ClassVar[int]
+    TEXT_FIELD_NUMBER: _ClassVar[int]
+    SPEAKER_FIELD_NUMBER: _ClassVar[int]
+    start_ms: int
+    end_ms: int
+    text: str
+    speaker: str
+    def __init__(self, start_ms: _Optional[int] = ..., end_ms: _Optional[int] = ..., text: _Optional[str] = ..., speaker: _Optional[str] = ...) -> None: ...
+
+class TranscriptResponse(_message.Message):
+    __slots__ = ("video_id", "available", "language", "segments", "message")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    AVAILABLE_FIELD_NUMBER: _ClassVar[int]
+    LANGUAGE_FIELD_NUMBER: _ClassVar[int]
+    SEGMENTS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    available: bool
+    language: str
+    segments: _containers.RepeatedCompositeFieldContainer[TranscriptSegment]
+    message: str
+    def __init__(self, video_id: _Optional[str] = ..., available: bool = ..., language: _Optional[str] = ..., segments: _Optional[_Iterable[_Union[TranscriptSegment, _Mapping]]] = ..., message: _Optional[str] = ...) -> None: ...
+
+class DetectionsRequest(_message.Message):
+    __slots__ = ("video_id",)
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    def __init__(self, video_id: _Optional[str] = ...) -> None: ...
+
+class BoundingBox(_message.Message):
+    __slots__ = ("timestamp_ms", "x", "y", "width", "height")
+    TIMESTAMP_MS_FIELD_NUMBER: _ClassVar[int]
+    X_FIELD_NUMBER: _ClassVar[int]
+    Y_FIELD_NUMBER: _ClassVar[int]
+    WIDTH_FIELD_NUMBER: _ClassVar[int]
+    HEIGHT_FIELD_NUMBER: _ClassVar[int]
+    timestamp_ms: int
+    x: float
+    y: float
+    width: float
+    height: float
+    def __init__(self, timestamp_ms: _Optional[int] = ..., x: _Optional[float] = ..., y: _Optional[float] = ..., width: _Optional[float] = ..., height: _Optional[float] = ...) -> None: ...
+
+class Detection(_message.Message):
+    __slots__ = ("label", "kind", "confidence", "ranges", "boxes")
+    LABEL_FIELD_NUMBER: _ClassVar[int]
+    KIND_FIELD_NUMBER: _ClassVar[int]
+    CONFIDENCE_FIELD_NUMBER: _ClassVar[int]
+    RANGES_FIELD_NUMBER: _ClassVar[int]
+    BOXES_FIELD_NUMBER: _ClassVar[int]
+    label: str
+    kind: str
+    confidence: float
+    ranges: _containers.RepeatedCompositeFieldContainer[TimeRange]
+    boxes: _containers.RepeatedCompositeFieldContainer[BoundingBox]
+    def __init__(self, label: _Optional[str] = ..., kind: _Optional[str] = ..., confidence: _Optional[float] = ..., ranges: _Optional[_Iterable[_Union[TimeRange, _Mapping]]] = ..., boxes: _Optional[_Iterable[_Union[BoundingBox, _Mapping]]] = ...) -> None: ...
+
+class DetectionsResponse(_message.Message):
+    __slots__ = ("video_id", "available", "detections", "message")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    AVAILABLE_FIELD_NUMBER: _ClassVar[int]
+    DETECTIONS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    available: bool
+    detections: _containers.RepeatedCompositeFieldContainer[Detection]
+    message: str
+    def __init__(self, video_id: _Optional[str] = ..., available: bool = ..., detections: _Optional[_Iterable[_Union[Detection, _Mapping]]] = ..., message: _Optional[str] = ...) -> None: ...
+
+class TimelineRequest(_message.Message):
+    __slots__ = ("video_id",)
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    def __init__(self, video_id: _Optional[str] = ...) -> None: ...
+
+class TimelineEvent(_message.Message):
+    __slots__ = ("start_ms", "end_ms", "kind", "title", "summary")
+    START_MS_FIELD_NUMBER: _ClassVar[int]
+    END_MS_FIELD_NUMBER: _ClassVar[int]
+    KIND_FIELD_NUMBER: _ClassVar[int]
+    TITLE_FIELD_NUMBER: _ClassVar[int]
+    SUMMARY_FIELD_NUMBER: _ClassVar[int]
+    start_ms: int
+    end_ms: int
+    kind: str
+    title: str
+    summary: str
+    def __init__(self, start_ms: _Optional[int] = ..., end_ms: _Optional[int] = ..., kind: _Optional[str] = ..., title: _Optional[str] = ..., summary: _Optional[str] = ...) -> None: ...
+
+class TimelineResponse(_message.Message):
+    __slots__ = ("video_id", "available", "events", "message")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    AVAILABLE_FIELD_NUMBER: _ClassVar[int]
+    EVENTS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    available: bool
+    events: _containers.RepeatedCompositeFieldContainer[TimelineEvent]
+    message: str
+    def __init__(self, video_id: _Optional[str] = ..., available: bool = ..., events: _Optional[_Iterable[_Union[TimelineEvent, _Mapping]]] = ..., message: _Optional[str] = ...) -> None: ...
+
+class ShareLinkRequest(_message.Message):
+    __slots__ = ("video_id", "expires_in_secs")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    EXPIRES_IN_SECS_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    expires_in_secs: int
+    def __init__(self, video_id: _Optional[str] = ..., expires_in_secs: _Optional[int] = ...) -> None: ...
+
+class ShareLinkResponse(_message.Message):
+    __slots__ = ("video_id", "url", "expires_at")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    URL_FIELD_NUMBER: _ClassVar[int]
+    EXPIRES_AT_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    url: str
+    expires_at: int
+    def __init__(self, video_id: _Optional[str] = ..., url: _Optional[str] = ..., expires_at: _Optional[int] = ...) -> None: ...
+
+class BackendQueueRequest(_message.Message):
+    __slots__ = ("only_mine",)
+    ONLY_MINE_FIELD_NUMBER: _ClassVar[int]
+    only_mine: bool
+    def __init__(self, only_mine: bool = ...) -> None: ...
+
+class QueuedTask(_message.Message):
+    __slots__ = ("task_id", "video_id", "owner", "is_mine", "kind", "state", "position", "submitted_at", "started_at", "progress")
+    TASK_ID_FIELD_NUMBER: _ClassVar[int]
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    OWNER_FIELD_NUMBER: _ClassVar[int]
+    IS_MINE_FIELD_NUMBER: _ClassVar[int]
+    KIND_FIELD_NUMBER: _ClassVar[int]
+    STATE_FIELD_NUMBER: _ClassVar[int]
+    POSITION_FIELD_NUMBER: _ClassVar[int]
+    SUBMITTED_AT_FIELD_NUMBER: _ClassVar[int]
+    STARTED_AT_FIELD_NUMBER: _ClassVar[int]
+    PROGRESS_FIELD_NUMBER: _ClassVar[int]
+    task_id: str
+    video_id: str
+    owner: str
+    is_mine: bool
+    kind: str
+    state: str
+    position: int
+    submitted_at: float
+    started_at: float
+    progress: float
+    def __init__(self, task_id: _Optional[str] = ..., video_id: _Optional[str] = ..., owner: _Optional[str] = ..., is_mine: bool = ..., kind: _Optional[str] = ..., state: _Optional[str] = ..., position: _Optional[int] = ..., submitted_at: _Optional[float] = ..., started_at: _Optional[float] = ..., progress: _Optional[float] = ...) -> None: ...
+
+class BackendQueueResponse(_message.Message):
+    __slots__ = ("tasks", "workers")
+    TASKS_FIELD_NUMBER: _ClassVar[int]
+    WORKERS_FIELD_NUMBER: _ClassVar[int]
+    tasks: _containers.RepeatedCompositeFieldContainer[QueuedTask]
+    workers: int
+    def __init__(self, tasks: _Optional[_Iterable[_Union[QueuedTask, _Mapping]]] = ..., workers: _Optional[int] = ...) -> None: ...
+
+class CancelProcessingRequest(_message.Message):
+    __slots__ = ("video_id",)
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    def __init__(self, video_id: _Optional[str] = ...) -> None: ...
+
+class CancelProcessingResponse(_message.Message):
+    __slots__ = ("success", "message", "cancelled_tasks")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    CANCELLED_TASKS_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    cancelled_tasks: int
+    def __init__(self, success: bool = ..., message: _Optional[str] = ..., cancelled_tasks: _Optional[int] = ...) -> None: ...
+
+class DeleteVideoRequest(_message.Message):
+    __slots__ = ("video_id",)
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    def __init__(self, video_id: _Optional[str] = ...) -> None: ...
+
+class DeleteVideoResponse(_message.Message):
+    __slots__ = ("success", "message", "deleted_messages", "freed_bytes")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    DELETED_MESSAGES_FIELD_NUMBER: _ClassVar[int]
+    FREED_BYTES_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    deleted_messages: int
+    freed_bytes: int
+    def __init__(self, success: bool = ..., message: _Optional[str] = ..., deleted_messages: _Optional[int] = ..., freed_bytes: _Optional[int] = ...) -> None: ...
+
+class UpdateVideoMetadataRequest(_message.Message):
+    __slots__ = ("video_id", "display_name")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    DISPLAY_NAME_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    display_name: str
+    def __init__(self, video_id: _Optional[str] = ..., display_name: _Optional[str] = ...) -> None: ...
+
+class UpdateVideoMetadataResponse(_message.Message):
+    __slots__ = ("success", "message", "display_name")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    DISPLAY_NAME_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    display_name: str
+    def __init__(self, success: bool = ..., message: _Optional[str] = ..., display_name: _Optional[str] = ...) -> None: ...
+
+class Caption(_message.Message):
+    __slots__ = ("language", "format", "text", "title")
+    LANGUAGE_FIELD_NUMBER: _ClassVar[int]
+    FORMAT_FIELD_NUMBER: _ClassVar[int]
+    TEXT_FIELD_NUMBER: _ClassVar[int]
+    TITLE_FIELD_NUMBER: _ClassVar[int]
+    language: str
+    format: str
+    text: str
+    title: str
+    def __init__(self, language: _Optional[str] = ..., format: _Optional[str] = ..., text: _Optional[str] = ..., title: _Optional[str] = ...) -> None: ...
+
+class AddCaptionsRequest(_message.Message):
+    __slots__ = ("video_id", "captions")
+    VIDEO_ID_FIELD_NUMBER: _ClassVar[int]
+    CAPTIONS_FIELD_NUMBER: _ClassVar[int]
+    video_id: str
+    captions: _containers.RepeatedCompositeFieldContainer[Caption]
+    def __init__(self, video_id: _Optional[str] = ..., captions: _Optional[_Iterable[_Union[Caption, _Mapping]]] = ...) -> None: ...
+
+class AddCaptionsResponse(_message.Message):
+    __slots__ = ("success", "message", "captions_kept")
+    SUCCESS_FIELD_NUMBER: _ClassVar[int]
+    MESSAGE_FIELD_NUMBER: _ClassVar[int]
+    CAPTIONS_KEPT_FIELD_NUMBER: _ClassVar[int]
+    success: bool
+    message: str
+    captions_kept: int
+    def __init__(self, success: bool = ..., message: _Optional[str] = ..., captions_kept: _Optional[int] = ...) -> None: ...
+
+class ServerInfo(_message.Message):
+    __slots__ = ("version", "proto_version", "min_client_version", "started_at", "features")
+    class FeaturesEntry(_message.Message):
+        __slots__ = ("key", "value")
+        KEY_FIELD_NUMBER: _ClassVar[int]
+        VALUE_FIELD_NUMBER: _ClassVar[int]
+        key: str
+        value: bool
+        def __init__(self, key: _Optional[str] = ..., value: bool = ...) -> None: ...
+    VERSION_FIELD_NUMBER: _ClassVar[int]
+    PROTO_VERSION_FIELD_NUMBER: _ClassVar[int]
+    MIN_CLIENT_VERSION_FIELD_NUMBER: _ClassVar[int]
+    STARTED_AT_FIELD_NUMBER: _ClassVar[int]
+    FEATURES_FIELD_NUMBER: _ClassVar[int]
+    version: str
+    proto_version: int
+    min_client_version: str
+    started_at: float
+    features: _containers.ScalarMap[str, bool]
+    def __init__(self, version: _Optional[str] = ..., proto_version: _Optional[int] = ..., min_client_version: _Optional[str] = ..., started_at: _Optional[float] = ..., features: _Optional[_Mapping[str, bool]] = ...) -> None: ...
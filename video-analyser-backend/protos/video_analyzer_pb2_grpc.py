@@ -39,6 +39,21 @@ class VideoAnalyzerServiceStub(object):
                 request_serializer=protos_dot_video__analyzer__pb2.VideoChunk.SerializeToString,
                 response_deserializer=protos_dot_video__analyzer__pb2.UploadResponse.FromString,
                 _registered_method=True)
+        self.GetUploadStatus = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetUploadStatus',
+                request_serializer=protos_dot_video__analyzer__pb2.UploadStatusRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.UploadStatusResponse.FromString,
+                _registered_method=True)
+        self.NegotiateUploadOffset = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/NegotiateUploadOffset',
+                request_serializer=protos_dot_video__analyzer__pb2.UploadOffsetRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.UploadOffsetResponse.FromString,
+                _registered_method=True)
+        self.CheckFileExists = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/CheckFileExists',
+                request_serializer=protos_dot_video__analyzer__pb2.CheckFileExistsRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.CheckFileExistsResponse.FromString,
+                _registered_method=True)
         self.RegisterLocalVideo = channel.unary_unary(
                 '/video_analyzer.VideoAnalyzerService/RegisterLocalVideo',
                 request_serializer=protos_dot_video__analyzer__pb2.RegisterVideoRequest.SerializeToString,
@@ -64,6 +79,86 @@ class VideoAnalyzerServiceStub(object):
                 request_serializer=protos_dot_video__analyzer__pb2.ClearHistoryRequest.SerializeToString,
                 response_deserializer=protos_dot_video__analyzer__pb2.ClearHistoryResponse.FromString,
                 _registered_method=True)
+        self.ResumeSession = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/ResumeSession',
+                request_serializer=protos_dot_video__analyzer__pb2.ResumeRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.ResumeResponse.FromString,
+                _registered_method=True)
+        self.GetStorageUsage = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetStorageUsage',
+                request_serializer=protos_dot_video__analyzer__pb2.Empty.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.StorageUsageResponse.FromString,
+                _registered_method=True)
+        self.CleanupVideos = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/CleanupVideos',
+                request_serializer=protos_dot_video__analyzer__pb2.CleanupRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.CleanupResponse.FromString,
+                _registered_method=True)
+        self.UpdateAnalysisOptions = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/UpdateAnalysisOptions',
+                request_serializer=protos_dot_video__analyzer__pb2.UpdateAnalysisOptionsRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.UpdateAnalysisOptionsResponse.FromString,
+                _registered_method=True)
+        self.SubmitFeedback = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/SubmitFeedback',
+                request_serializer=protos_dot_video__analyzer__pb2.FeedbackRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.FeedbackResponse.FromString,
+                _registered_method=True)
+        self.GetOfflineAssets = channel.unary_stream(
+                '/video_analyzer.VideoAnalyzerService/GetOfflineAssets',
+                request_serializer=protos_dot_video__analyzer__pb2.OfflineAssetsRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.OfflineAsset.FromString,
+                _registered_method=True)
+        self.GetTranscript = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetTranscript',
+                request_serializer=protos_dot_video__analyzer__pb2.TranscriptRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.TranscriptResponse.FromString,
+                _registered_method=True)
+        self.GetDetections = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetDetections',
+                request_serializer=protos_dot_video__analyzer__pb2.DetectionsRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.DetectionsResponse.FromString,
+                _registered_method=True)
+        self.GetTimeline = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetTimeline',
+                request_serializer=protos_dot_video__analyzer__pb2.TimelineRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.TimelineResponse.FromString,
+                _registered_method=True)
+        self.CreateShareLink = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/CreateShareLink',
+                request_serializer=protos_dot_video__analyzer__pb2.ShareLinkRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.ShareLinkResponse.FromString,
+                _registered_method=True)
+        self.GetBackendQueue = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetBackendQueue',
+                request_serializer=protos_dot_video__analyzer__pb2.BackendQueueRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.BackendQueueResponse.FromString,
+                _registered_method=True)
+        self.CancelProcessing = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/CancelProcessing',
+                request_serializer=protos_dot_video__analyzer__pb2.CancelProcessingRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.CancelProcessingResponse.FromString,
+                _registered_method=True)
+        self.DeleteVideo = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/DeleteVideo',
+                request_serializer=protos_dot_video__analyzer__pb2.DeleteVideoRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.DeleteVideoResponse.FromString,
+                _registered_method=True)
+        self.UpdateVideoMetadata = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/UpdateVideoMetadata',
+                request_serializer=protos_dot_video__analyzer__pb2.UpdateVideoMetadataRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.UpdateVideoMetadataResponse.FromString,
+                _registered_method=True)
+        self.AddCaptions = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/AddCaptions',
+                request_serializer=protos_dot_video__analyzer__pb2.AddCaptionsRequest.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.AddCaptionsResponse.FromString,
+                _registered_method=True)
+        self.GetServerInfo = channel.unary_unary(
+                '/video_analyzer.VideoAnalyzerService/GetServerInfo',
+                request_serializer=protos_dot_video__analyzer__pb2.Empty.SerializeToString,
+                response_deserializer=protos_dot_video__analyzer__pb2.ServerInfo.FromString,
+                _registered_method=True)
 
 
 class VideoAnalyzerServiceServicer(object):
@@ -76,6 +171,31 @@ class VideoAnalyzerServiceServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def GetUploadStatus(self, request, context):
+        """Whether the upload with this idempotency key already completed, so a
+        retry after a dropped connection doesn't store the video twice
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def NegotiateUploadOffset(self, request, context):
+        """Where to pick an interrupted upload back up: the first chunk the
+        backend doesn't have yet. The rest is then sent with UploadVideo under
+        the same idempotency key
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def CheckFileExists(self, request, context):
+        """Whether the backend already has a file with this content, so it
+        needn't be uploaded again
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
     def RegisterLocalVideo(self, request, context):
         """Desktop shortcut: register local files without streaming upload
         """
@@ -109,6 +229,121 @@ class VideoAnalyzerServiceServicer(object):
         context.set_details('Method not implemented!')
         raise NotImplementedError('Method not implemented!')
 
+    def ResumeSession(self, request, context):
+        """Session control
+        Explicitly resume a past session by video_id:
+        - Loads the stored video_path into the VideoContext
+        - Returns confirmation and resolved metadata
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetStorageUsage(self, request, context):
+        """Storage management: bytes used on the backend, per video and in total
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def CleanupVideos(self, request, context):
+        """Delete (or with dry_run, just list) every video matching the criteria
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def UpdateAnalysisOptions(self, request, context):
+        """Change the analysis options of an already uploaded/registered video
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def SubmitFeedback(self, request, context):
+        """User ratings of assistant answers, for model quality tracking
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetOfflineAssets(self, request, context):
+        """Everything needed to browse a video offline: thumbnail, key frames, report
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetTranscript(self, request, context):
+        """Timestamped speech transcript produced during analysis
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetDetections(self, request, context):
+        """Everything the detection models found in a video, with where and when
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetTimeline(self, request, context):
+        """Scene and event summaries with timestamps
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def CreateShareLink(self, request, context):
+        """Signed read-only link to a video's results, viewable in a browser
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetBackendQueue(self, request, context):
+        """Pending and running analysis tasks, with who submitted them
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def CancelProcessing(self, request, context):
+        """Stop a video's pending or running analysis tasks
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def DeleteVideo(self, request, context):
+        """Remove a video with its analysis and chat history
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def UpdateVideoMetadata(self, request, context):
+        """Change what is stored about a video, e.g. its display name
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def AddCaptions(self, request, context):
+        """Captions a video already has, used in place of transcribing its speech
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
+    def GetServerInfo(self, request, context):
+        """Backend version, checked by the client on connect
+        """
+        context.set_code(grpc.StatusCode.UNIMPLEMENTED)
+        context.set_details('Method not implemented!')
+        raise NotImplementedError('Method not implemented!')
+
 
 def add_VideoAnalyzerServiceServicer_to_server(servicer, server):
     rpc_method_handlers = {
@@ -117,6 +352,21 @@ def add_VideoAnalyzerServiceServicer_to_server(servicer, server):
                     request_deserializer=protos_dot_video__analyzer__pb2.VideoChunk.FromString,
                     response_serializer=protos_dot_video__analyzer__pb2.UploadResponse.SerializeToString,
             ),
+            'GetUploadStatus': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetUploadStatus,
+                    request_deserializer=protos_dot_video__analyzer__pb2.UploadStatusRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.UploadStatusResponse.SerializeToString,
+            ),
+            'NegotiateUploadOffset': grpc.unary_unary_rpc_method_handler(
+                    servicer.NegotiateUploadOffset,
+                    request_deserializer=protos_dot_video__analyzer__pb2.UploadOffsetRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.UploadOffsetResponse.SerializeToString,
+            ),
+            'CheckFileExists': grpc.unary_unary_rpc_method_handler(
+                    servicer.CheckFileExists,
+                    request_deserializer=protos_dot_video__analyzer__pb2.CheckFileExistsRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.CheckFileExistsResponse.SerializeToString,
+            ),
             'RegisterLocalVideo': grpc.unary_unary_rpc_method_handler(
                     servicer.RegisterLocalVideo,
                     request_deserializer=protos_dot_video__analyzer__pb2.RegisterVideoRequest.FromString,
@@ -142,6 +392,86 @@ def add_VideoAnalyzerServiceServicer_to_server(servicer, server):
                     request_deserializer=protos_dot_video__analyzer__pb2.ClearHistoryRequest.FromString,
                     response_serializer=protos_dot_video__analyzer__pb2.ClearHistoryResponse.SerializeToString,
             ),
+            'ResumeSession': grpc.unary_unary_rpc_method_handler(
+                    servicer.ResumeSession,
+                    request_deserializer=protos_dot_video__analyzer__pb2.ResumeRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.ResumeResponse.SerializeToString,
+            ),
+            'GetStorageUsage': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetStorageUsage,
+                    request_deserializer=protos_dot_video__analyzer__pb2.Empty.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.StorageUsageResponse.SerializeToString,
+            ),
+            'CleanupVideos': grpc.unary_unary_rpc_method_handler(
+                    servicer.CleanupVideos,
+                    request_deserializer=protos_dot_video__analyzer__pb2.CleanupRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.CleanupResponse.SerializeToString,
+            ),
+            'UpdateAnalysisOptions': grpc.unary_unary_rpc_method_handler(
+                    servicer.UpdateAnalysisOptions,
+                    request_deserializer=protos_dot_video__analyzer__pb2.UpdateAnalysisOptionsRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.UpdateAnalysisOptionsResponse.SerializeToString,
+            ),
+            'SubmitFeedback': grpc.unary_unary_rpc_method_handler(
+                    servicer.SubmitFeedback,
+                    request_deserializer=protos_dot_video__analyzer__pb2.FeedbackRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.FeedbackResponse.SerializeToString,
+            ),
+            'GetOfflineAssets': grpc.unary_stream_rpc_method_handler(
+                    servicer.GetOfflineAssets,
+                    request_deserializer=protos_dot_video__analyzer__pb2.OfflineAssetsRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.OfflineAsset.SerializeToString,
+            ),
+            'GetTranscript': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetTranscript,
+                    request_deserializer=protos_dot_video__analyzer__pb2.TranscriptRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.TranscriptResponse.SerializeToString,
+            ),
+            'GetDetections': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetDetections,
+                    request_deserializer=protos_dot_video__analyzer__pb2.DetectionsRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.DetectionsResponse.SerializeToString,
+            ),
+            'GetTimeline': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetTimeline,
+                    request_deserializer=protos_dot_video__analyzer__pb2.TimelineRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.TimelineResponse.SerializeToString,
+            ),
+            'CreateShareLink': grpc.unary_unary_rpc_method_handler(
+                    servicer.CreateShareLink,
+                    request_deserializer=protos_dot_video__analyzer__pb2.ShareLinkRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.ShareLinkResponse.SerializeToString,
+            ),
+            'GetBackendQueue': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetBackendQueue,
+                    request_deserializer=protos_dot_video__analyzer__pb2.BackendQueueRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.BackendQueueResponse.SerializeToString,
+            ),
+            'CancelProcessing': grpc.unary_unary_rpc_method_handler(
+                    servicer.CancelProcessing,
+                    request_deserializer=protos_dot_video__analyzer__pb2.CancelProcessingRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.CancelProcessingResponse.SerializeToString,
+            ),
+            'DeleteVideo': grpc.unary_unary_rpc_method_handler(
+                    servicer.DeleteVideo,
+                    request_deserializer=protos_dot_video__analyzer__pb2.DeleteVideoRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.DeleteVideoResponse.SerializeToString,
+            ),
+            'UpdateVideoMetadata': grpc.unary_unary_rpc_method_handler(
+                    servicer.UpdateVideoMetadata,
+                    request_deserializer=protos_dot_video__analyzer__pb2.UpdateVideoMetadataRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.UpdateVideoMetadataResponse.SerializeToString,
+            ),
+            'AddCaptions': grpc.unary_unary_rpc_method_handler(
+                    servicer.AddCaptions,
+                    request_deserializer=protos_dot_video__analyzer__pb2.AddCaptionsRequest.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.AddCaptionsResponse.SerializeToString,
+            ),
+            'GetServerInfo': grpc.unary_unary_rpc_method_handler(
+                    servicer.GetServerInfo,
+                    request_deserializer=protos_dot_video__analyzer__pb2.Empty.FromString,
+                    response_serializer=protos_dot_video__analyzer__pb2.ServerInfo.SerializeToString,
+            ),
     }
     generic_handler = grpc.method_handlers_generic_handler(
             'video_analyzer.VideoAnalyzerService', rpc_method_handlers)
@@ -180,6 +510,87 @@ class VideoAnalyzerService(object):
             metadata,
             _registered_method=True)
 
+    @staticmethod
+    def GetUploadStatus(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetUploadStatus',
+            protos_dot_video__analyzer__pb2.UploadStatusRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.UploadStatusResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def NegotiateUploadOffset(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/NegotiateUploadOffset',
+            protos_dot_video__analyzer__pb2.UploadOffsetRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.UploadOffsetResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def CheckFileExists(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/CheckFileExists',
+            protos_dot_video__analyzer__pb2.CheckFileExistsRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.CheckFileExistsResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
     @staticmethod
     def RegisterLocalVideo(request,
             target,
@@ -314,3 +725,435 @@ class VideoAnalyzerService(object):
             timeout,
             metadata,
             _registered_method=True)
+
+    @staticmethod
+    def ResumeSession(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/ResumeSession',
+            protos_dot_video__analyzer__pb2.ResumeRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.ResumeResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetStorageUsage(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetStorageUsage',
+            protos_dot_video__analyzer__pb2.Empty.SerializeToString,
+            protos_dot_video__analyzer__pb2.StorageUsageResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def CleanupVideos(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/CleanupVideos',
+            protos_dot_video__analyzer__pb2.CleanupRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.CleanupResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def UpdateAnalysisOptions(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/UpdateAnalysisOptions',
+            protos_dot_video__analyzer__pb2.UpdateAnalysisOptionsRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.UpdateAnalysisOptionsResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def SubmitFeedback(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/SubmitFeedback',
+            protos_dot_video__analyzer__pb2.FeedbackRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.FeedbackResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetOfflineAssets(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_stream(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetOfflineAssets',
+            protos_dot_video__analyzer__pb2.OfflineAssetsRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.OfflineAsset.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetTranscript(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetTranscript',
+            protos_dot_video__analyzer__pb2.TranscriptRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.TranscriptResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetDetections(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetDetections',
+            protos_dot_video__analyzer__pb2.DetectionsRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.DetectionsResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetTimeline(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetTimeline',
+            protos_dot_video__analyzer__pb2.TimelineRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.TimelineResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def CreateShareLink(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/CreateShareLink',
+            protos_dot_video__analyzer__pb2.ShareLinkRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.ShareLinkResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetBackendQueue(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetBackendQueue',
+            protos_dot_video__analyzer__pb2.BackendQueueRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.BackendQueueResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def CancelProcessing(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/CancelProcessing',
+            protos_dot_video__analyzer__pb2.CancelProcessingRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.CancelProcessingResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def DeleteVideo(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/DeleteVideo',
+            protos_dot_video__analyzer__pb2.DeleteVideoRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.DeleteVideoResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def UpdateVideoMetadata(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/UpdateVideoMetadata',
+            protos_dot_video__analyzer__pb2.UpdateVideoMetadataRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.UpdateVideoMetadataResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def AddCaptions(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/AddCaptions',
+            protos_dot_video__analyzer__pb2.AddCaptionsRequest.SerializeToString,
+            protos_dot_video__analyzer__pb2.AddCaptionsResponse.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
+
+    @staticmethod
+    def GetServerInfo(request,
+            target,
+            options=(),
+            channel_credentials=None,
+            call_credentials=None,
+            insecure=False,
+            compression=None,
+            wait_for_ready=None,
+            timeout=None,
+            metadata=None):
+        return grpc.experimental.unary_unary(
+            request,
+            target,
+            '/video_analyzer.VideoAnalyzerService/GetServerInfo',
+            protos_dot_video__analyzer__pb2.Empty.SerializeToString,
+            protos_dot_video__analyzer__pb2.ServerInfo.FromString,
+            options,
+            channel_credentials,
+            insecure,
+            call_credentials,
+            compression,
+            wait_for_ready,
+            timeout,
+            metadata,
+            _registered_method=True)
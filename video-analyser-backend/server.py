@@ -8,6 +8,7 @@ Updated to support:
 - OS-appropriate file storage
 """
 
+import os
 import grpc
 from concurrent import futures
 from protos import video_analyzer_pb2
@@ -576,4 +577,5 @@ def serve(port: int = 50051):
 
 
 if __name__ == '__main__':
-    serve()
+    # The desktop app passes a free port when 50051 is taken
+    serve(int(os.getenv("GRPC_PORT", "50051")))